@@ -0,0 +1,136 @@
+use bytes::Bytes;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::broadcast;
+
+// Counts heap allocations so the two output pipelines can be compared
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// A high-throughput session: the reader fills its 1KiB buffer every time and
+// the processor flushes every 8 reads
+const READ_SIZE: usize = 1024;
+const CHUNKS_PER_FLUSH: usize = 8;
+const FLUSHES: usize = 10_000;
+// Two web clients (grid WebSocket, which also subscribes to raw output), one
+// raw WebSocket and one SSH login
+const SUBSCRIBERS: usize = 4;
+
+fn main() {
+    println!("🚀 STARTING PTY OUTPUT FAN-OUT BENCHMARK\n");
+    println!(
+        "{} flushes of {} x {} bytes, {} subscribers\n",
+        FLUSHES, CHUNKS_PER_FLUSH, READ_SIZE, SUBSCRIBERS
+    );
+
+    let owned = bench_pipeline("Vec<u8> (owned per subscriber)", run_vec_pipeline);
+    println!();
+    let shared = bench_pipeline("Bytes (shared)", run_bytes_pipeline);
+
+    println!("\n=== COMPARISON ===");
+    println!(
+        "Allocations: {:.1}x fewer, allocated bytes: {:.1}x fewer",
+        owned.0 as f64 / shared.0 as f64,
+        owned.1 as f64 / shared.1 as f64
+    );
+
+    println!("\n✅ BENCHMARK COMPLETE");
+}
+
+fn bench_pipeline(name: &str, run: fn() -> usize) -> (usize, usize) {
+    println!("=== {} ===", name);
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes_before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    let start = std::time::Instant::now();
+
+    let received = black_box(run());
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes_before;
+    println!("Time: {:?}", elapsed);
+    println!("Bytes delivered: {}", received);
+    println!(
+        "Allocations: {} ({:.1} per flush)",
+        allocations,
+        allocations as f64 / FLUSHES as f64
+    );
+    println!(
+        "Allocated: {} KiB ({:.1} KiB per flush)",
+        bytes / 1024,
+        bytes as f64 / 1024.0 / FLUSHES as f64
+    );
+    (allocations, bytes)
+}
+
+fn read_buffer() -> [u8; READ_SIZE] {
+    let mut buffer = [0u8; READ_SIZE];
+    for (i, byte) in buffer.iter_mut().enumerate() {
+        *byte = b'a' + (i % 26) as u8;
+    }
+    buffer
+}
+
+// The pipeline before: each read copied into a Vec, the chunks appended to a
+// growing Vec, and every subscriber's receive cloning it
+fn run_vec_pipeline() -> usize {
+    let (tx, _) = broadcast::channel::<Vec<u8>>(1000);
+    let mut receivers: Vec<_> = (0..SUBSCRIBERS).map(|_| tx.subscribe()).collect();
+    let buffer = read_buffer();
+    let mut received = 0;
+
+    for _ in 0..FLUSHES {
+        let pending: Vec<Vec<u8>> = (0..CHUNKS_PER_FLUSH).map(|_| buffer[..].to_vec()).collect();
+        let mut all_data = Vec::new();
+        for data in pending {
+            all_data.extend_from_slice(&data);
+        }
+        let _ = tx.send(all_data);
+        for rx in &mut receivers {
+            received += rx.try_recv().unwrap().len();
+        }
+    }
+    received
+}
+
+// The pipeline now: each read copied into Bytes, the chunks joined once, and
+// subscribers sharing the result
+fn run_bytes_pipeline() -> usize {
+    let (tx, _) = broadcast::channel::<Bytes>(1000);
+    let mut receivers: Vec<_> = (0..SUBSCRIBERS).map(|_| tx.subscribe()).collect();
+    let buffer = read_buffer();
+    let mut received = 0;
+
+    for _ in 0..FLUSHES {
+        let pending: Vec<Bytes> = (0..CHUNKS_PER_FLUSH)
+            .map(|_| Bytes::copy_from_slice(&buffer[..]))
+            .collect();
+        let all_data = match pending.as_slice() {
+            [data] => data.clone(),
+            chunks => Bytes::from(chunks.concat()),
+        };
+        let _ = tx.send(all_data);
+        for rx in &mut receivers {
+            received += rx.try_recv().unwrap().len();
+        }
+    }
+    received
+}
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnnouncementKind } from "./AnnouncementKind";
+
+/**
+ * Screen-reader friendly view of a session: finished lines of text plus announcements
+ */
+export type AccessibleEvent = { "type": "line", text: string, } | { "type": "announcement", kind: AnnouncementKind, text: string, };
@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * PTY traffic observed during one bucket
+ */
+export type ActivityBucket = { 
+/**
+ * Bucket start, in seconds since the Unix epoch
+ */
+start: number, 
+/**
+ * Bytes written to the PTY by clients
+ */
+bytes_in: number, 
+/**
+ * Bytes produced by the agent
+ */
+bytes_out: number, 
+/**
+ * Number of input messages (key presses, pastes, raw writes)
+ */
+input_events: number, 
+/**
+ * Number of output reads from the PTY
+ */
+output_events: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActivityBucket } from "./ActivityBucket";
+
+/**
+ * Per-bucket I/O history of a session, oldest bucket first
+ */
+export type ActivityTimeseries = { bucket_seconds: number, buckets: Array<ActivityBucket>, };
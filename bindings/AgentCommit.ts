@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A commit an agent announced in its session
+ */
+export type AgentCommit = { session_id: string, 
+/**
+ * Full hash when the session's repository knows it, otherwise as announced
+ */
+hash: string, message: string, 
+/**
+ * ISO 8601 timestamp of when it was announced
+ */
+created_at: string, };
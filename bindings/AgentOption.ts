@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type AgentOption = { name: string, 
+/**
+ * Whether the agent's executable was found on the server's `PATH`
+ */
+installed: boolean, supports_sandbox: boolean, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { OnMismatch } from "./OnMismatch";
+import type { VersionCheck } from "./VersionCheck";
+
+/**
+ * Version check of one agent, served by `GET /api/agents`
+ */
+export type AgentVersionStatus = { agent: string, 
+/**
+ * Installed version, when it could be read
+ */
+installed: string | null, 
+/**
+ * Pinned version requirement from `[agents.<name>]`
+ */
+pin: string | null, on_mismatch: OnMismatch, status: VersionCheck, 
+/**
+ * Why the version is unknown
+ */
+detail: string | null, checked_at: string, };
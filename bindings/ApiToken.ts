@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenScope } from "./TokenScope";
+
+/**
+ * An API token, without its secret
+ */
+export type ApiToken = { id: string, name: string, scope: TokenScope, 
+/**
+ * RFC 3339
+ */
+created_at: string, 
+/**
+ * RFC 3339; `None` for tokens that don't expire
+ */
+expires_at: string | null, 
+/**
+ * The only session the token opens, for share links
+ */
+session_id?: string, };
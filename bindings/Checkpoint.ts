@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A pinned checkpoint of a session
+ */
+export type Checkpoint = { 
+/**
+ * Name under the session's refs, e.g. `turn-3` or `safety-20250902-143000`
+ */
+name: string, reference: string, commit: string, 
+/**
+ * When the snapshot was taken, as RFC 3339
+ */
+created_at: string, message: string, };
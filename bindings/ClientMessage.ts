@@ -6,4 +6,4 @@ import type { ScrollDirection } from "./ScrollDirection";
 /**
  * Messages sent from client to server
  */
-export type ClientMessage = { "type": "key", code: KeyCode, modifiers: KeyModifiers, } | { "type": "resize", rows: number, cols: number, } | { "type": "scroll", direction: ScrollDirection, lines: number, };
+export type ClientMessage = { "type": "key", code: KeyCode, modifiers: KeyModifiers, seq?: number, } | { "type": "resize", rows: number, cols: number, driver?: boolean, } | { "type": "scroll", direction: ScrollDirection, lines: number, } | { "type": "input", data: Array<number>, seq?: number, } | { "type": "paste", text: string, seq?: number, } | { "type": "request_control" } | { "type": "release_control" };
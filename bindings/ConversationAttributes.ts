@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A conversation an agent kept for a project, served by
+ * `GET /api/projects/:id/conversations` whether or not a session runs it
+ */
+export type ConversationAttributes = { agent: string, 
+/**
+ * First thing the user asked, shortened
+ */
+title: string | null, last_modified: string, 
+/**
+ * Transcript size in bytes
+ */
+size: number, 
+/**
+ * Written to in the last few minutes
+ */
+active: boolean, };
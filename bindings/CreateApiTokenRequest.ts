@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenScope } from "./TokenScope";
+
+/**
+ * Body of `POST /api/tokens`
+ */
+export type CreateApiTokenRequest = { name: string, scope: TokenScope, 
+/**
+ * Expire this many seconds from now; never without
+ */
+ttl_seconds?: bigint, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Size of a set of changes, as in `git diff --stat`
+ */
+export type DiffStats = { files: number, additions: number, deletions: number, };
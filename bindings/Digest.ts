@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Served by `GET /api/digest`
+ */
+export type Digest = { 
+/**
+ * ISO 8601 timestamps of the covered period
+ */
+since: string, until: string, sessions: number, projects: number, markdown: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Do-not-disturb switched on by hand, e.g. with `codemux dnd on --for 2h`
+ */
+export type DoNotDisturb = { enabled: boolean, 
+/**
+ * ISO 8601 timestamp when it switches off by itself; unset lasts until turned off
+ */
+until?: string, };
@@ -5,4 +5,9 @@ import type { SerializablePtySize } from "./SerializablePtySize";
 /**
  * Terminal grid update messages
  */
-export type GridUpdateMessage = { "Keyframe": { size: SerializablePtySize, cells: Array<[[number, number], GridCell]>, cursor: [number, number], cursor_visible: boolean, scrollback_position: number, scrollback_total: number, timestamp: string, } } | { "Diff": { changes: Array<[number, number, GridCell]>, cursor: [number, number] | null, cursor_visible: boolean | null, scrollback_position: number | null, scrollback_total: number | null, timestamp: string, } };
+export type GridUpdateMessage = { "Keyframe": { size: SerializablePtySize, cells: Array<[[number, number], GridCell]>, cursor: [number, number], cursor_visible: boolean, scrollback_position: number, scrollback_total: number, timestamp: string, 
+/**
+ * (row, col, len, cell): `len` identical cells from (row, col) on, in place of
+ * `cells` for clients with the `rle` capability, see `run_length`
+ */
+runs?: Array<[number, number, number, GridCell]>, } } | { "Diff": { changes: Array<[number, number, GridCell]>, cursor: [number, number] | null, cursor_visible: boolean | null, scrollback_position: number | null, scrollback_total: number | null, timestamp: string, } };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputMode } from "./InputMode";
+
+/**
+ * A session's input mode and who holds write control, as sent to its clients
+ */
+export type InputControl = { mode: InputMode, 
+/**
+ * Client that holds write control
+ */
+holder?: string, 
+/**
+ * Clients waiting for control in `round_robin` mode, next first
+ */
+waiting: Array<string>, };
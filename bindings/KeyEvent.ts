@@ -5,4 +5,8 @@ import type { KeyModifiers } from "./KeyModifiers";
 /**
  * Key event structure
  */
-export type KeyEvent = { code: KeyCode, modifiers: KeyModifiers, };
+export type KeyEvent = { code: KeyCode, 
+/**
+ * None pressed when left out
+ */
+modifiers: KeyModifiers, };
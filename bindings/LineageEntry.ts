@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionLinkKind } from "./SessionLinkKind";
+
+/**
+ * One session in a lineage breadcrumb
+ */
+export type LineageEntry = { id: string, agent: string, color: string, icon: string, name?: string, 
+/**
+ * How this session came from the one before it in the lineage
+ */
+kind?: SessionLinkKind, 
+/**
+ * Template the session was started from
+ */
+template?: string, };
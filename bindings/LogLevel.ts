@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The log filter, as `GET /api/log-level` answers and `PUT` takes it
+ */
+export type LogLevel = { 
+/**
+ * `RUST_LOG` directives such as `codemux=debug,hyper=info`; a bare
+ * level like `debug` applies to codemux only
+ */
+filter: string, };
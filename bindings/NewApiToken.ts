@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TokenScope } from "./TokenScope";
+
+/**
+ * A token just created, the only time its secret is known
+ */
+export type NewApiToken = { 
+/**
+ * Send as a Bearer token, like the server's
+ */
+secret: string, id: string, name: string, scope: TokenScope, 
+/**
+ * RFC 3339
+ */
+created_at: string, 
+/**
+ * RFC 3339; `None` for tokens that don't expire
+ */
+expires_at: string | null, 
+/**
+ * The only session the token opens, for share links
+ */
+session_id?: string, };
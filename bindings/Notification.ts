@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { NotificationKind } from "./NotificationKind";
+
+/**
+ * An event worth telling someone about, kept until it drops out of the store
+ */
+export type Notification = { id: string, kind: NotificationKind, 
+/**
+ * Empty for notifications that aren't about one session
+ */
+session_id: string, message: string, 
+/**
+ * ISO 8601 timestamp of when the event fired
+ */
+created_at: string, read: boolean, 
+/**
+ * Recorded without an alert, because of quiet hours, do-not-disturb or the severity threshold
+ */
+silenced: boolean, };
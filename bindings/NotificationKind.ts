@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a notification is about
+ */
+export type NotificationKind = "waiting_for_input" | "exited" | "checkpoint_created" | "digest" | "mass_deletion" | "commit" | "approval_needed";
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Notification } from "./Notification";
+
+/**
+ * Served by `GET /api/notifications`
+ */
+export type NotificationList = { 
+/**
+ * Newest first
+ */
+notifications: Array<Notification>, unread: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What to do when an agent's installed version doesn't match its pin
+ */
+export type OnMismatch = "warn" | "block";
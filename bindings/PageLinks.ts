@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `links` of a paginated list
+ */
+export type PageLinks = { first: string, prev?: string, next?: string, last: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `meta` of a paginated list
+ */
+export type PageMeta = { 
+/**
+ * Entries in the whole list, after filtering
+ */
+total: number, page_number: number, page_size: number, pages: number, };
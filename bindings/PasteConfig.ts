@@ -0,0 +1,25 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Safeguards against pasting more into an agent than intended, configured as `[paste]`
+ *
+ * The TUI and web UI ask before pasting past the `confirm_*` thresholds; the
+ * server drops pastes larger than `max_bytes` whichever client sends them.
+ */
+export type PasteConfig = { 
+/**
+ * Ask before pasting more lines than this; 0 never asks
+ */
+confirm_lines: number, 
+/**
+ * Ask before pasting more bytes than this; 0 never asks
+ */
+confirm_bytes: number, 
+/**
+ * Offer to save a large paste to a file and send the agent its path instead
+ */
+offer_file: boolean, 
+/**
+ * Largest paste written to an agent
+ */
+max_bytes: number, };
@@ -1,3 +1,20 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProjectDefaults } from "./ProjectDefaults";
 
-export type ProjectAttributes = { name: string, path: string, };
+export type ProjectAttributes = { name: string, path: string, 
+/**
+ * Accent color as `#rrggbb`, derived from the project's name
+ */
+color: string, 
+/**
+ * Emoji for the kind of project, e.g. 🦀 for a Cargo package
+ */
+icon: string, 
+/**
+ * URL of the repository's `origin` remote
+ */
+remote?: string, 
+/**
+ * What the project's sessions start with unless told otherwise
+ */
+defaults?: ProjectDefaults, };
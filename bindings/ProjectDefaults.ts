@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A project's default agent, arguments and environment, set with
+ * `codemux project set`
+ */
+export type ProjectDefaults = { 
+/**
+ * Agent to run when a session in the project is started without one
+ */
+agent?: string, 
+/**
+ * Arguments for sessions of the default agent started without any
+ */
+args: Array<string>, 
+/**
+ * Environment of every session in the project; a session's own env wins
+ */
+env: { [key in string]?: string }, };
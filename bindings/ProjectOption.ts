@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProjectOption = { id: string, name: string, path: string, icon: string, 
+/**
+ * Worktrees can only be created for git repositories
+ */
+git: boolean, };
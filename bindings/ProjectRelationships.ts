@@ -1,4 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Relationship } from "./Relationship";
 import type { SessionResourceTS } from "./SessionResourceTS";
 
-export type ProjectRelationships = { recent_sessions?: Array<SessionResourceTS> | null, };
+export type ProjectRelationships = { recent_sessions?: Array<SessionResourceTS> | null, 
+/**
+ * Project this one is a sub-project of
+ */
+parent?: Relationship, 
+/**
+ * Running sessions of the sub-projects, rolled up from all levels below
+ */
+subproject_sessions?: Array<SessionResourceTS>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TranscriptStats } from "./TranscriptStats";
+
+/**
+ * Usage of the sessions of one project
+ */
+export type ProjectUsage = { 
+/**
+ * Project ID, `None` for sessions outside any project
+ */
+project: string | null, name: string | null, sessions: number, usage: TranscriptStats, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What an agent is waiting for
+ */
+export type PromptType = { "type": "TextInput", prompt: string, default: string | null, } | { "type": "Confirmation", prompt: string, default: boolean | null, } | { "type": "MultiSelect", prompt: string, options: Array<string>, selected: Array<number>, } | { "type": "SingleSelect", prompt: string, options: Array<string>, default: number | null, } | { "type": "FilePath", prompt: string, default: string | null, } | { "type": "Permission", prompt: string, options: Array<string>, selected: number | null, };
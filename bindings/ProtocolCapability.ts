@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Optional protocol features, asked for with `?capabilities=` on the terminal WebSocket
+ */
+export type ProtocolCapability = "batch" | "msgpack" | "deflate" | "rle";
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Entry in the quick-switch list served by `GET /api/recent`
+ */
+export type QuickSession = { id: string, agent: string | null, project: string | null, color: string | null, icon: string | null, 
+/**
+ * Whether the session is currently running on the server
+ */
+running: boolean, favorite: boolean, attached_at: string | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Control messages accepted as text frames on the raw PTY WebSocket
+ *
+ * Binary frames on that socket are written to the PTY untouched.
+ */
+export type RawClientMessage = { "type": "input", data: string, } | { "type": "resize", rows: number, cols: number, };
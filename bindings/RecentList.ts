@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QuickProject } from "./QuickProject";
+import type { QuickSession } from "./QuickSession";
+
+/**
+ * Starred sessions first, then other recently attached ones, newest first
+ */
+export type RecentList = { sessions: Array<QuickSession>, 
+/**
+ * Starred projects, in the order they were starred
+ */
+projects: Array<QuickProject>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonApiResourceRef } from "./JsonApiResourceRef";
+
+/**
+ * A JSON:API relationship to one resource
+ */
+export type Relationship = { data: JsonApiResourceRef, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GridUpdateMessage } from "./GridUpdateMessage";
+
+/**
+ * Render failure reported by a client renderer, along with the updates that led to it
+ */
+export type RenderCrashReport = { 
+/**
+ * Renderer that hit the inconsistency, e.g. "tui" or "web"
+ */
+source: string, reason: string, 
+/**
+ * Most recent grid updates, oldest first
+ */
+updates: Array<GridUpdateMessage>, };
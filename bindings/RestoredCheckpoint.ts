@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Checkpoint } from "./Checkpoint";
+
+/**
+ * A checkpoint put back into a session's working tree
+ */
+export type RestoredCheckpoint = { restored: Checkpoint, 
+/**
+ * Checkpoint of the files as they were before, to undo the restore with;
+ * unset outside a git repository
+ */
+backup?: string, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GridCell } from "./GridCell";
+
+/**
+ * A session's screen, from `GET /api/sessions/:id/screen`
+ */
+export type ScreenGrid = { rows: number, cols: number, 
+/**
+ * (row, col)
+ */
+cursor: [number, number], cursor_visible: boolean, 
+/**
+ * Each row's text without trailing spaces
+ */
+lines: Array<string>, 
+/**
+ * Each row's cells, `cols` of them; the second half of a wide character
+ * has an empty `char`
+ */
+cells: Array<Array<GridCell>>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Text of part of a session's screen, from `GET /api/sessions/:id/screen/text`
+ */
+export type ScreenText = { text: string, 
+/**
+ * Size of the whole screen
+ */
+rows: number, cols: number, };
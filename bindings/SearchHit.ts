@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchSource } from "./SearchSource";
+
+/**
+ * A document that matched
+ */
+export type SearchHit = { session_id: string | null, 
+/**
+ * Resume it with `codemux run <agent> --resume <conversation>`
+ */
+conversation: string | null, agent: string, project: string | null, source: SearchSource, updated_at: string | null, 
+/**
+ * Excerpts around the matches
+ */
+snippets: Array<string>, 
+/**
+ * How often the query's words occur
+ */
+matches: number, };
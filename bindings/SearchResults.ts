@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchHit } from "./SearchHit";
+
+/**
+ * Response of `GET /api/search`
+ */
+export type SearchResults = { query: string, 
+/**
+ * Best matches first
+ */
+hits: Array<SearchHit>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How the cells between the two ends of a selection are picked
+ */
+export type SelectionShape = "lines" | "block";
@@ -1,8 +1,24 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { GridCell } from "./GridCell";
+import type { InputMode } from "./InputMode";
+import type { PromptType } from "./PromptType";
+import type { ProtocolCapability } from "./ProtocolCapability";
 import type { SerializablePtySize } from "./SerializablePtySize";
 
 /**
  * Messages sent from server to client - flattened to match frontend expectations
  */
-export type ServerMessage = { "type": "output", data: Array<number>, timestamp: string, } | { "type": "grid_update", } & ({ "Keyframe": { size: SerializablePtySize, cells: Array<[[number, number], GridCell]>, cursor: [number, number], cursor_visible: boolean, scrollback_position: number, scrollback_total: number, timestamp: string, } } | { "Diff": { changes: Array<[number, number, GridCell]>, cursor: [number, number] | null, cursor_visible: boolean | null, scrollback_position: number | null, scrollback_total: number | null, timestamp: string, } }) | { "type": "pty_size", rows: number, cols: number, } | { "type": "error", message: string, };
+export type ServerMessage = { "type": "output", data: Array<number>, timestamp: string, } | { "type": "grid_update", } & ({ "Keyframe": { size: SerializablePtySize, cells: Array<[[number, number], GridCell]>, cursor: [number, number], cursor_visible: boolean, scrollback_position: number, scrollback_total: number, timestamp: string, 
+/**
+ * (row, col, len, cell): `len` identical cells from (row, col) on, in place of
+ * `cells` for clients with the `rle` capability, see `run_length`
+ */
+runs?: Array<[number, number, number, GridCell]>, } } | { "Diff": { changes: Array<[number, number, GridCell]>, cursor: [number, number] | null, cursor_visible: boolean | null, scrollback_position: number | null, scrollback_total: number | null, timestamp: string, } }) | { "type": "pty_size", rows: number, cols: number, } | { "type": "error", message: string, } | { "type": "rebound", restarted: boolean, } | { "type": "alert", message: string, } | { "type": "input_control", client_id: string, can_write: boolean, mode: InputMode, 
+/**
+ * Client that holds write control
+ */
+holder?: string, 
+/**
+ * Clients waiting for control in `round_robin` mode, next first
+ */
+waiting: Array<string>, } | { "type": "capabilities", capabilities: Array<ProtocolCapability>, } | { "type": "input_ack", seq: number, written: boolean, } | { "type": "input_lag", lagging: boolean, } | { "type": "prompt_detected", prompt: PromptType, } | { "type": "prompt_cleared" } | { "type": "exited", code: number, };
@@ -1,4 +1,39 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffStats } from "./DiffStats";
 import type { SessionType } from "./SessionType";
 
-export type SessionAttributes = { agent: string, project: string | null, status: string, session_type: SessionType, last_modified: string | null, last_message: string | null, };
+export type SessionAttributes = { agent: string, project: string | null, status: string, session_type: SessionType, last_modified: string | null, last_message: string | null, 
+/**
+ * Accent color as `#rrggbb`, stable for the lifetime of the session
+ */
+color: string, 
+/**
+ * Emoji shown next to the session, picked from the agent unless overridden
+ */
+icon: string, 
+/**
+ * Handover note left when someone detached, e.g. "waiting on CI"
+ */
+note?: string, 
+/**
+ * Name given at creation, usable wherever the session ID is
+ */
+name?: string, 
+/**
+ * When a killed session ends for good unless the kill is undone, as RFC 3339
+ */
+terminates_at?: string, 
+/**
+ * When the session is ended for sitting idle unless it's used, as RFC
+ * 3339; unset while a client is attached or without `[session] idle_timeout_secs`
+ */
+idle_expires_at?: string, 
+/**
+ * Uncommitted changes in the session's directory, only on
+ * `GET /api/sessions/:id` of a running session in a git repository
+ */
+diff_stats?: DiffStats, 
+/**
+ * Exit code of the agent of an `exited` session, when it could be read
+ */
+exit_code?: number, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How often each cell of a session's screen changed over the recent window
+ */
+export type SessionHeatmap = { window_seconds: number, rows: number, cols: number, 
+/**
+ * Changes per cell in row-major order, `rows * cols` entries
+ */
+counts: Array<number>, 
+/**
+ * Highest entry of `counts`, for scaling an overlay
+ */
+max: number, 
+/**
+ * Grid diffs sent in the window
+ */
+diffs: number, 
+/**
+ * Full keyframes sent in the window; they repaint every cell and are not in `counts`
+ */
+keyframes: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionLinkKind } from "./SessionLinkKind";
+
+export type SessionLink = { kind: SessionLinkKind, 
+/**
+ * ID of the related session, or the template name for `spawned_from_template`
+ */
+id: string, };
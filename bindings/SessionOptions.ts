@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgentOption } from "./AgentOption";
+import type { ProjectOption } from "./ProjectOption";
+import type { TemplateOption } from "./TemplateOption";
+
+/**
+ * Everything the "New Session" form needs, served by `GET /api/session-options`
+ */
+export type SessionOptions = { agents: Array<AgentOption>, templates: Array<TemplateOption>, projects: Array<ProjectOption>, 
+/**
+ * Agent preselected in the form
+ */
+default_agent: string | null, };
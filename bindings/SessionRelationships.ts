@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Relationship } from "./Relationship";
+
+/**
+ * `relationships` of a session resource, one member per kind of link
+ */
+export type SessionRelationships = { spawned_from_template?: Relationship, clone_of?: Relationship, retry_of?: Relationship, worktree_of?: Relationship, };
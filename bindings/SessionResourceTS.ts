@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { SessionAttributes } from "./SessionAttributes";
+import type { SessionRelationships } from "./SessionRelationships";
 
-export type SessionResourceTS = { type: string, id: string, attributes: SessionAttributes | null, };
+export type SessionResourceTS = { type: string, id: string, attributes: SessionAttributes | null, relationships?: SessionRelationships, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Reusable session settings, configured as `[templates.<name>]`
+ */
+export type SessionTemplate = { description?: string, agent: string, 
+/**
+ * Passed to the agent before any arguments given at launch
+ */
+args: Array<string>, env: { [key in string]?: string }, worktree: boolean, sandbox: boolean, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TranscriptMessage } from "./TranscriptMessage";
+import type { TranscriptStats } from "./TranscriptStats";
+
+/**
+ * The conversation of a running session, as plain messages
+ */
+export type SessionTranscript = { 
+/**
+ * Transcript file the messages come from, `None` until the agent wrote one
+ */
+path: string | null, messages: Array<TranscriptMessage>, stats: TranscriptStats, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TranscriptStats } from "./TranscriptStats";
+
+/**
+ * Token and cost totals of a running session, served by `GET /api/sessions/:id/usage`
+ */
+export type SessionUsage = { session_id: string, agent: string, 
+/**
+ * Project ID
+ */
+project: string | null, usage: TranscriptStats, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Body of `PUT /api/notifications/dnd`
+ */
+export type SetDoNotDisturbRequest = { enabled: boolean, 
+/**
+ * Switch off by itself after this many minutes
+ */
+minutes?: number, };
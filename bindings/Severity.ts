@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How much a notification deserves an alert, lowest first
+ */
+export type Severity = "info" | "warning" | "urgent";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Body of `POST /api/sessions/:id/share`
+ */
+export type ShareSessionRequest = { 
+/**
+ * Let whoever opens the link type into the session, not just watch it
+ */
+interactive?: boolean, 
+/**
+ * Expire this many seconds from now; never without
+ */
+ttl_seconds?: bigint, };
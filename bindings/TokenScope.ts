@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What an API token may do; each scope includes the ones before it
+ */
+export type TokenScope = "read" | "sessions:write" | "admin";
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One message of a conversation
+ */
+export type TranscriptMessage = { 
+/**
+ * `user` or `assistant`, or whatever else the agent calls the sender
+ */
+role: string, content: string, timestamp?: string, input_tokens?: number, output_tokens?: number, 
+/**
+ * In US dollars, for agents that report it
+ */
+cost?: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Totals over a conversation
+ */
+export type TranscriptStats = { messages: number, input_tokens: number, output_tokens: number, 
+/**
+ * `None` when no message reported a cost
+ */
+cost: number | null, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Display preferences shared by the TUI and web UI
+ */
+export type UiPreferences = { 
+/**
+ * Use a high-contrast palette for the terminal and UI chrome
+ */
+high_contrast: boolean, 
+/**
+ * Disable animated UI elements such as ticking timers, countdowns and transitions
+ */
+reduced_motion: boolean, 
+/**
+ * Starred session IDs, listed first in the quick-switch list
+ */
+favorite_sessions: Array<string>, 
+/**
+ * Starred project IDs
+ */
+favorite_projects: Array<string>, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Partial update accepted by `PATCH /api/preferences`; unset fields are left alone
+ */
+export type UpdatePreferencesRequest = { high_contrast?: boolean, reduced_motion?: boolean, 
+/**
+ * Replaces the starred sessions
+ */
+favorite_sessions?: Array<string>, 
+/**
+ * Replaces the starred projects
+ */
+favorite_projects?: Array<string>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ProjectUsage } from "./ProjectUsage";
+import type { SessionUsage } from "./SessionUsage";
+import type { TranscriptStats } from "./TranscriptStats";
+
+/**
+ * Usage of every running session, per session, per project and overall,
+ * served by `GET /api/usage`
+ */
+export type UsageSummary = { sessions: Array<SessionUsage>, projects: Array<ProjectUsage>, total: TranscriptStats, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Someone signed in through the provider
+ */
+export type User = { 
+/**
+ * From the configured `username_claim`
+ */
+name: string, email: string | null, 
+/**
+ * The provider's ID for the user
+ */
+subject: string, issuer: string, };
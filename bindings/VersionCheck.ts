@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of comparing an agent's installed version with its pin
+ */
+export type VersionCheck = "ok" | "unpinned" | "mismatch" | "not_installed" | "unknown";
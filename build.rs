@@ -30,6 +30,11 @@ fn find_npm_command() -> &'static str {
 }
 
 fn main() {
+    // Nothing to embed without the web UI
+    if env::var("CARGO_FEATURE_WEB_UI").is_err() {
+        return;
+    }
+
     // Skip React Native build if SKIP_WEB_BUILD is set
     if env::var("SKIP_WEB_BUILD").is_ok() {
         println!("cargo:warning=Skipping React Native Web build (SKIP_WEB_BUILD set)");
@@ -1 +1,6 @@
 /// <reference types="nativewind/types" />
+
+interface Window {
+	// Set by the server's index.html when codemux is served under a path prefix
+	__CODEMUX_BASE_PATH__?: string;
+}
@@ -4,6 +4,7 @@ import { QueryClientProvider } from "@tanstack/react-query";
 import { Slot } from "expo-router";
 import { StatusBar } from "expo-status-bar";
 import { useColorScheme } from "nativewind";
+import { PreferencesEffects } from "../components/PreferencesEffects";
 import { queryClient } from "../lib/queryClient";
 
 export default function Layout() {
@@ -11,6 +12,7 @@ export default function Layout() {
 
 	return (
 		<QueryClientProvider client={queryClient}>
+			<PreferencesEffects />
 			<StatusBar style={colorScheme === "dark" ? "light" : "dark"} />
 			<Slot />
 			<PortalHost />
@@ -0,0 +1,71 @@
+import React, { useState } from "react";
+import { Text, TouchableOpacity, View } from "react-native";
+import Terminal from "../../components/Terminal";
+import { wsUrl } from "../../lib/apiClient";
+
+// Mirrors the "Expect:" line of each scene in src/core/render_test.rs
+const CHECKS = [
+	{
+		scene: "Wide characters",
+		expect:
+			"Each wide glyph fills exactly two cells and every | lines up in one column",
+	},
+	{
+		scene: "Right-to-left text",
+		expect:
+			"Letters stay one per cell in the order shown, nothing is mirrored across the line",
+	},
+	{
+		scene: "Combining marks",
+		expect: "Marks sit on their base letter and never take a cell of their own",
+	},
+	{
+		scene: "Colors and attributes",
+		expect:
+			"Smooth 64-step ramps, distinct palette cells, each attribute visible",
+	},
+	{
+		scene: "Rapid diffs",
+		expect:
+			"The counter ends at 120/120 with no stale cells left by the block or the toggling text",
+	},
+];
+
+// Renderer checklist to run in each supported browser before a release
+export default function RenderTestPage() {
+	const [passed, setPassed] = useState<Record<string, boolean>>({});
+	const browser =
+		typeof navigator !== "undefined" ? navigator.userAgent : "unknown";
+
+	return (
+		<View className="flex-1 bg-background">
+			<View className="p-4 border-b border-border">
+				<Text className="text-foreground text-lg font-bold">Render test</Text>
+				<Text className="text-muted-foreground text-xs mb-2">{browser}</Text>
+				{CHECKS.map(({ scene, expect }) => (
+					<TouchableOpacity
+						key={scene}
+						onPress={() =>
+							setPassed((current) => ({
+								...current,
+								[scene]: !current[scene],
+							}))
+						}
+						className="flex-row py-1"
+						role="checkbox"
+						aria-checked={passed[scene] ?? false}
+					>
+						<Text className="text-foreground mr-2">
+							{passed[scene] ? "☑" : "☐"}
+						</Text>
+						<Text className="text-foreground flex-1">
+							<Text className="font-bold">{scene}: </Text>
+							{expect}
+						</Text>
+					</TouchableOpacity>
+				))}
+			</View>
+			<Terminal sessionId="render-test" url={wsUrl("/api/dev/render-test")} />
+		</View>
+	);
+}
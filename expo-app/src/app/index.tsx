@@ -1,14 +1,36 @@
-import React from "react";
+import React, { useState } from "react";
 import { ScrollView, View } from "react-native";
 import { EmptyProjectsState } from "../components/EmptyProjectsState";
 import { ErrorState } from "../components/ErrorState";
 import { LoadingState } from "../components/LoadingState";
+import { NewSessionForm } from "../components/NewSessionForm";
 import { ProjectContainer } from "../components/ProjectContainer";
 import { ProjectsHeader } from "../components/ProjectsHeader";
+import { QuickSwitchList } from "../components/QuickSwitchList";
 import { useProjects } from "../hooks/api";
+import type { Project } from "../types/api";
+
+// How far parents are followed, in case they form a loop
+const MAX_PROJECT_DEPTH = 16;
+
+// A sub-project's name prefixed with its parents' names, e.g. "mono/api"
+function qualifiedName(project: Project, projects: Project[]): string {
+	const names = [project.attributes?.name ?? project.id];
+	let parentId = project.relationships?.parent?.data.id;
+	while (parentId && names.length < MAX_PROJECT_DEPTH) {
+		const parent = projects.find((p) => p.id === parentId);
+		if (!parent) {
+			break;
+		}
+		names.unshift(parent.attributes?.name ?? parent.id);
+		parentId = parent.relationships?.parent?.data.id;
+	}
+	return names.join("/");
+}
 
 export default function Page() {
 	const { data: projects = [], isLoading: loading, error } = useProjects();
+	const [showNewSession, setShowNewSession] = useState(false);
 
 	if (loading) {
 		return <LoadingState />;
@@ -20,14 +42,22 @@ export default function Page() {
 
 	return (
 		<View className="flex-1 bg-background">
-			<ProjectsHeader />
+			<ProjectsHeader onNewSession={() => setShowNewSession(true)} />
+			{showNewSession && (
+				<NewSessionForm onClose={() => setShowNewSession(false)} />
+			)}
 
 			<ScrollView className="flex-1 p-4">
+				<QuickSwitchList />
 				{projects.length === 0 ? (
 					<EmptyProjectsState />
 				) : (
 					projects.map((project) => (
-						<ProjectContainer key={project.id} project={project} />
+						<ProjectContainer
+							key={project.id}
+							project={project}
+							qualifiedName={qualifiedName(project, projects)}
+						/>
 					))
 				)}
 			</ScrollView>
@@ -1,17 +1,40 @@
-import { Tabs } from "expo-router";
-import React from "react";
+import { Tabs, useLocalSearchParams } from "expo-router";
+import React, { useEffect } from "react";
+import { Platform } from "react-native";
+import { useSession } from "../../../../hooks/api";
+import { useTerminalStore } from "../../../../stores/terminalStore";
 
 export default function SessionTabsLayout() {
+	const presentationMode = useTerminalStore((state) => state.presentationMode);
+	const { sessionId } = useLocalSearchParams<{ sessionId: string }>();
+	const { data: session } = useSession(sessionId);
+	const accent = session?.attributes?.color;
+	const icon = session?.attributes?.icon;
+	const agent = session?.attributes?.agent;
+
+	// Lets people with several sessions open tell the browser tabs apart
+	useEffect(() => {
+		if (Platform.OS !== "web" || !agent) {
+			return;
+		}
+		const prefix = icon ? `${icon} ` : "";
+		document.title = `${prefix}${agent} · ${sessionId.slice(0, 8)} - CodeMux`;
+	}, [agent, icon, sessionId]);
+
 	return (
 		<Tabs
 			screenOptions={{
 				headerShown: false,
 				tabBarStyle: {
+					// Presentation mode hides all chrome around the terminal
+					display: presentationMode ? "none" : "flex",
 					backgroundColor: "hsl(0 0% 3.9%)", // --card (dark theme)
-					borderTopColor: "hsl(0 0% 14.9%)", // --border (dark theme)
+					// The session's accent color, or --border (dark theme)
+					borderTopColor: accent ?? "hsl(0 0% 14.9%)",
+					borderTopWidth: accent ? 2 : undefined,
 					height: 60,
 				},
-				tabBarActiveTintColor: "hsl(0 0% 98%)", // --primary (dark theme)
+				tabBarActiveTintColor: accent ?? "hsl(0 0% 98%)", // --primary (dark theme)
 				tabBarInactiveTintColor: "hsl(0 0% 63.9%)", // --muted-foreground (dark theme)
 				tabBarLabelStyle: {
 					fontSize: 12,
@@ -37,6 +60,16 @@ export default function SessionTabsLayout() {
 					),
 				}}
 			/>
+			<Tabs.Screen
+				name="accessible"
+				options={{
+					title: "Transcript",
+					tabBarAccessibilityLabel: "Screen reader transcript",
+					tabBarIcon: ({ color }) => (
+						<span style={{ color, fontSize: 18 }}>🗣️</span>
+					),
+				}}
+			/>
 			<Tabs.Screen
 				name="logs"
 				options={{
@@ -0,0 +1,9 @@
+import { useLocalSearchParams } from "expo-router";
+import React from "react";
+import { AccessibleTranscript } from "../../../../components/AccessibleTranscript";
+
+export default function AccessibleTab() {
+	const { sessionId } = useLocalSearchParams<{ sessionId: string }>();
+
+	return <AccessibleTranscript sessionId={sessionId || ""} />;
+}
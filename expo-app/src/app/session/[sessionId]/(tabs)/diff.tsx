@@ -2,12 +2,14 @@ import { useLocalSearchParams } from "expo-router";
 import React from "react";
 import { View } from "react-native";
 import GitDiffViewer from "../../../../components/GitDiffViewer";
+import SessionCommits from "../../../../components/SessionCommits";
 
 export default function DiffTab() {
 	const { sessionId } = useLocalSearchParams<{ sessionId: string }>();
 
 	return (
 		<View className="flex-1 w-full">
+			<SessionCommits sessionId={sessionId || ""} />
 			<GitDiffViewer sessionId={sessionId || ""} />
 		</View>
 	);
@@ -1,6 +1,8 @@
 import { useLocalSearchParams } from "expo-router";
 import React from "react";
 import { View } from "react-native";
+import { HandoverNote } from "../../../../components/HandoverNote";
+import { SessionLineage } from "../../../../components/SessionLineage";
 import Terminal from "../../../../components/Terminal";
 
 export default function TerminalTab() {
@@ -8,6 +10,8 @@ export default function TerminalTab() {
 
 	return (
 		<View className="flex-1 w-full">
+			<SessionLineage sessionId={sessionId || ""} />
+			<HandoverNote sessionId={sessionId || ""} />
 			<Terminal sessionId={sessionId || ""} />
 		</View>
 	);
@@ -1,12 +1,16 @@
 import { Tabs } from "expo-router";
 import React from "react";
+import { useTerminalStore } from "../../../stores/terminalStore";
 
 export default function SessionLayout() {
+	const presentationMode = useTerminalStore((state) => state.presentationMode);
+
 	return (
 		<Tabs
 			screenOptions={{
 				headerShown: false,
 				tabBarStyle: {
+					display: presentationMode ? "none" : "flex",
 					backgroundColor: "hsl(0 0% 3.9%)", // --card (dark theme)
 					borderTopColor: "hsl(0 0% 14.9%)", // --border (dark theme)
 				},
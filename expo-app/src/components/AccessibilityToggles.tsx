@@ -0,0 +1,42 @@
+import { ContrastIcon, PauseIcon } from "lucide-react-native";
+import React from "react";
+import { View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Icon } from "@/components/ui/icon";
+import { usePreferences, useUpdatePreferences } from "../hooks/api";
+
+export function AccessibilityToggles() {
+	const { high_contrast, reduced_motion } = usePreferences();
+	const updatePreferences = useUpdatePreferences();
+
+	return (
+		<View className="flex-row">
+			<Button
+				onPress={() =>
+					updatePreferences.mutate({ high_contrast: !high_contrast })
+				}
+				size="icon"
+				variant={high_contrast ? "secondary" : "ghost"}
+				className="rounded-full"
+				role="switch"
+				aria-checked={high_contrast}
+				aria-label="High contrast"
+			>
+				<Icon as={ContrastIcon} className="size-6" />
+			</Button>
+			<Button
+				onPress={() =>
+					updatePreferences.mutate({ reduced_motion: !reduced_motion })
+				}
+				size="icon"
+				variant={reduced_motion ? "secondary" : "ghost"}
+				className="rounded-full"
+				role="switch"
+				aria-checked={reduced_motion}
+				aria-label="Reduce motion"
+			>
+				<Icon as={PauseIcon} className="size-6" />
+			</Button>
+		</View>
+	);
+}
@@ -0,0 +1,100 @@
+import React, { useCallback, useState } from "react";
+import { ScrollView, Text, View } from "react-native";
+import { useWebSocketWithReconnect } from "../hooks/useWebSocketWithReconnect";
+import { wsUrl } from "../lib/apiClient";
+import type { AccessibleEvent, AnnouncementKind } from "../types/bindings";
+
+interface AccessibleTranscriptProps {
+	sessionId: string;
+}
+
+// Older lines are dropped so long sessions don't grow the page without bound
+const MAX_LINES = 500;
+
+const ANNOUNCEMENT_LABELS: Record<AnnouncementKind, string> = {
+	question: "Agent is asking a question",
+	error: "Error",
+	session_ended: "Session ended",
+};
+
+interface TranscriptEntry {
+	id: number;
+	text: string;
+	announcement?: AnnouncementKind;
+}
+
+/**
+ * Linear, screen-reader friendly view of a session's output.
+ *
+ * New lines land in a polite live region; questions and errors are repeated in
+ * an assertive region so they interrupt whatever is being read.
+ */
+export function AccessibleTranscript({ sessionId }: AccessibleTranscriptProps) {
+	const [entries, setEntries] = useState<TranscriptEntry[]>([]);
+	const [announcement, setAnnouncement] = useState("");
+
+	const handleMessage = useCallback((event: MessageEvent) => {
+		let message: AccessibleEvent;
+		try {
+			message = JSON.parse(event.data);
+		} catch (error) {
+			console.error("Failed to parse accessible stream message:", error);
+			return;
+		}
+
+		if (message.type !== "line" && message.type !== "announcement") {
+			return;
+		}
+
+		const entry: TranscriptEntry = {
+			id: Date.now() + Math.random(),
+			text: message.text,
+			announcement: message.type === "announcement" ? message.kind : undefined,
+		};
+		setEntries((previous) => [...previous, entry].slice(-MAX_LINES));
+		if (message.type === "announcement") {
+			setAnnouncement(`${ANNOUNCEMENT_LABELS[message.kind]}: ${message.text}`);
+		}
+	}, []);
+
+	const { isConnected } = useWebSocketWithReconnect({
+		url: wsUrl(`/ws/session/${sessionId}/accessible`),
+		onMessage: handleMessage,
+	});
+
+	return (
+		<View className="flex-1 bg-background p-4">
+			<Text role="heading" className="text-foreground text-lg mb-2">
+				Session transcript
+			</Text>
+			<Text className="text-muted-foreground mb-2">
+				{isConnected ? "Connected" : "Connecting…"}
+			</Text>
+
+			<View aria-live="assertive" className="mb-2">
+				{announcement !== "" && (
+					<Text className="text-foreground font-semibold">{announcement}</Text>
+				)}
+			</View>
+
+			<ScrollView className="flex-1">
+				<View role="log" aria-live="polite" aria-label="Session output">
+					{entries.map((entry) => (
+						<Text
+							key={entry.id}
+							className={
+								entry.announcement
+									? "text-foreground font-semibold font-mono text-sm mb-1"
+									: "text-foreground font-mono text-sm mb-1"
+							}
+						>
+							{entry.announcement
+								? `${ANNOUNCEMENT_LABELS[entry.announcement]}: ${entry.text}`
+								: entry.text}
+						</Text>
+					))}
+				</View>
+			</ScrollView>
+		</View>
+	);
+}
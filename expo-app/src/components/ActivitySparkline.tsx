@@ -0,0 +1,72 @@
+import React from "react";
+import { Text, View } from "react-native";
+import { useSessionTimeseries } from "../hooks/api";
+
+interface ActivitySparklineProps {
+	sessionId: string;
+	// Only the most recent buckets are drawn to keep the card compact
+	maxBuckets?: number;
+}
+
+const SPARKLINE_HEIGHT = 24;
+
+export function ActivitySparkline({
+	sessionId,
+	maxBuckets = 60,
+}: ActivitySparklineProps) {
+	const { data } = useSessionTimeseries({ sessionId });
+	const buckets = data?.attributes.buckets.slice(-maxBuckets) ?? [];
+
+	if (buckets.length === 0) {
+		return null;
+	}
+
+	const peak = Math.max(
+		1,
+		...buckets.map((bucket) => bucket.bytes_in + bucket.bytes_out),
+	);
+	const idleMinutes = buckets
+		.slice()
+		.reverse()
+		.findIndex((bucket) => bucket.bytes_out > 0);
+
+	return (
+		<View className="mt-2">
+			<View
+				className="flex-row items-end gap-px"
+				style={{ height: SPARKLINE_HEIGHT }}
+			>
+				{buckets.map((bucket) => {
+					const total = bucket.bytes_in + bucket.bytes_out;
+					const outputHeight = (bucket.bytes_out / peak) * SPARKLINE_HEIGHT;
+					const inputHeight = (bucket.bytes_in / peak) * SPARKLINE_HEIGHT;
+					return (
+						<View key={bucket.start} className="flex-1 justify-end">
+							{total > 0 && (
+								<>
+									<View
+										className="bg-primary rounded-sm"
+										style={{ height: Math.max(1, outputHeight) }}
+									/>
+									{bucket.bytes_in > 0 && (
+										<View
+											className="bg-muted-foreground"
+											style={{ height: Math.max(1, inputHeight) }}
+										/>
+									)}
+								</>
+							)}
+						</View>
+					);
+				})}
+			</View>
+			<Text className="text-xs text-muted-foreground mt-1">
+				{idleMinutes > 0
+					? `Agent quiet for ${idleMinutes} min`
+					: idleMinutes === 0
+						? "Agent active this minute"
+						: "No output yet"}
+			</Text>
+		</View>
+	);
+}
@@ -0,0 +1,37 @@
+import { StarIcon } from "lucide-react-native";
+import React from "react";
+import { Button } from "@/components/ui/button";
+import { Icon } from "@/components/ui/icon";
+import { usePreferences, useToggleFavorite } from "../hooks/api";
+
+interface FavoriteButtonProps {
+	kind: "session" | "project";
+	id: string;
+}
+
+export function FavoriteButton({ kind, id }: FavoriteButtonProps) {
+	const preferences = usePreferences();
+	const toggleFavorite = useToggleFavorite();
+	const starred = (
+		kind === "session"
+			? preferences.favorite_sessions
+			: preferences.favorite_projects
+	).includes(id);
+
+	return (
+		<Button
+			variant="ghost"
+			size="icon"
+			onPress={() => toggleFavorite(kind, id)}
+			aria-pressed={starred}
+			aria-label={starred ? `Unstar ${kind}` : `Star ${kind}`}
+		>
+			<Icon
+				as={StarIcon}
+				className={
+					starred ? "text-yellow-500 fill-yellow-500" : "text-muted-foreground"
+				}
+			/>
+		</Button>
+	);
+}
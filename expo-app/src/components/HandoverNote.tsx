@@ -0,0 +1,76 @@
+import { useRouter } from "expo-router";
+import React, { useState } from "react";
+import { TextInput, View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Text } from "@/components/ui/text";
+import { useSession, useSetSessionNote } from "../hooks/api";
+
+interface HandoverNoteProps {
+	sessionId: string;
+}
+
+const inputClassName =
+	"flex-1 border border-border rounded-md px-3 py-2 text-foreground bg-background";
+
+// Shows the note left by whoever detached last, and lets you leave one when you go
+export function HandoverNote({ sessionId }: HandoverNoteProps) {
+	const router = useRouter();
+	const { data: session } = useSession(sessionId);
+	const setNote = useSetSessionNote(sessionId);
+	const [dismissed, setDismissed] = useState(false);
+	const [draft, setDraft] = useState<string | null>(null);
+
+	const note = session?.attributes?.note;
+	if (session?.attributes?.status !== "running") {
+		return null;
+	}
+
+	const saveAndDetach = () => {
+		const text = draft?.trim();
+		setNote.mutate(text ? text : null, {
+			onSuccess: () => router.push("/"),
+		});
+	};
+
+	if (draft !== null) {
+		return (
+			<View className="flex-row items-center gap-2 px-3 py-2 border-b border-border">
+				<TextInput
+					className={inputClassName}
+					value={draft}
+					onChangeText={setDraft}
+					onSubmitEditing={saveAndDetach}
+					placeholder="Waiting on CI, resume after lunch"
+					aria-label="Handover note"
+					autoFocus
+				/>
+				<Button size="sm" onPress={saveAndDetach} disabled={setNote.isPending}>
+					<Text className="text-xs">Save & detach</Text>
+				</Button>
+				<Button variant="ghost" size="sm" onPress={() => setDraft(null)}>
+					<Text className="text-xs">Cancel</Text>
+				</Button>
+			</View>
+		);
+	}
+
+	return (
+		<View className="flex-row items-center gap-2 px-3 py-2 border-b border-border">
+			{note && !dismissed ? (
+				<View className="flex-1 flex-row items-center gap-2 rounded-md bg-secondary px-3 py-2">
+					<Text className="flex-1 text-sm text-secondary-foreground">
+						📝 {note}
+					</Text>
+					<Button variant="ghost" size="sm" onPress={() => setDismissed(true)}>
+						<Text className="text-xs">Dismiss</Text>
+					</Button>
+				</View>
+			) : (
+				<View className="flex-1" />
+			)}
+			<Button variant="outline" size="sm" onPress={() => setDraft(note ?? "")}>
+				<Text className="text-xs">Detach with note</Text>
+			</Button>
+		</View>
+	);
+}
@@ -0,0 +1,322 @@
+import { useRouter } from "expo-router";
+import React, { useEffect, useState } from "react";
+import { Switch, TextInput, View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Text } from "@/components/ui/text";
+import { useCreateSession, useSessionOptions } from "../hooks/api";
+import type { CreateSessionRequest } from "../types/api";
+
+interface NewSessionFormProps {
+	onClose: () => void;
+}
+
+const inputClassName =
+	"border border-border rounded-md px-3 py-2 text-foreground bg-background";
+
+// Parse "KEY=value" lines into an env map, ignoring blank lines
+const parseEnv = (text: string): Record<string, string> => {
+	const env: Record<string, string> = {};
+	for (const line of text.split("\n")) {
+		const separator = line.indexOf("=");
+		if (separator > 0) {
+			env[line.slice(0, separator).trim()] = line.slice(separator + 1);
+		}
+	}
+	return env;
+};
+
+function Choice({
+	label,
+	selected,
+	disabled,
+	onPress,
+}: {
+	label: string;
+	selected: boolean;
+	disabled?: boolean;
+	onPress: () => void;
+}) {
+	return (
+		<Button
+			size="sm"
+			variant={selected ? "default" : "outline"}
+			disabled={disabled}
+			onPress={onPress}
+			aria-pressed={selected}
+			className="mr-2 mb-2"
+		>
+			<Text>{label}</Text>
+		</Button>
+	);
+}
+
+function Field({
+	label,
+	hint,
+	children,
+}: {
+	label: string;
+	hint?: string;
+	children: React.ReactNode;
+}) {
+	return (
+		<View className="mb-4">
+			<Text className="text-sm font-medium mb-2">{label}</Text>
+			{children}
+			{hint && (
+				<Text className="text-xs text-muted-foreground mt-1">{hint}</Text>
+			)}
+		</View>
+	);
+}
+
+export function NewSessionForm({ onClose }: NewSessionFormProps) {
+	const router = useRouter();
+	const { data: options, isLoading } = useSessionOptions();
+	const createSession = useCreateSession();
+
+	const [template, setTemplate] = useState<string | null>(null);
+	const [agent, setAgent] = useState<string | null>(null);
+	const [projectId, setProjectId] = useState<string | null>(null);
+	const [path, setPath] = useState("");
+	const [cwd, setCwd] = useState("");
+	const [args, setArgs] = useState("");
+	const [env, setEnv] = useState("");
+	const [worktree, setWorktree] = useState(false);
+	const [sandbox, setSandbox] = useState(false);
+	const [record, setRecord] = useState(false);
+
+	const attributes = options?.attributes;
+	const selectedTemplate = attributes?.templates.find(
+		(t) => t.name === template,
+	);
+	const selectedAgent = attributes?.agents.find((a) => a.name === agent);
+	const selectedProject = attributes?.projects.find((p) => p.id === projectId);
+
+	// Preselect the server's default agent once options arrive
+	useEffect(() => {
+		if (agent === null && attributes?.default_agent) {
+			setAgent(attributes.default_agent);
+		}
+	}, [agent, attributes?.default_agent]);
+
+	const chooseTemplate = (name: string | null) => {
+		setTemplate(name);
+		const chosen = attributes?.templates.find((t) => t.name === name);
+		if (chosen) {
+			setAgent(chosen.agent);
+		}
+	};
+
+	// A custom path may or may not be a repository; the server reports the error
+	const canUseWorktree = selectedProject ? selectedProject.git : path !== "";
+
+	const submit = () => {
+		if (!agent) {
+			return;
+		}
+		const request: CreateSessionRequest = {
+			agent,
+			args: args.split(/\s+/).filter(Boolean),
+			env: parseEnv(env),
+			worktree: worktree && canUseWorktree,
+			sandbox: sandbox && !!selectedAgent?.supports_sandbox,
+		};
+		if (record) {
+			request.record = true;
+		}
+		if (template) {
+			request.template = template;
+		}
+		if (selectedProject) {
+			request.project_id = selectedProject.id;
+		} else if (path) {
+			request.path = path;
+		}
+		if (cwd.trim()) {
+			request.cwd = cwd.trim();
+		}
+
+		createSession.mutate(request, {
+			onSuccess: (session) => {
+				onClose();
+				router.push(`/session/${session.id}/terminal`);
+			},
+		});
+	};
+
+	if (isLoading || !attributes) {
+		return (
+			<View className="bg-card p-6 border-b border-border">
+				<Text className="text-muted-foreground">Loading session options…</Text>
+			</View>
+		);
+	}
+
+	return (
+		<View
+			className="bg-card p-6 border-b border-border"
+			role="form"
+			aria-label="New session"
+		>
+			<Text className="text-xl font-bold mb-4">New Session</Text>
+
+			{attributes.templates.length > 0 && (
+				<Field
+					label="Template"
+					hint={
+						selectedTemplate &&
+						[
+							selectedTemplate.description,
+							selectedTemplate.args.length > 0 &&
+								`Adds: ${selectedTemplate.args.join(" ")}`,
+						]
+							.filter(Boolean)
+							.join(" · ")
+					}
+				>
+					<View className="flex-row flex-wrap">
+						<Choice
+							label="None"
+							selected={template === null}
+							onPress={() => chooseTemplate(null)}
+						/>
+						{attributes.templates.map((t) => (
+							<Choice
+								key={t.name}
+								label={t.name}
+								selected={template === t.name}
+								onPress={() => chooseTemplate(t.name)}
+							/>
+						))}
+					</View>
+				</Field>
+			)}
+
+			<Field
+				label="Agent"
+				hint={selectedTemplate ? "Set by the template" : undefined}
+			>
+				<View className="flex-row flex-wrap">
+					{attributes.agents.map((a) => (
+						<Choice
+							key={a.name}
+							label={a.installed ? a.name : `${a.name} (not installed)`}
+							selected={agent === a.name}
+							disabled={!a.installed || !!selectedTemplate}
+							onPress={() => setAgent(a.name)}
+						/>
+					))}
+				</View>
+			</Field>
+
+			<Field
+				label="Project"
+				hint="Pick a project or enter a directory on the server"
+			>
+				<View className="flex-row flex-wrap">
+					{attributes.projects.map((p) => (
+						<Choice
+							key={p.id}
+							label={`${p.icon} ${p.name}`}
+							selected={projectId === p.id}
+							onPress={() => setProjectId(projectId === p.id ? null : p.id)}
+						/>
+					))}
+				</View>
+				{!selectedProject && (
+					<TextInput
+						className={inputClassName}
+						value={path}
+						onChangeText={setPath}
+						placeholder="/path/to/project"
+						aria-label="Project directory"
+					/>
+				)}
+			</Field>
+
+			<Field
+				label="Working directory"
+				hint="Where the agent starts, relative to the project; the project's directory when empty"
+			>
+				<TextInput
+					className={inputClassName}
+					value={cwd}
+					onChangeText={setCwd}
+					placeholder="packages/api"
+					aria-label="Working directory"
+				/>
+			</Field>
+
+			<Field label="Arguments" hint="Space separated, passed to the agent">
+				<TextInput
+					className={inputClassName}
+					value={args}
+					onChangeText={setArgs}
+					placeholder="--model sonnet"
+					aria-label="Agent arguments"
+				/>
+			</Field>
+
+			<Field label="Environment" hint="One KEY=value per line">
+				<TextInput
+					className={inputClassName}
+					value={env}
+					onChangeText={setEnv}
+					multiline
+					numberOfLines={3}
+					aria-label="Environment variables"
+				/>
+			</Field>
+
+			<View className="flex-row items-center mb-2">
+				<Switch
+					value={worktree && canUseWorktree}
+					onValueChange={setWorktree}
+					disabled={!canUseWorktree}
+					aria-label="Run in a new git worktree"
+				/>
+				<Text className="ml-2">Run in a new git worktree</Text>
+			</View>
+			<View className="flex-row items-center mb-2">
+				<Switch
+					value={sandbox && !!selectedAgent?.supports_sandbox}
+					onValueChange={setSandbox}
+					disabled={!selectedAgent?.supports_sandbox}
+					aria-label="Sandbox"
+				/>
+				<Text className="ml-2">
+					{selectedAgent?.supports_sandbox
+						? "Start in the agent's sandbox"
+						: "Sandbox not available for this agent"}
+				</Text>
+			</View>
+			<View className="flex-row items-center mb-4">
+				<Switch
+					value={record}
+					onValueChange={setRecord}
+					aria-label="Record the session"
+				/>
+				<Text className="ml-2">Record the session for replaying it later</Text>
+			</View>
+
+			{createSession.error && (
+				<Text className="text-destructive mb-4" role="alert">
+					{createSession.error.message}
+				</Text>
+			)}
+
+			<View className="flex-row justify-end">
+				<Button variant="ghost" onPress={onClose} className="mr-2">
+					<Text>Cancel</Text>
+				</Button>
+				<Button
+					onPress={submit}
+					disabled={!agent || createSession.isPending}
+				>
+					<Text>{createSession.isPending ? "Starting…" : "Start Session"}</Text>
+				</Button>
+			</View>
+		</View>
+	);
+}
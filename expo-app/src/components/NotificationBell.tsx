@@ -0,0 +1,165 @@
+import { useRouter } from "expo-router";
+import { BellIcon, BellOffIcon } from "lucide-react-native";
+import React, { useEffect, useRef, useState } from "react";
+import { Platform, ScrollView, TouchableOpacity, View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Icon } from "@/components/ui/icon";
+import { Text } from "@/components/ui/text";
+import {
+	useDoNotDisturb,
+	useMarkNotificationRead,
+	useNotifications,
+	useSetDoNotDisturb,
+} from "../hooks/api";
+import type { Notification, NotificationKind } from "../types/bindings";
+
+const KIND_LABELS: Record<NotificationKind, string> = {
+	waiting_for_input: "Waiting for input",
+	approval_needed: "Approval needed",
+	exited: "Exited",
+	checkpoint_created: "Checkpoint",
+	digest: "Daily digest",
+	mass_deletion: "Mass deletion",
+	commit: "Commit",
+};
+
+const canAlert = () =>
+	Platform.OS === "web" && typeof window !== "undefined" && "Notification" in window;
+
+// Raise a browser notification for each new notification the server didn't silence
+function useDesktopAlerts(notifications: Notification[] | undefined) {
+	const seen = useRef<Set<string> | null>(null);
+
+	useEffect(() => {
+		if (!notifications) {
+			return;
+		}
+		// Whatever was there when the page loaded is not new
+		if (seen.current === null) {
+			seen.current = new Set(notifications.map((n) => n.id));
+			return;
+		}
+		for (const notification of notifications) {
+			if (seen.current.has(notification.id)) {
+				continue;
+			}
+			seen.current.add(notification.id);
+			if (
+				!notification.silenced &&
+				!notification.read &&
+				canAlert() &&
+				window.Notification.permission === "granted"
+			) {
+				new window.Notification(`Codemux: ${KIND_LABELS[notification.kind]}`, {
+					body: notification.message,
+					tag: notification.id,
+				});
+			}
+		}
+	}, [notifications]);
+}
+
+// Bell in the header listing events from every session, newest first
+export function NotificationBell() {
+	const router = useRouter();
+	const [open, setOpen] = useState(false);
+	const { data } = useNotifications();
+	const { data: dnd } = useDoNotDisturb();
+	const markRead = useMarkNotificationRead();
+	const setDnd = useSetDoNotDisturb();
+	const notifications = data?.attributes.notifications ?? [];
+	const unread = data?.attributes.unread ?? 0;
+	const dndOn = dnd?.attributes.enabled ?? false;
+	useDesktopAlerts(data?.attributes.notifications);
+
+	const toggleOpen = () => {
+		// Browsers only ask for permission in response to a click
+		if (!open && canAlert() && window.Notification.permission === "default") {
+			window.Notification.requestPermission();
+		}
+		setOpen(!open);
+	};
+
+	return (
+		<View className="relative">
+			<Button
+				onPress={toggleOpen}
+				size="icon"
+				variant={open ? "secondary" : "ghost"}
+				className="rounded-full"
+				aria-label={`Notifications, ${unread} unread`}
+			>
+				<Icon as={dndOn ? BellOffIcon : BellIcon} className="size-6" />
+				{unread > 0 && (
+					<View className="absolute top-0 right-0 bg-destructive rounded-full min-w-4 h-4 px-1 items-center justify-center">
+						<Text className="text-destructive-foreground text-[10px] font-bold">
+							{unread > 99 ? "99+" : unread}
+						</Text>
+					</View>
+				)}
+			</Button>
+
+			{open && (
+				<View className="absolute top-10 right-0 bg-card border border-border rounded shadow-lg z-10 w-80">
+					<TouchableOpacity
+						onPress={() =>
+							setDnd.mutate(
+								dndOn ? { enabled: false } : { enabled: true, minutes: 60 },
+							)
+						}
+						className="p-3 border-b border-border"
+						role="switch"
+						aria-checked={dndOn}
+					>
+						<Text className="text-xs text-muted-foreground">
+							{dndOn
+								? `Do not disturb${dnd?.attributes.until ? ` until ${new Date(dnd.attributes.until).toLocaleTimeString()}` : ""} · turn off`
+								: "Do not disturb for an hour"}
+						</Text>
+					</TouchableOpacity>
+					{notifications.length === 0 ? (
+						<Text className="p-3 text-sm text-muted-foreground">
+							No notifications
+						</Text>
+					) : (
+						<ScrollView className="max-h-96">
+							{notifications.map((notification) => (
+								<TouchableOpacity
+									key={notification.id}
+									onPress={() => {
+										if (!notification.read) {
+											markRead.mutate(notification.id);
+										}
+										setOpen(false);
+										// Digests aren't about a single session
+										if (notification.session_id) {
+											router.push(
+												`/session/${notification.session_id}/terminal`,
+											);
+										}
+									}}
+									className="p-3 border-b border-border last:border-b-0"
+								>
+									<Text
+										className={`text-xs ${notification.read ? "text-muted-foreground" : "text-card-foreground font-bold"}`}
+									>
+										{notification.read ? "" : "● "}
+										{KIND_LABELS[notification.kind]} ·{" "}
+										{notification.session_id
+											? `${notification.session_id.slice(0, 8)} · `
+											: ""}
+										{new Date(notification.created_at).toLocaleTimeString()}
+										{notification.silenced ? " · 🔕" : ""}
+									</Text>
+									<Text className="text-sm text-card-foreground mt-1">
+										{notification.message}
+									</Text>
+								</TouchableOpacity>
+							))}
+						</ScrollView>
+					)}
+				</View>
+			)}
+		</View>
+	);
+}
@@ -0,0 +1,91 @@
+import React from "react";
+import { View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Text } from "@/components/ui/text";
+import type { PasteConfig } from "../types/bindings";
+
+const byteLength = (text: string) => new TextEncoder().encode(text).length;
+
+const lineCount = (text: string) =>
+	text === "" ? 0 : text.replace(/\r?\n$/, "").split(/\r?\n/).length;
+
+// Mirrors `PasteConfig::exceeds_max` and `needs_confirmation` on the server;
+// pastes over the limit always ask since the server would drop them
+export const pasteExceedsMax = (text: string, settings: PasteConfig) =>
+	byteLength(text) > settings.max_bytes;
+
+export const pasteNeedsConfirmation = (text: string, settings: PasteConfig) =>
+	(settings.confirm_lines > 0 && lineCount(text) > settings.confirm_lines) ||
+	(settings.confirm_bytes > 0 && byteLength(text) > settings.confirm_bytes) ||
+	pasteExceedsMax(text, settings);
+
+const describePaste = (text: string) => {
+	const lines = lineCount(text);
+	const bytes = byteLength(text);
+	const size =
+		bytes < 1024
+			? `${bytes} bytes`
+			: bytes < 1024 * 1024
+				? `${(bytes / 1024).toFixed(1)} KB`
+				: `${(bytes / (1024 * 1024)).toFixed(1)} MB`;
+	return `${lines} line${lines === 1 ? "" : "s"}, ${size}`;
+};
+
+interface PasteConfirmDialogProps {
+	text: string;
+	settings: PasteConfig;
+	saving: boolean;
+	onPaste: () => void;
+	onPasteAsFile: () => void;
+	onCancel: () => void;
+}
+
+// Asks before a large paste reaches the agent
+export function PasteConfirmDialog({
+	text,
+	settings,
+	saving,
+	onPaste,
+	onPasteAsFile,
+	onCancel,
+}: PasteConfirmDialogProps) {
+	const tooLarge = pasteExceedsMax(text, settings);
+
+	return (
+		<View
+			className="absolute inset-0 items-center justify-center bg-black/60"
+			role="alertdialog"
+			aria-label="Large paste"
+		>
+			<View className="bg-card border border-border rounded-xl p-4 max-w-md">
+				<Text className="font-medium mb-2">Large paste</Text>
+				<Text className="text-sm text-muted-foreground mb-4">
+					You're pasting {describePaste(text)}.
+					{tooLarge
+						? ` That's over the server's ${settings.max_bytes} byte paste limit.`
+						: ""}
+				</Text>
+				<View className="flex-row gap-2 justify-end">
+					<Button variant="ghost" size="sm" onPress={onCancel}>
+						<Text>Cancel</Text>
+					</Button>
+					{settings.offer_file && (
+						<Button
+							variant="outline"
+							size="sm"
+							disabled={saving}
+							onPress={onPasteAsFile}
+						>
+							<Text>{saving ? "Saving..." : "Send as file"}</Text>
+						</Button>
+					)}
+					{!tooLarge && (
+						<Button size="sm" onPress={onPaste}>
+							<Text>Paste</Text>
+						</Button>
+					)}
+				</View>
+			</View>
+		</View>
+	);
+}
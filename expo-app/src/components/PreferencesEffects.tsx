@@ -0,0 +1,24 @@
+import { useEffect } from "react";
+import { Platform } from "react-native";
+import { usePreferences } from "../hooks/api";
+
+/**
+ * Applies the shared display preferences to the page.
+ *
+ * Renders nothing; toggles the `high-contrast` and `reduce-motion` root classes
+ * used by global.css. The terminal picks its palette itself.
+ */
+export function PreferencesEffects() {
+	const { high_contrast, reduced_motion } = usePreferences();
+
+	useEffect(() => {
+		if (Platform.OS !== "web" || typeof document === "undefined") {
+			return;
+		}
+		const root = document.documentElement;
+		root.classList.toggle("high-contrast", high_contrast);
+		root.classList.toggle("reduce-motion", reduced_motion);
+	}, [high_contrast, reduced_motion]);
+
+	return null;
+}
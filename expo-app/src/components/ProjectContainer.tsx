@@ -1,10 +1,13 @@
 import React from "react";
 import { Text, View } from "react-native";
+import { FavoriteButton } from "./FavoriteButton";
 import { SessionCard } from "./SessionCard";
 
 interface ProjectAttributes {
 	name?: string;
 	path?: string;
+	color?: string;
+	icon?: string;
 }
 
 interface Session {
@@ -19,6 +22,7 @@ interface Session {
 
 interface ProjectRelationships {
 	recent_sessions?: Session[];
+	subproject_sessions?: Session[];
 }
 
 interface Project {
@@ -29,29 +33,52 @@ interface Project {
 
 interface ProjectContainerProps {
 	project: Project;
+	// Name including the parent projects', e.g. "mono/api"
+	qualifiedName?: string;
 }
 
-export function ProjectContainer({ project }: ProjectContainerProps) {
+export function ProjectContainer({
+	project,
+	qualifiedName,
+}: ProjectContainerProps) {
 	const projectSessions = project.relationships?.recent_sessions || [];
+	const subprojectSessions = project.relationships?.subproject_sessions || [];
+	const accent = project.attributes?.color;
 
 	return (
 		<View className="mb-4">
 			{/* Project Header */}
-			<View className="flex-row justify-between items-start mb-3">
+			<View
+				className="flex-row justify-between items-start mb-3"
+				style={
+					accent
+						? { borderLeftWidth: 4, borderLeftColor: accent, paddingLeft: 8 }
+						: undefined
+				}
+			>
 				<View className="flex-1">
 					<Text className="text-card-foreground text-xl font-bold">
-						{project.attributes?.name || "Unknown Project"}
+						{project.attributes?.icon ? `${project.attributes.icon} ` : ""}
+						{qualifiedName || project.attributes?.name || "Unknown Project"}
 					</Text>
 					<Text className="text-muted-foreground text-sm mt-1">
 						{project.attributes?.path || "Unknown Path"}
 					</Text>
 				</View>
+				<FavoriteButton kind="project" id={project.id} />
 				<View className="px-2 py-1 rounded bg-secondary">
 					<Text className="text-secondary-foreground text-xs">
 						{projectSessions.length} SESSION
 						{projectSessions.length !== 1 ? "S" : ""}
 					</Text>
 				</View>
+				{subprojectSessions.length > 0 && (
+					<View className="px-2 py-1 rounded bg-muted ml-2">
+						<Text className="text-muted-foreground text-xs">
+							+{subprojectSessions.length} IN SUB-PROJECTS
+						</Text>
+					</View>
+				)}
 			</View>
 
 			{/* Sessions for this project */}
@@ -1,8 +1,18 @@
+import { PlusIcon } from "lucide-react-native";
 import React from "react";
 import { Text, View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Icon } from "@/components/ui/icon";
+import { Text as UiText } from "@/components/ui/text";
+import { AccessibilityToggles } from "./AccessibilityToggles";
+import { NotificationBell } from "./NotificationBell";
 import { ThemeToggle } from "./ThemeToggle";
 
-export function ProjectsHeader() {
+interface ProjectsHeaderProps {
+	onNewSession: () => void;
+}
+
+export function ProjectsHeader({ onNewSession }: ProjectsHeaderProps) {
 	return (
 		<View className="bg-card p-6 border-b border-border">
 			<View className="flex-row justify-between items-start mb-2">
@@ -11,7 +21,15 @@ export function ProjectsHeader() {
 						Codemux Projects
 					</Text>
 				</View>
-				<ThemeToggle />
+				<View className="flex-row items-center">
+					<Button onPress={onNewSession} size="sm" className="mr-2">
+						<Icon as={PlusIcon} className="text-primary-foreground" />
+						<UiText>New Session</UiText>
+					</Button>
+					<NotificationBell />
+					<AccessibilityToggles />
+					<ThemeToggle />
+				</View>
 			</View>
 			<Text className="text-muted-foreground">
 				Manage your AI coding projects
@@ -0,0 +1,61 @@
+import { useRouter } from "expo-router";
+import React from "react";
+import { ScrollView, View } from "react-native";
+import { Button } from "@/components/ui/button";
+import { Text } from "@/components/ui/text";
+import { useRecent } from "../hooks/api";
+
+// Starred and recently attached sessions, shown above the project list
+export function QuickSwitchList() {
+	const router = useRouter();
+	const { data } = useRecent();
+	const sessions = data?.attributes.sessions ?? [];
+	const projects = data?.attributes.projects ?? [];
+
+	if (sessions.length === 0 && projects.length === 0) {
+		return null;
+	}
+
+	return (
+		<View className="mb-4" role="navigation" aria-label="Quick switch">
+			<Text className="text-sm font-medium text-muted-foreground mb-2">
+				Starred & Recent
+			</Text>
+			<ScrollView horizontal showsHorizontalScrollIndicator={false}>
+				{sessions.map((session) => (
+					<Button
+						key={session.id}
+						variant="outline"
+						size="sm"
+						className="mr-2"
+						style={
+							session.color
+								? { borderBottomWidth: 2, borderBottomColor: session.color }
+								: undefined
+						}
+						disabled={!session.running}
+						onPress={() => router.push(`/session/${session.id}/terminal`)}
+					>
+						<Text className="text-xs">
+							{session.favorite ? "★ " : ""}
+							{session.icon ? `${session.icon} ` : ""}
+							{session.agent ?? "session"} · {session.id.slice(0, 8)}
+							{session.running ? "" : " (ended)"}
+						</Text>
+					</Button>
+				))}
+				{projects.map((project) => (
+					<View
+						key={project.id}
+						className="mr-2 px-3 py-1 rounded-md bg-secondary justify-center"
+					>
+						<Text className="text-xs text-secondary-foreground">
+							★ {project.icon ? `${project.icon} ` : ""}
+							{project.name}
+						</Text>
+					</View>
+				))}
+			</ScrollView>
+		</View>
+	);
+}
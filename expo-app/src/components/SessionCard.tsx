@@ -10,6 +10,8 @@ import {
 	CardTitle,
 } from "@/components/ui/card";
 import { Text as UIText } from "@/components/ui/text";
+import { ActivitySparkline } from "./ActivitySparkline";
+import { FavoriteButton } from "./FavoriteButton";
 import { LastMessage } from "./LastMessage";
 
 interface SessionAttributes {
@@ -17,6 +19,9 @@ interface SessionAttributes {
 	status?: string;
 	session_type?: string;
 	last_message?: string;
+	color?: string;
+	icon?: string;
+	note?: string;
 }
 
 interface Session {
@@ -73,16 +78,26 @@ export function SessionCard({ session }: SessionCardProps) {
 
 	const sessionStatus = getSessionStatus();
 
+	const accent = session.attributes?.color;
+
 	return (
-		<Card>
+		<Card
+			style={
+				accent ? { borderLeftWidth: 4, borderLeftColor: accent } : undefined
+			}
+		>
 			<CardHeader>
 				<View className="flex-row justify-between items-center">
 					<View className="flex-1">
-						<CardTitle className="text-sm">Session: {session.id}</CardTitle>
+						<CardTitle className="text-sm">
+							{session.attributes?.icon ? `${session.attributes.icon} ` : ""}
+							Session: {session.attributes?.name ?? session.id}
+						</CardTitle>
 						<CardDescription className="text-xs mt-1">
 							Agent: {session.attributes?.agent || "Unknown"}
 						</CardDescription>
 					</View>
+					<FavoriteButton kind="session" id={session.id} />
 					<View className={`px-2 py-1 rounded ${sessionStatus.bg}`}>
 						<Text className={`text-xs ${sessionStatus.text}`}>
 							{sessionStatus.label}
@@ -92,11 +107,23 @@ export function SessionCard({ session }: SessionCardProps) {
 			</CardHeader>
 
 			<CardContent>
+				{session.attributes?.note && (
+					<View className="mb-2 rounded bg-secondary px-2 py-1">
+						<Text className="text-xs text-secondary-foreground">
+							📝 {session.attributes.note}
+						</Text>
+					</View>
+				)}
+
 				<LastMessage
 					message={session.attributes?.last_message}
 					agent={session.attributes?.agent}
 				/>
 
+				{session.attributes?.status === "running" && (
+					<ActivitySparkline sessionId={session.id} />
+				)}
+
 				<View className="flex-row gap-2 mt-2">
 					<Button
 						variant="outline"
@@ -0,0 +1,38 @@
+import React from "react";
+import { Text, View } from "react-native";
+import { useSessionCommits } from "../hooks/api";
+
+interface SessionCommitsProps {
+	sessionId: string;
+}
+
+// Commits the agent announced during the session, newest first; nothing for agents that don't
+export default function SessionCommits({ sessionId }: SessionCommitsProps) {
+	const { data: commits } = useSessionCommits(sessionId);
+
+	if (!commits?.length) {
+		return null;
+	}
+
+	return (
+		<View className="bg-card p-3 border-b border-border">
+			<Text className="text-foreground text-sm mb-2">
+				{commits.length} {commits.length === 1 ? "commit" : "commits"} by the
+				agent
+			</Text>
+			{[...commits].reverse().map(({ attributes: commit }) => (
+				<View key={commit.hash} className="flex-row items-center py-0.5">
+					<Text className="text-primary font-mono text-xs mr-2">
+						{commit.hash.slice(0, 7)}
+					</Text>
+					<Text className="text-foreground text-xs flex-1" numberOfLines={1}>
+						{commit.message}
+					</Text>
+					<Text className="text-muted-foreground text-xs ml-2">
+						{new Date(commit.created_at).toLocaleTimeString()}
+					</Text>
+				</View>
+			))}
+		</View>
+	);
+}
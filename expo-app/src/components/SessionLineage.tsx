@@ -0,0 +1,70 @@
+import { useRouter } from "expo-router";
+import React from "react";
+import { Pressable, Text, View } from "react-native";
+import { useSessionLineage } from "../hooks/api";
+import type { SessionLinkKind } from "../types/bindings";
+
+interface SessionLineageProps {
+	sessionId: string;
+}
+
+const KIND_LABELS: Record<SessionLinkKind, string> = {
+	spawned_from_template: "from template",
+	clone_of: "clone",
+	retry_of: "retry",
+	worktree_of: "worktree",
+};
+
+// Breadcrumb of the sessions this one was cloned or retried from; nothing for a first attempt
+export function SessionLineage({ sessionId }: SessionLineageProps) {
+	const router = useRouter();
+	const { data: lineage } = useSessionLineage(sessionId);
+
+	const template = lineage?.[0]?.attributes.template;
+	if (!lineage || (lineage.length < 2 && !template)) {
+		return null;
+	}
+
+	return (
+		<View className="flex-row flex-wrap items-center gap-1 px-3 py-1 border-b border-border">
+			{template && (
+				<Text className="text-xs text-muted-foreground">
+					📄 {template} ›
+				</Text>
+			)}
+			{lineage.map(({ id, attributes: entry }, index) => {
+				const label = entry.name ?? id.slice(0, 8);
+				const current = index === lineage.length - 1;
+				return (
+					<View key={id} className="flex-row items-center gap-1">
+						{entry.kind && entry.kind !== "spawned_from_template" && (
+							<Text className="text-xs text-muted-foreground">
+								{KIND_LABELS[entry.kind]} ›
+							</Text>
+						)}
+						{current ? (
+							<Text
+								className="text-xs text-foreground"
+								style={{ color: entry.color }}
+							>
+								{entry.icon} {label}
+							</Text>
+						) : (
+							<Pressable
+								onPress={() => router.push(`/session/${id}/terminal`)}
+								accessibilityRole="link"
+							>
+								<Text
+									className="text-xs underline"
+									style={{ color: entry.color }}
+								>
+									{entry.icon} {label}
+								</Text>
+							</Pressable>
+						)}
+					</View>
+				);
+			})}
+		</View>
+	);
+}
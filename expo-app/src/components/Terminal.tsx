@@ -8,17 +8,40 @@ import {
 	TouchableOpacity,
 	View,
 } from "react-native";
+import {
+	useCopyScreenText,
+	usePasteSettings,
+	usePreferences,
+	useRetractMessage,
+	useSavePasteFile,
+	useSessionHeatmap,
+} from "../hooks/api";
 import { useWebSocketWithReconnect } from "../hooks/useWebSocketWithReconnect";
+import { wsUrl } from "../lib/apiClient";
+import { RenderWatchdog } from "../lib/renderWatchdog";
+import { expandRuns } from "../lib/runLength";
 import {
 	availableThemes,
+	highContrastTheme,
 	useTerminalStore,
 	type WebKeyEvent,
 } from "../stores/terminalStore";
-import type { ClientMessage, ServerMessage } from "../types/bindings";
+import type {
+	ClientMessage,
+	PromptType,
+	ServerMessage,
+} from "../types/bindings";
+import {
+	PasteConfirmDialog,
+	pasteExceedsMax,
+	pasteNeedsConfirmation,
+} from "./PasteConfirmDialog";
 import { TerminalCell } from "./TerminalCell";
 
 interface TerminalProps {
 	sessionId: string;
+	// Stream to render instead of the session's WebSocket, e.g. the render test
+	url?: string;
 }
 
 // Memoized row component to prevent unnecessary re-renders
@@ -56,8 +79,15 @@ TerminalGrid.displayName = "TerminalGrid";
 
 // Separate input component to isolate input state changes
 const TerminalInput = memo(
-	({ onSubmit }: { onSubmit: (text: string) => void }) => {
+	({
+		onSubmit,
+		onRetract,
+	}: {
+		onSubmit: (text: string) => void;
+		onRetract: () => Promise<string | null>;
+	}) => {
 		const [inputValue, setInputValue] = useState("");
+		const [retracting, setRetracting] = useState(false);
 		const { colorScheme } = useColorScheme();
 
 		const handleSubmit = useCallback(() => {
@@ -67,6 +97,21 @@ const TerminalInput = memo(
 			}
 		}, [inputValue, onSubmit]);
 
+		// Interrupt the agent and bring the last message back for editing
+		const handleRetract = useCallback(async () => {
+			setRetracting(true);
+			try {
+				const text = await onRetract();
+				if (text) {
+					setInputValue(text);
+				}
+			} catch {
+				// The mutation reports the error
+			} finally {
+				setRetracting(false);
+			}
+		}, [onRetract]);
+
 		// Define placeholder color based on theme
 		const placeholderColor = colorScheme === "dark" ? "#737373" : "#9ca3af";
 
@@ -84,6 +129,14 @@ const TerminalInput = memo(
 					autoCorrect={false}
 					autoCapitalize="none"
 				/>
+				<TouchableOpacity
+					onPress={handleRetract}
+					disabled={retracting}
+					className="bg-muted px-2 py-1 rounded mr-2"
+					accessibilityLabel="Retract last message"
+				>
+					<Text className="text-muted-foreground text-xs">↶ Retract</Text>
+				</TouchableOpacity>
 				<Text className="text-muted-foreground text-xs bg-muted px-2 py-1 rounded">
 					Enter to send
 				</Text>
@@ -187,99 +240,319 @@ const ThemeSelector = memo(() => {
 
 ThemeSelector.displayName = "ThemeSelector";
 
-export default function Terminal({ sessionId }: TerminalProps) {
+// Enter presentation mode
+const PresentButton = memo(() => {
+	const setPresentationMode = useTerminalStore(
+		(state) => state.setPresentationMode,
+	);
+
+	return (
+		<TouchableOpacity
+			onPress={() => setPresentationMode(true)}
+			className="bg-gray-700 px-3 py-1 rounded ml-2"
+			accessibilityLabel="Present: read-only view with larger text"
+		>
+			<Text className="text-white text-xs">📽️ Present</Text>
+		</TouchableOpacity>
+	);
+});
+
+PresentButton.displayName = "PresentButton";
+
+// Toggle the repaint heatmap overlay
+const HeatmapButton = memo(
+	({ active, onPress }: { active: boolean; onPress: () => void }) => (
+		<TouchableOpacity
+			onPress={onPress}
+			className={`${active ? "bg-orange-600" : "bg-gray-700"} px-3 py-1 rounded ml-2`}
+			accessibilityLabel="Heatmap: highlight the cells the agent repaints most"
+		>
+			<Text className="text-white text-xs">🔥 Heatmap</Text>
+		</TouchableOpacity>
+	),
+);
+
+HeatmapButton.displayName = "HeatmapButton";
+
+// Copies the whole screen as text, since the grid's cells can't be selected
+const CopyScreenButton = memo(({ sessionId }: { sessionId: string }) => {
+	const copyScreen = useCopyScreenText(sessionId);
+	const lines = copyScreen.data;
+	const label =
+		lines === undefined
+			? "📋 Copy"
+			: `✓ Copied ${lines} ${lines === 1 ? "line" : "lines"}`;
+
+	return (
+		<TouchableOpacity
+			onPress={() => copyScreen.mutate()}
+			disabled={copyScreen.isPending}
+			className="bg-gray-700 px-3 py-1 rounded ml-2"
+			accessibilityLabel="Copy the text on the screen"
+		>
+			<Text className="text-white text-xs">{label}</Text>
+		</TouchableOpacity>
+	);
+});
+
+CopyScreenButton.displayName = "CopyScreenButton";
+
+type InputControlMessage = Extract<ServerMessage, { type: "input_control" }>;
+
+// Who may type, for sessions that don't let every client write at once
+const InputControlBar = memo(
+	({
+		control,
+		onRequest,
+		onRelease,
+	}: {
+		control: InputControlMessage;
+		onRequest: () => void;
+		onRelease: () => void;
+	}) => {
+		if (control.mode === "shared") {
+			return null;
+		}
+		const holding = control.holder === control.client_id;
+		const place = control.waiting.indexOf(control.client_id);
+		let status = "Nobody has control - type or take it";
+		if (control.mode === "read_only") {
+			status = "Read-only session: nobody can type";
+		} else if (holding) {
+			status = "You have control of this session";
+		} else if (place >= 0) {
+			status = `Waiting for control (${place + 1} in line)`;
+		} else if (control.holder) {
+			status = `${control.holder} has control of this session`;
+		}
+		// Exclusive control can only be taken while nobody holds it
+		const canRequest =
+			!holding &&
+			place < 0 &&
+			(control.mode === "round_robin" ||
+				(control.mode === "exclusive" && !control.holder));
+
+		return (
+			<View className="p-2 flex-row items-center bg-gray-700">
+				<Text className="flex-1 text-white text-sm">
+					{control.can_write ? "✍️" : "🔒"} {status}
+				</Text>
+				{canRequest && (
+					<TouchableOpacity
+						onPress={onRequest}
+						className="bg-white bg-opacity-20 px-2 py-1 rounded ml-2"
+					>
+						<Text className="text-white text-xs">
+							{control.holder ? "Wait for Turn" : "Take Control"}
+						</Text>
+					</TouchableOpacity>
+				)}
+				{(holding || place >= 0) && (
+					<TouchableOpacity
+						onPress={onRelease}
+						className="bg-white bg-opacity-20 px-2 py-1 rounded ml-2"
+					>
+						<Text className="text-white text-xs">
+							{holding ? "Release Control" : "Leave Queue"}
+						</Text>
+					</TouchableOpacity>
+				)}
+			</View>
+		);
+	},
+);
+
+InputControlBar.displayName = "InputControlBar";
+
+export default function Terminal({ sessionId, url }: TerminalProps) {
 	const scrollViewRef = useRef<ScrollView>(null);
 	const terminalRef = useRef<View>(null);
 	const { colorScheme } = useColorScheme();
 	const setTheme = useTerminalStore((state) => state.setTheme);
+	const renderWatchdog = useRef(new RenderWatchdog(sessionId));
+	const { high_contrast, reduced_motion } = usePreferences();
+	const presentationMode = useTerminalStore((state) => state.presentationMode);
+	const setPresentationMode = useTerminalStore(
+		(state) => state.setPresentationMode,
+	);
+	const pasteSettings = usePasteSettings();
+	const savePasteFile = useSavePasteFile(sessionId);
+	const retractMessage = useRetractMessage(sessionId);
+	// Large paste waiting for an answer in the paste dialog
+	const [pendingPaste, setPendingPaste] = useState<string | null>(null);
+	// Set when a reconnect found the server restarted and the session restored
+	const [serverRestarted, setServerRestarted] = useState(false);
+	// Latest warning from the server, e.g. a mass deletion, until dismissed
+	const [serverAlert, setServerAlert] = useState<string | null>(null);
+	// Set while typed input waits unusually long for the agent
+	const [inputLagging, setInputLagging] = useState(false);
+	// Approval the agent waits for, e.g. a tool permission
+	const [waitingPrompt, setWaitingPrompt] = useState<PromptType | null>(null);
+	// Exit code of the agent once it exited; there is nothing to reconnect to then
+	const [exitCode, setExitCode] = useState<number | null>(null);
+	// Input mode of the session and who may type in it
+	const [inputControl, setInputControl] = useState<InputControlMessage | null>(
+		null,
+	);
+	// Repaint heatmap overlay, for debugging agents that thrash the screen
+	const [showHeatmap, setShowHeatmap] = useState(false);
+	const setHeatmap = useTerminalStore((state) => state.setHeatmap);
+	const { data: heatmap } = useSessionHeatmap({
+		sessionId,
+		enabled: showHeatmap && !url,
+	});
 
-	// Sync terminal theme with app color scheme
 	useEffect(() => {
+		setHeatmap(showHeatmap ? (heatmap?.attributes ?? null) : null);
+	}, [showHeatmap, heatmap, setHeatmap]);
+
+	// Don't leave the overlay on for the next session
+	useEffect(() => () => setHeatmap(null), [setHeatmap]);
+
+	// Leave presentation mode when navigating away from the session
+	useEffect(() => {
+		return () => setPresentationMode(false);
+	}, [setPresentationMode]);
+
+	// Sync terminal theme with app color scheme, unless high contrast is preferred
+	useEffect(() => {
+		if (high_contrast) {
+			setTheme(highContrastTheme);
+			return;
+		}
 		const targetTheme =
 			colorScheme === "dark"
 				? availableThemes.find((t) => t.name === "Default Dark") ||
 					availableThemes[0]
 				: availableThemes.find((t) => t.name === "Light") || availableThemes[1];
 		setTheme(targetTheme);
-	}, [colorScheme, setTheme]);
-
-	const handleWebSocketMessage = useCallback((event: MessageEvent) => {
-		try {
-			const message = JSON.parse(event.data) as ServerMessage;
-			console.log("WebSocket message received:", message.type, message);
-
-			switch (message.type) {
-				case "grid_update":
-					if ("Keyframe" in message) {
-						console.log("Grid update keyframe:", {
-							size: message.Keyframe.size,
-							cellCount: message.Keyframe.cells.length,
-							cursor: message.Keyframe.cursor,
-							cursor_visible: message.Keyframe.cursor_visible,
-						});
-
-						// Transform keyframe data to match store expectations
-						const transformedMessage = {
-							type: "grid_update",
-							size: message.Keyframe.size,
-							cells: message.Keyframe.cells,
-							cursor: {
-								row: message.Keyframe.cursor[0],
-								col: message.Keyframe.cursor[1],
-							},
-							cursor_visible: message.Keyframe.cursor_visible,
-							timestamp: message.Keyframe.timestamp,
-						};
-
-						useTerminalStore.getState().handleGridUpdate(transformedMessage);
-					} else if ("Diff" in message) {
-						console.log("Grid update diff:", {
-							changeCount: message.Diff.changes.length,
-							cursor: message.Diff.cursor,
-							cursor_visible: message.Diff.cursor_visible,
-						});
-
-						// Transform diff data to match store expectations
-						const transformedMessage = {
-							type: "grid_update",
-							cells: message.Diff.changes,
-							cursor: message.Diff.cursor
-								? {
-										row: message.Diff.cursor[0],
-										col: message.Diff.cursor[1],
-									}
-								: undefined,
-							cursor_visible: message.Diff.cursor_visible,
-							timestamp: message.Diff.timestamp,
-						};
-
-						useTerminalStore.getState().handleGridUpdate(transformedMessage);
-					}
-					break;
-				case "pty_size":
-					console.log("PTY size update:", message.rows, "x", message.cols);
-					useTerminalStore.getState().updateSize(message.rows, message.cols);
-					break;
-				case "output":
-					// Handle legacy output messages - these are raw terminal output
-					console.log(
-						"Received raw output:",
-						message.data,
-						"at",
-						message.timestamp,
-					);
-					break;
-				case "error":
-					console.error("Server error:", message.message);
-					break;
-				default:
-					console.log("Unknown message type:", message);
-			}
-		} catch (error) {
-			console.error("Failed to parse WebSocket message:", error);
+	}, [colorScheme, high_contrast, setTheme]);
+
+	const handleServerMessage = useCallback((message: ServerMessage) => {
+		console.log("WebSocket message received:", message.type, message);
+
+		switch (message.type) {
+			case "grid_update":
+				if ("Keyframe" in message) {
+					message.Keyframe = expandRuns(message.Keyframe);
+				}
+				// Keep the bare GridUpdateMessage so crash dumps replay on the server
+				renderWatchdog.current.record(
+					"Keyframe" in message
+						? { Keyframe: message.Keyframe }
+						: { Diff: message.Diff },
+				);
+				if ("Keyframe" in message) {
+					console.log("Grid update keyframe:", {
+						size: message.Keyframe.size,
+						cellCount: message.Keyframe.cells.length,
+						cursor: message.Keyframe.cursor,
+						cursor_visible: message.Keyframe.cursor_visible,
+					});
+
+					// Transform keyframe data to match store expectations
+					const transformedMessage = {
+						type: "grid_update",
+						size: message.Keyframe.size,
+						cells: message.Keyframe.cells,
+						cursor: {
+							row: message.Keyframe.cursor[0],
+							col: message.Keyframe.cursor[1],
+						},
+						cursor_visible: message.Keyframe.cursor_visible,
+						timestamp: message.Keyframe.timestamp,
+					};
+
+					useTerminalStore.getState().handleGridUpdate(transformedMessage);
+				} else if ("Diff" in message) {
+					console.log("Grid update diff:", {
+						changeCount: message.Diff.changes.length,
+						cursor: message.Diff.cursor,
+						cursor_visible: message.Diff.cursor_visible,
+					});
+
+					// Transform diff data to match store expectations
+					const transformedMessage = {
+						type: "grid_update",
+						cells: message.Diff.changes,
+						cursor: message.Diff.cursor
+							? {
+									row: message.Diff.cursor[0],
+									col: message.Diff.cursor[1],
+								}
+							: undefined,
+						cursor_visible: message.Diff.cursor_visible,
+						timestamp: message.Diff.timestamp,
+					};
+
+					useTerminalStore.getState().handleGridUpdate(transformedMessage);
+				}
+				renderWatchdog.current.check(useTerminalStore.getState());
+				break;
+			case "pty_size":
+				console.log("PTY size update:", message.rows, "x", message.cols);
+				useTerminalStore.getState().updateSize(message.rows, message.cols);
+				break;
+			case "output":
+				// Handle legacy output messages - these are raw terminal output
+				console.log(
+					"Received raw output:",
+					message.data,
+					"at",
+					message.timestamp,
+				);
+				break;
+			case "error":
+				console.error("Server error:", message.message);
+				break;
+			case "rebound":
+				setServerRestarted(message.restarted);
+				break;
+			case "alert":
+				setServerAlert(message.message);
+				break;
+			case "input_control":
+				setInputControl(message);
+				break;
+			case "capabilities":
+				console.log("Server capabilities:", message.capabilities);
+				break;
+			case "input_lag":
+				setInputLagging(message.lagging);
+				break;
+			case "prompt_detected":
+				setWaitingPrompt(message.prompt);
+				break;
+			case "prompt_cleared":
+				setWaitingPrompt(null);
+				break;
+			case "exited":
+				setExitCode(message.code);
+				setServerAlert(`The agent exited with code ${message.code}`);
+				break;
+			case "input_ack":
+				// Only sent for input with a `seq`, which this view doesn't number
+				break;
+			default:
+				console.log("Unknown message type:", message);
 		}
 	}, []);
 
+	const handleWebSocketMessage = useCallback(
+		(event: MessageEvent) => {
+			try {
+				// With the batch capability, messages close together arrive as an array
+				const parsed = JSON.parse(event.data) as ServerMessage | ServerMessage[];
+				for (const message of Array.isArray(parsed) ? parsed : [parsed]) {
+					handleServerMessage(message);
+				}
+			} catch (error) {
+				console.error("Failed to parse WebSocket message:", error);
+			}
+		},
+		[handleServerMessage],
+	);
+
 	// WebSocket connection with auto-reconnection
 	const {
 		isConnected,
@@ -287,15 +560,22 @@ export default function Terminal({ sessionId }: TerminalProps) {
 		reconnectAttempt,
 		nextReconnectIn,
 		send,
+		close,
 		reconnect,
 	} = useWebSocketWithReconnect({
-		url: `ws://localhost:8765/ws/${sessionId}`,
+		url: url ?? wsUrl(`/ws/${sessionId}?capabilities=batch,rle`),
+		reconnectUrl:
+			url ?? wsUrl(`/ws/${sessionId}?rebind=true&capabilities=batch,rle`),
 		maxReconnectAttempts: 10,
 		baseDelay: 5000,
 		maxDelay: 30000,
 		backoffFactor: 2,
 		onOpen: () => {
 			console.log("WebSocket connected");
+			// The server says so again on connect if input is still lagging
+			setInputLagging(false);
+			// Likewise for a prompt still waiting for approval
+			setWaitingPrompt(null);
 			// Request initial keyframe to get current terminal state
 			// TODO: This message type is not in the generated ClientMessage union
 			// Consider adding it to the Rust backend or removing this functionality
@@ -315,6 +595,21 @@ export default function Terminal({ sessionId }: TerminalProps) {
 		},
 	});
 
+	// Reconnecting would restore the session the agent just left
+	useEffect(() => {
+		if (exitCode !== null) {
+			close();
+		}
+	}, [exitCode, close]);
+
+	const sendControlMessage = useCallback(
+		(type: "request_control" | "release_control") => {
+			const message: ClientMessage = { type };
+			send(JSON.stringify(message));
+		},
+		[send],
+	);
+
 	const sendScrollEvent = useCallback(
 		(direction: "Up" | "Down", lines: number = 1) => {
 			const message: ClientMessage = {
@@ -333,6 +628,11 @@ export default function Terminal({ sessionId }: TerminalProps) {
 			// Prevent default scroll behavior
 			event.preventDefault();
 
+			// Presentation mode is read-only; scrolling would move everyone's view
+			if (presentationMode) {
+				return;
+			}
+
 			// Determine scroll direction from wheel delta
 			const direction = event.deltaY > 0 ? "Down" : "Up";
 
@@ -346,7 +646,7 @@ export default function Terminal({ sessionId }: TerminalProps) {
 				document.removeEventListener("wheel", handleWheel);
 			};
 		}
-	}, [sendScrollEvent]);
+	}, [sendScrollEvent, presentationMode]);
 
 	const _sendInput = useCallback(
 		(data: string) => {
@@ -372,6 +672,14 @@ export default function Terminal({ sessionId }: TerminalProps) {
 		[send],
 	);
 
+	const sendPaste = useCallback(
+		(text: string) => {
+			const message: ClientMessage = { type: "paste", text };
+			send(JSON.stringify(message));
+		},
+		[send],
+	);
+
 	const handleInputSubmit = useCallback(
 		(text: string) => {
 			// Send each character as a key event for better terminal compatibility
@@ -393,6 +701,27 @@ export default function Terminal({ sessionId }: TerminalProps) {
 	// Handle keyboard events for direct key input
 	const handleKeyDown = useCallback(
 		(event: KeyboardEvent) => {
+			// Presentation mode is read-only; Escape is the only key and leaves it
+			if (presentationMode) {
+				if (event.key === "Escape") {
+					setPresentationMode(false);
+				}
+				return;
+			}
+
+			// The paste dialog has the keyboard until it's answered
+			if (pendingPaste !== null) {
+				if (event.key === "Escape") {
+					setPendingPaste(null);
+				}
+				return;
+			}
+
+			// Let the browser fire a paste event for Ctrl/Cmd+V
+			if ((event.ctrlKey || event.metaKey) && event.key.toLowerCase() === "v") {
+				return;
+			}
+
 			// Prevent default browser behavior for most keys
 			if (!["F5", "F12"].includes(event.key)) {
 				event.preventDefault();
@@ -472,7 +801,7 @@ export default function Terminal({ sessionId }: TerminalProps) {
 				modifiers: modifiers,
 			});
 		},
-		[sendKeyEvent],
+		[sendKeyEvent, presentationMode, setPresentationMode, pendingPaste],
 	);
 
 	// Set up keyboard event listener
@@ -487,40 +816,147 @@ export default function Terminal({ sessionId }: TerminalProps) {
 		};
 	}, [handleKeyDown]);
 
+	// Paste the clipboard as one message, asking first if it's large
+	useEffect(() => {
+		const handlePaste = (event: ClipboardEvent) => {
+			if (presentationMode || pendingPaste !== null) {
+				return;
+			}
+			event.preventDefault();
+
+			const text = event.clipboardData?.getData("text") ?? "";
+			if (!text) {
+				// Nothing textual (e.g. an image), so let the agent see Ctrl+V
+				sendKeyEvent({
+					code: { Char: "v" },
+					modifiers: { shift: false, ctrl: true, alt: false, meta: false },
+				});
+				return;
+			}
+
+			if (pasteNeedsConfirmation(text, pasteSettings)) {
+				setPendingPaste(text);
+			} else {
+				sendPaste(text);
+			}
+		};
+
+		if (typeof window !== "undefined") {
+			document.addEventListener("paste", handlePaste);
+			return () => {
+				document.removeEventListener("paste", handlePaste);
+			};
+		}
+	}, [presentationMode, pendingPaste, pasteSettings, sendPaste, sendKeyEvent]);
+
+	const confirmPaste = useCallback(() => {
+		if (pendingPaste !== null && !pasteExceedsMax(pendingPaste, pasteSettings)) {
+			sendPaste(pendingPaste);
+		}
+		setPendingPaste(null);
+	}, [pendingPaste, pasteSettings, sendPaste]);
+
+	const pasteAsFile = useCallback(async () => {
+		if (pendingPaste === null) {
+			return;
+		}
+		try {
+			const path = await savePasteFile.mutateAsync(pendingPaste);
+			sendPaste(path);
+			setPendingPaste(null);
+		} catch {
+			// Leave the dialog open; the mutation reports the error
+		}
+	}, [pendingPaste, savePasteFile, sendPaste]);
+
 	return (
 		<View className="flex-1 bg-black" ref={terminalRef}>
 			{/* Connection status and theme controls */}
-			<View
-				className={`p-2 flex-row justify-between items-center ${
-					isConnected
-						? "bg-green-700"
-						: isReconnecting
-							? "bg-yellow-700"
-							: "bg-red-700"
-				}`}
-			>
-				<View className="flex-1">
-					<Text className="text-white text-xs">
-						{isConnected
-							? `Connected to session ${sessionId.slice(0, 8)}`
+			{!presentationMode && (
+				<View
+					className={`p-2 flex-row justify-between items-center ${
+						isConnected
+							? "bg-green-700"
 							: isReconnecting
-								? `Reconnecting (${reconnectAttempt}/10)${nextReconnectIn > 0 ? ` in ${nextReconnectIn}s` : "..."}`
-								: "Disconnected"}
+								? "bg-yellow-700"
+								: "bg-red-700"
+					}`}
+				>
+					<View className="flex-1">
+						<Text className="text-white text-xs">
+							{isConnected
+								? `Connected to session ${sessionId.slice(0, 8)}${serverRestarted ? " - server restarted, session restored" : ""}`
+								: isReconnecting
+									? `Reconnecting (${reconnectAttempt}/10)${nextReconnectIn > 0 && !reduced_motion ? ` in ${nextReconnectIn}s` : "..."}`
+									: "Disconnected"}
+						</Text>
+						{showHeatmap && heatmap && (
+							<Text className="text-white text-xs">
+								{`Heatmap: ${heatmap.attributes.diffs} diffs, ${heatmap.attributes.keyframes} keyframes in the last ${heatmap.attributes.window_seconds}s`}
+							</Text>
+						)}
+						{isReconnecting && (
+							<TouchableOpacity
+								onPress={reconnect}
+								className="bg-white bg-opacity-20 px-2 py-1 rounded mt-1 self-start"
+							>
+								<Text className="text-white text-xs">Reconnect Now</Text>
+							</TouchableOpacity>
+						)}
+					</View>
+					<View className="flex-row items-center">
+						<DarkLightToggle />
+						<ThemeSelector />
+						<PresentButton />
+						{!url && <CopyScreenButton sessionId={sessionId} />}
+						{!url && (
+							<HeatmapButton
+								active={showHeatmap}
+								onPress={() => setShowHeatmap((shown) => !shown)}
+							/>
+						)}
+					</View>
+				</View>
+			)}
+
+			{serverAlert !== null && (
+				<View
+					className="p-2 flex-row items-center bg-red-800"
+					accessibilityRole="alert"
+				>
+					<Text className="flex-1 text-white text-sm">⚠️ {serverAlert}</Text>
+					<TouchableOpacity
+						onPress={() => setServerAlert(null)}
+						className="bg-white bg-opacity-20 px-2 py-1 rounded ml-2"
+					>
+						<Text className="text-white text-xs">Dismiss</Text>
+					</TouchableOpacity>
+				</View>
+			)}
+
+			{inputLagging && (
+				<View className="p-2 bg-yellow-700" accessibilityRole="alert">
+					<Text className="text-white text-sm">
+						⏳ Input is lagging: the agent hasn't read what was typed yet
 					</Text>
-					{isReconnecting && (
-						<TouchableOpacity
-							onPress={reconnect}
-							className="bg-white bg-opacity-20 px-2 py-1 rounded mt-1 self-start"
-						>
-							<Text className="text-white text-xs">Reconnect Now</Text>
-						</TouchableOpacity>
-					)}
 				</View>
-				<View className="flex-row items-center">
-					<DarkLightToggle />
-					<ThemeSelector />
+			)}
+
+			{waitingPrompt !== null && (
+				<View className="p-2 bg-amber-500" accessibilityRole="alert">
+					<Text className="text-black text-sm font-semibold">
+						⏸ Waiting for approval: {waitingPrompt.prompt}
+					</Text>
 				</View>
-			</View>
+			)}
+
+			{inputControl !== null && (
+				<InputControlBar
+					control={inputControl}
+					onRequest={() => sendControlMessage("request_control")}
+					onRelease={() => sendControlMessage("release_control")}
+				/>
+			)}
 
 			{/* Terminal grid container - constrain ScrollView size */}
 			<TerminalBackground>
@@ -543,7 +979,31 @@ export default function Terminal({ sessionId }: TerminalProps) {
 			</TerminalBackground>
 
 			{/* Input area */}
-			<TerminalInput onSubmit={handleInputSubmit} />
+			{presentationMode ? (
+				<TouchableOpacity
+					onPress={() => setPresentationMode(false)}
+					className="absolute top-2 right-2 px-2 py-1 rounded opacity-30 hover:opacity-100"
+					accessibilityLabel="Exit presentation mode"
+				>
+					<Text className="text-white text-xs">Esc to exit</Text>
+				</TouchableOpacity>
+			) : (
+				<TerminalInput
+					onSubmit={handleInputSubmit}
+					onRetract={retractMessage.mutateAsync}
+				/>
+			)}
+
+			{pendingPaste !== null && (
+				<PasteConfirmDialog
+					text={pendingPaste}
+					settings={pasteSettings}
+					saving={savePasteFile.isPending}
+					onPaste={confirmPaste}
+					onPasteAsFile={pasteAsFile}
+					onCancel={() => setPendingPaste(null)}
+				/>
+			)}
 		</View>
 	);
 }
@@ -17,6 +17,15 @@ export const TerminalCell = memo(({ row, col }: TerminalCellProps) => {
 	const cell = useTerminalStore((state) => state.cells.get(cellKey));
 	const resolveColor = useTerminalStore((state) => state.resolveColor);
 	const theme = useTerminalStore((state) => state.theme);
+	const presentationMode = useTerminalStore((state) => state.presentationMode);
+	// This cell's changes relative to the busiest cell, while the heatmap is shown
+	const heat = useTerminalStore((state) => {
+		const heatmap = state.heatmap;
+		if (!heatmap || heatmap.max === 0 || col >= heatmap.cols) {
+			return 0;
+		}
+		return (heatmap.counts[row * heatmap.cols + col] ?? 0) / heatmap.max;
+	});
 
 	const char = cell?.char || " ";
 
@@ -31,6 +40,9 @@ export const TerminalCell = memo(({ row, col }: TerminalCellProps) => {
 		if (cell?.has_cursor) {
 			return theme.cursor;
 		}
+		if (heat > 0) {
+			return `rgba(255, 64, 0, ${0.15 + heat * 0.6})`;
+		}
 		if (cell?.reverse) {
 			return resolveColor(cell?.fg_color, false);
 		}
@@ -51,9 +63,14 @@ export const TerminalCell = memo(({ row, col }: TerminalCellProps) => {
 		backgroundColor: getBackgroundColor(),
 	};
 
+	// Presentation mode enlarges the text so it stays readable over screen sharing
+	const sizeClasses = presentationMode
+		? "text-xl leading-7 min-w-[14px]"
+		: "text-sm leading-5 min-w-[9px]";
+
 	return (
 		<Text
-			className={`font-mono text-sm leading-5 text-center min-w-[9px] ${dynamicClasses}`}
+			className={`font-mono text-center ${sizeClasses} ${dynamicClasses}`}
 			style={dynamicStyle}
 		>
 			{char}
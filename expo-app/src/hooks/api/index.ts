@@ -26,6 +26,22 @@ export {
 	useHasGitChanges,
 	useRefreshGit,
 } from "./useGit";
+// Notification center
+export {
+	useDoNotDisturb,
+	useMarkNotificationRead,
+	useNotifications,
+	useSetDoNotDisturb,
+} from "./useNotifications";
+// Paste safeguards
+export { usePasteSettings, useSavePasteFile } from "./usePaste";
+// Preferences hooks
+export {
+	usePreferences,
+	useRecent,
+	useToggleFavorite,
+	useUpdatePreferences,
+} from "./usePreferences";
 // Projects hooks
 export {
 	useCreateProject,
@@ -37,11 +53,19 @@ export {
 	useRefetchProjects,
 } from "./useProjects";
 export {
+	useCopyScreenText,
 	useCreateSession,
 	useDeleteSession,
 	useRefetchSessions,
+	useRetractMessage,
 	useSession,
+	useSessionCommits,
 	useSessionExists,
+	useSessionHeatmap,
+	useSessionLineage,
+	useSessionOptions,
 	useSessions,
 	useSessionsCount,
+	useSessionTimeseries,
+	useSetSessionNote,
 } from "./useSessions";
@@ -151,15 +151,9 @@ export const useGitSummary = (sessionId: string, enabled = true) => {
 	}
 
 	const summary = {
-		totalFiles: gitDiff.files.length,
-		totalAdditions: gitDiff.files.reduce(
-			(sum, file) => sum + file.additions,
-			0,
-		),
-		totalDeletions: gitDiff.files.reduce(
-			(sum, file) => sum + file.deletions,
-			0,
-		),
+		totalFiles: gitDiff.summary.files,
+		totalAdditions: gitDiff.summary.additions,
+		totalDeletions: gitDiff.summary.deletions,
 		filesByStatus: gitDiff.files.reduce(
 			(acc, file) => {
 				acc[file.status] = (acc[file.status] || 0) + 1;
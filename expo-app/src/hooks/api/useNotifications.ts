@@ -0,0 +1,61 @@
+import { useMutation, useQuery } from "@tanstack/react-query";
+import { api, handleApiError } from "../../lib/apiClient";
+import { queryClient, queryKeys } from "../../lib/queryClient";
+import type { SetDoNotDisturbRequest } from "../../types/bindings";
+
+// Hook to read stored notifications; polls so new events show up in the bell
+export const useNotifications = () => {
+	return useQuery({
+		queryKey: queryKeys.notifications(),
+		queryFn: () => api.notifications.list(),
+		refetchInterval: 10000,
+		meta: {
+			errorMessage: "Failed to fetch notifications",
+		},
+	});
+};
+
+// Hook to mark a notification read
+export const useMarkNotificationRead = () => {
+	return useMutation({
+		mutationFn: (id: string) => api.notifications.markRead(id),
+		onSuccess: (notifications) => {
+			queryClient.setQueryData(queryKeys.notifications(), notifications);
+		},
+		onError: (error) => {
+			console.error("Failed to mark notification read:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to mark notification read",
+		},
+	});
+};
+
+// Hook to read whether alerts are switched off by hand
+export const useDoNotDisturb = () => {
+	return useQuery({
+		queryKey: queryKeys.doNotDisturb(),
+		queryFn: () => api.notifications.dnd(),
+		refetchInterval: 60000,
+		meta: {
+			errorMessage: "Failed to fetch do-not-disturb",
+		},
+	});
+};
+
+// Hook to switch do-not-disturb on, optionally for some minutes, or off
+export const useSetDoNotDisturb = () => {
+	return useMutation({
+		mutationFn: (request: SetDoNotDisturbRequest) =>
+			api.notifications.setDnd(request),
+		onSuccess: (dnd) => {
+			queryClient.setQueryData(queryKeys.doNotDisturb(), dnd);
+		},
+		onError: (error) => {
+			console.error("Failed to update do-not-disturb:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to update do-not-disturb",
+		},
+	});
+};
@@ -0,0 +1,41 @@
+import { useMutation, useQuery } from "@tanstack/react-query";
+import { api, handleApiError } from "../../lib/apiClient";
+import { queryKeys } from "../../lib/queryClient";
+import type { PasteConfig } from "../../types/bindings";
+
+// Matches the server defaults until the real settings have loaded
+const DEFAULT_PASTE_SETTINGS: PasteConfig = {
+	confirm_lines: 100,
+	confirm_bytes: 8 * 1024,
+	offer_file: true,
+	max_bytes: 1024 * 1024,
+};
+
+// Hook to read the paste confirmation thresholds and the server's paste limit
+export const usePasteSettings = (): PasteConfig => {
+	const { data } = useQuery({
+		queryKey: queryKeys.pasteSettings(),
+		queryFn: () => api.pasteSettings.get(),
+		staleTime: 5 * 60 * 1000,
+		meta: {
+			errorMessage: "Failed to fetch paste settings",
+		},
+	});
+	return data?.attributes ?? DEFAULT_PASTE_SETTINGS;
+};
+
+// Hook to save a large paste on the server; resolves to the file's path there
+export const useSavePasteFile = (sessionId: string) => {
+	return useMutation({
+		mutationFn: async (text: string) => {
+			const file = await api.sessions.pasteFile(sessionId, text);
+			return file.attributes.path;
+		},
+		onError: (error) => {
+			console.error("Failed to save paste:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to save paste",
+		},
+	});
+};
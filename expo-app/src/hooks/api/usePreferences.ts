@@ -0,0 +1,70 @@
+import { useMutation, useQuery } from "@tanstack/react-query";
+import { api, handleApiError } from "../../lib/apiClient";
+import { queryClient, queryKeys } from "../../lib/queryClient";
+import type { UiPreferences, UpdatePreferencesRequest } from "../../types/bindings";
+
+const DEFAULT_PREFERENCES: UiPreferences = {
+	high_contrast: false,
+	reduced_motion: false,
+	favorite_sessions: [],
+	favorite_projects: [],
+};
+
+// Hook to read display preferences; falls back to defaults until loaded
+export const usePreferences = (): UiPreferences => {
+	const { data } = useQuery({
+		queryKey: queryKeys.preferences(),
+		queryFn: () => api.preferences.get(),
+		staleTime: 5 * 60 * 1000,
+		meta: {
+			errorMessage: "Failed to fetch preferences",
+		},
+	});
+	return data?.attributes ?? DEFAULT_PREFERENCES;
+};
+
+// Hook to change display preferences
+export const useUpdatePreferences = () => {
+	return useMutation({
+		mutationFn: (update: UpdatePreferencesRequest) =>
+			api.preferences.update(update),
+		onSuccess: (preferences) => {
+			queryClient.setQueryData(queryKeys.preferences(), preferences);
+			// Starring changes the quick-switch list
+			queryClient.invalidateQueries({ queryKey: queryKeys.recent() });
+		},
+		onError: (error) => {
+			console.error("Failed to update preferences:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to update preferences",
+		},
+	});
+};
+
+// Hook to star or unstar a session or project
+export const useToggleFavorite = () => {
+	const preferences = usePreferences();
+	const updatePreferences = useUpdatePreferences();
+
+	return (kind: "session" | "project", id: string) => {
+		const key = kind === "session" ? "favorite_sessions" : "favorite_projects";
+		const current = preferences[key];
+		updatePreferences.mutate({
+			[key]: current.includes(id)
+				? current.filter((favorite) => favorite !== id)
+				: [...current, id],
+		});
+	};
+};
+
+// Hook to read starred and recently attached sessions
+export const useRecent = () => {
+	return useQuery({
+		queryKey: queryKeys.recent(),
+		queryFn: () => api.recent.get(),
+		meta: {
+			errorMessage: "Failed to fetch recent sessions",
+		},
+	});
+};
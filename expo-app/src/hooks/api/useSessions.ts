@@ -8,7 +8,9 @@ import {
 import type {
 	CreateSessionRequest,
 	Session,
+	UseSessionHeatmapOptions,
 	UseSessionsOptions,
+	UseSessionTimeseriesOptions,
 } from "../../types/api";
 
 // Hook to fetch all sessions
@@ -51,6 +53,77 @@ export const useSession = (sessionId: string, enabled = true) => {
 	});
 };
 
+// Hook to fetch per-minute I/O activity for a running session
+export const useSessionTimeseries = (options: UseSessionTimeseriesOptions) => {
+	const { sessionId, refetchInterval = 15000, enabled = true } = options;
+
+	return useQuery({
+		queryKey: queryKeys.sessionTimeseries(sessionId),
+		queryFn: () => api.sessions.timeseries(sessionId),
+		enabled: enabled && !!sessionId,
+		refetchInterval: enabled ? refetchInterval : false,
+		// Buckets are per minute, so slightly stale data is fine
+		staleTime: 10000,
+		meta: {
+			errorMessage: `Failed to fetch activity for session ${sessionId}`,
+		},
+	});
+};
+
+// Hook to poll the repaint heatmap of a session (debug overlay)
+export const useSessionHeatmap = (options: UseSessionHeatmapOptions) => {
+	const { sessionId, refetchInterval = 2000, enabled = true } = options;
+
+	return useQuery({
+		queryKey: queryKeys.sessionHeatmap(sessionId),
+		queryFn: () => api.sessions.heatmap(sessionId),
+		enabled: enabled && !!sessionId,
+		refetchInterval: enabled ? refetchInterval : false,
+		meta: {
+			errorMessage: `Failed to fetch heatmap for session ${sessionId}`,
+		},
+	});
+};
+
+// Hook to fetch the commits a session's agent announced
+export const useSessionCommits = (sessionId: string, enabled = true) => {
+	return useQuery({
+		queryKey: queryKeys.sessionCommits(sessionId),
+		queryFn: () => api.sessions.commits(sessionId),
+		enabled: enabled && !!sessionId,
+		refetchInterval: enabled ? 10000 : false,
+		meta: {
+			errorMessage: `Failed to fetch commits for session ${sessionId}`,
+		},
+	});
+};
+
+// Hook to fetch the sessions a session was cloned or retried from
+export const useSessionLineage = (sessionId: string, enabled = true) => {
+	return useQuery({
+		queryKey: queryKeys.sessionLineage(sessionId),
+		queryFn: () => api.sessions.lineage(sessionId),
+		enabled: enabled && !!sessionId,
+		// Links are set when a session starts and never change
+		staleTime: Number.POSITIVE_INFINITY,
+		meta: {
+			errorMessage: `Failed to fetch lineage of session ${sessionId}`,
+		},
+	});
+};
+
+// Hook to fetch agents, templates and projects for the "New Session" form
+export const useSessionOptions = (enabled = true) => {
+	return useQuery({
+		queryKey: queryKeys.sessionOptions(),
+		queryFn: () => api.sessions.options(),
+		enabled,
+		meta: {
+			errorMessage: "Failed to fetch session options",
+		},
+	});
+};
+
 // Hook to create a new session
 export const useCreateSession = () => {
 	return useMutation({
@@ -62,6 +135,10 @@ export const useCreateSession = () => {
 
 			// Add the new session to the cache
 			queryClient.setQueryData(queryKeys.session(newSession.id), newSession);
+
+			// Path-based sessions may have added a project
+			invalidateQueries.projects();
+			queryClient.invalidateQueries({ queryKey: queryKeys.sessionOptions() });
 		},
 		onError: (error) => {
 			console.error("Failed to create session:", handleApiError(error));
@@ -95,6 +172,60 @@ export const useDeleteSession = () => {
 	});
 };
 
+// Hook to interrupt the agent after an accidental send; resolves to the
+// retracted message so it can go back into the compose box
+export const useRetractMessage = (sessionId: string) => {
+	return useMutation({
+		mutationFn: async () => {
+			const retraction = await api.sessions.retract(sessionId);
+			return retraction.attributes.text;
+		},
+		onError: (error) => {
+			console.error("Failed to retract message:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to retract message",
+		},
+	});
+};
+
+// Hook to copy the text on a session's screen to the clipboard; resolves to
+// the number of lines copied
+export const useCopyScreenText = (sessionId: string) => {
+	return useMutation({
+		mutationFn: async () => {
+			const screen = await api.sessions.screenText(sessionId);
+			const text = screen.attributes.text.replace(/\n+$/, "");
+			await navigator.clipboard.writeText(text);
+			return text.split("\n").length;
+		},
+		onError: (error) => {
+			console.error("Failed to copy screen:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to copy the screen",
+		},
+	});
+};
+
+// Hook to leave a handover note on a session, or clear it with null
+export const useSetSessionNote = (sessionId: string) => {
+	return useMutation({
+		mutationFn: (note: string | null) =>
+			api.sessions.setNote(sessionId, note),
+		onSuccess: (session) => {
+			queryClient.setQueryData(queryKeys.session(sessionId), session);
+			invalidateQueries.sessions();
+		},
+		onError: (error) => {
+			console.error("Failed to save note:", handleApiError(error));
+		},
+		meta: {
+			errorMessage: "Failed to save note",
+		},
+	});
+};
+
 // Hook to refetch sessions manually
 export const useRefetchSessions = () => {
 	return () => {
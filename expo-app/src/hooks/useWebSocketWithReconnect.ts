@@ -2,6 +2,8 @@ import { useCallback, useEffect, useRef, useState } from "react";
 
 export interface WebSocketConfig {
 	url: string;
+	// Used instead of url when reconnecting, e.g. to ask the server to rebind
+	reconnectUrl?: string;
 	protocols?: string | string[];
 	maxReconnectAttempts?: number;
 	baseDelay?: number; // Base delay in milliseconds
@@ -32,6 +34,7 @@ export function useWebSocketWithReconnect(
 } {
 	const {
 		url,
+		reconnectUrl,
 		protocols,
 		maxReconnectAttempts = 5,
 		baseDelay = 5000,
@@ -100,136 +103,141 @@ export function useWebSocketWithReconnect(
 	}, []);
 
 	// Create WebSocket connection
-	const createConnection = useCallback(() => {
-		console.log(`[WebSocket] Connecting to ${url}...`);
+	const createConnection = useCallback(
+		(isReconnect = false) => {
+			const target = isReconnect && reconnectUrl ? reconnectUrl : url;
+			console.log(`[WebSocket] Connecting to ${target}...`);
 
-		// Close existing socket if it exists
-		if (socketRef.current) {
-			socketRef.current.close();
-			socketRef.current = null;
-		}
+			// Close existing socket if it exists
+			if (socketRef.current) {
+				socketRef.current.close();
+				socketRef.current = null;
+			}
+
+			try {
+				const socket = new WebSocket(target, protocols);
+				socketRef.current = socket;
+
+				socket.onopen = (_event) => {
+					console.log("[WebSocket] Connected");
+					reconnectAttemptRef.current = 0;
+					clearReconnectTimer();
+
+					setState((prev) => ({
+						...prev,
+						socket,
+						isConnected: true,
+						isReconnecting: false,
+						reconnectAttempt: 0,
+						nextReconnectIn: 0,
+						error: null,
+					}));
 
-		try {
-			const socket = new WebSocket(url, protocols);
-			socketRef.current = socket;
-
-			socket.onopen = (_event) => {
-				console.log("[WebSocket] Connected");
-				reconnectAttemptRef.current = 0;
-				clearReconnectTimer();
-
-				setState((prev) => ({
-					...prev,
-					socket,
-					isConnected: true,
-					isReconnecting: false,
-					reconnectAttempt: 0,
-					nextReconnectIn: 0,
-					error: null,
-				}));
-
-				callbacksRef.current.onOpen?.();
-			};
-
-			socket.onmessage = (event) => {
-				callbacksRef.current.onMessage?.(event);
-			};
-
-			socket.onclose = (event) => {
-				console.log("[WebSocket] Disconnected", event.code, event.reason);
-
-				setState((prev) => ({
-					...prev,
-					socket: null,
-					isConnected: false,
-				}));
-
-				callbacksRef.current.onClose?.(event);
-
-				// Only attempt reconnection if it wasn't a clean close and we should reconnect
-				if (
-					shouldReconnectRef.current &&
-					event.code !== 1000 &&
-					reconnectAttemptRef.current < maxReconnectAttempts
-				) {
-					// Schedule reconnection with exponential backoff
-					const attempt = reconnectAttemptRef.current;
-					const delay = calculateDelay(attempt);
-
-					console.log(
-						`[WebSocket] Scheduling reconnect attempt ${attempt + 1}/${maxReconnectAttempts} in ${Math.round(delay / 1000)}s`,
-					);
-
-					reconnectAttemptRef.current++;
+					callbacksRef.current.onOpen?.();
+				};
+
+				socket.onmessage = (event) => {
+					callbacksRef.current.onMessage?.(event);
+				};
+
+				socket.onclose = (event) => {
+					console.log("[WebSocket] Disconnected", event.code, event.reason);
 
 					setState((prev) => ({
 						...prev,
-						isReconnecting: true,
-						reconnectAttempt: attempt + 1,
-						nextReconnectIn: Math.round(delay / 1000),
+						socket: null,
+						isConnected: false,
 					}));
 
-					callbacksRef.current.onReconnectAttempt?.(attempt + 1, delay);
+					callbacksRef.current.onClose?.(event);
+
+					// Only attempt reconnection if it wasn't a clean close and we should reconnect
+					if (
+						shouldReconnectRef.current &&
+						event.code !== 1000 &&
+						reconnectAttemptRef.current < maxReconnectAttempts
+					) {
+						// Schedule reconnection with exponential backoff
+						const attempt = reconnectAttemptRef.current;
+						const delay = calculateDelay(attempt);
+
+						console.log(
+							`[WebSocket] Scheduling reconnect attempt ${attempt + 1}/${maxReconnectAttempts} in ${Math.round(delay / 1000)}s`,
+						);
+
+						reconnectAttemptRef.current++;
 
-					// Start countdown
-					let remainingTime = Math.round(delay / 1000);
-					countdownIntervalRef.current = setInterval(() => {
-						remainingTime--;
 						setState((prev) => ({
 							...prev,
-							nextReconnectIn: remainingTime,
+							isReconnecting: true,
+							reconnectAttempt: attempt + 1,
+							nextReconnectIn: Math.round(delay / 1000),
 						}));
 
-						if (remainingTime <= 0) {
+						callbacksRef.current.onReconnectAttempt?.(attempt + 1, delay);
+
+						// Start countdown
+						let remainingTime = Math.round(delay / 1000);
+						countdownIntervalRef.current = setInterval(() => {
+							remainingTime--;
+							setState((prev) => ({
+								...prev,
+								nextReconnectIn: remainingTime,
+							}));
+
+							if (remainingTime <= 0) {
+								if (countdownIntervalRef.current) {
+									clearInterval(countdownIntervalRef.current);
+									countdownIntervalRef.current = null;
+								}
+							}
+						}, 1000);
+
+						// Schedule actual reconnection
+						reconnectTimeoutRef.current = setTimeout(() => {
+							// Clear the countdown interval when reconnecting
 							if (countdownIntervalRef.current) {
 								clearInterval(countdownIntervalRef.current);
 								countdownIntervalRef.current = null;
 							}
-						}
-					}, 1000);
-
-					// Schedule actual reconnection
-					reconnectTimeoutRef.current = setTimeout(() => {
-						// Clear the countdown interval when reconnecting
-						if (countdownIntervalRef.current) {
-							clearInterval(countdownIntervalRef.current);
-							countdownIntervalRef.current = null;
-						}
-
-						if (shouldReconnectRef.current) {
-							createConnection();
-						}
-					}, delay);
-				} else if (reconnectAttemptRef.current >= maxReconnectAttempts) {
-					console.log("[WebSocket] Max reconnect attempts reached");
+
+							if (shouldReconnectRef.current) {
+								createConnection(true);
+							}
+						}, delay);
+					} else if (reconnectAttemptRef.current >= maxReconnectAttempts) {
+						console.log("[WebSocket] Max reconnect attempts reached");
+						setState((prev) => ({
+							...prev,
+							isReconnecting: false,
+							nextReconnectIn: 0,
+						}));
+					}
+				};
+
+				socket.onerror = (event) => {
+					console.error("[WebSocket] Error:", event);
+
 					setState((prev) => ({
 						...prev,
-						isReconnecting: false,
-						nextReconnectIn: 0,
+						error: event,
 					}));
-				}
-			};
-
-			socket.onerror = (event) => {
-				console.error("[WebSocket] Error:", event);
-
-				setState((prev) => ({
-					...prev,
-					error: event,
-				}));
 
-				callbacksRef.current.onError?.(event);
-			};
-		} catch (error) {
-			console.error("[WebSocket] Failed to create socket:", error);
-		}
-	}, [
-		url,
-		protocols,
-		maxReconnectAttempts,
-		clearReconnectTimer,
-		calculateDelay,
-	]);
+					callbacksRef.current.onError?.(event);
+				};
+			} catch (error) {
+				console.error("[WebSocket] Failed to create socket:", error);
+			}
+		},
+		[
+			url,
+			reconnectUrl,
+			protocols,
+			maxReconnectAttempts,
+			clearReconnectTimer,
+			calculateDelay,
+		],
+	);
 
 	// Send message
 	const send = useCallback((data: string) => {
@@ -277,7 +285,7 @@ export function useWebSocketWithReconnect(
 			nextReconnectIn: 0,
 		}));
 
-		createConnection();
+		createConnection(true);
 	}, [createConnection, clearReconnectTimer]);
 
 	// Initial connection
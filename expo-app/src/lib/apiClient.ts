@@ -1,14 +1,49 @@
 import type {
+	RenderCrashReport,
+	SelectionShape,
+	SetDoNotDisturbRequest,
+	UpdatePreferencesRequest,
+} from "../types/bindings";
+import type {
+	AgentResource,
+	CommitResource,
+	ConversationResource,
+	CreateProjectRequest,
 	CreateSessionRequest,
+	DoNotDisturbResource,
 	GitDiff,
 	GitFileDiff,
 	GitStatus,
+	LineageEntryResource,
+	NotificationsResource,
+	PasteFileResource,
+	PasteSettingsResource,
+	Preferences,
 	Project,
+	RecentResource,
+	RetractionResource,
+	ScreenTextResource,
 	Session,
+	SessionHeatmapResource,
+	SessionOptionsResource,
+	SessionTimeseries,
+	SessionUsageResource,
+	SessionWorkspaceResource,
+	TranscriptResource,
+	UsageSummaryResource,
+	UserResource,
 } from "../types/api";
 
+// Path prefix injected by the server when codemux is mounted under one, e.g. "/codemux"
+const BASE_PATH =
+	(typeof window !== "undefined" && window.__CODEMUX_BASE_PATH__) || "";
+
 // API configuration
 const getBaseUrl = (): string => {
+	// Mounted inside another service: the API lives next to the page
+	if (BASE_PATH) {
+		return `${window.location.origin}${BASE_PATH}`;
+	}
 	// Use port 8765 which is the default port for quick mode
 	// Check if we're running on the Expo dev server (port 8081)
 	if (typeof window !== "undefined" && window.location.port === "8081") {
@@ -25,6 +60,44 @@ const getBaseUrl = (): string => {
 
 const BASE_URL = getBaseUrl();
 
+// Token the server requires on its API and WebSockets. It comes in the
+// address the server prints (`?token=...`), is kept for later visits and
+// dropped from the address bar.
+const TOKEN_KEY = "codemux-token";
+
+const getToken = (): string | null => {
+	if (typeof window === "undefined") {
+		return null;
+	}
+	const params = new URLSearchParams(window.location.search);
+	const token = params.get("token");
+	if (!token) {
+		return window.localStorage?.getItem(TOKEN_KEY) ?? null;
+	}
+	window.localStorage?.setItem(TOKEN_KEY, token);
+	params.delete("token");
+	const search = params.toString();
+	window.history.replaceState(
+		null,
+		"",
+		`${window.location.pathname}${search ? `?${search}` : ""}${window.location.hash}`,
+	);
+	return token;
+};
+
+const TOKEN = getToken();
+
+// WebSocket URL for a server path such as `/ws/<session-id>`; browsers can't
+// send headers with a WebSocket, so the token goes in the query
+export const wsUrl = (path: string): string => {
+	const url = `${BASE_URL.replace(/^http/, "ws")}${path}`;
+	if (!TOKEN) {
+		return url;
+	}
+	const separator = path.includes("?") ? "&" : "?";
+	return `${url}${separator}token=${encodeURIComponent(TOKEN)}`;
+};
+
 // Custom error class for API errors
 export class ApiClientError extends Error {
 	constructor(
@@ -55,6 +128,7 @@ class ApiClient {
 		const config: RequestInit = {
 			headers: {
 				"Content-Type": "application/json",
+				...(TOKEN ? { Authorization: `Bearer ${TOKEN}` } : {}),
 				...options.headers,
 			},
 			...options,
@@ -64,9 +138,32 @@ class ApiClient {
 			const response = await fetch(url, config);
 
 			if (!response.ok) {
-				const _errorText = await response.text().catch(() => "Unknown error");
+				const errorText = await response.text().catch(() => "Unknown error");
+				// Prefer the server's JSON:API error detail when there is one
+				let detail: string | undefined;
+				let title: string | undefined;
+				try {
+					const error = JSON.parse(errorText).errors?.[0];
+					detail = error?.detail;
+					title = error?.title;
+				} catch {
+					detail = undefined;
+				}
+				// Signing in through the server's identity provider (`[web.oidc]`)
+				// replaces the token; come back to this page afterwards
+				if (
+					response.status === 401 &&
+					title === "Login Required" &&
+					typeof window !== "undefined"
+				) {
+					const returnTo = `${window.location.pathname}${window.location.search}`;
+					window.location.assign(
+						`${this.baseUrl}/auth/login?return_to=${encodeURIComponent(returnTo)}`,
+					);
+				}
 				throw new ApiClientError(
-					`API request failed: ${response.status} ${response.statusText}`,
+					detail ??
+						`API request failed: ${response.status} ${response.statusText}`,
 					response.status,
 					response.statusText,
 					url,
@@ -155,6 +252,14 @@ class ApiClient {
 		});
 	}
 
+	// PATCH request
+	async patch<T>(endpoint: string, data?: unknown): Promise<T> {
+		return this.request<T>(endpoint, {
+			method: "PATCH",
+			body: data ? JSON.stringify(data) : undefined,
+		});
+	}
+
 	// DELETE request
 	async delete<T>(endpoint: string): Promise<T> {
 		return this.request<T>(endpoint, { method: "DELETE" });
@@ -172,36 +277,153 @@ export const api = {
 		get: (id: string): Promise<Session> => apiClient.get(`/api/sessions/${id}`),
 		create: (data: CreateSessionRequest): Promise<Session> =>
 			apiClient.post("/api/sessions", data),
+		options: (): Promise<SessionOptionsResource> =>
+			apiClient.get("/api/session-options"),
 		delete: (id: string): Promise<void> =>
 			apiClient.delete(`/api/sessions/${id}`),
+		timeseries: (id: string): Promise<SessionTimeseries> =>
+			apiClient.get(`/api/sessions/${id}/metrics/timeseries`),
+		heatmap: (id: string): Promise<SessionHeatmapResource> =>
+			apiClient.get(`/api/sessions/${id}/metrics/heatmap`),
+		commits: (id: string): Promise<CommitResource[]> =>
+			apiClient.get(`/api/sessions/${id}/commits`),
+		lineage: (id: string): Promise<LineageEntryResource[]> =>
+			apiClient.get(`/api/sessions/${id}/lineage`),
+		reportRenderCrash: (
+			id: string,
+			report: RenderCrashReport,
+		): Promise<{ type: "crash-dump"; id: string }> =>
+			apiClient.post(`/api/sessions/${id}/crash-dumps`, report),
+		pasteFile: (id: string, text: string): Promise<PasteFileResource> =>
+			apiClient.post(`/api/sessions/${id}/paste-file`, { text }),
+		retract: (id: string, restore = false): Promise<RetractionResource> =>
+			apiClient.post(`/api/sessions/${id}/retract`, { restore }),
+		setNote: (id: string, note: string | null): Promise<Session> =>
+			apiClient.put(`/api/sessions/${id}/note`, { note }),
+		usage: (id: string): Promise<SessionUsageResource> =>
+			apiClient.get(`/api/sessions/${id}/usage`),
+		workspace: (id: string, messages = 50): Promise<SessionWorkspaceResource> =>
+			apiClient.get(`/api/sessions/${id}/workspace?messages=${messages}`),
+		// The whole screen without a region
+		screenText: (
+			id: string,
+			region: {
+				top?: number;
+				left?: number;
+				bottom?: number;
+				right?: number;
+				shape?: SelectionShape;
+			} = {},
+		): Promise<ScreenTextResource> => {
+			const params = new URLSearchParams();
+			for (const [key, value] of Object.entries(region)) {
+				if (value !== undefined) {
+					params.set(key, String(value));
+				}
+			}
+			const query = params.toString();
+			return apiClient.get(
+				`/api/sessions/${id}/screen/text${query ? `?${query}` : ""}`,
+			);
+		},
+	},
+
+	// Who is signed in through the server's identity provider
+	auth: {
+		me: (): Promise<UserResource> => apiClient.get("/auth/me"),
+		logout: (): Promise<void> => apiClient.post("/auth/logout"),
+	},
+
+	// Whitelisted agents and their version checks
+	agents: {
+		list: (): Promise<AgentResource[]> => apiClient.get("/api/agents"),
+	},
+
+	// Paste confirmation thresholds and limit
+	pasteSettings: {
+		get: (): Promise<PasteSettingsResource> =>
+			apiClient.get("/api/paste-settings"),
+	},
+
+	// Display preferences
+	preferences: {
+		get: (): Promise<Preferences> => apiClient.get("/api/preferences"),
+		update: (data: UpdatePreferencesRequest): Promise<Preferences> =>
+			apiClient.patch("/api/preferences", data),
+	},
+
+	// Starred and recently attached sessions
+	recent: {
+		get: (): Promise<RecentResource> => apiClient.get("/api/recent"),
+	},
+
+	// Tokens and cost of every running session, per project and overall
+	usage: {
+		get: (): Promise<UsageSummaryResource> => apiClient.get("/api/usage"),
+	},
+
+	// Notification center
+	notifications: {
+		list: (): Promise<NotificationsResource> =>
+			apiClient.get("/api/notifications"),
+		markRead: (id: string): Promise<NotificationsResource> =>
+			apiClient.post(`/api/notifications/${id}/read`),
+		dnd: (): Promise<DoNotDisturbResource> =>
+			apiClient.get("/api/notifications/dnd"),
+		setDnd: (data: SetDoNotDisturbRequest): Promise<DoNotDisturbResource> =>
+			apiClient.put("/api/notifications/dnd", data),
 	},
 
 	// Projects
 	projects: {
 		list: (): Promise<Project[]> => apiClient.get("/api/projects"),
 		get: (id: string): Promise<Project> => apiClient.get(`/api/projects/${id}`),
-		create: (data: { name: string; path: string }): Promise<Project> =>
+		create: (data: CreateProjectRequest): Promise<Project> =>
 			apiClient.post("/api/projects", data),
 		delete: (id: string): Promise<void> =>
 			apiClient.delete(`/api/projects/${id}`),
+		// Past Claude conversations, newest first
+		conversations: (id: string): Promise<ConversationResource[]> =>
+			apiClient.get(`/api/projects/${id}/conversations`),
+		conversationMessages: (
+			id: string,
+			conversationId: string,
+		): Promise<TranscriptResource> =>
+			apiClient.get(
+				`/api/projects/${id}/conversations/${conversationId}/messages`,
+			),
 	},
 
 	// Git operations
 	git: {
 		status: (sessionId: string): Promise<GitStatus> =>
 			apiClient.get(`/api/sessions/${sessionId}/git/status`),
-		diff: (sessionId: string): Promise<GitDiff> =>
-			apiClient.get(`/api/sessions/${sessionId}/git/diff`),
+		diff: (sessionId: string, staged?: boolean): Promise<GitDiff> =>
+			apiClient.get(
+				`/api/sessions/${sessionId}/git/diff${stagedQuery(staged)}`,
+			),
 		fileDiff: (sessionId: string, filePath: string): Promise<GitFileDiff> =>
 			apiClient.get(
 				`/api/sessions/${sessionId}/git/diff/${encodeURIComponent(filePath)}`,
 			),
+		projectStatus: (projectId: string): Promise<GitStatus> =>
+			apiClient.get(`/api/projects/${projectId}/git/status`),
+		projectDiff: (projectId: string, staged?: boolean): Promise<GitDiff> =>
+			apiClient.get(`/api/projects/${projectId}/diff${stagedQuery(staged)}`),
 	},
 } as const;
 
+// `?staged=` for the git diff endpoints; both staged and unstaged changes without it
+function stagedQuery(staged?: boolean): string {
+	return staged === undefined ? "" : `?staged=${staged}`;
+}
+
 // Helper function to handle API errors in components
 export const handleApiError = (error: unknown): string => {
 	if (error instanceof ApiClientError) {
+		if (error.status === 401) {
+			return "Not authorized. Open the web interface from the address the server printed, it includes the token.";
+		}
 		if (error.status === 404) {
 			return "Resource not found";
 		}
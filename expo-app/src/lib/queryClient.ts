@@ -43,6 +43,20 @@ export const queryKeys = {
 	all: ["api"] as const,
 	sessions: () => [...queryKeys.all, "sessions"] as const,
 	session: (id: string) => [...queryKeys.sessions(), id] as const,
+	sessionOptions: () => [...queryKeys.all, "session-options"] as const,
+	sessionTimeseries: (id: string) =>
+		[...queryKeys.session(id), "timeseries"] as const,
+	sessionHeatmap: (id: string) =>
+		[...queryKeys.session(id), "heatmap"] as const,
+	sessionCommits: (id: string) =>
+		[...queryKeys.session(id), "commits"] as const,
+	sessionLineage: (id: string) =>
+		[...queryKeys.session(id), "lineage"] as const,
+	preferences: () => [...queryKeys.all, "preferences"] as const,
+	recent: () => [...queryKeys.all, "recent"] as const,
+	notifications: () => [...queryKeys.all, "notifications"] as const,
+	doNotDisturb: () => [...queryKeys.notifications(), "dnd"] as const,
+	pasteSettings: () => [...queryKeys.all, "paste-settings"] as const,
 	projects: () => [...queryKeys.all, "projects"] as const,
 	project: (id: string) => [...queryKeys.projects(), id] as const,
 	git: {
@@ -0,0 +1,79 @@
+import type { GridUpdateMessage, RenderCrashReport } from "../types/bindings";
+import { api } from "./apiClient";
+
+// Same history length the TUI keeps, so dumps from both renderers look alike
+const HISTORY_LIMIT = 64;
+
+interface RenderedGrid {
+	size: { rows: number; cols: number };
+	cursor: { row: number; col: number };
+}
+
+// Keeps recent grid updates and sends them to the server as a crash dump the
+// first time the rendered grid ends up in a state that can't be drawn correctly.
+export class RenderWatchdog {
+	private history: GridUpdateMessage[] = [];
+	private reported = false;
+
+	constructor(private readonly sessionId: string) {}
+
+	record(update: GridUpdateMessage) {
+		this.history.push(update);
+		if (this.history.length > HISTORY_LIMIT) {
+			this.history.shift();
+		}
+	}
+
+	// Check the grid after the latest recorded update has been applied
+	check(grid: RenderedGrid) {
+		if (this.reported) {
+			return;
+		}
+
+		const latest = this.history[this.history.length - 1];
+		const reason = latest ? findInconsistency(latest, grid) : null;
+		if (reason) {
+			this.reported = true;
+			void this.report(reason);
+		}
+	}
+
+	private async report(reason: string) {
+		const report: RenderCrashReport = {
+			source: "web",
+			reason,
+			updates: this.history,
+		};
+		try {
+			const dump = await api.sessions.reportRenderCrash(
+				this.sessionId,
+				report,
+			);
+			console.warn(`Render inconsistency (${reason}), crash dump ${dump.id}`);
+		} catch (error) {
+			console.error("Failed to upload render crash dump:", error);
+		}
+	}
+}
+
+function findInconsistency(
+	update: GridUpdateMessage,
+	{ size, cursor }: RenderedGrid,
+): string | null {
+	// A cursor column equal to the width is the pending-wrap position
+	if (cursor.row >= size.rows || cursor.col > size.cols) {
+		return `cursor (${cursor.row}, ${cursor.col}) outside ${size.cols}x${size.rows} grid`;
+	}
+
+	const positions =
+		"Keyframe" in update
+			? update.Keyframe.cells.map(([[row, col]]) => [row, col])
+			: update.Diff.changes.map(([row, col]) => [row, col]);
+	for (const [row, col] of positions) {
+		if (row >= size.rows || col >= size.cols) {
+			return `cell (${row}, ${col}) outside ${size.cols}x${size.rows} grid`;
+		}
+	}
+
+	return null;
+}
@@ -0,0 +1,20 @@
+import type { GridUpdateMessage } from "../types/bindings";
+
+type Keyframe = Extract<GridUpdateMessage, { Keyframe: unknown }>["Keyframe"];
+
+/**
+ * A keyframe sent with the `rle` capability, with its runs of identical
+ * cells expanded back into one entry per cell
+ */
+export function expandRuns(keyframe: Keyframe): Keyframe {
+	if (!keyframe.runs?.length) {
+		return keyframe;
+	}
+	const cells = [...keyframe.cells];
+	for (const [row, col, len, cell] of keyframe.runs) {
+		for (let offset = 0; offset < len; offset++) {
+			cells.push([[row, col + offset], cell]);
+		}
+	}
+	return { ...keyframe, cells, runs: undefined };
+}
@@ -5,6 +5,7 @@ import type {
 	KeyCode,
 	KeyEvent,
 	KeyModifiers,
+	SessionHeatmap,
 	TerminalColor,
 } from "../types/bindings";
 
@@ -102,7 +103,39 @@ export const monochromeTheme: TerminalTheme = {
 	],
 };
 
-export const availableThemes = [defaultTheme, lightTheme, monochromeTheme];
+// Pure black background with bright, saturated colours; used by the high-contrast preference
+export const highContrastTheme: TerminalTheme = {
+	name: "High Contrast",
+	background: "#000000",
+	foreground: "#ffffff",
+	cursor: "#ffff00",
+	selection: "#ffffff",
+	colors: [
+		"#000000",
+		"#ff5555",
+		"#55ff55",
+		"#ffff55",
+		"#5fd7ff",
+		"#ff55ff",
+		"#55ffff",
+		"#ffffff",
+		"#bfbfbf",
+		"#ff8080",
+		"#80ff80",
+		"#ffff80",
+		"#87e1ff",
+		"#ff80ff",
+		"#80ffff",
+		"#ffffff",
+	],
+};
+
+export const availableThemes = [
+	defaultTheme,
+	lightTheme,
+	monochromeTheme,
+	highContrastTheme,
+];
 
 // Re-export key event types from bindings for backward compatibility
 export type WebKeyModifiers = KeyModifiers;
@@ -115,6 +148,10 @@ interface TerminalState {
 	cursor: { row: number; col: number };
 	cursor_visible: boolean;
 	theme: TerminalTheme;
+	// Read-only view with enlarged text and no controls, for screen sharing
+	presentationMode: boolean;
+	// Per-cell change counts shown as an overlay while debugging repaints
+	heatmap: SessionHeatmap | null;
 
 	// Actions
 	updateSize: (rows: number, cols: number) => void;
@@ -134,6 +171,8 @@ interface TerminalState {
 		[key: string]: unknown;
 	}) => void;
 	setTheme: (theme: TerminalTheme) => void;
+	setPresentationMode: (presentationMode: boolean) => void;
+	setHeatmap: (heatmap: SessionHeatmap | null) => void;
 	resolveColor: (color: TerminalColor | null, isBackground?: boolean) => string;
 }
 
@@ -144,6 +183,8 @@ export const useTerminalStore = create<TerminalState>()(
 		cursor: { row: 0, col: 0 },
 		cursor_visible: true,
 		theme: defaultTheme,
+		presentationMode: false,
+		heatmap: null,
 
 		updateSize: (rows, cols) =>
 			set(() => ({
@@ -377,6 +418,16 @@ export const useTerminalStore = create<TerminalState>()(
 				theme,
 			})),
 
+		setPresentationMode: (presentationMode) =>
+			set(() => ({
+				presentationMode,
+			})),
+
+		setHeatmap: (heatmap) =>
+			set(() => ({
+				heatmap,
+			})),
+
 		resolveColor: (color, isBackground = false) => {
 			const state = get();
 
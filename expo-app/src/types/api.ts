@@ -1,7 +1,27 @@
 import type {
+	ActivityTimeseries,
+	AgentCommit,
+	AgentVersionStatus,
+	ConversationAttributes,
+	DiffStats,
+	DoNotDisturb,
 	GridCell,
+	GridUpdateMessage,
+	LineageEntry,
+	NotificationList,
+	PasteConfig,
 	ProjectResourceTS,
+	RecentList,
+	ScreenText,
+	SessionHeatmap,
+	SessionLink,
+	SessionOptions,
 	SessionResourceTS,
+	SessionTranscript,
+	SessionUsage,
+	UiPreferences,
+	UsageSummary,
+	User,
 } from "./bindings";
 
 // Base types for API responses
@@ -21,24 +41,200 @@ export interface ApiError {
 export type Session = SessionResourceTS;
 export type Project = ProjectResourceTS;
 
+// Per-minute I/O history of a running session
+export interface SessionTimeseries {
+	type: "session-timeseries";
+	id: string;
+	attributes: ActivityTimeseries;
+}
+
+// Per-cell change counts of a running session over the last minute
+export interface SessionHeatmapResource {
+	type: "session-heatmap";
+	id: string;
+	attributes: SessionHeatmap;
+}
+
+// Agents, templates and projects for the "New Session" form
+export interface SessionOptionsResource {
+	type: "session-options";
+	id: "new";
+	attributes: SessionOptions;
+}
+
+// Installed version of a whitelisted agent, checked against its pin
+export interface AgentResource {
+	type: "agent";
+	id: string;
+	attributes: AgentVersionStatus;
+}
+
+// Display preferences shared by the TUI and web UI
+export interface Preferences {
+	type: "preferences";
+	id: "ui";
+	attributes: UiPreferences;
+}
+
+// Paste confirmation thresholds and the server's paste limit
+export interface PasteSettingsResource {
+	type: "paste-settings";
+	id: "current";
+	attributes: PasteConfig;
+}
+
+// A large paste saved on the server so its path can be sent instead
+export interface PasteFileResource {
+	type: "paste-file";
+	id: string;
+	attributes: { path: string; bytes: number };
+}
+
+// Result of retracting the last message sent to a session's agent
+export interface RetractionResource {
+	type: "retraction";
+	id: string;
+	attributes: { text: string | null; restored: boolean };
+}
+
+// A commit the session's agent announced, e.g. aider after each edit
+export interface CommitResource {
+	type: "commit";
+	id: string;
+	attributes: AgentCommit;
+}
+
+// A session in the chain of clones and retries that led to a session
+export interface LineageEntryResource {
+	type: "lineage-entry";
+	id: string;
+	attributes: LineageEntry;
+}
+
+// Starred and recently attached sessions for the dashboard quick-switch list
+export interface RecentResource {
+	type: "recent";
+	id: "quick-switch";
+	attributes: RecentList;
+}
+
+// Stored notifications, newest first, with the unread count
+export interface NotificationsResource {
+	type: "notifications";
+	id: "inbox";
+	attributes: NotificationList;
+}
+
+// Do-not-disturb switch for notification alerts
+export interface DoNotDisturbResource {
+	type: "do-not-disturb";
+	id: "server";
+	attributes: DoNotDisturb;
+}
+
+// A session with its screen, recent conversation and git status, for the
+// session page's panes in one request
+export interface SessionWorkspaceResource {
+	type: "workspace";
+	id: string;
+	attributes: {
+		session: Session;
+		keyframe: GridUpdateMessage | null;
+		transcript: SessionTranscript | null;
+		git_status: GitStatus | null;
+	};
+}
+
+// Text of part of a session's screen, for copying from the web terminal
+export interface ScreenTextResource {
+	type: "screen-text";
+	id: string;
+	attributes: ScreenText;
+}
+
+// A past conversation of a project, readable without a running session
+export interface ConversationResource {
+	type: "conversation";
+	id: string;
+	attributes: ConversationAttributes;
+}
+
+// Messages of a conversation with token and cost totals
+export interface TranscriptResource {
+	type: "transcript";
+	id: string;
+	attributes: SessionTranscript;
+}
+
+// Tokens and cost a session's agent has used so far
+export interface SessionUsageResource {
+	type: "usage";
+	id: string;
+	attributes: SessionUsage;
+}
+
+// Usage of every running session, per project and overall
+export interface UsageSummaryResource {
+	type: "usage-summary";
+	id: string;
+	attributes: UsageSummary;
+}
+
+// Someone signed in through the server's identity provider
+export interface UserResource {
+	type: "user";
+	id: string;
+	attributes: User;
+}
+
 // Re-export response wrapper types
 export type { ProjectListResponse, SessionResponse } from "./bindings";
 
 export interface CreateSessionRequest {
-	agent?: string;
+	agent: string;
+	args?: string[];
 	project_id?: string;
-	project_path?: string;
+	path?: string;
+	env?: Record<string, string>;
+	// Name of a configured template; its args come before `args`
+	template?: string;
+	// Run in a new git worktree of the project
+	worktree?: boolean;
+	sandbox?: boolean;
+	// Accent color as #rrggbb; derived from the session ID when omitted
+	color?: string;
+	// Icon (usually an emoji); chosen by agent when omitted
+	icon?: string;
+	// Name to address the session by instead of its ID
+	name?: string;
+	// Sessions this one is a clone, retry or worktree of
+	links?: SessionLink[];
+	// Directory to run the agent in, relative to the project or absolute
+	cwd?: string;
+	// Record the session's output on the server, see `codemux recordings`
+	record?: boolean;
+	// Never end the session for sitting idle, see `[session] idle_timeout_secs`
+	keep_alive?: boolean;
 }
 
 export interface CreateProjectRequest {
 	name: string;
 	path: string;
+	// Parent project (ID, name or "parent/child") for a sub-project of a monorepo
+	parent?: string;
 }
 
 // Git status types
 export interface GitFileStatus {
 	path: string;
-	status: "modified" | "added" | "deleted" | "renamed" | "untracked" | "staged";
+	status:
+		| "modified"
+		| "added"
+		| "deleted"
+		| "renamed"
+		| "copied"
+		| "conflicted"
+		| "untracked";
 	additions?: number;
 	deletions?: number;
 	old_path?: string; // For renamed files
@@ -59,7 +255,14 @@ export interface GitStatus {
 export interface GitFileDiff {
 	path: string;
 	old_path?: string; // For renamed files
-	status: "modified" | "added" | "deleted" | "renamed";
+	status:
+		| "modified"
+		| "added"
+		| "deleted"
+		| "renamed"
+		| "copied"
+		| "conflicted"
+		| "untracked";
 	additions: number;
 	deletions: number;
 	diff: string; // Raw diff content
@@ -85,11 +288,7 @@ export interface DiffLine {
 
 export interface GitDiff {
 	files: GitFileDiff[];
-	summary?: {
-		total_files: number;
-		total_additions: number;
-		total_deletions: number;
-	};
+	summary: DiffStats;
 }
 
 // WebSocket message types for real-time updates
@@ -169,6 +368,18 @@ export interface UseSessionsOptions {
 	enabled?: boolean;
 }
 
+export interface UseSessionTimeseriesOptions {
+	sessionId: string;
+	refetchInterval?: number;
+	enabled?: boolean;
+}
+
+export interface UseSessionHeatmapOptions {
+	sessionId: string;
+	refetchInterval?: number;
+	enabled?: boolean;
+}
+
 export interface UseGitStatusOptions {
 	sessionId: string;
 	refetchInterval?: number;
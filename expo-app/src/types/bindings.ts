@@ -1,7 +1,17 @@
 // Re-export all generated TypeScript bindings from Rust
 // This centralizes imports and provides a single source of truth for types
 
+export type { AccessibleEvent } from "../../../bindings/AccessibleEvent";
+export type { ActivityBucket } from "../../../bindings/ActivityBucket";
+export type { ActivityTimeseries } from "../../../bindings/ActivityTimeseries";
+export type { AgentCommit } from "../../../bindings/AgentCommit";
+export type { AgentOption } from "../../../bindings/AgentOption";
+export type { AgentVersionStatus } from "../../../bindings/AgentVersionStatus";
+export type { ApiToken } from "../../../bindings/ApiToken";
+export type { AnnouncementKind } from "../../../bindings/AnnouncementKind";
+export type { Checkpoint } from "../../../bindings/Checkpoint";
 export type { ClientMessage } from "../../../bindings/ClientMessage";
+export type { ConversationAttributes } from "../../../bindings/ConversationAttributes";
 // Re-export under legacy names for compatibility during transition
 export type {
 	GridCell,
@@ -11,7 +21,13 @@ export type {
 	GridUpdateMessage,
 	GridUpdateMessage as ApiGridUpdateMessage,
 } from "../../../bindings/GridUpdateMessage";
+export type { InputControl } from "../../../bindings/InputControl";
+export type { InputMode } from "../../../bindings/InputMode";
 // JSON API types
+export type { CreateApiTokenRequest } from "../../../bindings/CreateApiTokenRequest";
+export type { Digest } from "../../../bindings/Digest";
+export type { DiffStats } from "../../../bindings/DiffStats";
+export type { DoNotDisturb } from "../../../bindings/DoNotDisturb";
 export type { JsonApiDocument } from "../../../bindings/JsonApiDocument";
 export type { JsonApiError } from "../../../bindings/JsonApiError";
 export type { JsonApiErrorDocument } from "../../../bindings/JsonApiErrorDocument";
@@ -27,21 +43,56 @@ export type {
 	KeyModifiers,
 	KeyModifiers as WebKeyModifiers,
 } from "../../../bindings/KeyModifiers";
+export type { LineageEntry } from "../../../bindings/LineageEntry";
+export type { NewApiToken } from "../../../bindings/NewApiToken";
+export type { Notification } from "../../../bindings/Notification";
+export type { NotificationKind } from "../../../bindings/NotificationKind";
+export type { NotificationList } from "../../../bindings/NotificationList";
+export type { OnMismatch } from "../../../bindings/OnMismatch";
+export type { PasteConfig } from "../../../bindings/PasteConfig";
 export type { ProjectAttributes } from "../../../bindings/ProjectAttributes";
 export type { ProjectInfo } from "../../../bindings/ProjectInfo";
+export type { ProjectOption } from "../../../bindings/ProjectOption";
 export type { ProjectListResponse } from "../../../bindings/ProjectListResponse";
 export type { ProjectRelationships } from "../../../bindings/ProjectRelationships";
 export type { ProjectResourceTS } from "../../../bindings/ProjectResourceTS";
+export type { ProjectUsage } from "../../../bindings/ProjectUsage";
 export type { ProjectWithSessions } from "../../../bindings/ProjectWithSessions";
+export type { PromptType } from "../../../bindings/PromptType";
+export type { QuickProject } from "../../../bindings/QuickProject";
+export type { QuickSession } from "../../../bindings/QuickSession";
+export type { RecentList } from "../../../bindings/RecentList";
+export type { Relationship } from "../../../bindings/Relationship";
+export type { RenderCrashReport } from "../../../bindings/RenderCrashReport";
+export type { RestoredCheckpoint } from "../../../bindings/RestoredCheckpoint";
+export type { ScreenText } from "../../../bindings/ScreenText";
 export type { ScrollDirection } from "../../../bindings/ScrollDirection";
+export type { SelectionShape } from "../../../bindings/SelectionShape";
 export type { SerializablePtySize } from "../../../bindings/SerializablePtySize";
 export type { ServerMessage } from "../../../bindings/ServerMessage";
 export type { SessionAttributes } from "../../../bindings/SessionAttributes";
+export type { SessionHeatmap } from "../../../bindings/SessionHeatmap";
 export type { SessionInfo } from "../../../bindings/SessionInfo";
+export type { SessionLink } from "../../../bindings/SessionLink";
+export type { SessionLinkKind } from "../../../bindings/SessionLinkKind";
+export type { SessionOptions } from "../../../bindings/SessionOptions";
+export type { SessionRelationships } from "../../../bindings/SessionRelationships";
 export type { SessionResourceTS } from "../../../bindings/SessionResourceTS";
 export type { SessionResponse } from "../../../bindings/SessionResponse";
+export type { SessionTemplate } from "../../../bindings/SessionTemplate";
+export type { SessionTranscript } from "../../../bindings/SessionTranscript";
 export type { SessionType } from "../../../bindings/SessionType";
+export type { SessionUsage } from "../../../bindings/SessionUsage";
+export type { SetDoNotDisturbRequest } from "../../../bindings/SetDoNotDisturbRequest";
+export type { Severity } from "../../../bindings/Severity";
+export type { TemplateOption } from "../../../bindings/TemplateOption";
 export type {
 	TerminalColor,
 	TerminalColor as StoreTerminalColor,
 } from "../../../bindings/TerminalColor";
+export type { TokenScope } from "../../../bindings/TokenScope";
+export type { UiPreferences } from "../../../bindings/UiPreferences";
+export type { UpdatePreferencesRequest } from "../../../bindings/UpdatePreferencesRequest";
+export type { UsageSummary } from "../../../bindings/UsageSummary";
+export type { User } from "../../../bindings/User";
+export type { VersionCheck } from "../../../bindings/VersionCheck";
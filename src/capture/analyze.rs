@@ -1,21 +1,25 @@
 use anyhow::Result;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use crate::capture::session_data::SessionEvent;
+use crate::capture::session_data::{SessionEvent, SessionRecording};
+use crate::core::crash_dump::{read_crash_dump, CrashDumpHeader, RenderWatchdog};
+use crate::core::pty_session::GridUpdateMessage;
 
 pub async fn analyze_jsonl_data(input_path: &Path, verbose: bool) -> Result<()> {
-    println!("📊 Loading JSONL data from: {}", input_path.display());
+    // Render crash dumps share the JSONL layout but carry grid updates instead of PTY events
+    if let Some((header, updates)) = read_crash_dump(input_path)? {
+        analyze_crash_dump(&header, &updates, verbose);
+        return Ok(());
+    }
 
-    let file = File::open(input_path)?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
+    println!("📊 Loading JSONL data from: {}", input_path.display());
 
-    // Skip metadata line
-    if let Some(metadata_line) = lines.next() {
-        println!("📋 Metadata: {}", metadata_line?);
-    }
+    // Reads captures cut short by a crash up to where they stop
+    let recording = SessionRecording::load_jsonl(input_path)?;
+    println!(
+        "📋 Metadata: {}",
+        serde_json::to_string(&recording.metadata)?
+    );
 
     // Compare two VT100 processing approaches
     let mut incremental_parser = tui_term::vt100::Parser::new(30, 120, 0);
@@ -34,13 +38,7 @@ pub async fn analyze_jsonl_data(input_path: &Path, verbose: bool) -> Result<()>
     );
     println!("{}", "-".repeat(90));
 
-    for line in lines {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
-        }
-
-        let event: SessionEvent = serde_json::from_str(&line)?;
+    for event in recording.events {
         event_count += 1;
 
         match event {
@@ -492,3 +490,70 @@ fn analyze_input_stream(data: &[u8], incremental_cursor: (u16, u16), batched_cur
         println!("      💡 Contains \\x1b[27m (normal video - cursor unhighlight)");
     }
 }
+
+/// Replay a render crash dump and point at the update that broke the grid
+fn analyze_crash_dump(header: &CrashDumpHeader, updates: &[GridUpdateMessage], verbose: bool) {
+    println!("💥 Render crash dump {}", header.crash_id);
+    println!("   Session:  {}", header.session_id);
+    println!(
+        "   Renderer: {} (codemux {})",
+        header.source, header.version
+    );
+    println!("   Reported: {}", header.reason);
+    println!("   Updates:  {}", updates.len());
+    println!();
+
+    let mut watchdog = RenderWatchdog::new();
+    let mut culprit = None;
+
+    for (idx, update) in updates.iter().enumerate() {
+        let problem = watchdog.observe(update);
+
+        if verbose || problem.is_some() {
+            match update {
+                GridUpdateMessage::Keyframe {
+                    size,
+                    cells,
+                    cursor,
+                    ..
+                } => println!(
+                    "#{:<4} Keyframe {}x{}, {} cells, cursor ({}, {})",
+                    idx,
+                    size.cols,
+                    size.rows,
+                    cells.len(),
+                    cursor.0,
+                    cursor.1
+                ),
+                GridUpdateMessage::Diff {
+                    changes, cursor, ..
+                } => println!(
+                    "#{:<4} Diff {} changes, cursor {:?}",
+                    idx,
+                    changes.len(),
+                    cursor
+                ),
+            }
+        }
+
+        if let Some(problem) = problem {
+            println!("      ❌ {}", problem);
+            culprit.get_or_insert(idx);
+        }
+    }
+
+    println!();
+    match culprit {
+        Some(idx) => println!("🎯 Grid first became inconsistent at update #{}", idx),
+        None if updates
+            .iter()
+            .all(|u| matches!(u, GridUpdateMessage::Diff { .. })) =>
+        {
+            println!("⚠️  Dump contains no keyframe, so grid bounds could not be checked")
+        }
+        None => println!(
+            "✅ Replay is consistent - the {} renderer diverged from the update stream",
+            header.source
+        ),
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Result};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::capture::session_data::{SessionEvent, SessionRecording};
+use crate::core::session::DEMO_AGENT;
+
+/// Longest pause between two outputs, so idle time in a recording doesn't stall the demo
+const MAX_PAUSE: Duration = Duration::from_millis(1500);
+
+const DIM: &str = "\x1b[2m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const ORANGE: &str = "\x1b[38;5;208m";
+const RESET: &str = "\x1b[0m";
+
+/// Program and arguments that run the simulated agent inside a session's PTY
+pub fn demo_command(args: Vec<String>) -> Result<(String, Vec<String>)> {
+    let codemux = std::env::current_exe()
+        .map_err(|e| anyhow!("Failed to get current executable path: {}", e))?;
+    let mut demo_args = vec!["demo".to_string(), "--agent".to_string()];
+    demo_args.extend(args);
+    Ok((codemux.to_string_lossy().to_string(), demo_args))
+}
+
+/// Run the simulated agent on stdin/stdout, as started by `demo_command`
+///
+/// Plays `recording` (or the built-in script), waiting for a line of input
+/// wherever the user typed something, then answers any further input with a
+/// canned reply until stdin closes.
+pub fn run_demo_agent(recording: Option<&Path>, speed: f64) -> Result<()> {
+    let recording = match recording {
+        Some(path) => SessionRecording::load(path)
+            .map_err(|e| anyhow!("Failed to load recording {}: {}", path.display(), e))?,
+        None => demo_recording(),
+    };
+
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut output = std::io::stdout();
+    if !play_recording(&recording, &mut input, &mut output, speed)? {
+        return Ok(());
+    }
+
+    loop {
+        write!(output, "\r\n{}> {}", BOLD, RESET)?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        write!(
+            output,
+            "\r\n{}●{} This is a scripted demo, so there is nothing more to say about \
+             \"{}\".\r\n  Install an agent such as claude, gemini or aider and start it \
+             with `codemux run <agent>`.\r\n",
+            ORANGE,
+            RESET,
+            line.trim()
+        )?;
+    }
+}
+
+/// Replay the output of a recording, pausing for a line of input wherever the user pressed Enter
+///
+/// The typed text is shown as a hint at each prompt. A `speed` of 0 plays
+/// without delays. Returns false if the input closed before the recording ended.
+pub fn play_recording(
+    recording: &SessionRecording,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    speed: f64,
+) -> Result<bool> {
+    let mut last_timestamp = None;
+    let mut typed = Vec::new();
+
+    for event in &recording.events {
+        let (timestamp, data) = match event {
+            SessionEvent::Output { timestamp, data } => (*timestamp, data),
+            SessionEvent::RawPtyOutput {
+                timestamp_begin,
+                data,
+                ..
+            } => (*timestamp_begin, data),
+            SessionEvent::Input { data, .. } => {
+                typed.extend_from_slice(data);
+                if !data.iter().any(|byte| matches!(byte, b'\r' | b'\n')) {
+                    continue;
+                }
+
+                let hint: String = String::from_utf8_lossy(&typed)
+                    .chars()
+                    .filter(|c| !c.is_control())
+                    .collect();
+                typed.clear();
+                if !hint.trim().is_empty() {
+                    write!(output, "\r\n{}  try: {}{}", DIM, hint.trim(), RESET)?;
+                }
+                write!(output, "\r\n{}> {}", BOLD, RESET)?;
+                output.flush()?;
+
+                let mut line = String::new();
+                if input.read_line(&mut line)? == 0 {
+                    return Ok(false);
+                }
+                last_timestamp = None;
+                continue;
+            }
+            _ => continue,
+        };
+
+        if let Some(last) = last_timestamp {
+            if speed > 0.0 {
+                let pause = Duration::from_millis(timestamp.saturating_sub(last) as u64);
+                std::thread::sleep(pause.min(MAX_PAUSE).div_f64(speed));
+            }
+        }
+        last_timestamp = Some(timestamp);
+        output.write_all(data)?;
+        output.flush()?;
+    }
+    Ok(true)
+}
+
+/// The built-in demo: a short scripted coding-agent conversation
+pub fn demo_recording() -> SessionRecording {
+    let mut script = Script::new();
+
+    script.print(&format!(
+        "{ORANGE}✻{RESET} {BOLD}Welcome to the codemux demo!{RESET}\r\n\r\n\
+         {DIM}  This is a simulated agent: nothing is installed, nothing is sent anywhere.\r\n\
+         \x20 Press Enter to send each suggested message, or type your own.{RESET}\r\n"
+    ));
+
+    script.user("Why does the parser crash on empty input?");
+    script.think("Reading the code");
+    script.print(&format!(
+        "{ORANGE}●{RESET} {BOLD}Read{RESET}(src/parser.rs)\r\n  ⎿  Read 42 lines\r\n\r\n"
+    ));
+    script.say(
+        "The crash comes from `tokens[0]` on line 17: with empty input the token \
+         list is empty, so indexing panics. Returning early fixes it.",
+    );
+
+    script.user("Go ahead and fix it");
+    script.think("Editing");
+    script.print(&format!(
+        "{ORANGE}●{RESET} {BOLD}Update{RESET}(src/parser.rs)\r\n\
+         \x20 ⎿  Updated src/parser.rs with 3 additions and 1 removal\r\n\
+         \x20     15    pub fn parse(input: &str) -> Result<Ast> {{\r\n\
+         \x20     16        let tokens = tokenize(input);\r\n\
+         \x20     17 {RED}-      let first = &tokens[0];{RESET}\r\n\
+         \x20     17 {GREEN}+      let Some(first) = tokens.first() else {{{RESET}\r\n\
+         \x20     18 {GREEN}+          return Ok(Ast::empty());{RESET}\r\n\
+         \x20     19 {GREEN}+      }};{RESET}\r\n\r\n"
+    ));
+    script.say("Fixed: empty input now parses to an empty AST instead of panicking.");
+
+    script.user("What else can codemux do?");
+    script.say(
+        "You are looking at a real codemux session, just with a pretend agent. \
+         Try these while it runs:\r\n\
+         \x20 • Ctrl+T switches between monitoring and interactive mode\r\n\
+         \x20 • Press o (in monitoring mode) to open the same session in the web UI\r\n\
+         \x20 • Quit this TUI, then `codemux list` and `codemux attach <id>` to come back\r\n\
+         \x20 • `codemux run claude` starts a real agent the same way",
+    );
+
+    script.recording.finalize();
+    script.recording
+}
+
+/// Builds a recording with realistic pacing
+struct Script {
+    recording: SessionRecording,
+    time: u32,
+}
+
+impl Script {
+    fn new() -> Self {
+        Self {
+            recording: SessionRecording::new(DEMO_AGENT.to_string(), Vec::new()),
+            time: 0,
+        }
+    }
+
+    fn output(&mut self, delay: u32, text: &str) {
+        self.time += delay;
+        self.recording.add_event(SessionEvent::Output {
+            timestamp: self.time,
+            data: text.as_bytes().to_vec(),
+        });
+    }
+
+    /// Print a block at once
+    fn print(&mut self, text: &str) {
+        self.output(300, text);
+    }
+
+    /// The user sends a message
+    fn user(&mut self, text: &str) {
+        self.time += 500;
+        self.recording.add_event(SessionEvent::Input {
+            timestamp: self.time,
+            data: format!("{}\r", text).into_bytes(),
+        });
+        self.output(0, "\r\n");
+    }
+
+    /// A spinner with a status line
+    fn think(&mut self, status: &str) {
+        for frame in ["✢", "✳", "✶", "✻", "✽", "✻", "✶", "✳"] {
+            self.output(120, &format!("\r{ORANGE}{frame}{RESET} {status}…"));
+        }
+        self.output(120, "\r\x1b[2K");
+    }
+
+    /// Stream a reply word by word
+    fn say(&mut self, text: &str) {
+        self.output(200, &format!("{ORANGE}●{RESET} "));
+        for (i, word) in text.split(' ').enumerate() {
+            let word = if i == 0 {
+                word.to_string()
+            } else {
+                format!(" {}", word)
+            };
+            self.output(40, &word);
+        }
+        self.output(0, "\r\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plays_demo_script_one_turn_per_line() {
+        let recording = demo_recording();
+        let mut output = Vec::new();
+
+        let mut input = "\n\n".as_bytes();
+        let finished = play_recording(&recording, &mut input, &mut output, 0.0).unwrap();
+        let text = String::from_utf8_lossy(&output);
+        assert!(!finished);
+        assert!(text.contains("try: Why does the parser crash on empty input?"));
+        assert!(text.contains("Returning early fixes it."));
+        assert!(!text.contains("codemux attach <id>"));
+
+        output.clear();
+        let mut input = "\n\n\n".as_bytes();
+        assert!(play_recording(&recording, &mut input, &mut output, 0.0).unwrap());
+        assert!(String::from_utf8_lossy(&output).contains("codemux attach <id>"));
+    }
+}
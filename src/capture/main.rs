@@ -78,6 +78,9 @@ enum Commands {
         /// Capture mode: raw (PTY output), grid (VT100 parsed), or both
         #[arg(short, long, default_value = "raw")]
         mode: String,
+        /// Append to the recording already in the output file, e.g. one interrupted by a crash
+        #[arg(long)]
+        resume: bool,
         /// Arguments to pass to the agent
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
@@ -127,6 +130,7 @@ async fn main() -> Result<()> {
             agent,
             output,
             mode,
+            resume,
             args,
         } => {
             println!("🎬 Starting capture session for {}", agent);
@@ -145,7 +149,8 @@ async fn main() -> Result<()> {
                 }
             };
 
-            let mut capture = CaptureSession::new(agent, args, output, capture_mode)?;
+            let mut capture =
+                CaptureSession::new(agent, args, output, capture_mode)?.with_resume(resume);
             capture.start_recording().await?;
         }
         Commands::Replay {
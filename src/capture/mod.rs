@@ -1,4 +1,6 @@
 pub mod analyze;
+pub mod demo;
+pub mod player;
 pub mod replay;
 pub mod session;
 pub mod session_data;
@@ -0,0 +1,381 @@
+//! Playing a recording back inside a session, for `codemux replay`
+//!
+//! The player runs as the session's program, like the demo agent, so the TUI
+//! and the web terminal show a replay as they show any other session. Nothing
+//! typed into it reaches a program: keys only control playback. Space plays
+//! and pauses, ←/→ seek, +/- change the speed and q ends the replay. Seeking
+//! back starts the terminal over and writes everything up to that point at once.
+
+use anyhow::{anyhow, Result};
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::capture::session_data::{GridCellWithPos, SessionEvent, SessionRecording};
+
+/// How far ←/→ seek
+const SEEK_STEP: Duration = Duration::from_secs(10);
+/// Longest pause between two outputs; idle time beyond it is skipped
+const MAX_IDLE: Duration = Duration::from_secs(3);
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 16.0;
+/// Full terminal reset, to draw a recording again from the start
+const RESET_TERMINAL: &[u8] = b"\x1bc";
+
+/// Play `path` on stdin/stdout until q is pressed or stdin closes, as
+/// started by `codemux demo --agent --replay`
+pub fn run_replay_agent(path: &Path, speed: f64) -> Result<()> {
+    let recording = SessionRecording::load(path)
+        .map_err(|e| anyhow!("Failed to load recording {}: {}", path.display(), e))?;
+    let mut player = Player::new(frames(&recording), speed);
+
+    let (control_tx, control_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin();
+        let mut buf = [0u8; 64];
+        while let Ok(n @ 1..) = stdin.read(&mut buf) {
+            for control in controls(&buf[..n]) {
+                if control_tx.send(control).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    // Keys have to arrive one by one and unechoed to control playback
+    crossterm::terminal::enable_raw_mode()?;
+    let mut output = std::io::stdout();
+    let result = (|| -> Result<()> {
+        player.play_due(&mut output)?;
+        player.draw_status(&mut output)?;
+        loop {
+            let wait = player.play_due(&mut output)?;
+            let control = match wait {
+                Some(wait) => control_rx.recv_timeout(wait),
+                None => control_rx
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match control {
+                Ok(Control::Quit) | Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                Ok(control) => player.control(control, &mut output)?,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+        }
+    })();
+    let _ = crossterm::terminal::disable_raw_mode();
+    result
+}
+
+/// Output of the recording due at a point of the replay
+#[derive(Debug)]
+struct Frame {
+    at: Duration,
+    data: Vec<u8>,
+}
+
+/// The recording's output as it is to be written, with long idle stretches cut
+/// short
+///
+/// Recordings with only screens and no raw output are drawn screen by screen.
+fn frames(recording: &SessionRecording) -> Vec<Frame> {
+    let has_output = recording.events.iter().any(|event| {
+        matches!(
+            event,
+            SessionEvent::Output { .. } | SessionEvent::RawPtyOutput { .. }
+        )
+    });
+
+    let mut frames = Vec::new();
+    let mut at = Duration::ZERO;
+    let mut last_timestamp = None;
+    for event in &recording.events {
+        let (timestamp, data) = match event {
+            SessionEvent::Output { timestamp, data } if has_output => (*timestamp, data.clone()),
+            SessionEvent::RawPtyOutput {
+                timestamp_begin,
+                data,
+                ..
+            } if has_output => (*timestamp_begin, data.clone()),
+            SessionEvent::GridUpdate {
+                timestamp_begin,
+                cells,
+                cursor,
+                ..
+            } if !has_output => (*timestamp_begin, paint(cells, *cursor)),
+            _ => continue,
+        };
+        if let Some(last) = last_timestamp {
+            let gap = Duration::from_millis(u32::saturating_sub(timestamp, last) as u64);
+            at += gap.min(MAX_IDLE);
+        }
+        last_timestamp = Some(timestamp);
+        frames.push(Frame { at, data });
+    }
+    frames
+}
+
+/// Escape sequences drawing a recorded screen
+fn paint(cells: &[GridCellWithPos], cursor: (u16, u16)) -> Vec<u8> {
+    let mut cells: Vec<_> = cells.iter().collect();
+    cells.sort_by_key(|cell| (cell.row, cell.col));
+
+    let mut out = String::from("\x1b[0m\x1b[2J");
+    for GridCellWithPos { row, col, cell } in cells {
+        let mut codes = Vec::new();
+        for (on, code) in [
+            (cell.bold, "1"),
+            (cell.italic, "3"),
+            (cell.underline, "4"),
+            (cell.reverse, "7"),
+        ] {
+            if on {
+                codes.push(code.to_string());
+            }
+        }
+        for (color, layer) in [(&cell.fg_color, 38), (&cell.bg_color, 48)] {
+            if let Some((r, g, b)) = color.as_deref().and_then(rgb) {
+                codes.push(format!("{};2;{};{};{}", layer, r, g, b));
+            }
+        }
+        let _ = write!(out, "\x1b[{};{}H", row + 1, col + 1);
+        if !codes.is_empty() {
+            let _ = write!(out, "\x1b[{}m", codes.join(";"));
+        }
+        let _ = write!(out, "{}\x1b[0m", cell.char);
+    }
+    let _ = write!(out, "\x1b[{};{}H", cursor.0 + 1, cursor.1 + 1);
+    out.into_bytes()
+}
+
+/// `#rrggbb` as its components
+fn rgb(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let component = |at: usize| u8::from_str_radix(hex.get(at..at + 2)?, 16).ok();
+    Some((component(0)?, component(2)?, component(4)?))
+}
+
+/// What a key does to playback
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    PlayPause,
+    Back,
+    Forward,
+    Faster,
+    Slower,
+    Quit,
+}
+
+/// The controls among bytes read from the terminal; other keys are ignored
+fn controls(bytes: &[u8]) -> Vec<Control> {
+    let mut controls = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let control = match &bytes[i..] {
+            [b'\x1b', b'[' | b'O', b'C', ..] => {
+                i += 2;
+                Some(Control::Forward)
+            }
+            [b'\x1b', b'[' | b'O', b'D', ..] => {
+                i += 2;
+                Some(Control::Back)
+            }
+            [b' ', ..] => Some(Control::PlayPause),
+            [b'l', ..] => Some(Control::Forward),
+            [b'h', ..] => Some(Control::Back),
+            [b'+' | b'=', ..] => Some(Control::Faster),
+            [b'-', ..] => Some(Control::Slower),
+            // Ctrl+C arrives as a byte in raw mode
+            [b'q' | b'\x03', ..] => Some(Control::Quit),
+            _ => None,
+        };
+        controls.extend(control);
+        i += 1;
+    }
+    controls
+}
+
+struct Player {
+    frames: Vec<Frame>,
+    /// First frame not written yet
+    next: usize,
+    /// Position when playback last started or stopped
+    position: Duration,
+    /// Set while playing
+    playing_since: Option<Instant>,
+    speed: f64,
+}
+
+impl Player {
+    fn new(frames: Vec<Frame>, speed: f64) -> Self {
+        Self {
+            frames,
+            next: 0,
+            position: Duration::ZERO,
+            playing_since: None,
+            speed: speed.clamp(MIN_SPEED, MAX_SPEED),
+        }
+    }
+
+    fn position(&self) -> Duration {
+        match self.playing_since {
+            Some(since) => {
+                (self.position + since.elapsed().mul_f64(self.speed)).min(self.duration())
+            }
+            None => self.position,
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        self.frames.last().map(|frame| frame.at).unwrap_or_default()
+    }
+
+    /// Write the frames due by now, returning how long until the next one
+    /// is due while playing
+    fn play_due(&mut self, output: &mut impl Write) -> Result<Option<Duration>> {
+        let position = self.position();
+        self.write_until(position, output)?;
+        if self.playing_since.is_some() && self.next == self.frames.len() {
+            self.pause();
+            self.draw_status(output)?;
+        }
+        output.flush()?;
+        Ok(match (self.playing_since, self.frames.get(self.next)) {
+            (Some(_), Some(frame)) => Some((frame.at - position).div_f64(self.speed)),
+            _ => None,
+        })
+    }
+
+    fn write_until(&mut self, position: Duration, output: &mut impl Write) -> Result<()> {
+        while let Some(frame) = self
+            .frames
+            .get(self.next)
+            .filter(|frame| frame.at <= position)
+        {
+            output.write_all(&frame.data)?;
+            self.next += 1;
+        }
+        Ok(())
+    }
+
+    fn control(&mut self, control: Control, output: &mut impl Write) -> Result<()> {
+        match control {
+            Control::PlayPause if self.playing_since.is_some() => {
+                self.pause();
+            }
+            Control::PlayPause => {
+                let from = if self.next == self.frames.len() {
+                    Duration::ZERO
+                } else {
+                    self.position
+                };
+                // Draw again without the status line
+                self.seek(from, true, output)?;
+                self.playing_since = Some(Instant::now());
+            }
+            Control::Back => self.seek(self.position().saturating_sub(SEEK_STEP), false, output)?,
+            Control::Forward => self.seek(self.position() + SEEK_STEP, false, output)?,
+            Control::Faster | Control::Slower => {
+                self.position = self.position();
+                self.playing_since = self.playing_since.map(|_| Instant::now());
+                self.speed = if control == Control::Faster {
+                    (self.speed * 2.0).min(MAX_SPEED)
+                } else {
+                    (self.speed / 2.0).max(MIN_SPEED)
+                };
+            }
+            Control::Quit => {}
+        }
+        if self.playing_since.is_none() {
+            self.draw_status(output)?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    fn pause(&mut self) {
+        self.position = self.position();
+        self.playing_since = None;
+    }
+
+    /// Jump to `to`, drawing the terminal over from the start when going back
+    /// or when `redraw` is set
+    fn seek(&mut self, to: Duration, redraw: bool, output: &mut impl Write) -> Result<()> {
+        let to = to.min(self.duration());
+        if redraw || to < self.position() {
+            output.write_all(RESET_TERMINAL)?;
+            self.next = 0;
+        }
+        self.position = to;
+        self.playing_since = self.playing_since.map(|_| Instant::now());
+        self.write_until(to, output)
+    }
+
+    /// Show where the replay is on the bottom line while paused
+    fn draw_status(&self, output: &mut impl Write) -> Result<()> {
+        let state = if self.next == self.frames.len() {
+            "■"
+        } else {
+            "⏸"
+        };
+        write!(
+            output,
+            "\x1b7\x1b[999;1H\x1b[0m\x1b[7m {} {} / {}  {}x  Space play/pause · ←/→ {}s · +/- speed · q quit \x1b[0m\x1b[K\x1b8",
+            state,
+            clock(self.position()),
+            clock(self.duration()),
+            self.speed,
+            SEEK_STEP.as_secs()
+        )?;
+        Ok(())
+    }
+}
+
+/// `m:ss`
+fn clock(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeks_by_writing_the_recording_up_to_the_new_position() {
+        let mut recording = SessionRecording::new("claude".to_string(), Vec::new());
+        for (timestamp, text) in [(0, "one "), (1_000, "two "), (600_000, "three ")] {
+            recording.add_event(SessionEvent::Output {
+                timestamp,
+                data: text.as_bytes().to_vec(),
+            });
+        }
+        let mut player = Player::new(frames(&recording), 1.0);
+        assert_eq!(player.duration(), Duration::from_secs(1) + MAX_IDLE);
+
+        let mut output = Vec::new();
+        player.control(Control::Forward, &mut output).unwrap();
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.starts_with("one two three "));
+        assert!(text.contains("■ 0:04 / 0:04"));
+
+        output.clear();
+        player
+            .seek(Duration::from_millis(500), false, &mut output)
+            .unwrap();
+        assert_eq!(output, b"\x1bcone ");
+
+        assert_eq!(
+            controls(b"x \x1b[C\x1bOD+q"),
+            [
+                Control::PlayPause,
+                Control::Forward,
+                Control::Back,
+                Control::Faster,
+                Control::Quit
+            ]
+        );
+    }
+}
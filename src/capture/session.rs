@@ -6,11 +6,13 @@ use std::collections::HashMap;
 use std::io::{stdout, Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc;
 
-use crate::capture::session_data::{GridCell, GridCellWithPos, JsonlRecorder, SessionEvent};
+use crate::capture::session_data::{
+    GridCell, GridCellWithPos, JsonlRecorder, SessionEvent, SYNC_INTERVAL,
+};
 
 pub struct CaptureSession {
     agent: String,
@@ -18,6 +20,8 @@ pub struct CaptureSession {
     output_path: PathBuf,
     start_time: Instant,
     capture_mode: CaptureMode,
+    /// Append to an interrupted recording in `output_path` instead of starting over
+    resume: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -40,14 +44,35 @@ impl CaptureSession {
             output_path,
             start_time: Instant::now(),
             capture_mode,
+            resume: false,
         })
     }
 
+    /// Continue the recording in `output_path` if there is one, e.g. after a crash
+    pub fn with_resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
     pub async fn start_recording(&mut self) -> Result<()> {
         println!("🎬 Starting capture session...");
         println!("📝 Press Ctrl+C to stop recording and save");
         println!("📊 Capture mode: {:?}", self.capture_mode);
 
+        let recorder = if self.resume && self.output_path.exists() {
+            JsonlRecorder::resume(&self.output_path)?
+        } else {
+            JsonlRecorder::new(&self.output_path, self.agent.clone(), self.args.clone())?
+        };
+        if recorder.resumed_at() > 0 {
+            println!("⏯️  Resuming recording at {}ms", recorder.resumed_at());
+            // Timestamps continue from the last recorded event
+            let resumed_at = Duration::from_millis(recorder.resumed_at() as u64);
+            self.start_time = Instant::now()
+                .checked_sub(resumed_at)
+                .unwrap_or(self.start_time);
+        }
+
         // Create PTY system
         let pty_system = NativePtySystem::default();
 
@@ -89,23 +114,26 @@ impl CaptureSession {
             let (tx, mut rx) = mpsc::unbounded_channel::<SessionEvent>();
             let (completion_tx, completion_rx) = mpsc::unbounded_channel::<()>();
 
-            // Create JSONL recorder and task to write events in real-time
-            let agent = self.agent.clone();
-            let args = self.args.clone();
+            // Task to write events to the JSONL recording in real-time
             let output_path = self.output_path.clone();
             let handle = tokio::spawn(async move {
-                let mut recorder = match JsonlRecorder::new(&output_path, agent, args) {
-                    Ok(r) => r,
-                    Err(e) => {
-                        eprintln!("❌ Failed to create JSONL recorder: {}", e);
-                        let _ = completion_tx.send(());
-                        return;
-                    }
-                };
-
-                while let Some(event) = rx.recv().await {
-                    if let Err(e) = recorder.write_event(&event) {
-                        eprintln!("❌ Failed to write event: {}", e);
+                let mut recorder = recorder;
+                // Sync the last events before a pause too, not only on the next write
+                let mut sync_interval = tokio::time::interval(SYNC_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        event = rx.recv() => {
+                            let Some(event) = event else { break };
+                            if let Err(e) = recorder.write_event(&event) {
+                                eprintln!("❌ Failed to write event: {}", e);
+                            }
+                        }
+                        _ = sync_interval.tick() => {
+                            if let Err(e) = recorder.sync_if_due() {
+                                eprintln!("❌ Failed to sync recording: {}", e);
+                            }
+                        }
                     }
                 }
 
@@ -1,9 +1,12 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Write};
 use std::path::Path;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a recorder may leave written events unsynced before forcing them to disk
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Represents a single I/O event in the session
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -134,14 +137,25 @@ impl SessionRecording {
         Ok(())
     }
 
-    /// Load recording from file
+    /// Load recording from file, either saved with `save` or written by a `JsonlRecorder`
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if is_jsonl(path)? {
+            return Self::load_jsonl(path);
+        }
         let file = File::open(path)?;
         let reader = BufReader::new(file);
         let recording = serde_json::from_reader(reader)?;
         Ok(recording)
     }
 
+    /// Load a recording written by a `JsonlRecorder`
+    ///
+    /// A last line cut short by a crash is left out rather than failing the load.
+    pub fn load_jsonl<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(read_jsonl(path.as_ref())?.recording)
+    }
+
     /// Get events in a time range (in milliseconds)
     pub fn get_events_in_range(&self, start: u32, end: u32) -> Vec<&SessionEvent> {
         self.events
@@ -190,18 +204,108 @@ impl SessionRecording {
     }
 }
 
+/// Whether the file starts with a `JsonlRecorder` metadata line
+fn is_jsonl(path: &Path) -> Result<bool> {
+    use std::io::BufRead;
+
+    let mut first_line = String::new();
+    BufReader::new(File::open(path)?).read_line(&mut first_line)?;
+    Ok(serde_json::from_str::<SessionMetadata>(&first_line).is_ok())
+}
+
+/// What a JSONL recording holds, up to where it was cut short
+struct JsonlContents {
+    recording: SessionRecording,
+    /// Length of the file up to the end of the last event read
+    complete_len: u64,
+}
+
+/// Read a JSONL recording, leaving out a tail that was cut short
+///
+/// Events are appended a line at a time, so after a crash only the end of the
+/// file can be broken: a line without its newline, or zeros where the file
+/// system grew the file but never wrote the data. Lines that don't parse are
+/// only ignored at the end; one followed by events that do is an error.
+fn read_jsonl(path: &Path) -> Result<JsonlContents> {
+    let content = std::fs::read(path)?;
+    let mut lines = content.split_inclusive(|byte| *byte == b'\n');
+
+    let metadata_line = lines
+        .next()
+        .ok_or_else(|| anyhow!("{} is empty", path.display()))?;
+    let metadata: SessionMetadata = serde_json::from_slice(metadata_line)
+        .map_err(|e| anyhow!("{} has no recording metadata: {}", path.display(), e))?;
+    let mut recording = SessionRecording {
+        metadata,
+        events: Vec::new(),
+    };
+    let mut complete_len = metadata_line.len();
+    if !metadata_line.ends_with(b"\n") {
+        return Err(anyhow!("{} ends within its metadata", path.display()));
+    }
+
+    let mut broken_at = None;
+    for (index, line) in lines.enumerate() {
+        let line_number = index + 2;
+        let parsed = match line.strip_suffix(b"\n") {
+            Some(json) if json.iter().all(u8::is_ascii_whitespace) => {
+                if broken_at.is_none() {
+                    complete_len += line.len();
+                }
+                continue;
+            }
+            Some(json) => serde_json::from_slice::<SessionEvent>(json).ok(),
+            // Cut short before its newline
+            None => None,
+        };
+        match (parsed, broken_at) {
+            (Some(_), Some(broken)) => {
+                return Err(anyhow!("Line {} of {} is corrupt", broken, path.display()))
+            }
+            (Some(event), None) => {
+                recording.add_event(event);
+                complete_len += line.len();
+            }
+            (None, _) => {
+                broken_at.get_or_insert(line_number);
+            }
+        }
+    }
+
+    if let Some(line) = broken_at {
+        tracing::warn!(
+            "Recording {} was cut short at line {}; reading the {} events before it",
+            path.display(),
+            line,
+            recording.events.len()
+        );
+    }
+    recording.finalize();
+    Ok(JsonlContents {
+        recording,
+        complete_len: complete_len as u64,
+    })
+}
+
 /// JSONL streaming writer for real-time event recording
+///
+/// Every event is written as a whole line at once and the file is synced to
+/// disk at least every `SYNC_INTERVAL`, so a crash loses a second of events at
+/// most and leaves a file that `SessionRecording::load` reads up to there.
 pub struct JsonlRecorder {
-    writer: BufWriter<File>,
+    file: File,
     metadata: SessionMetadata,
     start_time: SystemTime,
+    /// Milliseconds recorded before this recorder took over, when resuming
+    resumed_at: u32,
+    last_sync: Instant,
+    unsynced: bool,
 }
 
 impl JsonlRecorder {
     /// Create a new JSONL recorder
     pub fn new<P: AsRef<Path>>(path: P, agent: String, args: Vec<String>) -> Result<Self> {
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
+        let mut file = File::create(path)?;
 
         let metadata = SessionMetadata {
             agent,
@@ -213,22 +317,70 @@ impl JsonlRecorder {
         };
 
         // Write metadata as first line
-        let metadata_json = serde_json::to_string(&metadata)?;
-        writeln!(writer, "{}", metadata_json)?;
-        writer.flush()?;
+        file.write_all(&json_line(&metadata)?)?;
+        file.sync_data()?;
 
         Ok(Self {
-            writer,
+            file,
             metadata,
             start_time: SystemTime::now(),
+            resumed_at: 0,
+            last_sync: Instant::now(),
+            unsynced: false,
+        })
+    }
+
+    /// Continue an interrupted recording in the same file
+    ///
+    /// A tail cut short by the interruption is cut off first. Keep recording
+    /// with timestamps from `resumed_at` on, so playback continues where the
+    /// recording stopped instead of pausing for as long as it was interrupted.
+    pub fn resume<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = read_jsonl(path)?;
+        let resumed_at = contents
+            .recording
+            .events
+            .last()
+            .map(|event| contents.recording.get_event_timestamp(event))
+            .unwrap_or(0);
+
+        let file = OpenOptions::new().write(true).open(path)?;
+        file.set_len(contents.complete_len)?;
+        file.sync_data()?;
+        drop(file);
+        let file = OpenOptions::new().append(true).open(path)?;
+
+        Ok(Self {
+            file,
+            start_time: contents.recording.metadata.start_time,
+            metadata: contents.recording.metadata,
+            resumed_at,
+            last_sync: Instant::now(),
+            unsynced: false,
         })
     }
 
     /// Write an event to the JSONL file
     pub fn write_event(&mut self, event: &SessionEvent) -> Result<()> {
-        let event_json = serde_json::to_string(event)?;
-        writeln!(self.writer, "{}", event_json)?;
-        self.writer.flush()?;
+        self.file.write_all(&json_line(event)?)?;
+        self.unsynced = true;
+        self.sync_if_due()
+    }
+
+    /// Sync written events to disk if the last sync was `SYNC_INTERVAL` ago;
+    /// call it periodically too, so the last events before a pause are synced
+    pub fn sync_if_due(&mut self) -> Result<()> {
+        if self.unsynced && self.last_sync.elapsed() >= SYNC_INTERVAL {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.file.sync_data()?;
+        self.last_sync = Instant::now();
+        self.unsynced = false;
         Ok(())
     }
 
@@ -242,6 +394,11 @@ impl JsonlRecorder {
         self.start_time
     }
 
+    /// Timestamp (milliseconds) of the last event before resuming, 0 for a new recording
+    pub fn resumed_at(&self) -> u32 {
+        self.resumed_at
+    }
+
     /// Get elapsed time since session start
     pub fn elapsed_time(&self) -> Duration {
         self.start_time.elapsed().unwrap_or(Duration::ZERO)
@@ -249,7 +406,62 @@ impl JsonlRecorder {
 
     /// Finalize the recording
     pub fn finalize(mut self) -> Result<()> {
-        self.writer.flush()?;
-        Ok(())
+        self.sync()
+    }
+}
+
+/// `value` as JSON followed by a newline, to be written in one go
+fn json_line<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(timestamp: u32) -> SessionEvent {
+        SessionEvent::Output {
+            timestamp,
+            data: b"hello".to_vec(),
+        }
+    }
+
+    #[test]
+    fn resumes_recording_cut_short_by_a_crash() {
+        let path =
+            std::env::temp_dir().join(format!("codemux-recording-{}.jsonl", std::process::id()));
+        let mut recorder = JsonlRecorder::new(&path, "claude".into(), Vec::new()).unwrap();
+        recorder.write_event(&output(10)).unwrap();
+        recorder.write_event(&output(20)).unwrap();
+        drop(recorder);
+
+        // A crash mid-write, then one that left zeros behind
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"Output":{"timestamp":30,"da"#).unwrap();
+        let recording = SessionRecording::load(&path).unwrap();
+        assert_eq!(recording.events.len(), 2);
+        file.write_all(&[0; 64]).unwrap();
+        assert_eq!(SessionRecording::load(&path).unwrap().events.len(), 2);
+
+        let mut recorder = JsonlRecorder::resume(&path).unwrap();
+        assert_eq!(recorder.resumed_at(), 20);
+        recorder.write_event(&output(25)).unwrap();
+        recorder.finalize().unwrap();
+        let recording = SessionRecording::load(&path).unwrap();
+        assert_eq!(recording.metadata.agent, "claude");
+        assert_eq!(recording.metadata.total_events, 3);
+        assert_eq!(recording.total_duration(), 15);
+
+        // Events after a broken line mean the file is corrupt, not cut short
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(
+            &path,
+            content.replacen(r#"{"Output":{"timestamp":20"#, "{oops", 1),
+        )
+        .unwrap();
+        assert!(SessionRecording::load_jsonl(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
     }
 }
@@ -1,11 +1,19 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use std::time::Duration;
+
+use super::shell_init::Shell;
+use crate::core::api_tokens::TokenScope;
+use crate::core::render::ExportFormat;
 
 #[derive(Parser, Debug)]
 #[command(name = "codemux")]
 #[command(about = "Terminal multiplexer for AI code agents", long_about = None)]
 #[command(version)]
 pub struct Cli {
+    /// Server profile from a `[profiles.<name>]` config table to use
+    #[arg(long, global = true, env = "CODEMUX_PROFILE")]
+    pub profile: Option<String>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -23,16 +31,133 @@ pub enum Commands {
         /// Resume from a specific session ID
         #[arg(long = "resume")]
         resume_session: Option<String>,
-        /// Project path or ID (e.g. /path/to/project, ., or project-uuid)
-        #[arg(long)]
+        /// Project path, name or ID (e.g. /path/to/project, ., mono/api, or project-uuid)
+        #[arg(long, env = "CODEMUX_PROJECT")]
         project: Option<String>,
         /// Path to write logs to file (in addition to TUI display)
         #[arg(long)]
         logfile: Option<PathBuf>,
+        /// Read-only fullscreen view for screen sharing: no status bar, input ignored (Ctrl+C exits)
+        #[arg(long)]
+        present: bool,
+        /// Session accent color as #rrggbb (default: derived from the session ID)
+        #[arg(long)]
+        color: Option<String>,
+        /// Session icon, e.g. an emoji (default: chosen by agent)
+        #[arg(long)]
+        icon: Option<String>,
+        /// Name to attach to or kill the session by instead of its ID
+        #[arg(long)]
+        name: Option<String>,
+        /// Directory to run the agent in, relative to the project (default: the project's directory)
+        #[arg(long)]
+        cwd: Option<String>,
+        /// Record the session's output for replaying it later, see `codemux recordings`
+        #[arg(long)]
+        record: bool,
+        /// Never end the session for sitting idle, see `[session] idle_timeout_secs`
+        #[arg(long)]
+        keep_alive: bool,
         /// Arguments to pass to Claude
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
     },
+    /// Run any whitelisted agent under codemux
+    Run {
+        /// Agent to run (e.g. claude, gemini, aider; default: the project's default agent)
+        agent: Option<String>,
+        /// Auto-open the web interface in browser
+        #[arg(short, long)]
+        open: bool,
+        /// Continue the agent's most recent conversation in the project
+        #[arg(long = "continue")]
+        continue_session: bool,
+        /// Resume the agent's conversation with this ID
+        #[arg(long = "resume")]
+        resume_session: Option<String>,
+        /// Project path, name or ID (e.g. /path/to/project, ., mono/api, or project-uuid)
+        #[arg(long, env = "CODEMUX_PROJECT")]
+        project: Option<String>,
+        /// Path to write logs to file (in addition to TUI display)
+        #[arg(long)]
+        logfile: Option<PathBuf>,
+        /// Read-only fullscreen view for screen sharing: no status bar, input ignored (Ctrl+C exits)
+        #[arg(long)]
+        present: bool,
+        /// Session accent color as #rrggbb (default: derived from the session ID)
+        #[arg(long)]
+        color: Option<String>,
+        /// Session icon, e.g. an emoji (default: chosen by agent)
+        #[arg(long)]
+        icon: Option<String>,
+        /// Name to attach to or kill the session by instead of its ID
+        #[arg(long)]
+        name: Option<String>,
+        /// Directory to run the agent in, relative to the project (default: the project's directory)
+        #[arg(long)]
+        cwd: Option<String>,
+        /// Record the session's output for replaying it later, see `codemux recordings`
+        #[arg(long)]
+        record: bool,
+        /// Never end the session for sitting idle, see `[session] idle_timeout_secs`
+        #[arg(long)]
+        keep_alive: bool,
+        /// Open the session in a tmux window running `codemux attach --raw` instead of the TUI
+        #[arg(long, conflicts_with = "present")]
+        tmux: bool,
+        /// Arguments to pass to the agent
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Try codemux with a simulated agent - no AI CLI or account needed
+    Demo {
+        /// Auto-open the web interface in browser
+        #[arg(short, long)]
+        open: bool,
+        /// Read-only fullscreen view for screen sharing: no status bar, input ignored (Ctrl+C exits)
+        #[arg(long)]
+        present: bool,
+        /// Play a saved session recording (JSON) instead of the built-in script
+        #[arg(long)]
+        recording: Option<PathBuf>,
+        /// Playback speed multiplier; 0 plays without delays
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// Run the simulated agent itself on stdin/stdout (used inside the session)
+        #[arg(long, hide = true)]
+        agent: bool,
+        /// With --agent, play the recording with playback controls instead
+        #[arg(long, hide = true)]
+        replay: bool,
+    },
+    /// Play back a session recording in a read-only session, in the TUI and the web UI
+    ///
+    /// Space plays and pauses, ←/→ seek 10 seconds, +/- change the speed and q ends the replay.
+    Replay {
+        /// Recording file, or the ID of a session recorded with --record
+        recording: PathBuf,
+        /// Auto-open the web interface in browser
+        #[arg(short, long)]
+        open: bool,
+        /// Playback speed multiplier
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+    /// Install wrappers that make plain `claude` etc. run under codemux
+    Shim {
+        #[command(subcommand)]
+        command: ShimCommands,
+    },
+    /// Print shell code that tracks the current git repository as the project
+    ///
+    /// Add `eval "$(codemux shell-init zsh)"` to ~/.zshrc (or the bash equivalent to ~/.bashrc).
+    ShellInit {
+        /// Shell to generate code for
+        shell: Shell,
+        /// Also register each repository you cd into as a project
+        #[arg(long)]
+        register: bool,
+    },
     /// Server management commands
     Server {
         #[command(subcommand)]
@@ -40,13 +165,102 @@ pub enum Commands {
     },
     /// Attach to an existing session
     Attach {
-        /// Session ID to attach to
+        /// Session ID or name to attach to (pick from starred and recent sessions if omitted)
+        session_id: Option<String>,
+        /// Pass the session's raw terminal through instead of drawing the TUI, like `tmux attach`;
+        /// Ctrl+\ then d detaches
+        #[arg(long)]
+        raw: bool,
+        /// Watch without typing, e.g. to show a teammate the session; the server
+        /// refuses input from this client and the session keeps its size
+        #[arg(long, conflicts_with = "raw")]
+        read_only: bool,
+    },
+    /// Show sessions as tmux windows
+    Tmux {
+        #[command(subcommand)]
+        command: TmuxCommands,
+    },
+    /// Print a link that opens one session in the web UI, e.g. for a teammate
+    ///
+    /// The link carries a token for that session only, read-only unless
+    /// `--interactive` is given; revoke it early with `codemux token revoke`.
+    Share {
+        /// Session ID or name to share
+        session_id: String,
+        /// Expire the link after this long, e.g. `30m`, `1h` or `2d`
+        #[arg(long, default_value = "1h", value_parser = parse_duration)]
+        ttl: Duration,
+        /// Let whoever opens the link type into the session, not just watch it
+        #[arg(long)]
+        interactive: bool,
+    },
+    /// Jump to a project's running session in the web UI, starting one if needed
+    ///
+    /// Meant to be bound to a global hotkey: prints the session URL and opens it.
+    Focus {
+        /// Project ID, name or path (defaults to the current directory)
+        #[arg(default_value = ".")]
+        project: String,
+        /// Agent to start when the project has no running session
+        #[arg(short, long, default_value = "claude")]
+        agent: String,
+        /// Only print the URL instead of opening the browser
+        #[arg(long)]
+        no_open: bool,
+    },
+    /// Follow a session's output without taking input
+    Watch {
+        /// Session ID to watch
         session_id: String,
+        /// Print finished lines and announcements for screen readers instead of raw terminal output
+        #[arg(long)]
+        accessible: bool,
+    },
+    /// Find agent CLIs started outside codemux and take one over as a session
+    Adopt {
+        /// Process ID of the agent to take over (requires reptyr)
+        pid: Option<u32>,
+        /// Only list agent processes running outside codemux
+        #[arg(long, conflicts_with = "pid")]
+        scan: bool,
     },
     /// Kill a specific session
     KillSession {
-        /// Session ID to terminate
+        /// Session ID or name to terminate
+        session_id: String,
+        /// End it right away, without the grace period that allows undo-kill
+        #[arg(long)]
+        now: bool,
+    },
+    /// Bring back a killed session during its grace period
+    UndoKill {
+        /// Session ID or name that was killed
+        session_id: String,
+    },
+    /// Export a session's conversation as a shareable document, e.g. for a PR description
+    ///
+    /// Uses the agent's transcript when codemux can read it, and the terminal's
+    /// scrollback otherwise.
+    Export {
+        /// Session ID or name
         session_id: String,
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Markdown)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export a session's scrollback as styled HTML
+    ExportScrollback {
+        /// Session ID to export
+        session_id: String,
+        /// Number of lines of history to include
+        #[arg(short, long, default_value_t = 1000)]
+        lines: usize,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
     /// Add a project to the server
     AddProject {
@@ -55,13 +269,335 @@ pub enum Commands {
         /// Optional project name (defaults to directory name)
         #[arg(short, long)]
         name: Option<String>,
+        /// Register it as a sub-project of this project (name, ID or parent/child),
+        /// e.g. a package of a monorepo
+        #[arg(long)]
+        parent: Option<String>,
+    },
+    /// Set a project's default agent, arguments and environment, e.g.
+    /// `codemux project set web --agent claude --args "--model sonnet"`
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommands,
+    },
+    /// Remove a project; its running sessions run on without a project
+    /// unless `--kill-sessions`
+    RemoveProject {
+        /// Project name, ID or parent/child
+        project: String,
+        /// End the project's running sessions too
+        #[arg(long)]
+        kill_sessions: bool,
+    },
+    /// Rename a project
+    RenameProject {
+        /// Project name, ID or parent/child
+        project: String,
+        /// New name
+        name: String,
+    },
+    /// Add every git repository below a directory as a project, skipping
+    /// those already added
+    Scan {
+        /// Directory to search
+        dir: PathBuf,
+        /// How many directories deep to look
+        #[arg(long, default_value_t = crate::core::project_scan::DEFAULT_SCAN_DEPTH)]
+        max_depth: usize,
+        /// Only show which repositories would be added
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List sessions by project, e.g. `codemux list --agent claude --status running`
+    List {
+        /// Only sessions of this agent
+        #[arg(long)]
+        agent: Option<String>,
+        /// Only sessions with this status: running, exited or killed
+        #[arg(long)]
+        status: Option<String>,
+        /// Only this project (name, ID or parent/child)
+        #[arg(long)]
+        project: Option<String>,
+        /// Projects per page
+        #[arg(long)]
+        page_size: Option<usize>,
+        /// Page to show, starting at 1
+        #[arg(long, requires = "page_size")]
+        page: Option<usize>,
     },
-    /// List all sessions
-    List,
     /// List all projects
     ListProjects,
+    /// List starred and recently attached sessions and starred projects
+    Recent,
+    /// Pause notification alerts, e.g. `codemux dnd on --for 2h`
+    Dnd {
+        #[command(subcommand)]
+        command: DndCommands,
+    },
+    /// Manage the agent config files (CLAUDE.md, settings, ...) written into projects
+    AgentConfig {
+        #[command(subcommand)]
+        command: AgentConfigCommands,
+    },
+    /// Print a Markdown summary of the sessions of the last day
+    Digest {
+        /// Hours to cover
+        #[arg(long, default_value_t = 24)]
+        hours: u32,
+    },
+    /// Show the tokens and cost the agents of running sessions have used
+    ///
+    /// Totals come from the agents' transcripts, so agents codemux can't read
+    /// transcripts of count as zero.
+    Usage {
+        /// Only this session (ID or name) instead of every session per project
+        session_id: Option<String>,
+    },
+    /// Find sessions by what was said or shown in them, e.g. `codemux search rate limiter`
+    ///
+    /// Looks through agent transcripts, the scrollback of running sessions and
+    /// handover notes; every word has to occur.
+    Search {
+        /// Words to look for
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Most sessions to show
+        #[arg(short, long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// List and export recordings of sessions started with `--record`
+    Recordings {
+        #[command(subcommand)]
+        command: RecordingsCommands,
+    },
+    /// List or restore the git checkpoints taken after each agent turn (`[checkpoints]`)
+    Checkpoints {
+        #[command(subcommand)]
+        command: CheckpointsCommands,
+    },
+    /// Bundle a project's session history, transcripts, recordings and checkpoints into one
+    /// `.tar.zst`, e.g. for retention or when a teammate takes the project over
+    Archive {
+        /// Project ID or name
+        #[arg(long)]
+        project: String,
+        /// File to write (default: <project>.tar.zst)
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// Sign in to a server and keep its token in the OS keychain
+    Login {
+        /// Server URL, e.g. https://codemux.example.com (default: `[client] server_url` or the local server)
+        server: Option<String>,
+        /// Read a token from stdin, e.g. the server's own, instead of signing in through the browser
+        #[arg(long)]
+        with_token: bool,
+    },
+    /// Forget the token `codemux login` kept for a server
+    Logout {
+        /// Server URL (default: `[client] server_url` or the local server)
+        server: Option<String>,
+    },
+    /// Manage API tokens with limited access, e.g. `codemux token create --scope read --ttl 7d` for CI
+    Token {
+        #[command(subcommand)]
+        command: TokenCommands,
+    },
     /// Stop the server
     Stop,
+    /// Check the config file, data directories and server for problems
+    Doctor,
+    /// Tools for working on codemux itself
+    #[command(hide = true)]
+    Dev {
+        #[command(subcommand)]
+        command: DevCommands,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TmuxCommands {
+    /// Give every running session a tmux window and close the windows of ended ones
+    ///
+    /// Inside tmux the windows go into the current tmux session, otherwise
+    /// into one named `codemux`.
+    Sync,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ShimCommands {
+    /// Put an `<agent>` wrapper in the shim directory that runs `codemux run <agent>`
+    Install {
+        /// Agent to wrap
+        agent: String,
+        /// Replace an existing file that is not a codemux shim
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove an agent's wrapper
+    Uninstall {
+        /// Agent whose wrapper to remove
+        agent: String,
+    },
+    /// Show installed wrappers and whether they are active in PATH
+    List,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum AgentConfigCommands {
+    /// Edit a template in $EDITOR; it's written into projects when their next session starts
+    Edit {
+        /// Path in the project, e.g. `.claude/settings.json` (defaults to the agent's instructions file)
+        file: Option<PathBuf>,
+        /// Agent the template is for
+        #[arg(short, long, default_value = "claude")]
+        agent: String,
+    },
+    /// Show the templates, their version and the version each project has
+    List,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DndCommands {
+    /// Stop alerts; notifications are still recorded
+    On {
+        /// Switch back off after this long, e.g. `90m`, `2h` or `1h30m`
+        #[arg(long = "for", value_parser = parse_duration)]
+        duration: Option<Duration>,
+    },
+    /// Resume alerts
+    Off,
+    /// Show whether do-not-disturb is on
+    Status,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum TokenCommands {
+    /// Create a token and print its secret, which is shown only once
+    Create {
+        /// Name to tell the token apart by, e.g. `github-actions` (default: one with the date)
+        #[arg(long)]
+        name: Option<String>,
+        /// read, sessions:write or admin
+        #[arg(long, default_value = "read")]
+        scope: TokenScope,
+        /// Expire after this long, e.g. `12h` or `7d` (default: never)
+        #[arg(long, value_parser = parse_duration)]
+        ttl: Option<Duration>,
+    },
+    /// Show the tokens, without their secrets
+    List,
+    /// Revoke a token by ID or name
+    Revoke { token: String },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CheckpointsCommands {
+    /// Show a session's checkpoints, oldest first
+    List {
+        /// Session ID or name
+        session_id: String,
+    },
+    /// Put the session's files back the way they were at a checkpoint; the files
+    /// as they are now are checkpointed first
+    Restore {
+        /// Session ID or name
+        session_id: String,
+        /// Checkpoint name, e.g. `turn-3`, or commit (default: the latest)
+        checkpoint: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DevCommands {
+    /// Print example API documents for developing the web app without a server
+    Fixtures {
+        /// Write each document to `<name>.json` in this directory instead
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ProjectCommands {
+    /// Change the defaults sessions in the project start with
+    Set {
+        /// Project name, ID or parent/child
+        project: String,
+        /// Agent `codemux run --project` starts without one given; "" unsets it
+        #[arg(long)]
+        agent: Option<String>,
+        /// Arguments for sessions of the default agent started without any,
+        /// separated by spaces; "" unsets them
+        #[arg(long, allow_hyphen_values = true)]
+        args: Option<String>,
+        /// Environment variable for every session in the project, as NAME=value;
+        /// NAME= unsets it. Repeat for more
+        #[arg(long = "env", value_parser = parse_env_var)]
+        env: Vec<(String, String)>,
+        /// Start from no defaults instead of the current ones
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Show a project's defaults
+    Show {
+        /// Project name, ID or parent/child
+        project: String,
+    },
+}
+
+/// Parse `NAME=value`
+fn parse_env_var(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=value, got {:?}", value)),
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RecordingsCommands {
+    /// Show the recorded sessions
+    List,
+    /// Write a session's recording as one JSON file, e.g. to attach to a bug report
+    Export {
+        /// Session ID, or the start of it
+        session_id: String,
+        /// File to write (default: <session-id>.json)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Parse durations such as `45m`, `2h`, `1h30m` or `1d`
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for ch in value.trim().chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(format!("unknown unit `{}` in {:?}", ch, value)),
+        };
+        let amount: u64 = digits
+            .parse()
+            .map_err(|_| format!("expected a number before `{}` in {:?}", ch, value))?;
+        total += amount * unit;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(format!(
+            "expected a duration such as 30m or 2h, got {:?}",
+            value
+        ));
+    }
+    Ok(Duration::from_secs(total))
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -74,9 +610,32 @@ pub enum ServerCommands {
         /// Run server in background (detached)
         #[arg(short, long)]
         detach: bool,
+        /// Stay in the foreground, the default; spelled out for container entrypoints
+        #[arg(long, conflicts_with = "detach")]
+        foreground: bool,
+        /// Don't write the PID file, e.g. as a container's PID 1; `server upgrade`
+        /// needs it
+        #[arg(long, conflicts_with = "detach")]
+        no_pid_file: bool,
+        /// Take over the port and sessions of the running server (used by `server upgrade`)
+        #[arg(long, hide = true)]
+        takeover: bool,
     },
     /// Show server status
     Status,
     /// Stop the server
     Stop,
+    /// Replace the running server with a new binary without ending its sessions
+    Upgrade {
+        /// New codemux binary; defaults to this one, e.g. after a package upgrade
+        #[arg(long)]
+        binary: Option<PathBuf>,
+    },
+    /// Show or change the running server's log level without restarting it
+    LogLevel {
+        /// `trace`, `debug`, `info`, `warn`, `error` or `off` for codemux, or
+        /// `RUST_LOG` directives like `codemux=debug,tower_http=debug`; shows the
+        /// current filter when left out
+        level: Option<String>,
+    },
 }
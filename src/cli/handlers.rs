@@ -1,26 +1,192 @@
 // Command handlers - placeholder implementations
 // TODO: Move actual implementations from old main.rs
 
-use crate::cli::ServerCommands;
-use crate::client::{CodeMuxClient, SessionTui};
-use crate::server::{manager::SessionManagerHandle, start_web_server};
+use crate::cli::{
+    AgentConfigCommands, CheckpointsCommands, DevCommands, DndCommands, ProjectCommands,
+    RecordingsCommands, ServerCommands, ShimCommands, TmuxCommands, TokenCommands,
+};
+use crate::client::http::CreateSessionRequest;
+#[cfg(feature = "tui-client")]
+use crate::client::keybindings::Keybindings;
+use crate::client::raw_attach::{self, RawAttachEnd};
+use crate::client::tmux::{self, TmuxSession};
+use crate::client::CodeMuxClient;
+#[cfg(feature = "tui-client")]
+use crate::client::SessionTui;
+use crate::core::accessible::AccessibleEvent;
+use crate::core::adopt;
+use crate::core::agent_adapter;
+use crate::core::agent_config;
+use crate::core::agent_version::{check_agent_version, AgentConfig, VersionCheck};
+use crate::core::config::PROFILE_ENV;
+use crate::core::credentials::{Credential, CredentialStore};
+use crate::core::dirs::DirKind;
+use crate::core::fixtures;
+use crate::core::listing::ListQuery;
+use crate::core::process;
+use crate::core::project_groups;
+use crate::core::project_metadata::ProjectMetadata;
+use crate::core::project_scan;
+use crate::core::render::ExportFormat;
+use crate::core::search::SearchSource;
+use crate::core::session::{ProjectDefaults, SessionType, DEMO_AGENT};
+use crate::core::shim::{self, ShimPrecedence};
+use crate::core::transcript::TranscriptStats;
+use crate::core::ServerMessage;
+use crate::core::SessionResource;
+#[cfg(feature = "server")]
+use crate::server::lifecycle::{self, InstanceLock};
+#[cfg(feature = "server")]
+use crate::server::{handover, manager::SessionManagerHandle, serve_listener};
 use crate::utils::tui_writer::LogEntry;
 use crate::{Config, Result};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub struct RunSessionParams {
     pub config: Config,
-    pub agent: String,
+    /// `None` for the project's default agent
+    pub agent: Option<String>,
     pub open: bool,
     pub continue_session: bool,
     pub resume_session: Option<String>,
     pub project: Option<String>,
     pub logfile: Option<PathBuf>,
+    pub present: bool,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub name: Option<String>,
+    pub cwd: Option<String>,
+    pub record: bool,
+    pub keep_alive: bool,
+    /// Open the session in a tmux window instead of the TUI
+    pub tmux: bool,
     pub args: Vec<String>,
     pub log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
 }
 
+/// Fail with a clear message when this binary was built without a cargo feature
+fn require_feature(enabled: bool, feature: &str, what: &str) -> Result<()> {
+    if !enabled {
+        anyhow::bail!(
+            "{} needs the `{}` feature, which this codemux build was compiled without",
+            what,
+            feature
+        );
+    }
+    Ok(())
+}
+
+/// Start the server as an independent process unless it is already running,
+/// and return a client for it
+///
+/// The client is made once the server runs, so it has the token a newly
+/// started server generated.
+async fn ensure_server_running(config: &Config) -> Result<CodeMuxClient> {
+    let client = CodeMuxClient::from_config(config)?;
+    if client.is_server_running().await {
+        return Ok(client);
+    }
+    // A server on another machine can't be started from here
+    if let Some(url) = &config.client.server_url {
+        anyhow::bail!("The codemux server at {} can't be reached", url);
+    }
+    require_feature(
+        cfg!(feature = "server"),
+        "server",
+        "No codemux server is running, and starting one",
+    )?;
+
+    tracing::info!("🚀 Starting CodeMux server as independent process...");
+
+    // Start server as independent process using current executable
+    let current_exe = std::env::current_exe()
+        .map_err(|e| anyhow::anyhow!("Failed to get current executable path: {}", e))?;
+
+    let mut cmd = tokio::process::Command::new(&current_exe);
+    cmd.args(["server", "start"]);
+
+    // Pass through RUST_LOG environment variable
+    if let Ok(rust_log) = std::env::var("RUST_LOG") {
+        cmd.env("RUST_LOG", rust_log);
+    }
+    // The server has to use the profile's data dir too
+    if let Some(profile) = &config.profile {
+        cmd.env(PROFILE_ENV, profile);
+    }
+    // Keep the server out of this terminal's Ctrl+C
+    process::detach(&mut cmd);
+
+    // Spawn the server process
+    let child = cmd
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to spawn server process: {}", e))?;
+
+    tracing::info!(
+        "Spawned server process with PID: {}",
+        child.id().unwrap_or(0)
+    );
+
+    // Wait a moment for server to start
+    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+
+    // Verify server is now running
+    if !client.is_server_running().await {
+        anyhow::bail!(
+            "Failed to start server process. Please run 'codemux server start' manually."
+        );
+    }
+
+    tracing::info!("✅ Server process started successfully");
+    CodeMuxClient::from_config(config)
+}
+
+/// Default agent of the project given with `--project`, else of the project
+/// in the current directory
+async fn default_agent(client: &CodeMuxClient, project: Option<&str>) -> Result<String> {
+    let projects = client.list_projects().await?;
+    let found = match project {
+        Some(project) => project_groups::find_project(&projects, project),
+        None => {
+            let here = std::env::current_dir()?;
+            projects.iter().find(|p| {
+                p.attributes
+                    .as_ref()
+                    .is_some_and(|attributes| PathBuf::from(&attributes.path) == here)
+            })
+        }
+    };
+    found
+        .and_then(|project| {
+            project
+                .attributes
+                .as_ref()?
+                .defaults
+                .as_ref()?
+                .agent
+                .clone()
+        })
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No agent given and the project has no default agent; \
+                 set one with: codemux project set <project> --agent <agent>"
+            )
+        })
+}
+
+/// Directory of a registered project given by ID, name or `parent/child`, or an existing path
+async fn resolve_project_dir(client: &CodeMuxClient, project: &str) -> Result<PathBuf> {
+    let projects = client.list_projects().await?;
+    let registered = project_groups::find_project(&projects, project)
+        .and_then(|p| Some(PathBuf::from(&p.attributes.as_ref()?.path)));
+    match registered {
+        Some(path) => Ok(path),
+        None => PathBuf::from(project)
+            .canonicalize()
+            .map_err(|_| anyhow::anyhow!("No project or directory named '{}'", project)),
+    }
+}
+
 pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
     let RunSessionParams {
         config,
@@ -28,13 +194,31 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
         open,
         continue_session,
         resume_session,
-        project: _project,
+        project,
         logfile: _logfile, // Logfile handling is done in main.rs tracing setup
+        present,
+        color,
+        icon,
+        name,
+        cwd,
+        record,
+        keep_alive,
+        tmux,
         args,
         log_rx,
     } = params;
 
     tracing::info!("=== ENTERING run_client_session ===");
+
+    // Without an agent, the project's default one runs
+    let (agent, client) = match agent {
+        Some(agent) => (agent, None),
+        None => {
+            let client = ensure_server_running(&config).await?;
+            let agent = default_agent(&client, project.as_deref()).await?;
+            (agent, Some(client))
+        }
+    };
     tracing::info!(
         "Agent: {}, Open: {}, Continue: {}, Resume: {:?}",
         agent,
@@ -45,7 +229,7 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
     tracing::info!("Args: {:?}", args);
 
     tracing::debug!("Checking if agent '{}' is whitelisted", agent);
-    if !config.is_agent_allowed(&agent) {
+    if agent != DEMO_AGENT && !config.is_agent_allowed(&agent) {
         tracing::error!("Agent '{}' is not whitelisted in config", agent);
         anyhow::bail!(
             "Code agent '{}' is not whitelisted. Add it to the config to use.",
@@ -57,46 +241,10 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
     tracing::info!("=== CONNECTING TO SERVER ===");
 
     // Create HTTP client
-    let client = CodeMuxClient::from_config(&config);
-
-    // Check if server is running, start it if not
-    if !client.is_server_running().await {
-        tracing::info!("🚀 Starting CodeMux server as independent process...");
-
-        // Start server as independent process using current executable
-        let current_exe = std::env::current_exe()
-            .map_err(|e| anyhow::anyhow!("Failed to get current executable path: {}", e))?;
-
-        let mut cmd = tokio::process::Command::new(&current_exe);
-        cmd.args(["server", "start"]);
-
-        // Pass through RUST_LOG environment variable
-        if let Ok(rust_log) = std::env::var("RUST_LOG") {
-            cmd.env("RUST_LOG", rust_log);
-        }
-
-        // Spawn the server process
-        let child = cmd
-            .spawn()
-            .map_err(|e| anyhow::anyhow!("Failed to spawn server process: {}", e))?;
-
-        tracing::info!(
-            "Spawned server process with PID: {}",
-            child.id().unwrap_or(0)
-        );
-
-        // Wait a moment for server to start
-        tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-
-        // Verify server is now running
-        if !client.is_server_running().await {
-            anyhow::bail!(
-                "Failed to start server process. Please run 'codemux server start' manually."
-            );
-        }
-
-        tracing::info!("✅ Server process started successfully");
-    }
+    let client = match client {
+        Some(client) => client,
+        None => ensure_server_running(&config).await?,
+    };
 
     // Validate that both --continue and --resume aren't used together
     if continue_session && resume_session.is_some() {
@@ -134,21 +282,25 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
 
     // Prepare agent arguments with session continuation info
     let mut agent_args = args;
-    if agent.to_lowercase() == "claude" && is_continuing {
-        if let Some(prev_id) = &previous_session_id {
-            // Specific session ID provided (from --resume flag)
-            tracing::info!("Adding --resume flag with session ID to Claude agent args");
-            agent_args.push("--resume".to_string());
-            agent_args.push(prev_id.clone());
-        } else if continue_session {
-            // --continue flag: let server/Claude find the most recent session
-            tracing::info!("Adding --continue flag to Claude agent args");
-            agent_args.push("--continue".to_string());
+    if is_continuing {
+        let conversation = previous_session_id.as_deref();
+        match agent_adapter::resume_args(&agent, config.agents.get(&agent), conversation) {
+            // For Claude's --continue the server finds the most recent session
+            Some(resume_args) => agent_args.extend(resume_args),
+            // Agents codemux doesn't know get the flags as they were given
+            None => match &previous_session_id {
+                Some(prev_id) => agent_args.extend(["--resume".to_string(), prev_id.clone()]),
+                None => agent_args.push("--continue".to_string()),
+            },
         }
+        tracing::info!("Continuing {} with args {:?}", agent, agent_args);
     }
 
-    // Get current directory path
-    let current_dir = std::env::current_dir()?;
+    // Run in the requested project (from --project or CODEMUX_PROJECT), else here
+    let current_dir = match &project {
+        Some(project) => resolve_project_dir(&client, project).await?,
+        None => std::env::current_dir()?,
+    };
     let current_path = current_dir.to_string_lossy().to_string();
 
     // Create session on server
@@ -160,10 +312,19 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
         current_path
     );
 
-    let session_info = match client
-        .create_session_with_path(agent.clone(), agent_args.clone(), current_path)
-        .await
-    {
+    let request = CreateSessionRequest {
+        agent: agent.clone(),
+        args: agent_args.clone(),
+        project_id: None,
+        path: Some(current_path),
+        color,
+        icon,
+        name,
+        cwd: cwd.clone(),
+        record,
+        keep_alive,
+    };
+    let session_info = match client.send_create_session(&request).await {
         Ok(info) => {
             tracing::info!(
                 "✅ Session created successfully on server with ID: {}",
@@ -184,11 +345,11 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
     println!("🔄 Session created - WebSocket will connect when entering interactive mode");
 
     // Create session info for TUI
-    let working_dir = env::current_dir()
-        .unwrap_or_else(|_| PathBuf::from("unknown"))
-        .display()
-        .to_string();
-    let url = format!("http://localhost:{}/session/{}", crate::core::config::default_server_port(), session_id);
+    let working_dir = match &cwd {
+        Some(cwd) => current_dir.join(cwd).display().to_string(),
+        None => current_dir.display().to_string(),
+    };
+    let url = client.get_session_url(&session_id);
 
     // Print session info
     if is_continuing {
@@ -200,6 +361,13 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
         println!("\n🚀 CodeMux - {} Agent Session", agent.to_uppercase());
     }
     println!("📋 Session ID: {}", session_id);
+    if let Some(name) = session_info
+        .attributes
+        .as_ref()
+        .and_then(|a| a.name.as_deref())
+    {
+        println!("🏷️  Name: {}", name);
+    }
     println!("🌐 Web Interface: {}", url);
     println!("📁 Working Directory: {}", working_dir);
 
@@ -224,6 +392,15 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
             "   History will be in: ~/.claude/projects/{}/",
             project_path
         );
+    } else if let Some(adapter) = agent_adapter::adapter_for(
+        &agent,
+        config.agents.get(&agent).and_then(|a| a.transcript.as_ref()),
+    ) {
+        let home = PathBuf::from(env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()));
+        println!(
+            "💡 History will be in: {}",
+            adapter.transcript_dir(&home, &current_dir).display()
+        );
     }
 
     // Open URL if requested
@@ -235,70 +412,499 @@ pub async fn run_client_session(params: RunSessionParams) -> Result<()> {
         } else {
             println!("✅ Web interface opened in your default browser");
         }
-    } else {
+    } else if cfg!(feature = "tui-client") && !tmux {
         println!("\n💡 Press 'o' in monitoring mode to open the web interface");
     }
 
-    // Try to start TUI, fall back to simple display if it fails
-    tracing::info!("Attempting to create TUI...");
-    match SessionTui::new(session_id.clone()) {
-        Ok(mut tui) => {
-            tracing::info!("TUI created successfully");
-            // Run TUI in a separate task
-            let tui_session_info = crate::client::tui::SessionInfo {
-                id: session_id.clone(),
-                agent: agent.clone(),
-                _port: crate::core::config::default_server_port(),
-                working_dir,
-                url: url.clone(),
-            };
+    if tmux {
+        let label = tmux_window_name(&session_info);
+        let window = TmuxSession {
+            id: &session_id,
+            label: &label,
+        };
+        return tmux::open(&window, config.profile.as_deref());
+    }
+
+    open_session_tui(
+        &config,
+        &client,
+        &session_info,
+        working_dir,
+        present,
+        log_rx,
+    )
+    .await?;
+
+    // Clean up session - PTY session will be cleaned up when dropped
+    tracing::info!("Session {} finished", session_id);
+
+    Ok(())
+}
+
+/// Name of a session's tmux window: its name, or the agent and a short ID
+fn tmux_window_name(session: &SessionResource) -> String {
+    let attributes = session.attributes.as_ref();
+    match attributes.and_then(|a| a.name.clone()) {
+        Some(name) => name,
+        None => format!(
+            "{}-{}",
+            attributes.map(|a| a.agent.as_str()).unwrap_or("session"),
+            session.id.chars().take(8).collect::<String>()
+        ),
+    }
+}
+
+pub async fn handle_tmux_command(config: Config, command: TmuxCommands) -> Result<()> {
+    match command {
+        TmuxCommands::Sync => tmux_sync(config).await,
+    }
+}
+
+async fn tmux_sync(config: Config) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let sessions: Vec<SessionResource> = client
+        .list_sessions()
+        .await?
+        .into_iter()
+        .filter(|session| {
+            session
+                .attributes
+                .as_ref()
+                .is_some_and(|a| matches!(a.session_type, SessionType::Active))
+        })
+        .collect();
+    let names: Vec<String> = sessions.iter().map(tmux_window_name).collect();
+    let windows: Vec<TmuxSession> = sessions
+        .iter()
+        .zip(&names)
+        .map(|(session, name)| TmuxSession {
+            id: &session.id,
+            label: name,
+        })
+        .collect();
+    let report = tmux::sync(&windows, config.profile.as_deref())?;
+
+    println!(
+        "🪟 {} session(s) in tmux session '{}': {} opened, {} closed",
+        windows.len(),
+        report.target,
+        report.opened.len(),
+        report.closed.len()
+    );
+    if !tmux::inside_tmux() {
+        println!("💡 Switch to them with: tmux attach -t {}", report.target);
+    }
+    Ok(())
+}
 
-            let tui_handle = tokio::spawn(async move { tui.run(tui_session_info, log_rx).await });
+/// Run the TUI on a session until it exits or Ctrl+C, or wait for Ctrl+C
+/// without it
+async fn open_session_tui(
+    config: &Config,
+    client: &CodeMuxClient,
+    session: &SessionResource,
+    working_dir: String,
+    present: bool,
+    log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
+) -> Result<()> {
+    let url = client.get_session_url(&session.id);
+    #[cfg(feature = "tui-client")]
+    let attributes = session.attributes.as_ref();
 
-            // Wait for either Ctrl+C or TUI to exit
-            tokio::select! {
-                _ = tokio::signal::ctrl_c() => {
-                    // Don't print here - TUI is still active
+    // Try to start TUI, fall back to simple display if it fails
+    #[cfg(feature = "tui-client")]
+    let fallback_reason = {
+        let keybindings = Keybindings::from_config(&config.keybindings).unwrap_or_else(|e| {
+            eprintln!("⚠️  Invalid key bindings, using defaults: {}", e);
+            Keybindings::default()
+        });
+
+        tracing::info!("Attempting to create TUI...");
+        match SessionTui::new(session.id.clone()) {
+            Ok(mut tui) => {
+                tracing::info!("TUI created successfully");
+                tui.set_crash_dump_data_dir(config.server.data_dir.clone());
+                tui.set_presentation_mode(present);
+                tui.set_keybindings(keybindings);
+                tui.set_paste_config(config.paste.clone());
+                tui.set_client(client.clone());
+
+                // Show the tour on the first attach only
+                let tour_marker = config
+                    .server
+                    .data_dir
+                    .join(crate::client::tui::TOUR_MARKER_FILE);
+                if !present && !tour_marker.exists() {
+                    tui.start_tour();
+                    if let Err(e) = std::fs::create_dir_all(&config.server.data_dir)
+                        .and_then(|_| std::fs::write(&tour_marker, ""))
+                    {
+                        tracing::warn!("Failed to record that the tour was shown: {}", e);
+                    }
                 }
-                result = tui_handle => {
-                    // TUI has exited, safe to print after cleanup
-                    match result {
-                        Ok(Ok(_)) => {}, // Normal exit
-                        Ok(Err(e)) => tracing::error!("TUI error: {}", e),
-                        Err(e) => tracing::error!("TUI task error: {}", e),
+                match client.get_preferences().await {
+                    Ok(preferences) => tui.set_preferences(preferences),
+                    Err(e) => tracing::warn!("Using default display preferences: {}", e),
+                }
+                // Run TUI in a separate task
+                let tui_session_info = crate::client::tui::SessionInfo {
+                    id: session.id.clone(),
+                    agent: attributes.map(|a| a.agent.clone()).unwrap_or_default(),
+                    _port: crate::core::config::default_server_port(),
+                    working_dir,
+                    url: url.clone(),
+                    color: attributes.map(|a| a.color.clone()).unwrap_or_default(),
+                    icon: attributes.map(|a| a.icon.clone()).unwrap_or_default(),
+                    note: attributes.and_then(|a| a.note.clone()),
+                };
+
+                let tui_handle =
+                    tokio::spawn(async move { tui.run(tui_session_info, log_rx).await });
+
+                // Wait for either Ctrl+C or TUI to exit
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {
+                        // Don't print here - TUI is still active
+                    }
+                    result = tui_handle => {
+                        // TUI has exited, safe to print after cleanup
+                        match result {
+                            Ok(Ok(_)) => {}, // Normal exit
+                            Ok(Err(e)) => tracing::error!("TUI error: {}", e),
+                            Err(e) => tracing::error!("TUI task error: {}", e),
+                        }
                     }
                 }
+
+                // TUI has cleaned up, now safe to print
+                let address = attributes
+                    .and_then(|a| a.name.clone())
+                    .unwrap_or_else(|| session.id.clone());
+                eprintln!("\n👋 Detached; the session keeps running on the server");
+                eprintln!("💡 Attach again with: codemux attach {}", address);
+                None
             }
+            Err(e) => {
+                tracing::error!("TUI creation failed: {}", e);
+                Some(format!("Enhanced TUI not available: {}", e))
+            }
+        }
+    };
+    #[cfg(not(feature = "tui-client"))]
+    let fallback_reason = {
+        let _ = (config, client, working_dir, log_rx, present);
+        Some("This codemux build has no TUI (tui-client feature)".to_string())
+    };
+
+    if let Some(reason) = fallback_reason {
+        eprintln!("\n⚠️  {}", reason);
+        eprintln!("📺 Using simple mode (press Ctrl+C to stop)");
+        eprintln!("\n┌─────────────────────────────────────────┐");
+        eprintln!("│  ⚡ Status: Running                     │");
+        eprintln!("│  🌐 Web UI: {:<23} │", url);
+        eprintln!("└─────────────────────────────────────────┘");
+
+        // Simple fallback - just wait for Ctrl+C
+        tokio::signal::ctrl_c().await?;
+        eprintln!("\nShutting down...");
+    }
+
+    Ok(())
+}
+
+/// Start a session running the simulated agent and open the TUI on it
+pub async fn run_demo(
+    config: Config,
+    open: bool,
+    present: bool,
+    recording: Option<PathBuf>,
+    speed: f64,
+    log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
+) -> Result<()> {
+    let mut args = vec!["--speed".to_string(), speed.to_string()];
+    if let Some(recording) = recording {
+        // The agent runs in the server's PTY, so it needs a path that works from anywhere
+        let recording = std::fs::canonicalize(&recording).map_err(|e| {
+            anyhow::anyhow!("Failed to find recording {}: {}", recording.display(), e)
+        })?;
+        args.push("--recording".to_string());
+        args.push(recording.to_string_lossy().to_string());
+    }
+
+    run_client_session(RunSessionParams {
+        config,
+        agent: Some(DEMO_AGENT.to_string()),
+        open,
+        continue_session: false,
+        resume_session: None,
+        project: None,
+        logfile: None,
+        present,
+        color: None,
+        icon: None,
+        name: None,
+        cwd: None,
+        record: false,
+        keep_alive: false,
+        tmux: false,
+        args,
+        log_rx,
+    })
+    .await
+}
+
+/// Start a session playing back a recording and open the TUI on it
+///
+/// The recording is played by the demo agent in replay mode, so the session
+/// can be watched and sought from the TUI and the web UI alike.
+pub async fn run_replay(
+    config: Config,
+    recording: PathBuf,
+    open: bool,
+    speed: f64,
+    log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
+) -> Result<()> {
+    if speed <= 0.0 {
+        return Err(anyhow::anyhow!("The speed must be above 0"));
+    }
+    let recording = find_recording(&config, &recording)?;
+    let name = recording
+        .file_stem()
+        .map(|stem| format!("replay-{}", stem.to_string_lossy()));
+
+    run_client_session(RunSessionParams {
+        config,
+        agent: Some(DEMO_AGENT.to_string()),
+        open,
+        continue_session: false,
+        resume_session: None,
+        project: None,
+        logfile: None,
+        present: false,
+        color: None,
+        icon: None,
+        name,
+        cwd: None,
+        record: false,
+        keep_alive: false,
+        tmux: false,
+        args: vec![
+            "--replay".to_string(),
+            "--speed".to_string(),
+            speed.to_string(),
+            "--recording".to_string(),
+            recording.to_string_lossy().to_string(),
+        ],
+        log_rx,
+    })
+    .await
+}
+
+/// Absolute path of a recording file, or of the recording of a session
+/// started with `--record`
+fn find_recording(config: &Config, recording: &Path) -> Result<PathBuf> {
+    // The agent runs in the server's PTY, so it needs a path that works from anywhere
+    if let Ok(path) = std::fs::canonicalize(recording) {
+        return Ok(path);
+    }
+    #[cfg(all(feature = "server", feature = "capture"))]
+    if let Ok(found) = crate::server::recording::find(
+        &config.server.recordings_dir(),
+        &recording.to_string_lossy(),
+    ) {
+        return Ok(found.path);
+    }
+    #[cfg(not(all(feature = "server", feature = "capture")))]
+    let _ = config;
+    Err(anyhow::anyhow!(
+        "No recording file or recorded session {}",
+        recording.display()
+    ))
+}
+
+/// Run the server in this process until it is stopped; SIGTERM and Ctrl+C
+/// end its sessions and it returns, so the process exits with 0
+///
+/// With `takeover`, the sessions and port of the server already running are
+/// taken over and that server is stopped (see `server::handover`).
+#[cfg(feature = "server")]
+async fn serve(config: Config, port: u16, takeover: bool, pid_file: bool) -> Result<()> {
+    use std::io::IsTerminal;
+
+    let ssh_config = config.ssh.clone();
+    let data_dir = config.server.data_dir.clone();
+    let runtime_dir = config.server.runtime_dir.clone();
+    let pid_path = config.server.pid_file();
+    let lock_path = config.server.lock_file();
+    let token_file = config.server.token_file();
+    let socket_path = config.server.socket_path.clone();
+
+    // Held until the server stops; a server taking over gets it once the old one exited
+    crate::core::dirs::create_dir(DirKind::Runtime, &runtime_dir)?;
+    let instance = if takeover {
+        None
+    } else {
+        Some(InstanceLock::acquire(&lock_path)?)
+    };
+
+    let auth_token = crate::core::auth::server_token(&config, takeover);
+    let oidc = config.web.oidc.clone();
+    let storage = crate::server::storage::open(&config).await?;
+    let session_manager = SessionManagerHandle::with_storage(config, storage);
+
+    let socket = handover::bind_shared(port)?;
+    let (listener, taken_over) = if takeover {
+        // Not listening yet, so the handover request can only reach the old server
+        let old_pid = handover::take_over(port, auth_token.as_deref(), &session_manager).await?;
+        let listener = handover::listen(socket)?;
+        handover::stop_old_server(old_pid).await?;
+        println!("🔁 Took over from server PID {}", old_pid);
+        // The sessions are ours now, so an old server that's slow to exit is
+        // no reason to give up
+        let lock = InstanceLock::acquire_within(&lock_path, lifecycle::TAKEOVER_TIMEOUT)
+            .await
+            .inspect_err(|e| tracing::warn!("Running without the server lock: {}", e))
+            .ok();
+        (listener, lock)
+    } else {
+        (handover::listen(socket)?, None)
+    };
+    let _instance = instance.or(taken_over);
+    if let Some(token) = &auth_token {
+        crate::core::auth::write_token(&token_file, token)?;
+    }
+    if pid_file {
+        std::fs::write(&pid_path, std::process::id().to_string())?;
+    }
 
-            // TUI has cleaned up, now safe to print
-            eprintln!("\nShutting down...");
+    if ssh_config.enabled {
+        #[cfg(feature = "ssh-server")]
+        {
+            let ssh_port = ssh_config.port;
+            let ssh_manager = session_manager.clone();
+            tokio::spawn(async move {
+                if let Err(e) = crate::server::ssh::start_ssh_server(
+                    ssh_config,
+                    data_dir,
+                    ssh_manager,
+                )
+                .await
+                {
+                    tracing::error!("SSH server stopped: {}", e);
+                }
+            });
+            println!("🔑 SSH attach available on port {}", ssh_port);
         }
-        Err(e) => {
-            tracing::error!("TUI creation failed: {}", e);
-            eprintln!("\n⚠️  Enhanced TUI not available: {}", e);
-            eprintln!("📺 Using simple mode (press Ctrl+C to stop)");
-            eprintln!("\n┌─────────────────────────────────────────┐");
-            eprintln!("│  ⚡ Status: Running                     │");
-            eprintln!("│  🌐 Web UI: {:<23} │", url);
-            eprintln!("└─────────────────────────────────────────┘");
+        #[cfg(not(feature = "ssh-server"))]
+        {
+            let _ = data_dir;
+            println!("⚠️  ssh.enabled is set but this build lacks the ssh-server feature");
+        }
+    }
 
-            // Simple fallback - just wait for Ctrl+C
-            tokio::signal::ctrl_c().await?;
-            eprintln!("\nShutting down...");
+    println!("🚀 CodeMux server starting on http://localhost:{}", port);
+    match &auth_token {
+        // Keep the token out of the log file of a detached server
+        Some(token) if std::io::stdout().is_terminal() => println!(
+            "🌐 Web interface: http://localhost:{}/?token={}",
+            port, token
+        ),
+        Some(_) => println!("🔑 API token in {}", token_file.display()),
+        None => println!("⚠️  Authentication is off ([web] auth = false)"),
+    }
+    match &oidc {
+        Some(oidc) if auth_token.is_some() => {
+            println!("🔐 Sign-in through {} at /auth/login", oidc.issuer)
         }
+        Some(_) => println!("⚠️  [web.oidc] is ignored while [web] auth = false"),
+        None => {}
+    }
+    println!("💡 Use Ctrl+C to stop the server, or 'codemux server start -d' to run in background");
+
+    #[cfg(unix)]
+    if let Some(path) = socket_path {
+        // Bound after a takeover, so the old server's socket is replaced once it stopped
+        let socket = crate::server::bind_socket(&path)?;
+        println!("🔌 Local clients connect through {}", path.display());
+        return crate::server::serve_with_socket(
+            listener,
+            socket,
+            session_manager,
+            auth_token,
+            oidc,
+        )
+        .await;
+    }
+    #[cfg(not(unix))]
+    if socket_path.is_some() {
+        println!("⚠️  [server] socket_path is ignored on this platform");
     }
+    serve_listener(listener, session_manager, auth_token, oidc).await
+}
 
-    // Clean up session - PTY session will be cleaned up when dropped
-    tracing::info!("Session {} finished", session_id);
+#[cfg(not(feature = "server"))]
+async fn serve(_config: Config, _port: u16, _takeover: bool, _pid_file: bool) -> Result<()> {
+    require_feature(false, "server", "Starting a server")
+}
 
-    Ok(())
+/// Start `server start` of `exe` in the background, logging to the log dir
+fn spawn_detached_server(
+    config: &Config,
+    exe: &std::path::Path,
+    port: u16,
+    takeover: bool,
+) -> Result<(tokio::process::Child, PathBuf)> {
+    let mut cmd = tokio::process::Command::new(exe);
+    cmd.args(["server", "start", "--port", &port.to_string()]);
+    if takeover {
+        cmd.arg("--takeover");
+    }
+
+    // Pass through RUST_LOG environment variable
+    if let Ok(rust_log) = std::env::var("RUST_LOG") {
+        cmd.env("RUST_LOG", rust_log);
+    }
+    // The server has to use the profile's data dir too
+    if let Some(profile) = &config.profile {
+        cmd.env(PROFILE_ENV, profile);
+    }
+
+    // A detached server has no terminal, so its output goes to the log dir
+    let log_path = config.server.log_dir.join("server.log");
+    crate::core::dirs::create_dir(DirKind::Log, &config.server.log_dir)?;
+    let log_file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+    cmd.stdout(log_file.try_clone()?).stderr(log_file);
+    process::detach(&mut cmd);
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to spawn detached server: {}", e))?;
+    Ok((child, log_path))
 }
 
 pub async fn handle_server_command(config: Config, command: Option<ServerCommands>) -> Result<()> {
-    let client = CodeMuxClient::from_config(&config);
+    let client = CodeMuxClient::from_config(&config)?;
 
     match command {
-        Some(ServerCommands::Start { port, detach }) => {
+        Some(ServerCommands::Start {
+            port,
+            detach,
+            foreground: _,
+            no_pid_file,
+            takeover,
+        }) => {
+            if takeover {
+                return serve(config, port, true, true).await;
+            }
             println!("Starting server on port {}...", port);
 
             // Check if server is already running
@@ -308,41 +914,76 @@ pub async fn handle_server_command(config: Config, command: Option<ServerCommand
             }
 
             if detach {
+                require_feature(cfg!(feature = "server"), "server", "Starting a server")?;
+
                 // Start server in background (detached)
                 let current_exe = std::env::current_exe()?;
-                let mut cmd = tokio::process::Command::new(&current_exe);
-                cmd.args(["server", "start", "--port", &port.to_string()]);
-
-                // Pass through RUST_LOG environment variable
-                if let Ok(rust_log) = std::env::var("RUST_LOG") {
-                    cmd.env("RUST_LOG", rust_log);
-                }
-
-                let child = cmd
-                    .spawn()
-                    .map_err(|e| anyhow::anyhow!("Failed to spawn detached server: {}", e))?;
+                let (child, log_path) = spawn_detached_server(&config, &current_exe, port, false)?;
 
                 println!(
                     "🚀 CodeMux server started in background with PID: {}",
                     child.id().unwrap_or(0)
                 );
                 println!("📍 Server will be available at http://localhost:{}", port);
+                println!("📝 Logging to {}", log_path.display());
 
                 // Wait a moment and verify it started
                 tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
                 if client.is_server_running().await {
                     println!("✅ Server is running successfully");
+                    // It wrote its token by now
+                    let client = CodeMuxClient::from_config(&config)?;
+                    println!("🌐 Web interface: {}", client.web_url());
                 } else {
                     println!("⚠️  Server may still be starting up...");
                 }
             } else {
                 // Start server in foreground
-                let session_manager = SessionManagerHandle::new(config);
+                serve(config, port, false, !no_pid_file).await?;
+            }
+        }
+
+        Some(ServerCommands::Upgrade { binary }) => {
+            require_feature(cfg!(feature = "server"), "server", "Upgrading a server")?;
+            if !client.is_server_running().await {
+                anyhow::bail!("No server is running; start one with 'codemux server start'");
+            }
 
-                println!("🚀 CodeMux server starting on http://localhost:{}", port);
-                println!("💡 Use Ctrl+C to stop the server, or 'codemux server start -d' to run in background");
-                start_web_server(port, session_manager).await?;
+            let binary = match binary {
+                Some(binary) => binary,
+                None => std::env::current_exe()?,
+            };
+            let port = config.server.port;
+            let (mut child, log_path) = spawn_detached_server(&config, &binary, port, true)?;
+            let new_pid = child.id().unwrap_or(0);
+            println!(
+                "🔁 Handing the server over to {} (PID {})...",
+                binary.display(),
+                new_pid
+            );
+
+            // The new server writes its PID file once it has taken over and listens
+            let pid_file = config.server.pid_file();
+            for _ in 0..60 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+                if let Some(status) = child.try_wait()? {
+                    anyhow::bail!(
+                        "The new server exited ({}) before taking over; see {}",
+                        status,
+                        log_path.display()
+                    );
+                }
+                let written = std::fs::read_to_string(&pid_file).unwrap_or_default();
+                if written.trim() == new_pid.to_string() {
+                    println!("✅ Server upgraded; sessions kept running");
+                    println!("📝 Logging to {}", log_path.display());
+                    return Ok(());
+                }
             }
+            anyhow::bail!(
+                "The new server has not taken over after 30s; see {}",
+                log_path.display()
+            );
         }
 
         Some(ServerCommands::Status) => {
@@ -350,6 +991,7 @@ pub async fn handle_server_command(config: Config, command: Option<ServerCommand
 
             if client.is_server_running().await {
                 println!("✅ Server is running");
+                println!("🌐 Web interface: {}", client.web_url());
 
                 // Get project list to show more details
                 match client.list_projects().await {
@@ -381,32 +1023,20 @@ pub async fn handle_server_command(config: Config, command: Option<ServerCommand
             }
         }
 
-        Some(ServerCommands::Stop) => {
-            tracing::info!("Stopping server...");
+        Some(ServerCommands::Stop) => stop_server(config).await?,
 
+        Some(ServerCommands::LogLevel { level }) => {
             if !client.is_server_running().await {
-                tracing::info!("❌ Server is not running");
+                println!("❌ Server is not running");
+                println!("💡 Start the server first with: codemux server start");
                 return Ok(());
             }
-
-            match client.shutdown_server().await {
-                Ok(()) => {
-                    tracing::info!("✅ Server shutdown successfully");
-
-                    // Wait a moment for server to shut down
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-
-                    // Verify server is stopped
-                    if !client.is_server_running().await {
-                        tracing::info!("🛑 Server has stopped");
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("❌ Failed to shutdown server: {}", e);
-                    tracing::info!(
-                        "💡 Server may have already stopped or use Ctrl+C to force stop"
-                    );
+            match level {
+                Some(level) => {
+                    let filter = client.set_log_level(&level).await?;
+                    println!("✅ Server now logs with filter {}", filter);
                 }
+                None => println!("📝 Log filter: {}", client.get_log_level().await?),
             }
         }
 
@@ -422,6 +1052,8 @@ pub async fn handle_server_command(config: Config, command: Option<ServerCommand
                 println!("  • codemux server start    - Start the server");
                 println!("  • codemux server status   - Check server status");
                 println!("  • codemux server stop     - Stop the server");
+                println!("  • codemux server upgrade  - Replace the server, keeping its sessions");
+                println!("  • codemux server log-level - Show or change the server's log level");
             }
         }
     }
@@ -430,86 +1062,988 @@ pub async fn handle_server_command(config: Config, command: Option<ServerCommand
 }
 
 pub async fn attach_to_session(
-    _config: Config,
-    _session_id: String,
-    _log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
+    config: Config,
+    session_id: Option<String>,
+    raw: bool,
+    read_only: bool,
+    log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
 ) -> Result<()> {
-    println!("Attach command - implementation needed");
-    Ok(())
+    let session_id = match session_id {
+        Some(session_id) => session_id,
+        None => match pick_recent_session(&config).await? {
+            Some(session_id) => session_id,
+            None => return Ok(()),
+        },
+    };
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    // The server resolves names, the session comes back with its ID
+    let session = client
+        .get_session(&session_id)
+        .await
+        .map_err(|_| anyhow::anyhow!("No session with ID or name '{}'", session_id))?;
+    if raw {
+        // Only the session's output goes to stdout, so it can be piped
+        eprintln!(
+            "📋 Attached to session {} (Ctrl+\\ then d detaches)",
+            session.id
+        );
+        let end = raw_attach::attach_raw(&client, &session.id).await?;
+        match end {
+            RawAttachEnd::Detached => eprintln!("\n📋 Detached from session {}", session.id),
+            RawAttachEnd::Disconnected => eprintln!("\n📋 Session {} disconnected", session.id),
+        }
+        eprintln!("💡 Attach again with: codemux attach {}", session.id);
+        return Ok(());
+    }
+    let working_dir = match session.attributes.as_ref().and_then(|a| a.project.as_ref()) {
+        Some(project) => client
+            .list_projects()
+            .await?
+            .into_iter()
+            .find(|p| &p.id == project)
+            .and_then(|p| p.attributes)
+            .map(|attributes| attributes.path)
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+    if read_only {
+        // Watching is presenting, with the server refusing input as well
+        println!("👀 Watching session {} (read-only)", session.id);
+        let client = client.read_only();
+        return open_session_tui(&config, &client, &session, working_dir, true, log_rx).await;
+    }
+    println!("📋 Attaching to session {}", session.id);
+    open_session_tui(&config, &client, &session, working_dir, false, log_rx).await
 }
 
-// Removed: create_and_attach_session - no longer needed after removing NewSession command
+/// Ask which starred or recent session to attach to
+async fn pick_recent_session(config: &Config) -> Result<Option<String>> {
+    let client = CodeMuxClient::from_config(config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(None);
+    }
 
-pub async fn kill_session(_config: Config, _session_id: String) -> Result<()> {
-    println!("Kill session command - implementation needed");
-    Ok(())
+    let recent = client.get_recent().await?;
+    if recent.sessions.is_empty() {
+        println!("No starred or recent sessions");
+        println!("💡 Pass a session ID: codemux attach <session-id>");
+        return Ok(None);
+    }
+
+    for (index, session) in recent.sessions.iter().enumerate() {
+        println!("  {:>2}. {}", index + 1, describe_quick_session(session));
+    }
+    print!("Attach to [1-{}]: ", recent.sessions.len());
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut choice = String::new();
+    std::io::stdin().read_line(&mut choice)?;
+    match choice.trim().parse::<usize>() {
+        Ok(n) if (1..=recent.sessions.len()).contains(&n) => {
+            Ok(Some(recent.sessions[n - 1].id.clone()))
+        }
+        _ => {
+            println!("No session selected");
+            Ok(None)
+        }
+    }
 }
 
-pub async fn add_project(config: Config, path: PathBuf, name: Option<String>) -> Result<()> {
-    let client = CodeMuxClient::from_config(&config);
+/// Minutes until an idle session is ended, when that's soon enough to warn about
+fn idle_minutes_left(expires_at: &str) -> Option<i64> {
+    let expires_at = chrono::DateTime::parse_from_rfc3339(expires_at).ok()?;
+    let left = expires_at.signed_duration_since(chrono::Utc::now());
+    (left.to_std().unwrap_or_default() <= crate::core::idle::EXPIRY_WARNING)
+        .then(|| left.num_minutes().max(0))
+}
 
-    // Check if server is running
-    if !client.is_server_running().await {
-        println!("❌ Server is not running");
-        println!("💡 Start the server first with: codemux server start");
-        return Ok(());
-    }
+/// A session's icon after a dot in its accent color, on terminals that show color
+fn session_badge(color: Option<&str>, icon: Option<&str>) -> String {
+    use crossterm::style::Stylize;
+    use std::io::IsTerminal;
+
+    let rgb = color
+        .and_then(|color| color.strip_prefix('#'))
+        .filter(|hex| hex.len() == 6)
+        .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+    let dot = match rgb {
+        Some(rgb) if std::io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none() => "●"
+            .with(crossterm::style::Color::Rgb {
+                r: (rgb >> 16) as u8,
+                g: (rgb >> 8) as u8,
+                b: rgb as u8,
+            })
+            .to_string(),
+        _ => "•".to_string(),
+    };
+    format!(
+        "{} {}",
+        dot,
+        icon.filter(|icon| !icon.is_empty()).unwrap_or("🚀")
+    )
+}
 
-    println!("Adding project...");
+fn describe_quick_session(session: &crate::core::recent::QuickSession) -> String {
+    let short_id = &session.id[..session.id.len().min(8)];
+    format!(
+        "{} {} {} {:<8} {}{}",
+        if session.favorite { "★" } else { " " },
+        session_badge(session.color.as_deref(), session.icon.as_deref()),
+        short_id,
+        session.agent.as_deref().unwrap_or("unknown"),
+        if session.running { "running" } else { "ended" },
+        session
+            .attached_at
+            .as_deref()
+            .map(|at| format!(", attached {}", at))
+            .unwrap_or_default()
+    )
+}
 
-    // Canonicalize the path
-    let canonical_path = path
-        .canonicalize()
-        .map_err(|e| anyhow::anyhow!("Invalid path {:?}: {}", path, e))?;
+/// Bring up a project's session, preferring the one attached to most recently
+pub async fn focus_project(
+    config: Config,
+    project: String,
+    agent: String,
+    no_open: bool,
+) -> Result<()> {
+    let client = ensure_server_running(&config).await?;
 
-    let project_name = name.unwrap_or_else(|| {
-        canonical_path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unnamed-project")
-            .to_string()
-    });
+    let projects = client.list_projects().await?;
+    let project_id = match project_groups::find_project(&projects, &project) {
+        Some(found) => Some(found.id.clone()),
+        None => client.resolve_project_path(&project).await?,
+    };
 
-    match client
-        .create_project(
-            project_name.clone(),
-            canonical_path.to_string_lossy().to_string(),
-        )
+    // Project listings include the project's active sessions alongside its history
+    let running: Vec<&str> = projects
+        .iter()
+        .filter(|p| Some(&p.id) == project_id.as_ref())
+        .filter_map(|p| p.relationships.as_ref()?.recent_sessions.as_ref())
+        .flatten()
+        .filter(|session| {
+            session
+                .attributes
+                .as_ref()
+                .is_some_and(|a| matches!(a.session_type, SessionType::Active))
+        })
+        .map(|session| session.id.as_str())
+        .collect();
+
+    let recent = client
+        .get_recent()
         .await
-    {
-        Ok(_) => {
-            println!("✅ Project '{}' added successfully", project_name);
-            println!("📁 Path: {}", canonical_path.display());
+        .map(|r| r.sessions)
+        .unwrap_or_default();
+    let existing = recent
+        .iter()
+        .map(|session| session.id.as_str())
+        .find(|id| running.contains(id))
+        .or_else(|| running.first().copied());
+
+    let session_id = match existing {
+        Some(session_id) => {
+            eprintln!("🎯 Focusing session {}", session_id);
+            session_id.to_string()
         }
-        Err(e) => {
-            println!("❌ Failed to add project: {}", e);
+        None => {
+            if !config.is_agent_allowed(&agent) {
+                anyhow::bail!(
+                    "Code agent '{}' is not whitelisted. Add it to the config to use.",
+                    agent
+                );
+            }
+            let session = match &project_id {
+                Some(project_id) => {
+                    client
+                        .create_session(agent.clone(), Vec::new(), Some(project_id.clone()))
+                        .await?
+                }
+                None => {
+                    let path = std::path::Path::new(&project).canonicalize().map_err(|_| {
+                        anyhow::anyhow!("No project or directory named '{}'", project)
+                    })?;
+                    client
+                        .create_session_with_path(
+                            agent.clone(),
+                            Vec::new(),
+                            path.to_string_lossy().to_string(),
+                        )
+                        .await?
+                }
+            };
+            eprintln!("🚀 Started {} session {}", agent, session.id);
+            session.id
+        }
+    };
+
+    // The URL goes to stdout on its own so hotkey tools can capture it
+    let url = client.get_session_url(&session_id);
+    println!("{}", url);
+    if !no_open {
+        if let Err(e) = open::that(&url) {
+            eprintln!("⚠️  Could not auto-open browser: {}", e);
         }
     }
+    eprintln!(
+        "💡 In a terminal, follow it with: codemux watch {}",
+        session_id
+    );
 
     Ok(())
 }
 
-pub async fn list_sessions(config: Config) -> Result<()> {
-    let client = CodeMuxClient::from_config(&config);
+pub async fn watch_session(config: Config, session_id: String, accessible: bool) -> Result<()> {
+    use futures_util::StreamExt;
+    use std::io::Write;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let client = CodeMuxClient::from_config(&config)?;
 
-    // Check if server is running
     if !client.is_server_running().await {
-        println!("❌ Server is not running");
-        println!("💡 Start the server first with: codemux server start");
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
         return Ok(());
     }
 
-    println!("📋 Active Sessions:");
+    let mut stream = client.watch_session(&session_id, accessible).await?;
+    let mut stdout = std::io::stdout();
 
-    match client.list_projects().await {
-        Ok(projects) => {
-            if projects.is_empty() {
+    while let Some(message) = stream.next().await {
+        match message? {
+            Message::Binary(data) if !accessible => {
+                stdout.write_all(&data)?;
+                stdout.flush()?;
+            }
+            Message::Text(text) => {
+                if let Ok(ServerMessage::Error { message }) = serde_json::from_str(&text) {
+                    return Err(anyhow::anyhow!(message));
+                }
+                if !accessible {
+                    continue;
+                }
+                // Plain lines only, so screen readers read each event once without decoration
+                match serde_json::from_str::<AccessibleEvent>(&text) {
+                    Ok(AccessibleEvent::Line { text }) => println!("{}", text),
+                    Ok(AccessibleEvent::Announcement { kind, text }) => {
+                        println!("{}: {}", kind.describe(), text)
+                    }
+                    Err(e) => tracing::debug!("Ignoring unexpected watch message: {}", e),
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// List agents running outside codemux, or take one over with reptyr
+pub async fn adopt_process(config: Config, pid: Option<u32>, scan: bool) -> Result<()> {
+    let agents = config.agent_names();
+    let processes = adopt::scan_agent_processes(&agents)?;
+
+    let Some(pid) = pid.filter(|_| !scan) else {
+        if processes.is_empty() {
+            println!("No agents running outside codemux");
+            return Ok(());
+        }
+        println!("🔎 Agents running outside codemux:");
+        for process in &processes {
+            let mut command: String = process.command.chars().take(72).collect();
+            if command.len() < process.command.len() {
+                command.push('…');
+            }
+            println!("   {:>7}  {:<8} {}", process.pid, process.agent, command);
+            if let Some(cwd) = &process.cwd {
+                println!("            in {}", cwd.display());
+            }
+        }
+        println!("\n💡 Take one over with: codemux adopt <pid> (needs reptyr)");
+        println!("💡 Or exit it and restart under codemux, e.g.: codemux claude --continue");
+        return Ok(());
+    };
+
+    let process = processes
+        .iter()
+        .find(|process| process.pid == pid)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "PID {} is not an agent running outside codemux (see: codemux adopt --scan)",
+                pid
+            )
+        })?;
+
+    let client = ensure_server_running(&config).await?;
+
+    let path = process
+        .cwd
+        .as_ref()
+        .map(|cwd| cwd.to_string_lossy().to_string());
+    match client.adopt_process(process.agent.clone(), pid, path).await {
+        Ok(session) => {
+            println!(
+                "✅ Adopted {} (PID {}) as session {}",
+                process.agent, pid, session.id
+            );
+            println!("🌐 Web Interface: {}", client.get_session_url(&session.id));
+            println!(
+                "💡 If the session ends at once, reptyr could not attach (check ptrace_scope)"
+            );
+        }
+        Err(e) => {
+            println!("❌ Could not adopt PID {}: {}", pid, e);
+            let restart = match &process.cwd {
+                Some(cwd) => format!("cd {} && codemux {}", cwd.display(), process.agent),
+                None => format!("codemux {}", process.agent),
+            };
+            println!(
+                "💡 Exit the agent and restart it under codemux instead: {}",
+                restart
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_shim_command(config: Config, command: ShimCommands) -> Result<()> {
+    let dir = shim::default_shim_dir(&config.server.data_dir);
+    let path_var = env::var_os("PATH").unwrap_or_default();
+
+    match command {
+        ShimCommands::Install { agent, force } => {
+            if !config.is_agent_allowed(&agent) {
+                anyhow::bail!(
+                    "Code agent '{}' is not whitelisted. Add it to the config to use.",
+                    agent
+                );
+            }
+            let codemux = env::current_exe()
+                .map_err(|e| anyhow::anyhow!("Failed to get current executable path: {}", e))?;
+            let path = shim::install_shim(&dir, &agent, &codemux, force)?;
+            println!("✅ Installed {}", path.display());
+
+            match shim::shim_precedence(&dir, &agent, &path_var) {
+                ShimPrecedence::Active => {
+                    println!("💡 `{}` now runs under codemux in new shells", agent)
+                }
+                ShimPrecedence::NotInPath => {
+                    println!(
+                        "💡 Add the shim directory to the front of PATH in your shell profile:"
+                    );
+                    println!("   export PATH=\"{}:$PATH\"", dir.display());
+                }
+                ShimPrecedence::Shadowed(other) => {
+                    println!(
+                        "⚠️  {} comes before the shim in PATH; move {} to the front:",
+                        other.display(),
+                        dir.display()
+                    );
+                    println!("   export PATH=\"{}:$PATH\"", dir.display());
+                }
+            }
+        }
+        ShimCommands::Uninstall { agent } => {
+            let path = shim::uninstall_shim(&dir, &agent)?;
+            println!("✅ Removed {}", path.display());
+        }
+        ShimCommands::List => {
+            let agents = shim::installed_shims(&dir);
+            if agents.is_empty() {
+                println!("No shims installed in {}", dir.display());
+                println!("💡 Install one with: codemux shim install claude");
+            }
+            for agent in agents {
+                let status = match shim::shim_precedence(&dir, &agent, &path_var) {
+                    ShimPrecedence::Active => "active".to_string(),
+                    ShimPrecedence::NotInPath => "shim directory not in PATH".to_string(),
+                    ShimPrecedence::Shadowed(other) => format!("shadowed by {}", other.display()),
+                };
+                println!("   • {} ({})", agent, status);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_agent_config_command(config: Config, command: AgentConfigCommands) -> Result<()> {
+    let data_dir = &config.server.data_dir;
+    let root = agent_config::templates_root(data_dir);
+
+    match command {
+        AgentConfigCommands::Edit { file, agent } => {
+            let file = file
+                .unwrap_or_else(|| PathBuf::from(agent_config::default_template_file(&agent)));
+            let path = agent_config::template_path(&root, &agent, &file)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let editor = env::var("VISUAL")
+                .or_else(|_| env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".to_string());
+            // $EDITOR may come with arguments, e.g. `code --wait`
+            let mut words = editor.split_whitespace();
+            let program = words.next().unwrap_or("vi");
+            let status = std::process::Command::new(program)
+                .args(words)
+                .arg(&path)
+                .status()
+                .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", editor, e))?;
+            if !status.success() {
+                anyhow::bail!("{} exited with {}", editor, status);
+            }
+            let version = agent_config::template_version(&root, &agent)?;
+            println!("✅ {} templates are at version {}", agent, version);
+            println!(
+                "💡 Projects get them when their next {} session starts",
+                agent
+            );
+        }
+        AgentConfigCommands::List => {
+            let mut agents: Vec<String> = std::fs::read_dir(&root)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter(|entry| entry.path().is_dir())
+                        .map(|entry| entry.file_name().to_string_lossy().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            agents.sort();
+            if agents.is_empty() {
+                println!("No agent config templates in {}", root.display());
+                println!("💡 Create one with: codemux agent-config edit");
+            }
+            let state = agent_config::load_sync_state(data_dir)?;
+            for agent in agents {
+                let version = agent_config::template_version(&root, &agent)?;
+                println!("{} templates, version {}:", agent, version);
+                for file in agent_config::load_templates(&root, &agent)?.keys() {
+                    println!("   • {}", file.display());
+                }
+                for (project, synced) in &state {
+                    let Some(synced) = synced.get(&agent) else {
+                        continue;
+                    };
+                    if synced.version == version {
+                        println!("   ✅ {} is up to date", project.display());
+                    } else {
+                        println!(
+                            "   ⏳ {} has version {}, updated when its next session starts",
+                            project.display(),
+                            synced.version
+                        );
+                    }
+                }
+            }
+            if !config.agent_config.enabled {
+                println!("⚠️  Templates aren't written into projects: [agent_config] enabled = false");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Removed: create_and_attach_session - no longer needed after removing NewSession command
+
+pub async fn kill_session(config: Config, session_id: String, now: bool) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let session = client
+        .get_session(&session_id)
+        .await
+        .map_err(|_| anyhow::anyhow!("No session with ID or name '{}'", session_id))?;
+    match client.delete_session(&session.id, now).await? {
+        Some(ends_at) => {
+            println!("⏳ Session {} paused, it ends at {}", session_id, ends_at);
+            println!(
+                "💡 Changed your mind? Run: codemux undo-kill {}",
+                session_id
+            );
+        }
+        None => println!("✅ Session {} killed", session_id),
+    }
+    Ok(())
+}
+
+pub async fn undo_kill(config: Config, session_id: String) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let session = client
+        .get_session(&session_id)
+        .await
+        .map_err(|_| anyhow::anyhow!("No session with ID or name '{}'", session_id))?;
+    client.undo_kill(&session.id).await?;
+    println!("✅ Session {} is running again", session_id);
+    Ok(())
+}
+
+pub async fn export_conversation(
+    config: Config,
+    session_id: String,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let document = client.export_conversation(&session_id, format).await?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, document)
+                .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", path, e))?;
+            println!("✅ Conversation exported to {}", path.display());
+        }
+        None => print!("{}", document),
+    }
+    Ok(())
+}
+
+pub async fn export_scrollback(
+    config: Config,
+    session_id: String,
+    lines: usize,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let html = client.get_scrollback_html(&session_id, lines).await?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, html)
+                .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", path, e))?;
+            println!("✅ Scrollback exported to {}", path.display());
+        }
+        None => print!("{}", html),
+    }
+
+    Ok(())
+}
+
+pub async fn archive_project(config: Config, project: String, out: Option<PathBuf>) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let (file_name, archive) = client.download_project_archive(&project).await?;
+    // Only a file named with --out is overwritten
+    let written = match &out {
+        Some(path) => std::fs::write(path, &archive),
+        None => std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&file_name)
+            .and_then(|mut file| std::io::Write::write_all(&mut file, &archive)),
+    };
+    let path = out.unwrap_or_else(|| PathBuf::from(file_name));
+    written.map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => anyhow::anyhow!(
+            "{} already exists; pass --out {} to overwrite it",
+            path.display(),
+            path.display()
+        ),
+        _ => anyhow::anyhow!("Failed to write {:?}: {}", path, e),
+    })?;
+    println!(
+        "✅ Archived {} to {} ({} KB)",
+        project,
+        path.display(),
+        archive.len().div_ceil(1024)
+    );
+    Ok(())
+}
+
+/// List or restore a session's git checkpoints, see `core::checkpoint`
+pub async fn handle_checkpoints_command(
+    config: Config,
+    command: CheckpointsCommands,
+) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        eprintln!("❌ Server is not running");
+        eprintln!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    match command {
+        CheckpointsCommands::List { session_id } => {
+            let checkpoints = client.list_checkpoints(&session_id).await?;
+            println!("📸 Checkpoints of {}:", session_id);
+            if checkpoints.is_empty() {
+                println!("   None yet - turn them on with `enabled = true` under [checkpoints]");
+            }
+            for checkpoint in &checkpoints {
+                println!(
+                    "   {:<28} {} {}",
+                    checkpoint.name,
+                    &checkpoint.commit[..12.min(checkpoint.commit.len())],
+                    checkpoint.created_at
+                );
+            }
+        }
+        CheckpointsCommands::Restore {
+            session_id,
+            checkpoint,
+        } => {
+            let checkpoint = match checkpoint {
+                Some(checkpoint) => checkpoint,
+                None => client
+                    .list_checkpoints(&session_id)
+                    .await?
+                    .pop()
+                    .map(|checkpoint| checkpoint.name)
+                    .ok_or_else(|| anyhow::anyhow!("Session {} has no checkpoints", session_id))?,
+            };
+            let restored = client.restore_checkpoint(&session_id, &checkpoint).await?;
+            println!(
+                "✅ Restored {} ({})",
+                restored.restored.name, restored.restored.created_at
+            );
+            if let Some(backup) = restored.backup {
+                println!(
+                    "   The files from before are checkpoint {}; undo with: codemux checkpoints restore {} {}",
+                    backup, session_id, backup
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn add_project(
+    config: Config,
+    path: PathBuf,
+    name: Option<String>,
+    parent: Option<String>,
+) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    println!("Adding project...");
+
+    // Canonicalize the path
+    let canonical_path = path
+        .canonicalize()
+        .map_err(|e| anyhow::anyhow!("Invalid path {:?}: {}", path, e))?;
+
+    // The package or repository name beats directory names like `app`
+    let project_name =
+        name.unwrap_or_else(|| ProjectMetadata::read(&canonical_path).name_or_dir(&canonical_path));
+
+    let parent = match parent {
+        Some(parent) => {
+            let projects = client.list_projects().await?;
+            let found = project_groups::find_project(&projects, &parent)
+                .ok_or_else(|| anyhow::anyhow!("No project named '{}'", parent))?;
+            let parent_path = found.attributes.as_ref().map(|a| PathBuf::from(&a.path));
+            project_groups::validate_subproject(&canonical_path, &parent_path.unwrap_or_default())?;
+            Some((
+                found.id.clone(),
+                project_groups::qualified_name(&projects, found),
+            ))
+        }
+        None => None,
+    };
+
+    match client
+        .create_project(
+            project_name.clone(),
+            canonical_path.to_string_lossy().to_string(),
+            parent.as_ref().map(|(id, _)| id.clone()),
+        )
+        .await
+    {
+        Ok(_) => {
+            match &parent {
+                Some((_, parent_name)) => println!(
+                    "✅ Project '{}/{}' added as a sub-project of '{}'",
+                    parent_name, project_name, parent_name
+                ),
+                None => println!("✅ Project '{}' added successfully", project_name),
+            }
+            println!("📁 Path: {}", canonical_path.display());
+        }
+        Err(e) => {
+            println!("❌ Failed to add project: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn remove_project(config: Config, project: String, kill_sessions: bool) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    match client.remove_project(&project, kill_sessions).await {
+        Ok(sessions) => {
+            println!("✅ Project '{}' removed", project);
+            match (sessions.len(), kill_sessions) {
+                (0, _) => {}
+                (count, true) => println!("🛑 Ended {} running session(s)", count),
+                (count, false) => {
+                    println!("📎 {} running session(s) run on without a project", count);
+                    println!("💡 End them along with the project with --kill-sessions");
+                }
+            }
+        }
+        Err(e) => {
+            println!("❌ {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_project_command(config: Config, command: ProjectCommands) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let address = match &command {
+        ProjectCommands::Set { project, .. } | ProjectCommands::Show { project } => project,
+    };
+    let projects = client.list_projects().await?;
+    let found = project_groups::find_project(&projects, address)
+        .ok_or_else(|| anyhow::anyhow!("No project named '{}'", address))?;
+    let name = project_groups::qualified_name(&projects, found);
+    let current = found
+        .attributes
+        .as_ref()
+        .and_then(|attributes| attributes.defaults.clone())
+        .unwrap_or_default();
+
+    let defaults = match command {
+        ProjectCommands::Show { .. } => current,
+        ProjectCommands::Set {
+            agent,
+            args,
+            env,
+            clear,
+            ..
+        } => {
+            let mut defaults = if clear {
+                ProjectDefaults::default()
+            } else {
+                current
+            };
+            if let Some(agent) = agent {
+                defaults.agent = Some(agent).filter(|agent| !agent.is_empty());
+            }
+            if let Some(args) = args {
+                defaults.args = args.split_whitespace().map(str::to_string).collect();
+            }
+            for (var, value) in env {
+                if value.is_empty() {
+                    defaults.env.remove(&var);
+                } else {
+                    defaults.env.insert(var, value);
+                }
+            }
+            let updated = client.set_project_defaults(&found.id, &defaults).await?;
+            println!("✅ Defaults of '{}' updated", name);
+            updated
+                .attributes
+                .and_then(|attributes| attributes.defaults)
+                .unwrap_or_default()
+        }
+    };
+
+    println!("📁 {}", name);
+    if defaults.is_empty() {
+        println!("   No defaults; sessions start as `codemux run` is told");
+        return Ok(());
+    }
+    println!(
+        "   Agent: {}",
+        defaults.agent.as_deref().unwrap_or("(none)")
+    );
+    if !defaults.args.is_empty() {
+        println!("   Args:  {}", defaults.args.join(" "));
+    }
+    for (var, value) in &defaults.env {
+        println!("   Env:   {}={}", var, value);
+    }
+    Ok(())
+}
+
+pub async fn rename_project(config: Config, project: String, name: String) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    match client.rename_project(&project, &name).await {
+        Ok(_) => println!("✅ Project '{}' renamed to '{}'", project, name),
+        Err(e) => println!("❌ {}", e),
+    }
+
+    Ok(())
+}
+
+/// `codemux scan`: add the git repositories below `dir` as projects
+pub async fn scan_projects(
+    config: Config,
+    dir: PathBuf,
+    max_depth: usize,
+    dry_run: bool,
+) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    println!("🔍 Scanning {} for git repositories...", dir.display());
+    let repos = project_scan::find_git_repos(&dir, max_depth)?;
+    if repos.is_empty() {
+        println!("   No git repositories found");
+        return Ok(());
+    }
+
+    // Paths are compared canonicalized, as add-project stores them
+    let known: Vec<PathBuf> = client
+        .list_projects()
+        .await?
+        .into_iter()
+        .filter_map(|project| project.attributes)
+        .map(|attributes| {
+            let path = PathBuf::from(&attributes.path);
+            path.canonicalize().unwrap_or(path)
+        })
+        .collect();
+
+    let (mut added, mut skipped, mut failed) = (0, 0, 0);
+    for repo in &repos {
+        if known.contains(repo) {
+            println!("   ⏭️  {} (already a project)", repo.display());
+            skipped += 1;
+            continue;
+        }
+        let name = ProjectMetadata::read(repo).name_or_dir(repo);
+        if dry_run {
+            println!("   ➕ {} as '{}'", repo.display(), name);
+            added += 1;
+            continue;
+        }
+        match client
+            .create_project(name.clone(), repo.to_string_lossy().to_string(), None)
+            .await
+        {
+            Ok(_) => {
+                println!("   ✅ {} as '{}'", repo.display(), name);
+                added += 1;
+            }
+            Err(e) => {
+                println!("   ❌ {}: {}", repo.display(), e);
+                failed += 1;
+            }
+        }
+    }
+
+    let verb = if dry_run { "would be added" } else { "added" };
+    println!(
+        "\n📁 {} of {} repositories {}, {} already projects",
+        added,
+        repos.len(),
+        verb,
+        skipped
+    );
+    if failed > 0 {
+        println!("⚠️  {} couldn't be added", failed);
+    }
+    Ok(())
+}
+
+pub async fn list_sessions(config: Config, query: ListQuery) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    // Check if server is running
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    println!("📋 Active Sessions:");
+
+    match client.list_projects_page(&query).await {
+        Ok(page) => {
+            let projects = page.data;
+            if projects.is_empty() && query.filters_sessions() {
+                println!("   No sessions match");
+            } else if projects.is_empty() {
                 println!("   No projects or sessions found");
                 println!("💡 Add a project with: codemux add-project <path>");
             } else {
-                for project_resource in projects {
-                    if let Some(project) = project_resource.attributes {
-                        println!("\n📂 Project: {}", project.name);
+                for project_resource in &projects {
+                    if let Some(project) = &project_resource.attributes {
+                        println!(
+                            "\n{} Project: {}",
+                            project_icon(&project.icon),
+                            project_groups::qualified_name(&projects, project_resource)
+                        );
                         if project_resource
                             .relationships
                             .as_ref()
@@ -525,12 +2059,46 @@ pub async fn list_sessions(config: Config) -> Result<()> {
                                 .and_then(|r| r.recent_sessions.as_deref())
                                 .unwrap_or(&[])
                             {
-                                println!("   🚀 Session: {}", session_ref.id);
+                                let attributes = session_ref.attributes.as_ref();
+                                let label = match attributes.and_then(|a| a.name.as_deref()) {
+                                    Some(name) => format!("{} [{}]", name, session_ref.id),
+                                    None => session_ref.id.clone(),
+                                };
+                                println!(
+                                    "   {} Session: {} ({})",
+                                    session_badge(
+                                        attributes.map(|a| a.color.as_str()),
+                                        attributes.map(|a| a.icon.as_str())
+                                    ),
+                                    label,
+                                    attributes.map(|a| a.agent.as_str()).unwrap_or("unknown")
+                                );
+                                if let Some(note) = attributes.and_then(|a| a.note.as_deref()) {
+                                    println!("      📝 {}", note);
+                                }
+                                if let Some(code) = attributes.and_then(|a| a.exit_code) {
+                                    println!("      ⚫ Exited with code {}", code);
+                                }
+                                if let Some(minutes) = attributes
+                                    .and_then(|a| a.idle_expires_at.as_deref())
+                                    .and_then(idle_minutes_left)
+                                {
+                                    println!(
+                                        "      ⏳ Idle, ends in {} min unless attached",
+                                        minutes
+                                    );
+                                }
                             }
                         }
                     }
                 }
             }
+            if page.meta.pages > 1 {
+                println!(
+                    "\n📄 Page {} of {} ({} projects)",
+                    page.meta.page_number, page.meta.pages, page.meta.total
+                );
+            }
         }
         Err(e) => {
             println!("❌ Failed to list sessions: {}", e);
@@ -540,8 +2108,17 @@ pub async fn list_sessions(config: Config) -> Result<()> {
     Ok(())
 }
 
+/// A project's icon, for servers that don't send one
+fn project_icon(icon: &str) -> &str {
+    if icon.is_empty() {
+        "📂"
+    } else {
+        icon
+    }
+}
+
 pub async fn list_projects(config: Config) -> Result<()> {
-    let client = CodeMuxClient::from_config(&config);
+    let client = CodeMuxClient::from_config(&config)?;
 
     // Check if server is running
     if !client.is_server_running().await {
@@ -558,15 +2135,29 @@ pub async fn list_projects(config: Config) -> Result<()> {
                 println!("   No projects registered");
                 println!("💡 Add a project with: codemux add-project <path>");
             } else {
-                for project_resource in projects {
-                    if let Some(project) = project_resource.attributes {
+                for project_resource in &projects {
+                    if let Some(project) = &project_resource.attributes {
                         let session_count = project_resource
                             .relationships
                             .as_ref()
                             .and_then(|r| r.recent_sessions.as_deref())
                             .unwrap_or(&[])
                             .len();
-                        println!("   • {} ({} sessions)", project.name, session_count);
+                        let subproject_count = project_resource
+                            .relationships
+                            .as_ref()
+                            .and_then(|r| r.subproject_sessions.as_deref())
+                            .map_or(0, |sessions| sessions.len());
+                        let name = project_groups::qualified_name(&projects, project_resource);
+                        let icon = project_icon(&project.icon);
+                        if subproject_count > 0 {
+                            println!(
+                                "   {} {} ({} sessions, {} running in sub-projects)",
+                                icon, name, session_count, subproject_count
+                            );
+                        } else {
+                            println!("   {} {} ({} sessions)", icon, name, session_count);
+                        }
                         if session_count > 0 {
                             for session_ref in project_resource
                                 .relationships
@@ -574,7 +2165,15 @@ pub async fn list_projects(config: Config) -> Result<()> {
                                 .and_then(|r| r.recent_sessions.as_deref())
                                 .unwrap_or(&[])
                             {
-                                println!("     └── Session: {}", session_ref.id);
+                                let attributes = session_ref.attributes.as_ref();
+                                println!(
+                                    "     └── {} Session: {}",
+                                    session_badge(
+                                        attributes.map(|a| a.color.as_str()),
+                                        attributes.map(|a| a.icon.as_str())
+                                    ),
+                                    session_ref.id
+                                );
                             }
                         }
                     }
@@ -589,33 +2188,621 @@ pub async fn list_projects(config: Config) -> Result<()> {
     Ok(())
 }
 
-pub async fn stop_server(config: Config) -> Result<()> {
-    let client = CodeMuxClient::from_config(&config);
+pub async fn list_recent(config: Config) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let recent = client.get_recent().await?;
+
+    println!("⏱️  Starred & Recent Sessions:");
+    if recent.sessions.is_empty() {
+        println!("   None yet - sessions appear here once you attach to them");
+    }
+    for session in &recent.sessions {
+        println!("   {}", describe_quick_session(session));
+    }
+
+    if !recent.projects.is_empty() {
+        println!("\n📂 Starred Projects:");
+        for project in &recent.projects {
+            println!(
+                "   ★ {} {} ({})",
+                project_icon(&project.icon),
+                project.name,
+                project.path
+            );
+        }
+    }
+
+    Ok(())
+}
 
-    tracing::info!("Stopping server...");
+#[cfg(all(feature = "server", feature = "capture"))]
+pub fn handle_recordings_command(config: Config, command: RecordingsCommands) -> Result<()> {
+    use crate::capture::session_data::SessionRecording;
+    use crate::server::recording;
+
+    let dir = config.server.recordings_dir();
+    match command {
+        RecordingsCommands::List => {
+            let recordings = recording::list(&dir)?;
+            if recordings.is_empty() {
+                println!("No recordings in {}", dir.display());
+                println!("💡 Record a session with: codemux run <agent> --record");
+            }
+            for recording in recordings {
+                let started = chrono::DateTime::<chrono::Local>::from(recording.start_time);
+                let minutes = recording
+                    .modified
+                    .duration_since(recording.start_time)
+                    .unwrap_or_default()
+                    .as_secs()
+                    / 60;
+                println!(
+                    "   • {} {} started {}, {} min, {} KiB",
+                    recording.session_id,
+                    recording.agent,
+                    started.format("%Y-%m-%d %H:%M"),
+                    minutes,
+                    recording.size / 1024
+                );
+            }
+        }
+        RecordingsCommands::Export { session_id, output } => {
+            let found = recording::find(&dir, &session_id)?;
+            let mut session = SessionRecording::load_jsonl(&found.path)?;
+            session.finalize();
+            let output =
+                output.unwrap_or_else(|| PathBuf::from(format!("{}.json", found.session_id)));
+            session
+                .save(&output)
+                .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", output, e))?;
+            println!("✅ Recording exported to {}", output.display());
+            println!("💡 Play it with: codemux replay {}", output.display());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(all(feature = "server", feature = "capture")))]
+pub fn handle_recordings_command(_config: Config, _command: RecordingsCommands) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Recordings need the `server` and `capture` features, which this build was compiled without"
+    ))
+}
+
+pub async fn print_digest(config: Config, hours: u32) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
 
     if !client.is_server_running().await {
-        tracing::info!("❌ Server is not running");
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
         return Ok(());
     }
 
-    match client.shutdown_server().await {
-        Ok(()) => {
-            tracing::info!("✅ Server shutdown successfully");
+    let digest = client.get_digest(hours).await?;
+    print!("{}", digest.markdown);
+    Ok(())
+}
+
+pub async fn print_usage(config: Config, session_id: Option<String>) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
 
-            // Wait a moment for server to shut down
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
 
-            // Verify server is stopped
-            if !client.is_server_running().await {
-                tracing::info!("🛑 Server has stopped");
+    if let Some(session_id) = session_id {
+        let usage = client.get_session_usage(&session_id).await?;
+        println!(
+            "💰 {} {}: {}",
+            usage.session_id,
+            usage.agent,
+            usage_line(&usage.usage)
+        );
+        return Ok(());
+    }
+
+    let summary = client.get_usage().await?;
+    if summary.sessions.is_empty() {
+        println!("No running sessions");
+        return Ok(());
+    }
+    for project in &summary.projects {
+        println!(
+            "\n📁 {} ({} session{}): {}",
+            project
+                .name
+                .as_deref()
+                .or(project.project.as_deref())
+                .unwrap_or("no project"),
+            project.sessions,
+            if project.sessions == 1 { "" } else { "s" },
+            usage_line(&project.usage)
+        );
+        for session in summary
+            .sessions
+            .iter()
+            .filter(|session| session.project == project.project)
+        {
+            println!(
+                "   {} {}: {}",
+                session.session_id,
+                session.agent,
+                usage_line(&session.usage)
+            );
+        }
+    }
+    println!("\n💰 Total: {}", usage_line(&summary.total));
+    Ok(())
+}
+
+/// "12 messages, 3400 input / 1200 output tokens, $0.42"
+fn usage_line(usage: &TranscriptStats) -> String {
+    let mut line = format!(
+        "{} messages, {} input / {} output tokens",
+        usage.messages, usage.input_tokens, usage.output_tokens
+    );
+    if let Some(cost) = usage.cost {
+        line.push_str(&format!(", ${:.2}", cost));
+    }
+    line
+}
+
+pub async fn search_sessions(config: Config, query: String, limit: usize) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let hits = client.search(&query, limit).await?;
+    if hits.is_empty() {
+        println!("No sessions mention \"{}\"", query);
+        return Ok(());
+    }
+    for hit in hits {
+        let source = match hit.source {
+            SearchSource::Transcript => "transcript",
+            SearchSource::Scrollback => "scrollback",
+            SearchSource::Note => "note",
+        };
+        let when = hit
+            .updated_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| {
+                at.with_timezone(&chrono::Local)
+                    .format(", %Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        println!(
+            "\n🔎 {} {} in {} ({}{})",
+            hit.session_id
+                .as_deref()
+                .or(hit.conversation.as_deref())
+                .unwrap_or("?"),
+            hit.agent,
+            hit.project.as_deref().unwrap_or("no project"),
+            source,
+            when
+        );
+        for snippet in &hit.snippets {
+            println!("   {}", snippet);
+        }
+        if hit.session_id.is_none() {
+            if let Some(conversation) = &hit.conversation {
+                println!(
+                    "   💡 Resume with: codemux run {} --resume {}",
+                    hit.agent, conversation
+                );
             }
         }
-        Err(e) => {
-            tracing::error!("❌ Failed to shutdown server: {}", e);
-            tracing::info!("💡 Server may have already stopped or use Ctrl+C to force stop");
+    }
+    Ok(())
+}
+
+pub async fn handle_dnd_command(config: Config, command: DndCommands) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let dnd = match command {
+        DndCommands::On { duration } => {
+            // Round up so `--for 30s` still switches on
+            let minutes = duration.map(|duration| duration.as_secs().div_ceil(60) as u32);
+            client.set_do_not_disturb(true, minutes).await?
+        }
+        DndCommands::Off => client.set_do_not_disturb(false, None).await?,
+        DndCommands::Status => client.get_do_not_disturb().await?,
+    };
+
+    match (dnd.enabled, dnd.until.clone()) {
+        (false, _) => println!("🔔 Do not disturb is off"),
+        (true, None) => println!("🔕 Do not disturb is on until you turn it off"),
+        (true, Some(until)) => {
+            let until = chrono::DateTime::parse_from_rfc3339(&until)
+                .map(|until| {
+                    until
+                        .with_timezone(&chrono::Local)
+                        .format("%H:%M")
+                        .to_string()
+                })
+                .unwrap_or(until);
+            println!("🔕 Do not disturb is on until {}", until);
+        }
+    }
+    if dnd.enabled {
+        println!("   Notifications are still recorded; see them in the web UI");
+    }
+
+    Ok(())
+}
+
+/// Sign in to a server and keep its token in the OS keychain, see
+/// `core::credentials`
+///
+/// Through the browser, the server's identity provider signs the user in and
+/// the server sends a token back through the browser to a port this opens on
+/// localhost, along with a random state so no other page can plant one. With
+/// `with_token`, a token given on stdin is checked with the server and kept.
+pub async fn login(config: Config, server: Option<String>, with_token: bool) -> Result<()> {
+    let server = server
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| config.client.server_url(config.server.port));
+    let client = CodeMuxClient::new(server.clone())?;
+    if !client.is_server_running().await {
+        anyhow::bail!("No codemux server answers at {}", server);
+    }
+
+    let credential = if with_token {
+        let mut token = String::new();
+        std::io::stdin().read_line(&mut token)?;
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            anyhow::bail!("No token on stdin");
+        }
+        CodeMuxClient::with_token(server.clone(), Some(token.clone()))?
+            .check_token()
+            .await?;
+        Credential {
+            token,
+            expires_at: None,
+        }
+    } else {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let callback = format!("http://127.0.0.1:{}/", listener.local_addr()?.port());
+        let state = crate::core::auth::generate_token();
+        let url = client.login_url(&callback, &state);
+        println!("🔐 Sign in in your browser: {}", url);
+        if let Err(e) = open::that(&url) {
+            println!("⚠️  Could not auto-open browser: {}", e);
+        }
+
+        let (token, expires_at) = tokio::time::timeout(
+            std::time::Duration::from_secs(10 * 60),
+            receive_login(&listener, &state),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Gave up waiting for the sign-in after 10 minutes"))??;
+        Credential {
+            token,
+            expires_at: (!expires_at.is_empty()).then_some(expires_at),
+        }
+    };
+
+    let kept_in = CredentialStore::new(&config.server.data_dir).store(&server, &credential)?;
+    match &credential.expires_at {
+        Some(expires_at) => println!("✅ Signed in to {} until {}", server, expires_at),
+        None => println!("✅ Signed in to {}", server),
+    }
+    println!("   The token is kept in {}", kept_in);
+    Ok(())
+}
+
+/// Forget the token `codemux login` kept for a server
+pub fn logout(config: Config, server: Option<String>) -> Result<()> {
+    let server = server.unwrap_or_else(|| config.client.server_url(config.server.port));
+    if CredentialStore::new(&config.server.data_dir).remove(&server)? {
+        println!("✅ Signed out of {}", server);
+    } else {
+        println!("ℹ️  Not signed in to {}", server);
+    }
+    Ok(())
+}
+
+/// Create, list or revoke the server's API tokens, see `core::api_tokens`
+pub async fn handle_token_command(config: Config, command: TokenCommands) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    match command {
+        TokenCommands::Create { name, scope, ttl } => {
+            let name = name.unwrap_or_else(|| {
+                format!("token-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"))
+            });
+            let created = client.create_token(&name, scope, ttl).await?;
+            println!(
+                "✅ Created token {} ({})",
+                created.token.name,
+                created.token.scope.as_str()
+            );
+            match &created.token.expires_at {
+                Some(expires_at) => println!("   Expires at {}", expires_at),
+                None => println!(
+                    "   Never expires; revoke it with: codemux token revoke {}",
+                    name
+                ),
+            }
+            println!("\n{}\n", created.secret);
+            println!("   Copy it now, it won't be shown again. Send it as a Bearer token.");
+        }
+        TokenCommands::List => {
+            let tokens = client.list_tokens().await?;
+            println!("🔑 API Tokens:");
+            if tokens.is_empty() {
+                println!("   None yet - create one with: codemux token create --scope read");
+            }
+            for token in &tokens {
+                let expiry = match &token.expires_at {
+                    _ if token.expired() => "expired".to_string(),
+                    Some(expires_at) => format!("expires {}", expires_at),
+                    None => "never expires".to_string(),
+                };
+                println!(
+                    "   {} {} [{}] created {}, {}",
+                    &token.id[..8.min(token.id.len())],
+                    token.name,
+                    token.scope.as_str(),
+                    token.created_at,
+                    expiry
+                );
+            }
+        }
+        TokenCommands::Revoke { token } => {
+            let revoked = client.revoke_token(&token).await?;
+            println!("✅ Revoked token {}", revoked.name);
         }
     }
 
     Ok(())
 }
+
+/// Print a share link for one session, see `ApiTokenStore::share`
+pub async fn share_session(
+    config: Config,
+    session_id: &str,
+    ttl: std::time::Duration,
+    interactive: bool,
+) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        println!("💡 Start the server first with: codemux server start");
+        return Ok(());
+    }
+
+    let shared = client.share_session(session_id, interactive, ttl).await?;
+    let session_id = shared.token.session_id.as_deref().unwrap_or(session_id);
+    let access = if interactive {
+        "can type into it"
+    } else {
+        "read-only"
+    };
+    println!("🔗 Shared session {} ({})", session_id, access);
+    if let Some(expires_at) = &shared.token.expires_at {
+        println!("   Expires at {}", expires_at);
+    }
+    println!("\n{}\n", client.share_url(session_id, &shared.secret));
+    println!(
+        "   Anyone with the link can open the session; revoke it with: codemux token revoke {}",
+        shared.token.name
+    );
+    Ok(())
+}
+
+/// Wait for the browser to bring the token, answering it with a page to close
+async fn receive_login(
+    listener: &tokio::net::TcpListener,
+    state: &str,
+) -> Result<(String, String)> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut request = vec![0; 8192];
+        let read = stream.read(&mut request).await?;
+        let request = String::from_utf8_lossy(&request[..read]);
+        let target = request
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/");
+        let url = url::Url::parse(&format!("http://localhost{}", target))?;
+        let query = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        let login = match (query("token"), query("state")) {
+            (Some(token), Some(sent)) if sent == state => {
+                Some((token, query("expires_at").unwrap_or_default()))
+            }
+            _ => None,
+        };
+        let (status, body) = match login {
+            Some(_) => ("200 OK", "Signed in to codemux. You can close this tab."),
+            None => ("400 Bad Request", "Not the sign-in codemux waits for."),
+        };
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await?;
+        if let Some(login) = login {
+            return Ok(login);
+        }
+    }
+}
+
+/// Ask the server to end its sessions and exit, then wait until it has
+pub fn handle_dev_command(command: DevCommands) -> Result<()> {
+    match command {
+        DevCommands::Fixtures { output: None } => {
+            let documents: serde_json::Map<String, serde_json::Value> = fixtures::fixtures()?
+                .into_iter()
+                .map(|(name, document)| (name.to_string(), document))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&documents)?);
+        }
+        DevCommands::Fixtures { output: Some(dir) } => {
+            std::fs::create_dir_all(&dir)?;
+            for (name, document) in fixtures::fixtures()? {
+                let path = dir.join(format!("{}.json", name));
+                std::fs::write(&path, serde_json::to_string_pretty(&document)? + "\n")?;
+                println!("Wrote {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn stop_server(config: Config) -> Result<()> {
+    let client = CodeMuxClient::from_config(&config)?;
+
+    if !client.is_server_running().await {
+        println!("❌ Server is not running");
+        return Ok(());
+    }
+
+    println!("🛑 Stopping server...");
+    if let Err(e) = client.shutdown_server().await {
+        // It may have exited before answering
+        if client.is_server_running().await {
+            anyhow::bail!("Failed to stop the server: {}", e);
+        }
+    }
+
+    // The response comes once the agents have exited, only the exit is left
+    for _ in 0..50 {
+        if !client.is_server_running().await {
+            println!("✅ Server stopped");
+            return Ok(());
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+    anyhow::bail!(
+        "The server is still running after 5s; see {}",
+        config.server.log_dir.join("server.log").display()
+    )
+}
+
+pub async fn doctor(config: Config) -> Result<()> {
+    let mut problems = 0;
+
+    println!("🩺 CodeMux doctor\n");
+
+    match Config::config_path() {
+        Some(path) if path.exists() => println!("✅ Config file: {}", path.display()),
+        Some(path) => println!("ℹ️  Config file: {} (not found, using defaults)", path.display()),
+        None => println!("ℹ️  Config file: no home directory, using defaults"),
+    }
+
+    println!("\n📁 Directories:");
+    for kind in DirKind::ALL {
+        let path = kind.path(&config.server);
+        let source = if std::env::var_os(kind.env_var()).is_some_and(|v| !v.is_empty()) {
+            format!(" (from {})", kind.env_var())
+        } else {
+            String::new()
+        };
+
+        match check_writable_dir(kind, path) {
+            Ok(()) => println!("  ✅ {:<8} {}{}", kind.name(), path.display(), source),
+            Err(e) => {
+                problems += 1;
+                println!("  ❌ {:<8} {}{}: {}", kind.name(), path.display(), source, e);
+            }
+        }
+
+        #[cfg(unix)]
+        if kind == DirKind::Runtime {
+            use std::os::unix::fs::PermissionsExt;
+            if let Ok(metadata) = std::fs::metadata(path) {
+                if metadata.permissions().mode() & 0o077 != 0 {
+                    println!(
+                        "  ⚠️  {} is accessible by other users; consider chmod 700",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    println!("\n🤖 Agents:");
+    let default_agent_config = AgentConfig::default();
+    for agent in &config.agent_names() {
+        let agent_config = config.agents.get(agent).unwrap_or(&default_agent_config);
+        let status = check_agent_version(agent, agent_config).await;
+        match status.status {
+            VersionCheck::Ok | VersionCheck::Unpinned => println!("  ✅ {}", status.summary()),
+            VersionCheck::NotInstalled => println!("  ℹ️  {}", status.summary()),
+            VersionCheck::Mismatch if status.blocks() => {
+                problems += 1;
+                println!("  ❌ {}; new sessions are blocked", status.summary());
+            }
+            VersionCheck::Mismatch | VersionCheck::Unknown => {
+                println!("  ⚠️  {}", status.summary())
+            }
+        }
+        let missing_env = config.missing_agent_env(agent, &Default::default());
+        if !missing_env.is_empty() {
+            println!("  ⚠️  {} needs {} set", agent, missing_env.join(", "));
+        }
+    }
+
+    println!("\n🌐 Server:");
+    let client = CodeMuxClient::from_config(&config)?;
+    if client.is_server_running().await {
+        println!("  ✅ Running on port {}", config.server.port);
+    } else {
+        println!("  ℹ️  Not running on port {}", config.server.port);
+    }
+
+    if problems > 0 {
+        return Err(anyhow::anyhow!("{} problem(s) found", problems));
+    }
+    println!("\n✅ No problems found");
+    Ok(())
+}
+
+/// Create `dir` if needed and confirm files can be written to it
+fn check_writable_dir(kind: DirKind, dir: &std::path::Path) -> Result<()> {
+    crate::core::dirs::create_dir(kind, dir)?;
+    let probe = dir.join(format!(".codemux-doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"ok")?;
+    std::fs::remove_file(&probe)?;
+    Ok(())
+}
@@ -1,5 +1,10 @@
 pub mod commands;
 pub mod handlers;
+pub mod shell_init;
 
-pub use commands::{Cli, Commands, ServerCommands};
+pub use commands::{
+    AgentConfigCommands, CheckpointsCommands, Cli, Commands, DevCommands, DndCommands,
+    ProjectCommands, RecordingsCommands, ServerCommands, ShimCommands, TmuxCommands,
+    TokenCommands,
+};
 pub use handlers::*;
@@ -0,0 +1,73 @@
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Zsh,
+    Bash,
+}
+
+/// Shared hook body: track the git repository around `$PWD` in `CODEMUX_PROJECT`
+const HOOK_BODY: &str = r#"  local root
+  root=$(command git rev-parse --show-toplevel 2>/dev/null) || {
+    unset CODEMUX_PROJECT
+    return
+  }
+  [ "$root" = "$CODEMUX_PROJECT" ] && return
+  export CODEMUX_PROJECT="$root"
+"#;
+
+/// Registers the repository in the background; `add-project` is a no-op for known paths
+const REGISTER: &str = r#"  ( command codemux add-project "$root" >/dev/null 2>&1 & )
+"#;
+
+/// Script for `eval "$(codemux shell-init <shell>)"`
+pub fn shell_init_script(shell: Shell, register: bool) -> String {
+    let hook = format!(
+        "_codemux_hook() {{\n{}{}}}\n",
+        HOOK_BODY,
+        if register { REGISTER } else { "" }
+    );
+
+    match shell {
+        Shell::Zsh => format!(
+            "# codemux shell integration for zsh\n{}\
+             autoload -Uz add-zsh-hook\n\
+             add-zsh-hook chpwd _codemux_hook\n\
+             _codemux_hook\n",
+            hook
+        ),
+        // Bash has no directory-change hook, so check on every prompt
+        Shell::Bash => format!(
+            "# codemux shell integration for bash\n{}\
+             _codemux_prompt() {{\n  \
+               [ \"$PWD\" = \"$_CODEMUX_LAST_PWD\" ] && return\n  \
+               _CODEMUX_LAST_PWD=\"$PWD\"\n  \
+               _codemux_hook\n\
+             }}\n\
+             case \";$PROMPT_COMMAND;\" in\n  \
+               *\";_codemux_prompt;\"*) ;;\n  \
+               *) PROMPT_COMMAND=\"_codemux_prompt${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}\" ;;\n\
+             esac\n",
+            hook
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registration_is_opt_in() {
+        let zsh = shell_init_script(Shell::Zsh, false);
+        assert!(zsh.contains("add-zsh-hook chpwd _codemux_hook"));
+        assert!(zsh.contains("export CODEMUX_PROJECT=\"$root\""));
+        assert!(!zsh.contains("add-project"));
+
+        let bash = shell_init_script(Shell::Bash, true);
+        assert!(
+            bash.contains("PROMPT_COMMAND=\"_codemux_prompt${PROMPT_COMMAND:+;$PROMPT_COMMAND}\"")
+        );
+        assert!(bash.contains("command codemux add-project \"$root\""));
+    }
+}
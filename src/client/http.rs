@@ -1,19 +1,52 @@
 use anyhow::{anyhow, Result};
 use reqwest::Client;
 use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use tokio::time::sleep;
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio_tungstenite::tungstenite::{
+    client::IntoClientRequest, handshake::client::Request, Message,
+};
+
+use super::transport::{connect_ws, WsStream};
 
+use crate::core::api_tokens::{
+    ApiToken, CreateApiTokenRequest, NewApiToken, ShareSessionRequest, TokenScope,
+};
+use crate::core::auth;
+use crate::core::checkpoint::{Checkpoint, RestoredCheckpoint};
+use crate::core::digest::Digest;
+use crate::core::frame_encoding::FrameEncoding;
+use crate::core::json_api::JsonApiPage;
+use crate::core::listing::ListQuery;
+use crate::core::log_level::LogLevel;
+use crate::core::notifications::{DoNotDisturb, SetDoNotDisturbRequest};
+use crate::core::preferences::UiPreferences;
 use crate::core::pty_session::{GridUpdateMessage, PtyInputMessage};
+use crate::core::recent::RecentList;
+use crate::core::render::ExportFormat;
+use crate::core::search::{SearchHit, SearchResults};
+use crate::core::session::ProjectDefaults;
+use crate::core::transcript::{SessionUsage, UsageSummary};
 use crate::core::{
-    ClientMessage, Config, JsonApiDocument, ProjectResource, ServerMessage, SessionResource,
+    ClientMessage, Config, JsonApiDocument, JsonApiErrorDocument, JsonApiResource, ProjectResource,
+    ServerMessage, SessionResource,
 };
 
+/// Read-only session stream opened by `CodeMuxClient::watch_session`
+pub type WatchStream = WsStream;
+
 #[derive(Debug, Clone)]
 pub struct CodeMuxClient {
     base_url: String,
     client: Client,
+    /// Sent with every request, see `core::auth`
+    token: Option<String>,
+    /// The server's Unix socket, which requests and WebSockets go through
+    /// instead of `base_url`'s port when set
+    socket: Option<PathBuf>,
+    /// Sessions are attached to read-only, see `ConnectQuery`
+    read_only: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -22,12 +55,33 @@ pub struct CreateSessionRequest {
     pub args: Vec<String>,
     pub project_id: Option<String>,
     pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Directory to run the agent in, relative to the project or absolute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Record the session's output, see `codemux recordings`
+    #[serde(skip_serializing_if = "is_false")]
+    pub record: bool,
+    /// Never end the session for sitting idle, see `[session] idle_timeout_secs`
+    #[serde(skip_serializing_if = "is_false")]
+    pub keep_alive: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
 }
 
 #[derive(Debug, Serialize)]
 pub struct CreateProjectRequest {
     pub name: String,
     pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,18 +104,68 @@ impl Default for ReconnectionConfig {
 }
 
 impl CodeMuxClient {
-    pub fn new(base_url: String) -> Self {
-        let client = Client::builder()
+    pub fn new(base_url: String) -> Result<Self> {
+        Self::with_token(base_url, None)
+    }
+
+    /// Client for a server that requires `token`
+    pub fn with_token(base_url: String, token: Option<String>) -> Result<Self> {
+        Self::connect(base_url, token, None)
+    }
+
+    fn connect(base_url: String, token: Option<String>, socket: Option<PathBuf>) -> Result<Self> {
+        // Tokens from the config file may carry a trailing newline
+        let token = token
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(token) = &token {
+            let mut value = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))
+                .map_err(|_| anyhow!("The API token contains characters that can't be sent"))?;
+            value.set_sensitive(true);
+            headers.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        let builder = Client::builder()
             .timeout(Duration::from_secs(30))
+            .default_headers(headers);
+        #[cfg(unix)]
+        let builder = match &socket {
+            Some(socket) => builder.unix_socket(socket.clone()),
+            None => builder,
+        };
+        let client = builder
             .build()
-            .expect("Failed to create HTTP client");
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        Ok(Self {
+            base_url,
+            client,
+            token,
+            socket,
+            read_only: false,
+        })
+    }
 
-        Self { base_url, client }
+    /// The same client, attaching to sessions without being able to type
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
     }
 
-    pub fn from_config(config: &Config) -> Self {
-        let base_url = format!("http://localhost:{}", config.server.port);
-        Self::new(base_url)
+    /// Client for the server in `[client] server_url`, or else the local one,
+    /// with the token for it, see `auth::client_token`
+    ///
+    /// The local server is reached through its `[server] socket_path` when it
+    /// has one; links for browsers still use the port.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let base_url = config.client.server_url(config.server.port);
+        let token = auth::client_token(config, &base_url);
+        let socket = config
+            .server
+            .socket_path
+            .clone()
+            .filter(|path| cfg!(unix) && config.client.server_url.is_none() && path.exists());
+        Self::connect(base_url, token, socket)
     }
 
     /// Check if server is running by trying to connect
@@ -86,47 +190,15 @@ impl CodeMuxClient {
             args: args.clone(),
             project_id: project_id.clone(),
             path: None,
+            color: None,
+            icon: None,
+            name: None,
+            cwd: None,
+            record: false,
+            keep_alive: false,
         };
 
-        tracing::debug!("POST /api/sessions request body: {:?}", request);
-        if let Ok(json) = serde_json::to_string_pretty(&request) {
-            tracing::debug!("POST /api/sessions JSON body:\n{}", json);
-        }
-
-        let url = format!("{}/api/sessions", self.base_url);
-        tracing::debug!("Making POST request to: {}", url);
-
-        let response = self.client.post(&url).json(&request).send().await?;
-
-        let status = response.status();
-        tracing::debug!("POST /api/sessions response status: {}", status);
-
-        if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            tracing::error!(
-                "Session creation failed with status {}: {}",
-                status,
-                error_text
-            );
-            return Err(anyhow!(
-                "Failed to create session: {} - {}",
-                status,
-                error_text
-            ));
-        }
-
-        tracing::debug!("POST /api/sessions response status: {}", response.status());
-
-        let response_text = response.text().await?;
-        let json_api: JsonApiDocument<SessionResource> = serde_json::from_str(&response_text)
-            .map_err(|e| anyhow!("Failed to parse session response: {}", e))?;
-        let session_resource = json_api.data;
-
-        tracing::debug!("Parsed session resource: {:?}", session_resource);
-        Ok(session_resource)
+        self.send_create_session(&request).await
     }
 
     /// Create a new session on the server with explicit path
@@ -141,8 +213,44 @@ impl CodeMuxClient {
             args: args.clone(),
             project_id: None,
             path: Some(path.clone()),
+            color: None,
+            icon: None,
+            name: None,
+            cwd: None,
+            record: false,
+            keep_alive: false,
         };
 
+        self.send_create_session(&request).await
+    }
+
+    /// Take over an agent process started outside codemux as a new session
+    pub async fn adopt_process(
+        &self,
+        agent: String,
+        pid: u32,
+        path: Option<String>,
+    ) -> Result<SessionResource> {
+        let response = self
+            .client
+            .post(format!("{}/api/adopt", self.base_url))
+            .json(&serde_json::json!({ "agent": agent, "pid": pid, "path": path }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow!("{} - {}", status, error_text));
+        }
+        let json_api: JsonApiDocument<SessionResource> = response.json().await?;
+        Ok(json_api.data)
+    }
+
+    /// Create a session from a full request, e.g. with a color, icon or name
+    pub async fn send_create_session(
+        &self,
+        request: &CreateSessionRequest,
+    ) -> Result<SessionResource> {
         tracing::debug!("POST /api/sessions request body: {:?}", request);
         if let Ok(json) = serde_json::to_string_pretty(&request) {
             tracing::debug!("POST /api/sessions JSON body:\n{}", json);
@@ -151,7 +259,7 @@ impl CodeMuxClient {
         let url = format!("{}/api/sessions", self.base_url);
         tracing::debug!("Making POST request to: {}", url);
 
-        let response = self.client.post(&url).json(&request).send().await?;
+        let response = self.client.post(&url).json(request).send().await?;
 
         let status = response.status();
         tracing::debug!("POST /api/sessions response status: {}", status);
@@ -234,11 +342,13 @@ impl CodeMuxClient {
         Ok(all_sessions)
     }
 
-    /// Delete a session
-    pub async fn delete_session(&self, session_id: &str) -> Result<()> {
+    /// Delete a session; returns when it ends if the server holds it for a
+    /// grace period first, `None` if it ended right away
+    pub async fn delete_session(&self, session_id: &str, now: bool) -> Result<Option<String>> {
         let response = self
             .client
             .delete(format!("{}/api/sessions/{}", self.base_url, session_id))
+            .query(&[("now", now)])
             .send()
             .await?;
 
@@ -246,12 +356,552 @@ impl CodeMuxClient {
             return Err(anyhow!("Failed to delete session: {}", response.status()));
         }
 
-        Ok(())
+        let body: serde_json::Value = response.json().await.unwrap_or_default();
+        Ok(body["data"]["ends_at"].as_str().map(str::to_string))
+    }
+
+    /// Keep a killed session running while its grace period lasts
+    pub async fn undo_kill(&self, session_id: &str) -> Result<SessionResource> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/api/sessions/{}/undo-kill",
+                self.base_url, session_id
+            ))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response
+                .json::<JsonApiErrorDocument>()
+                .await
+                .ok()
+                .and_then(|doc| doc.errors.into_iter().next())
+                .and_then(|error| error.detail);
+            return Err(match detail {
+                Some(detail) => anyhow!("Failed to undo kill: {}", detail),
+                None => anyhow!("Failed to undo kill: {}", status),
+            });
+        }
+
+        let json_api: JsonApiDocument<SessionResource> = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse session response: {}", e))?;
+        Ok(json_api.data)
     }
 
-    /// Create a new project
-    pub async fn create_project(&self, name: String, path: String) -> Result<ProjectResource> {
-        let request = CreateProjectRequest { name, path };
+    /// Make sure the server accepts this client's token
+    pub async fn check_token(&self) -> Result<()> {
+        let response = self
+            .client
+            .get(format!("{}/api/projects", self.base_url))
+            .send()
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::UNAUTHORIZED => Err(anyhow!("The server refused the token")),
+            status => Err(anyhow!("Failed to check the token: {}", status)),
+        }
+    }
+
+    /// Fetch the display preferences shared by all clients
+    pub async fn get_preferences(&self) -> Result<UiPreferences> {
+        let response = self
+            .client
+            .get(format!("{}/api/preferences", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get preferences: {}", response.status()));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<UiPreferences, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Preferences response has no attributes"))
+    }
+
+    /// Fetch starred and recently attached sessions and starred projects
+    pub async fn get_recent(&self) -> Result<RecentList> {
+        let response = self
+            .client
+            .get(format!("{}/api/recent", self.base_url))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to get recent sessions: {}",
+                response.status()
+            ));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<RecentList, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Recent sessions response has no attributes"))
+    }
+
+    /// Fetch the do-not-disturb switch
+    pub async fn get_do_not_disturb(&self) -> Result<DoNotDisturb> {
+        let response = self
+            .client
+            .get(format!("{}/api/notifications/dnd", self.base_url))
+            .send()
+            .await?;
+        Self::do_not_disturb_from(response).await
+    }
+
+    /// Switch do-not-disturb on, for `minutes` if given, or off
+    pub async fn set_do_not_disturb(
+        &self,
+        enabled: bool,
+        minutes: Option<u32>,
+    ) -> Result<DoNotDisturb> {
+        let response = self
+            .client
+            .put(format!("{}/api/notifications/dnd", self.base_url))
+            .json(&SetDoNotDisturbRequest { enabled, minutes })
+            .send()
+            .await?;
+        Self::do_not_disturb_from(response).await
+    }
+
+    async fn do_not_disturb_from(response: reqwest::Response) -> Result<DoNotDisturb> {
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to update do-not-disturb: {}",
+                response.status()
+            ));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<DoNotDisturb, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Do-not-disturb response has no attributes"))
+    }
+
+    /// Fetch the server's log filter
+    pub async fn get_log_level(&self) -> Result<String> {
+        let response = self
+            .client
+            .get(format!("{}/api/log-level", self.base_url))
+            .send()
+            .await?;
+        Self::log_level_from(response).await
+    }
+
+    /// Change the server's log filter to `filter`, a level or `RUST_LOG`
+    /// directives; returns the filter now in effect
+    pub async fn set_log_level(&self, filter: &str) -> Result<String> {
+        let response = self
+            .client
+            .put(format!("{}/api/log-level", self.base_url))
+            .json(&LogLevel {
+                filter: filter.to_string(),
+            })
+            .send()
+            .await?;
+        Self::log_level_from(response).await
+    }
+
+    async fn log_level_from(response: reqwest::Response) -> Result<String> {
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response
+                .json::<JsonApiErrorDocument>()
+                .await
+                .ok()
+                .and_then(|doc| doc.errors.into_iter().next())
+                .and_then(|error| error.detail);
+            return Err(anyhow!(
+                "Failed to change the log level: {}",
+                detail.unwrap_or_else(|| status.to_string())
+            ));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<LogLevel, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .map(|level| level.filter)
+            .ok_or_else(|| anyhow!("Log level response has no attributes"))
+    }
+
+    /// Create an API token limited to `scope`, expiring after `ttl` if given
+    pub async fn create_token(
+        &self,
+        name: &str,
+        scope: TokenScope,
+        ttl: Option<Duration>,
+    ) -> Result<NewApiToken> {
+        let response = self
+            .client
+            .post(format!("{}/api/tokens", self.base_url))
+            .json(&CreateApiTokenRequest {
+                name: name.to_string(),
+                scope,
+                ttl_seconds: ttl.map(|ttl| ttl.as_secs()),
+            })
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "create the token").await);
+        }
+
+        let document: JsonApiDocument<JsonApiResource<NewApiToken, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Token response has no attributes"))
+    }
+
+    /// Create a token for a link that opens only `session_id`, see `share_url`
+    pub async fn share_session(
+        &self,
+        session_id: &str,
+        interactive: bool,
+        ttl: Duration,
+    ) -> Result<NewApiToken> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/api/sessions/{}/share",
+                self.base_url, session_id
+            ))
+            .json(&ShareSessionRequest {
+                interactive,
+                ttl_seconds: Some(ttl.as_secs()),
+            })
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "share the session").await);
+        }
+
+        let document: JsonApiDocument<JsonApiResource<NewApiToken, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Share response has no attributes"))
+    }
+
+    /// The server's API tokens, without their secrets
+    pub async fn list_tokens(&self) -> Result<Vec<ApiToken>> {
+        let response = self
+            .client
+            .get(format!("{}/api/tokens", self.base_url))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "list tokens").await);
+        }
+
+        let document: JsonApiDocument<Vec<JsonApiResource<ApiToken, ()>>> = response.json().await?;
+        Ok(document
+            .data
+            .into_iter()
+            .filter_map(|resource| resource.attributes)
+            .collect())
+    }
+
+    /// Revoke an API token by ID or name
+    pub async fn revoke_token(&self, id_or_name: &str) -> Result<ApiToken> {
+        let response = self
+            .client
+            .delete(format!("{}/api/tokens/{}", self.base_url, id_or_name))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "revoke the token").await);
+        }
+
+        let document: JsonApiDocument<JsonApiResource<ApiToken, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Token response has no attributes"))
+    }
+
+    async fn token_error(response: reqwest::Response, action: &str) -> anyhow::Error {
+        let status = response.status();
+        let detail = response
+            .json::<JsonApiErrorDocument>()
+            .await
+            .ok()
+            .and_then(|doc| doc.errors.into_iter().next())
+            .and_then(|error| error.detail);
+        let reason = detail.unwrap_or_else(|| status.to_string());
+        anyhow!("Failed to {}: {}", action, reason)
+    }
+
+    /// A session's git checkpoints, oldest first
+    pub async fn list_checkpoints(&self, session_id: &str) -> Result<Vec<Checkpoint>> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/api/sessions/{}/checkpoints",
+                self.base_url, session_id
+            ))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "list checkpoints").await);
+        }
+
+        let document: JsonApiDocument<Vec<JsonApiResource<Checkpoint, ()>>> =
+            response.json().await?;
+        Ok(document
+            .data
+            .into_iter()
+            .filter_map(|resource| resource.attributes)
+            .collect())
+    }
+
+    /// Put a session's working tree back the way it was at a checkpoint
+    pub async fn restore_checkpoint(
+        &self,
+        session_id: &str,
+        checkpoint: &str,
+    ) -> Result<RestoredCheckpoint> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/api/sessions/{}/checkpoints/{}/restore",
+                self.base_url, session_id, checkpoint
+            ))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "restore the checkpoint").await);
+        }
+
+        let document: JsonApiDocument<JsonApiResource<RestoredCheckpoint, ()>> =
+            response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Checkpoint response has no attributes"))
+    }
+
+    /// Summary of the sessions of the last `hours`
+    pub async fn get_digest(&self, hours: u32) -> Result<Digest> {
+        let response = self
+            .client
+            .get(format!("{}/api/digest?hours={}", self.base_url, hours))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to get digest: {}", response.status()));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<Digest, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Digest response has no attributes"))
+    }
+
+    /// Sessions whose transcripts, scrollback or notes contain every word of `query`
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>> {
+        let response = self
+            .client
+            .get(format!("{}/api/search", self.base_url))
+            .query(&[("q", query)])
+            .query(&[("limit", limit)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to search: {}", response.status()));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<SearchResults, ()>> = response.json().await?;
+        Ok(document
+            .data
+            .attributes
+            .map(|results| results.hits)
+            .unwrap_or_default())
+    }
+
+    /// Save a paste to a file on the server and return the file's path there
+    pub async fn save_paste_file(&self, session_id: &str, text: &str) -> Result<String> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/api/sessions/{}/paste-file",
+                self.base_url, session_id
+            ))
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to save paste: {}", response.status()));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<serde_json::Value, ()>> =
+            response.json().await?;
+        document
+            .data
+            .attributes
+            .as_ref()
+            .and_then(|attributes| attributes["path"].as_str())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("Paste file response has no path"))
+    }
+
+    /// Interrupt the agent and return the last message sent to it, if any
+    ///
+    /// With `restore` the server types the message back into the agent's input.
+    pub async fn retract_last_message(
+        &self,
+        session_id: &str,
+        restore: bool,
+    ) -> Result<Option<String>> {
+        let response = self
+            .client
+            .post(format!(
+                "{}/api/sessions/{}/retract",
+                self.base_url, session_id
+            ))
+            .json(&serde_json::json!({ "restore": restore }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to retract: {}", response.status()));
+        }
+
+        let document: JsonApiDocument<JsonApiResource<serde_json::Value, ()>> =
+            response.json().await?;
+        Ok(document
+            .data
+            .attributes
+            .as_ref()
+            .and_then(|attributes| attributes["text"].as_str())
+            .map(str::to_string))
+    }
+
+    /// Leave a handover note on a running session; `None` clears it
+    pub async fn set_session_note(
+        &self,
+        session_id: &str,
+        note: Option<String>,
+    ) -> Result<SessionResource> {
+        let response = self
+            .client
+            .put(format!("{}/api/sessions/{}/note", self.base_url, session_id))
+            .json(&serde_json::json!({ "note": note }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("Failed to save note: {}", response.status()));
+        }
+
+        let document: JsonApiDocument<SessionResource> = response.json().await?;
+        Ok(document.data)
+    }
+
+    /// Fetch the session's scrollback rendered as a standalone HTML page
+    pub async fn get_scrollback_html(&self, session_id: &str, lines: usize) -> Result<String> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/api/sessions/{}/scrollback.html",
+                self.base_url, session_id
+            ))
+            .query(&[("lines", lines)])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Failed to export scrollback: {}",
+                response.status()
+            ));
+        }
+
+        Ok(response.text().await?)
+    }
+
+    /// A session's conversation rendered as a document
+    pub async fn export_conversation(
+        &self,
+        session_id: &str,
+        format: ExportFormat,
+    ) -> Result<String> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/api/sessions/{}/export",
+                self.base_url, session_id
+            ))
+            .query(&[("format", format)])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "export the conversation").await);
+        }
+        Ok(response.text().await?)
+    }
+
+    /// Tokens and cost one session's agent has used so far
+    pub async fn get_session_usage(&self, session_id: &str) -> Result<SessionUsage> {
+        let response = self
+            .client
+            .get(format!(
+                "{}/api/sessions/{}/usage",
+                self.base_url, session_id
+            ))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "get the session's usage").await);
+        }
+
+        let document: JsonApiDocument<JsonApiResource<SessionUsage, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Usage response has no attributes"))
+    }
+
+    /// Usage of every running session, per project and overall
+    pub async fn get_usage(&self) -> Result<UsageSummary> {
+        let response = self
+            .client
+            .get(format!("{}/api/usage", self.base_url))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "get usage").await);
+        }
+
+        let document: JsonApiDocument<JsonApiResource<UsageSummary, ()>> = response.json().await?;
+        document
+            .data
+            .attributes
+            .ok_or_else(|| anyhow!("Usage response has no attributes"))
+    }
+
+    /// Register a project, as a sub-project of `parent` if given
+    pub async fn create_project(
+        &self,
+        name: String,
+        path: String,
+        parent: Option<String>,
+    ) -> Result<ProjectResource> {
+        let request = CreateProjectRequest { name, path, parent };
 
         let response = self
             .client
@@ -271,6 +921,96 @@ impl CodeMuxClient {
         Ok(project_resource)
     }
 
+    /// Remove a project by ID or (qualified) name, ending its running
+    /// sessions with `kill_sessions`; returns the IDs of those sessions
+    pub async fn remove_project(&self, project: &str, kill_sessions: bool) -> Result<Vec<String>> {
+        let mut url = url::Url::parse(&format!("{}/api/projects", self.base_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid server URL {}", self.base_url))?
+            .push(project);
+        let response = self
+            .client
+            .delete(url)
+            .query(&[("kill_sessions", kill_sessions)])
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response
+                .json::<JsonApiErrorDocument>()
+                .await
+                .ok()
+                .and_then(|doc| doc.errors.into_iter().next())
+                .and_then(|error| error.detail);
+            return Err(match detail {
+                Some(detail) => anyhow!("Failed to remove project: {}", detail),
+                None => anyhow!("Failed to remove project: {}", status),
+            });
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Removed {
+            sessions: Vec<String>,
+        }
+        let json_api: JsonApiDocument<Removed> = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse project removal response: {}", e))?;
+        Ok(json_api.data.sessions)
+    }
+
+    /// Rename a project by ID or (qualified) name
+    pub async fn rename_project(&self, project: &str, name: &str) -> Result<ProjectResource> {
+        self.update_project(project, serde_json::json!({ "name": name }))
+            .await
+            .map_err(|e| anyhow!("Failed to rename project: {}", e))
+    }
+
+    /// Replace the defaults of a project by ID or (qualified) name
+    pub async fn set_project_defaults(
+        &self,
+        project: &str,
+        defaults: &ProjectDefaults,
+    ) -> Result<ProjectResource> {
+        self.update_project(project, serde_json::json!({ "defaults": defaults }))
+            .await
+            .map_err(|e| anyhow!("Failed to update project: {}", e))
+    }
+
+    /// `PATCH` a project with `body`
+    async fn update_project(
+        &self,
+        project: &str,
+        body: serde_json::Value,
+    ) -> Result<ProjectResource> {
+        let mut url = url::Url::parse(&format!("{}/api/projects", self.base_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid server URL {}", self.base_url))?
+            .push(project);
+        let response = self.client.patch(url).json(&body).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response
+                .json::<JsonApiErrorDocument>()
+                .await
+                .ok()
+                .and_then(|doc| doc.errors.into_iter().next())
+                .and_then(|error| error.detail);
+            return Err(match detail {
+                Some(detail) => anyhow!(detail),
+                None => anyhow!("{}", status),
+            });
+        }
+
+        let json_api: JsonApiDocument<ProjectResource> = response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse project response: {}", e))?;
+        Ok(json_api.data)
+    }
+
     /// List all projects
     pub async fn list_projects(&self) -> Result<Vec<ProjectResource>> {
         let response = self
@@ -290,6 +1030,64 @@ impl CodeMuxClient {
         Ok(json_api.data)
     }
 
+    /// One page of the projects, with their sessions filtered by `query`
+    pub async fn list_projects_page(
+        &self,
+        query: &ListQuery,
+    ) -> Result<JsonApiPage<ProjectResource>> {
+        let response = self
+            .client
+            .get(format!("{}/api/projects", self.base_url))
+            .query(&query.params())
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response
+                .json::<JsonApiErrorDocument>()
+                .await
+                .ok()
+                .and_then(|doc| doc.errors.into_iter().next())
+                .and_then(|error| error.detail);
+            return Err(match detail {
+                Some(detail) => anyhow!("Failed to list projects: {}", detail),
+                None => anyhow!("Failed to list projects: {}", status),
+            });
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| anyhow!("Failed to parse JSON API page response: {}", e))
+    }
+
+    /// Download the `.tar.zst` archive of a project by ID or (qualified)
+    /// name, with the file name the server suggests
+    pub async fn download_project_archive(&self, project: &str) -> Result<(String, Vec<u8>)> {
+        let mut url = url::Url::parse(&format!("{}/api/projects", self.base_url))?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Invalid server URL {}", self.base_url))?
+            .push(project)
+            .push("archive");
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Err(Self::token_error(response, "archive the project").await);
+        }
+
+        // Only the name is taken, so a server can't point the file elsewhere
+        let file_name = response
+            .headers()
+            .get(reqwest::header::CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split_once("filename=\""))
+            .and_then(|(_, rest)| rest.split_once('"'))
+            .and_then(|(name, _)| std::path::Path::new(name).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}.tar.zst", project.replace('/', "-")));
+        Ok((file_name, response.bytes().await?.to_vec()))
+    }
+
     /// Resolve a directory path to a project ID
     /// Accepts both absolute paths and relative paths (resolved from current directory)
     /// Special case: "." resolves to current directory
@@ -364,22 +1162,37 @@ impl CodeMuxClient {
         session_id: &str,
         config: ReconnectionConfig,
     ) -> Result<SessionConnection> {
-        let ws_url = format!(
-            "ws://localhost:{}/ws/{}",
-            self.base_url.trim_start_matches("http://localhost:"),
+        // http becomes ws and https wss; grid updates come compact, see `frame_encoding`
+        let mut ws_url = format!(
+            "ws{}/ws/{}?capabilities=msgpack,deflate,rle",
+            self.base_url.trim_start_matches("http"),
             session_id
         );
+        if self.read_only {
+            ws_url.push_str("&read_only=true");
+        }
 
         // Try to connect with exponential backoff
         for attempt in 0..=config.max_attempts {
-            match connect_async(&ws_url).await {
-                Ok((ws_stream, _)) => {
+            match connect_ws(
+                ws_request(&ws_url, self.token.as_deref())?,
+                self.socket.as_deref(),
+            )
+            .await
+            {
+                Ok(ws_stream) => {
                     tracing::info!(
                         "WebSocket connected to session {} (attempt {})",
                         session_id,
                         attempt + 1
                     );
-                    return Ok(SessionConnection::new(ws_stream, session_id.to_string()));
+                    return Ok(SessionConnection::new(
+                        ws_stream,
+                        session_id.to_string(),
+                        ws_url,
+                        self.token.clone(),
+                        self.socket.clone(),
+                    ));
                 }
                 Err(e) => {
                     if attempt < config.max_attempts {
@@ -425,9 +1238,57 @@ impl CodeMuxClient {
         unreachable!()
     }
 
-    /// Get the web interface URL for a session
+    /// Open a read-only stream of a session's output
+    ///
+    /// The raw stream carries PTY bytes in binary frames; the accessible stream
+    /// carries `AccessibleEvent` JSON in text frames.
+    pub async fn watch_session(&self, session_id: &str, accessible: bool) -> Result<WatchStream> {
+        let stream = if accessible { "accessible" } else { "raw" };
+        let ws_url = format!(
+            "ws{}/ws/session/{}/{}",
+            self.base_url.trim_start_matches("http"),
+            session_id,
+            stream
+        );
+        connect_ws(
+            ws_request(&ws_url, self.token.as_deref())?,
+            self.socket.as_deref(),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to connect to {}: {}", ws_url, e))
+    }
+
+    /// Get the web interface URL for a session, with the token the page needs
     pub fn get_session_url(&self, session_id: &str) -> String {
-        format!("{}/session/{}", self.base_url, session_id)
+        self.with_token_query(format!("{}/session/{}", self.base_url, session_id))
+    }
+
+    /// The web UI's page for a session, opened with a share link's token
+    /// instead of this client's
+    pub fn share_url(&self, session_id: &str, secret: &str) -> String {
+        format!("{}/session/{}?token={}", self.base_url, session_id, secret)
+    }
+
+    /// Get the web interface URL, with the token the page needs
+    pub fn web_url(&self) -> String {
+        self.with_token_query(format!("{}/", self.base_url))
+    }
+
+    /// Sign-in page of the server's identity provider that ends by sending a
+    /// CLI token to `callback` with `state`, see `server::web::oidc`
+    pub fn login_url(&self, callback: &str, state: &str) -> String {
+        let mut login = format!("{}/auth/login?cli_callback=", self.base_url);
+        login.extend(url::form_urlencoded::byte_serialize(callback.as_bytes()));
+        login.push_str("&cli_state=");
+        login.extend(url::form_urlencoded::byte_serialize(state.as_bytes()));
+        login
+    }
+
+    fn with_token_query(&self, url: String) -> String {
+        match &self.token {
+            Some(token) => format!("{}?token={}", url, token),
+            None => url,
+        }
     }
 
     /// Shutdown the server
@@ -448,22 +1309,31 @@ impl CodeMuxClient {
 
 /// WebSocket connection to a specific session
 pub struct SessionConnection {
-    ws_stream: tokio_tungstenite::WebSocketStream<
-        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-    >,
+    ws_stream: WsStream,
     session_id: String,
+    /// For reconnecting
+    ws_url: String,
+    token: Option<String>,
+    socket: Option<PathBuf>,
+    /// How binary frames are encoded, from the server's `capabilities` message
+    encoding: FrameEncoding,
 }
 
 impl SessionConnection {
     fn new(
-        ws_stream: tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
+        ws_stream: WsStream,
         session_id: String,
+        ws_url: String,
+        token: Option<String>,
+        socket: Option<PathBuf>,
     ) -> Self {
         Self {
             ws_stream,
             session_id,
+            ws_url,
+            token,
+            socket,
+            encoding: FrameEncoding::default(),
         }
     }
 
@@ -479,7 +1349,8 @@ impl SessionConnection {
         use futures_util::{SinkExt, StreamExt};
 
         // Create channels for PTY communication
-        let (input_tx, mut input_rx) = tokio::sync::mpsc::unbounded_channel::<PtyInputMessage>();
+        // The server acknowledges and reports lag; they're passed on through this queue
+        let (input_tx, mut input_rx) = crate::core::input_queue::channel();
         let (output_tx, _output_rx) = tokio::sync::broadcast::channel::<PtyOutputMessage>(100);
         let (grid_tx, _grid_rx) = tokio::sync::broadcast::channel::<GridUpdateMessage>(100);
         let (control_tx, mut control_rx) =
@@ -487,21 +1358,38 @@ impl SessionConnection {
         let (size_tx, _size_rx) = tokio::sync::broadcast::channel::<portable_pty::PtySize>(10);
         let (connection_status_tx, _connection_status_rx) =
             tokio::sync::broadcast::channel::<ConnectionStatus>(10);
+        let (alert_tx, _alert_rx) = tokio::sync::broadcast::channel::<String>(10);
+        let prompt_tx = tokio::sync::watch::Sender::new(None);
+        let exit_tx = tokio::sync::watch::Sender::new(None);
 
         let ws_stream = self.ws_stream;
         let session_id = self.session_id.clone();
+        let ws_url = self.ws_url.clone();
+        let token = self.token.clone();
+        let socket = self.socket.clone();
 
         // Clone the broadcast senders for use in the spawn task
         let output_tx_clone = output_tx.clone();
         let grid_tx_clone = grid_tx.clone();
         let connection_status_tx_clone = connection_status_tx.clone();
+        let alert_tx_clone = alert_tx.clone();
+        let prompt_tx_clone = prompt_tx.clone();
+        let exit_tx_clone = exit_tx.clone();
+        let input_tx_clone = input_tx.clone();
 
         // Spawn task to handle WebSocket -> PTY channel forwarding with auto-reconnection
         tokio::spawn(async move {
             let reconnect_config = ReconnectionConfig::default();
             let mut current_ws = ws_stream;
             let mut reconnect_attempt = 0u32;
-            let should_reconnect = true;
+            // Cleared once the agent exits: reconnecting would restore the session
+            let mut should_reconnect = true;
+            // Warn once when another client takes control, not on every update
+            let mut could_write = true;
+            // What the server calls this client, for its acknowledgements
+            let mut server_client_id = String::new();
+            // Set by the server's `capabilities` message, once per connection
+            let mut encoding = self.encoding;
 
             // Send initial connected status
             let _ = connection_status_tx_clone.send(ConnectionStatus::Connected);
@@ -510,13 +1398,12 @@ impl SessionConnection {
             async fn attempt_reconnect(
                 attempt: u32,
                 session_id: &str,
+                ws_url: &str,
+                token: Option<&str>,
+                socket: Option<&Path>,
                 reconnect_config: &ReconnectionConfig,
                 status_tx: &tokio::sync::broadcast::Sender<ConnectionStatus>,
-            ) -> Option<
-                tokio_tungstenite::WebSocketStream<
-                    tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-                >,
-            > {
+            ) -> Option<WsStream> {
                 if attempt >= reconnect_config.max_attempts {
                     tracing::error!(
                         "Max reconnection attempts reached for session {}",
@@ -554,9 +1441,22 @@ impl SessionConnection {
 
                 sleep(delay_with_jitter).await;
 
-                let ws_url = format!("ws://localhost:{}/ws/{}", crate::core::config::default_server_port(), session_id);
-                match connect_async(&ws_url).await {
-                    Ok((new_ws, _)) => {
+                // Ask the server to restore the session if it restarted meanwhile
+                let ws_url = format!("{}&rebind=true", ws_url);
+                let request = match ws_request(&ws_url, token) {
+                    Ok(request) => request,
+                    Err(e) => {
+                        tracing::error!(
+                            "Invalid WebSocket request for session {}: {}",
+                            session_id,
+                            e
+                        );
+                        let _ = status_tx.send(ConnectionStatus::Disconnected);
+                        return None;
+                    }
+                };
+                match connect_ws(request, socket).await {
+                    Ok(new_ws) => {
                         tracing::info!(
                             "WebSocket reconnected to session {} (attempt {})",
                             session_id,
@@ -581,18 +1481,7 @@ impl SessionConnection {
                 tokio::select! {
                     // Handle input from TUI -> WebSocket
                     Some(input_msg) = input_rx.recv() => {
-                        // Handle both Key and Scroll events
-                        let client_msg = match input_msg.input {
-                            crate::core::pty_session::PtyInput::Key { event, .. } => {
-                                ClientMessage::Key {
-                                    code: event.code,
-                                    modifiers: event.modifiers
-                                }
-                            }
-                            crate::core::pty_session::PtyInput::Scroll { direction, lines, .. } => {
-                                ClientMessage::Scroll { direction, lines }
-                            }
-                        };
+                        let client_msg = input_client_message(input_msg);
 
                         if let Ok(json) = serde_json::to_string(&client_msg) {
                             tracing::trace!("Client WebSocket sending input: {} chars", json.len());
@@ -600,7 +1489,7 @@ impl SessionConnection {
                                 tracing::error!("Failed to send input via client WebSocket - connection lost");
                                 // Trigger reconnection
                                 if should_reconnect {
-                                    if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &reconnect_config, &connection_status_tx_clone).await {
+                                    if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &ws_url, token.as_deref(), socket.as_deref(), &reconnect_config, &connection_status_tx_clone).await {
                                         current_ws = new_ws;
                                         reconnect_attempt = 0; // Reset counter on successful reconnection
                                         continue;
@@ -624,12 +1513,12 @@ impl SessionConnection {
                     Some(control_msg) = control_rx.recv() => {
                         match control_msg {
                             PtyControlMessage::Resize { rows, cols } => {
-                                let client_msg = ClientMessage::Resize { rows, cols };
+                                let client_msg = ClientMessage::Resize { rows, cols, driver: false };
                                 if let Ok(json) = serde_json::to_string(&client_msg) {
                                     if current_ws.send(Message::Text(json)).await.is_err() {
                                         // Trigger reconnection on control message failure
                                         if should_reconnect {
-                                            if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &reconnect_config, &connection_status_tx_clone).await {
+                                            if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &ws_url, token.as_deref(), socket.as_deref(), &reconnect_config, &connection_status_tx_clone).await {
                                                 current_ws = new_ws;
                                                 reconnect_attempt = 0;
                                                 continue;
@@ -652,6 +1541,31 @@ impl SessionConnection {
                                 tracing::warn!("Client received RequestKeyframe - ignoring as server handles keyframes automatically");
                                 drop(response_tx);
                             }
+                            PtyControlMessage::RequestScreenState { response_tx } => {
+                                // Only raw PTY consumers need escape-sequence snapshots
+                                tracing::warn!("Client received RequestScreenState - ignoring");
+                                drop(response_tx);
+                            }
+                            PtyControlMessage::RequestScrollback { response_tx, .. } => {
+                                // Scrollback is exported over HTTP, not the terminal WebSocket
+                                tracing::warn!("Client received RequestScrollback - ignoring, use the scrollback export endpoint");
+                                drop(response_tx);
+                            }
+                            PtyControlMessage::RequestActivity { response_tx } => {
+                                // Activity metrics are tracked by the server's PTY session
+                                tracing::warn!("Client received RequestActivity - ignoring");
+                                drop(response_tx);
+                            }
+                            PtyControlMessage::RequestHeatmap { response_tx } => {
+                                // The heatmap is tracked by the server's PTY session
+                                tracing::warn!("Client received RequestHeatmap - ignoring");
+                                drop(response_tx);
+                            }
+                            PtyControlMessage::Retract { response_tx, .. } => {
+                                // The input transcript lives in the server's PTY session
+                                tracing::warn!("Client received Retract - ignoring");
+                                drop(response_tx);
+                            }
                             PtyControlMessage::Terminate => {
                                 // Send close message and break
                                 let _ = current_ws.close(None).await;
@@ -663,17 +1577,22 @@ impl SessionConnection {
                     // Handle messages from WebSocket -> PTY channels
                     msg = current_ws.next() => {
                         match msg {
-                            Some(Ok(Message::Text(text))) => {
-                                tracing::trace!("Client WebSocket received message: {} chars", text.len());
-                                if let Ok(server_msg) = serde_json::from_str::<ServerMessage>(&text) {
+                            Some(Ok(frame @ (Message::Text(_) | Message::Binary(_)))) => {
+                                tracing::trace!("Client WebSocket received message: {} bytes", frame.len());
+                                let parsed = match &frame {
+                                    Message::Binary(data) => encoding.decode(data),
+                                    _ => frame.to_text().map_err(anyhow::Error::from).and_then(|text| serde_json::from_str(text).map_err(anyhow::Error::from)),
+                                };
+                                if let Ok(server_msg) = parsed {
                                     match server_msg {
                                         ServerMessage::Output { data, timestamp } => {
                                             tracing::debug!("Client WebSocket forwarding output to PTY channel");
-                                            let output_msg = crate::core::pty_session::PtyOutputMessage { data, timestamp };
+                                            let output_msg = crate::core::pty_session::PtyOutputMessage { data: data.into(), timestamp, seq: 0 };
                                             let _ = output_tx_clone.send(output_msg);
                                         }
-                                        ServerMessage::GridUpdate { update } => {
+                                        ServerMessage::GridUpdate { mut update } => {
                                             tracing::debug!("Client WebSocket forwarding grid update to PTY channel");
+                                            update.run_length_decode();
                                             let _ = grid_tx_clone.send(update);
                                         }
                                         ServerMessage::PtySize { rows, cols } => {
@@ -683,9 +1602,59 @@ impl SessionConnection {
                                         ServerMessage::Error { message } => {
                                             tracing::error!("Server error: {}", message);
                                         }
+                                        ServerMessage::Rebound { restarted } => {
+                                            tracing::info!("Rebound to session {} (restarted: {})", session_id, restarted);
+                                            if restarted {
+                                                let _ = connection_status_tx_clone.send(ConnectionStatus::Restored);
+                                            }
+                                        }
+                                        ServerMessage::Alert { message } => {
+                                            tracing::warn!("Alert for session {}: {}", session_id, message);
+                                            let _ = alert_tx_clone.send(message);
+                                        }
+                                        ServerMessage::InputControl { control, client_id, can_write } => {
+                                            tracing::debug!("Input control of session {} as {}: {:?}", session_id, client_id, control);
+                                            if could_write && !can_write {
+                                                let message = match control.holder {
+                                                    Some(holder) => format!("Typing is paused: {} has control of this session", holder),
+                                                    None => "This session is read-only".to_string(),
+                                                };
+                                                let _ = alert_tx_clone.send(message);
+                                            }
+                                            could_write = can_write;
+                                            server_client_id = client_id;
+                                        }
+                                        ServerMessage::Capabilities { capabilities } => {
+                                            tracing::debug!("Session {} enabled capabilities {:?}", session_id, capabilities);
+                                            encoding = FrameEncoding::new(&capabilities);
+                                        }
+                                        ServerMessage::InputAck { seq, written } => {
+                                            input_tx_clone.publish_ack(crate::core::input_queue::InputAck {
+                                                client_id: server_client_id.clone(),
+                                                seq,
+                                                written,
+                                            });
+                                        }
+                                        ServerMessage::InputLag { lagging } => {
+                                            tracing::debug!("Input to session {} lagging: {}", session_id, lagging);
+                                            input_tx_clone.publish_lag(lagging);
+                                        }
+                                        ServerMessage::PromptDetected { prompt } => {
+                                            tracing::debug!("Session {} waits for approval: {:?}", session_id, prompt);
+                                            prompt_tx_clone.send_replace(Some(prompt));
+                                        }
+                                        ServerMessage::PromptCleared => {
+                                            prompt_tx_clone.send_replace(None);
+                                        }
+                                        ServerMessage::Exited { code } => {
+                                            tracing::info!("Agent of session {} exited with code {}", session_id, code);
+                                            should_reconnect = false;
+                                            exit_tx_clone.send_replace(Some(code));
+                                            let _ = connection_status_tx_clone.send(ConnectionStatus::Exited { code });
+                                        }
                                     }
                                 } else {
-                                    tracing::warn!("Failed to parse WebSocket message: {}", text);
+                                    tracing::warn!("Failed to parse WebSocket message: {}", frame.to_text().unwrap_or("<binary frame>"));
                                 }
                                 // Reset reconnection counter on successful message receive
                                 reconnect_attempt = 0;
@@ -694,7 +1663,7 @@ impl SessionConnection {
                                 tracing::info!("WebSocket connection closed for session {}", session_id);
                                 // Attempt to reconnect unless explicitly terminated
                                 if should_reconnect {
-                                    if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &reconnect_config, &connection_status_tx_clone).await {
+                                    if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &ws_url, token.as_deref(), socket.as_deref(), &reconnect_config, &connection_status_tx_clone).await {
                                         current_ws = new_ws;
                                         reconnect_attempt = 0;
                                         tracing::info!("Successfully reconnected to session {}", session_id);
@@ -714,7 +1683,7 @@ impl SessionConnection {
                                 tracing::error!("WebSocket error for session {}: {}", session_id, e);
                                 // Attempt to reconnect on error
                                 if should_reconnect {
-                                    if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &reconnect_config, &connection_status_tx_clone).await {
+                                    if let Some(new_ws) = attempt_reconnect(reconnect_attempt, &session_id, &ws_url, token.as_deref(), socket.as_deref(), &reconnect_config, &connection_status_tx_clone).await {
                                         current_ws = new_ws;
                                         reconnect_attempt = 0;
                                         tracing::info!("Successfully reconnected after error to session {}", session_id);
@@ -744,6 +1713,17 @@ impl SessionConnection {
             size_tx,
             grid_tx,
             connection_status_tx,
+            alert_tx,
+            // The server decides who may type and tells us through `input_control`
+            input_control: Default::default(),
+            // The server tells idle sessions apart
+            idle: Default::default(),
+            prompt_tx,
+            // The pipeline runs on the server; its spans aren't exported from here
+            trace: crate::core::telemetry::PipelineTrace::default(),
+            // The server fits the screen to this client's size
+            viewports: Default::default(),
+            exit_tx,
         }
     }
 
@@ -764,12 +1744,15 @@ impl SessionConnection {
             match self.ws_stream.next().await {
                 Some(Ok(Message::Text(text))) => {
                     let message: ServerMessage = serde_json::from_str(&text)?;
-                    return Ok(Some(message));
+                    if let ServerMessage::Capabilities { capabilities } = &message {
+                        self.encoding = FrameEncoding::new(capabilities);
+                    }
+                    return Ok(Some(expand_keyframe(message)));
                 }
                 Some(Ok(Message::Close(_))) => return Ok(None),
-                Some(Ok(Message::Binary(_))) => {
-                    // Skip binary messages, continue loop
-                    continue;
+                Some(Ok(Message::Binary(data))) => {
+                    let message = self.encoding.decode(&data)?;
+                    return Ok(Some(expand_keyframe(message)));
                 }
                 Some(Ok(Message::Ping(_))) | Some(Ok(Message::Pong(_))) => {
                     // Skip ping/pong messages, continue loop
@@ -787,22 +1770,17 @@ impl SessionConnection {
 
     /// Send PTY input to the session
     pub async fn send_input(&mut self, input: PtyInputMessage) -> Result<()> {
-        let client_msg = match input.input {
-            crate::core::pty_session::PtyInput::Key { event, .. } => ClientMessage::Key {
-                code: event.code,
-                modifiers: event.modifiers,
-            },
-            crate::core::pty_session::PtyInput::Scroll {
-                direction, lines, ..
-            } => ClientMessage::Scroll { direction, lines },
-        };
-        self.send_message(client_msg).await
+        self.send_message(input_client_message(input)).await
     }
 
     /// Send resize event to the session
     pub async fn send_resize(&mut self, rows: u16, cols: u16) -> Result<()> {
-        self.send_message(ClientMessage::Resize { rows, cols })
-            .await
+        self.send_message(ClientMessage::Resize {
+            rows,
+            cols,
+            driver: false,
+        })
+        .await
     }
 
     /// Close the connection
@@ -813,3 +1791,41 @@ impl SessionConnection {
         Ok(())
     }
 }
+
+/// The terminal WebSocket message for input sent through `PtyChannels`
+fn input_client_message(input_msg: PtyInputMessage) -> ClientMessage {
+    let seq = input_msg.seq;
+    match input_msg.input {
+        crate::core::pty_session::PtyInput::Key { event, .. } => ClientMessage::Key {
+            code: event.code,
+            modifiers: event.modifiers,
+            seq,
+        },
+        crate::core::pty_session::PtyInput::Scroll {
+            direction, lines, ..
+        } => ClientMessage::Scroll { direction, lines },
+        crate::core::pty_session::PtyInput::Raw { data, .. } => ClientMessage::Input { data, seq },
+        crate::core::pty_session::PtyInput::Paste { text, .. } => {
+            ClientMessage::Paste { text, seq }
+        }
+    }
+}
+
+/// `message` with a run-length encoded keyframe back to one cell per entry
+fn expand_keyframe(mut message: ServerMessage) -> ServerMessage {
+    if let ServerMessage::GridUpdate { update } = &mut message {
+        update.run_length_decode();
+    }
+    message
+}
+
+/// WebSocket handshake request for `url`, carrying `token` if there is one
+fn ws_request(url: &str, token: Option<&str>) -> Result<Request> {
+    let mut request = url.into_client_request()?;
+    if let Some(token) = token {
+        request
+            .headers_mut()
+            .insert("Authorization", format!("Bearer {}", token).parse()?);
+    }
+    Ok(request)
+}
@@ -0,0 +1,438 @@
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fmt;
+
+use crate::core::config::KeybindingsConfig;
+
+/// Something the TUI does in response to a key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Detach,
+    ToggleInteractive,
+    OpenWeb,
+    Refresh,
+    Help,
+    Retract,
+    DetachWithNote,
+    CopyMode,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::ToggleInteractive,
+        Action::CopyMode,
+        Action::Retract,
+        Action::OpenWeb,
+        Action::Refresh,
+        Action::Help,
+        Action::DetachWithNote,
+        Action::Detach,
+        Action::Quit,
+    ];
+
+    /// Short name for status bars
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Quit => "Exit",
+            Action::Detach => "Detach",
+            Action::ToggleInteractive => "Toggle Mode",
+            Action::OpenWeb => "Open Web",
+            Action::Refresh => "Refresh",
+            Action::Help => "Help",
+            Action::Retract => "Retract",
+            Action::DetachWithNote => "Detach + Note",
+            Action::CopyMode => "Copy",
+        }
+    }
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            Action::Quit => "Detach and exit (the session keeps running on the server)",
+            Action::Detach => {
+                "Detach, leaving the agent running; `codemux attach` picks it up again"
+            }
+            Action::ToggleInteractive => "Switch between monitoring and interactive mode",
+            Action::OpenWeb => "Open the web interface in your browser",
+            Action::Refresh => "Refresh the display",
+            Action::Help => "Show this help",
+            Action::Retract => "Interrupt the agent and type your last message back in",
+            Action::DetachWithNote => "Leave a note for whoever attaches next, then detach",
+            Action::CopyMode => "Select text on the screen and copy it to the clipboard",
+        }
+    }
+}
+
+/// A single key with modifiers, written like `ctrl+t`, `?` or `f1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        // A lone "+" is the plus key rather than a separator
+        let (modifier_names, key) = match spec.rsplit_once('+') {
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').unwrap_or(modifiers), "+"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", spec),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            modifiers |= match name.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(anyhow!("unknown modifier `{}` in key `{}`", name, spec)),
+            };
+        }
+
+        let lower = key.to_lowercase();
+        let code = match lower.as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            _ if lower.len() > 1 && lower.starts_with('f') => match lower[1..].parse::<u8>() {
+                Ok(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(anyhow!("unknown key `{}`", spec)),
+            },
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c.to_ascii_lowercase()),
+                    _ => return Err(anyhow!("unknown key `{}`", spec)),
+                }
+            }
+        };
+
+        Ok(KeyBinding { code, modifiers })
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let key = normalize(key);
+        // Shift is implied by the character itself ("?" arrives as shift+/)
+        let strip = |modifiers: KeyModifiers| modifiers - KeyModifiers::SHIFT;
+        let code = match key.code {
+            KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+            other => other,
+        };
+        code == self.code && strip(key.modifiers) == strip(self.modifiers)
+    }
+
+    /// Plain typing keys belong to the agent while in interactive mode
+    pub fn is_typing_key(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Esc => write!(f, "Esc"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// The key as it was typed: terminals send Ctrl+\ ] ^ _ as control bytes,
+/// which crossterm reads as Ctrl+4 to Ctrl+7
+pub fn normalize(key: &KeyEvent) -> KeyEvent {
+    let mut key = *key;
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        key.code = match key.code {
+            KeyCode::Char('4') => KeyCode::Char('\\'),
+            KeyCode::Char('5') => KeyCode::Char(']'),
+            KeyCode::Char('6') => KeyCode::Char('^'),
+            KeyCode::Char('7') => KeyCode::Char('_'),
+            code => code,
+        };
+    }
+    key
+}
+
+/// One key, or a prefix key and the key pressed after it, like `ctrl+\ d`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Keys {
+    first: KeyBinding,
+    then: Option<KeyBinding>,
+}
+
+impl Keys {
+    fn parse(spec: &str) -> Result<Self> {
+        match spec.split_whitespace().collect::<Vec<_>>()[..] {
+            [first] => Ok(Keys {
+                first: KeyBinding::parse(first)?,
+                then: None,
+            }),
+            [first, then] => Ok(Keys {
+                first: KeyBinding::parse(first)?,
+                then: Some(KeyBinding::parse(then)?),
+            }),
+            _ => Err(anyhow!("`{}` should be a key or two keys in a row", spec)),
+        }
+    }
+}
+
+impl fmt::Display for Keys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.then {
+            Some(then) => write!(f, "{} {}", self.first, then),
+            None => write!(f, "{}", self.first),
+        }
+    }
+}
+
+/// What a key press comes to, given the key held back before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyPress {
+    /// The first key of a two-key binding; the next key tells what it means
+    Held,
+    /// Handle `key` as usual, after typing `forward`, a held-back key that
+    /// wasn't followed by the rest of its binding, into the agent
+    Key {
+        key: KeyEvent,
+        action: Option<Action>,
+        forward: Option<KeyEvent>,
+    },
+}
+
+/// The active key bindings, built from the `[keybindings]` config section
+#[derive(Debug, Clone)]
+pub struct Keybindings {
+    bindings: Vec<(Action, Keys)>,
+    /// First key of a two-key binding, waiting for the second
+    held: Option<KeyEvent>,
+}
+
+impl Keybindings {
+    pub fn from_config(config: &KeybindingsConfig) -> Result<Self> {
+        let sections = [
+            (Action::Quit, "quit", &config.quit),
+            (Action::Detach, "detach", &config.detach),
+            (
+                Action::ToggleInteractive,
+                "toggle_interactive",
+                &config.toggle_interactive,
+            ),
+            (Action::OpenWeb, "open_web", &config.open_web),
+            (Action::Refresh, "refresh", &config.refresh),
+            (Action::Help, "help", &config.help),
+            (Action::Retract, "retract", &config.retract),
+            (
+                Action::DetachWithNote,
+                "detach_with_note",
+                &config.detach_with_note,
+            ),
+            (Action::CopyMode, "copy_mode", &config.copy_mode),
+        ];
+
+        let mut bindings = Vec::new();
+        for (action, name, keys) in sections {
+            for key in keys {
+                let keys = Keys::parse(key).map_err(|e| anyhow!("keybindings.{}: {}", name, e))?;
+                bindings.push((action, keys));
+            }
+        }
+
+        if !bindings
+            .iter()
+            .any(|(action, keys)| *action == Action::Quit && !keys.first.is_typing_key())
+        {
+            return Err(anyhow!(
+                "keybindings.quit needs a key that works in interactive mode, such as `ctrl+c`"
+            ));
+        }
+
+        Ok(Keybindings {
+            bindings,
+            held: None,
+        })
+    }
+
+    /// Action bound to a single key press, if any
+    ///
+    /// In interactive mode only bindings with Ctrl/Alt or non-character keys
+    /// apply; everything else is typed into the agent.
+    pub fn action_for(&self, key: &KeyEvent, interactive: bool) -> Option<Action> {
+        self.active(interactive)
+            .find(|(_, keys)| keys.then.is_none() && keys.first.matches(key))
+            .map(|(action, _)| action)
+    }
+
+    /// Take a key press, holding back the first key of a two-key binding
+    /// such as `ctrl+\ d` until the next key shows whether it completes it
+    pub fn press(&mut self, key: &KeyEvent, interactive: bool) -> KeyPress {
+        let key = normalize(key);
+        let Some(held) = self.held.take() else {
+            if self
+                .active(interactive)
+                .any(|(_, keys)| keys.then.is_some() && keys.first.matches(&key))
+            {
+                self.held = Some(key);
+                return KeyPress::Held;
+            }
+            let action = self.action_for(&key, interactive);
+            return KeyPress::Key {
+                key,
+                action,
+                forward: None,
+            };
+        };
+
+        let completed = self.active(interactive).find(|(_, keys)| {
+            keys.first.matches(&held) && keys.then.is_some_and(|then| then.matches(&key))
+        });
+        match completed {
+            Some((action, _)) => KeyPress::Key {
+                key,
+                action: Some(action),
+                forward: None,
+            },
+            // Pressed twice, the held key itself goes through
+            None if held == key => KeyPress::Key {
+                key,
+                action: None,
+                forward: None,
+            },
+            None => KeyPress::Key {
+                key,
+                action: self.action_for(&key, interactive),
+                forward: Some(held),
+            },
+        }
+    }
+
+    /// Keys that trigger `action` in the given mode, e.g. "Ctrl+T / i"
+    pub fn keys_for(&self, action: Action, interactive: bool) -> Option<String> {
+        let keys: Vec<String> = self
+            .active(interactive)
+            .filter(|(bound, _)| *bound == action)
+            .map(|(_, keys)| keys.to_string())
+            .collect();
+        (!keys.is_empty()).then(|| keys.join(" / "))
+    }
+
+    fn active(&self, interactive: bool) -> impl Iterator<Item = (Action, Keys)> + '_ {
+        self.bindings
+            .iter()
+            .copied()
+            .filter(move |(_, keys)| !interactive || !keys.first.is_typing_key())
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self::from_config(&KeybindingsConfig::default()).expect("default keybindings are valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn typing_keys_only_bind_in_monitoring_mode() {
+        let keybindings = Keybindings::default();
+        let question = press(KeyCode::Char('?'), KeyModifiers::SHIFT);
+        let ctrl_t = press(KeyCode::Char('t'), KeyModifiers::CONTROL);
+
+        assert_eq!(keybindings.action_for(&question, false), Some(Action::Help));
+        assert_eq!(keybindings.action_for(&question, true), None);
+        assert_eq!(
+            keybindings.action_for(&ctrl_t, true),
+            Some(Action::ToggleInteractive)
+        );
+        assert_eq!(
+            keybindings.keys_for(Action::Help, true).as_deref(),
+            Some("F1")
+        );
+        assert_eq!(
+            keybindings
+                .keys_for(Action::ToggleInteractive, false)
+                .as_deref(),
+            Some("Ctrl+T / i")
+        );
+    }
+
+    #[test]
+    fn remapped_keys_are_validated() {
+        let config = KeybindingsConfig {
+            toggle_interactive: vec!["alt+i".to_string()],
+            ..KeybindingsConfig::default()
+        };
+        let keybindings = Keybindings::from_config(&config).unwrap();
+        assert_eq!(
+            keybindings.action_for(&press(KeyCode::Char('i'), KeyModifiers::ALT), true),
+            Some(Action::ToggleInteractive)
+        );
+
+        let config = KeybindingsConfig {
+            quit: vec!["q".to_string()],
+            ..KeybindingsConfig::default()
+        };
+        assert!(Keybindings::from_config(&config).is_err());
+        assert!(KeyBinding::parse("hyper+x").is_err());
+    }
+
+    #[test]
+    fn detach_sequence_holds_back_its_first_key() {
+        let mut keybindings = Keybindings::default();
+        // Ctrl+\ arrives as a control byte that crossterm reads as Ctrl+4
+        let prefix = press(KeyCode::Char('4'), KeyModifiers::CONTROL);
+        let d = press(KeyCode::Char('d'), KeyModifiers::NONE);
+        assert_eq!(keybindings.press(&prefix, true), KeyPress::Held);
+        assert_eq!(
+            keybindings.press(&d, true),
+            KeyPress::Key {
+                key: d,
+                action: Some(Action::Detach),
+                forward: None,
+            }
+        );
+
+        // Any other key lets the held one through to the agent
+        let x = press(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(keybindings.press(&prefix, true), KeyPress::Held);
+        let KeyPress::Key {
+            action, forward, ..
+        } = keybindings.press(&x, true)
+        else {
+            panic!("the second key isn't held");
+        };
+        assert_eq!(action, None);
+        assert_eq!(forward.map(|key| key.code), Some(KeyCode::Char('\\')));
+        assert_eq!(
+            keybindings.keys_for(Action::Detach, true).as_deref(),
+            Some("Ctrl+\\ d")
+        );
+    }
+}
@@ -1,5 +1,11 @@
 pub mod http;
+pub mod keybindings;
+pub mod raw_attach;
+pub mod tmux;
+pub mod transport;
+#[cfg(feature = "tui-client")]
 pub mod tui;
 
 pub use http::{CodeMuxClient, SessionConnection};
+#[cfg(feature = "tui-client")]
 pub use tui::SessionTui;
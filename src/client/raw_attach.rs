@@ -0,0 +1,174 @@
+//! `codemux attach --raw`: a session's terminal without the TUI
+//!
+//! PTY output is copied to stdout as it comes and stdin to the session, over
+//! the raw WebSocket, so it works in terminals the TUI can't draw in and in
+//! pipes. On a terminal, stdin is switched to raw mode, the session is sized
+//! to the window and Ctrl+\ then d detaches; from a pipe, the end of the
+//! input does.
+
+use anyhow::{anyhow, Result};
+use futures_util::{SinkExt, StreamExt};
+use std::io::{IsTerminal, Write};
+use tokio::io::AsyncReadExt;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::CodeMuxClient;
+use crate::core::{RawClientMessage, ServerMessage};
+
+/// First key of the detach sequence, Ctrl+\
+pub const DETACH_PREFIX: u8 = 0x1c;
+/// Second key of the detach sequence
+pub const DETACH_KEY: u8 = b'd';
+
+/// How a raw attach ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawAttachEnd {
+    /// By the detach keys or the end of the input; the session keeps running
+    Detached,
+    /// The server closed the connection, e.g. because the session ended
+    Disconnected,
+}
+
+/// Finds the detach sequence in typed input, passing everything else through
+#[derive(Debug, Default)]
+pub struct DetachDetector {
+    prefix_seen: bool,
+}
+
+impl DetachDetector {
+    /// Bytes of `input` to send to the session, and whether it detaches
+    pub fn filter(&mut self, input: &[u8]) -> (Vec<u8>, bool) {
+        let mut forward = Vec::with_capacity(input.len());
+        for &byte in input {
+            if self.prefix_seen {
+                self.prefix_seen = false;
+                match byte {
+                    DETACH_KEY => return (forward, true),
+                    // Pressed twice, the prefix itself goes through
+                    DETACH_PREFIX => forward.push(DETACH_PREFIX),
+                    _ => forward.extend([DETACH_PREFIX, byte]),
+                }
+            } else if byte == DETACH_PREFIX {
+                self.prefix_seen = true;
+            } else {
+                forward.push(byte);
+            }
+        }
+        (forward, false)
+    }
+}
+
+/// Raw mode for the time of the attach, restored even on errors
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = crossterm::terminal::disable_raw_mode();
+    }
+}
+
+/// Attach to a session by ID until detached or disconnected
+pub async fn attach_raw(client: &CodeMuxClient, session_id: &str) -> Result<RawAttachEnd> {
+    let (mut sink, mut stream) = client.watch_session(session_id, false).await?.split();
+    let interactive = std::io::stdin().is_terminal();
+    let _raw_mode = interactive.then(RawMode::enable).transpose()?;
+    let (size_tx, mut size_rx) = mpsc::unbounded_channel();
+    if interactive {
+        watch_window_size(size_tx);
+    }
+
+    let mut stdin = tokio::io::stdin();
+    let mut stdout = std::io::stdout();
+    let mut buffer = [0u8; 4096];
+    let mut detector = DetachDetector::default();
+    loop {
+        tokio::select! {
+            message = stream.next() => match message {
+                Some(Ok(Message::Binary(data))) => {
+                    stdout.write_all(&data)?;
+                    stdout.flush()?;
+                }
+                Some(Ok(Message::Text(text))) => {
+                    if let Ok(ServerMessage::Error { message }) = serde_json::from_str(&text) {
+                        return Err(anyhow!(message));
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => return Ok(RawAttachEnd::Disconnected),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+            read = stdin.read(&mut buffer) => {
+                let read = read?;
+                let (input, detach) = if read == 0 {
+                    (Vec::new(), true)
+                } else if interactive {
+                    detector.filter(&buffer[..read])
+                } else {
+                    (buffer[..read].to_vec(), false)
+                };
+                if !input.is_empty() {
+                    sink.send(Message::Binary(input)).await?;
+                }
+                if detach {
+                    let _ = sink.close().await;
+                    return Ok(RawAttachEnd::Detached);
+                }
+            }
+            Some((cols, rows)) = size_rx.recv() => {
+                let resize = serde_json::to_string(&RawClientMessage::Resize { rows, cols })?;
+                sink.send(Message::Text(resize)).await?;
+            }
+        }
+    }
+}
+
+/// Send the terminal's size now and whenever the window changes
+fn watch_window_size(size_tx: mpsc::UnboundedSender<(u16, u16)>) {
+    if let Ok(size) = crossterm::terminal::size() {
+        let _ = size_tx.send(size);
+    }
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let Ok(mut changes) = signal(SignalKind::window_change()) else {
+            return;
+        };
+        while changes.recv().await.is_some() {
+            let Ok(size) = crossterm::terminal::size() else {
+                continue;
+            };
+            if size_tx.send(size).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detaches_on_ctrl_backslash_then_d() {
+        let mut detector = DetachDetector::default();
+        assert_eq!(detector.filter(b"ls\r"), (b"ls\r".to_vec(), false));
+        // The sequence may arrive split across reads
+        assert_eq!(detector.filter(b"x\x1c"), (b"x".to_vec(), false));
+        assert_eq!(detector.filter(b"dmore"), (Vec::new(), true));
+
+        let mut detector = DetachDetector::default();
+        assert_eq!(
+            detector.filter(b"\x1c\x1c\x1ca"),
+            (b"\x1c\x1ca".to_vec(), false)
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Sessions as tmux windows, for `codemux run --tmux` and `codemux tmux sync`
+//!
+//! Each window runs `codemux attach --raw <id>`, so tmux draws the session
+//! and codemux only carries it. Windows are tagged with the `@codemux_session`
+//! window option, which is how an existing window is found again. Inside tmux
+//! they go into the current tmux session, outside it into one named
+//! `TMUX_SESSION`, created when needed.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+
+/// tmux session that windows go into when codemux runs outside tmux
+pub const TMUX_SESSION: &str = "codemux";
+/// Window option holding the codemux session a window shows
+const SESSION_OPTION: &str = "@codemux_session";
+
+/// A codemux session to show in a window
+pub struct TmuxSession<'a> {
+    pub id: &'a str,
+    /// Window name, e.g. the session's name
+    pub label: &'a str,
+}
+
+/// Whether codemux itself runs inside tmux
+pub fn inside_tmux() -> bool {
+    std::env::var_os("TMUX").is_some_and(|value| !value.is_empty())
+}
+
+fn tmux(args: &[&str]) -> Result<String> {
+    let output = Command::new("tmux")
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run tmux; is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "tmux {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// tmux session new windows go into, created detached outside tmux if
+/// needed; then also the ID of the shell window it was created with
+fn target_session() -> Result<(String, Option<String>)> {
+    if inside_tmux() {
+        let name = tmux(&["display-message", "-p", "#{session_name}"])?;
+        return Ok((name.trim().to_string(), None));
+    }
+    if tmux(&["has-session", "-t", TMUX_SESSION]).is_ok() {
+        return Ok((TMUX_SESSION.to_string(), None));
+    }
+    let window = tmux(&[
+        "new-session",
+        "-d",
+        "-s",
+        TMUX_SESSION,
+        "-P",
+        "-F",
+        "#{window_id}",
+    ])?;
+    Ok((TMUX_SESSION.to_string(), Some(window.trim().to_string())))
+}
+
+/// Close the shell window a new tmux session came with, once it has others
+fn close_placeholder(placeholder: Option<String>) -> Result<()> {
+    match placeholder {
+        Some(window) => tmux(&["kill-window", "-t", &window]).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Windows showing a codemux session, by session ID
+fn session_windows() -> Result<HashMap<String, String>> {
+    // Without a tmux server running there are no windows yet
+    let Ok(listing) = tmux(&[
+        "list-windows",
+        "-a",
+        "-F",
+        &format!("#{{window_id}} #{{{}}}", SESSION_OPTION),
+    ]) else {
+        return Ok(HashMap::new());
+    };
+    Ok(listing
+        .lines()
+        .filter_map(|line| {
+            let (window, session) = line.split_once(' ')?;
+            (!session.is_empty()).then(|| (session.to_string(), window.to_string()))
+        })
+        .collect())
+}
+
+/// `codemux attach --raw <id>` with this binary and the current profile
+fn attach_command(session_id: &str, profile: Option<&str>) -> Result<Vec<String>> {
+    let exe = std::env::current_exe().context("Failed to find the codemux binary")?;
+    let mut command = vec![exe.display().to_string()];
+    if let Some(profile) = profile {
+        command.extend(["--profile".to_string(), profile.to_string()]);
+    }
+    command.extend([
+        "attach".to_string(),
+        "--raw".to_string(),
+        session_id.to_string(),
+    ]);
+    Ok(command)
+}
+
+/// Open a window for `session` in `target` unless one shows it already;
+/// returns the window ID
+fn ensure_window(
+    session: &TmuxSession,
+    target: &str,
+    profile: Option<&str>,
+    windows: &HashMap<String, String>,
+) -> Result<(String, bool)> {
+    if let Some(window) = windows.get(session.id) {
+        return Ok((window.clone(), false));
+    }
+    let target = format!("{}:", target);
+    let command = attach_command(session.id, profile)?;
+    let mut args = vec![
+        "new-window",
+        "-d",
+        "-P",
+        "-F",
+        "#{window_id}",
+        "-t",
+        &target,
+        "-n",
+        session.label,
+    ];
+    args.extend(command.iter().map(String::as_str));
+    let window = tmux(&args)?.trim().to_string();
+    tmux(&[
+        "set-option",
+        "-w",
+        "-t",
+        &window,
+        SESSION_OPTION,
+        session.id,
+    ])?;
+    Ok((window, true))
+}
+
+/// Show `session` in a tmux window and switch to it; outside tmux this
+/// attaches to the `TMUX_SESSION` tmux session until it's detached from
+pub fn open(session: &TmuxSession, profile: Option<&str>) -> Result<()> {
+    let (target, placeholder) = target_session()?;
+    let (window, _) = ensure_window(session, &target, profile, &session_windows()?)?;
+    tmux(&["select-window", "-t", &window])?;
+    close_placeholder(placeholder)?;
+    if inside_tmux() {
+        return Ok(());
+    }
+    let status = Command::new("tmux")
+        .args(["attach-session", "-t", &target])
+        .status()
+        .map_err(|e| anyhow!("Failed to attach to tmux: {}", e))?;
+    if !status.success() {
+        bail!("tmux attach-session exited with {}", status);
+    }
+    Ok(())
+}
+
+/// What `sync` changed
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub opened: Vec<String>,
+    pub closed: Vec<String>,
+    /// tmux session the windows are in
+    pub target: String,
+}
+
+/// Give each of `sessions` a window, and close the windows of sessions
+/// that aren't among them
+pub fn sync(sessions: &[TmuxSession], profile: Option<&str>) -> Result<SyncReport> {
+    let (target, placeholder) = target_session()?;
+    let windows = session_windows()?;
+    let mut report = SyncReport {
+        target: target.clone(),
+        ..SyncReport::default()
+    };
+    for session in sessions {
+        let (_, opened) = ensure_window(session, &target, profile, &windows)?;
+        if opened {
+            report.opened.push(session.id.to_string());
+        }
+    }
+    for (session_id, window) in &windows {
+        if !sessions.iter().any(|session| session.id == session_id) {
+            tmux(&["kill-window", "-t", window])?;
+            report.closed.push(session_id.clone());
+        }
+    }
+    if !report.opened.is_empty() {
+        close_placeholder(placeholder)?;
+    }
+    Ok(report)
+}
@@ -1,13 +1,22 @@
+use crate::client::http::CodeMuxClient;
+use crate::client::keybindings::{Action, KeyPress, Keybindings};
+use crate::core::crash_dump::{write_crash_dump, RenderCrashReport, RenderWatchdog};
+use crate::core::input_queue::PendingInput;
+use crate::core::paste::{describe_paste, PasteConfig};
+use crate::core::preferences::UiPreferences;
 use crate::core::pty_session::GridCell as PtyGridCell;
 use crate::core::pty_session::{
     ConnectionStatus as PtyConnectionStatus, GridUpdateMessage, PtyChannels, PtyControlMessage,
     PtyInput, PtyInputMessage, ScrollDirection, TerminalColor,
 };
+use crate::core::selection::{Selection, SelectionShape};
 use crate::utils::tui_writer::{LogEntry, LogLevel};
+use crate::utils::PromptType;
 use anyhow::Result;
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEventKind,
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, EventStream, KeyCode, KeyEventKind,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -24,9 +33,17 @@ use ratatui::{
 
 // UI Layout constants
 const STATUS_BAR_HEIGHT: u16 = 1;
+
+/// Keystrokes not written to the agent after this long show up in the status bar
+const INPUT_PENDING_AFTER: std::time::Duration = std::time::Duration::from_millis(150);
+
+/// Marker in the data dir recording that the first-attach tour has been shown
+pub const TOUR_MARKER_FILE: &str = "tour-seen";
+const TOUR_STEPS: usize = 3;
 use serde::{Deserialize, Serialize};
 use std::io;
 use tokio::time::{Duration, Instant};
+use unicode_width::UnicodeWidthChar;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct GridCell {
@@ -126,6 +143,71 @@ pub struct GridUpdate {
     pub cells: Vec<(u16, u16, GridCell)>, // (row, col, cell)
 }
 
+/// Popup drawn over either mode until dismissed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overlay {
+    Help,
+    Tour { step: usize },
+    ConfirmPaste,
+    /// The note left by whoever detached last
+    HandoverNote,
+    /// Editing a note to leave before detaching
+    EditNote,
+    /// A warning from the server, e.g. a mass deletion
+    Alert,
+}
+
+/// Selecting text on a frozen copy of the screen, tmux style
+struct CopyMode {
+    grid: std::collections::HashMap<(u16, u16), GridCell>,
+    size: (u16, u16),
+    cursor: (u16, u16),
+    /// Where the selection started, once Space was pressed
+    anchor: Option<(u16, u16)>,
+    shape: SelectionShape,
+}
+
+impl CopyMode {
+    fn selection(&self) -> Selection {
+        Selection {
+            anchor: self.anchor.unwrap_or(self.cursor),
+            cursor: self.cursor,
+            shape: self.shape,
+        }
+    }
+
+    /// The selected text, or the cursor's line when nothing is selected yet
+    fn text(&self) -> String {
+        let selection = match self.anchor {
+            Some(_) => self.selection(),
+            None => Selection {
+                anchor: (self.cursor.0, 0),
+                cursor: (self.cursor.0, self.size.1),
+                shape: SelectionShape::Lines,
+            },
+        };
+        let char_at = |row: u16, col: u16| self.grid.get(&(row, col)).map(|cell| cell.char);
+        selection.text(self.size.1, |line, row, col| {
+            // The second half of a wide character
+            if col > 0 && char_at(row, col - 1).is_some_and(|c| c.width().unwrap_or(0) > 1) {
+                return;
+            }
+            line.push(char_at(row, col).unwrap_or(' '));
+        })
+    }
+
+    /// Move the cursor by rows and columns, staying on the screen
+    fn move_by(&mut self, rows: i32, cols: i32) {
+        let clamp = |value: u16, by: i32, size: u16| {
+            (i32::from(value) + by).clamp(0, i32::from(size.saturating_sub(1))) as u16
+        };
+        self.cursor = (
+            clamp(self.cursor.0, rows, self.size.0),
+            clamp(self.cursor.1, cols, self.size.1),
+        );
+    }
+}
+
 pub struct SessionTui {
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
     start_time: Instant,
@@ -147,9 +229,38 @@ pub struct SessionTui {
     last_render_time: std::time::Instant,
     // Session ID for generating URLs
     session_id: String,
+    // Server API client, carrying its token
+    client: CodeMuxClient,
     // Connection state tracking
     connection_status: PtyConnectionStatus,
     last_connection_attempt: Option<Instant>,
+    // Render consistency checks; dumps go to the data dir when one is set
+    render_watchdog: RenderWatchdog,
+    crash_dump_data_dir: Option<std::path::PathBuf>,
+    preferences: UiPreferences,
+    // Read-only fullscreen view for screen sharing
+    presentation_mode: bool,
+    keybindings: Keybindings,
+    overlay: Option<Overlay>,
+    paste_config: PasteConfig,
+    // Large paste waiting for an answer to the `ConfirmPaste` overlay
+    pending_paste: Option<String>,
+    // Text typed into the `EditNote` overlay
+    note_draft: String,
+    // Last warning from the server, shown in the `Alert` overlay
+    alert: Option<String>,
+    // Input sent and not acknowledged by the server yet, and whether it reports lag
+    pending_input: PendingInput,
+    input_lagging: bool,
+    shown_input_indicator: Option<&'static str>,
+    // Approval the agent waits for, shown as a banner in the status bar
+    waiting_prompt: Option<PromptType>,
+    // What the agent has cost so far, for agents that report it
+    cost: Option<f64>,
+    // Text being selected for copying, over a frozen copy of the screen
+    copy_mode: Option<CopyMode>,
+    // Kept open, since on X11 the copied text goes away with it
+    clipboard: Option<arboard::Clipboard>,
 }
 
 pub struct SessionInfo {
@@ -158,13 +269,24 @@ pub struct SessionInfo {
     pub _port: u16,
     pub working_dir: String,
     pub url: String,
+    /// Session accent color (`#rrggbb`) and icon from the server
+    pub color: String,
+    pub icon: String,
+    /// Handover note left when the session was last detached
+    pub note: Option<String>,
 }
 
 impl SessionTui {
     pub fn new(session_id: String) -> Result<Self> {
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        // Bracketed paste delivers a paste as one event, so large ones can be caught
+        execute!(
+            stdout,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste
+        )?;
         let backend = CrosstermBackend::new(stdout);
         let terminal = Terminal::new(backend)?;
 
@@ -172,7 +294,7 @@ impl SessionTui {
             terminal,
             start_time: Instant::now(),
             interactive_mode: false,
-            status_message: "Ready - Press Ctrl+T for interactive mode".to_string(),
+            status_message: String::new(),
             system_logs: Vec::new(),
             terminal_grid: std::collections::HashMap::new(),
             terminal_cursor: (0, 0),
@@ -184,17 +306,288 @@ impl SessionTui {
             cursor_dirty: false,
             last_render_time: std::time::Instant::now(),
             session_id,
+            client: CodeMuxClient::new(format!(
+                "http://localhost:{}",
+                crate::core::config::default_server_port()
+            ))?,
             connection_status: PtyConnectionStatus::Disconnected,
             last_connection_attempt: None,
+            render_watchdog: RenderWatchdog::new(),
+            crash_dump_data_dir: None,
+            preferences: UiPreferences::default(),
+            presentation_mode: false,
+            keybindings: Keybindings::default(),
+            overlay: None,
+            paste_config: PasteConfig::default(),
+            pending_paste: None,
+            note_draft: String::new(),
+            alert: None,
+            pending_input: PendingInput::default(),
+            input_lagging: false,
+            shown_input_indicator: None,
+            waiting_prompt: None,
+            cost: None,
+            copy_mode: None,
+            clipboard: None,
         })
     }
 
+    /// Fetch the session's running cost for the status bar; it stays as it
+    /// was when the server can't tell
+    async fn refresh_cost(&mut self) {
+        if let Ok(usage) = self.client.get_session_usage(&self.session_id).await {
+            self.cost = usage.usage.cost;
+        }
+    }
+
+    /// Status bar note while typing isn't getting through to the agent
+    fn input_indicator(&self) -> Option<&'static str> {
+        if self.input_lagging {
+            Some("⏳ INPUT LAGGING")
+        } else if self
+            .pending_input
+            .overdue(std::time::Instant::now(), INPUT_PENDING_AFTER)
+            > 0
+        {
+            Some("⌨️ SENDING…")
+        } else {
+            None
+        }
+    }
+
     pub fn set_pty_channels(&mut self, pty_channels: PtyChannels) {
         self.pty_channels = Some(pty_channels);
     }
 
-    fn get_web_url(&self) -> String {
-        format!("http://localhost:{}/session/{}", crate::core::config::default_server_port(), self.session_id)
+    /// Enable automatic crash dumps of render inconsistencies under `data_dir`
+    pub fn set_crash_dump_data_dir(&mut self, data_dir: std::path::PathBuf) {
+        self.crash_dump_data_dir = Some(data_dir);
+    }
+
+    /// Apply display preferences (high contrast, reduced motion) from the server
+    pub fn set_preferences(&mut self, preferences: UiPreferences) {
+        self.preferences = preferences;
+        self.needs_redraw = true;
+    }
+
+    /// Show only the terminal, without status bars, and ignore all input except Ctrl+C
+    ///
+    /// The PTY isn't resized to this view either, so presenting never disturbs the session.
+    pub fn set_presentation_mode(&mut self, presentation_mode: bool) {
+        self.presentation_mode = presentation_mode;
+        self.needs_redraw = true;
+    }
+
+    pub fn set_keybindings(&mut self, keybindings: Keybindings) {
+        self.keybindings = keybindings;
+        self.needs_redraw = true;
+    }
+
+    /// Thresholds for asking before a large paste, from the `[paste]` config section
+    pub fn set_paste_config(&mut self, paste_config: PasteConfig) {
+        self.paste_config = paste_config;
+    }
+
+    /// Server to talk to, instead of one on the default port without a token
+    pub fn set_client(&mut self, client: CodeMuxClient) {
+        self.client = client;
+    }
+
+    /// Walk through the basics (mode toggle, detaching, web URL) before anything else
+    pub fn start_tour(&mut self) {
+        self.overlay = Some(Overlay::Tour { step: 0 });
+        self.needs_redraw = true;
+    }
+
+    /// Status line suffix pointing at the mode toggle and help keys
+    fn key_hint(&self) -> String {
+        let toggle = self.keybindings.keys_for(Action::ToggleInteractive, false);
+        let help = self.keybindings.keys_for(Action::Help, false);
+        match (toggle, help) {
+            (Some(toggle), Some(help)) => {
+                format!(" - Press {} for interactive mode, {} for help", toggle, help)
+            }
+            (Some(toggle), None) => format!(" - Press {} for interactive mode", toggle),
+            (None, Some(help)) => format!(" - Press {} for help", help),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Dismiss the help overlay, or advance the tour (Esc skips the rest)
+    fn handle_overlay_key(&mut self, key: &event::KeyEvent) {
+        self.overlay = match self.overlay {
+            Some(Overlay::Tour { step }) if key.code != KeyCode::Esc && step + 1 < TOUR_STEPS => {
+                Some(Overlay::Tour { step: step + 1 })
+            }
+            _ => None,
+        };
+    }
+
+    /// Title and text of the open overlay, generated from the active key bindings
+    fn overlay_content(&self, session_info: &SessionInfo) -> Option<(String, Vec<Line<'static>>)> {
+        let interactive = self.interactive_mode;
+        let keys = |action| {
+            self.keybindings
+                .keys_for(action, interactive)
+                .unwrap_or_else(|| "(unbound)".to_string())
+        };
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let hint_style = Style::default().fg(Color::Gray);
+
+        match self.overlay? {
+            Overlay::ConfirmPaste => {
+                let text = self.pending_paste.as_deref()?;
+                let mut lines = vec![
+                    Line::from(format!("You're pasting {}.", describe_paste(text))),
+                    Line::from(""),
+                ];
+                if self.paste_config.exceeds_max(text) {
+                    lines.push(Line::from(format!(
+                        "That's over the server's {} byte paste limit.",
+                        self.paste_config.max_bytes
+                    )));
+                } else {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{:>14}  ", "y / Enter"), key_style),
+                        Span::raw("Paste it"),
+                    ]));
+                }
+                if self.paste_config.offer_file {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{:>14}  ", "f"), key_style),
+                        Span::raw("Save it to a file on the server and paste the path"),
+                    ]));
+                }
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:>14}  ", "n / Esc"), key_style),
+                    Span::raw("Cancel"),
+                ]));
+                Some((" 📋 Large paste ".to_string(), lines))
+            }
+            Overlay::HandoverNote => {
+                let note = session_info.note.as_deref()?;
+                let mut lines: Vec<Line<'static>> =
+                    note.lines().map(|line| Line::from(line.to_string())).collect();
+                lines.push(Line::from(""));
+                lines.push(Line::styled("Press any key to close", hint_style));
+                Some((" 📝 Handover note ".to_string(), lines))
+            }
+            Overlay::Alert => {
+                let alert = self.alert.as_deref()?;
+                let mut lines: Vec<Line<'static>> = alert
+                    .lines()
+                    .map(|line| Line::styled(line.to_string(), Style::default().fg(Color::Red)))
+                    .collect();
+                lines.push(Line::from(""));
+                lines.push(Line::styled("Press any key to close", hint_style));
+                Some((" ⚠️ Alert ".to_string(), lines))
+            }
+            Overlay::EditNote => {
+                let lines = vec![
+                    Line::from("Leave a note for whoever attaches next, e.g. \"waiting on CI\"."),
+                    Line::from(""),
+                    Line::from(vec![
+                        Span::styled("> ", key_style),
+                        Span::raw(self.note_draft.clone()),
+                        Span::styled("_", hint_style),
+                    ]),
+                    Line::from(""),
+                    Line::styled(
+                        "Enter: save and detach, Esc: cancel (an empty note clears it)",
+                        hint_style,
+                    ),
+                ];
+                Some((" 📝 Detach with a note ".to_string(), lines))
+            }
+            Overlay::Help => {
+                let mode = if interactive {
+                    "Interactive mode: keys are typed into the agent. Only shortcuts with Ctrl, Alt or function keys reach codemux."
+                } else {
+                    "Monitoring mode: the agent runs in the background while codemux shows session status."
+                };
+                let mut lines = vec![Line::from(mode), Line::from("")];
+                for action in Action::ALL {
+                    if let Some(keys) = self.keybindings.keys_for(action, interactive) {
+                        lines.push(Line::from(vec![
+                            Span::styled(format!("{:>14}  ", keys), key_style),
+                            Span::raw(action.describe()),
+                        ]));
+                    }
+                }
+                lines.push(Line::from(""));
+                lines.push(Line::from(format!("Web interface: {}", session_info.url)));
+                lines.push(Line::from(""));
+                lines.push(Line::styled("Press any key to close", hint_style));
+                Some((" ❓ Help ".to_string(), lines))
+            }
+            Overlay::Tour { step } => {
+                let (title, text) = match step {
+                    0 => (
+                        "Interactive mode",
+                        format!(
+                            "You're watching the agent. Press {} to type into it directly, and again to come back.",
+                            self.keybindings
+                                .keys_for(Action::ToggleInteractive, true)
+                                .unwrap_or_else(|| keys(Action::ToggleInteractive))
+                        ),
+                    ),
+                    1 => (
+                        "Detaching",
+                        format!(
+                            "Press {} to detach. The agent keeps running on the server, so you can pick it up again from the web interface.",
+                            keys(Action::Detach)
+                        ),
+                    ),
+                    _ => (
+                        "Web interface",
+                        format!(
+                            "Follow along or take over from a browser at {}",
+                            session_info.url
+                        ),
+                    ),
+                };
+                let lines = vec![
+                    Line::styled(title.to_string(), key_style),
+                    Line::from(""),
+                    Line::from(text),
+                    Line::from(""),
+                    Line::styled(
+                        format!("Step {} of {} - Enter: next, Esc: skip", step + 1, TOUR_STEPS),
+                        hint_style,
+                    ),
+                ];
+                Some((" 👋 Welcome to codemux ".to_string(), lines))
+            }
+        }
+    }
+
+    /// Write the recent grid updates to a crash dump so the bad state can be reproduced
+    fn dump_render_state(&self, reason: String) {
+        let Some(data_dir) = &self.crash_dump_data_dir else {
+            tracing::warn!("Render inconsistency: {}", reason);
+            return;
+        };
+
+        let report = RenderCrashReport {
+            source: "tui".to_string(),
+            reason,
+            updates: self.render_watchdog.history(),
+        };
+        match write_crash_dump(data_dir, &self.session_id, &report) {
+            Ok((crash_id, path)) => tracing::warn!(
+                "Render inconsistency ({}), crash dump {} written to {}",
+                report.reason,
+                crash_id,
+                path.display()
+            ),
+            Err(e) => tracing::error!(
+                "Render inconsistency ({}), failed to write crash dump: {}",
+                report.reason,
+                e
+            ),
+        }
     }
 
     /// Create terminal area with standard calculation (single source of truth)
@@ -217,15 +610,12 @@ impl SessionTui {
     }
 
     pub async fn connect_websocket(&mut self) -> Result<()> {
-        use crate::client::http::CodeMuxClient;
-
         self.last_connection_attempt = Some(Instant::now());
 
         tracing::info!("Connecting to WebSocket for session {}", self.session_id);
 
         // Create client and connect to WebSocket (this now includes auto-reconnection)
-        let client = CodeMuxClient::new(format!("http://localhost:{}", crate::core::config::default_server_port()));
-        let session_connection = client.connect_to_session(&self.session_id).await?;
+        let session_connection = self.client.connect_to_session(&self.session_id).await?;
 
         // Convert SessionConnection to PtyChannels
         let pty_channels = session_connection.into_pty_channels();
@@ -243,8 +633,7 @@ impl SessionTui {
         self.pty_channels = None;
         self.has_received_keyframe = false; // Reset keyframe state
         self.connection_status = PtyConnectionStatus::Disconnected;
-        self.status_message =
-            "WebSocket disconnected - Press Ctrl+T for interactive mode".to_string();
+        self.status_message = format!("WebSocket disconnected{}", self.key_hint());
     }
 
     fn get_pty_channels(&self) -> Result<&PtyChannels> {
@@ -255,6 +644,10 @@ impl SessionTui {
 
     /// Centralized handler for GridUpdateMessage with keyframe state tracking
     fn handle_grid_update(&mut self, update: GridUpdateMessage) -> bool {
+        if let Some(reason) = self.render_watchdog.observe(&update) {
+            self.dump_render_state(reason);
+        }
+
         match update {
             GridUpdateMessage::Keyframe {
                 size,
@@ -402,11 +795,11 @@ impl SessionTui {
         }
     }
 
-    async fn send_input_to_pty(&self, key: &crossterm::event::KeyEvent) {
+    async fn send_input_to_pty(&mut self, key: &crossterm::event::KeyEvent) {
         tracing::trace!("send_input_to_pty called with key: {:?}", key);
 
-        let channels = match self.get_pty_channels() {
-            Ok(channels) => channels,
+        let input_tx = match self.get_pty_channels() {
+            Ok(channels) => channels.input_tx.clone(),
             Err(_) => {
                 tracing::warn!("PTY not connected yet, ignoring input");
                 return;
@@ -434,9 +827,11 @@ impl SessionTui {
                 event: key_event,
                 client_id: "tui".to_string(),
             },
+            seq: Some(self.pending_input.track()),
+            span: tracing::Span::none(),
         };
 
-        if let Err(e) = channels.input_tx.send(input_msg) {
+        if let Err(e) = input_tx.send(input_msg).await {
             tracing::warn!("Failed to send input to PTY: {}", e);
         } else {
             // For debugging: if this is Enter, also log that we sent a line terminator
@@ -446,6 +841,188 @@ impl SessionTui {
         }
     }
 
+    async fn send_paste_to_pty(&mut self, text: String) {
+        let input_tx = match self.get_pty_channels() {
+            Ok(channels) => channels.input_tx.clone(),
+            Err(_) => {
+                tracing::warn!("PTY not connected yet, ignoring paste");
+                return;
+            }
+        };
+
+        let input_msg = PtyInputMessage {
+            input: PtyInput::Paste {
+                text,
+                client_id: "tui".to_string(),
+            },
+            seq: Some(self.pending_input.track()),
+            span: tracing::Span::none(),
+        };
+        if let Err(e) = input_tx.send(input_msg).await {
+            tracing::warn!("Failed to send paste to PTY: {}", e);
+        }
+    }
+
+    /// Paste right away, or hold a large paste until the prompt is answered
+    async fn handle_paste(&mut self, text: String) {
+        if self.paste_config.needs_confirmation(&text) || self.paste_config.exceeds_max(&text) {
+            self.pending_paste = Some(text);
+            self.overlay = Some(Overlay::ConfirmPaste);
+        } else {
+            self.send_paste_to_pty(text).await;
+        }
+    }
+
+    /// Act on the large-paste prompt: paste, send as a file path, or cancel
+    async fn answer_paste_prompt(&mut self, key: &event::KeyEvent) {
+        let Some(text) = self.pending_paste.take() else {
+            self.overlay = None;
+            return;
+        };
+        self.overlay = None;
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Enter if !self.paste_config.exceeds_max(&text) => {
+                self.send_paste_to_pty(text).await;
+            }
+            KeyCode::Char('f') if self.paste_config.offer_file => {
+                let client = self.client.clone();
+                match client.save_paste_file(&self.session_id, &text).await {
+                    Ok(path) => {
+                        self.status_message = format!("Pasted path of {}", path);
+                        self.send_paste_to_pty(path).await;
+                    }
+                    Err(e) => self.status_message = format!("Failed to save paste: {}", e),
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.status_message = "Paste cancelled".to_string();
+            }
+            // Any other key keeps the prompt open
+            _ => {
+                self.pending_paste = Some(text);
+                self.overlay = Some(Overlay::ConfirmPaste);
+            }
+        }
+    }
+
+    /// Open the note editor, starting from the note the session already has
+    fn start_note(&mut self, session_info: &SessionInfo) {
+        self.note_draft = session_info.note.clone().unwrap_or_default();
+        self.overlay = Some(Overlay::EditNote);
+    }
+
+    /// Type into the note editor; returns true once the note is saved and the TUI should detach
+    async fn handle_note_key(&mut self, key: &event::KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Esc => {
+                self.overlay = None;
+                self.status_message = "Detach cancelled".to_string();
+            }
+            KeyCode::Enter => {
+                let client = self.client.clone();
+                let note = Some(self.note_draft.trim().to_string()).filter(|note| !note.is_empty());
+                match client.set_session_note(&self.session_id, note).await {
+                    Ok(_) => return true,
+                    Err(e) => {
+                        self.overlay = None;
+                        self.status_message = format!("Failed to save note: {}", e);
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                self.note_draft.pop();
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(event::KeyModifiers::CONTROL | event::KeyModifiers::ALT) =>
+            {
+                self.note_draft.push(c);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Freeze the screen and start selecting on it from the terminal cursor
+    fn enter_copy_mode(&mut self) {
+        let size = calculate_grid_dimensions(&self.terminal_grid);
+        let mut copy = CopyMode {
+            grid: self.terminal_grid.clone(),
+            size,
+            cursor: self.terminal_cursor,
+            anchor: None,
+            shape: SelectionShape::Lines,
+        };
+        copy.move_by(0, 0);
+        self.copy_mode = Some(copy);
+        self.status_message =
+            "Copy mode: move with arrows or hjkl, Space to select, Enter to copy".to_string();
+    }
+
+    /// Move or select in copy mode; Enter or y copies and leaves it
+    fn handle_copy_key(&mut self, key: &event::KeyEvent) {
+        let Some(copy) = self.copy_mode.as_mut() else {
+            return;
+        };
+        let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.copy_mode = None;
+                self.status_message = "Copy cancelled".to_string();
+            }
+            KeyCode::Char('c') if ctrl => {
+                self.copy_mode = None;
+                self.status_message = "Copy cancelled".to_string();
+            }
+            KeyCode::Up | KeyCode::Char('k') => copy.move_by(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => copy.move_by(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => copy.move_by(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => copy.move_by(0, 1),
+            KeyCode::Home | KeyCode::Char('0') => copy.cursor.1 = 0,
+            KeyCode::End | KeyCode::Char('$') => copy.cursor.1 = copy.size.1.saturating_sub(1),
+            KeyCode::Char('g') => copy.cursor.0 = 0,
+            KeyCode::Char('G') => copy.cursor.0 = copy.size.0.saturating_sub(1),
+            KeyCode::Char(' ') => copy.anchor = Some(copy.cursor),
+            KeyCode::Char('v') => {
+                copy.shape = match copy.shape {
+                    SelectionShape::Lines => SelectionShape::Block,
+                    SelectionShape::Block => SelectionShape::Lines,
+                };
+            }
+            KeyCode::Enter | KeyCode::Char('y') => {
+                let text = copy.text();
+                self.copy_mode = None;
+                self.copy_to_clipboard(text);
+            }
+            _ => {}
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) {
+        let lines = text.lines().count().max(1);
+        let copied = match self.clipboard.as_mut() {
+            Some(clipboard) => clipboard.set_text(text),
+            None => arboard::Clipboard::new()
+                .and_then(|clipboard| self.clipboard.insert(clipboard).set_text(text)),
+        };
+        self.status_message = match copied {
+            Ok(()) if lines == 1 => "📋 Copied 1 line".to_string(),
+            Ok(()) => format!("📋 Copied {} lines", lines),
+            Err(e) => format!("Failed to copy: {}", e),
+        };
+    }
+
+    /// Interrupt the agent right after an accidental send and type the message back in
+    async fn retract_last_message(&mut self) {
+        let client = self.client.clone();
+        self.status_message = match client.retract_last_message(&self.session_id, true).await {
+            Ok(Some(text)) => format!("Retracted \"{}\"", truncate_for_status(&text)),
+            Ok(None) => "Interrupted the agent; no message to retract".to_string(),
+            Err(e) => format!("Failed to retract: {}", e),
+        };
+    }
+
     async fn send_scroll_to_pty(&self, direction: ScrollDirection, lines: u16) {
         tracing::debug!(
             "send_scroll_to_pty called with direction: {:?}, lines: {}",
@@ -467,9 +1044,11 @@ impl SessionTui {
                 lines,
                 client_id: "tui".to_string(),
             },
+            seq: None,
+            span: tracing::Span::none(),
         };
 
-        if let Err(e) = channels.input_tx.send(input_msg) {
+        if let Err(e) = channels.input_tx.send(input_msg).await {
             tracing::warn!("Failed to send scroll to PTY: {}", e);
         }
     }
@@ -479,8 +1058,12 @@ impl SessionTui {
         session_info: SessionInfo,
         mut log_rx: tokio::sync::mpsc::UnboundedReceiver<LogEntry>,
     ) -> Result<()> {
-        self.interactive_mode = false;
-        self.status_message = "Ready - Press Ctrl+T for interactive mode".to_string();
+        // Presentation goes straight to the fullscreen terminal and stays there
+        self.interactive_mode = self.presentation_mode;
+        self.status_message = format!("Ready{}", self.key_hint());
+        if session_info.note.is_some() && self.overlay.is_none() {
+            self.overlay = Some(Overlay::HandoverNote);
+        }
 
         loop {
             let should_quit = if self.interactive_mode {
@@ -520,7 +1103,8 @@ impl SessionTui {
         let _ = execute!(
             self.terminal.backend_mut(),
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableBracketedPaste
         );
         let _ = self.terminal.show_cursor();
     }
@@ -559,48 +1143,53 @@ impl SessionTui {
                             if key.kind == KeyEventKind::Press {
                                 tracing::debug!("MONITORING: Key pressed: {:?} modifiers: {:?}", key.code, key.modifiers);
 
-                                // Handle quit
-                                if key.code == KeyCode::Char('c') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                                    tracing::info!("MONITORING: Exiting due to Ctrl+C");
-                                    return Ok(true); // Signal to quit
+                                // The note editor takes every key, shortcuts included
+                                if self.overlay == Some(Overlay::EditNote) {
+                                    if self.handle_note_key(&key).await {
+                                        return Ok(true);
+                                    }
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                    continue;
                                 }
 
-                                // Handle toggle to interactive mode
-                                if key.code == KeyCode::Char('t') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
-                                    tracing::info!("SWITCHING TO INTERACTIVE MODE");
+                                let KeyPress::Key { key, action, .. } = self.keybindings.press(&key, false) else {
+                                    continue; // The first of two keys, like Ctrl+\ of Ctrl+\ d
+                                };
 
-                                    self.interactive_mode = true;
-                                    self.status_message = "Interactive mode ON - Direct PTY input (Ctrl+T to toggle off)".to_string();
-
-                                    // Get terminal area for PTY sizing
-                                    // Don't resize PTY in monitoring mode - only in interactive mode
+                                // Handle quit
+                                if matches!(action, Some(Action::Quit | Action::Detach)) {
+                                    tracing::info!("MONITORING: Exiting due to {:?}", key.code);
+                                    return Ok(true); // Signal to quit
+                                }
 
-                                    // Re-render and exit to switch modes
+                                // An open help overlay or tour takes every other key
+                                if self.overlay.is_some() {
+                                    self.handle_overlay_key(&key);
                                     let uptime = self.start_time.elapsed();
                                     self.draw(session_info, uptime)?;
-                                    tracing::info!("MONITORING: Exiting to switch to interactive mode (Ctrl+T)");
-                                    return Ok(false); // Switch modes
+                                    continue;
                                 }
 
-                                // Handle other monitoring mode keys
-                                match key.code {
-                                    KeyCode::Char('i') => {
-                                        // Switch to interactive mode
+                                match action {
+                                    Some(Action::ToggleInteractive) => {
+                                        tracing::info!("SWITCHING TO INTERACTIVE MODE");
+
                                         self.interactive_mode = true;
-                                        self.status_message = "Switching to interactive mode...".to_string();
+                                        self.status_message = "Interactive mode ON - Direct PTY input".to_string();
 
-                                        // Get proper terminal dimensions for interactive mode
                                         // Don't resize PTY in monitoring mode - only in interactive mode
 
+                                        // Re-render and exit to switch modes
                                         let uptime = self.start_time.elapsed();
                                         self.draw(session_info, uptime)?;
-                                        tracing::info!("MONITORING: Exiting to switch to interactive mode (i key)");
+                                        tracing::info!("MONITORING: Exiting to switch to interactive mode");
                                         return Ok(false); // Switch modes
                                     }
-                                    KeyCode::Char('o') => {
+                                    Some(Action::OpenWeb) => {
                                         // Open web interface
                                         self.status_message = "Opening web interface...".to_string();
-                                        if let Err(e) = open::that(self.get_web_url()) {
+                                        if let Err(e) = open::that(&session_info.url) {
                                             self.status_message = format!("Failed to open browser: {}", e);
                                         } else {
                                             self.status_message = "Web interface opened".to_string();
@@ -608,12 +1197,33 @@ impl SessionTui {
                                         let uptime = self.start_time.elapsed();
                                         self.draw(session_info, uptime)?;
                                     }
-                                    KeyCode::Char('r') => {
+                                    Some(Action::Refresh) => {
                                         self.status_message = "Display refreshed".to_string();
                                         let uptime = self.start_time.elapsed();
                                         self.draw(session_info, uptime)?;
                                     }
-                                    _ => {}
+                                    Some(Action::Help) => {
+                                        self.overlay = Some(Overlay::Help);
+                                        let uptime = self.start_time.elapsed();
+                                        self.draw(session_info, uptime)?;
+                                    }
+                                    Some(Action::Retract) => {
+                                        self.retract_last_message().await;
+                                        let uptime = self.start_time.elapsed();
+                                        self.draw(session_info, uptime)?;
+                                    }
+                                    Some(Action::DetachWithNote) => {
+                                        self.start_note(session_info);
+                                        let uptime = self.start_time.elapsed();
+                                        self.draw(session_info, uptime)?;
+                                    }
+                                    Some(Action::CopyMode) => {
+                                        // The screen is only drawn in interactive mode
+                                        self.status_message = "Switch to interactive mode to copy from the screen".to_string();
+                                        let uptime = self.start_time.elapsed();
+                                        self.draw(session_info, uptime)?;
+                                    }
+                                    Some(Action::Quit | Action::Detach) | None => {}
                                 }
                             }
                         }
@@ -684,8 +1294,10 @@ impl SessionTui {
                     self.status_message = "Connected - Interactive mode active".to_string();
 
                     // Send initial resize to match current terminal size
-                    let terminal_area = self.get_pty_terminal_area()?;
-                    self.resize_pty_to_match_tui(terminal_area).await;
+                    if !self.presentation_mode {
+                        let terminal_area = self.get_pty_terminal_area()?;
+                        self.resize_pty_to_match_tui(terminal_area).await;
+                    }
                 }
                 Err(e) => {
                     tracing::error!("Failed to connect WebSocket: {}", e);
@@ -695,8 +1307,8 @@ impl SessionTui {
             }
         }
 
-        // Clone grid_tx and connection_status_tx for receiving updates - server will automatically send keyframe
-        let (grid_tx, connection_status_tx) = {
+        // Clone grid_tx, connection_status_tx and alert_tx for receiving updates - server will automatically send keyframe
+        let (grid_tx, connection_status_tx, alert_tx, input_tx, prompt_tx) = {
             let channels = match self.get_pty_channels() {
                 Ok(channels) => channels,
                 Err(e) => {
@@ -708,6 +1320,9 @@ impl SessionTui {
             (
                 channels.grid_tx.clone(),
                 channels.connection_status_tx.clone(),
+                channels.alert_tx.clone(),
+                channels.input_tx.clone(),
+                channels.prompt_tx.clone(),
             )
         };
 
@@ -715,6 +1330,11 @@ impl SessionTui {
         let mut event_stream = EventStream::new();
         let mut grid_update_stream = grid_tx.subscribe();
         let mut connection_status_stream = connection_status_tx.subscribe();
+        let mut alert_stream = alert_tx.subscribe();
+        let mut ack_stream = input_tx.subscribe_acks();
+        let mut lag_stream = input_tx.subscribe_lag();
+        let mut prompt_stream = prompt_tx.subscribe();
+        self.waiting_prompt = prompt_stream.borrow_and_update().clone();
 
         // Add a periodic timer to keep the display updated
         use tokio::time::interval;
@@ -724,6 +1344,7 @@ impl SessionTui {
         let mut pty_throttle = interval(Duration::from_millis(16));
 
         // Initial render after keyframe
+        self.refresh_cost().await;
         let uptime = self.start_time.elapsed();
         tracing::debug!("Performing initial draw after keyframe");
         self.draw(session_info, uptime)?;
@@ -758,6 +1379,7 @@ impl SessionTui {
 
                 // Periodic display update (also serves as heartbeat)
                 _ = display_interval.tick() => {
+                    self.refresh_cost().await;
                     let uptime = self.start_time.elapsed();
                     tracing::trace!("Interactive mode heartbeat - uptime: {}s", uptime.as_secs());
                     self.draw(session_info, uptime)?;
@@ -766,10 +1388,59 @@ impl SessionTui {
                 // Handle connection status updates
                 Ok(status) = connection_status_stream.recv() => {
                     tracing::debug!("Connection status updated: {:?}", status);
+                    if matches!(status, PtyConnectionStatus::Restored) {
+                        self.status_message =
+                            "Server restarted - session restored, screen refreshed".to_string();
+                    }
+                    if let PtyConnectionStatus::Exited { code } = status {
+                        self.status_message = format!("Agent exited with code {}", code);
+                    }
+                    // Acknowledgements of input sent before the connection dropped won't come
+                    if !matches!(status, PtyConnectionStatus::Connected) {
+                        self.pending_input.clear();
+                    }
+                    // The server repeats a prompt still on screen once reconnected
+                    if matches!(status, PtyConnectionStatus::Disconnected | PtyConnectionStatus::Reconnecting { .. }) {
+                        self.waiting_prompt = None;
+                    }
                     self.connection_status = status;
                     self.needs_redraw = true;
                 }
 
+                // Input written to the agent; the status bar catches up on the next tick
+                Ok(ack) = ack_stream.recv() => {
+                    self.pending_input.acknowledge(ack.seq, ack.written);
+                }
+
+                // The server tells us when typing stops getting through to the agent
+                Ok(()) = lag_stream.changed() => {
+                    self.input_lagging = *lag_stream.borrow_and_update();
+                    self.needs_redraw = true;
+                    let uptime = self.start_time.elapsed();
+                    self.draw(session_info, uptime)?;
+                }
+
+                // The agent started or stopped waiting for approval
+                Ok(()) = prompt_stream.changed() => {
+                    self.waiting_prompt = prompt_stream.borrow_and_update().clone();
+                    self.needs_redraw = true;
+                    let uptime = self.start_time.elapsed();
+                    self.draw(session_info, uptime)?;
+                }
+
+                // Server alerts take over the screen until a key is pressed
+                Ok(alert) = alert_stream.recv() => {
+                    self.status_message = format!("⚠️ {}", alert);
+                    self.alert = Some(alert);
+                    // Don't throw away a half-typed note or an unanswered paste prompt
+                    if !matches!(self.overlay, Some(Overlay::EditNote | Overlay::ConfirmPaste)) {
+                        self.overlay = Some(Overlay::Alert);
+                    }
+                    self.mark_full_redraw();
+                    let uptime = self.start_time.elapsed();
+                    self.draw(session_info, uptime)?;
+                }
+
                 // Handle keyboard events from async stream (prioritize user input)
                 maybe_event = event_stream.next() => {
                     match maybe_event {
@@ -777,29 +1448,95 @@ impl SessionTui {
                             if key.kind == KeyEventKind::Press {
                                 tracing::debug!("INTERACTIVE MODE - Key: {:?} modifiers: {:?}", key.code, key.modifiers);
 
+                                if self.overlay == Some(Overlay::EditNote) {
+                                    if self.handle_note_key(&key).await {
+                                        return Ok(true);
+                                    }
+                                    self.mark_full_redraw();
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                    continue;
+                                }
+
+                                // So does copy mode, until something is copied or it is left
+                                if self.copy_mode.is_some() {
+                                    self.handle_copy_key(&key);
+                                    self.mark_full_redraw();
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                    continue;
+                                }
+
+                                let KeyPress::Key { key, action, forward } = self.keybindings.press(&key, true) else {
+                                    continue; // The first of two keys, like Ctrl+\ of Ctrl+\ d
+                                };
+                                // A held key the binding wasn't finished after was meant for the agent
+                                if let Some(held) = forward {
+                                    if !self.presentation_mode && self.overlay.is_none() {
+                                        self.send_input_to_pty(&held).await;
+                                    }
+                                }
+
                                 // Handle quit
-                                if key.code == KeyCode::Char('c') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
+                                if matches!(action, Some(Action::Quit | Action::Detach)) {
                                     return Ok(true); // Signal to quit
                                 }
 
-                                // Handle toggle back to monitoring mode
-                                if key.code == KeyCode::Char('t') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
+                                if self.presentation_mode {
+                                    // Presentation mode is read-only
+                                    tracing::trace!("Ignoring key while presenting");
+                                } else if self.overlay == Some(Overlay::ConfirmPaste) {
+                                    self.answer_paste_prompt(&key).await;
+                                    self.mark_full_redraw();
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                } else if self.overlay.is_some() {
+                                    // Keys go to the help overlay or tour rather than the agent
+                                    self.handle_overlay_key(&key);
+                                    self.mark_full_redraw();
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                } else if action == Some(Action::ToggleInteractive) {
                                     tracing::info!("SWITCHING TO MONITORING MODE");
 
                                     self.interactive_mode = false;
                                     self.disconnect_websocket();
-                                    self.status_message = "Interactive mode OFF - Press Ctrl+T to toggle on".to_string();
+                                    self.status_message = "Interactive mode OFF".to_string();
 
                                     // Re-render and exit to switch modes
                                     let uptime = self.start_time.elapsed();
                                     self.draw(session_info, uptime)?;
                                     return Ok(false); // Switch modes
+                                } else if action == Some(Action::Help) {
+                                    self.overlay = Some(Overlay::Help);
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                } else if action == Some(Action::Retract) {
+                                    self.retract_last_message().await;
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                } else if action == Some(Action::DetachWithNote) {
+                                    self.start_note(session_info);
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                } else if action == Some(Action::CopyMode) {
+                                    self.enter_copy_mode();
+                                    self.mark_full_redraw();
+                                    let uptime = self.start_time.elapsed();
+                                    self.draw(session_info, uptime)?;
+                                } else {
+                                    // Send all other keys to PTY
+                                    self.send_input_to_pty(&key).await;
                                 }
-
-                                // Send all other keys to PTY
-                                self.send_input_to_pty(&key).await;
                             }
                         }
+                        Some(Ok(Event::Mouse(_) | Event::Paste(_))) if self.presentation_mode => {}
+                        Some(Ok(Event::Paste(text))) => {
+                            tracing::debug!("INTERACTIVE MODE - Paste: {} bytes", text.len());
+                            self.handle_paste(text).await;
+                            let uptime = self.start_time.elapsed();
+                            self.draw(session_info, uptime)?;
+                        }
                         Some(Ok(Event::Mouse(mouse))) => {
                             match mouse {
                                 crossterm::event::MouseEvent {
@@ -827,7 +1564,9 @@ impl SessionTui {
                             self.mark_full_redraw(); // Terminal resize requires full redraw
 
                             // Resize PTY to match new terminal size
-                            self.resize_pty_to_match_tui(terminal_area).await;
+                            if !self.presentation_mode {
+                                self.resize_pty_to_match_tui(terminal_area).await;
+                            }
 
                             // Redraw with new size
                             let uptime = self.start_time.elapsed();
@@ -892,6 +1631,9 @@ impl SessionTui {
                     } else if updates_processed > 0 {
                         tracing::debug!("Processed {} grid updates, batching (dirty cells: {}, time since last: {}ms)",
                             updates_processed, self.dirty_cells.len(), self.last_render_time.elapsed().as_millis());
+                    } else if self.input_indicator() != self.shown_input_indicator {
+                        let uptime = self.start_time.elapsed();
+                        self.draw(session_info, uptime)?;
                     }
                 }
             }
@@ -904,15 +1646,80 @@ impl SessionTui {
 
         // Extract needed data before the draw closure to avoid borrowing issues
         let interactive_mode = self.interactive_mode;
-        let terminal_grid = self.terminal_grid.clone();
-        let terminal_cursor = self.terminal_cursor;
-        let cursor_visible = self.terminal_cursor_visible;
+        let presentation_mode = self.presentation_mode;
+        // Copy mode draws the screen as it was when it started, with its own cursor
+        let copy = self.copy_mode.as_ref();
+        let terminal_grid =
+            copy.map_or_else(|| self.terminal_grid.clone(), |copy| copy.grid.clone());
+        let terminal_cursor = copy.map_or(self.terminal_cursor, |copy| copy.cursor);
+        let cursor_visible = copy.is_some() || self.terminal_cursor_visible;
+        let selection = copy.and_then(|copy| copy.anchor.map(|_| copy.selection()));
+        let copy_hints = copy.map(|copy| {
+            let shape = match copy.shape {
+                SelectionShape::Lines => "v=Block",
+                SelectionShape::Block => "v=Lines",
+            };
+            format!(
+                "Arrows/hjkl=Move | Space=Select | {} | Enter=Copy | Esc=Cancel",
+                shape
+            )
+        });
         let _terminal_grid_size = (
             terminal_size.height.saturating_sub(STATUS_BAR_HEIGHT),
             terminal_size.width,
         );
         let system_logs = self.system_logs.clone();
         let connection_status = self.connection_status.clone();
+        let high_contrast = self.preferences.high_contrast;
+        let session_color = string_color_to_ratatui(&session_info.color);
+        let session_icon = if session_info.icon.is_empty() {
+            "🚀"
+        } else {
+            session_info.icon.as_str()
+        };
+        // A per-second clock is constant motion; reduced motion shows whole minutes
+        let uptime_text = if self.preferences.reduced_motion {
+            format_duration_minutes(uptime)
+        } else {
+            format_duration(uptime)
+        };
+        // Key hints come from the active bindings so they stay accurate when remapped
+        let key_hints = |interactive: bool, separator: &str| {
+            Action::ALL
+                .iter()
+                .filter_map(|action| {
+                    self.keybindings
+                        .keys_for(*action, interactive)
+                        .map(|keys| format!("{}{}{}", keys, separator, action.label()))
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        let status_bar_hints = key_hints(true, "=");
+        let footer_hints = key_hints(false, ": ");
+        let instructions: Vec<(String, &'static str)> = Action::ALL
+            .iter()
+            .filter_map(|action| {
+                self.keybindings
+                    .keys_for(*action, false)
+                    .map(|keys| (keys, action.describe()))
+            })
+            .collect();
+        let overlay = self.overlay_content(session_info);
+        let input_indicator = self.input_indicator();
+        self.shown_input_indicator = input_indicator;
+        let input_note = input_indicator
+            .map(|note| format!(" | {}", note))
+            .unwrap_or_default();
+        let cost_note = self
+            .cost
+            .map(|cost| format!(" | 💰 ${:.2}", cost))
+            .unwrap_or_default();
+        let approval_banner = self
+            .waiting_prompt
+            .as_ref()
+            .map(|prompt| format!("⏸ WAITING FOR APPROVAL: {}", prompt.prompt()));
+        let approval_style = Style::default().bg(Color::Yellow).fg(Color::Black);
 
         self.terminal.draw(move |f| {
             let size = f.area();
@@ -921,20 +1728,34 @@ impl SessionTui {
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
-                        Constraint::Length(1),  // Minimal status bar
+                        // Minimal status bar, hidden while presenting
+                        Constraint::Length(if presentation_mode { 0 } else { STATUS_BAR_HEIGHT }),
                         Constraint::Min(0),     // Full PTY terminal
                     ])
                     .split(size);
 
-                // Minimal status bar
-                let mode_text = format!("🚀 {} | 💬 INTERACTIVE | {} | Ctrl+T=Toggle | Ctrl+C=Exit",
+                // Minimal status bar in the session's color, so sessions are told apart at a glance
+                let mode_text = format!("{} {} | {} | {}{}{} | {}",
+                    session_icon,
                     session_info.agent.to_uppercase(),
-                    format_duration(uptime)
+                    if copy_hints.is_some() { "📋 COPY" } else { "💬 INTERACTIVE" },
+                    uptime_text,
+                    cost_note,
+                    input_note,
+                    copy_hints.as_deref().or(approval_banner.as_deref()).unwrap_or(&status_bar_hints)
                 );
+                // A waiting agent stands out from the session's color
+                let status_style = match session_color {
+                    _ if approval_banner.is_some() => approval_style,
+                    Some(color) if !high_contrast => Style::default().bg(color).fg(Color::Black),
+                    _ => Style::default().bg(Color::Blue).fg(Color::White),
+                };
                 let status_bar = Paragraph::new(mode_text)
-                    .style(Style::default().bg(Color::Blue).fg(Color::White).add_modifier(Modifier::BOLD))
+                    .style(status_style.add_modifier(Modifier::BOLD))
                     .alignment(Alignment::Center);
-                f.render_widget(status_bar, chunks[0]);
+                if !presentation_mode {
+                    f.render_widget(status_bar, chunks[0]);
+                }
 
                 // PTY terminal area - render from grid state
                 let terminal_area = chunks[1];
@@ -956,7 +1777,7 @@ impl SessionTui {
 
                 // Create terminal content from grid state - calculate dimensions from grid
                 let grid_dimensions = calculate_grid_dimensions(&terminal_grid);
-                let terminal_content = render_terminal_from_grid(&terminal_grid, grid_dimensions, terminal_cursor, cursor_visible, terminal_area.height, terminal_area.width);
+                let terminal_content = render_terminal_from_grid(&terminal_grid, grid_dimensions, terminal_cursor, cursor_visible, selection, terminal_area.height, terminal_area.width);
                 let terminal_widget = Paragraph::new(terminal_content)
                     .block(Block::default().borders(Borders::NONE));
                     // No wrapping - each line should be rendered exactly as provided
@@ -964,7 +1785,10 @@ impl SessionTui {
 
                 // Draw disconnection overlay if not connected
                 // Use the full screen size for proper centering
-                if !matches!(connection_status, PtyConnectionStatus::Connected) {
+                if !matches!(
+                    connection_status,
+                    PtyConnectionStatus::Connected | PtyConnectionStatus::Restored
+                ) {
                     draw_connection_overlay(f, f.area(), &connection_status);
                 }
 
@@ -980,10 +1804,10 @@ impl SessionTui {
                     .split(size);
 
                 // Header
-                let header = Paragraph::new(format!("🚀 CodeMux - {} Agent Session", session_info.agent.to_uppercase()))
+                let header = Paragraph::new(format!("{} CodeMux - {} Agent Session", session_icon, session_info.agent.to_uppercase()))
                     .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
                     .alignment(Alignment::Center)
-                    .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Blue)));
+                    .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(session_color.unwrap_or(Color::Blue))));
                 f.render_widget(header, chunks[0]);
 
                 // Main content area
@@ -1001,19 +1825,27 @@ impl SessionTui {
                 // Session information
                 draw_session_info(f, content_chunks[0], session_info);
                 // Status section
-                draw_status(f, content_chunks[1], uptime, interactive_mode, &connection_status);
+                draw_status(f, content_chunks[1], &uptime_text, interactive_mode, &connection_status);
                 // System logs section
                 draw_system_logs(f, content_chunks[2], &system_logs);
                 // Instructions
-                draw_instructions(f, content_chunks[3]);
+                draw_instructions(f, content_chunks[3], &instructions);
 
                 // Footer
-                let footer = Paragraph::new("Ctrl+C: Stop | i: Interactive Mode | o: Open Web | r: Refresh | Ctrl+T: Interactive Mode")
+                let footer = Paragraph::new(footer_hints)
                     .style(Style::default().fg(Color::Gray))
                     .alignment(Alignment::Center)
                     .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Gray)));
                 f.render_widget(footer, chunks[2]);
             }
+
+            if let Some((title, lines)) = overlay {
+                draw_text_overlay(f, f.area(), title, lines);
+            }
+
+            if high_contrast {
+                apply_high_contrast(f.buffer_mut());
+            }
         })?;
 
         Ok(())
@@ -1043,6 +1875,7 @@ fn render_terminal_from_grid(
     terminal_size: (u16, u16),
     cursor_pos: (u16, u16),
     cursor_visible: bool,
+    selection: Option<Selection>,
     display_height: u16,
     display_width: u16,
 ) -> Vec<ratatui::text::Line> {
@@ -1060,6 +1893,9 @@ fn render_terminal_from_grid(
         // Build line from grid cells
         for col in 0..std::cmp::min(grid_cols, display_width) {
             let is_cursor = (row, col) == cursor_pos;
+            let selected =
+                selection.is_some_and(|selection| selection.contains(row, col, grid_cols));
+            let selected_style = Style::default().bg(Color::LightBlue).fg(Color::Black);
 
             if let Some(cell) = terminal_grid.get(&(row, col)) {
                 // Convert grid cell to styled content
@@ -1095,6 +1931,9 @@ fn render_terminal_from_grid(
                         Modifier::empty()
                     });
 
+                if selected {
+                    cell_style = cell_style.patch(selected_style);
+                }
                 // Highlight cursor position with reversed colors (only if cursor is visible)
                 if is_cursor && cursor_visible {
                     cell_style = cell_style.add_modifier(Modifier::REVERSED);
@@ -1117,6 +1956,9 @@ fn render_terminal_from_grid(
             } else {
                 // Empty cell - use space, but highlight if cursor is here and visible
                 let mut empty_style = Style::default();
+                if selected {
+                    empty_style = selected_style;
+                }
                 if is_cursor && cursor_visible {
                     empty_style = empty_style.add_modifier(Modifier::REVERSED);
                 }
@@ -1290,7 +2132,7 @@ fn draw_session_info(f: &mut Frame, area: Rect, session_info: &SessionInfo) {
 fn draw_status(
     f: &mut Frame,
     area: Rect,
-    uptime: Duration,
+    uptime_str: &str,
     interactive_mode: bool,
     connection_status: &PtyConnectionStatus,
 ) {
@@ -1299,8 +2141,6 @@ fn draw_status(
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Green));
 
-    let uptime_str = format_duration(uptime);
-
     let mode_status = if interactive_mode {
         Span::styled(
             "💬 Interactive",
@@ -1324,6 +2164,12 @@ fn draw_status(
                 .fg(Color::Green)
                 .add_modifier(Modifier::BOLD),
         ),
+        PtyConnectionStatus::Restored => Span::styled(
+            "🟢 Connected (server restarted)",
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
         PtyConnectionStatus::Disconnected => Span::styled(
             "🔴 Disconnected",
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
@@ -1337,6 +2183,27 @@ fn draw_status(
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         ),
+        PtyConnectionStatus::Exited { .. } => Span::styled(
+            "⚫ Closed",
+            Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::BOLD),
+        ),
+    };
+
+    let session_status = match connection_status {
+        PtyConnectionStatus::Exited { code } => Span::styled(
+            format!("⚫ Exited (code {})", code),
+            Style::default()
+                .fg(if *code == 0 { Color::Gray } else { Color::Red })
+                .add_modifier(Modifier::BOLD),
+        ),
+        _ => Span::styled(
+            "🟢 Running",
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+        ),
     };
 
     let mut status_lines = vec![
@@ -1347,12 +2214,7 @@ fn draw_status(
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::styled(
-                "🟢 Running",
-                Style::default()
-                    .fg(Color::Green)
-                    .add_modifier(Modifier::BOLD),
-            ),
+            session_status,
         ]),
         Line::from(vec![
             Span::styled(
@@ -1442,17 +2304,17 @@ fn draw_system_logs(f: &mut Frame, area: Rect, logs: &[LogEntry]) {
     }
 }
 
-fn draw_instructions(f: &mut Frame, area: Rect) {
+fn draw_instructions(f: &mut Frame, area: Rect, keys: &[(String, &str)]) {
     let instructions_block = Block::default()
         .title("💡 Instructions")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
-    let instructions = vec![
-        Line::from("• Press 'i' to enter interactive mode and control the agent directly"),
-        Line::from("• Press 'o' to open the web interface in your browser"),
-        Line::from("• Press 'r' to refresh the display"),
-        Line::from("• Press Ctrl+C to stop the session"),
+    let mut instructions: Vec<Line> = keys
+        .iter()
+        .map(|(keys, description)| Line::from(format!("• {}: {}", keys, description)))
+        .collect();
+    instructions.extend([
         Line::from(""),
         Line::from(vec![
             Span::styled(
@@ -1461,9 +2323,9 @@ fn draw_instructions(f: &mut Frame, area: Rect) {
                     .fg(Color::Yellow)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw("Keep this terminal open to maintain the session"),
+            Span::raw("Exiting detaches; the agent keeps running on the server"),
         ]),
-    ];
+    ]);
 
     let instructions_paragraph = Paragraph::new(instructions)
         .block(instructions_block)
@@ -1493,6 +2355,96 @@ fn format_duration(duration: Duration) -> String {
     }
 }
 
+fn format_duration_minutes(duration: Duration) -> String {
+    let total_minutes = duration.as_secs() / 60;
+    let hours = total_minutes / 60;
+    let minutes = total_minutes % 60;
+
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        "<1m".to_string()
+    }
+}
+
+/// First line of a message, shortened to fit in the status bar
+fn truncate_for_status(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > 40 || text.contains('\n') {
+        format!("{}…", line.chars().take(40).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Rewrite a rendered frame for high contrast
+///
+/// Dim and dark foregrounds become white or their bright variants, and any
+/// coloured background becomes an inverse-video white bar. Applied after
+/// drawing so the agent's output and codemux's own chrome are treated alike.
+fn apply_high_contrast(buffer: &mut ratatui::buffer::Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if !matches!(cell.bg, Color::Reset | Color::Black) {
+            cell.set_bg(Color::White);
+            cell.set_fg(Color::Black);
+            continue;
+        }
+        let fg = match cell.fg {
+            Color::Gray | Color::DarkGray | Color::Indexed(232..=255) => Color::White,
+            Color::Red => Color::LightRed,
+            Color::Green => Color::LightGreen,
+            Color::Yellow => Color::LightYellow,
+            Color::Blue | Color::Cyan => Color::LightCyan,
+            Color::Magenta => Color::LightMagenta,
+            // Perceived brightness below half is hard to read on black
+            Color::Rgb(r, g, b)
+                if (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000 < 128 =>
+            {
+                Color::White
+            }
+            other => other,
+        };
+        cell.set_fg(fg);
+    }
+}
+
+/// Centered popup with a border, sized to its text
+fn draw_text_overlay(f: &mut Frame, area: Rect, title: String, lines: Vec<Line<'static>>) {
+    use ratatui::widgets::Clear;
+
+    let width = 72.min(area.width);
+    // Borders plus the text once wrapped to the inner width
+    let inner_width = width.saturating_sub(4).max(1) as usize;
+    let text_height: usize = lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(inner_width))
+        .sum();
+    let height = (text_height as u16 + 2).min(area.height);
+
+    let overlay_area = Rect::new(
+        area.x + area.width.saturating_sub(width) / 2,
+        area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    );
+
+    let overlay = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan))
+                .padding(ratatui::widgets::Padding::horizontal(1)),
+        )
+        .style(Style::default().bg(Color::Black).fg(Color::White))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(Clear, overlay_area);
+    f.render_widget(overlay, overlay_area);
+}
+
 fn draw_connection_overlay(f: &mut Frame, area: Rect, connection_status: &PtyConnectionStatus) {
     use ratatui::widgets::Clear;
 
@@ -1552,7 +2504,26 @@ fn draw_connection_overlay(f: &mut Frame, area: Rect, connection_status: &PtyCon
                 .fg(Color::Black)
                 .add_modifier(Modifier::BOLD),
         ),
-        PtyConnectionStatus::Connected => {
+        PtyConnectionStatus::Exited { code } => (
+            " ⚫ AGENT EXITED ",
+            vec![
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    format!("The agent exited with code {}", code),
+                    Style::default().fg(Color::White),
+                )]),
+                Line::from(""),
+                Line::from(vec![Span::styled(
+                    "Detach to leave the TUI",
+                    Style::default().fg(Color::Gray),
+                )]),
+            ],
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        ),
+        PtyConnectionStatus::Connected | PtyConnectionStatus::Restored => {
             // This shouldn't happen as we only show overlay when not connected
             return;
         }
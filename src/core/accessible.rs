@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use ts_rs::TS;
+
+use crate::core::pty_session::{GridCell, GridUpdateMessage};
+
+/// Number of recently emitted lines remembered to suppress repeats from redraws and scrolling
+const RECENT_LINES: usize = 500;
+
+/// Screen-reader friendly view of a session: finished lines of text plus announcements
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type")]
+#[ts(export)]
+pub enum AccessibleEvent {
+    #[serde(rename = "line")]
+    Line { text: String },
+    #[serde(rename = "announcement")]
+    Announcement {
+        kind: AnnouncementKind,
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum AnnouncementKind {
+    /// The agent is waiting for an answer
+    Question,
+    Error,
+    SessionEnded,
+}
+
+impl AnnouncementKind {
+    /// Spoken prefix for the announcement
+    pub fn describe(self) -> &'static str {
+        match self {
+            AnnouncementKind::Question => "Agent is asking a question",
+            AnnouncementKind::Error => "Error",
+            AnnouncementKind::SessionEnded => "Session ended",
+        }
+    }
+}
+
+/// Turns grid updates into a linear stream of new text
+///
+/// A row is emitted once the cursor has left it, so partially typed lines and
+/// spinners aren't read out character by character. Rows the agent is still
+/// sitting on are only emitted early when they look like a question. Lines
+/// already emitted recently (modulo digits and symbols) are skipped, which
+/// absorbs screen redraws, scrolling and elapsed-time counters.
+#[derive(Debug, Default)]
+pub struct Linearizer {
+    cells: HashMap<(u16, u16), GridCell>,
+    cursor_row: u16,
+    pending: BTreeSet<u16>,
+    recent: VecDeque<String>,
+    recent_set: HashSet<String>,
+}
+
+impl Linearizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, update: &GridUpdateMessage) -> Vec<AccessibleEvent> {
+        match update {
+            GridUpdateMessage::Keyframe { cells, cursor, .. } => {
+                self.cells = cells.iter().cloned().collect();
+                self.pending = self.cells.keys().map(|(row, _)| *row).collect();
+                self.cursor_row = cursor.0;
+            }
+            GridUpdateMessage::Diff {
+                changes, cursor, ..
+            } => {
+                for (row, col, cell) in changes {
+                    self.cells.insert((*row, *col), cell.clone());
+                    self.pending.insert(*row);
+                }
+                if let Some(cursor) = cursor {
+                    self.cursor_row = cursor.0;
+                }
+            }
+        }
+
+        let mut events = Vec::new();
+        let rows: Vec<u16> = self.pending.iter().copied().collect();
+        for row in rows {
+            let text = self.row_text(row);
+            let announcement = classify(&text);
+            if row == self.cursor_row && announcement != Some(AnnouncementKind::Question) {
+                continue;
+            }
+            self.pending.remove(&row);
+            if let Some(event) = self.emit(text, announcement) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    fn row_text(&self, row: u16) -> String {
+        let mut cols: Vec<(u16, &str)> = self
+            .cells
+            .iter()
+            .filter(|((r, _), _)| *r == row)
+            .map(|((_, col), cell)| (*col, cell.char.as_str()))
+            .collect();
+        cols.sort_by_key(|(col, _)| *col);
+
+        let mut text = String::new();
+        let mut next_col = 0;
+        for (col, ch) in cols {
+            // Missing cells are blank, and wide characters leave an empty continuation cell
+            for _ in next_col..col {
+                text.push(' ');
+            }
+            text.push_str(ch);
+            next_col = col + 1;
+        }
+        text.trim_end().to_string()
+    }
+
+    fn emit(
+        &mut self,
+        text: String,
+        announcement: Option<AnnouncementKind>,
+    ) -> Option<AccessibleEvent> {
+        let key = dedup_key(&text);
+        // Borders, spinners and blank rows carry nothing worth reading out
+        if key.is_empty() || self.recent_set.contains(&key) {
+            return None;
+        }
+
+        if self.recent.len() == RECENT_LINES {
+            if let Some(old) = self.recent.pop_front() {
+                self.recent_set.remove(&old);
+            }
+        }
+        self.recent.push_back(key.clone());
+        self.recent_set.insert(key);
+
+        let text = text.trim().to_string();
+        Some(match announcement {
+            Some(kind) => AccessibleEvent::Announcement { kind, text },
+            None => AccessibleEvent::Line { text },
+        })
+    }
+}
+
+fn dedup_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphabetic() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn classify(text: &str) -> Option<AnnouncementKind> {
+    let trimmed = text.trim();
+    let lower = trimmed.to_lowercase();
+
+    if lower.contains("[y/n]")
+        || lower.contains("(y/n)")
+        || lower.starts_with("do you want")
+        || lower.starts_with("would you like")
+        || (trimmed.ends_with('?') && trimmed.split_whitespace().count() > 2)
+    {
+        return Some(AnnouncementKind::Question);
+    }
+
+    let first_word = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .find(|word| !word.is_empty())
+        .unwrap_or_default();
+    if matches!(first_word, "error" | "fatal" | "panic") {
+        return Some(AnnouncementKind::Error);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::SystemTime;
+
+    fn text_diff(row: u16, text: &str, cursor: (u16, u16)) -> GridUpdateMessage {
+        let changes = text
+            .chars()
+            .enumerate()
+            .map(|(col, ch)| {
+                let cell = GridCell {
+                    char: ch.to_string(),
+                    fg_color: None,
+                    bg_color: None,
+                    bold: false,
+                    italic: false,
+                    underline: false,
+                    reverse: false,
+                };
+                (row, col as u16, cell)
+            })
+            .collect();
+        GridUpdateMessage::Diff {
+            changes,
+            cursor: Some(cursor),
+            cursor_visible: None,
+            scrollback_position: None,
+            scrollback_total: None,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn emits_finished_lines_once_and_announces_questions() {
+        let mut linearizer = Linearizer::new();
+
+        // The line under the cursor is held back until the cursor moves on
+        assert_eq!(
+            linearizer.apply(&text_diff(0, "Reading files", (0, 13))),
+            vec![]
+        );
+        assert_eq!(
+            linearizer.apply(&text_diff(1, "✻ Working… (1s)", (2, 0))),
+            vec![
+                AccessibleEvent::Line {
+                    text: "Reading files".to_string()
+                },
+                AccessibleEvent::Line {
+                    text: "✻ Working… (1s)".to_string()
+                },
+            ]
+        );
+        // Spinner frames and ticking timers are not repeated
+        assert_eq!(
+            linearizer.apply(&text_diff(1, "✶ Working… (2s)", (2, 0))),
+            vec![]
+        );
+
+        assert_eq!(
+            linearizer.apply(&text_diff(2, "Do you want to apply this edit?", (2, 31))),
+            vec![AccessibleEvent::Announcement {
+                kind: AnnouncementKind::Question,
+                text: "Do you want to apply this edit?".to_string()
+            }]
+        );
+        assert_eq!(
+            linearizer.apply(&text_diff(3, "Error: permission denied", (4, 0))),
+            vec![AccessibleEvent::Announcement {
+                kind: AnnouncementKind::Error,
+                text: "Error: permission denied".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+use ts_rs::TS;
+
+/// Width of a single timeseries bucket
+pub const ACTIVITY_BUCKET_SECONDS: u64 = 60;
+/// Number of buckets kept per session (two hours of per-minute history)
+pub const ACTIVITY_BUCKET_COUNT: usize = 120;
+
+/// PTY traffic observed during one bucket
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ActivityBucket {
+    /// Bucket start, in seconds since the Unix epoch
+    #[ts(type = "number")]
+    pub start: u64,
+    /// Bytes written to the PTY by clients
+    #[ts(type = "number")]
+    pub bytes_in: u64,
+    /// Bytes produced by the agent
+    #[ts(type = "number")]
+    pub bytes_out: u64,
+    /// Number of input messages (key presses, pastes, raw writes)
+    pub input_events: u32,
+    /// Number of output reads from the PTY
+    pub output_events: u32,
+}
+
+/// Per-bucket I/O history of a session, oldest bucket first
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ActivityTimeseries {
+    #[ts(type = "number")]
+    pub bucket_seconds: u64,
+    pub buckets: Vec<ActivityBucket>,
+}
+
+/// Ring buffer of per-minute I/O counters for one PTY session
+///
+/// Buckets are contiguous: minutes without traffic are stored as zero buckets
+/// so the series can be plotted without gap handling on the client.
+#[derive(Debug, Default)]
+pub struct ActivityRecorder {
+    buckets: VecDeque<ActivityBucket>,
+}
+
+impl ActivityRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_input(&mut self, bytes: usize) {
+        let bucket = self.bucket_at(now_secs());
+        bucket.bytes_in += bytes as u64;
+        bucket.input_events += 1;
+    }
+
+    pub fn record_output(&mut self, bytes: usize) {
+        let bucket = self.bucket_at(now_secs());
+        bucket.bytes_out += bytes as u64;
+        bucket.output_events += 1;
+    }
+
+    /// Snapshot the history up to and including the current bucket
+    pub fn timeseries(&mut self) -> ActivityTimeseries {
+        self.timeseries_at(now_secs())
+    }
+
+    fn timeseries_at(&mut self, now: u64) -> ActivityTimeseries {
+        if !self.buckets.is_empty() {
+            self.bucket_at(now);
+        }
+        ActivityTimeseries {
+            bucket_seconds: ACTIVITY_BUCKET_SECONDS,
+            buckets: self.buckets.iter().cloned().collect(),
+        }
+    }
+
+    fn bucket_at(&mut self, now: u64) -> &mut ActivityBucket {
+        let start = now - now % ACTIVITY_BUCKET_SECONDS;
+
+        match self.buckets.back() {
+            Some(last) if last.start >= start => {}
+            Some(last) => {
+                // Zero-fill idle minutes, but never more than the buffer can hold
+                let missing = ((start - last.start) / ACTIVITY_BUCKET_SECONDS) as usize;
+                let first_new = start
+                    - (missing.min(ACTIVITY_BUCKET_COUNT) as u64 - 1) * ACTIVITY_BUCKET_SECONDS;
+                let mut bucket_start = first_new.max(last.start + ACTIVITY_BUCKET_SECONDS);
+                while bucket_start <= start {
+                    self.push(ActivityBucket {
+                        start: bucket_start,
+                        ..Default::default()
+                    });
+                    bucket_start += ACTIVITY_BUCKET_SECONDS;
+                }
+            }
+            None => self.push(ActivityBucket {
+                start,
+                ..Default::default()
+            }),
+        }
+
+        self.buckets.back_mut().expect("bucket was just ensured")
+    }
+
+    fn push(&mut self, bucket: ActivityBucket) {
+        if self.buckets.len() == ACTIVITY_BUCKET_COUNT {
+            self.buckets.pop_front();
+        }
+        self.buckets.push_back(bucket);
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_idle_minutes_and_caps_history() {
+        let mut recorder = ActivityRecorder::new();
+        recorder.bucket_at(600).bytes_out += 10;
+        recorder.bucket_at(630).bytes_out += 5;
+        recorder.bucket_at(785).bytes_in += 3;
+
+        let series = recorder.timeseries_at(790);
+        let starts: Vec<u64> = series.buckets.iter().map(|b| b.start).collect();
+        assert_eq!(starts, vec![600, 660, 720, 780]);
+        assert_eq!(series.buckets[0].bytes_out, 15);
+        assert_eq!(series.buckets[3].bytes_in, 3);
+
+        // A long idle stretch only keeps the most recent window
+        let series = recorder.timeseries_at(600 + 10_000 * ACTIVITY_BUCKET_SECONDS);
+        assert_eq!(series.buckets.len(), ACTIVITY_BUCKET_COUNT);
+        assert!(series
+            .buckets
+            .iter()
+            .all(|b| b.bytes_in == 0 && b.bytes_out == 0));
+    }
+}
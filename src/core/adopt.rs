@@ -0,0 +1,194 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+use super::launch::find_executable;
+
+/// An agent CLI running outside codemux
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentProcess {
+    pub pid: u32,
+    pub agent: String,
+    /// Full command line as reported by `ps`
+    pub command: String,
+    /// Working directory, where the platform lets us read it
+    pub cwd: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ProcessEntry {
+    pid: u32,
+    ppid: u32,
+    comm: String,
+    args: String,
+}
+
+fn executable_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn parse_ps_output(output: &str) -> Vec<ProcessEntry> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let ppid = fields.next()?.parse().ok()?;
+            let comm = executable_name(fields.next()?).to_string();
+            let args = fields.collect::<Vec<_>>().join(" ");
+            Some(ProcessEntry {
+                pid,
+                ppid,
+                comm,
+                args,
+            })
+        })
+        .collect()
+}
+
+/// Which of `agents` a process is running, either directly or as a script
+/// (`node /usr/local/bin/claude`)
+fn matching_agent<'a>(entry: &ProcessEntry, agents: &'a [String]) -> Option<&'a String> {
+    let mut args = entry.args.split_whitespace().map(executable_name);
+    let (first, second) = (args.next(), args.next());
+    agents.iter().find(|agent| {
+        entry.comm == agent.as_str()
+            || first == Some(agent.as_str())
+            || (second == Some(agent.as_str())
+                && matches!(first, Some("node" | "python" | "python3" | "bun" | "deno")))
+    })
+}
+
+/// Agent processes in `entries` that don't descend from a codemux process
+fn find_unmanaged(entries: &[ProcessEntry], agents: &[String]) -> Vec<(u32, String, String)> {
+    let managed = |mut pid: u32| {
+        // Bounded walk in case `ps` reports a parent loop
+        for _ in 0..64 {
+            let Some(entry) = entries.iter().find(|e| e.pid == pid) else {
+                return false;
+            };
+            if entry.comm == "codemux" {
+                return true;
+            }
+            if entry.ppid == pid || entry.ppid == 0 {
+                return false;
+            }
+            pid = entry.ppid;
+        }
+        false
+    };
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let agent = matching_agent(entry, agents)?;
+            // Only the top process of an agent, not its own helper subprocesses
+            let parent_is_same_agent = entries
+                .iter()
+                .find(|e| e.pid == entry.ppid)
+                .and_then(|parent| matching_agent(parent, agents))
+                == Some(agent);
+            (!parent_is_same_agent && !managed(entry.pid))
+                .then(|| (entry.pid, agent.clone(), entry.args.clone()))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+fn process_cwd(pid: u32) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_cwd(_pid: u32) -> Option<PathBuf> {
+    None
+}
+
+fn ps(selection: &[&str]) -> Result<Vec<ProcessEntry>> {
+    let output = std::process::Command::new("ps")
+        .args(selection)
+        .args(["-o", "pid=,ppid=,comm=,args="])
+        .output()
+        .map_err(|e| anyhow!("Failed to run ps: {}", e))?;
+    // `ps -p` also fails when the process doesn't exist, with nothing on stderr
+    if !output.status.success() && !output.stderr.is_empty() {
+        return Err(anyhow!(
+            "ps failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(parse_ps_output(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Find running agent CLIs that were started outside codemux
+pub fn scan_agent_processes(agents: &[String]) -> Result<Vec<AgentProcess>> {
+    let entries = ps(&["-ax"])?;
+    let own_pid = std::process::id();
+    Ok(find_unmanaged(&entries, agents)
+        .into_iter()
+        .filter(|(pid, _, _)| *pid != own_pid)
+        .map(|(pid, agent, command)| AgentProcess {
+            pid,
+            agent,
+            command,
+            cwd: process_cwd(pid),
+        })
+        .collect())
+}
+
+/// Command that moves process `pid` onto a new codemux PTY with reptyr
+///
+/// The process has to be running `agent`, under its name or its configured
+/// `command`, recognized as `codemux adopt --scan` does, so a session can't
+/// take over shells, editors or the server itself.
+pub fn adopt_command(pid: u32, agent: &str, command: &str) -> Result<(String, Vec<String>)> {
+    let reptyr = find_executable("reptyr").ok_or_else(|| {
+        anyhow!("Adopting a running process needs reptyr (https://github.com/nelhage/reptyr)")
+    })?;
+    let pid_arg = pid.to_string();
+    let entry = ps(&["-p", &pid_arg])?
+        .into_iter()
+        .find(|entry| entry.pid == pid)
+        .ok_or_else(|| anyhow!("No process with PID {}", pid))?;
+    let names = [agent.to_string(), executable_name(command).to_string()];
+    if matching_agent(&entry, &names).is_none() {
+        return Err(anyhow!("PID {} is not running {}", pid, agent));
+    }
+    Ok((reptyr.to_string_lossy().to_string(), vec![pid.to_string()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_agents_outside_codemux() {
+        let ps = "\
+            1     0 /sbin/init     /sbin/init
+          100     1 zsh            -zsh
+          101   100 claude         claude --model sonnet
+          102   101 node           node /tmp/helper.js
+          200     1 codemux        codemux server start
+          201   200 claude         claude --session-id abc
+          300   100 node           node /usr/local/bin/gemini
+          301   300 gemini         gemini --helper
+          400   100 vim            vim claude.md
+        ";
+        let agents = vec!["claude".to_string(), "gemini".to_string()];
+        let found = find_unmanaged(&parse_ps_output(ps), &agents);
+        assert_eq!(
+            found,
+            [
+                (
+                    101,
+                    "claude".to_string(),
+                    "claude --model sonnet".to_string()
+                ),
+                (
+                    300,
+                    "gemini".to_string(),
+                    "node /usr/local/bin/gemini".to_string()
+                ),
+            ]
+        );
+    }
+}
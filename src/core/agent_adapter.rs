@@ -0,0 +1,496 @@
+//! Conversation continuity for agents that keep their own history
+//!
+//! codemux's `--continue` (the latest conversation) and `--resume <id>` mean the
+//! same thing for every agent, but each agent spells them differently and keeps
+//! its transcripts somewhere else. An adapter knows both for one agent, so
+//! resuming and the structured conversation view work beyond Claude.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+use super::agent_version::AgentConfig;
+use super::transcript::{lookup, text_of, SpecAdapter, TranscriptMessage, TranscriptSpec};
+
+/// How one agent resumes conversations and where it keeps them
+pub trait AgentAdapter: Send + Sync {
+    /// Agent arguments that pick up the latest conversation in the working
+    /// directory, or the one with the given ID
+    fn resume_args(&self, conversation: Option<&str>) -> Vec<String>;
+
+    /// Directory the agent keeps the conversations of `project` in
+    fn transcript_dir(&self, home: &Path, project: &Path) -> PathBuf;
+
+    /// Whether a file in `transcript_dir` is a conversation transcript
+    fn is_transcript(&self, path: &Path) -> bool;
+
+    /// Transcripts of the conversations in `project`, most recently written first
+    fn transcripts(&self, home: &Path, project: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(self.transcript_dir(home, project)) else {
+            return Vec::new();
+        };
+        let mut transcripts: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| self.is_transcript(path))
+            .filter_map(|path| {
+                let modified = path.metadata().and_then(|meta| meta.modified()).ok()?;
+                Some((modified, path))
+            })
+            .collect();
+        transcripts.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        transcripts.into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// Transcript of the codemux session `session_id`, by default the newest one
+    fn session_transcript(&self, home: &Path, project: &Path, session_id: &str) -> Option<PathBuf> {
+        let _ = session_id;
+        self.transcripts(home, project).into_iter().next()
+    }
+
+    /// Entries of a transcript, oldest first; unreadable entries are skipped
+    fn parse_transcript(&self, content: &str) -> Vec<Value>;
+
+    /// The messages among transcript entries, leaving out bookkeeping and tool plumbing
+    fn messages(&self, entries: &[Value]) -> Vec<TranscriptMessage>;
+}
+
+/// Claude Code: JSONL files under `~/.claude/projects/<path with dashes>/`
+pub struct ClaudeAdapter;
+
+impl AgentAdapter for ClaudeAdapter {
+    fn resume_args(&self, conversation: Option<&str>) -> Vec<String> {
+        match conversation {
+            Some(id) => vec!["--resume".to_string(), id.to_string()],
+            None => vec!["--continue".to_string()],
+        }
+    }
+
+    fn transcript_dir(&self, home: &Path, project: &Path) -> PathBuf {
+        let path = project.to_string_lossy();
+        let encoded = match path.strip_prefix('/') {
+            Some(stripped) => format!("-{}", stripped.replace('/', "-")),
+            None => format!("-{}", path.replace('/', "-")),
+        };
+        home.join(".claude").join("projects").join(encoded)
+    }
+
+    fn is_transcript(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "jsonl")
+    }
+
+    /// Claude runs under the codemux session's ID, see `--session-id`
+    fn session_transcript(&self, home: &Path, project: &Path, session_id: &str) -> Option<PathBuf> {
+        let path = self
+            .transcript_dir(home, project)
+            .join(format!("{}.jsonl", session_id));
+        path.exists().then_some(path)
+    }
+
+    fn parse_transcript(&self, content: &str) -> Vec<Value> {
+        content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    fn messages(&self, entries: &[Value]) -> Vec<TranscriptMessage> {
+        entries
+            .iter()
+            .filter(|entry| matches!(entry["type"].as_str(), Some("user" | "assistant")))
+            .filter_map(|entry| {
+                // Tool results come back as user entries without text
+                let content = text_of(lookup(entry, "message.content")?);
+                if content.is_empty() {
+                    return None;
+                }
+                let usage = |key: &str| lookup(entry, &format!("message.usage.{}", key))?.as_u64();
+                Some(TranscriptMessage {
+                    role: text_of(&entry["message"]["role"]),
+                    content,
+                    timestamp: entry["timestamp"].as_str().map(str::to_string),
+                    input_tokens: usage("input_tokens"),
+                    output_tokens: usage("output_tokens"),
+                    cost: entry["costUSD"].as_f64(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Gemini CLI: one JSON document per conversation under
+/// `~/.gemini/tmp/<sha256 of the project path>/chats/`, rewritten as it goes
+pub struct GeminiAdapter;
+
+impl AgentAdapter for GeminiAdapter {
+    fn resume_args(&self, conversation: Option<&str>) -> Vec<String> {
+        vec![
+            "--resume".to_string(),
+            conversation.unwrap_or("latest").to_string(),
+        ]
+    }
+
+    fn transcript_dir(&self, home: &Path, project: &Path) -> PathBuf {
+        let hash = Sha256::digest(project.to_string_lossy().as_bytes());
+        let hash: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+        home.join(".gemini").join("tmp").join(hash).join("chats")
+    }
+
+    fn is_transcript(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "json")
+            && path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("session-"))
+    }
+
+    fn parse_transcript(&self, content: &str) -> Vec<Value> {
+        match serde_json::from_str::<Value>(content) {
+            Ok(Value::Object(mut conversation)) => match conversation.remove("messages") {
+                Some(Value::Array(messages)) => messages,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn messages(&self, entries: &[Value]) -> Vec<TranscriptMessage> {
+        entries
+            .iter()
+            .filter_map(|entry| {
+                let content = text_of(&entry["content"]);
+                if content.is_empty() {
+                    return None;
+                }
+                let role = match entry["type"].as_str() {
+                    Some("gemini") => "assistant".to_string(),
+                    _ => text_of(&entry["type"]),
+                };
+                Some(TranscriptMessage {
+                    role,
+                    content,
+                    timestamp: entry["timestamp"].as_str().map(str::to_string),
+                    input_tokens: entry["tokens"]["input"].as_u64(),
+                    output_tokens: entry["tokens"]["output"].as_u64(),
+                    cost: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Aider: one Markdown chat history per repository, `.aider.chat.history.md`,
+/// appended to by every run; user messages are `####` lines and tool output is
+/// quoted with `>`
+pub struct AiderAdapter;
+
+/// Name of Aider's chat history, next to the code
+const AIDER_HISTORY: &str = ".aider.chat.history.md";
+
+impl AgentAdapter for AiderAdapter {
+    /// Aider has no conversation IDs; it picks up the history of the repository
+    fn resume_args(&self, _conversation: Option<&str>) -> Vec<String> {
+        vec!["--restore-chat-history".to_string()]
+    }
+
+    fn transcript_dir(&self, _home: &Path, project: &Path) -> PathBuf {
+        project.to_path_buf()
+    }
+
+    fn is_transcript(&self, path: &Path) -> bool {
+        path.file_name().is_some_and(|name| name == AIDER_HISTORY)
+    }
+
+    fn parse_transcript(&self, content: &str) -> Vec<Value> {
+        let mut entries = Vec::new();
+        let mut started: Option<&str> = None;
+        let mut current: Option<(&str, Vec<&str>)> = None;
+
+        for line in content.lines() {
+            if let Some(at) = line.strip_prefix("# aider chat started at ") {
+                flush_aider_message(&mut entries, &mut current, started);
+                started = Some(at.trim());
+            } else if let Some(text) = line.strip_prefix("####") {
+                if !matches!(current, Some(("user", _))) {
+                    flush_aider_message(&mut entries, &mut current, started);
+                }
+                current
+                    .get_or_insert(("user", Vec::new()))
+                    .1
+                    .push(text.trim());
+            } else if let Some(note) = line.strip_prefix('>') {
+                flush_aider_message(&mut entries, &mut current, started);
+                // Usage is reported after the reply it belongs to
+                if let (Some((input, output, cost)), Some(reply)) = (
+                    aider_usage(note),
+                    entries
+                        .last_mut()
+                        .filter(|entry| entry["type"] == "assistant"),
+                ) {
+                    reply["input_tokens"] = input.into();
+                    reply["output_tokens"] = output.into();
+                    reply["cost"] = cost.into();
+                }
+            } else {
+                if matches!(current, Some(("user", _))) {
+                    flush_aider_message(&mut entries, &mut current, started);
+                }
+                current
+                    .get_or_insert(("assistant", Vec::new()))
+                    .1
+                    .push(line);
+            }
+        }
+        flush_aider_message(&mut entries, &mut current, started);
+        entries
+    }
+
+    fn messages(&self, entries: &[Value]) -> Vec<TranscriptMessage> {
+        entries
+            .iter()
+            .map(|entry| TranscriptMessage {
+                role: text_of(&entry["type"]),
+                content: text_of(&entry["content"]),
+                timestamp: entry["timestamp"].as_str().map(str::to_string),
+                input_tokens: entry["input_tokens"].as_u64(),
+                output_tokens: entry["output_tokens"].as_u64(),
+                cost: entry["cost"].as_f64(),
+            })
+            .collect()
+    }
+}
+
+fn flush_aider_message<'a>(
+    entries: &mut Vec<Value>,
+    current: &mut Option<(&'a str, Vec<&'a str>)>,
+    started: Option<&str>,
+) {
+    if let Some((role, lines)) = current.take() {
+        let content = lines.join("\n").trim().to_string();
+        if !content.is_empty() {
+            entries.push(serde_json::json!({
+                "type": role,
+                "content": content,
+                "timestamp": started,
+            }));
+        }
+    }
+}
+
+/// Tokens sent and received and the message's cost from an Aider usage line,
+/// `Tokens: 2.3k sent, 156 received. Cost: $0.0093 message, $0.15 session.`
+fn aider_usage(note: &str) -> Option<(u64, u64, Option<f64>)> {
+    let usage = note.trim().strip_prefix("Tokens:")?;
+    let (tokens, cost) = usage.split_once("Cost:").unwrap_or((usage, ""));
+    let count = |label: &str| {
+        tokens
+            .split(',')
+            .map(|part| part.trim().trim_end_matches('.'))
+            .find_map(|part| part.strip_suffix(label))
+            .and_then(|count| aider_count(count.trim()))
+    };
+    let cost = cost
+        .split(',')
+        .find_map(|part| part.trim().strip_suffix("message"))
+        .and_then(|cost| cost.trim().trim_start_matches('$').parse().ok());
+    Some((count("sent")?, count("received")?, cost))
+}
+
+/// "2.3k" as 2300
+fn aider_count(count: &str) -> Option<u64> {
+    let (number, scale) = match count.chars().last()? {
+        'k' => (&count[..count.len() - 1], 1e3),
+        'M' => (&count[..count.len() - 1], 1e6),
+        _ => (count, 1.0),
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .map(|number| (number * scale).round() as u64)
+}
+
+/// How an agent spells "the latest conversation" and "this conversation",
+/// configured as `[agents.<name>.resume]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResumeFlags {
+    /// Arguments that pick up the latest conversation in the working directory
+    pub latest: Vec<String>,
+    /// Arguments that pick up one conversation; `{id}` is replaced by its ID
+    pub by_id: Vec<String>,
+}
+
+impl Default for ResumeFlags {
+    fn default() -> Self {
+        ResumeFlags {
+            latest: vec!["--continue".to_string()],
+            by_id: vec!["--resume".to_string(), "{id}".to_string()],
+        }
+    }
+}
+
+impl ResumeFlags {
+    pub fn args(&self, conversation: Option<&str>) -> Vec<String> {
+        match conversation {
+            Some(id) => self
+                .by_id
+                .iter()
+                .map(|arg| arg.replace("{id}", id))
+                .collect(),
+            None => self.latest.clone(),
+        }
+    }
+}
+
+/// Arguments that resume a conversation of `agent`: its `[agents.<name>.resume]`
+/// flags if it has them, otherwise its adapter's; `None` if codemux doesn't know
+pub fn resume_args(
+    agent: &str,
+    config: Option<&AgentConfig>,
+    conversation: Option<&str>,
+) -> Option<Vec<String>> {
+    if let Some(flags) = config.and_then(|config| config.resume.as_ref()) {
+        return Some(flags.args(conversation));
+    }
+    let spec = config.and_then(|config| config.transcript.as_ref());
+    adapter_for(agent, spec).map(|adapter| adapter.resume_args(conversation))
+}
+
+/// The adapter for `agent`: its `[agents.<name>.transcript]` spec if it has
+/// one, otherwise the built-in one if codemux knows the agent
+pub fn adapter_for(agent: &str, spec: Option<&TranscriptSpec>) -> Option<Box<dyn AgentAdapter>> {
+    if let Some(spec) = spec {
+        return Some(Box::new(SpecAdapter(spec.clone())));
+    }
+    match agent.to_lowercase().as_str() {
+        "claude" => Some(Box::new(ClaudeAdapter)),
+        "gemini" => Some(Box::new(GeminiAdapter)),
+        "aider" => Some(Box::new(AiderAdapter)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_and_reads_gemini_conversations() {
+        let home = std::env::temp_dir().join(format!("codemux-gemini-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&home);
+        let gemini = adapter_for("Gemini", None).unwrap();
+        let project = Path::new("/home/me/api");
+
+        let dir = gemini.transcript_dir(&home, project);
+        assert!(dir.ends_with(
+            ".gemini/tmp/aa140980ea5b64948e6830717bfbf729c900f4224c44587a978d7471157def8e/chats"
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("logs.json"), "[]").unwrap();
+        std::fs::write(
+            dir.join("session-2025-09-20T14-03-1a2b3c4d.json"),
+            r#"{"sessionId":"1a2b3c4d-0000","messages":[
+                {"id":"1","type":"user","content":"fix the login form"},
+                {"id":"2","type":"gemini","content":"Done."}]}"#,
+        )
+        .unwrap();
+
+        let found = gemini.transcripts(&home, project);
+        assert_eq!(found.len(), 1);
+        let entries = gemini.parse_transcript(&std::fs::read_to_string(&found[0]).unwrap());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["type"], "gemini");
+        let messages = gemini.messages(&entries);
+        assert_eq!(messages[1].role, "assistant");
+        assert_eq!(messages[1].content, "Done.");
+
+        assert_eq!(gemini.resume_args(None), vec!["--resume", "latest"]);
+        assert_eq!(
+            adapter_for("claude", None)
+                .unwrap()
+                .resume_args(Some("abc")),
+            vec!["--resume", "abc"]
+        );
+        assert!(adapter_for("amp", None).is_none());
+        assert_eq!(resume_args("amp", None, None), None);
+
+        let codex = AgentConfig {
+            resume: Some(ResumeFlags {
+                latest: vec!["resume".into(), "--last".into()],
+                by_id: vec!["resume".into(), "{id}".into()],
+            }),
+            ..AgentConfig::default()
+        };
+        assert_eq!(
+            resume_args("codex", Some(&codex), Some("abc")).unwrap(),
+            vec!["resume", "abc"]
+        );
+        assert_eq!(
+            resume_args("codex", Some(&codex), None).unwrap(),
+            vec!["resume", "--last"]
+        );
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn reads_aider_chat_history() {
+        let aider = adapter_for("aider", None).unwrap();
+        let project = Path::new("/home/me/api");
+        assert_eq!(
+            aider.transcript_dir(Path::new("/home/me"), project),
+            project
+        );
+        assert!(aider.is_transcript(&project.join(".aider.chat.history.md")));
+        assert_eq!(aider.resume_args(None), vec!["--restore-chat-history"]);
+
+        let history = "\
+# aider chat started at 2025-09-20 14:03:11
+
+> Aider v0.58.1
+> Main model: claude-3-5-sonnet
+
+#### fix the login form
+#### it rejects valid emails
+
+The regex is missing a `+`. Here's the fix:
+
+login.py
+```python
+EMAIL = r\"[^@]+@[^@]+\"
+```
+
+> Tokens: 2.3k sent, 156 received. Cost: $0.0093 message, $0.15 session.
+> Applied edit to login.py
+> Commit 1a2b3c4 fix: Accept valid emails
+
+# aider chat started at 2025-09-21 09:00:02
+
+#### thanks
+";
+        let entries = aider.parse_transcript(history);
+        let messages = aider.messages(&entries);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].role, "user");
+        assert_eq!(
+            messages[0].content,
+            "fix the login form\nit rejects valid emails"
+        );
+        assert_eq!(
+            messages[0].timestamp.as_deref(),
+            Some("2025-09-20 14:03:11")
+        );
+        assert_eq!(messages[1].role, "assistant");
+        assert!(messages[1].content.starts_with("The regex is missing"));
+        assert!(messages[1].content.ends_with("```"));
+        assert_eq!(messages[1].input_tokens, Some(2300));
+        assert_eq!(messages[1].output_tokens, Some(156));
+        assert_eq!(messages[1].cost, Some(0.0093));
+        assert_eq!(messages[0].cost, None);
+        assert_eq!(messages[2].content, "thanks");
+        assert_eq!(
+            messages[2].timestamp.as_deref(),
+            Some("2025-09-21 09:00:02")
+        );
+    }
+}
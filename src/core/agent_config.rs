@@ -0,0 +1,318 @@
+//! Managed agent config files
+//!
+//! Files under `agent-config/<agent>/` in the codemux config directory, e.g.
+//! `agent-config/claude/CLAUDE.md` or `agent-config/claude/.claude/settings.json`,
+//! are templates for the same paths in every project. When a session of that
+//! agent starts, they are rendered and written into the project, so agents
+//! behave the same everywhere. Each agent's template set carries a version
+//! that goes up whenever its files change, and the data directory remembers
+//! which version each project got and what was written, so files edited in
+//! the project are left alone.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::dirs;
+
+/// Template versions, next to the agents' template directories
+const VERSIONS_FILE: &str = "versions.json";
+/// What was written where, in the data directory
+const STATE_FILE: &str = "agent-config.json";
+
+/// Writing agent config templates into projects, configured as `[agent_config]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfigSettings {
+    pub enabled: bool,
+    /// Replace project files that were changed after codemux wrote them, or
+    /// that were there before
+    pub overwrite: bool,
+}
+
+impl Default for AgentConfigSettings {
+    fn default() -> Self {
+        AgentConfigSettings {
+            enabled: true,
+            overwrite: false,
+        }
+    }
+}
+
+/// `agent-config` in the config directory, or in the data directory without one
+pub fn templates_root(data_dir: &Path) -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| data_dir.to_path_buf())
+        .join("agent-config")
+}
+
+/// The instructions file an agent reads, edited when `codemux agent-config edit` gets no file
+pub fn default_template_file(agent: &str) -> &'static str {
+    match agent {
+        "claude" => "CLAUDE.md",
+        "gemini" => "GEMINI.md",
+        _ => "AGENTS.md",
+    }
+}
+
+/// Path of a template, refusing anything that would end up outside the project
+pub fn template_path(root: &Path, agent: &str, file: &Path) -> Result<PathBuf> {
+    let inside = !file.as_os_str().is_empty()
+        && file
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if agent.is_empty() || agent.contains(['/', '\\']) || !inside {
+        return Err(anyhow!(
+            "Templates are paths relative to the project, got {} for {}",
+            file.display(),
+            agent
+        ));
+    }
+    Ok(root.join(agent).join(file))
+}
+
+/// Values filled in for `{{project}}`, `{{project_path}}` and `{{agent}}`
+pub struct TemplateVars<'a> {
+    pub project: &'a str,
+    pub project_path: &'a Path,
+    pub agent: &'a str,
+}
+
+impl TemplateVars<'_> {
+    fn render(&self, template: &str) -> String {
+        template
+            .replace("{{project}}", self.project)
+            .replace("{{project_path}}", &self.project_path.to_string_lossy())
+            .replace("{{agent}}", self.agent)
+    }
+}
+
+/// The template files of one agent, by path relative to the project
+pub fn load_templates(root: &Path, agent: &str) -> Result<BTreeMap<PathBuf, String>> {
+    let mut templates = BTreeMap::new();
+    let dir = root.join(agent);
+    if dir.is_dir() {
+        collect(&dir, &dir, &mut templates)?;
+    }
+    Ok(templates)
+}
+
+fn collect(base: &Path, dir: &Path, templates: &mut BTreeMap<PathBuf, String>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(base, &path, templates)?;
+        } else if let Ok(content) = std::fs::read_to_string(&path) {
+            let relative = path.strip_prefix(base).unwrap_or(&path).to_path_buf();
+            templates.insert(relative, content);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TemplateVersion {
+    version: u32,
+    fingerprint: String,
+    updated_at: String,
+}
+
+/// Version of the agent's current templates, bumped and saved if they changed
+/// since the version was last looked at
+pub fn template_version(root: &Path, agent: &str) -> Result<u32> {
+    let templates = load_templates(root, agent)?;
+    let path = root.join(VERSIONS_FILE);
+    let mut versions: BTreeMap<String, TemplateVersion> = read_json(&path)?;
+    let fingerprint = fingerprint_set(&templates);
+    let current = versions.entry(agent.to_string()).or_default();
+    if current.fingerprint != fingerprint {
+        current.version += 1;
+        current.fingerprint = fingerprint;
+        current.updated_at = chrono::Utc::now().to_rfc3339();
+        let version = current.version;
+        write_json(&path, &versions)?;
+        return Ok(version);
+    }
+    Ok(current.version)
+}
+
+/// Last sync of one agent's templates into one project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncedProject {
+    pub version: u32,
+    pub synced_at: String,
+    /// Fingerprints of the files as written, to notice edits made in the project
+    files: BTreeMap<PathBuf, String>,
+}
+
+/// Synced projects by path, then agent
+pub type SyncState = BTreeMap<PathBuf, BTreeMap<String, SyncedProject>>;
+
+pub fn load_sync_state(data_dir: &Path) -> Result<SyncState> {
+    read_json(&data_dir.join(STATE_FILE))
+}
+
+/// What a sync did to the project
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub version: u32,
+    pub written: Vec<PathBuf>,
+    /// Files left as they were because they were edited in the project
+    pub kept: Vec<PathBuf>,
+}
+
+/// Write the agent's templates into `vars.project_path`
+pub fn sync(
+    root: &Path,
+    data_dir: &Path,
+    vars: &TemplateVars,
+    settings: &AgentConfigSettings,
+) -> Result<SyncReport> {
+    let templates = load_templates(root, vars.agent)?;
+    if templates.is_empty() {
+        return Ok(SyncReport::default());
+    }
+    let version = template_version(root, vars.agent)?;
+    let state_path = data_dir.join(STATE_FILE);
+    let mut state: SyncState = read_json(&state_path)?;
+    let synced = state
+        .entry(vars.project_path.to_path_buf())
+        .or_default()
+        .entry(vars.agent.to_string())
+        .or_default();
+
+    let mut report = SyncReport {
+        version,
+        ..Default::default()
+    };
+    for (relative, template) in templates {
+        let content = vars.render(&template);
+        let target = vars.project_path.join(&relative);
+        let written = fingerprint(&content);
+        match std::fs::read_to_string(&target) {
+            Ok(existing) if existing == content => {}
+            Ok(existing)
+                if !settings.overwrite
+                    && synced.files.get(&relative) != Some(&fingerprint(&existing)) =>
+            {
+                report.kept.push(relative);
+                continue;
+            }
+            _ => {
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&target, &content)?;
+                report.written.push(relative.clone());
+            }
+        }
+        synced.files.insert(relative, written);
+    }
+    synced.version = version;
+    synced.synced_at = chrono::Utc::now().to_rfc3339();
+    write_json(&state_path, &state)?;
+    Ok(report)
+}
+
+fn fingerprint_set(templates: &BTreeMap<PathBuf, String>) -> String {
+    let mut all = String::new();
+    for (path, content) in templates {
+        all.push_str(&path.to_string_lossy());
+        all.push('\0');
+        all.push_str(&fingerprint(content));
+        all.push('\0');
+    }
+    fingerprint(&all)
+}
+
+/// FNV-1a, stable across builds unlike `DefaultHasher`
+fn fingerprint(content: &str) -> String {
+    let hash = content
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+fn read_json<T: serde::de::DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_templates_and_keeps_files_edited_in_the_project() {
+        let base =
+            std::env::temp_dir().join(format!("codemux-agent-config-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let (root, data_dir, project) =
+            (base.join("templates"), base.join("data"), base.join("api"));
+        std::fs::create_dir_all(&project).unwrap();
+        let settings = AgentConfigSettings::default();
+        let vars = TemplateVars {
+            project: "api",
+            project_path: &project,
+            agent: "claude",
+        };
+
+        assert_eq!(
+            sync(&root, &data_dir, &vars, &settings)
+                .unwrap()
+                .written
+                .len(),
+            0
+        );
+
+        let claude_md = template_path(&root, "claude", Path::new("CLAUDE.md")).unwrap();
+        let settings_json =
+            template_path(&root, "claude", Path::new(".claude/settings.json")).unwrap();
+        std::fs::create_dir_all(settings_json.parent().unwrap()).unwrap();
+        std::fs::write(&claude_md, "# {{project}}\nRun tests before committing.\n").unwrap();
+        std::fs::write(&settings_json, "{}").unwrap();
+        assert!(template_path(&root, "claude", Path::new("../escape")).is_err());
+
+        let report = sync(&root, &data_dir, &vars, &settings).unwrap();
+        assert_eq!(report.version, 1);
+        assert_eq!(report.written.len(), 2);
+        let written = std::fs::read_to_string(project.join("CLAUDE.md")).unwrap();
+        assert_eq!(written, "# api\nRun tests before committing.\n");
+
+        // Edited in the project, then the template changes
+        std::fs::write(project.join("CLAUDE.md"), "# api\nMy own rules\n").unwrap();
+        std::fs::write(&claude_md, "# {{project}}\nRun all tests.\n").unwrap();
+        std::fs::write(&settings_json, "{\"model\": \"opus\"}").unwrap();
+        let report = sync(&root, &data_dir, &vars, &settings).unwrap();
+        assert_eq!(report.version, 2);
+        assert_eq!(report.kept, vec![PathBuf::from("CLAUDE.md")]);
+        assert_eq!(report.written, vec![PathBuf::from(".claude/settings.json")]);
+        assert_eq!(template_version(&root, "claude").unwrap(), 2);
+
+        let overwrite = AgentConfigSettings {
+            overwrite: true,
+            ..Default::default()
+        };
+        sync(&root, &data_dir, &vars, &overwrite).unwrap();
+        let written = std::fs::read_to_string(project.join("CLAUDE.md")).unwrap();
+        assert_eq!(written, "# api\nRun all tests.\n");
+        let state = load_sync_state(&data_dir).unwrap();
+        assert_eq!(state[&project]["claude"].version, 2);
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,477 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use ts_rs::TS;
+
+use super::agent_adapter::ResumeFlags;
+use super::transcript::TranscriptSpec;
+
+/// How long a passing version check is trusted before the agent is asked again
+pub const VERSION_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Longest wait for `<agent> --version`; some agents start an interpreter first
+const VERSION_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What to do when an agent's installed version doesn't match its pin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum OnMismatch {
+    /// Log a warning and start the session anyway
+    #[default]
+    Warn,
+    /// Refuse to start sessions until the agent is upgraded or downgraded
+    Block,
+}
+
+/// Per-agent settings, configured as `[agents.<name>]`
+///
+/// An agent with a table here can be run without being in `[whitelist]`, which
+/// is how agents codemux doesn't know, e.g. `codex` or a local LLM CLI, are added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AgentConfig {
+    /// Program to run, when it isn't called like the agent, e.g. `"npx"` or a full path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Arguments passed before the session's own, e.g. `["@sourcegraph/amp"]` for `npx`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// How the agent spells `--continue` and `--resume <id>`, as `[agents.<name>.resume]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume: Option<ResumeFlags>,
+    /// Regexes matching the agent's own approval prompts, on top of the built-in ones
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub prompt_patterns: Vec<String>,
+    /// Environment variables the agent can't start without, e.g. `OPENAI_API_KEY`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required_env: Vec<String>,
+    /// Acceptable versions, such as `">=0.50, <0.60"`, `"^1.2"` or `"1.0.*"`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub on_mismatch: OnMismatch,
+    /// Arguments that make the agent print its version, after `args`
+    pub version_args: Vec<String>,
+    /// Running sessions of this agent allowed at once, on top of `[limits]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_sessions: Option<usize>,
+    /// Where an agent codemux doesn't know keeps its transcripts, as `[agents.<name>.transcript]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<TranscriptSpec>,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        AgentConfig {
+            command: None,
+            args: Vec::new(),
+            resume: None,
+            prompt_patterns: Vec::new(),
+            required_env: Vec::new(),
+            version: None,
+            on_mismatch: OnMismatch::default(),
+            version_args: vec!["--version".to_string()],
+            max_sessions: None,
+            transcript: None,
+        }
+    }
+}
+
+impl AgentConfig {
+    /// `prompt_patterns`, compiled
+    pub fn approval_patterns(&self) -> Result<Vec<regex::Regex>> {
+        self.prompt_patterns
+            .iter()
+            .map(|pattern| {
+                regex::Regex::new(pattern)
+                    .map_err(|e| anyhow!("invalid prompt pattern `{}`: {}", pattern, e))
+            })
+            .collect()
+    }
+}
+
+/// A `major.minor.patch` version; missing parts count as 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    /// First version number in an agent's `--version` output, e.g. "aider 0.58.1"
+    pub fn find_in(output: &str) -> Option<Version> {
+        let pattern = regex::Regex::new(r"(\d+)\.(\d+)(?:\.(\d+))?").expect("valid regex");
+        let captures = pattern.captures(output)?;
+        let part = |i: usize| {
+            captures
+                .get(i)
+                .and_then(|m| m.as_str().parse().ok())
+                .unwrap_or(0)
+        };
+        Some(Version {
+            major: part(1),
+            minor: part(2),
+            patch: part(3),
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Caret,
+    Tilde,
+}
+
+/// One comparator of a requirement; `parts` holds only the components written out
+#[derive(Debug, Clone, PartialEq)]
+struct Comparator {
+    op: Op,
+    parts: Vec<u64>,
+}
+
+impl Comparator {
+    fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| spec.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((Op::Exact, spec));
+
+        let mut parts = Vec::new();
+        for part in rest.trim().trim_start_matches('v').split('.') {
+            if matches!(part, "*" | "x" | "X") {
+                break;
+            }
+            parts.push(
+                part.parse()
+                    .map_err(|_| anyhow!("invalid version `{}`", spec))?,
+            );
+        }
+        if parts.is_empty() || parts.len() > 3 {
+            return Err(anyhow!("invalid version `{}`", spec));
+        }
+        Ok(Comparator { op, parts })
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let actual = [version.major, version.minor, version.patch];
+        let n = self.parts.len();
+        // Compare only the components the comparator spells out, so "1.2" covers 1.2.x
+        let prefix = actual[..n].cmp(&self.parts[..]);
+        match self.op {
+            Op::Exact => prefix == Ordering::Equal,
+            Op::Greater => prefix == Ordering::Greater,
+            Op::GreaterEq => prefix != Ordering::Less,
+            Op::Less => prefix == Ordering::Less,
+            Op::LessEq => prefix != Ordering::Greater,
+            Op::Tilde => {
+                // Patch updates only (minor updates when only the major is given)
+                let fixed = n.min(2);
+                prefix != Ordering::Less && actual[..fixed] == self.parts[..fixed]
+            }
+            Op::Caret => {
+                // Everything up to the first non-zero component is fixed
+                let fixed = self
+                    .parts
+                    .iter()
+                    .position(|part| *part != 0)
+                    .map_or(n, |i| i + 1);
+                prefix != Ordering::Less && actual[..fixed] == self.parts[..fixed]
+            }
+        }
+    }
+}
+
+/// A comma-separated list of comparators that must all match
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let comparators = spec
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(VersionReq { comparators })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        self.comparators.iter().all(|c| c.matches(version))
+    }
+}
+
+/// Result of comparing an agent's installed version with its pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum VersionCheck {
+    /// Installed version satisfies the pin
+    Ok,
+    /// No pin configured
+    Unpinned,
+    /// Installed version is outside the pin
+    Mismatch,
+    /// The agent isn't on the server's `PATH`
+    NotInstalled,
+    /// The agent ran but no version could be read, or the pin is invalid
+    Unknown,
+}
+
+/// Version check of one agent, served by `GET /api/agents`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AgentVersionStatus {
+    pub agent: String,
+    /// Installed version, when it could be read
+    pub installed: Option<String>,
+    /// Pinned version requirement from `[agents.<name>]`
+    pub pin: Option<String>,
+    pub on_mismatch: OnMismatch,
+    pub status: VersionCheck,
+    /// Why the version is unknown
+    pub detail: Option<String>,
+    #[ts(type = "string")]
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AgentVersionStatus {
+    /// Whether sessions of this agent must not start
+    pub fn blocks(&self) -> bool {
+        self.status == VersionCheck::Mismatch && self.on_mismatch == OnMismatch::Block
+    }
+
+    /// One-line description such as "aider 0.61.0 does not match pin >=0.50, <0.60"
+    pub fn summary(&self) -> String {
+        let installed = self.installed.as_deref().unwrap_or("?");
+        match self.status {
+            VersionCheck::Ok => format!(
+                "{} {} (pinned {})",
+                self.agent,
+                installed,
+                self.pin.as_deref().unwrap_or_default()
+            ),
+            VersionCheck::Unpinned => format!("{} {}", self.agent, installed),
+            VersionCheck::Mismatch => format!(
+                "{} {} does not match pin {}",
+                self.agent,
+                installed,
+                self.pin.as_deref().unwrap_or_default()
+            ),
+            VersionCheck::NotInstalled => format!("{} is not installed", self.agent),
+            VersionCheck::Unknown => format!(
+                "{}: version unknown ({})",
+                self.agent,
+                self.detail.as_deref().unwrap_or("no version in output")
+            ),
+        }
+    }
+}
+
+/// Run the agent's version command and compare the result with its pin
+pub async fn check_agent_version(agent: &str, config: &AgentConfig) -> AgentVersionStatus {
+    let mut status = AgentVersionStatus {
+        agent: agent.to_string(),
+        installed: None,
+        pin: config.version.clone(),
+        on_mismatch: config.on_mismatch,
+        status: VersionCheck::Unknown,
+        detail: None,
+        checked_at: chrono::Utc::now(),
+    };
+
+    let program = config.command.as_deref().unwrap_or(agent);
+    let args: Vec<String> = config
+        .args
+        .iter()
+        .chain(&config.version_args)
+        .cloned()
+        .collect();
+    let installed = match read_installed_version(program, &args).await {
+        Ok(version) => version,
+        Err(e) => {
+            let not_found = e
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound);
+            if not_found {
+                status.status = VersionCheck::NotInstalled;
+            } else {
+                status.detail = Some(e.to_string());
+            }
+            return status;
+        }
+    };
+    status.installed = Some(installed.to_string());
+
+    status.status = match config.version.as_deref().map(VersionReq::parse) {
+        None => VersionCheck::Unpinned,
+        Some(Ok(req)) if req.matches(&installed) => VersionCheck::Ok,
+        Some(Ok(_)) => VersionCheck::Mismatch,
+        Some(Err(e)) => {
+            status.detail = Some(format!("invalid pin: {}", e));
+            VersionCheck::Unknown
+        }
+    };
+    status
+}
+
+async fn read_installed_version(program: &str, args: &[String]) -> Result<Version> {
+    let output = tokio::time::timeout(
+        VERSION_COMMAND_TIMEOUT,
+        tokio::process::Command::new(program)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow!("`{} {}` timed out", program, args.join(" ")))??;
+
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Version::find_in(&text)
+        .ok_or_else(|| anyhow!("no version in `{} {}` output", program, args.join(" ")))
+}
+
+/// Cached version checks for the server's agents
+///
+/// Passing checks are reused for [`VERSION_CHECK_INTERVAL`]; mismatches and
+/// unknown versions are checked again each time, so fixing the install takes
+/// effect on the next session.
+#[derive(Clone)]
+pub struct AgentVersionChecker {
+    agents: Arc<BTreeMap<String, AgentConfig>>,
+    cache: Arc<Mutex<HashMap<String, AgentVersionStatus>>>,
+}
+
+impl AgentVersionChecker {
+    pub fn new(agents: BTreeMap<String, AgentConfig>) -> Self {
+        AgentVersionChecker {
+            agents: Arc::new(agents),
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Agents with a version pin
+    pub fn pinned(&self) -> impl Iterator<Item = &str> {
+        self.agents
+            .iter()
+            .filter(|(_, config)| config.version.is_some())
+            .map(|(name, _)| name.as_str())
+    }
+
+    pub async fn check(&self, agent: &str) -> AgentVersionStatus {
+        if let Some(cached) = self.cache.lock().await.get(agent) {
+            let age = chrono::Utc::now() - cached.checked_at;
+            let fresh = age.to_std().is_ok_and(|age| age < VERSION_CHECK_INTERVAL);
+            if fresh && matches!(cached.status, VersionCheck::Ok | VersionCheck::Unpinned) {
+                return cached.clone();
+            }
+        }
+
+        let default_config = AgentConfig::default();
+        let config = self.agents.get(agent).unwrap_or(&default_config);
+        let status = check_agent_version(agent, config).await;
+        self.cache
+            .lock()
+            .await
+            .insert(agent.to_string(), status.clone());
+        status
+    }
+
+    /// Check a pinned agent before starting a session, failing if the pin blocks it
+    pub async fn ensure_allowed(&self, agent: &str) -> Result<()> {
+        if !self.pinned().any(|pinned| pinned == agent) {
+            return Ok(());
+        }
+        let status = self.check(agent).await;
+        if status.blocks() {
+            return Err(anyhow!(
+                "{} (on_mismatch = \"block\" in [agents.{}])",
+                status.summary(),
+                agent
+            ));
+        }
+        if matches!(
+            status.status,
+            VersionCheck::Mismatch | VersionCheck::Unknown
+        ) {
+            tracing::warn!("Agent version check: {}", status.summary());
+        }
+        Ok(())
+    }
+
+    /// Re-check every pinned agent once a day, logging mismatches
+    pub fn spawn_daily_check(&self) {
+        let checker = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(VERSION_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                let pinned: Vec<String> = checker.pinned().map(str::to_string).collect();
+                for agent in pinned {
+                    let status = checker.check(&agent).await;
+                    if status.status == VersionCheck::Mismatch {
+                        tracing::warn!("Agent version check: {}", status.summary());
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_requirements() {
+        let version = |s: &str| Version::find_in(s).unwrap();
+        let req = |s: &str| VersionReq::parse(s).unwrap();
+
+        assert_eq!(version("aider v0.58.1\n").to_string(), "0.58.1");
+        assert_eq!(version("1.2 (Claude Code)").to_string(), "1.2.0");
+        assert!(Version::find_in("no version here").is_none());
+
+        assert!(req(">=0.50, <0.60").matches(&version("0.58.1")));
+        assert!(!req(">=0.50, <0.60").matches(&version("0.60.0")));
+        assert!(req("1.0.*").matches(&version("1.0.44")));
+        assert!(!req("1.0").matches(&version("1.1.0")));
+        assert!(req("^1.2").matches(&version("1.9.0")));
+        assert!(!req("^0.58").matches(&version("0.59.0")));
+        assert!(req("~1.2.3").matches(&version("1.2.9")));
+        assert!(!req("~1.2.3").matches(&version("1.3.0")));
+        assert!(!req("<=1.2").matches(&version("1.3.0")));
+        assert!(req("<=1.2").matches(&version("1.2.7")));
+        assert!(VersionReq::parse(">=one").is_err());
+    }
+}
@@ -0,0 +1,334 @@
+//! Named API tokens with a scope and an expiry, for CI and other scripts
+//!
+//! Unlike the server's own token, which can do anything, each is limited to
+//! reading, to working with sessions, or to everything (`admin`), and can be
+//! revoked on its own. Only a hash of each token is kept, in
+//! `api_tokens.json` in the data directory, so the secret is shown once when
+//! the token is created.
+//!
+//! Share links carry tokens too, limited to one session as well: `read` ones
+//! watch it, `sessions:write` ones can also type into it.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use ts_rs::TS;
+
+use super::auth::{generate_token, write_token};
+
+/// Name of the file inside the data directory holding the tokens
+pub const API_TOKENS_FILE_NAME: &str = "api_tokens.json";
+/// Start of every API token, telling them apart from the server's token
+pub const API_TOKEN_PREFIX: &str = "cmx_";
+
+/// What an API token may do; each scope includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum TokenScope {
+    /// Look at sessions, projects and the rest of the API without changing anything
+    #[serde(rename = "read")]
+    Read,
+    /// Also start, stop and attach to sessions
+    #[serde(rename = "sessions:write")]
+    SessionsWrite,
+    /// Also manage tokens and shut the server down
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl TokenScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenScope::Read => "read",
+            TokenScope::SessionsWrite => "sessions:write",
+            TokenScope::Admin => "admin",
+        }
+    }
+}
+
+impl std::str::FromStr for TokenScope {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "read" => Ok(TokenScope::Read),
+            "sessions:write" => Ok(TokenScope::SessionsWrite),
+            "admin" => Ok(TokenScope::Admin),
+            _ => Err(format!(
+                "unknown scope {:?}; use read, sessions:write or admin",
+                value
+            )),
+        }
+    }
+}
+
+/// An API token, without its secret
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scope: TokenScope,
+    /// RFC 3339
+    pub created_at: String,
+    /// RFC 3339; `None` for tokens that don't expire
+    pub expires_at: Option<String>,
+    /// The only session the token opens, for share links
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+impl ApiToken {
+    pub fn expired(&self) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at <= chrono::Utc::now())
+    }
+}
+
+/// A token just created, the only time its secret is known
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NewApiToken {
+    #[serde(flatten)]
+    pub token: ApiToken,
+    /// Send as a Bearer token, like the server's
+    pub secret: String,
+}
+
+/// Body of `POST /api/tokens`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scope: TokenScope,
+    /// Expire this many seconds from now; never without
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+}
+
+/// Body of `POST /api/sessions/:id/share`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ShareSessionRequest {
+    /// Let whoever opens the link type into the session, not just watch it
+    #[ts(as = "Option<bool>", optional)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interactive: bool,
+    /// Expire this many seconds from now; never without
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredToken {
+    #[serde(flatten)]
+    token: ApiToken,
+    /// Hex SHA-256 of the secret
+    secret_hash: String,
+}
+
+/// The tokens of a server, kept in memory and written through to the file
+pub struct ApiTokenStore {
+    file: PathBuf,
+    tokens: Mutex<Vec<StoredToken>>,
+}
+
+impl ApiTokenStore {
+    pub fn open(data_dir: &Path) -> Self {
+        let file = data_dir.join(API_TOKENS_FILE_NAME);
+        let tokens = std::fs::read_to_string(&file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        ApiTokenStore {
+            file,
+            tokens: Mutex::new(tokens),
+        }
+    }
+
+    /// Tokens, oldest first
+    pub fn list(&self) -> Vec<ApiToken> {
+        self.lock()
+            .iter()
+            .map(|stored| stored.token.clone())
+            .collect()
+    }
+
+    /// Create a token; names of tokens that haven't expired must be unique
+    pub fn create(
+        &self,
+        name: &str,
+        scope: TokenScope,
+        ttl: Option<Duration>,
+    ) -> Result<NewApiToken> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("The token needs a name");
+        }
+        self.insert(name, scope, ttl, None)
+    }
+
+    /// Create a token for a link to `session_id` that opens nothing else
+    pub fn share(
+        &self,
+        session_id: &str,
+        interactive: bool,
+        ttl: Option<Duration>,
+    ) -> Result<NewApiToken> {
+        let scope = if interactive {
+            TokenScope::SessionsWrite
+        } else {
+            TokenScope::Read
+        };
+        let short_id = &session_id[..8.min(session_id.len())];
+        let name = format!("share-{}-{}", short_id, &generate_token()[..8]);
+        self.insert(&name, scope, ttl, Some(session_id.to_string()))
+    }
+
+    /// Refuse a TTL too long to give an expiry date, instead of never expiring
+    pub fn check_ttl(ttl: Duration) -> Result<()> {
+        expiry(chrono::Utc::now(), ttl).map(|_| ())
+    }
+
+    fn insert(
+        &self,
+        name: &str,
+        scope: TokenScope,
+        ttl: Option<Duration>,
+        session_id: Option<String>,
+    ) -> Result<NewApiToken> {
+        let mut tokens = self.lock();
+        tokens.retain(|stored| !stored.token.expired());
+        if tokens.iter().any(|stored| stored.token.name == name) {
+            bail!("There already is a token named {:?}", name);
+        }
+
+        let now = chrono::Utc::now();
+        let expires_at = ttl
+            .map(|ttl| expiry(now, ttl))
+            .transpose()?
+            .map(|at| at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+        let token = ApiToken {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            scope,
+            created_at: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            expires_at,
+            session_id,
+        };
+        let secret = format!("{}{}", API_TOKEN_PREFIX, generate_token());
+        tokens.push(StoredToken {
+            token: token.clone(),
+            secret_hash: hash(&secret),
+        });
+        self.save(&tokens)?;
+        Ok(NewApiToken { token, secret })
+    }
+
+    /// Revoke the token with this ID or name; `None` if there is none
+    pub fn revoke(&self, id_or_name: &str) -> Result<Option<ApiToken>> {
+        let mut tokens = self.lock();
+        let Some(index) = tokens
+            .iter()
+            .position(|stored| stored.token.id == id_or_name)
+            .or_else(|| {
+                tokens
+                    .iter()
+                    .position(|stored| stored.token.name == id_or_name)
+            })
+        else {
+            return Ok(None);
+        };
+        let revoked = tokens.remove(index);
+        self.save(&tokens)?;
+        Ok(Some(revoked.token))
+    }
+
+    /// The token a secret belongs to, unless it expired or was revoked
+    pub fn authenticate(&self, secret: &str) -> Option<ApiToken> {
+        if !secret.starts_with(API_TOKEN_PREFIX) {
+            return None;
+        }
+        // Comparing hashes doesn't tell how much of a guess was right
+        let secret_hash = hash(secret);
+        self.lock()
+            .iter()
+            .find(|stored| stored.secret_hash == secret_hash)
+            .map(|stored| stored.token.clone())
+            .filter(|token| !token.expired())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StoredToken>> {
+        self.tokens.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn save(&self, tokens: &[StoredToken]) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_token(&self.file, &serde_json::to_string_pretty(tokens)?)
+    }
+}
+
+fn hash(secret: &str) -> String {
+    format!("{:x}", Sha256::digest(secret.as_bytes()))
+}
+
+fn expiry(
+    now: chrono::DateTime<chrono::Utc>,
+    ttl: Duration,
+) -> Result<chrono::DateTime<chrono::Utc>> {
+    chrono::Duration::from_std(ttl)
+        .ok()
+        .and_then(|ttl| now.checked_add_signed(ttl))
+        .ok_or_else(|| anyhow!("A TTL of {} seconds is too long", ttl.as_secs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authenticates_tokens_until_they_expire_or_are_revoked() {
+        let data_dir =
+            std::env::temp_dir().join(format!("codemux-api-tokens-{}", std::process::id()));
+        let store = ApiTokenStore::open(&data_dir);
+
+        let forever = Duration::from_secs(u64::MAX);
+        assert!(store
+            .create("forever", TokenScope::Read, Some(forever))
+            .is_err());
+        assert!(store.share("session", false, Some(forever)).is_err());
+        assert!(store.list().is_empty());
+
+        let ci = store.create("ci", TokenScope::Read, None).unwrap();
+        assert!(ci.secret.starts_with(API_TOKEN_PREFIX));
+        assert!(store.create("ci", TokenScope::Admin, None).is_err());
+        let expired = store
+            .create("old", TokenScope::Admin, Some(Duration::ZERO))
+            .unwrap();
+
+        // Kept across restarts, without the secrets
+        let store = ApiTokenStore::open(&data_dir);
+        let saved = std::fs::read_to_string(data_dir.join(API_TOKENS_FILE_NAME)).unwrap();
+        assert!(!saved.contains(&ci.secret));
+        assert_eq!(store.authenticate(&ci.secret), Some(ci.token.clone()));
+        assert_eq!(store.authenticate(&expired.secret), None);
+        assert_eq!(store.authenticate("cmx_guess"), None);
+        assert!(TokenScope::SessionsWrite > TokenScope::Read);
+        assert_eq!("sessions:write".parse(), Ok(TokenScope::SessionsWrite));
+
+        assert_eq!(store.revoke("ci").unwrap(), Some(ci.token));
+        assert_eq!(store.authenticate(&ci.secret), None);
+        assert_eq!(store.revoke("ci").unwrap(), None);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,103 @@
+//! Token the web server requires on its API and WebSockets
+//!
+//! Unless `[web] token` fixes one, the server generates a token each time it
+//! starts and writes it to the runtime directory, readable only by the user.
+//! Clients on the same machine read it from there, so only that user's
+//! processes can reach the sessions. A server taking over with
+//! `codemux server upgrade` keeps the token of the one it replaces.
+
+use anyhow::Result;
+use std::path::Path;
+
+use super::config::Config;
+use super::credentials::CredentialStore;
+
+/// Name of the token file inside the runtime directory
+pub const TOKEN_FILE_NAME: &str = "auth.token";
+
+/// A new random token
+pub fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+/// Token a starting server requires, `None` when `[web] auth` is off
+///
+/// With `reuse`, the token of the running server is kept if it has one.
+pub fn server_token(config: &Config, reuse: bool) -> Option<String> {
+    if !config.web.auth {
+        return None;
+    }
+    if let Some(token) = &config.web.token {
+        return Some(token.clone());
+    }
+    let existing = reuse
+        .then(|| read_token(&config.server.token_file()))
+        .flatten();
+    Some(existing.unwrap_or_else(generate_token))
+}
+
+/// Token for clients of `server` to send: `[client] token` when set, for the
+/// local server the one from the config or its token file, otherwise the last
+/// `codemux login`'s
+pub fn client_token(config: &Config, server: &str) -> Option<String> {
+    if let Some(token) = &config.client.token {
+        return Some(token.clone());
+    }
+    let local = config.client.server_url.is_none();
+    if local && !config.web.auth {
+        return None;
+    }
+    let local_token = || {
+        config
+            .web
+            .token
+            .clone()
+            .or_else(|| read_token(&config.server.token_file()))
+    };
+    local.then(local_token).flatten().or_else(|| {
+        CredentialStore::new(&config.server.data_dir)
+            .get(server)
+            .map(|credential| credential.token)
+    })
+}
+
+fn read_token(path: &Path) -> Option<String> {
+    let token = std::fs::read_to_string(path).ok()?;
+    let token = token.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Write the token file, readable by the user only
+pub fn write_token(path: &Path, token: &str) -> Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // An existing file keeps its mode, so tighten it in case it was created otherwise
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::io::Write::write_all(&mut file, token.as_bytes())?;
+    Ok(())
+}
+
+/// Compare a token sent by a client, taking as long whatever it has in common
+/// with the real one
+pub fn token_matches(sent: &str, token: &str) -> bool {
+    sent.len() == token.len()
+        && sent
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
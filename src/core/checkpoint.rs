@@ -0,0 +1,360 @@
+//! Git snapshots of a session's working tree
+//!
+//! A snapshot is a commit of everything in the working tree, untracked files
+//! included and ignored ones left out, parented on `HEAD`. It's built in a
+//! throwaway index, so the repository's index, branches and files are left
+//! alone. Snapshots that should survive `git gc` are pinned under
+//! `refs/codemux/checkpoints/`, a session's under `<session-id>/`.
+//!
+//! With `[checkpoints]` on, the server pins one after each agent turn, see
+//! [`TurnTracker`], and `codemux checkpoints restore` puts one back.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// Where pinned checkpoints live in the repository
+pub const CHECKPOINT_REFS: &str = "refs/codemux/checkpoints";
+
+/// Checkpoints of a session's working tree after each agent turn,
+/// configured as `[checkpoints]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckpointsConfig {
+    pub enabled: bool,
+    /// Seconds without output after which the agent's turn counts as over
+    pub settle_secs: u64,
+    /// Turn checkpoints kept per session, oldest dropped first
+    pub keep: usize,
+}
+
+impl Default for CheckpointsConfig {
+    fn default() -> Self {
+        CheckpointsConfig {
+            enabled: false,
+            settle_secs: 5,
+            keep: 50,
+        }
+    }
+}
+
+impl CheckpointsConfig {
+    pub fn settle(&self) -> Duration {
+        Duration::from_secs(self.settle_secs.max(1))
+    }
+}
+
+/// A pinned checkpoint of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Checkpoint {
+    /// Name under the session's refs, e.g. `turn-3` or `safety-20250902-143000`
+    pub name: String,
+    pub reference: String,
+    pub commit: String,
+    /// When the snapshot was taken, as RFC 3339
+    pub created_at: String,
+    pub message: String,
+}
+
+/// A checkpoint put back into a session's working tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RestoredCheckpoint {
+    pub restored: Checkpoint,
+    /// Checkpoint of the files as they were before, to undo the restore with;
+    /// unset outside a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub backup: Option<String>,
+}
+
+/// Tells when an agent is done with a turn: it wrote something and then went
+/// quiet, or stopped mid-way for approval
+#[derive(Debug)]
+pub struct TurnTracker {
+    settle: Duration,
+    /// Last output of the turn in progress, `None` between turns
+    last_output: Option<Instant>,
+}
+
+impl TurnTracker {
+    pub fn new(settle: Duration) -> Self {
+        TurnTracker {
+            settle,
+            last_output: None,
+        }
+    }
+
+    /// The screen changed at `now`
+    pub fn output(&mut self, now: Instant) {
+        self.last_output = Some(now);
+    }
+
+    /// Whether the turn in progress has been quiet long enough by `now` to be
+    /// over; each turn is reported once
+    pub fn settled(&mut self, now: Instant) -> bool {
+        match self.last_output {
+            Some(at) if now.duration_since(at) >= self.settle => {
+                self.last_output = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// End the turn in progress early, e.g. at an approval prompt or when the
+    /// session ends; false if there was none
+    pub fn interrupt(&mut self) -> bool {
+        self.last_output.take().is_some()
+    }
+}
+
+/// Snapshot the working tree at `dir`, or `None` if it isn't in a git repository
+pub fn snapshot(dir: &Path, message: &str) -> Result<Option<String>> {
+    let Some(index) = scratch_index(dir) else {
+        return Ok(None);
+    };
+    let result = commit_working_tree(dir, &index, message);
+    let _ = std::fs::remove_file(&index);
+    result.map(Some)
+}
+
+/// Keep `commit` reachable as `refs/codemux/checkpoints/<name>`, returning the ref
+pub fn pin(dir: &Path, name: &str, commit: &str) -> Result<String> {
+    let reference = format!("{}/{}", CHECKPOINT_REFS, name);
+    git(dir, None, &["update-ref", &reference, commit])?;
+    Ok(reference)
+}
+
+/// Pinned checkpoints of the repository at `dir` as (ref, commit); none
+/// outside a repository
+pub fn pinned(dir: &Path) -> Vec<(String, String)> {
+    let format = "--format=%(refname) %(objectname)";
+    let Ok(refs) = git(dir, None, &["for-each-ref", format, CHECKPOINT_REFS]) else {
+        return Vec::new();
+    };
+    refs.lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(reference, commit)| (reference.to_string(), commit.to_string()))
+        .collect()
+}
+
+/// Write the pinned checkpoints with everything they need to a git bundle at
+/// `path`, which `git fetch <path> 'refs/codemux/*:refs/codemux/*'` reads back
+pub fn bundle(dir: &Path, path: &Path) -> Result<()> {
+    let path = path.to_string_lossy();
+    let glob = format!("--glob={}", CHECKPOINT_REFS);
+    git(dir, None, &["bundle", "create", "-q", &path, &glob])?;
+    Ok(())
+}
+
+/// Drop a pinned checkpoint; its commit goes with the next `git gc`
+pub fn unpin(dir: &Path, reference: &str) -> Result<()> {
+    git(dir, None, &["update-ref", "-d", reference])?;
+    Ok(())
+}
+
+/// Checkpoints pinned for a session in the repository at `dir`, oldest first;
+/// none outside a repository
+pub fn session_checkpoints(dir: &Path, session_id: &str) -> Vec<Checkpoint> {
+    let prefix = format!("{}/{}/", CHECKPOINT_REFS, session_id);
+    let format =
+        "--format=%(refname)%09%(objectname)%09%(creatordate:iso-strict)%09%(contents:subject)";
+    let Ok(refs) = git(
+        dir,
+        None,
+        &["for-each-ref", "--sort=creatordate", format, &prefix],
+    ) else {
+        return Vec::new();
+    };
+    refs.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let reference = fields.next()?;
+            Some(Checkpoint {
+                name: reference.strip_prefix(&prefix)?.to_string(),
+                reference: reference.to_string(),
+                commit: fields.next()?.to_string(),
+                created_at: fields.next()?.to_string(),
+                message: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Whether two snapshots hold the same files
+pub fn same_tree(dir: &Path, a: &str, b: &str) -> bool {
+    let tree = |commit: &str| git(dir, None, &["rev-parse", &format!("{}^{{tree}}", commit)]).ok();
+    matches!((tree(a), tree(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// Put the working tree at `dir` back the way it was in `commit`; files
+/// created since that git doesn't track are left alone
+pub fn restore(dir: &Path, commit: &str) -> Result<()> {
+    let source = format!("--source={}", commit);
+    git(dir, None, &["restore", &source, "--worktree", "--", "."])?;
+    Ok(())
+}
+
+/// Command that puts the working tree back the way it was in `commit`
+pub fn restore_command(commit: &str) -> String {
+    format!(
+        "git restore --source={} --worktree -- .",
+        commit.get(..12).unwrap_or(commit)
+    )
+}
+
+fn scratch_index(dir: &Path) -> Option<PathBuf> {
+    let path = git(dir, None, &["rev-parse", "--absolute-git-dir"]).ok()?;
+    Some(PathBuf::from(path).join(format!("codemux-index-{}", uuid::Uuid::new_v4())))
+}
+
+fn commit_working_tree(dir: &Path, index: &Path, message: &str) -> Result<String> {
+    let head = git(dir, None, &["rev-parse", "--verify", "-q", "HEAD"]).ok();
+    if head.is_some() {
+        git(dir, Some(index), &["read-tree", "HEAD"])?;
+    }
+    // `:/` covers the whole repository even when `dir` is a subdirectory
+    git(dir, Some(index), &["add", "-A", "--", ":/"])?;
+    let tree = git(dir, Some(index), &["write-tree"])?;
+    let mut args = vec!["commit-tree", tree.as_str(), "-m", message];
+    if let Some(head) = &head {
+        args.extend(["-p", head.as_str()]);
+    }
+    git(dir, None, &args)
+}
+
+/// Run git in `dir`, optionally against another index, returning trimmed stdout
+fn git(dir: &Path, index: Option<&Path>, args: &[&str]) -> Result<String> {
+    let mut command = Command::new("git");
+    // Snapshots are made by codemux, whether or not the user set up an identity
+    command
+        .args([
+            "-c",
+            "user.name=codemux",
+            "-c",
+            "user.email=codemux@localhost",
+        ])
+        .args(args)
+        .current_dir(dir);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+    let output = command
+        .output()
+        .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_the_working_tree_without_touching_the_index() {
+        let dir = &std::env::temp_dir().join(format!("codemux-checkpoint-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+        assert_eq!(snapshot(dir, "not a repo").unwrap(), None);
+
+        git(dir, None, &["init", "-q"]).unwrap();
+        std::fs::write(dir.join(".gitignore"), "build/\n").unwrap();
+        std::fs::write(dir.join("tracked.txt"), "tracked").unwrap();
+        git(dir, None, &["add", "-A"]).unwrap();
+        git(dir, None, &["commit", "-q", "-m", "initial"]).unwrap();
+        std::fs::write(dir.join("untracked.txt"), "untracked").unwrap();
+        std::fs::create_dir(dir.join("build")).unwrap();
+        std::fs::write(dir.join("build/output"), "ignored").unwrap();
+
+        let commit = snapshot(dir, "before cleanup").unwrap().unwrap();
+        let files = git(dir, None, &["ls-tree", "-r", "--name-only", &commit]).unwrap();
+        assert_eq!(files, ".gitignore\ntracked.txt\nuntracked.txt");
+        let status = git(dir, None, &["status", "--porcelain"]).unwrap();
+        assert_eq!(status, "?? untracked.txt");
+
+        let reference = pin(dir, "session/safety", &commit).unwrap();
+        std::fs::remove_file(dir.join("tracked.txt")).unwrap();
+        std::fs::remove_file(dir.join("untracked.txt")).unwrap();
+        git(
+            dir,
+            None,
+            &[
+                "restore",
+                &format!("--source={}", reference),
+                "--worktree",
+                "--",
+                ".",
+            ],
+        )
+        .unwrap();
+        assert!(dir.join("tracked.txt").exists());
+        assert!(dir.join("untracked.txt").exists());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn lists_and_restores_a_sessions_checkpoints() {
+        let dir = &std::env::temp_dir().join(format!("codemux-turns-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+        git(dir, None, &["init", "-q"]).unwrap();
+        std::fs::write(dir.join("main.rs"), "fn main() {}").unwrap();
+
+        let first = snapshot(dir, "codemux: turn 1").unwrap().unwrap();
+        pin(dir, "abc/turn-1", &first).unwrap();
+        pin(dir, "other/turn-1", &first).unwrap();
+        let again = snapshot(dir, "codemux: turn 2").unwrap().unwrap();
+        assert!(same_tree(dir, &first, &again));
+
+        std::fs::write(dir.join("main.rs"), "").unwrap();
+        let second = snapshot(dir, "codemux: turn 2").unwrap().unwrap();
+        assert!(!same_tree(dir, &first, &second));
+        pin(dir, "abc/turn-2", &second).unwrap();
+
+        let checkpoints = session_checkpoints(dir, "abc");
+        let names: Vec<_> = checkpoints.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["turn-1", "turn-2"]);
+        assert_eq!(checkpoints[0].commit, first);
+        assert_eq!(checkpoints[0].message, "codemux: turn 1");
+
+        restore(dir, &first).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("main.rs")).unwrap(),
+            "fn main() {}"
+        );
+        unpin(dir, &checkpoints[0].reference).unwrap();
+        assert_eq!(session_checkpoints(dir, "abc").len(), 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn a_turn_ends_once_the_agent_goes_quiet() {
+        let start = Instant::now();
+        let mut turns = TurnTracker::new(Duration::from_secs(5));
+        assert!(!turns.settled(start + Duration::from_secs(10)));
+
+        turns.output(start);
+        turns.output(start + Duration::from_secs(3));
+        assert!(!turns.settled(start + Duration::from_secs(7)));
+        assert!(turns.settled(start + Duration::from_secs(8)));
+        assert!(!turns.settled(start + Duration::from_secs(20)));
+
+        turns.output(start + Duration::from_secs(30));
+        assert!(turns.interrupt());
+        assert!(!turns.interrupt());
+        assert!(!turns.settled(start + Duration::from_secs(60)));
+    }
+}
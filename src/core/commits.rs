@@ -0,0 +1,81 @@
+//! Commits made by agents, picked out of their output
+//!
+//! Aider commits each change it makes and announces it as
+//! `Commit 3f9a2b1 feat: Add login form`. Those lines are linked to the session,
+//! so reviewing an aider run is a list of commits rather than scrolling back
+//! through the terminal.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::OnceLock;
+use ts_rs::TS;
+
+/// How many commits the store keeps across all sessions
+pub const MAX_COMMITS: usize = 1000;
+
+/// A commit an agent announced in its session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AgentCommit {
+    pub session_id: String,
+    /// Full hash when the session's repository knows it, otherwise as announced
+    pub hash: String,
+    pub message: String,
+    /// ISO 8601 timestamp of when it was announced
+    pub created_at: String,
+}
+
+/// Agents whose commit announcements are understood
+pub fn announces_commits(agent: &str) -> bool {
+    agent == "aider"
+}
+
+/// Hash and message of an aider commit announcement
+pub fn parse_announcement(line: &str) -> Option<(String, String)> {
+    static ANNOUNCEMENT: OnceLock<Regex> = OnceLock::new();
+    let announcement = ANNOUNCEMENT
+        .get_or_init(|| Regex::new(r"^Commit ([0-9a-f]{7,40})\b\s*(.*)$").expect("valid regex"));
+    let captures = announcement.captures(line.trim())?;
+    Some((captures[1].to_string(), captures[2].trim().to_string()))
+}
+
+/// Full hash and subject of `hash` in the repository at `dir`; announcements
+/// are cut off where the terminal wraps, the repository has the whole subject
+pub fn lookup(dir: &Path, hash: &str) -> Option<(String, String)> {
+    let output = std::process::Command::new("git")
+        .args(["show", "-s", "--format=%H%n%s", hash, "--"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    let mut lines = output.lines();
+    Some((
+        lines.next()?.to_string(),
+        lines.next().unwrap_or("").to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_aider_commit_announcements() {
+        assert_eq!(
+            parse_announcement("Commit 3f9a2b1 feat: Add login form "),
+            Some(("3f9a2b1".to_string(), "feat: Add login form".to_string()))
+        );
+        assert_eq!(
+            parse_announcement("Commit 3f9a2b1c0d"),
+            Some(("3f9a2b1c0d".to_string(), String::new()))
+        );
+        assert_eq!(parse_announcement("Commit the changes? (Y)es/(N)o"), None);
+        assert_eq!(parse_announcement("> Commit 3f9a2b1 quoted"), None);
+        assert!(announces_commits("aider"));
+        assert!(!announces_commits("claude"));
+    }
+}
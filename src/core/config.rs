@@ -1,13 +1,83 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::agent_config::AgentConfigSettings;
+use super::agent_version::AgentConfig;
+use super::auth;
+use super::checkpoint::CheckpointsConfig;
+use super::config_schema;
+use super::deletion_guard::DeletionGuardConfig;
+use super::digest::DigestConfig;
+use super::dirs;
+use super::hooks::Hook;
+use super::idle::SessionConfig;
+use super::input_control::InputConfig;
+use super::launch::SessionTemplate;
+use super::limits::LimitsConfig;
+use super::notifications::NotificationsConfig;
+use super::oidc::OidcConfig;
+use super::paste::PasteConfig;
+use super::recycle_bin::RecycleBinConfig;
+use super::telemetry::TelemetryConfig;
+
+/// On-disk config format version; bump it together with a new migration in `config_schema`
+pub const CONFIG_VERSION: u32 = 3;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default = "current_config_version")]
+    pub version: u32,
     pub whitelist: AgentWhitelist,
     pub server: ServerConfig,
     pub web: WebConfig,
+    #[serde(default)]
+    pub client: ClientConfig,
+    #[serde(default)]
+    pub ssh: SshConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub keybindings: KeybindingsConfig,
+    #[serde(default)]
+    pub paste: PasteConfig,
+    #[serde(default)]
+    pub input: InputConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub recycle_bin: RecycleBinConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub digest: DigestConfig,
+    #[serde(default)]
+    pub deletion_guard: DeletionGuardConfig,
+    #[serde(default)]
+    pub checkpoints: CheckpointsConfig,
+    #[serde(default)]
+    pub agent_config: AgentConfigSettings,
+    /// Commands and URLs run on session lifecycle events, as `[[hooks]]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<Hook>,
+    /// Session templates offered by `POST /api/sessions` and the web UI, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, SessionTemplate>,
+    /// Per-agent settings such as version pins, by agent name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub agents: BTreeMap<String, AgentConfig>,
+    /// Servers to pick with `--profile <name>`, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Profile applied by `use_profile`, handed on to servers the CLI starts
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,13 +88,203 @@ pub struct AgentWhitelist {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub port: u16,
+    /// Each directory can also be set with its `CODEMUX_*_DIR` environment variable
+    #[serde(default = "dirs::default_data_dir")]
     pub data_dir: PathBuf,
-    pub pid_file: PathBuf,
+    #[serde(default = "dirs::default_cache_dir")]
+    pub cache_dir: PathBuf,
+    /// Holds the PID file, the server's lock, API token and sockets
+    #[serde(default = "dirs::default_runtime_dir")]
+    pub runtime_dir: PathBuf,
+    #[serde(default = "dirs::default_log_dir")]
+    pub log_dir: PathBuf,
+    /// Unix socket to serve on besides the port, e.g. `/run/user/1000/codemux/server.sock`;
+    /// local clients use it when it's there. Only the server's user can
+    /// connect, so requests over it need no token.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_path: Option<PathBuf>,
+    /// How long an API request waits on the session manager before failing;
+    /// the manager finishes it regardless and logs it as stuck
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+impl ServerConfig {
+    pub fn pid_file(&self) -> PathBuf {
+        self.runtime_dir.join(dirs::PID_FILE_NAME)
+    }
+
+    /// Lock file held by the running server, see `server::lifecycle`
+    pub fn lock_file(&self) -> PathBuf {
+        self.runtime_dir.join(dirs::LOCK_FILE_NAME)
+    }
+
+    /// Token of the running server, see `auth`
+    pub fn token_file(&self) -> PathBuf {
+        self.runtime_dir.join(auth::TOKEN_FILE_NAME)
+    }
+
+    /// Recordings of sessions created with `record`
+    pub fn recordings_dir(&self) -> PathBuf {
+        self.data_dir.join("recordings")
+    }
+
+    /// Pastes saved to a file, see `core::paste`
+    pub fn pastes_dir(&self) -> PathBuf {
+        self.cache_dir.join("pastes")
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs.max(1))
+    }
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebConfig {
     pub static_dir: Option<PathBuf>,
+    /// Require a token on the API and WebSockets; only turn this off behind a
+    /// proxy that authenticates on its own
+    #[serde(default = "default_web_auth")]
+    pub auth: bool,
+    /// Token to require instead of one generated on every start, e.g. for
+    /// clients on other machines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Sign people in with an OpenID Connect provider, see `core::oidc`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oidc: Option<OidcConfig>,
+}
+
+fn default_web_auth() -> bool {
+    true
+}
+
+/// Which server the CLI talks to
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientConfig {
+    /// URL of a codemux server on another machine, e.g.
+    /// `https://codemux.example.com`, to use instead of starting one locally;
+    /// `CODEMUX_SERVER_URL` overrides it. Sign in with `codemux login`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_url: Option<String>,
+    /// Token to send instead of the local server's or the last `codemux login`'s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+/// Environment variable naming the server the CLI talks to
+pub const SERVER_URL_ENV: &str = "CODEMUX_SERVER_URL";
+
+/// Environment variable naming the profile to use, like `--profile`
+pub const PROFILE_ENV: &str = "CODEMUX_PROFILE";
+
+/// A server to talk to, picked with `--profile <name>`; unset fields keep
+/// the values from the rest of the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Overrides `[client] server_url`; without it the profile uses a local server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_url: Option<String>,
+    /// Overrides `[client] token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Overrides `[server] data_dir`, which also holds the profile's `codemux login` credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+}
+
+impl ClientConfig {
+    /// Base URL of the server the CLI talks to
+    pub fn server_url(&self, port: u16) -> String {
+        match &self.server_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://localhost:{}", port),
+        }
+    }
+}
+
+/// Embedded SSH server for attaching to sessions with a plain `ssh` client
+///
+/// Only used when codemux is built with the `ssh-server` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SshConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Host key in OpenSSH format; generated under the data dir when unset
+    pub host_key: Option<PathBuf>,
+    /// Public keys allowed to attach, one OpenSSH `authorized_keys` line each
+    pub authorized_keys: Vec<String>,
+}
+
+impl Default for SshConfig {
+    fn default() -> Self {
+        SshConfig {
+            enabled: false,
+            port: 2222,
+            host_key: None,
+            authorized_keys: Vec::new(),
+        }
+    }
+}
+
+/// Where the server keeps projects, session history, recent attaches and preferences
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    pub backend: StorageBackend,
+    /// Connection string for shared backends, e.g. `postgres://user@host/codemux`
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// JSON files in the data directory
+    #[default]
+    Local,
+    /// A PostgreSQL database shared by several servers; needs the `postgres` feature
+    Postgres,
+}
+
+/// TUI key bindings; each action takes a list of keys such as `"ctrl+t"`, `"?"` or `"f1"`,
+/// or two keys in a row such as `"ctrl+\\ d"`
+///
+/// Keys without Ctrl or Alt only apply in monitoring mode, since interactive
+/// mode types them into the agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeybindingsConfig {
+    pub quit: Vec<String>,
+    pub detach: Vec<String>,
+    pub toggle_interactive: Vec<String>,
+    pub open_web: Vec<String>,
+    pub refresh: Vec<String>,
+    pub help: Vec<String>,
+    pub retract: Vec<String>,
+    pub detach_with_note: Vec<String>,
+    pub copy_mode: Vec<String>,
+}
+
+impl Default for KeybindingsConfig {
+    fn default() -> Self {
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect();
+        KeybindingsConfig {
+            quit: keys(&["ctrl+c"]),
+            detach: keys(&["ctrl+\\ d"]),
+            toggle_interactive: keys(&["ctrl+t", "i"]),
+            open_web: keys(&["o"]),
+            refresh: keys(&["r"]),
+            help: keys(&["?", "f1"]),
+            retract: keys(&["alt+u"]),
+            detach_with_note: keys(&["alt+d"]),
+            copy_mode: keys(&["ctrl+\\ ["]),
+        }
+    }
 }
 
 impl Default for Config {
@@ -36,100 +296,242 @@ impl Default for Config {
         agents.insert("cursor".to_string());
         agents.insert("continue".to_string());
 
-        let data_dir = directories::ProjectDirs::from("com", "codemux", "codemux")
-            .map(|dirs| dirs.data_dir().to_path_buf())
-            .unwrap_or_else(|| PathBuf::from(".codemux"));
-
         Config {
+            version: CONFIG_VERSION,
             whitelist: AgentWhitelist { agents },
             server: ServerConfig {
                 port: default_server_port(),
-                data_dir: data_dir.clone(),
-                pid_file: data_dir.join("server.pid"),
+                data_dir: dirs::default_data_dir(),
+                cache_dir: dirs::default_cache_dir(),
+                runtime_dir: dirs::default_runtime_dir(),
+                log_dir: dirs::default_log_dir(),
+                socket_path: None,
+                request_timeout_secs: default_request_timeout_secs(),
+            },
+            web: WebConfig {
+                static_dir: None,
+                auth: default_web_auth(),
+                token: None,
+                oidc: None,
             },
-            web: WebConfig { static_dir: None },
+            client: ClientConfig::default(),
+            ssh: SshConfig::default(),
+            storage: StorageConfig::default(),
+            keybindings: KeybindingsConfig::default(),
+            paste: PasteConfig::default(),
+            input: InputConfig::default(),
+            limits: LimitsConfig::default(),
+            recycle_bin: RecycleBinConfig::default(),
+            session: SessionConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            notifications: NotificationsConfig::default(),
+            digest: DigestConfig::default(),
+            deletion_guard: DeletionGuardConfig::default(),
+            checkpoints: CheckpointsConfig::default(),
+            agent_config: AgentConfigSettings::default(),
+            hooks: Vec::new(),
+            templates: BTreeMap::new(),
+            agents: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            profile: None,
         }
     }
 }
 
+fn current_config_version() -> u32 {
+    CONFIG_VERSION
+}
+
 /// Get the default server port based on build type
 pub fn default_server_port() -> u16 {
     if cfg!(debug_assertions) { 18765 } else { 8765 }
 }
 
 impl Config {
+    /// Load the user's config file, then apply `CODEMUX_*_DIR` overrides
     pub fn load() -> Result<Self> {
-        if let Some(config_dir) = directories::ProjectDirs::from("com", "codemux", "codemux") {
-            let config_file = config_dir.config_dir().join("config.toml");
-            if config_file.exists() {
-                let content = std::fs::read_to_string(&config_file)?;
-
-                // Try to load as new format first
-                if let Ok(config) = toml::from_str::<Config>(&content) {
-                    return Ok(config);
-                }
-
-                // Try to load legacy format and migrate
-                if let Ok(legacy_config) = toml::from_str::<LegacyConfig>(&content) {
-                    let migrated_config = Config::from_legacy(legacy_config);
-
-                    // Save the migrated config
-                    if let Err(e) = migrated_config.save() {
-                        tracing::warn!("Failed to save migrated config: {}", e);
-                    } else {
-                        tracing::info!("Migrated legacy daemon config to server config");
-                    }
-
-                    return Ok(migrated_config);
-                }
+        let mut config = match Self::config_path() {
+            Some(config_file) if config_file.exists() => Self::load_from(&config_file)?,
+            _ => Config::default(),
+        };
+        dirs::apply_env_overrides(&mut config.server);
+        if let Ok(url) = std::env::var(SERVER_URL_ENV) {
+            config.client.server_url = Some(url).filter(|url| !url.is_empty());
+        }
+        Ok(config)
+    }
+
+    /// Load a config file, migrating older formats and warning about unknown keys
+    ///
+    /// A migrated file is rewritten in the current format after copying the
+    /// original next to it as `config.toml.v<old-version>.bak`.
+    pub fn load_from(config_file: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(config_file)?;
+        let invalid = |e: &dyn std::fmt::Display| {
+            anyhow!("Invalid config file {}:\n{}", config_file.display(), e)
+        };
+
+        let mut table: toml::Table = toml::from_str(&content).map_err(|e| invalid(&e))?;
+        let migrated_from = config_schema::migrate(&mut table).map_err(|e| invalid(&e))?;
+
+        for warning in config_schema::unknown_keys(&table) {
+            eprintln!("⚠️  {}: {}", config_file.display(), warning);
+        }
+
+        // Parse the original text when possible so errors point at the right line
+        let config: Config = match migrated_from {
+            Some(_) => toml::Value::Table(table).try_into(),
+            None => toml::from_str(&content),
+        }
+        .map_err(|e| invalid(&e))?;
+        if let Some(oidc) = &config.web.oidc {
+            oidc.validate().map_err(|e| invalid(&e))?;
+        }
+
+        if let Some(old_version) = migrated_from {
+            let backup = config_file.with_extension(format!("toml.v{}.bak", old_version));
+            let saved = std::fs::copy(config_file, &backup)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| config.save_to(config_file));
+            match saved {
+                Ok(()) => eprintln!(
+                    "ℹ️  Migrated {} from version {} to {} (backup at {})",
+                    config_file.display(),
+                    old_version,
+                    CONFIG_VERSION,
+                    backup.display()
+                ),
+                Err(e) => eprintln!("⚠️  Failed to save migrated config: {}", e),
             }
         }
-        Ok(Config::default())
+
+        Ok(config)
     }
 
     pub fn save(&self) -> Result<()> {
-        if let Some(config_dir) = directories::ProjectDirs::from("com", "codemux", "codemux") {
-            std::fs::create_dir_all(config_dir.config_dir())?;
-            let config_file = config_dir.config_dir().join("config.toml");
-            let content = toml::to_string_pretty(self)?;
-            std::fs::write(config_file, content)?;
+        if let Some(config_file) = Self::config_path() {
+            self.save_to(&config_file)?;
         }
         Ok(())
     }
 
-    fn from_legacy(legacy: LegacyConfig) -> Self {
-        Config {
-            whitelist: legacy.whitelist,
-            server: ServerConfig {
-                port: legacy.daemon.port,
-                data_dir: legacy.daemon.data_dir,
-                pid_file: legacy
-                    .daemon
-                    .pid_file
-                    .parent()
-                    .map(|p| p.join("server.pid"))
-                    .unwrap_or_else(|| PathBuf::from("server.pid")),
-            },
-            web: legacy.web,
+    fn save_to(&self, config_file: &Path) -> Result<()> {
+        if let Some(parent) = config_file.parent() {
+            std::fs::create_dir_all(parent)?;
         }
+        let content = toml::to_string_pretty(self)?;
+        std::fs::write(config_file, content)?;
+        Ok(())
+    }
+
+    pub fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("config.toml"))
     }
 
+    /// Talk to the server of the `[profiles.<name>]` table instead of the
+    /// default one; it wins over `CODEMUX_SERVER_URL` and `CODEMUX_DATA_DIR`
+    pub fn use_profile(&mut self, name: &str) -> Result<()> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                return Err(anyhow!(
+                    "Unknown profile '{}': no [profiles.<name>] tables are configured",
+                    name
+                ));
+            }
+            return Err(anyhow!(
+                "Unknown profile '{}', expected one of: {}",
+                name,
+                known.join(", ")
+            ));
+        };
+        self.client.server_url = profile.server_url;
+        if profile.token.is_some() {
+            self.client.token = profile.token;
+        }
+        if let Some(data_dir) = profile.data_dir {
+            self.server.data_dir = data_dir;
+        }
+        self.profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Whether `agent` is whitelisted or defined by an `[agents.<name>]` table
     pub fn is_agent_allowed(&self, agent: &str) -> bool {
-        self.whitelist.agents.contains(agent)
+        self.whitelist.agents.contains(agent) || self.agents.contains_key(agent)
     }
-}
 
-// Legacy config structures for migration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct LegacyConfig {
-    pub whitelist: AgentWhitelist,
-    pub daemon: LegacyDaemonConfig,
-    pub web: WebConfig,
+    /// Every agent that can be run, sorted
+    pub fn agent_names(&self) -> Vec<String> {
+        let names: std::collections::BTreeSet<&String> = self
+            .whitelist
+            .agents
+            .iter()
+            .chain(self.agents.keys())
+            .collect();
+        names.into_iter().cloned().collect()
+    }
+
+    /// Program that runs `agent`: its `command`, or the agent's own name
+    pub fn agent_command<'a>(&'a self, agent: &'a str) -> &'a str {
+        self.agents
+            .get(agent)
+            .and_then(|config| config.command.as_deref())
+            .unwrap_or(agent)
+    }
+
+    /// `required_env` variables of `agent` that are set neither in the
+    /// server's environment nor in `env`
+    pub fn missing_agent_env(&self, agent: &str, env: &BTreeMap<String, String>) -> Vec<String> {
+        let Some(config) = self.agents.get(agent) else {
+            return Vec::new();
+        };
+        config
+            .required_env
+            .iter()
+            .filter(|name| !env.contains_key(*name) && std::env::var_os(name).is_none())
+            .cloned()
+            .collect()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct LegacyDaemonConfig {
-    pub port: u16,
-    pub data_dir: PathBuf,
-    pub pid_file: PathBuf,
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_replaces_server_settings() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [whitelist]
+            agents = ["claude"]
+            [server]
+            port = 8765
+            [web]
+            [client]
+            server_url = "https://default.example.com"
+            token = "default-token"
+            [profiles.local]
+            data_dir = "/tmp/codemux-local"
+            [profiles.work]
+            server_url = "https://work.example.com"
+            token = "work-token"
+            "#,
+        )
+        .unwrap();
+
+        let mut local = config.clone();
+        local.use_profile("local").unwrap();
+        assert_eq!(local.client.server_url, None);
+        assert_eq!(local.client.token.as_deref(), Some("default-token"));
+        assert_eq!(local.server.data_dir, PathBuf::from("/tmp/codemux-local"));
+
+        config.use_profile("work").unwrap();
+        assert_eq!(config.client.server_url(8765), "https://work.example.com");
+        assert_eq!(config.client.token.as_deref(), Some("work-token"));
+        assert_eq!(config.profile.as_deref(), Some("work"));
+
+        let error = config.use_profile("home").unwrap_err().to_string();
+        assert!(error.contains("local, work"), "{}", error);
+    }
 }
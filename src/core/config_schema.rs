@@ -0,0 +1,350 @@
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use toml::{Table, Value};
+
+use super::config::CONFIG_VERSION;
+use super::dirs::PID_FILE_NAME;
+
+/// Keys accepted in each config table; "" is the top level
+///
+/// Keep in sync with `Config` - the tests check that every serialized field is listed.
+const SCHEMA: &[(&str, &[&str])] = &[
+    (
+        "",
+        &[
+            "version",
+            "whitelist",
+            "server",
+            "web",
+            "client",
+            "ssh",
+            "storage",
+            "keybindings",
+            "paste",
+            "input",
+            "limits",
+            "recycle_bin",
+            "session",
+            "telemetry",
+            "notifications",
+            "digest",
+            "deletion_guard",
+            "checkpoints",
+            "agent_config",
+            "hooks",
+            "templates",
+            "agents",
+            "profiles",
+        ],
+    ),
+    ("whitelist", &["agents"]),
+    (
+        "server",
+        &[
+            "port",
+            "data_dir",
+            "cache_dir",
+            "runtime_dir",
+            "log_dir",
+            "socket_path",
+            "request_timeout_secs",
+        ],
+    ),
+    ("web", &["static_dir", "auth", "token", "oidc"]),
+    (
+        "web.oidc",
+        &[
+            "issuer",
+            "client_id",
+            "client_secret",
+            "redirect_url",
+            "scopes",
+            "allowed_users",
+            "username_claim",
+            "session_ttl_hours",
+            "cli_token_ttl_hours",
+        ],
+    ),
+    ("client", &["server_url", "token"]),
+    ("ssh", &["enabled", "port", "host_key", "authorized_keys"]),
+    ("storage", &["backend", "url"]),
+    (
+        "keybindings",
+        &[
+            "quit",
+            "detach",
+            "toggle_interactive",
+            "open_web",
+            "refresh",
+            "help",
+            "retract",
+            "detach_with_note",
+            "copy_mode",
+        ],
+    ),
+    (
+        "paste",
+        &["confirm_lines", "confirm_bytes", "offer_file", "max_bytes"],
+    ),
+    ("input", &["mode"]),
+    (
+        "limits",
+        &[
+            "max_sessions",
+            "max_sessions_per_project",
+            "queue",
+            "queue_timeout_secs",
+        ],
+    ),
+    ("recycle_bin", &["grace_secs", "retention_hours"]),
+    ("session", &["idle_timeout_secs"]),
+    ("telemetry", &["otlp_endpoint", "service_name"]),
+    (
+        "notifications",
+        &[
+            "min_severity",
+            "quiet_hours",
+            "push_min_severity",
+            "desktop",
+            "webhooks",
+            "ntfy",
+            "projects",
+        ],
+    ),
+    ("notifications.ntfy", &["topic", "server", "token"]),
+    ("digest", &["enabled", "at", "save", "notify"]),
+    (
+        "deletion_guard",
+        &["enabled", "min_files", "window_secs", "ignore"],
+    ),
+    ("checkpoints", &["enabled", "settle_secs", "keep"]),
+    ("agent_config", &["enabled", "overwrite"]),
+];
+
+type Migration = fn(&mut Table) -> Result<()>;
+
+/// `MIGRATIONS[n]` upgrades a version `n + 1` config to version `n + 2`
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2, migrate_v2_to_v3];
+
+/// Version of a parsed config file
+///
+/// Files written before the `version` key existed are recognized by their layout.
+pub fn config_version(table: &Table) -> Result<u32> {
+    match table.get("version") {
+        Some(Value::Integer(version)) if *version >= 1 => Ok(*version as u32),
+        Some(other) => Err(anyhow!(
+            "`version` must be a positive integer, found {}",
+            other
+        )),
+        None if table.contains_key("daemon") => Ok(1),
+        None if table
+            .get("server")
+            .and_then(Value::as_table)
+            .is_some_and(|server| server.contains_key("pid_file")) =>
+        {
+            Ok(2)
+        }
+        None => Ok(CONFIG_VERSION),
+    }
+}
+
+/// Upgrade a parsed config to `CONFIG_VERSION` in place
+///
+/// Returns the original version if any migration ran.
+pub fn migrate(table: &mut Table) -> Result<Option<u32>> {
+    let original = config_version(table)?;
+    if original > CONFIG_VERSION {
+        return Err(anyhow!(
+            "config version {} is newer than this codemux supports ({}); upgrade codemux",
+            original,
+            CONFIG_VERSION
+        ));
+    }
+    if original == CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    for version in original..CONFIG_VERSION {
+        MIGRATIONS[(version - 1) as usize](table)?;
+    }
+    table.insert("version".to_string(), Value::Integer(CONFIG_VERSION as i64));
+    Ok(Some(original))
+}
+
+/// v1 -> v2: the `[daemon]` table became `[server]` and its pid file `server.pid`
+fn migrate_v1_to_v2(table: &mut Table) -> Result<()> {
+    let Some(daemon) = table.remove("daemon") else {
+        return Ok(());
+    };
+    let Value::Table(mut server) = daemon else {
+        return Err(anyhow!("`daemon` must be a table"));
+    };
+
+    if let Some(Value::String(pid_file)) = server.get("pid_file") {
+        let server_pid = Path::new(pid_file)
+            .parent()
+            .map(|dir| dir.join(PID_FILE_NAME))
+            .unwrap_or_else(|| PID_FILE_NAME.into());
+        server.insert(
+            "pid_file".to_string(),
+            Value::String(server_pid.to_string_lossy().into_owned()),
+        );
+    }
+
+    table.entry("server").or_insert(Value::Table(server));
+    Ok(())
+}
+
+/// v2 -> v3: `server.pid_file` became the PID file inside `server.runtime_dir`
+///
+/// A PID file that lived in the data dir was the old default, so it is dropped
+/// in favour of the XDG runtime dir; any other location is kept as the runtime dir.
+fn migrate_v2_to_v3(table: &mut Table) -> Result<()> {
+    let Some(Value::Table(server)) = table.get_mut("server") else {
+        return Ok(());
+    };
+    let Some(pid_file) = server.remove("pid_file") else {
+        return Ok(());
+    };
+    let Value::String(pid_file) = pid_file else {
+        return Err(anyhow!("`server.pid_file` must be a string"));
+    };
+
+    let pid_dir = Path::new(&pid_file).parent().unwrap_or(Path::new(""));
+    let data_dir = server
+        .get("data_dir")
+        .and_then(Value::as_str)
+        .map(Path::new);
+    if data_dir != Some(pid_dir) && !server.contains_key("runtime_dir") {
+        server.insert(
+            "runtime_dir".to_string(),
+            Value::String(pid_dir.to_string_lossy().into_owned()),
+        );
+    }
+    Ok(())
+}
+
+/// Describe keys that aren't part of the schema, suggesting the closest known key
+pub fn unknown_keys(table: &Table) -> Vec<String> {
+    let mut warnings = Vec::new();
+    check_table("", table, &mut warnings);
+    warnings
+}
+
+fn check_table(section: &str, table: &Table, warnings: &mut Vec<String>) {
+    let Some((_, known)) = SCHEMA.iter().find(|(name, _)| *name == section) else {
+        return;
+    };
+
+    for (key, value) in table {
+        let path = if section.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", section, key)
+        };
+
+        if !known.contains(&key.as_str()) {
+            match closest_key(key, known) {
+                Some(suggestion) => warnings.push(format!(
+                    "unknown key `{}` (did you mean `{}`?)",
+                    path, suggestion
+                )),
+                None => warnings.push(format!("unknown key `{}`", path)),
+            }
+            continue;
+        }
+
+        if let Value::Table(child) = value {
+            check_table(&path, child, warnings);
+        }
+    }
+}
+
+fn closest_key<'a>(key: &str, known: &[&'a str]) -> Option<&'a str> {
+    known
+        .iter()
+        .map(|candidate| (strsim::jaro_winkler(key, candidate), *candidate))
+        .filter(|(score, _)| *score >= 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Config;
+
+    #[test]
+    fn schema_covers_every_config_field() {
+        let mut config = Config::default();
+        // Optional fields are only serialized when set
+        config.web.static_dir = Some("/tmp".into());
+        config.server.socket_path = Some("/tmp/codemux.sock".into());
+        config.client.server_url = Some("https://codemux.example.com".into());
+        config.client.token = Some("cm_secret".into());
+        config.ssh.host_key = Some("/tmp/key".into());
+        config.storage.url = Some("postgres://localhost/codemux".into());
+        config.web.oidc = Some(crate::core::oidc::OidcConfig {
+            issuer: "https://id.example.com".into(),
+            client_id: "codemux".into(),
+            client_secret: Some("secret".into()),
+            redirect_url: "https://codemux.example.com/auth/callback".into(),
+            scopes: vec!["openid".into()],
+            allowed_users: vec!["*".into()],
+            username_claim: "email".into(),
+            session_ttl_hours: 24,
+            cli_token_ttl_hours: 12,
+        });
+        config.limits.max_sessions = Some(32);
+        config.limits.max_sessions_per_project = Some(8);
+        config.telemetry.otlp_endpoint = Some("http://localhost:4318/v1/traces".into());
+        config.notifications.ntfy = Some(crate::core::notifications::NtfyConfig {
+            topic: "alerts".into(),
+            server: "https://ntfy.sh".into(),
+            token: Some("tk_secret".into()),
+        });
+        config
+            .notifications
+            .projects
+            .insert("web".into(), Default::default());
+        config.hooks.push(crate::core::hooks::Hook {
+            events: vec![crate::core::hooks::HookEvent::AgentExited],
+            command: Some("./notify-ci.sh".into()),
+            url: Some("https://hooks.example.com/codemux".into()),
+        });
+
+        let table = toml::Table::try_from(&config).unwrap();
+        assert_eq!(unknown_keys(&table), Vec::<String>::new());
+    }
+
+    #[test]
+    fn migrates_legacy_daemon_config_and_flags_typos() {
+        let mut table: Table = toml::from_str(
+            r#"
+            [whitelist]
+            agents = ["claude"]
+
+            [daemon]
+            port = 8765
+            data_dir = "/data"
+            pid_file = "/run/codemux/daemon.pid"
+
+            [web]
+            statc_dir = "/web"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(migrate(&mut table).unwrap(), Some(1));
+        assert_eq!(
+            table["server"]["runtime_dir"].as_str(),
+            Some("/run/codemux")
+        );
+        assert!(!table["server"].as_table().unwrap().contains_key("pid_file"));
+        assert_eq!(
+            unknown_keys(&table),
+            vec!["unknown key `web.statc_dir` (did you mean `static_dir`?)"]
+        );
+        assert!(toml::Value::Table(table).try_into::<Config>().is_ok());
+    }
+}
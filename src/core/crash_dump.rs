@@ -0,0 +1,286 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use ts_rs::TS;
+use unicode_width::UnicodeWidthStr;
+
+use crate::core::pty_session::{GridCell, GridUpdateMessage};
+
+/// Number of grid updates kept in memory so a dump shows how the grid got into a bad state
+pub const CRASH_DUMP_HISTORY: usize = 64;
+
+/// Render failure reported by a client renderer, along with the updates that led to it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RenderCrashReport {
+    /// Renderer that hit the inconsistency, e.g. "tui" or "web"
+    pub source: String,
+    pub reason: String,
+    /// Most recent grid updates, oldest first
+    pub updates: Vec<GridUpdateMessage>,
+}
+
+/// First line of a crash dump file; the remaining lines are `GridUpdateMessage`s
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashDumpHeader {
+    pub crash_id: String,
+    pub session_id: String,
+    pub source: String,
+    pub reason: String,
+    pub created_at: SystemTime,
+    pub version: String,
+    pub update_count: usize,
+}
+
+/// Directory crash dumps are written to
+pub fn crash_dump_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("crash-dumps")
+}
+
+/// Write a crash dump as JSONL and return its id and path
+///
+/// The format is readable by `codemux-capture analyze`, which replays the
+/// updates and points at the one that broke the grid.
+pub fn write_crash_dump(
+    data_dir: &Path,
+    session_id: &str,
+    report: &RenderCrashReport,
+) -> Result<(String, PathBuf)> {
+    let crash_id = uuid::Uuid::new_v4().to_string()[..8].to_string();
+    let dir = crash_dump_dir(data_dir);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("render-{}.jsonl", crash_id));
+
+    let header = CrashDumpHeader {
+        crash_id: crash_id.clone(),
+        session_id: session_id.to_string(),
+        source: report.source.clone(),
+        reason: report.reason.clone(),
+        created_at: SystemTime::now(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        update_count: report.updates.len(),
+    };
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(&path)?);
+    writeln!(writer, "{}", serde_json::to_string(&header)?)?;
+    for update in &report.updates {
+        writeln!(writer, "{}", serde_json::to_string(update)?)?;
+    }
+    writer.flush()?;
+
+    Ok((crash_id, path))
+}
+
+/// Read a crash dump back, or `None` if the file isn't one
+pub fn read_crash_dump(path: &Path) -> Result<Option<(CrashDumpHeader, Vec<GridUpdateMessage>)>> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let mut lines = reader.lines();
+
+    let Some(first) = lines.next().transpose()? else {
+        return Ok(None);
+    };
+    let Ok(header) = serde_json::from_str::<CrashDumpHeader>(&first) else {
+        return Ok(None);
+    };
+
+    let mut updates = Vec::new();
+    for line in lines {
+        let line = line?;
+        if !line.trim().is_empty() {
+            updates.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(Some((header, updates)))
+}
+
+/// Mirrors a renderer's grid and flags states no renderer can draw correctly
+///
+/// Feed it every update the renderer applies; the first inconsistency is
+/// reported once, together with the recent history for a crash dump.
+#[derive(Debug, Default)]
+pub struct RenderWatchdog {
+    history: VecDeque<GridUpdateMessage>,
+    size: Option<(u16, u16)>,
+    cells: HashMap<(u16, u16), GridCell>,
+    cursor: (u16, u16),
+    tripped: bool,
+}
+
+impl RenderWatchdog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply an update and return a description of the problem if the grid is now inconsistent
+    pub fn observe(&mut self, update: &GridUpdateMessage) -> Option<String> {
+        if self.history.len() == CRASH_DUMP_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(update.clone());
+
+        let changed: Vec<(u16, u16)> = match update {
+            GridUpdateMessage::Keyframe {
+                size,
+                cells,
+                cursor,
+                ..
+            } => {
+                self.size = Some((size.rows, size.cols));
+                self.cells = cells.iter().cloned().collect();
+                self.cursor = *cursor;
+                self.cells.keys().copied().collect()
+            }
+            GridUpdateMessage::Diff {
+                changes, cursor, ..
+            } => {
+                for (row, col, cell) in changes {
+                    self.cells.insert((*row, *col), cell.clone());
+                }
+                if let Some(cursor) = cursor {
+                    self.cursor = *cursor;
+                }
+                changes.iter().map(|(row, col, _)| (*row, *col)).collect()
+            }
+        };
+
+        if self.tripped {
+            return None;
+        }
+        let problem = self.check(&changed);
+        self.tripped = problem.is_some();
+        problem
+    }
+
+    /// Updates seen so far, oldest first
+    pub fn history(&self) -> Vec<GridUpdateMessage> {
+        self.history.iter().cloned().collect()
+    }
+
+    fn check(&self, changed: &[(u16, u16)]) -> Option<String> {
+        let (rows, cols) = self.size?;
+
+        // A cursor column equal to the width is the pending-wrap position, not an error
+        let (cursor_row, cursor_col) = self.cursor;
+        if cursor_row >= rows || cursor_col > cols {
+            return Some(format!(
+                "cursor ({}, {}) outside {}x{} grid",
+                cursor_row, cursor_col, cols, rows
+            ));
+        }
+
+        for &(row, col) in changed {
+            if row >= rows || col >= cols {
+                return Some(format!(
+                    "cell ({}, {}) outside {}x{} grid",
+                    row, col, cols, rows
+                ));
+            }
+
+            // A change can break a wide character on either side of it
+            let problem = self.check_wide_char(row, col, cols).or_else(|| {
+                col.checked_sub(1)
+                    .and_then(|left| self.check_wide_char(row, left, cols))
+            });
+            if problem.is_some() {
+                return problem;
+            }
+        }
+
+        None
+    }
+
+    fn check_wide_char(&self, row: u16, col: u16, cols: u16) -> Option<String> {
+        let cell = self.cells.get(&(row, col))?;
+        if cell.char.width() < 2 {
+            return None;
+        }
+        if col + 1 >= cols {
+            return Some(format!(
+                "wide character {:?} at ({}, {}) in the last column",
+                cell.char, row, col
+            ));
+        }
+
+        // Diffs send a blank for a freshly emptied continuation cell, so only
+        // visible content counts as an overlap
+        let next = self.cells.get(&(row, col + 1))?;
+        if next.char.trim().is_empty() {
+            return None;
+        }
+        Some(format!(
+            "wide character {:?} at ({}, {}) overlaps {:?}",
+            cell.char, row, col, next.char
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::SerializablePtySize;
+
+    fn cell(ch: &str) -> GridCell {
+        GridCell {
+            char: ch.to_string(),
+            fg_color: None,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    fn diff(changes: Vec<(u16, u16, GridCell)>, cursor: Option<(u16, u16)>) -> GridUpdateMessage {
+        GridUpdateMessage::Diff {
+            changes,
+            cursor,
+            cursor_visible: None,
+            scrollback_position: None,
+            scrollback_total: None,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn flags_first_inconsistency_and_round_trips_dump() {
+        let mut watchdog = RenderWatchdog::new();
+        let keyframe = GridUpdateMessage::Keyframe {
+            size: SerializablePtySize { rows: 2, cols: 4 },
+            cells: vec![((0, 0), cell("中")), ((0, 1), cell(""))],
+            cursor: (0, 2),
+            cursor_visible: true,
+            scrollback_position: 0,
+            scrollback_total: 0,
+            timestamp: SystemTime::now(),
+            runs: Vec::new(),
+        };
+        assert_eq!(watchdog.observe(&keyframe), None);
+        // Pending-wrap cursor and a blanked continuation cell are both fine
+        assert_eq!(
+            watchdog.observe(&diff(vec![(0, 1, cell(" "))], Some((0, 4)))),
+            None
+        );
+
+        let problem = watchdog.observe(&diff(vec![(0, 1, cell("x"))], None));
+        assert!(problem.unwrap().contains("overlaps"));
+        // Only the first problem is reported
+        assert_eq!(watchdog.observe(&diff(vec![], Some((9, 0)))), None);
+
+        let report = RenderCrashReport {
+            source: "tui".to_string(),
+            reason: "test".to_string(),
+            updates: watchdog.history(),
+        };
+        let data_dir = std::env::temp_dir().join(format!("codemux-crash-{}", std::process::id()));
+        let (crash_id, path) = write_crash_dump(&data_dir, "session", &report).unwrap();
+        let (header, updates) = read_crash_dump(&path).unwrap().unwrap();
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        assert_eq!(header.crash_id, crash_id);
+        assert_eq!(updates.len(), 4);
+    }
+}
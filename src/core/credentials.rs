@@ -0,0 +1,233 @@
+//! Tokens `codemux login` got, for each server
+//!
+//! They go to the OS keychain where there is one: the login keychain through
+//! `security` on macOS, the Secret Service (GNOME Keyring, KWallet) through
+//! `secret-tool` on Linux. Without one, e.g. on a headless machine, they go
+//! to `credentials.json` in the data directory, readable by the user only.
+//! Servers are told apart by the origin of their URL, so logging in to a
+//! remote server leaves the local one's token alone.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::auth::write_token;
+
+/// Name of the file inside the data directory holding credentials when
+/// there's no keychain
+pub const CREDENTIALS_FILE_NAME: &str = "credentials.json";
+/// Service the credentials are filed under in the keychain
+const KEYCHAIN_SERVICE: &str = "codemux";
+
+/// A token for one server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Credential {
+    pub token: String,
+    /// RFC 3339; `None` for tokens that don't expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+}
+
+impl Credential {
+    pub fn expired(&self) -> bool {
+        self.expires_at
+            .as_deref()
+            .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+            .is_some_and(|at| at <= chrono::Utc::now())
+    }
+}
+
+/// The origin of a server URL, which its credentials are stored under
+pub fn server_key(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(url) => url.origin().ascii_serialization(),
+        Err(_) => url.trim_end_matches('/').to_string(),
+    }
+}
+
+/// Where credentials are kept
+pub struct CredentialStore {
+    file: PathBuf,
+    keychain: bool,
+}
+
+impl CredentialStore {
+    pub fn new(data_dir: &Path) -> Self {
+        CredentialStore {
+            file: data_dir.join(CREDENTIALS_FILE_NAME),
+            keychain: true,
+        }
+    }
+
+    /// The credential for `server`, unless it expired
+    pub fn get(&self, server: &str) -> Option<Credential> {
+        let key = server_key(server);
+        let keychain = self.keychain.then(|| keychain_get(&key)).flatten();
+        keychain
+            .or_else(|| self.read_file().remove(&key))
+            .filter(|credential| !credential.expired())
+    }
+
+    /// Keep the credential for `server`, replacing an earlier one, and tell
+    /// where it went
+    pub fn store(&self, server: &str, credential: &Credential) -> Result<String> {
+        let key = server_key(server);
+        if self.keychain {
+            match keychain_store(&key, credential) {
+                Ok(()) => {
+                    // Don't leave an older copy behind in the file
+                    self.remove_from_file(&key)?;
+                    return Ok("the OS keychain".to_string());
+                }
+                Err(e) => tracing::debug!("Keychain unavailable, using the file: {}", e),
+            }
+        }
+        let mut credentials = self.read_file();
+        credentials.insert(key, credential.clone());
+        self.write_file(&credentials)?;
+        Ok(self.file.display().to_string())
+    }
+
+    /// Forget the credential for `server`; false if there was none
+    pub fn remove(&self, server: &str) -> Result<bool> {
+        let key = server_key(server);
+        let in_keychain = self.keychain && keychain_remove(&key);
+        Ok(self.remove_from_file(&key)? || in_keychain)
+    }
+
+    fn remove_from_file(&self, key: &str) -> Result<bool> {
+        let mut credentials = self.read_file();
+        if credentials.remove(key).is_none() {
+            return Ok(false);
+        }
+        self.write_file(&credentials)?;
+        Ok(true)
+    }
+
+    fn read_file(&self) -> BTreeMap<String, Credential> {
+        std::fs::read_to_string(&self.file)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_file(&self, credentials: &BTreeMap<String, Credential>) -> Result<()> {
+        if let Some(dir) = self.file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_token(&self.file, &serde_json::to_string_pretty(credentials)?)
+    }
+}
+
+fn keychain_get(key: &str) -> Option<Credential> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["find-generic-password", "-s", KEYCHAIN_SERVICE])
+            .args(["-a", key, "-w"])
+            .stderr(Stdio::null())
+            .output()
+    } else if cfg!(target_os = "linux") {
+        Command::new("secret-tool")
+            .args(["lookup", "service", KEYCHAIN_SERVICE, "server", key])
+            .stderr(Stdio::null())
+            .output()
+    } else {
+        return None;
+    };
+    let output = output.ok().filter(|output| output.status.success())?;
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+fn keychain_store(key: &str, credential: &Credential) -> Result<()> {
+    let secret = serde_json::to_string(credential)?;
+    let status = if cfg!(target_os = "macos") {
+        // `security` only takes the secret as an argument
+        Command::new("security")
+            .args(["add-generic-password", "-U", "-s", KEYCHAIN_SERVICE])
+            .args(["-a", key, "-w", &secret])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?
+    } else if cfg!(target_os = "linux") {
+        let mut child = Command::new("secret-tool")
+            .arg("store")
+            .arg(format!("--label=codemux {}", key))
+            .args(["service", KEYCHAIN_SERVICE, "server", key])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(secret.as_bytes())?;
+        }
+        child.wait()?
+    } else {
+        bail!("No supported keychain on this platform");
+    };
+    if !status.success() {
+        bail!("The keychain refused the credential ({})", status);
+    }
+    Ok(())
+}
+
+fn keychain_remove(key: &str) -> bool {
+    let status = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["delete-generic-password", "-s", KEYCHAIN_SERVICE, "-a", key])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+    } else if cfg!(target_os = "linux") {
+        Command::new("secret-tool")
+            .args(["clear", "service", KEYCHAIN_SERVICE, "server", key])
+            .stderr(Stdio::null())
+            .status()
+    } else {
+        return false;
+    };
+    status.is_ok_and(|status| status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_a_credential_per_server() {
+        let data_dir =
+            std::env::temp_dir().join(format!("codemux-credentials-{}", std::process::id()));
+        let store = CredentialStore {
+            file: data_dir.join(CREDENTIALS_FILE_NAME),
+            keychain: false,
+        };
+        assert_eq!(
+            server_key("HTTPS://Codemux.Example.com:443/session/1"),
+            "https://codemux.example.com"
+        );
+
+        let remote = Credential {
+            token: "remote".to_string(),
+            expires_at: Some("2999-01-01T00:00:00Z".to_string()),
+        };
+        store
+            .store("https://codemux.example.com/", &remote)
+            .unwrap();
+        let expired = Credential {
+            token: "local".to_string(),
+            expires_at: Some("2000-01-01T00:00:00Z".to_string()),
+        };
+        store.store("http://localhost:8765", &expired).unwrap();
+
+        assert_eq!(store.get("https://codemux.example.com"), Some(remote));
+        assert_eq!(store.get("http://localhost:8765"), None);
+        assert_eq!(store.get("http://localhost:18765"), None);
+
+        assert!(store.remove("https://codemux.example.com").unwrap());
+        assert!(!store.remove("https://codemux.example.com").unwrap());
+        assert_eq!(store.get("https://codemux.example.com"), None);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Safety checkpoints when a session's working tree suddenly shrinks,
+/// configured as `[deletion_guard]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeletionGuardConfig {
+    pub enabled: bool,
+    /// Deleted files within `window_secs` that count as a mass deletion
+    pub min_files: usize,
+    pub window_secs: u64,
+    /// Directory names whose contents come and go on their own, e.g. build output
+    pub ignore: Vec<String>,
+}
+
+impl Default for DeletionGuardConfig {
+    fn default() -> Self {
+        DeletionGuardConfig {
+            enabled: false,
+            min_files: 50,
+            window_secs: 30,
+            ignore: [
+                "node_modules",
+                "target",
+                "dist",
+                "build",
+                ".venv",
+                "__pycache__",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+        }
+    }
+}
+
+impl DeletionGuardConfig {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_secs.max(1))
+    }
+
+    /// Whether deletions under `relative` (to the working tree) are left out
+    pub fn ignores(&self, relative: &std::path::Path) -> bool {
+        relative.components().any(|component| {
+            let name = component.as_os_str();
+            name == ".git" || self.ignore.iter().any(|ignored| name == ignored.as_str())
+        })
+    }
+}
+
+/// Counts deletions over a sliding window and reports each burst once
+#[derive(Debug)]
+pub struct DeletionBurst {
+    min_files: usize,
+    window: Duration,
+    deletions: VecDeque<Instant>,
+    reported: bool,
+}
+
+impl DeletionBurst {
+    pub fn new(config: &DeletionGuardConfig) -> Self {
+        DeletionBurst {
+            min_files: config.min_files.max(1),
+            window: config.window(),
+            deletions: VecDeque::new(),
+            reported: false,
+        }
+    }
+
+    /// Count `files` deleted at `now`; returns how many fell in the window when
+    /// this pushes a burst over the threshold, and `None` otherwise
+    pub fn record(&mut self, now: Instant, files: usize) -> Option<usize> {
+        self.expire(now);
+        self.deletions.extend(std::iter::repeat_n(now, files));
+        if self.reported || self.deletions.len() < self.min_files {
+            return None;
+        }
+        self.reported = true;
+        Some(self.deletions.len())
+    }
+
+    /// Whether deletions are still coming in, i.e. the working tree isn't settled
+    pub fn in_progress(&mut self, now: Instant) -> bool {
+        self.expire(now);
+        !self.deletions.is_empty()
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while self
+            .deletions
+            .front()
+            .is_some_and(|at| now.duration_since(*at) > self.window)
+        {
+            self.deletions.pop_front();
+        }
+        // Once the window is clear, the next burst is a new one
+        if self.deletions.is_empty() {
+            self.reported = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_a_burst_once_until_the_window_clears() {
+        let config = DeletionGuardConfig {
+            min_files: 10,
+            window_secs: 30,
+            ..Default::default()
+        };
+        let mut burst = DeletionBurst::new(&config);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        // A slow trickle never adds up
+        for secs in (0..300).step_by(5) {
+            assert_eq!(burst.record(at(secs), 1), None);
+        }
+
+        assert_eq!(burst.record(at(400), 6), None);
+        assert_eq!(burst.record(at(410), 6), Some(12));
+        assert_eq!(burst.record(at(411), 20), None);
+        assert!(burst.in_progress(at(430)));
+        assert!(!burst.in_progress(at(450)));
+        assert_eq!(burst.record(at(451), 10), Some(10));
+
+        assert!(config.ignores(std::path::Path::new("web/node_modules/react/index.js")));
+        assert!(config.ignores(std::path::Path::new(".git/index.lock")));
+        assert!(!config.ignores(std::path::Path::new("src/targets.rs")));
+    }
+}
@@ -0,0 +1,248 @@
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use ts_rs::TS;
+
+/// A daily overview of what ran, configured as `[digest]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub enabled: bool,
+    /// Local time to put the digest together, covering the 24 hours before it
+    #[serde(with = "clock_time")]
+    pub at: NaiveTime,
+    /// Write it to `digests/<date>.md` in the data dir
+    pub save: bool,
+    /// Announce it in the notification center
+    pub notify: bool,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        DigestConfig {
+            enabled: false,
+            at: NaiveTime::from_hms_opt(7, 0, 0).expect("valid time"),
+            save: true,
+            notify: true,
+        }
+    }
+}
+
+/// `HH:MM` times of day
+mod clock_time {
+    use chrono::NaiveTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &NaiveTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&time.format("%H:%M").to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|_| {
+            serde::de::Error::custom(format!("expected a time like \"07:00\", got {:?}", value))
+        })
+    }
+}
+
+/// How a session in the digest ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionOutcome {
+    Running,
+    /// The agent exited on its own
+    Exited,
+    /// Closed from codemux, or stopped with the server
+    Stopped,
+    /// Gone without a recorded end, e.g. the server was killed
+    Unknown,
+}
+
+/// One session as the digest sees it
+#[derive(Debug, Clone)]
+pub struct DigestSession {
+    pub id: String,
+    pub agent: String,
+    pub icon: String,
+    /// Project name; sessions without one are listed under "Other"
+    pub project: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub outcome: SessionOutcome,
+    /// Times the agent stopped to ask something
+    pub questions: usize,
+}
+
+/// Served by `GET /api/digest`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Digest {
+    /// ISO 8601 timestamps of the covered period
+    pub since: String,
+    pub until: String,
+    pub sessions: u32,
+    pub projects: u32,
+    pub markdown: String,
+}
+
+impl Digest {
+    /// Summarize the sessions that were running at some point between `since` and `until`
+    pub fn build(since: DateTime<Utc>, until: DateTime<Utc>, sessions: &[DigestSession]) -> Self {
+        let mut by_project: BTreeMap<&str, Vec<&DigestSession>> = BTreeMap::new();
+        for session in sessions {
+            let overlaps = session.started_at < until
+                && session.ended_at.is_none_or(|ended_at| ended_at >= since);
+            if overlaps {
+                by_project
+                    .entry(session.project.as_deref().unwrap_or("Other"))
+                    .or_default()
+                    .push(session);
+            }
+        }
+        let count: usize = by_project.values().map(Vec::len).sum();
+        let projects = by_project.len();
+
+        let mut markdown = format!(
+            "# codemux digest, {}\n\n",
+            until.with_timezone(&Local).format("%A %-d %B %Y")
+        );
+        let _ = writeln!(
+            markdown,
+            "{} in {} between {} and {}.",
+            plural(count, "session"),
+            plural(projects, "project"),
+            local_time(since),
+            local_time(until)
+        );
+        for (project, mut sessions) in by_project {
+            sessions.sort_by_key(|session| session.started_at);
+            let _ = write!(
+                markdown,
+                "\n## {}\n\n| Session | Agent | Started | Duration | Outcome |\n|---|---|---|---|---|\n",
+                project
+            );
+            for session in sessions {
+                let duration = session.ended_at.unwrap_or(until) - session.started_at;
+                let mut outcome = match session.outcome {
+                    SessionOutcome::Running => "still running",
+                    SessionOutcome::Exited => "exited on its own",
+                    SessionOutcome::Stopped => "stopped",
+                    SessionOutcome::Unknown => "ended, time unknown",
+                }
+                .to_string();
+                if session.questions > 0 {
+                    let _ = write!(
+                        outcome,
+                        ", asked for input {}",
+                        plural(session.questions, "time")
+                    );
+                }
+                let _ = writeln!(
+                    markdown,
+                    "| {} `{}` | {} | {} | {} | {} |",
+                    session.icon,
+                    session.id.get(..8).unwrap_or(&session.id),
+                    session.agent,
+                    local_time(session.started_at),
+                    if session.outcome == SessionOutcome::Unknown {
+                        "?".to_string()
+                    } else {
+                        format_minutes(duration.num_minutes())
+                    },
+                    outcome
+                );
+            }
+        }
+
+        Digest {
+            since: since.to_rfc3339(),
+            until: until.to_rfc3339(),
+            sessions: count as u32,
+            projects: projects as u32,
+            markdown,
+        }
+    }
+
+    /// One line for the notification center
+    pub fn summary(&self) -> String {
+        format!(
+            "Daily digest: {} in {}",
+            plural(self.sessions as usize, "session"),
+            plural(self.projects as usize, "project")
+        )
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn local_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%b %-d %H:%M")
+        .to_string()
+}
+
+fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => "<1m".to_string(),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_sessions_of_the_period_per_project() {
+        let until = Utc::now();
+        let since = until - chrono::Duration::hours(24);
+        let session = |id: &str, project: Option<&str>, hours_ago: i64, minutes: Option<i64>| {
+            let started_at = until - chrono::Duration::hours(hours_ago);
+            DigestSession {
+                id: id.to_string(),
+                agent: "claude".to_string(),
+                icon: "✳️".to_string(),
+                project: project.map(str::to_string),
+                started_at,
+                ended_at: minutes.map(|minutes| started_at + chrono::Duration::minutes(minutes)),
+                outcome: if minutes.is_some() {
+                    SessionOutcome::Exited
+                } else {
+                    SessionOutcome::Running
+                },
+                questions: 0,
+            }
+        };
+        let mut asked = session("asked-twice", Some("api"), 3, Some(95));
+        asked.questions = 2;
+        let sessions = vec![
+            asked,
+            session("overnight", Some("api"), 30, None),
+            session("web-fix", Some("web"), 2, Some(10)),
+            // Ended before the period
+            session("last-week", Some("web"), 48, Some(60)),
+        ];
+
+        let digest = Digest::build(since, until, &sessions);
+        assert_eq!(digest.sessions, 3);
+        assert_eq!(digest.projects, 2);
+        assert_eq!(digest.summary(), "Daily digest: 3 sessions in 2 projects");
+        assert!(digest.markdown.contains("\n## api\n"));
+        assert!(digest
+            .markdown
+            .contains("| 1h 35m | exited on its own, asked for input 2 times |"));
+        assert!(digest.markdown.contains("| 30h 0m | still running |"));
+        assert!(!digest.markdown.contains("last-wee"));
+
+        let config: DigestConfig = toml::from_str("enabled = true\nat = \"06:30\"").unwrap();
+        assert_eq!(config.at, NaiveTime::from_hms_opt(6, 30, 0).unwrap());
+        assert!(toml::from_str::<DigestConfig>("at = \"early\"").is_err());
+    }
+}
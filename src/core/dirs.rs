@@ -0,0 +1,138 @@
+use std::path::{Path, PathBuf};
+
+use super::config::ServerConfig;
+
+pub const DATA_DIR_ENV: &str = "CODEMUX_DATA_DIR";
+pub const CACHE_DIR_ENV: &str = "CODEMUX_CACHE_DIR";
+pub const RUNTIME_DIR_ENV: &str = "CODEMUX_RUNTIME_DIR";
+pub const LOG_DIR_ENV: &str = "CODEMUX_LOG_DIR";
+
+/// Name of the PID file inside the runtime directory
+pub const PID_FILE_NAME: &str = "server.pid";
+/// Name of the file the running server keeps locked, see `server::lifecycle`
+pub const LOCK_FILE_NAME: &str = "server.lock";
+
+/// One of the directories codemux writes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirKind {
+    /// Persistent state: session history, crash dumps, SSH host key
+    Data,
+    /// Regenerable files that may be deleted at any time, e.g. saved pastes
+    Cache,
+    /// Per-boot files such as the PID file, the server's lock and sockets
+    Runtime,
+    /// Server log files
+    Log,
+}
+
+impl DirKind {
+    pub const ALL: [DirKind; 4] = [
+        DirKind::Data,
+        DirKind::Cache,
+        DirKind::Runtime,
+        DirKind::Log,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DirKind::Data => "data",
+            DirKind::Cache => "cache",
+            DirKind::Runtime => "runtime",
+            DirKind::Log => "log",
+        }
+    }
+
+    pub fn env_var(self) -> &'static str {
+        match self {
+            DirKind::Data => DATA_DIR_ENV,
+            DirKind::Cache => CACHE_DIR_ENV,
+            DirKind::Runtime => RUNTIME_DIR_ENV,
+            DirKind::Log => LOG_DIR_ENV,
+        }
+    }
+
+    /// Path configured for this directory in `[server]`
+    pub fn path(self, server: &ServerConfig) -> &Path {
+        match self {
+            DirKind::Data => &server.data_dir,
+            DirKind::Cache => &server.cache_dir,
+            DirKind::Runtime => &server.runtime_dir,
+            DirKind::Log => &server.log_dir,
+        }
+    }
+
+    fn path_mut(self, server: &mut ServerConfig) -> &mut PathBuf {
+        match self {
+            DirKind::Data => &mut server.data_dir,
+            DirKind::Cache => &mut server.cache_dir,
+            DirKind::Runtime => &mut server.runtime_dir,
+            DirKind::Log => &mut server.log_dir,
+        }
+    }
+}
+
+/// Replace configured directories with any set through `CODEMUX_*_DIR`
+///
+/// Environment variables win over the config file so packagers and service
+/// managers can relocate state without editing it.
+pub fn apply_env_overrides(server: &mut ServerConfig) {
+    for kind in DirKind::ALL {
+        if let Some(dir) = std::env::var_os(kind.env_var()).filter(|dir| !dir.is_empty()) {
+            *kind.path_mut(server) = PathBuf::from(dir);
+        }
+    }
+}
+
+/// Create a directory of the given kind, keeping the runtime dir private to the user
+#[cfg_attr(not(unix), allow(unused_variables))]
+pub fn create_dir(kind: DirKind, path: &Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    if kind == DirKind::Runtime {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+fn project_dirs() -> Option<directories::ProjectDirs> {
+    directories::ProjectDirs::from("com", "codemux", "codemux")
+}
+
+/// Directory holding `config.toml`
+pub fn config_dir() -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+/// `$XDG_DATA_HOME/codemux` or the platform equivalent
+pub fn default_data_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.data_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from(".codemux"))
+}
+
+/// `$XDG_CACHE_HOME/codemux` or the platform equivalent
+pub fn default_cache_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| default_data_dir().join("cache"))
+}
+
+/// `$XDG_RUNTIME_DIR/codemux`, falling back to the data dir where there is no runtime dir
+pub fn default_runtime_dir() -> PathBuf {
+    project_dirs()
+        .and_then(|dirs| dirs.runtime_dir().map(Path::to_path_buf))
+        .unwrap_or_else(|| default_data_dir().join("run"))
+}
+
+/// `$XDG_STATE_HOME/codemux/logs`, or `logs` under the local data dir elsewhere
+pub fn default_log_dir() -> PathBuf {
+    project_dirs()
+        .map(|dirs| {
+            dirs.state_dir()
+                .unwrap_or_else(|| dirs.data_local_dir())
+                .join("logs")
+        })
+        .unwrap_or_else(|| default_data_dir().join("logs"))
+}
@@ -0,0 +1,221 @@
+//! Example JSON:API documents, the way the server sends them
+//!
+//! They are built from the same types as the real responses, with fixed IDs
+//! and timestamps, so the snapshot tests catch accidental changes to the wire
+//! format and the web app can be worked on without a server running
+//! (`codemux dev fixtures`).
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::api_tokens::{ApiToken, NewApiToken, TokenScope};
+use super::json_api::{json_api_error, json_api_response, ProjectRelationships, SessionResourceTS};
+use super::session::{
+    default_session_color, default_session_icon, DiffStats, ProjectAttributes, SessionAttributes,
+    SessionType,
+};
+use super::session_links::{SessionLink, SessionLinkKind, SessionRelationships};
+use super::{JsonApiResource, ProjectResource, SessionResource};
+
+const PROJECT_ID: &str = "6f1c2a4e-8b3d-4e59-9a7f-1d2c3b4a5e6f";
+const SUBPROJECT_ID: &str = "0a9b8c7d-6e5f-4a3b-8c2d-1e0f9a8b7c6d";
+const SESSION_ID: &str = "3f2b8c1e-5d47-4a9e-9c61-0b7e2d4f8a10";
+const RETRY_SESSION_ID: &str = "9d4e7a21-3c58-4b6f-8e90-5a1b2c3d4e5f";
+const HISTORICAL_SESSION_ID: &str = "c1d2e3f4-a5b6-4c7d-8e9f-0a1b2c3d4e5f";
+const NOW: &str = "2025-09-02T14:30:00+00:00";
+
+/// Each example document by name, as the server would serialize it
+pub fn fixtures() -> Result<Vec<(&'static str, Value)>> {
+    let sessions = [running_session(), killed_session(), historical_session()];
+    Ok(vec![
+        ("session", to_value(json_api_response(session_detail()))?),
+        ("session-killed", to_value(json_api_response(&sessions[1]))?),
+        (
+            "session-historical",
+            to_value(json_api_response(&sessions[2]))?,
+        ),
+        ("projects", to_value(json_api_response(projects()))?),
+        ("api-token", to_value(json_api_response(new_api_token()))?),
+        (
+            "error-not-found",
+            to_value(json_api_error(
+                "404".to_string(),
+                "Session Not Found".to_string(),
+                format!("Session with id '{}' not found", SESSION_ID),
+            ))?,
+        ),
+    ])
+}
+
+fn to_value(document: impl Serialize) -> Result<Value> {
+    Ok(serde_json::to_value(document)?)
+}
+
+fn session(id: &str, agent: &str, attributes: SessionAttributes) -> SessionResource {
+    JsonApiResource {
+        resource_type: "session".to_string(),
+        id: id.to_string(),
+        attributes: Some(SessionAttributes {
+            agent: agent.to_string(),
+            color: default_session_color(id),
+            icon: default_session_icon(agent),
+            ..attributes
+        }),
+        relationships: None,
+    }
+}
+
+fn attributes(status: &str, session_type: SessionType) -> SessionAttributes {
+    SessionAttributes {
+        agent: String::new(),
+        project: Some(PROJECT_ID.to_string()),
+        status: status.to_string(),
+        session_type,
+        last_modified: Some(NOW.to_string()),
+        last_message: None,
+        color: String::new(),
+        icon: String::new(),
+        note: None,
+        name: None,
+        terminates_at: None,
+        idle_expires_at: None,
+        diff_stats: None,
+        exit_code: None,
+    }
+}
+
+/// A named session started from a template as a retry of another, with a note
+fn running_session() -> SessionResource {
+    let mut session = session(
+        SESSION_ID,
+        "claude",
+        SessionAttributes {
+            note: Some("waiting on CI".to_string()),
+            name: Some("fix-login".to_string()),
+            idle_expires_at: Some("2025-09-02T16:30:00+00:00".to_string()),
+            ..attributes("running", SessionType::Active)
+        },
+    );
+    session.relationships = SessionRelationships::from_links(&[
+        SessionLink {
+            kind: SessionLinkKind::SpawnedFromTemplate,
+            id: "review".to_string(),
+        },
+        SessionLink {
+            kind: SessionLinkKind::RetryOf,
+            id: RETRY_SESSION_ID.to_string(),
+        },
+    ]);
+    session
+}
+
+/// The running session as `GET /api/sessions/:id` sends it, with its
+/// uncommitted changes
+fn session_detail() -> SessionResource {
+    let mut session = running_session();
+    if let Some(attributes) = session.attributes.as_mut() {
+        attributes.diff_stats = Some(DiffStats {
+            files: 3,
+            additions: 42,
+            deletions: 7,
+        });
+    }
+    session
+}
+
+/// A killed session in its `[recycle_bin]` grace period
+fn killed_session() -> SessionResource {
+    session(
+        RETRY_SESSION_ID,
+        "gemini",
+        SessionAttributes {
+            terminates_at: Some("2025-09-02T14:30:30+00:00".to_string()),
+            ..attributes("terminating", SessionType::Active)
+        },
+    )
+}
+
+/// A session from the Claude history
+fn historical_session() -> SessionResource {
+    session(
+        HISTORICAL_SESSION_ID,
+        "claude",
+        SessionAttributes {
+            last_message: Some("Added the migration and updated the tests.".to_string()),
+            ..attributes("completed", SessionType::Historical)
+        },
+    )
+}
+
+fn as_ts(session: SessionResource) -> SessionResourceTS {
+    SessionResourceTS {
+        resource_type: session.resource_type,
+        id: session.id,
+        attributes: session.attributes,
+        relationships: session.relationships,
+    }
+}
+
+/// A project with its sessions and a sub-project
+fn projects() -> Vec<ProjectResource> {
+    let project = |id: &str, name: &str, path: &str| ProjectResource {
+        resource_type: "project".to_string(),
+        id: id.to_string(),
+        attributes: Some(ProjectAttributes {
+            name: name.to_string(),
+            path: path.to_string(),
+            color: default_session_color(name),
+            icon: "🦀".to_string(),
+            remote: Some("git@github.com:example/shop.git".to_string()),
+            defaults: None,
+        }),
+        relationships: None,
+    };
+
+    let mut parent = project(PROJECT_ID, "shop", "/home/dev/shop");
+    parent.relationships = Some(ProjectRelationships {
+        recent_sessions: Some(
+            [running_session(), killed_session(), historical_session()]
+                .into_iter()
+                .map(as_ts)
+                .collect(),
+        ),
+        ..Default::default()
+    });
+    let mut subproject = project(SUBPROJECT_ID, "api", "/home/dev/shop/api");
+    subproject.relationships = super::project_groups::parent_relationships(Some(PROJECT_ID));
+    vec![parent, subproject]
+}
+
+fn new_api_token() -> JsonApiResource<NewApiToken> {
+    let token = ApiToken {
+        id: "5b6c7d8e-9f0a-4b1c-8d2e-3f4a5b6c7d8e".to_string(),
+        name: "github-actions".to_string(),
+        scope: TokenScope::Read,
+        created_at: "2025-09-02T14:30:00Z".to_string(),
+        expires_at: Some("2025-09-09T14:30:00Z".to_string()),
+        session_id: None,
+    };
+    JsonApiResource {
+        resource_type: "api-token".to_string(),
+        id: token.id.clone(),
+        attributes: Some(NewApiToken {
+            token,
+            secret: "cmx_0123456789abcdef0123456789abcdef".to_string(),
+        }),
+        relationships: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_match_snapshots() {
+        for (name, document) in fixtures().unwrap() {
+            insta::assert_json_snapshot!(name, document);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Binary WebSocket frames for grid updates and output
+//!
+//! A keyframe spells out every cell of the terminal as JSON, which adds up for
+//! a 200x60 screen on a remote connection. Clients that connect with the
+//! `msgpack` capability get grid updates and output as MessagePack in binary
+//! frames instead; with `deflate` those frames are compressed as well (raw
+//! DEFLATE, as `DecompressionStream("deflate-raw")` reads it). axum's WebSocket
+//! has no permessage-deflate, so compression is negotiated with the other
+//! capabilities and done per message. Everything else stays JSON text.
+
+use anyhow::{Context, Result};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+use super::pty_session::GridUpdateMessage;
+use super::websocket::{ProtocolCapability, ServerMessage};
+
+/// How a connection encodes its grid updates and output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameEncoding {
+    pub msgpack: bool,
+    pub deflate: bool,
+    /// Keyframes are run-length encoded, see `run_length`
+    pub rle: bool,
+}
+
+impl FrameEncoding {
+    /// The encoding the enabled `capabilities` call for
+    pub fn new(capabilities: &[ProtocolCapability]) -> Self {
+        FrameEncoding {
+            msgpack: capabilities.contains(&ProtocolCapability::Msgpack),
+            deflate: capabilities.contains(&ProtocolCapability::Deflate),
+            rle: capabilities.contains(&ProtocolCapability::Rle),
+        }
+    }
+
+    /// Shrink `update` as far as the client can read it back
+    pub fn compact(&self, update: &mut GridUpdateMessage) {
+        if self.rle {
+            update.run_length_encode();
+        }
+    }
+
+    /// Whether `message` goes out as a binary frame rather than JSON text
+    pub fn is_binary(&self, message: &ServerMessage) -> bool {
+        (self.msgpack || self.deflate)
+            && matches!(
+                message,
+                ServerMessage::GridUpdate { .. } | ServerMessage::Output { .. }
+            )
+    }
+
+    /// `message` as a binary frame: MessagePack or JSON, deflated if asked for
+    pub fn encode(&self, message: &ServerMessage) -> Result<Vec<u8>> {
+        let payload = if self.msgpack {
+            // Named fields, so the `type` tag and flattened updates survive
+            rmp_serde::to_vec_named(message)?
+        } else {
+            serde_json::to_vec(message)?
+        };
+        if !self.deflate {
+            return Ok(payload);
+        }
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&payload)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Read a binary frame written by `encode`
+    pub fn decode(&self, frame: &[u8]) -> Result<ServerMessage> {
+        let mut inflated = Vec::new();
+        let payload = if self.deflate {
+            DeflateDecoder::new(frame)
+                .read_to_end(&mut inflated)
+                .context("Invalid deflated frame")?;
+            &inflated[..]
+        } else {
+            frame
+        };
+        if self.msgpack {
+            Ok(rmp_serde::from_slice(payload)?)
+        } else {
+            Ok(serde_json::from_slice(payload)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::{GridCell, SerializablePtySize};
+    use crate::core::GridUpdateMessage;
+
+    fn keyframe() -> ServerMessage {
+        let cell = GridCell {
+            char: "x".to_string(),
+            fg_color: None,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        };
+        let cells = (0..60)
+            .flat_map(|row| (0..200).map(move |col| (row, col)))
+            .map(|position| (position, cell.clone()))
+            .collect();
+        ServerMessage::GridUpdate {
+            update: GridUpdateMessage::Keyframe {
+                size: SerializablePtySize {
+                    rows: 60,
+                    cols: 200,
+                },
+                cells,
+                cursor: (0, 0),
+                cursor_visible: true,
+                scrollback_position: 0,
+                scrollback_total: 0,
+                timestamp: std::time::SystemTime::now(),
+                runs: Vec::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn round_trips_and_shrinks_keyframes() {
+        let message = keyframe();
+        let json = serde_json::to_vec(&message).unwrap();
+
+        let off = FrameEncoding::new(&[ProtocolCapability::Batch]);
+        assert!(!off.is_binary(&message));
+
+        for capabilities in [
+            vec![ProtocolCapability::Msgpack],
+            vec![ProtocolCapability::Deflate],
+            vec![ProtocolCapability::Msgpack, ProtocolCapability::Deflate],
+        ] {
+            let encoding = FrameEncoding::new(&capabilities);
+            assert!(encoding.is_binary(&message));
+            assert!(!encoding.is_binary(&ServerMessage::PtySize { rows: 24, cols: 80 }));
+
+            let frame = encoding.encode(&message).unwrap();
+            assert!(frame.len() < json.len(), "{:?} didn't shrink", capabilities);
+            let decoded = encoding.decode(&frame).unwrap();
+            assert_eq!(serde_json::to_vec(&decoded).unwrap(), json);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+use ts_rs::TS;
+
+/// Width of a slice of change counts
+pub const HEATMAP_SLICE_SECONDS: u64 = 5;
+/// Number of slices summed into a heatmap (the last minute)
+pub const HEATMAP_SLICE_COUNT: usize = 12;
+
+/// How often each cell of a session's screen changed over the recent window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SessionHeatmap {
+    #[ts(type = "number")]
+    pub window_seconds: u64,
+    pub rows: u16,
+    pub cols: u16,
+    /// Changes per cell in row-major order, `rows * cols` entries
+    pub counts: Vec<u32>,
+    /// Highest entry of `counts`, for scaling an overlay
+    pub max: u32,
+    /// Grid diffs sent in the window
+    pub diffs: u32,
+    /// Full keyframes sent in the window; they repaint every cell and are not in `counts`
+    pub keyframes: u32,
+}
+
+#[derive(Debug, Default)]
+struct HeatmapSlice {
+    start: u64,
+    counts: HashMap<(u16, u16), u32>,
+    diffs: u32,
+    keyframes: u32,
+}
+
+/// Per-cell change counters of one PTY session over a sliding window
+///
+/// Used to find the screen regions an agent repaints over and over, which are
+/// the ones worth tuning the diff for.
+#[derive(Debug, Default)]
+pub struct ChangeHeatmap {
+    slices: VecDeque<HeatmapSlice>,
+}
+
+impl ChangeHeatmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a grid diff touching `cells`
+    pub fn record_diff(&mut self, cells: impl IntoIterator<Item = (u16, u16)>) {
+        let slice = self.slice_at(now_secs());
+        slice.diffs += 1;
+        for cell in cells {
+            *slice.counts.entry(cell).or_default() += 1;
+        }
+    }
+
+    pub fn record_keyframe(&mut self) {
+        self.slice_at(now_secs()).keyframes += 1;
+    }
+
+    /// Sum the window into a `rows` x `cols` grid; cells outside it are dropped
+    pub fn snapshot(&mut self, rows: u16, cols: u16) -> SessionHeatmap {
+        self.snapshot_at(now_secs(), rows, cols)
+    }
+
+    fn snapshot_at(&mut self, now: u64, rows: u16, cols: u16) -> SessionHeatmap {
+        self.expire(now);
+
+        let mut heatmap = SessionHeatmap {
+            window_seconds: HEATMAP_SLICE_SECONDS * HEATMAP_SLICE_COUNT as u64,
+            rows,
+            cols,
+            counts: vec![0; rows as usize * cols as usize],
+            ..Default::default()
+        };
+        for slice in &self.slices {
+            heatmap.diffs += slice.diffs;
+            heatmap.keyframes += slice.keyframes;
+            for (&(row, col), &count) in &slice.counts {
+                if row < rows && col < cols {
+                    heatmap.counts[row as usize * cols as usize + col as usize] += count;
+                }
+            }
+        }
+        heatmap.max = heatmap.counts.iter().copied().max().unwrap_or(0);
+        heatmap
+    }
+
+    fn slice_at(&mut self, now: u64) -> &mut HeatmapSlice {
+        let start = now - now % HEATMAP_SLICE_SECONDS;
+        if self.slices.back().map(|slice| slice.start) != Some(start) {
+            self.slices.push_back(HeatmapSlice {
+                start,
+                ..Default::default()
+            });
+            self.expire(now);
+        }
+        self.slices.back_mut().expect("slice was just ensured")
+    }
+
+    /// Drop slices that fell out of the window
+    fn expire(&mut self, now: u64) {
+        let window = HEATMAP_SLICE_SECONDS * HEATMAP_SLICE_COUNT as u64;
+        while self
+            .slices
+            .front()
+            .is_some_and(|slice| slice.start + window <= now)
+        {
+            self.slices.pop_front();
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sums_recent_changes_per_cell() {
+        let mut heatmap = ChangeHeatmap::new();
+        heatmap.slice_at(1000).counts.insert((0, 1), 3);
+        heatmap.slice_at(1000).diffs += 3;
+        let slice = heatmap.slice_at(1012);
+        slice.counts.insert((0, 1), 2);
+        slice.counts.insert((1, 0), 1);
+        // Outside the requested grid
+        slice.counts.insert((5, 5), 9);
+        slice.keyframes += 1;
+
+        let snapshot = heatmap.snapshot_at(1015, 2, 2);
+        assert_eq!(snapshot.counts, vec![0, 5, 1, 0]);
+        assert_eq!(snapshot.max, 5);
+        assert_eq!(snapshot.diffs, 3);
+        assert_eq!(snapshot.keyframes, 1);
+
+        // A minute later only the second slice is left
+        let snapshot = heatmap.snapshot_at(1065, 2, 2);
+        assert_eq!(snapshot.counts, vec![0, 2, 1, 0]);
+        assert_eq!(snapshot.diffs, 0);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Moments in a session's life a hook can fire on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    SessionCreated,
+    /// The agent asked a question or waits for approval
+    PromptDetected,
+    /// The agent exited on its own
+    AgentExited,
+    /// The session was ended by a kill or for sitting idle
+    SessionKilled,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::SessionCreated => "session_created",
+            HookEvent::PromptDetected => "prompt_detected",
+            HookEvent::AgentExited => "agent_exited",
+            HookEvent::SessionKilled => "session_killed",
+        }
+    }
+}
+
+/// A `[[hooks]]` entry: a shell command, a URL to POST to, or both
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hook {
+    /// Events it fires on; all of them when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<HookEvent>,
+    /// Run with `sh -c` in the session's directory, given `CODEMUX_*` variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Receives a `POST` with the `HookPayload` as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl Hook {
+    pub fn fires_on(&self, event: HookEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// The session a hook fires for
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HookSession {
+    pub session_id: String,
+    pub agent: String,
+    /// Project name
+    pub project: Option<String>,
+    /// The session's working directory
+    pub path: PathBuf,
+    pub name: Option<String>,
+}
+
+/// What a hook is told, as JSON or as environment variables
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    #[serde(flatten)]
+    pub session: HookSession,
+    /// Set for `agent_exited` when the code could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<u32>,
+    /// The question or approval prompt, for `prompt_detected`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// RFC 3339
+    pub timestamp: String,
+}
+
+impl HookPayload {
+    pub fn new(event: HookEvent, session: HookSession) -> Self {
+        HookPayload {
+            event,
+            session,
+            exit_code: None,
+            prompt: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// `CODEMUX_*` variables for hook commands; unset fields are left out
+    pub fn env(&self) -> Vec<(&'static str, String)> {
+        let session = &self.session;
+        let mut env = vec![
+            ("CODEMUX_EVENT", self.event.as_str().to_string()),
+            ("CODEMUX_SESSION_ID", session.session_id.clone()),
+            ("CODEMUX_AGENT", session.agent.clone()),
+            ("CODEMUX_SESSION_PATH", session.path.display().to_string()),
+            ("CODEMUX_TIMESTAMP", self.timestamp.clone()),
+        ];
+        let optional = [
+            ("CODEMUX_PROJECT", session.project.clone()),
+            ("CODEMUX_SESSION_NAME", session.name.clone()),
+            (
+                "CODEMUX_EXIT_CODE",
+                self.exit_code.map(|code| code.to_string()),
+            ),
+            ("CODEMUX_PROMPT", self.prompt.clone()),
+        ];
+        env.extend(
+            optional
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value?))),
+        );
+        env
+    }
+}
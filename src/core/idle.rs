@@ -0,0 +1,154 @@
+//! Ending sessions nobody uses
+//!
+//! With `[session] idle_timeout_secs` set, the server ends sessions that
+//! produced no output while no client was attached for that long, so agents
+//! forgotten in a corner don't hold memory and API quota for days. Sessions
+//! started with `--keep-alive` are left alone. `codemux list` warns about
+//! sessions close to being ended.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long before the end `codemux list` warns about an idle session
+pub const EXPIRY_WARNING: Duration = Duration::from_secs(15 * 60);
+
+/// Settings of every session, configured as `[session]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Seconds without output and without clients after which a session is
+    /// ended; 0 keeps sessions until they exit
+    pub idle_timeout_secs: u64,
+}
+
+impl SessionConfig {
+    /// How long sessions may sit idle, `None` if they never end for it
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_secs > 0).then(|| Duration::from_secs(self.idle_timeout_secs))
+    }
+}
+
+/// A session's output and attached clients, as far as being idle goes
+///
+/// Shared by the session's PTY tasks, which report output, and every client
+/// connection, which holds a `ClientGuard` while attached.
+#[derive(Clone)]
+pub struct IdleTracker {
+    inner: Arc<IdleState>,
+}
+
+struct IdleState {
+    clients: AtomicUsize,
+    /// Last output, or the last client leaving if that came later
+    last_used: Mutex<Instant>,
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        IdleTracker {
+            inner: Arc::new(IdleState {
+                clients: AtomicUsize::new(0),
+                last_used: Mutex::new(Instant::now()),
+            }),
+        }
+    }
+}
+
+impl IdleTracker {
+    /// The agent wrote something
+    pub fn output(&self) {
+        self.touch();
+    }
+
+    /// A client attached; it counts as attached until the guard is dropped
+    pub fn connect(&self) -> ClientGuard {
+        self.inner.clients.fetch_add(1, Ordering::SeqCst);
+        ClientGuard {
+            tracker: self.clone(),
+        }
+    }
+
+    pub fn clients(&self) -> usize {
+        self.inner.clients.load(Ordering::SeqCst)
+    }
+
+    /// How long the session has gone without output and without clients
+    pub fn idle_for(&self) -> Duration {
+        if self.clients() > 0 {
+            return Duration::ZERO;
+        }
+        self.last_used().elapsed()
+    }
+
+    /// When the session ends if it stays idle, as RFC 3339; `None` while a
+    /// client is attached
+    pub fn expires_at(&self, timeout: Duration) -> Option<String> {
+        if self.clients() > 0 {
+            return None;
+        }
+        let left = timeout.saturating_sub(self.idle_for());
+        let left = chrono::Duration::from_std(left).ok()?;
+        Some((chrono::Utc::now() + left).to_rfc3339())
+    }
+
+    fn last_used(&self) -> Instant {
+        *self
+            .inner
+            .last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn touch(&self) {
+        *self
+            .inner
+            .last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+}
+
+/// Keeps a session from counting as idle while a client is attached
+pub struct ClientGuard {
+    tracker: IdleTracker,
+}
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        // The idle time starts over from when the last client left
+        self.tracker.touch();
+        self.tracker.inner.clients.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_are_not_idle_while_a_client_is_attached() {
+        let tracker = IdleTracker::default();
+        let timeout = Duration::from_secs(600);
+
+        let first = tracker.connect();
+        let second = tracker.connect();
+        assert_eq!(tracker.clients(), 2);
+        assert_eq!(tracker.idle_for(), Duration::ZERO);
+        assert_eq!(tracker.expires_at(timeout), None);
+
+        drop(first);
+        assert_eq!(tracker.idle_for(), Duration::ZERO);
+        drop(second);
+        assert_eq!(tracker.clients(), 0);
+        assert!(tracker.idle_for() < Duration::from_secs(1));
+        let expires_at = tracker.expires_at(timeout).unwrap();
+        let left = chrono::DateTime::parse_from_rfc3339(&expires_at)
+            .unwrap()
+            .signed_duration_since(chrono::Utc::now());
+        assert!(left.num_seconds() > 590);
+
+        assert_eq!(SessionConfig::default().idle_timeout(), None);
+    }
+}
@@ -0,0 +1,266 @@
+//! Who may type into a session
+//!
+//! Any number of clients can watch a session - the web UI on two machines, the
+//! TUI, an SSH login - and by default all of them can type, so two people
+//! typing at once interleave their keystrokes. A session's input mode can let
+//! only one client write at a time instead:
+//!
+//! - `shared`: every client writes (the default)
+//! - `exclusive`: one client holds write control until it releases it or
+//!   disconnects; asking for control while someone else has it is refused
+//! - `round_robin`: clients asking while control is held wait their turn, and
+//!   control passes to the next one when the holder lets go
+//! - `read_only`: nobody writes, the session is only broadcast
+//!
+//! Typing while nobody holds control takes it, so clients that have no way to
+//! ask for control, like SSH logins, still work when they're alone.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum InputMode {
+    #[default]
+    Shared,
+    Exclusive,
+    RoundRobin,
+    ReadOnly,
+}
+
+/// Input mode new sessions start with
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputConfig {
+    pub mode: InputMode,
+}
+
+/// A session's input mode and who holds write control, as sent to its clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct InputControl {
+    pub mode: InputMode,
+    /// Client that holds write control
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub holder: Option<String>,
+    /// Clients waiting for control in `round_robin` mode, next first
+    #[serde(default)]
+    pub waiting: Vec<String>,
+}
+
+impl InputControl {
+    /// Whether input from `client` reaches the agent
+    pub fn can_write(&self, client: &str) -> bool {
+        may_write(self.mode, self.holder.as_deref(), client)
+    }
+}
+
+fn may_write(mode: InputMode, holder: Option<&str>, client: &str) -> bool {
+    match mode {
+        InputMode::Shared => true,
+        InputMode::ReadOnly => false,
+        InputMode::Exclusive | InputMode::RoundRobin => {
+            holder.is_none_or(|holder| holder == client)
+        }
+    }
+}
+
+/// Decides which client's input reaches the agent
+#[derive(Debug, Default)]
+pub struct InputArbiter {
+    mode: InputMode,
+    holder: Option<String>,
+    waiting: VecDeque<String>,
+}
+
+impl InputArbiter {
+    pub fn new(mode: InputMode) -> Self {
+        InputArbiter {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    /// Switch modes; the holder keeps control unless nobody can have it
+    pub fn set_mode(&mut self, mode: InputMode) {
+        self.mode = mode;
+        if matches!(mode, InputMode::Shared | InputMode::ReadOnly) {
+            self.holder = None;
+        }
+        if mode != InputMode::RoundRobin {
+            self.waiting.clear();
+        }
+    }
+
+    /// Whether input from `client` reaches the agent right now
+    pub fn can_write(&self, client: &str) -> bool {
+        may_write(self.mode, self.holder.as_deref(), client)
+    }
+
+    /// Ask for write control; returns whether anything changed
+    pub fn request(&mut self, client: &str) -> Result<bool> {
+        match (self.mode, self.holder.as_deref()) {
+            (InputMode::Shared, _) => Ok(false),
+            (InputMode::ReadOnly, _) => Err(anyhow!("This session is read-only")),
+            (_, None) => {
+                self.holder = Some(client.to_string());
+                Ok(true)
+            }
+            (_, Some(holder)) if holder == client => Ok(false),
+            (InputMode::Exclusive, Some(holder)) => {
+                Err(anyhow!("{} has control of this session", holder))
+            }
+            (InputMode::RoundRobin, Some(_)) => {
+                if self.waiting.iter().any(|waiting| waiting == client) {
+                    return Ok(false);
+                }
+                self.waiting.push_back(client.to_string());
+                Ok(true)
+            }
+        }
+    }
+
+    /// Give up write control, or a place in the queue, e.g. when `client`
+    /// disconnects; returns whether anything changed
+    pub fn release(&mut self, client: &str) -> bool {
+        if self.holder.as_deref() == Some(client) {
+            self.holder = self.waiting.pop_front();
+            return true;
+        }
+        let queued = self.waiting.len();
+        self.waiting.retain(|waiting| waiting != client);
+        self.waiting.len() != queued
+    }
+
+    pub fn state(&self) -> InputControl {
+        InputControl {
+            mode: self.mode,
+            holder: self.holder.clone(),
+            waiting: self.waiting.iter().cloned().collect(),
+        }
+    }
+}
+
+/// A session's arbiter, shared by its clients and its input task; every
+/// change is broadcast so clients can show who is typing
+#[derive(Clone)]
+pub struct InputControlHandle {
+    arbiter: Arc<Mutex<InputArbiter>>,
+    changes_tx: broadcast::Sender<InputControl>,
+}
+
+impl Default for InputControlHandle {
+    fn default() -> Self {
+        let (changes_tx, _) = broadcast::channel(16);
+        InputControlHandle {
+            arbiter: Arc::default(),
+            changes_tx,
+        }
+    }
+}
+
+impl InputControlHandle {
+    pub fn subscribe(&self) -> broadcast::Receiver<InputControl> {
+        self.changes_tx.subscribe()
+    }
+
+    pub fn state(&self) -> InputControl {
+        self.lock().state()
+    }
+
+    pub fn set_mode(&self, mode: InputMode) {
+        let mut arbiter = self.lock();
+        arbiter.set_mode(mode);
+        let _ = self.changes_tx.send(arbiter.state());
+    }
+
+    pub fn request(&self, client: &str) -> Result<()> {
+        let mut arbiter = self.lock();
+        if arbiter.request(client)? {
+            let _ = self.changes_tx.send(arbiter.state());
+        }
+        Ok(())
+    }
+
+    pub fn release(&self, client: &str) {
+        let mut arbiter = self.lock();
+        if arbiter.release(client) {
+            let _ = self.changes_tx.send(arbiter.state());
+        }
+    }
+
+    /// Whether input from `client` may be written, taking control for it
+    /// when nobody holds it
+    pub fn try_write(&self, client: &str) -> bool {
+        let mut arbiter = self.lock();
+        if !arbiter.can_write(client) {
+            return false;
+        }
+        if arbiter.request(client).unwrap_or(false) {
+            let _ = self.changes_tx.send(arbiter.state());
+        }
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, InputArbiter> {
+        self.arbiter.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_control_between_clients() {
+        let mut exclusive = InputArbiter::new(InputMode::Exclusive);
+        assert!(exclusive.can_write("web-1"));
+        assert!(exclusive.request("web-1").unwrap());
+        assert!(!exclusive.can_write("web-2"));
+        assert!(exclusive.request("web-2").is_err());
+        assert!(!exclusive.release("web-2"));
+        assert!(exclusive.release("web-1"));
+        assert!(exclusive.can_write("web-2"));
+
+        let mut round_robin = InputArbiter::new(InputMode::RoundRobin);
+        round_robin.request("web-1").unwrap();
+        assert!(round_robin.request("web-2").unwrap());
+        assert!(round_robin.request("tui").unwrap());
+        assert!(!round_robin.request("tui").unwrap());
+        assert_eq!(round_robin.state().waiting, ["web-2", "tui"]);
+        assert!(round_robin.release("web-2"));
+        assert!(round_robin.release("web-1"));
+        assert_eq!(round_robin.state().holder.as_deref(), Some("tui"));
+        assert!(!round_robin.can_write("web-1"));
+
+        round_robin.set_mode(InputMode::ReadOnly);
+        assert!(!round_robin.can_write("tui"));
+        assert!(round_robin.request("tui").is_err());
+        round_robin.set_mode(InputMode::Shared);
+        assert!(round_robin.can_write("web-1"));
+        assert_eq!(round_robin.state().holder, None);
+    }
+
+    #[test]
+    fn typing_takes_control_nobody_holds() {
+        let control = InputControlHandle::default();
+        let mut changes = control.subscribe();
+        control.set_mode(InputMode::Exclusive);
+        assert_eq!(changes.try_recv().unwrap().holder, None);
+
+        assert!(control.try_write("ssh"));
+        assert_eq!(changes.try_recv().unwrap().holder.as_deref(), Some("ssh"));
+        assert!(control.try_write("ssh"));
+        assert!(changes.try_recv().is_err());
+        assert!(!control.try_write("web-1"));
+        control.release("ssh");
+        assert!(control.try_write("web-1"));
+    }
+}
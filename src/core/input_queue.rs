@@ -0,0 +1,301 @@
+//! Backpressure and delivery acknowledgements for session input
+//!
+//! Input used to wait in an unbounded channel, so when writing to the PTY got
+//! stuck, e.g. on an agent that stopped reading its terminal, keystrokes piled
+//! up unseen and showed up seconds later all at once. The queue is bounded
+//! now:
+//!
+//! - input that doesn't fit waits a moment for room, then is refused, and the
+//!   client that sent it is told
+//! - while queued input waits longer than [`LAG_THRESHOLD`] the session counts
+//!   as lagging, which every client is told about
+//! - input sent with a sequence number is acknowledged once it was written to
+//!   the PTY, or refused, so clients can show what hasn't arrived yet
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, watch};
+
+use tokio::sync::mpsc::error::SendTimeoutError;
+pub use tokio::sync::mpsc::error::TrySendError;
+
+use crate::core::pty_session::PtyInputMessage;
+
+/// Input messages a session holds before refusing more
+pub const INPUT_QUEUE_CAPACITY: usize = 256;
+
+/// How long input may wait for the PTY before the session counts as lagging
+pub const LAG_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// How long input waits for room in a full queue before it is refused
+const FULL_QUEUE_WAIT: Duration = Duration::from_millis(250);
+
+/// How often the lag is looked at
+const LAG_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A client's input having been written to the PTY, or not
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputAck {
+    pub client_id: String,
+    pub seq: u64,
+    /// False when the input was refused or dropped, e.g. for lack of write control
+    pub written: bool,
+}
+
+struct Shared {
+    /// Messages sent and not taken by the writer yet
+    queued: AtomicUsize,
+    /// When the writer last took a message, or one arrived at an empty queue
+    progress: Mutex<Instant>,
+    acks_tx: broadcast::Sender<InputAck>,
+    lag_tx: watch::Sender<bool>,
+}
+
+impl Shared {
+    fn touch(&self) {
+        *self.progress.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn lagging_at(&self, now: Instant) -> bool {
+        let progress = *self.progress.lock().unwrap_or_else(|e| e.into_inner());
+        self.queued.load(Ordering::Relaxed) > 0 && now.duration_since(progress) > LAG_THRESHOLD
+    }
+
+    fn ack(&self, msg: &PtyInputMessage, written: bool) {
+        if let Some(seq) = msg.seq {
+            let _ = self.acks_tx.send(InputAck {
+                client_id: msg.input.client_id().to_string(),
+                seq,
+                written,
+            });
+        }
+    }
+}
+
+/// A session's input queue, one end for its clients and one for its writer
+pub fn channel() -> (InputQueue, InputReceiver) {
+    let (tx, rx) = mpsc::channel(INPUT_QUEUE_CAPACITY);
+    let (acks_tx, _) = broadcast::channel(INPUT_QUEUE_CAPACITY);
+    let shared = Arc::new(Shared {
+        queued: AtomicUsize::new(0),
+        progress: Mutex::new(Instant::now()),
+        acks_tx,
+        lag_tx: watch::Sender::new(false),
+    });
+    (
+        InputQueue {
+            tx,
+            shared: shared.clone(),
+        },
+        InputReceiver { rx, shared },
+    )
+}
+
+/// Where clients send a session's input
+#[derive(Clone)]
+pub struct InputQueue {
+    tx: mpsc::Sender<PtyInputMessage>,
+    shared: Arc<Shared>,
+}
+
+impl InputQueue {
+    /// Queue input; a burst that fills the queue waits briefly for the writer
+    /// to catch up, input that still doesn't fit, or arrives while the session
+    /// is lagging, is refused and acknowledged as not written
+    pub async fn send(&self, msg: PtyInputMessage) -> Result<(), TrySendError<PtyInputMessage>> {
+        if self.shared.queued.fetch_add(1, Ordering::Relaxed) == 0 {
+            self.shared.touch();
+        }
+        let result = match self.tx.try_send(msg) {
+            Err(TrySendError::Full(msg)) if !*self.shared.lag_tx.borrow() => self
+                .tx
+                .send_timeout(msg, FULL_QUEUE_WAIT)
+                .await
+                .map_err(|e| match e {
+                    SendTimeoutError::Timeout(msg) => TrySendError::Full(msg),
+                    SendTimeoutError::Closed(msg) => TrySendError::Closed(msg),
+                }),
+            result => result,
+        };
+        if let Err(e) = &result {
+            self.shared.queued.fetch_sub(1, Ordering::Relaxed);
+            if let TrySendError::Full(msg) = e {
+                self.shared.ack(msg, false);
+            }
+        }
+        result
+    }
+
+    /// Acknowledgements of input sent with a sequence number, for every client
+    pub fn subscribe_acks(&self) -> broadcast::Receiver<InputAck> {
+        self.shared.acks_tx.subscribe()
+    }
+
+    /// Whether the session is lagging, updated when that changes
+    pub fn subscribe_lag(&self) -> watch::Receiver<bool> {
+        self.shared.lag_tx.subscribe()
+    }
+
+    /// Pass on an acknowledgement received from elsewhere, e.g. by a client
+    /// from the server
+    pub fn publish_ack(&self, ack: InputAck) {
+        let _ = self.shared.acks_tx.send(ack);
+    }
+
+    /// Pass on a lag reported from elsewhere
+    pub fn publish_lag(&self, lagging: bool) {
+        self.shared
+            .lag_tx
+            .send_if_modified(|current| std::mem::replace(current, lagging) != lagging);
+    }
+}
+
+/// The writer's end of the queue
+pub struct InputReceiver {
+    rx: mpsc::Receiver<PtyInputMessage>,
+    shared: Arc<Shared>,
+}
+
+impl InputReceiver {
+    pub async fn recv(&mut self) -> Option<PtyInputMessage> {
+        let msg = self.rx.recv().await?;
+        self.shared.queued.fetch_sub(1, Ordering::Relaxed);
+        self.shared.touch();
+        Some(msg)
+    }
+
+    /// Tell the client that sent `msg` whether it reached the PTY
+    pub fn ack(&self, msg: &PtyInputMessage, written: bool) {
+        self.shared.ack(msg, written);
+    }
+
+    /// Keep the lag subscribers up to date; never returns, so run it next to
+    /// the writer rather than in it, a stuck write would stop it too
+    pub fn watch_lag(&self) -> impl std::future::Future<Output = ()> + 'static {
+        let shared = self.shared.clone();
+        async move {
+            let mut interval = tokio::time::interval(LAG_CHECK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let lagging = shared.lagging_at(Instant::now());
+                if shared
+                    .lag_tx
+                    .send_if_modified(|current| std::mem::replace(current, lagging) != lagging)
+                {
+                    tracing::debug!("Input lagging: {}", lagging);
+                }
+            }
+        }
+    }
+}
+
+/// Input a client numbered and has no acknowledgement for yet
+#[derive(Debug, Default)]
+pub struct PendingInput {
+    next_seq: u64,
+    sent: BTreeMap<u64, Instant>,
+}
+
+impl PendingInput {
+    /// Number the next input and start waiting for it
+    pub fn track(&mut self) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.sent.insert(seq, Instant::now());
+        seq
+    }
+
+    /// Stop waiting for `seq`; input is written in order, so a written one
+    /// also settles everything sent before it
+    pub fn acknowledge(&mut self, seq: u64, written: bool) {
+        if written {
+            self.sent = self.sent.split_off(&(seq + 1));
+        } else {
+            self.sent.remove(&seq);
+        }
+    }
+
+    /// Forget what's outstanding, e.g. after reconnecting to the server
+    pub fn clear(&mut self) {
+        self.sent.clear();
+    }
+
+    /// How much input has waited longer than `after` at `now`
+    pub fn overdue(&self, now: Instant, after: Duration) -> usize {
+        self.sent
+            .values()
+            .filter(|sent| now.duration_since(**sent) > after)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::PtyInput;
+
+    fn key(seq: u64) -> PtyInputMessage {
+        PtyInputMessage {
+            input: PtyInput::Raw {
+                data: b"x".to_vec(),
+                client_id: "tui".to_string(),
+            },
+            seq: Some(seq),
+            span: tracing::Span::none(),
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_input_past_capacity_and_reports_lag() {
+        let (queue, mut receiver) = channel();
+        let mut acks = queue.subscribe_acks();
+        for seq in 0..INPUT_QUEUE_CAPACITY as u64 {
+            queue.send(key(seq)).await.unwrap();
+        }
+        assert!(matches!(
+            queue.send(key(999)).await,
+            Err(TrySendError::Full(_))
+        ));
+        assert_eq!(
+            acks.try_recv().unwrap(),
+            InputAck {
+                client_id: "tui".to_string(),
+                seq: 999,
+                written: false
+            }
+        );
+
+        let later = Instant::now() + LAG_THRESHOLD * 2;
+        assert!(receiver.shared.lagging_at(later));
+
+        let msg = receiver.recv().await.unwrap();
+        receiver.ack(&msg, true);
+        assert_eq!(acks.try_recv().unwrap().seq, 0);
+        assert!(!receiver.shared.lagging_at(Instant::now()));
+
+        while receiver.shared.queued.load(Ordering::Relaxed) > 0 {
+            receiver.recv().await.unwrap();
+        }
+        assert!(!receiver.shared.lagging_at(later));
+        queue.send(key(1000)).await.unwrap();
+    }
+
+    #[test]
+    fn written_input_settles_what_was_sent_before() {
+        let mut pending = PendingInput::default();
+        let first = pending.track();
+        let refused = pending.track();
+        let last = pending.track();
+        let later = Instant::now() + Duration::from_secs(1);
+        assert_eq!(pending.overdue(later, Duration::from_millis(150)), 3);
+
+        pending.acknowledge(refused, false);
+        assert_eq!(pending.overdue(later, Duration::from_millis(150)), 2);
+        pending.acknowledge(last, true);
+        assert_eq!(pending.overdue(later, Duration::ZERO), 0);
+        assert_ne!(first, last);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use crate::core::pty_session::{KeyCode, KeyEvent};
+
+/// Number of sent messages kept per session
+const MAX_MESSAGES: usize = 50;
+
+/// Messages typed into an agent, split where Enter was pressed
+///
+/// Rebuilt from the key presses, raw bytes and pastes clients send, so it is a
+/// best effort: cursor movement and the agent's own editing keys are not
+/// replayed, only typing, Backspace and line clears.
+#[derive(Debug, Default)]
+pub struct InputTranscript {
+    /// Text typed since the last Enter
+    line: String,
+    /// Sent messages, oldest first
+    messages: VecDeque<String>,
+}
+
+impl InputTranscript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_key(&mut self, event: &KeyEvent) {
+        let modifiers = &event.modifiers;
+        match &event.code {
+            KeyCode::Char(c) if modifiers.ctrl => {
+                if matches!(c, 'c' | 'u') {
+                    self.line.clear();
+                }
+            }
+            KeyCode::Char(c) if !modifiers.alt && !modifiers.meta => self.line.push(*c),
+            KeyCode::Backspace => {
+                self.line.pop();
+            }
+            // Shift/Alt+Enter add a newline to the message in most agents
+            KeyCode::Enter if modifiers.shift || modifiers.alt => self.line.push('\n'),
+            KeyCode::Enter => self.submit(),
+            _ => {}
+        }
+    }
+
+    pub fn record_bytes(&mut self, data: &[u8]) {
+        let text = String::from_utf8_lossy(data);
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' | '\n' => self.submit(),
+                '\x7f' | '\x08' => {
+                    self.line.pop();
+                }
+                '\x03' | '\x15' => self.line.clear(),
+                // Skip escape sequences such as arrow keys up to their final byte
+                '\x1b' => {
+                    if chars.next_if(|&c| c == '[' || c == 'O').is_some() {
+                        while chars
+                            .next_if(|c| !c.is_ascii_alphabetic() && *c != '~')
+                            .is_some()
+                        {}
+                        chars.next();
+                    }
+                }
+                c if c.is_control() && c != '\t' => {}
+                c => self.line.push(c),
+            }
+        }
+    }
+
+    /// Pasted text belongs to the message being typed, newlines included
+    pub fn record_paste(&mut self, text: &str) {
+        self.line.push_str(text);
+    }
+
+    /// Remove and return the last sent message
+    pub fn take_last(&mut self) -> Option<String> {
+        self.messages.pop_back()
+    }
+
+    /// Start a new message with `text` already typed, after writing it back to the agent
+    pub fn restore(&mut self, text: &str) {
+        self.line = text.to_string();
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        self.messages.iter().map(String::as_str)
+    }
+
+    fn submit(&mut self) {
+        let message = std::mem::take(&mut self.line);
+        if message.trim().is_empty() {
+            return;
+        }
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+}
+
+/// Bytes that interrupt `agent` right after a message was sent
+///
+/// Claude stops on Escape (Ctrl+C would ask to exit); other agents and shells
+/// take Ctrl+C.
+pub fn interrupt_sequence(agent: &str) -> &'static [u8] {
+    match agent {
+        "claude" => b"\x1b",
+        _ => b"\x03",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers: KeyModifiers {
+                shift: false,
+                ctrl: false,
+                alt: false,
+                meta: false,
+            },
+        }
+    }
+
+    #[test]
+    fn splits_messages_on_enter() {
+        let mut transcript = InputTranscript::new();
+        for c in "helo".chars() {
+            transcript.record_key(&key(KeyCode::Char(c)));
+        }
+        transcript.record_key(&key(KeyCode::Backspace));
+        transcript.record_key(&key(KeyCode::Enter));
+        // Arrow keys are skipped, Ctrl+U clears the line
+        transcript.record_bytes(b"fix \x1b[Athe bug\roops\x15");
+        transcript.record_paste("line one\nline two");
+        transcript.record_bytes(b"\r");
+
+        assert_eq!(
+            transcript.messages().collect::<Vec<_>>(),
+            ["hel", "fix the bug", "line one\nline two"]
+        );
+        assert_eq!(
+            transcript.take_last().as_deref(),
+            Some("line one\nline two")
+        );
+        assert_eq!(transcript.take_last().as_deref(), Some("fix the bug"));
+        assert_eq!(interrupt_sequence("claude"), b"\x1b");
+        assert_eq!(interrupt_sequence("bash"), b"\x03");
+    }
+}
@@ -1,11 +1,22 @@
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize, Deserialize)]
+use super::listing::{Page, PageLinks, PageMeta};
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct JsonApiDocument<T> {
     pub data: T,
 }
 
+/// A JSON:API document with one page of a list, see `listing`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct JsonApiPage<T> {
+    pub data: Vec<T>,
+    pub links: PageLinks,
+    pub meta: PageMeta,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonApiResource<T, R = ()> {
     #[serde(rename = "type")]
@@ -16,14 +27,22 @@ pub struct JsonApiResource<T, R = ()> {
     pub relationships: Option<R>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, TS, ToSchema)]
 #[ts(export)]
 pub struct ProjectRelationships {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub recent_sessions: Option<Vec<SessionResourceTS>>,
+    /// Project this one is a sub-project of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub parent: Option<crate::core::session_links::Relationship>,
+    /// Running sessions of the sub-projects, rolled up from all levels below
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub subproject_sessions: Option<Vec<SessionResourceTS>>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct JsonApiResourceRef {
     #[serde(rename = "type")]
@@ -32,7 +51,7 @@ pub struct JsonApiResourceRef {
 }
 
 /// JSON API error object
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct JsonApiError {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -44,12 +63,13 @@ pub struct JsonApiError {
 }
 
 /// JSON API error document
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct JsonApiErrorDocument {
     pub errors: Vec<JsonApiError>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(skip)]
+    #[schema(value_type = Option<Object>)]
     pub meta: Option<serde_json::Value>,
 }
 
@@ -73,6 +93,7 @@ pub fn json_api_error(status: String, title: String, detail: String) -> JsonApiE
 }
 
 // HTTP Response helpers
+#[cfg(feature = "server")]
 use axum::{
     http::{header, StatusCode},
     response::{IntoResponse, Response},
@@ -80,6 +101,7 @@ use axum::{
 };
 
 /// Create a JSON API response with proper Content-Type header
+#[cfg(feature = "server")]
 pub fn json_api_response_with_headers<T>(data: T) -> Response
 where
     T: Serialize,
@@ -93,7 +115,27 @@ where
     response
 }
 
+/// Create a JSON API response for one page of a list, with links to the others
+#[cfg(feature = "server")]
+pub fn json_api_page_response_with_headers<T>(page: Page<T>, links: PageLinks) -> Response
+where
+    T: Serialize,
+{
+    let document = JsonApiPage {
+        data: page.items,
+        links,
+        meta: page.meta,
+    };
+    let mut response = Json(document).into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        "application/vnd.api+json".parse().unwrap(),
+    );
+    response
+}
+
 /// Create a JSON API error response with proper Content-Type header
+#[cfg(feature = "server")]
 pub fn json_api_error_response_with_headers(
     status: StatusCode,
     title: String,
@@ -111,10 +153,13 @@ pub fn json_api_error_response_with_headers(
 // Type aliases for common JSON API resources
 pub type ProjectResource =
     JsonApiResource<crate::core::session::ProjectAttributes, ProjectRelationships>;
-pub type SessionResource = JsonApiResource<crate::core::session::SessionAttributes, ()>;
+pub type SessionResource = JsonApiResource<
+    crate::core::session::SessionAttributes,
+    crate::core::session_links::SessionRelationships,
+>;
 
 // TypeScript-exported versions for frontend
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct ProjectResourceTS {
     #[serde(rename = "type")]
@@ -125,22 +170,25 @@ pub struct ProjectResourceTS {
     pub relationships: Option<ProjectRelationships>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct SessionResourceTS {
     #[serde(rename = "type")]
     pub resource_type: String,
     pub id: String,
     pub attributes: Option<crate::core::session::SessionAttributes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub relationships: Option<crate::core::session_links::SessionRelationships>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct ProjectListResponse {
     pub data: Vec<ProjectResourceTS>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct SessionResponse {
     pub data: SessionResourceTS,
@@ -0,0 +1,367 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use super::input_control::InputMode;
+use super::session_links::SessionLink;
+
+/// Reusable session settings, configured as `[templates.<name>]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SessionTemplate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub description: Option<String>,
+    pub agent: String,
+    /// Passed to the agent before any arguments given at launch
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub worktree: bool,
+    #[serde(default)]
+    pub sandbox: bool,
+}
+
+/// Launch settings accepted by `POST /api/sessions` on top of agent, args and project
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct LaunchOptions {
+    /// Extra environment variables for the agent process
+    pub env: BTreeMap<String, String>,
+    /// Name of a configured template to start from
+    pub template: Option<String>,
+    /// Run in a new git worktree of the project instead of the project itself
+    pub worktree: bool,
+    /// Start the agent in its own sandbox mode
+    pub sandbox: bool,
+    /// Take over this already-running agent process instead of starting a new
+    /// one; only set by `POST /api/adopt` and `codemux server upgrade`
+    #[serde(skip)]
+    pub adopt_pid: Option<u32>,
+    /// Accent color (`#rrggbb`) instead of the one derived from the session ID
+    pub color: Option<String>,
+    /// Icon instead of the agent's default
+    pub icon: Option<String>,
+    /// Name to address the session by instead of its ID
+    pub name: Option<String>,
+    /// Sessions this one is a clone, retry or worktree of
+    pub links: Vec<SessionLink>,
+    /// Which clients may type instead of the configured `[input] mode`
+    pub input_mode: Option<InputMode>,
+    /// Directory to run the agent in instead of the project's, relative to it
+    /// or absolute
+    pub cwd: Option<String>,
+    /// Record the session's output under the data dir, see `codemux recordings`
+    pub record: bool,
+    /// Never end the session for sitting idle, whatever `[session] idle_timeout_secs` says
+    pub keep_alive: bool,
+}
+
+/// Agent arguments and environment after applying the template and sandbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedLaunch {
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    pub worktree: bool,
+}
+
+/// Everything the "New Session" form needs, served by `GET /api/session-options`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionOptions {
+    pub agents: Vec<AgentOption>,
+    pub templates: Vec<TemplateOption>,
+    pub projects: Vec<ProjectOption>,
+    /// Agent preselected in the form
+    pub default_agent: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentOption {
+    pub name: String,
+    /// Whether the agent's executable was found on the server's `PATH`
+    pub installed: bool,
+    pub supports_sandbox: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TemplateOption {
+    pub name: String,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub template: SessionTemplate,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ProjectOption {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub icon: String,
+    /// Worktrees can only be created for git repositories
+    pub git: bool,
+}
+
+/// Variables that decide which program runs or what it loads, which a client
+/// could use to run something other than the whitelisted agent
+const RESERVED_ENV: &[&str] = &[
+    "PATH",
+    "NODE_OPTIONS",
+    "NODE_PATH",
+    "PYTHONPATH",
+    "PYTHONHOME",
+    "PYTHONSTARTUP",
+    "PERL5OPT",
+    "PERL5LIB",
+    "RUBYOPT",
+    "RUBYLIB",
+    "BASH_ENV",
+    "ENV",
+];
+
+/// Prefixes of the dynamic loader's variables, e.g. `LD_PRELOAD`
+const RESERVED_ENV_PREFIXES: &[&str] = &["LD_", "DYLD_"];
+
+/// Environment variables from a client that can't be passed to an agent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidEnvError(String);
+
+impl std::fmt::Display for InvalidEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for InvalidEnvError {}
+
+/// Check environment variables a client asked for: names must be non-empty
+/// without `=` or NUL, values without NUL, and none of `RESERVED_ENV`
+pub fn validate_env(env: &BTreeMap<String, String>) -> Result<(), InvalidEnvError> {
+    for (key, value) in env {
+        if key.is_empty() || key.contains(['=', '\0']) || value.contains('\0') {
+            return Err(InvalidEnvError(format!(
+                "Invalid environment variable '{}'",
+                key.escape_debug()
+            )));
+        }
+        let upper = key.to_ascii_uppercase();
+        if RESERVED_ENV.contains(&upper.as_str())
+            || RESERVED_ENV_PREFIXES
+                .iter()
+                .any(|prefix| upper.starts_with(prefix))
+        {
+            return Err(InvalidEnvError(format!(
+                "Environment variable '{}' can't be set for an agent",
+                key
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Arguments that put an agent in its sandbox mode, for agents that have one
+pub fn sandbox_args(agent: &str) -> Option<&'static [&'static str]> {
+    match agent {
+        "gemini" => Some(&["--sandbox"]),
+        "codex" => Some(&["--sandbox", "workspace-write"]),
+        _ => None,
+    }
+}
+
+/// Look an executable up on `PATH`
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .flat_map(|dir| {
+            let candidate = dir.join(name);
+            let exe = cfg!(windows).then(|| candidate.with_extension("exe"));
+            [Some(candidate), exe]
+        })
+        .flatten()
+        .find(|path| path.is_file())
+}
+
+/// Combine a template, launch options and launch arguments for `agent`
+pub fn resolve_launch(
+    agent: &str,
+    args: Vec<String>,
+    options: &LaunchOptions,
+    templates: &BTreeMap<String, SessionTemplate>,
+) -> Result<ResolvedLaunch> {
+    validate_env(&options.env)?;
+    let mut resolved = ResolvedLaunch {
+        args: Vec::new(),
+        env: BTreeMap::new(),
+        worktree: options.worktree,
+    };
+    let mut sandbox = options.sandbox;
+
+    if let Some(name) = &options.template {
+        let template = templates
+            .get(name)
+            .ok_or_else(|| anyhow!("Unknown session template '{}'", name))?;
+        if template.agent != agent {
+            return Err(anyhow!(
+                "Template '{}' is for agent '{}', not '{}'",
+                name,
+                template.agent,
+                agent
+            ));
+        }
+        resolved.args.extend(template.args.iter().cloned());
+        resolved.env.extend(template.env.clone());
+        resolved.worktree |= template.worktree;
+        sandbox |= template.sandbox;
+    }
+
+    if sandbox {
+        let sandbox =
+            sandbox_args(agent).ok_or_else(|| anyhow!("Agent '{}' has no sandbox mode", agent))?;
+        resolved
+            .args
+            .extend(sandbox.iter().map(|arg| arg.to_string()));
+    }
+
+    resolved.args.extend(args);
+    resolved.env.extend(options.env.clone());
+    Ok(resolved)
+}
+
+/// Directory an agent asked to run in `cwd` starts in, checking that it exists
+///
+/// Relative paths are taken from `run_dir`, the project's directory or its
+/// worktree. Absolute paths inside the project move along into the worktree.
+pub fn resolve_cwd(project_dir: &Path, run_dir: &Path, cwd: &str) -> Result<PathBuf> {
+    let cwd = Path::new(cwd);
+    let dir = match cwd.strip_prefix(project_dir) {
+        Ok(inside) => run_dir.join(inside),
+        Err(_) => run_dir.join(cwd),
+    };
+    if !dir.is_dir() {
+        return Err(anyhow!("Working directory {} doesn't exist", dir.display()));
+    }
+    Ok(dir)
+}
+
+/// Create a git worktree of `repo` on a new `codemux/<session>` branch
+///
+/// Worktrees live under `<data_dir>/worktrees` and are left in place when the
+/// session ends so no work is lost; remove them with `git worktree remove`.
+pub fn create_worktree(repo: &Path, data_dir: &Path, session_id: &str) -> Result<PathBuf> {
+    let short_id = &session_id[..session_id.len().min(8)];
+    let worktree = data_dir.join("worktrees").join(session_id);
+    std::fs::create_dir_all(data_dir.join("worktrees"))?;
+
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["worktree", "add", "-b", &format!("codemux/{}", short_id)])
+        .arg(&worktree)
+        .output()
+        .map_err(|e| anyhow!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Failed to create worktree of {}: {}",
+            repo.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(worktree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_args_and_env_come_first_and_launch_values_win() {
+        let template = SessionTemplate {
+            description: None,
+            agent: "gemini".to_string(),
+            args: vec!["--yolo".to_string()],
+            env: BTreeMap::from([
+                ("MODE".to_string(), "template".to_string()),
+                ("KEEP".to_string(), "1".to_string()),
+            ]),
+            worktree: true,
+            sandbox: true,
+        };
+        let templates = BTreeMap::from([("review".to_string(), template)]);
+        let options = LaunchOptions {
+            env: BTreeMap::from([("MODE".to_string(), "launch".to_string())]),
+            template: Some("review".to_string()),
+            ..LaunchOptions::default()
+        };
+
+        let resolved =
+            resolve_launch("gemini", vec!["-p".to_string()], &options, &templates).unwrap();
+        assert_eq!(resolved.args, ["--yolo", "--sandbox", "-p"]);
+        assert_eq!(resolved.env["MODE"], "launch");
+        assert_eq!(resolved.env["KEEP"], "1");
+        assert!(resolved.worktree);
+
+        assert!(resolve_launch("claude", vec![], &options, &templates).is_err());
+        let sandboxed = LaunchOptions {
+            sandbox: true,
+            ..LaunchOptions::default()
+        };
+        assert!(resolve_launch("aider", vec![], &sandboxed, &templates).is_err());
+    }
+
+    #[test]
+    fn refuses_environment_variables_that_change_what_runs() {
+        let env = |key: &str| BTreeMap::from([(key.to_string(), "x".to_string())]);
+        assert!(validate_env(&env("DEBUG")).is_ok());
+        for key in [
+            "PATH",
+            "Path",
+            "LD_PRELOAD",
+            "DYLD_INSERT_LIBRARIES",
+            "NODE_OPTIONS",
+        ] {
+            assert!(validate_env(&env(key)).is_err(), "{}", key);
+        }
+        for key in ["", "A=B", "A\0B"] {
+            assert!(validate_env(&env(key)).is_err());
+        }
+
+        let options = LaunchOptions {
+            env: env("LD_PRELOAD"),
+            ..LaunchOptions::default()
+        };
+        let error = resolve_launch("claude", vec![], &options, &BTreeMap::new()).unwrap_err();
+        assert!(error.downcast_ref::<InvalidEnvError>().is_some());
+    }
+
+    #[test]
+    fn resolves_working_directories_inside_the_project_or_its_worktree() {
+        let base = std::env::temp_dir().join(format!("codemux-cwd-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let (project, worktree) = (base.join("repo"), base.join("worktree"));
+        std::fs::create_dir_all(project.join("api")).unwrap();
+        std::fs::create_dir_all(worktree.join("api")).unwrap();
+
+        assert_eq!(
+            resolve_cwd(&project, &project, "api").unwrap(),
+            project.join("api")
+        );
+        let absolute = project.join("api").display().to_string();
+        assert_eq!(
+            resolve_cwd(&project, &worktree, &absolute).unwrap(),
+            worktree.join("api")
+        );
+        let elsewhere = base.display().to_string();
+        assert_eq!(resolve_cwd(&project, &project, &elsewhere).unwrap(), base);
+        assert!(resolve_cwd(&project, &project, "web").is_err());
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
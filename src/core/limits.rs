@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Caps on running sessions, configured as `[limits]`
+///
+/// They protect shared servers from scripts that spawn sessions in a loop.
+/// Per-agent caps are set with `max_sessions` under `[agents.<name>]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Running sessions on the whole server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_sessions: Option<usize>,
+    /// Running sessions in any one project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_sessions_per_project: Option<usize>,
+    /// Hold new sessions until a slot frees up instead of failing right away
+    pub queue: bool,
+    /// How long a queued session waits for a slot before failing
+    pub queue_timeout_secs: u64,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_sessions: None,
+            max_sessions_per_project: None,
+            queue: false,
+            queue_timeout_secs: 300,
+        }
+    }
+}
+
+/// Sessions already running where a new one would start
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunningSessions {
+    pub total: usize,
+    pub in_project: usize,
+    pub of_agent: usize,
+}
+
+/// Which cap a new session would exceed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitScope {
+    Server,
+    Project,
+    Agent(String),
+}
+
+/// A session was refused because a cap is reached, or it waited too long for a slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionLimitError {
+    Reached { scope: LimitScope, limit: usize },
+    QueueTimeout(Duration),
+}
+
+impl fmt::Display for SessionLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionLimitError::Reached { scope, limit } => {
+                let scope = match scope {
+                    LimitScope::Server => "on this server".to_string(),
+                    LimitScope::Project => "in this project".to_string(),
+                    LimitScope::Agent(agent) => format!("for agent '{}'", agent),
+                };
+                write!(
+                    f,
+                    "Session limit reached: {} running sessions allowed {}",
+                    limit, scope
+                )
+            }
+            SessionLimitError::QueueTimeout(waited) => write!(
+                f,
+                "No session slot became free within {} seconds",
+                waited.as_secs()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SessionLimitError {}
+
+impl LimitsConfig {
+    pub fn queue_timeout(&self) -> Duration {
+        Duration::from_secs(self.queue_timeout_secs)
+    }
+
+    /// Whether one more session of `agent` fits, given its `[agents]` cap
+    pub fn check(
+        &self,
+        running: RunningSessions,
+        agent: &str,
+        agent_limit: Option<usize>,
+    ) -> Result<(), SessionLimitError> {
+        let caps = [
+            (self.max_sessions, running.total, LimitScope::Server),
+            (
+                self.max_sessions_per_project,
+                running.in_project,
+                LimitScope::Project,
+            ),
+            (
+                agent_limit,
+                running.of_agent,
+                LimitScope::Agent(agent.to_string()),
+            ),
+        ];
+        for (limit, count, scope) in caps {
+            if let Some(limit) = limit {
+                if count >= limit {
+                    return Err(SessionLimitError::Reached { scope, limit });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_first_cap_reached() {
+        let limits = LimitsConfig {
+            max_sessions: Some(10),
+            max_sessions_per_project: Some(2),
+            ..LimitsConfig::default()
+        };
+        let running = RunningSessions {
+            total: 3,
+            in_project: 1,
+            of_agent: 1,
+        };
+        assert_eq!(limits.check(running, "claude", None), Ok(()));
+        assert_eq!(
+            limits.check(running, "claude", Some(1)),
+            Err(SessionLimitError::Reached {
+                scope: LimitScope::Agent("claude".to_string()),
+                limit: 1
+            })
+        );
+
+        let busy_project = RunningSessions {
+            in_project: 2,
+            ..running
+        };
+        let error = limits.check(busy_project, "claude", Some(1)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Session limit reached: 2 running sessions allowed in this project"
+        );
+
+        // No caps configured means no limit
+        let full = RunningSessions {
+            total: 500,
+            in_project: 500,
+            of_agent: 500,
+        };
+        assert_eq!(LimitsConfig::default().check(full, "claude", None), Ok(()));
+    }
+}
@@ -0,0 +1,258 @@
+//! Pagination, filtering and sorting of list endpoints, spelled the JSON:API
+//! way: `page[size]`, `page[number]`, `filter[<field>]` and `sort`
+//!
+//! Lists are small enough to be filtered, sorted and cut into pages in memory
+//! after they're built. Without `page[size]` the whole list is one page, so
+//! clients that don't paginate keep getting everything.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use ts_rs::TS;
+use utoipa::{IntoParams, ToSchema};
+
+use super::session::SessionAttributes;
+
+/// Largest `page[size]` a list is cut into
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Query parameters of `GET /api/sessions` and `GET /api/projects`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Entries per page, all of them when missing
+    #[serde(rename = "page[size]")]
+    pub page_size: Option<usize>,
+    /// 1-based, the first page when missing
+    #[serde(rename = "page[number]")]
+    pub page_number: Option<usize>,
+    /// Only sessions of this agent
+    #[serde(rename = "filter[agent]")]
+    pub agent: Option<String>,
+    /// Only sessions with this status: `running`, `exited` or `killed`
+    #[serde(rename = "filter[status]")]
+    pub status: Option<String>,
+    /// Only this project's sessions, by project ID or (qualified) name
+    #[serde(rename = "filter[project]")]
+    pub project: Option<String>,
+    /// Fields to sort by, comma-separated, each descending with a `-` in front
+    pub sort: Option<String>,
+}
+
+impl ListQuery {
+    /// Whether any of the filters on a session's own fields is set
+    pub fn filters_sessions(&self) -> bool {
+        self.agent.is_some() || self.status.is_some()
+    }
+
+    /// Whether a session with `attributes` passes the agent and status
+    /// filters; the project filter is up to the caller, which can resolve
+    /// project names
+    pub fn matches(&self, attributes: Option<&SessionAttributes>) -> bool {
+        let Some(attributes) = attributes else {
+            return !self.filters_sessions();
+        };
+        self.agent
+            .as_ref()
+            .is_none_or(|agent| attributes.agent.eq_ignore_ascii_case(agent))
+            && self
+                .status
+                .as_ref()
+                .is_none_or(|status| attributes.status.eq_ignore_ascii_case(status))
+    }
+
+    /// Sort `items` by the `sort` fields, which must be among `fields`;
+    /// `value` gives an item's value of a field
+    pub fn sort<T>(
+        &self,
+        items: &mut [T],
+        fields: &[&str],
+        value: impl Fn(&T, &str) -> Option<String>,
+    ) -> Result<()> {
+        let Some(sort) = self.sort.as_deref().filter(|sort| !sort.is_empty()) else {
+            return Ok(());
+        };
+        let mut keys = Vec::new();
+        for key in sort.split(',').map(str::trim) {
+            let (field, descending) = match key.strip_prefix('-') {
+                Some(field) => (field, true),
+                None => (key, false),
+            };
+            if !fields.contains(&field) {
+                bail!("Can't sort by `{}`; try {}", field, fields.join(", "));
+            }
+            keys.push((field, descending));
+        }
+        items.sort_by(|a, b| {
+            keys.iter()
+                .map(|(key, descending)| {
+                    // Missing values sort last either way
+                    let order = match (value(a, key), value(b, key)) {
+                        (Some(a), Some(b)) => a.cmp(&b),
+                        (Some(_), None) => return Ordering::Less,
+                        (None, Some(_)) => return Ordering::Greater,
+                        (None, None) => Ordering::Equal,
+                    };
+                    if *descending {
+                        order.reverse()
+                    } else {
+                        order
+                    }
+                })
+                .find(|order| order.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+        Ok(())
+    }
+
+    /// The requested page of `items`
+    pub fn paginate<T>(&self, items: Vec<T>) -> Result<Page<T>> {
+        let total = items.len();
+        let size = match self.page_size {
+            Some(0) => bail!("page[size] must be at least 1"),
+            Some(size) if size > MAX_PAGE_SIZE => {
+                bail!("page[size] can be at most {}", MAX_PAGE_SIZE)
+            }
+            Some(size) => size,
+            None => total.max(1),
+        };
+        let number = match self.page_number {
+            Some(0) => bail!("page[number] starts at 1"),
+            Some(number) => number,
+            None => 1,
+        };
+        let pages = total.div_ceil(size).max(1);
+        let items = items
+            .into_iter()
+            .skip((number - 1).saturating_mul(size))
+            .take(size)
+            .collect();
+        Ok(Page {
+            items,
+            meta: PageMeta {
+                total,
+                page_number: number,
+                page_size: size,
+                pages,
+            },
+        })
+    }
+
+    /// Links to the first, previous, next and last pages of `path`, keeping
+    /// the other parameters
+    pub fn links(&self, path: &str, meta: &PageMeta) -> PageLinks {
+        let page = |number: usize| {
+            let query = ListQuery {
+                page_number: Some(number),
+                ..self.clone()
+            };
+            let mut params = url::form_urlencoded::Serializer::new(String::new());
+            for (name, value) in query.params() {
+                params.append_pair(name, &value);
+            }
+            format!("{}?{}", path, params.finish())
+        };
+        PageLinks {
+            first: page(1),
+            prev: (meta.page_number > 1).then(|| page(meta.page_number - 1)),
+            next: (meta.page_number < meta.pages).then(|| page(meta.page_number + 1)),
+            last: page(meta.pages),
+        }
+    }
+
+    /// The parameters that are set, as a query string takes them
+    pub fn params(&self) -> Vec<(&'static str, String)> {
+        let params = [
+            ("page[size]", self.page_size.map(|size| size.to_string())),
+            (
+                "page[number]",
+                self.page_number.map(|number| number.to_string()),
+            ),
+            ("filter[agent]", self.agent.clone()),
+            ("filter[status]", self.status.clone()),
+            ("filter[project]", self.project.clone()),
+            ("sort", self.sort.clone()),
+        ];
+        params
+            .into_iter()
+            .filter_map(|(name, value)| Some((name, value?)))
+            .collect()
+    }
+}
+
+/// One page of a list
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub meta: PageMeta,
+}
+
+/// `meta` of a paginated list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct PageMeta {
+    /// Entries in the whole list, after filtering
+    pub total: usize,
+    pub page_number: usize,
+    pub page_size: usize,
+    pub pages: usize,
+}
+
+/// `links` of a paginated list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct PageLinks {
+    pub first: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub prev: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub next: Option<String>,
+    pub last: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_and_pages_with_links_to_the_other_pages() {
+        let query = ListQuery {
+            page_size: Some(2),
+            page_number: Some(2),
+            agent: Some("claude".to_string()),
+            sort: Some("-name".to_string()),
+            ..Default::default()
+        };
+        let mut names = vec!["b", "e", "a", "d", "c"];
+        query
+            .sort(&mut names, &["name"], |name, _| Some(name.to_string()))
+            .unwrap();
+        let page = query.paginate(names).unwrap();
+        assert_eq!(page.items, ["c", "b"]);
+        assert_eq!(page.meta.pages, 3);
+
+        let links = query.links("/api/sessions", &page.meta);
+        assert_eq!(
+            links.next.as_deref(),
+            Some("/api/sessions?page%5Bsize%5D=2&page%5Bnumber%5D=3&filter%5Bagent%5D=claude&sort=-name")
+        );
+        assert!(links.prev.unwrap().contains("page%5Bnumber%5D=1"));
+
+        let unsortable = ListQuery {
+            sort: Some("color".to_string()),
+            ..Default::default()
+        };
+        assert!(unsortable.sort(&mut ["a"], &["name"], |_, _| None).is_err());
+        assert_eq!(
+            ListQuery::default().paginate(vec![1, 2, 3]).unwrap().items,
+            [1, 2, 3]
+        );
+        assert!(ListQuery {
+            page_size: Some(0),
+            ..Default::default()
+        }
+        .paginate(vec![1])
+        .is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Changing the log filter of a running process, for `codemux server log-level`
+//!
+//! `main` installs the filter through [`reloadable`], which keeps a reload
+//! handle here; [`set`] swaps the filter through it without restarting, so a
+//! server can log at debug level while a bug is reproduced and go back to
+//! info afterwards, its sessions running on.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+/// Levels a bare `level` sets for codemux itself
+const LEVELS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "off"];
+
+static RELOAD: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// The log filter, as `GET /api/log-level` answers and `PUT` takes it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct LogLevel {
+    /// `RUST_LOG` directives such as `codemux=debug,hyper=info`; a bare
+    /// level like `debug` applies to codemux only
+    pub filter: String,
+}
+
+/// `filter` as a layer whose filter [`set`] can replace later
+pub fn reloadable(filter: EnvFilter) -> reload::Layer<EnvFilter, Registry> {
+    let (layer, handle) = reload::Layer::new(filter);
+    let _ = RELOAD.set(handle);
+    layer
+}
+
+/// Directives for `filter`, with a bare level meaning codemux's level
+pub fn directives(filter: &str) -> String {
+    let filter = filter.trim();
+    if LEVELS.contains(&filter.to_ascii_lowercase().as_str()) {
+        format!("codemux={}", filter.to_ascii_lowercase())
+    } else {
+        filter.to_string()
+    }
+}
+
+/// The filter in effect, `None` where it wasn't installed with [`reloadable`]
+pub fn current() -> Option<String> {
+    RELOAD.get()?.with_current(|filter| filter.to_string()).ok()
+}
+
+/// Replace the filter with `filter`; returns the new filter
+pub fn set(filter: &str) -> Result<String> {
+    let handle = RELOAD
+        .get()
+        .ok_or_else(|| anyhow!("This process's log filter can't be changed"))?;
+    let filter = EnvFilter::try_new(directives(filter))
+        .map_err(|e| anyhow!("Invalid log filter '{}': {}", filter, e))?;
+    let applied = filter.to_string();
+    handle
+        .reload(filter)
+        .map_err(|e| anyhow!("Failed to change the log filter: {}", e))?;
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_levels_apply_to_codemux() {
+        assert_eq!(directives("DEBUG"), "codemux=debug");
+        assert_eq!(directives(" warn "), "codemux=warn");
+        assert_eq!(
+            directives("codemux=trace,hyper=info"),
+            "codemux=trace,hyper=info"
+        );
+        assert!(EnvFilter::try_new(directives("codemux=loud")).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Several server messages in one WebSocket frame
+//!
+//! Bursty output turns into many small grid diffs, each sent as a frame of its
+//! own. Clients that connect with the `batch` capability get the messages that
+//! come up within a few milliseconds of each other in one frame instead, as a
+//! JSON array, which saves framing and syscalls on remote connections.
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How long a message waits for others to share its frame
+pub const BATCH_WINDOW: Duration = Duration::from_millis(5);
+
+/// A batch this large is sent without waiting out the window
+pub const MAX_BATCH_BYTES: usize = 256 * 1024;
+
+/// Serialized messages waiting to be sent together
+#[derive(Debug, Default)]
+pub struct MessageBatch {
+    /// `None` when the client didn't ask for batching
+    window: Option<Duration>,
+    messages: Vec<String>,
+    bytes: usize,
+    deadline: Option<Instant>,
+}
+
+impl MessageBatch {
+    pub fn new(enabled: bool) -> Self {
+        MessageBatch {
+            window: enabled.then_some(BATCH_WINDOW),
+            ..Default::default()
+        }
+    }
+
+    /// Add a message; returns the frame to send right away when batching is
+    /// off or the batch is full
+    pub fn push(&mut self, message: String) -> Option<String> {
+        let Some(window) = self.window else {
+            return Some(message);
+        };
+        if self.messages.is_empty() {
+            self.deadline = Some(Instant::now() + window);
+        }
+        self.bytes += message.len();
+        self.messages.push(message);
+        if self.bytes >= MAX_BATCH_BYTES {
+            return self.take();
+        }
+        None
+    }
+
+    /// When the waiting messages are due, if there are any
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// The waiting messages as one frame: a single message as it is, several
+    /// as a JSON array
+    pub fn take(&mut self) -> Option<String> {
+        self.deadline = None;
+        self.bytes = 0;
+        match self.messages.len() {
+            0 => None,
+            1 => self.messages.pop(),
+            _ => Some(format!(
+                "[{}]",
+                std::mem::take(&mut self.messages).join(",")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ServerMessage;
+
+    fn message(rows: u16) -> String {
+        serde_json::to_string(&ServerMessage::PtySize { rows, cols: 80 }).unwrap()
+    }
+
+    #[test]
+    fn sends_messages_of_a_burst_as_one_array() {
+        let mut off = MessageBatch::new(false);
+        assert_eq!(off.push(message(24)), Some(message(24)));
+        assert_eq!(off.deadline(), None);
+
+        let mut batch = MessageBatch::new(true);
+        assert_eq!(batch.push(message(24)), None);
+        assert!(batch.deadline().is_some());
+        assert_eq!(batch.push(message(25)), None);
+        let frame = batch.take().unwrap();
+        let messages: Vec<ServerMessage> = serde_json::from_str(&frame).unwrap();
+        assert!(matches!(
+            messages[1],
+            ServerMessage::PtySize { rows: 25, .. }
+        ));
+        assert_eq!(batch.take(), None);
+        assert_eq!(batch.deadline(), None);
+
+        batch.push(message(30));
+        assert_eq!(batch.take(), Some(message(30)));
+
+        let large = "x".repeat(MAX_BATCH_BYTES);
+        let full = batch.push(serde_json::to_string(&large).unwrap());
+        assert!(full.is_some());
+    }
+}
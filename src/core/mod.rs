@@ -1,18 +1,72 @@
+pub mod accessible;
+pub mod activity;
+pub mod adopt;
+pub mod agent_adapter;
+pub mod agent_config;
+pub mod agent_version;
+pub mod api_tokens;
+pub mod auth;
+pub mod checkpoint;
+pub mod commits;
 pub mod config;
+pub mod config_schema;
+pub mod crash_dump;
+pub mod credentials;
+pub mod deletion_guard;
+pub mod digest;
+pub mod dirs;
+pub mod fixtures;
+pub mod frame_encoding;
+pub mod heatmap;
+pub mod hooks;
+pub mod idle;
+pub mod input_control;
+pub mod input_queue;
+pub mod input_transcript;
 pub mod json_api;
+pub mod launch;
+pub mod limits;
+pub mod listing;
+pub mod log_level;
+pub mod message_batch;
+pub mod notifications;
+pub mod oidc;
+pub mod paste;
+pub mod preferences;
+pub mod process;
+pub mod project_groups;
+pub mod project_metadata;
+pub mod project_scan;
 pub mod pty_session;
+pub mod recent;
+pub mod recycle_bin;
+pub mod render;
+pub mod render_test;
+pub mod run_length;
+pub mod search;
+pub mod selection;
 pub mod session;
+pub mod session_links;
+pub mod view_quality;
+pub mod viewport;
+pub mod shim;
+pub mod telemetry;
+pub mod transcript;
 pub mod websocket;
 
 pub use config::Config;
 pub use json_api::{
-    json_api_error, json_api_error_response_with_headers, json_api_response,
-    json_api_response_with_headers, JsonApiDocument, JsonApiError, JsonApiErrorDocument,
+    json_api_error, json_api_response, JsonApiDocument, JsonApiError, JsonApiErrorDocument,
     JsonApiResource, JsonApiResourceRef, ProjectRelationships, ProjectResource, SessionResource,
 };
+#[cfg(feature = "server")]
+pub use json_api::{
+    json_api_error_response_with_headers, json_api_page_response_with_headers,
+    json_api_response_with_headers,
+};
 pub use pty_session::{
     GridUpdateMessage, PtyChannels, PtyControlMessage, PtyInputMessage, PtyOutputMessage,
     PtySession,
 };
-pub use session::{ProjectAttributes, SessionAttributes};
-pub use websocket::{ClientMessage, ServerMessage};
+pub use session::{ProjectAttributes, ProjectDefaults, SessionAttributes};
+pub use websocket::{ClientMessage, ProtocolCapability, RawClientMessage, ServerMessage};
@@ -0,0 +1,402 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use ts_rs::TS;
+
+/// How many notifications the store keeps, oldest dropped first
+pub const MAX_NOTIFICATIONS: usize = 200;
+
+/// What a notification is about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum NotificationKind {
+    /// The agent asked a question and is waiting for an answer
+    WaitingForInput,
+    /// The agent process exited on its own
+    Exited,
+    /// A checkpoint of the session's work was created
+    CheckpointCreated,
+    /// The daily digest is ready; not tied to a session
+    Digest,
+    /// Many files disappeared from the session's working tree at once
+    MassDeletion,
+    /// The agent committed a change, e.g. aider after each edit
+    Commit,
+    /// The agent waits for approval, e.g. of a tool it wants to run
+    ApprovalNeeded,
+}
+
+impl NotificationKind {
+    pub fn severity(self) -> Severity {
+        match self {
+            NotificationKind::WaitingForInput
+            | NotificationKind::MassDeletion
+            | NotificationKind::ApprovalNeeded => Severity::Urgent,
+            NotificationKind::Exited => Severity::Warning,
+            NotificationKind::CheckpointCreated
+            | NotificationKind::Digest
+            | NotificationKind::Commit => Severity::Info,
+        }
+    }
+}
+
+/// How much a notification deserves an alert, lowest first
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS,
+)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    /// An agent is blocked until someone answers
+    Urgent,
+}
+
+/// An event worth telling someone about, kept until it drops out of the store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Notification {
+    pub id: String,
+    pub kind: NotificationKind,
+    /// Empty for notifications that aren't about one session
+    pub session_id: String,
+    pub message: String,
+    /// ISO 8601 timestamp of when the event fired
+    pub created_at: String,
+    pub read: bool,
+    /// Recorded without an alert, because of quiet hours, do-not-disturb or the severity threshold
+    #[serde(default)]
+    pub silenced: bool,
+}
+
+impl Notification {
+    pub fn new(kind: NotificationKind, session_id: &str, message: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            session_id: session_id.to_string(),
+            message: message.into(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            read: false,
+            silenced: false,
+        }
+    }
+}
+
+/// Served by `GET /api/notifications`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NotificationList {
+    /// Newest first
+    pub notifications: Vec<Notification>,
+    pub unread: u32,
+}
+
+impl NotificationList {
+    pub fn new(notifications: Vec<Notification>) -> Self {
+        let unread = notifications.iter().filter(|n| !n.read).count() as u32;
+        Self {
+            notifications,
+            unread,
+        }
+    }
+}
+
+/// When notifications raise alerts, configured as `[notifications]`
+///
+/// Notifications are always recorded; these rules only decide which of them
+/// alert someone, so nothing is lost while it is quiet. Alerts at
+/// `push_min_severity` or above are also pushed to the desktop, webhooks and
+/// ntfy, see `server::notify`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Notifications below this severity never alert
+    pub min_severity: Severity,
+    /// Local times without alerts, e.g. `["22:00-07:00", "12:00-13:00"]`
+    pub quiet_hours: Vec<QuietHours>,
+    /// Alerts below this severity stay in the web UI
+    pub push_min_severity: Severity,
+    /// Show alerts on the desktop of the machine running the server
+    pub desktop: bool,
+    /// URLs to POST alerts to as JSON
+    pub webhooks: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ntfy: Option<NtfyConfig>,
+    /// Overrides for projects, by project name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, ProjectNotificationRules>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            min_severity: Severity::default(),
+            quiet_hours: Vec::new(),
+            push_min_severity: Severity::Urgent,
+            desktop: false,
+            webhooks: Vec::new(),
+            ntfy: None,
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+/// An ntfy topic to publish alerts to, e.g. for the ntfy app on a phone
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NtfyConfig {
+    /// Anyone who knows the topic can read it, so pick one hard to guess
+    pub topic: String,
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    /// Access token for servers with access control
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_string()
+}
+
+/// Where a project's alerts are pushed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PushTargets {
+    pub desktop: bool,
+    pub webhooks: Vec<String>,
+    pub ntfy: Option<NtfyConfig>,
+}
+
+impl PushTargets {
+    pub fn is_empty(&self) -> bool {
+        !self.desktop && self.webhooks.is_empty() && self.ntfy.is_none()
+    }
+}
+
+/// `[notifications.projects.<name>]`; unset fields fall back to `[notifications]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectNotificationRules {
+    /// Never alert for this project
+    pub muted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_severity: Option<Severity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quiet_hours: Option<Vec<QuietHours>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub push_min_severity: Option<Severity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhooks: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ntfy: Option<NtfyConfig>,
+}
+
+impl NotificationsConfig {
+    /// Whether a notification of `kind` in `project` may alert at local time `now`
+    pub fn allows(&self, kind: NotificationKind, project: Option<&str>, now: NaiveTime) -> bool {
+        let rules = project.and_then(|name| self.projects.get(name));
+        if rules.is_some_and(|rules| rules.muted) {
+            return false;
+        }
+        let min_severity = rules
+            .and_then(|rules| rules.min_severity)
+            .unwrap_or(self.min_severity);
+        let quiet_hours = rules
+            .and_then(|rules| rules.quiet_hours.as_ref())
+            .unwrap_or(&self.quiet_hours);
+        kind.severity() >= min_severity && !quiet_hours.iter().any(|quiet| quiet.contains(now))
+    }
+
+    /// Where an alert of `kind` in `project` is pushed; empty below the push
+    /// threshold
+    pub fn push_targets(&self, kind: NotificationKind, project: Option<&str>) -> PushTargets {
+        let rules = project.and_then(|name| self.projects.get(name));
+        let push_min_severity = rules
+            .and_then(|rules| rules.push_min_severity)
+            .unwrap_or(self.push_min_severity);
+        if kind.severity() < push_min_severity {
+            return PushTargets::default();
+        }
+        PushTargets {
+            desktop: rules
+                .and_then(|rules| rules.desktop)
+                .unwrap_or(self.desktop),
+            webhooks: rules
+                .and_then(|rules| rules.webhooks.clone())
+                .unwrap_or_else(|| self.webhooks.clone()),
+            ntfy: rules
+                .and_then(|rules| rules.ntfy.clone())
+                .or_else(|| self.ntfy.clone()),
+        }
+    }
+}
+
+/// A daily time range such as `22:00-07:00`; ranges ending before they start wrap past midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl TryFrom<String> for QuietHours {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+        value
+            .split_once('-')
+            .and_then(|(start, end)| {
+                Some(QuietHours {
+                    start: parse(start)?,
+                    end: parse(end)?,
+                })
+            })
+            .ok_or_else(|| {
+                format!(
+                    "quiet hours must look like \"22:00-07:00\", got {:?}",
+                    value
+                )
+            })
+    }
+}
+
+impl From<QuietHours> for String {
+    fn from(quiet: QuietHours) -> Self {
+        quiet.to_string()
+    }
+}
+
+impl fmt::Display for QuietHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// Do-not-disturb switched on by hand, e.g. with `codemux dnd on --for 2h`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct DoNotDisturb {
+    pub enabled: bool,
+    /// ISO 8601 timestamp when it switches off by itself; unset lasts until turned off
+    #[ts(optional)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+}
+
+impl DoNotDisturb {
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        match &self.until {
+            Some(until) => DateTime::parse_from_rfc3339(until).is_ok_and(|until| now < until),
+            None => true,
+        }
+    }
+}
+
+/// Body of `PUT /api/notifications/dnd`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SetDoNotDisturbRequest {
+    pub enabled: bool,
+    /// Switch off by itself after this many minutes
+    #[ts(optional)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minutes: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(value, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn applies_quiet_hours_thresholds_and_project_overrides() {
+        let config: NotificationsConfig = toml::from_str(
+            r#"
+            min_severity = "warning"
+            quiet_hours = ["22:00-07:00"]
+
+            [projects.infra]
+            min_severity = "urgent"
+            quiet_hours = []
+
+            [projects.sandbox]
+            muted = true
+
+            [projects.web]
+            webhooks = ["https://hooks.example.com/web"]
+            [projects.web.ntfy]
+            topic = "web-alerts"
+            "#,
+        )
+        .unwrap();
+
+        // Quiet hours wrap past midnight
+        assert!(!config.allows(NotificationKind::Exited, None, time("23:30")));
+        assert!(!config.allows(NotificationKind::Exited, None, time("06:59")));
+        assert!(config.allows(NotificationKind::Exited, None, time("07:00")));
+        // Below the threshold
+        assert!(!config.allows(NotificationKind::CheckpointCreated, None, time("12:00")));
+
+        // The project has no quiet hours but a higher threshold
+        assert!(config.allows(
+            NotificationKind::WaitingForInput,
+            Some("infra"),
+            time("23:30")
+        ));
+        assert!(!config.allows(NotificationKind::Exited, Some("infra"), time("12:00")));
+        assert!(!config.allows(
+            NotificationKind::WaitingForInput,
+            Some("sandbox"),
+            time("12:00")
+        ));
+
+        assert!(toml::from_str::<NotificationsConfig>(r#"quiet_hours = ["late"]"#).is_err());
+
+        // Only urgent alerts are pushed by default, with the project's targets
+        assert!(config
+            .push_targets(NotificationKind::Exited, Some("web"))
+            .is_empty());
+        let targets = config.push_targets(NotificationKind::ApprovalNeeded, Some("web"));
+        assert_eq!(targets.webhooks, vec!["https://hooks.example.com/web"]);
+        assert_eq!(targets.ntfy.unwrap().server, "https://ntfy.sh");
+        assert!(config
+            .push_targets(NotificationKind::ApprovalNeeded, None)
+            .is_empty());
+
+        let now = Utc::now();
+        let dnd = DoNotDisturb {
+            enabled: true,
+            until: Some((now + chrono::Duration::hours(2)).to_rfc3339()),
+        };
+        assert!(dnd.is_active_at(now));
+        assert!(!dnd.is_active_at(now + chrono::Duration::hours(3)));
+    }
+}
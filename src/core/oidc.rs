@@ -0,0 +1,353 @@
+//! Signing in with an OpenID Connect provider instead of the server's token
+//!
+//! With `[web.oidc]` set, people open the web UI and are sent to the
+//! provider's login page (the authorization code flow with PKCE); the server
+//! keeps who they are in a browser cookie. `codemux login` goes through the
+//! same flow in the browser and receives a short-lived token for the CLI. The
+//! server's own token keeps working alongside.
+//!
+//! The ID token comes straight from the provider's token endpoint over TLS, so
+//! its claims are checked but its signature isn't, as OpenID Connect Core
+//! 3.1.3.7 allows for that case.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use super::auth::generate_token;
+
+/// The identity provider and who may sign in, configured as `[web.oidc]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OidcConfig {
+    /// Issuer URL, where `/.well-known/openid-configuration` is found
+    pub issuer: String,
+    pub client_id: String,
+    /// Only for confidential clients; PKCE protects public ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    /// This server's `/auth/callback` as browsers reach it, registered with
+    /// the provider, e.g. `https://codemux.example.com/auth/callback`
+    pub redirect_url: String,
+    #[serde(default = "default_scopes")]
+    pub scopes: Vec<String>,
+    /// Who may sign in: emails, `@domain` for everyone with an address there,
+    /// or `*` for everyone the provider knows
+    #[serde(default)]
+    pub allowed_users: Vec<String>,
+    /// Claim naming the user in codemux
+    #[serde(default = "default_username_claim")]
+    pub username_claim: String,
+    /// How long a browser stays signed in
+    #[serde(default = "default_session_ttl_hours")]
+    pub session_ttl_hours: u64,
+    /// How long a token from `codemux login` is valid
+    #[serde(default = "default_cli_token_ttl_hours")]
+    pub cli_token_ttl_hours: u64,
+}
+
+/// Longest `session_ttl_hours` and `cli_token_ttl_hours`, ten years
+pub const MAX_TTL_HOURS: u64 = 24 * 365 * 10;
+
+impl OidcConfig {
+    /// Check the values serde can't, when the config is loaded
+    pub fn validate(&self) -> Result<()> {
+        for (key, hours) in [
+            ("session_ttl_hours", self.session_ttl_hours),
+            ("cli_token_ttl_hours", self.cli_token_ttl_hours),
+        ] {
+            if !(1..=MAX_TTL_HOURS).contains(&hours) {
+                bail!(
+                    "[web.oidc] {} must be between 1 and {}, not {}",
+                    key,
+                    MAX_TTL_HOURS,
+                    hours
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn default_scopes() -> Vec<String> {
+    vec!["openid".into(), "email".into(), "profile".into()]
+}
+
+fn default_username_claim() -> String {
+    "email".to_string()
+}
+
+fn default_session_ttl_hours() -> u64 {
+    24
+}
+
+fn default_cli_token_ttl_hours() -> u64 {
+    12
+}
+
+/// Someone signed in through the provider
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct User {
+    /// From the configured `username_claim`
+    pub name: String,
+    pub email: Option<String>,
+    /// The provider's ID for the user
+    pub subject: String,
+    pub issuer: String,
+}
+
+/// The endpoints a provider publishes at `/.well-known/openid-configuration`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+}
+
+/// Where the provider publishes its metadata
+pub fn discovery_url(issuer: &str) -> String {
+    format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    )
+}
+
+/// A PKCE code verifier and its S256 challenge (RFC 7636)
+pub struct Pkce {
+    pub verifier: String,
+    pub challenge: String,
+}
+
+impl Pkce {
+    pub fn new() -> Self {
+        Self::from_verifier(generate_token())
+    }
+
+    fn from_verifier(verifier: String) -> Self {
+        let challenge = base64url_encode(&Sha256::digest(verifier.as_bytes()));
+        Pkce {
+            verifier,
+            challenge,
+        }
+    }
+}
+
+impl Default for Pkce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The provider's login page for a new sign-in
+pub fn authorization_url(
+    config: &OidcConfig,
+    provider: &ProviderMetadata,
+    state: &str,
+    nonce: &str,
+    pkce: &Pkce,
+) -> Result<String> {
+    let mut url = url::Url::parse(&provider.authorization_endpoint)?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", &config.client_id)
+        .append_pair("redirect_uri", &config.redirect_url)
+        .append_pair("scope", &config.scopes.join(" "))
+        .append_pair("state", state)
+        .append_pair("nonce", nonce)
+        .append_pair("code_challenge", &pkce.challenge)
+        .append_pair("code_challenge_method", "S256");
+    Ok(url.into())
+}
+
+/// Check the claims of an ID token the token endpoint returned and tell who
+/// signed in
+pub fn verify_id_token(
+    config: &OidcConfig,
+    provider: &ProviderMetadata,
+    id_token: &str,
+    nonce: &str,
+    now: i64,
+) -> Result<User> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| anyhow!("The ID token is not a JWT"))?;
+    let claims: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_slice(&base64url_decode(payload)?)?;
+    let claim = |name: &str| claims.get(name).and_then(|value| value.as_str());
+
+    let issuer = claim("iss").unwrap_or_default();
+    if issuer.trim_end_matches('/') != provider.issuer.trim_end_matches('/') {
+        bail!("The ID token was issued by {}", issuer);
+    }
+    let audience_matches = match claims.get("aud") {
+        Some(serde_json::Value::String(audience)) => *audience == config.client_id,
+        Some(serde_json::Value::Array(audiences)) => audiences
+            .iter()
+            .any(|audience| audience.as_str() == Some(&config.client_id)),
+        _ => false,
+    };
+    if !audience_matches {
+        bail!("The ID token is for another client");
+    }
+    let expires = claims.get("exp").and_then(|exp| exp.as_i64()).unwrap_or(0);
+    if expires <= now {
+        bail!("The ID token expired");
+    }
+    if claim("nonce") != Some(nonce) {
+        bail!("The ID token belongs to another sign-in");
+    }
+
+    let subject = claim("sub").ok_or_else(|| anyhow!("The ID token names no user"))?;
+    // An address the provider didn't verify could be anyone's
+    let email = claim("email")
+        .filter(|_| claims.get("email_verified") != Some(&serde_json::Value::Bool(false)));
+    let name = claim(&config.username_claim)
+        .filter(|_| config.username_claim != "email" || email.is_some())
+        .ok_or_else(|| {
+            anyhow!(
+                "The provider sent no verified `{}` claim",
+                config.username_claim
+            )
+        })?;
+    if !user_allowed(&config.allowed_users, email) {
+        bail!("{} may not use this server", email.unwrap_or(name));
+    }
+    Ok(User {
+        name: name.to_string(),
+        email: email.map(str::to_string),
+        subject: subject.to_string(),
+        issuer: issuer.to_string(),
+    })
+}
+
+/// Whether `allowed_users` lets someone with this address in
+fn user_allowed(allowed_users: &[String], email: Option<&str>) -> bool {
+    allowed_users.iter().any(|allowed| {
+        if allowed == "*" {
+            return true;
+        }
+        let Some(email) = email else {
+            return false;
+        };
+        match allowed.strip_prefix('@') {
+            Some(domain) => email
+                .rsplit_once('@')
+                .is_some_and(|(_, at)| at.eq_ignore_ascii_case(domain)),
+            None => email.eq_ignore_ascii_case(allowed),
+        }
+    })
+}
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Unpadded base64url, as PKCE and JWTs use it
+fn base64url_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            encoded.push(BASE64URL[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    encoded
+}
+
+fn base64url_decode(text: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut count) = (0u32, 0);
+    for c in text.trim_end_matches('=').bytes() {
+        let value = BASE64URL
+            .iter()
+            .position(|&b| b == c)
+            .ok_or_else(|| anyhow!("Invalid base64url"))?;
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_in_allowed_users_with_valid_id_tokens() {
+        // RFC 7636, appendix B
+        let pkce = Pkce::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string());
+        assert_eq!(
+            pkce.challenge,
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+
+        let config: OidcConfig = toml::from_str(
+            r#"
+            issuer = "https://id.example.com/"
+            client_id = "codemux"
+            redirect_url = "https://codemux.example.com/auth/callback"
+            allowed_users = ["@example.com"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        let forever = OidcConfig {
+            session_ttl_hours: u64::MAX,
+            ..config.clone()
+        };
+        assert!(forever.validate().is_err());
+        let provider = ProviderMetadata {
+            issuer: "https://id.example.com".to_string(),
+            authorization_endpoint: "https://id.example.com/authorize".to_string(),
+            token_endpoint: "https://id.example.com/token".to_string(),
+        };
+        let id_token = |claims: serde_json::Value| {
+            format!(
+                "e30.{}.signature",
+                base64url_encode(claims.to_string().as_bytes())
+            )
+        };
+        let claims = |email: &str| {
+            serde_json::json!({
+                "iss": "https://id.example.com",
+                "aud": ["codemux", "other"],
+                "sub": "1234",
+                "exp": 2000,
+                "nonce": "n-0S6",
+                "email": email,
+            })
+        };
+
+        let user = verify_id_token(
+            &config,
+            &provider,
+            &id_token(claims("ada@Example.com")),
+            "n-0S6",
+            1000,
+        )
+        .unwrap();
+        assert_eq!(user.name, "ada@Example.com");
+        assert_eq!(user.subject, "1234");
+
+        let fails = |token: String, nonce: &str, now: i64| {
+            verify_id_token(&config, &provider, &token, nonce, now).is_err()
+        };
+        assert!(fails(id_token(claims("eve@evil.com")), "n-0S6", 1000));
+        assert!(fails(id_token(claims("ada@example.com")), "other", 1000));
+        assert!(fails(id_token(claims("ada@example.com")), "n-0S6", 2000));
+        let mut unverified = claims("ada@example.com");
+        unverified["email_verified"] = false.into();
+        assert!(fails(id_token(unverified), "n-0S6", 1000));
+
+        let url = authorization_url(&config, &provider, "xyz", "n-0S6", &pkce).unwrap();
+        assert!(url.starts_with("https://id.example.com/authorize?response_type=code"));
+        assert!(url.contains("code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"));
+    }
+}
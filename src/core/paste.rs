@@ -0,0 +1,129 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Safeguards against pasting more into an agent than intended, configured as `[paste]`
+///
+/// The TUI and web UI ask before pasting past the `confirm_*` thresholds; the
+/// server drops pastes larger than `max_bytes` whichever client sends them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct PasteConfig {
+    /// Ask before pasting more lines than this; 0 never asks
+    pub confirm_lines: usize,
+    /// Ask before pasting more bytes than this; 0 never asks
+    pub confirm_bytes: usize,
+    /// Offer to save a large paste to a file and send the agent its path instead
+    pub offer_file: bool,
+    /// Largest paste written to an agent
+    pub max_bytes: usize,
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        PasteConfig {
+            confirm_lines: 100,
+            confirm_bytes: 8 * 1024,
+            offer_file: true,
+            max_bytes: 1024 * 1024,
+        }
+    }
+}
+
+impl PasteConfig {
+    /// Whether `text` is large enough to ask before pasting it
+    pub fn needs_confirmation(&self, text: &str) -> bool {
+        (self.confirm_lines > 0 && text.lines().count() > self.confirm_lines)
+            || (self.confirm_bytes > 0 && text.len() > self.confirm_bytes)
+    }
+
+    pub fn exceeds_max(&self, text: &str) -> bool {
+        text.len() > self.max_bytes
+    }
+}
+
+/// Size of a paste for prompts, e.g. "1200 lines, 48.2 KB"
+pub fn describe_paste(text: &str) -> String {
+    let lines = text.lines().count();
+    let bytes = text.len();
+    let size = if bytes < 1024 {
+        format!("{} bytes", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    };
+    format!(
+        "{} line{}, {}",
+        lines,
+        if lines == 1 { "" } else { "s" },
+        size
+    )
+}
+
+/// Marks the start of a bracketed paste
+pub const PASTE_START: &str = "\x1b[200~";
+/// Marks the end of a bracketed paste
+pub const PASTE_END: &str = "\x1b[201~";
+
+/// Bytes to write to the agent for a paste, the way a terminal would send them
+///
+/// Line breaks become carriage returns, as typed. When the agent has turned
+/// on bracketed paste (`ESC[?2004h`) the text is wrapped in its markers, so
+/// the agent takes it as one paste instead of submitting at the first line
+/// break; markers inside the text are dropped so it can't end the paste early.
+pub fn paste_bytes(text: &str, bracketed: bool) -> Vec<u8> {
+    let text = text.replace("\r\n", "\r").replace('\n', "\r");
+    if bracketed {
+        let text = text.replace(PASTE_START, "").replace(PASTE_END, "");
+        format!("{}{}{}", PASTE_START, text, PASTE_END).into_bytes()
+    } else {
+        text.into_bytes()
+    }
+}
+
+/// Save a paste under `<pastes_dir>/<session>` so its path can be sent instead
+pub fn write_paste_file(pastes_dir: &Path, session_id: &str, text: &str) -> Result<PathBuf> {
+    let dir = pastes_dir.join(session_id);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!(
+        "paste-{}.txt",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f")
+    ));
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_and_limits() {
+        let config = PasteConfig {
+            confirm_lines: 2,
+            confirm_bytes: 0,
+            offer_file: true,
+            max_bytes: 16,
+        };
+        assert!(!config.needs_confirmation("one\ntwo"));
+        assert!(config.needs_confirmation("one\ntwo\nthree"));
+        assert!(!config.needs_confirmation(&"x".repeat(100)));
+        assert!(config.exceeds_max(&"x".repeat(17)));
+        assert_eq!(describe_paste("a\nb\n"), "2 lines, 4 bytes");
+        assert_eq!(describe_paste(&"x".repeat(1536)), "1 line, 1.5 KB");
+        assert_eq!(paste_bytes("a\r\nb\n", false), b"a\rb\r");
+        assert_eq!(
+            paste_bytes("a\n\x1b[201~b", true),
+            b"\x1b[200~a\rb\x1b[201~"
+        );
+
+        let pastes_dir = std::env::temp_dir().join(format!("codemux-paste-{}", std::process::id()));
+        let path = write_paste_file(&pastes_dir, "session", "hello").unwrap();
+        assert!(path.starts_with(pastes_dir.join("session")));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+        let _ = std::fs::remove_dir_all(&pastes_dir);
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+/// Display preferences shared by the TUI and web UI
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct UiPreferences {
+    /// Use a high-contrast palette for the terminal and UI chrome
+    pub high_contrast: bool,
+    /// Disable animated UI elements such as ticking timers, countdowns and transitions
+    pub reduced_motion: bool,
+    /// Starred session IDs, listed first in the quick-switch list
+    pub favorite_sessions: Vec<String>,
+    /// Starred project IDs
+    pub favorite_projects: Vec<String>,
+}
+
+/// Partial update accepted by `PATCH /api/preferences`; unset fields are left alone
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct UpdatePreferencesRequest {
+    #[ts(optional)]
+    pub high_contrast: Option<bool>,
+    #[ts(optional)]
+    pub reduced_motion: Option<bool>,
+    /// Replaces the starred sessions
+    #[ts(optional)]
+    pub favorite_sessions: Option<Vec<String>>,
+    /// Replaces the starred projects
+    #[ts(optional)]
+    pub favorite_projects: Option<Vec<String>>,
+}
+
+impl UiPreferences {
+    pub fn apply(&mut self, update: &UpdatePreferencesRequest) {
+        if let Some(high_contrast) = update.high_contrast {
+            self.high_contrast = high_contrast;
+        }
+        if let Some(reduced_motion) = update.reduced_motion {
+            self.reduced_motion = reduced_motion;
+        }
+        if let Some(favorite_sessions) = &update.favorite_sessions {
+            self.favorite_sessions = favorite_sessions.clone();
+        }
+        if let Some(favorite_projects) = &update.favorite_projects {
+            self.favorite_projects = favorite_projects.clone();
+        }
+    }
+}
+
+fn preferences_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("preferences.json")
+}
+
+/// Read stored preferences, falling back to defaults when none are saved
+pub fn load_preferences(data_dir: &Path) -> Result<UiPreferences> {
+    match std::fs::read_to_string(preferences_path(data_dir)) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(UiPreferences::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn save_preferences(data_dir: &Path, preferences: &UiPreferences) -> Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    std::fs::write(
+        preferences_path(data_dir),
+        serde_json::to_string_pretty(preferences)?,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,257 @@
+//! Process checks and signals that work on Unix and Windows
+//!
+//! The server stops agents and the servers it takes over from, and pauses the
+//! agents of killed sessions; the CLI starts servers in the background. Unix
+//! gets real signals through `libc`; Windows has no signals, so asking a
+//! process to exit ends it, and processes can't be paused.
+
+/// What to tell a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ask it to exit
+    Terminate,
+    /// End it without asking
+    Kill,
+    /// Pause it
+    Stop,
+    /// Resume it after `Stop`
+    Continue,
+}
+
+#[cfg(unix)]
+impl Signal {
+    fn number(self) -> libc::c_int {
+        match self {
+            Signal::Terminate => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+            Signal::Stop => libc::SIGSTOP,
+            Signal::Continue => libc::SIGCONT,
+        }
+    }
+}
+
+/// Whether `pid` is still running; exited children nobody reaped yet don't count
+#[cfg(unix)]
+pub fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks whether the process exists
+    let exists = unsafe { libc::kill(pid, 0) } == 0
+        // It exists, it just isn't ours to signal
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+    exists && !is_zombie(pid)
+}
+
+#[cfg(unix)]
+fn is_zombie(pid: libc::pid_t) -> bool {
+    std::process::Command::new("ps")
+        .args(["-o", "stat=", "-p", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .is_ok_and(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .starts_with('Z')
+        })
+}
+
+/// Send `signal` to `pid`; false if there's no such process or it isn't ours
+#[cfg(unix)]
+pub fn signal(pid: u32, signal: Signal) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: plain kill(2) on a positive PID
+    pid > 0 && unsafe { libc::kill(pid, signal.number()) } == 0
+}
+
+/// Send `signal` to the process group `pid` leads
+#[cfg(unix)]
+pub fn signal_group(pid: u32, signal: Signal) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: a negative PID addresses the process group
+    pid > 0 && unsafe { libc::kill(-pid, signal.number()) } == 0
+}
+
+/// Start `command` in its own process group, so it outlives the terminal
+/// and the process that started it
+#[cfg(unix)]
+pub fn detach(command: &mut tokio::process::Command) {
+    command.process_group(0);
+}
+
+#[cfg(windows)]
+mod windows {
+    use windows_sys::Win32::Foundation::{
+        CloseHandle, FILETIME, HANDLE, INVALID_HANDLE_VALUE, STILL_ACTIVE,
+    };
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32First, Process32Next, PROCESSENTRY32, TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, GetProcessTimes, OpenProcess, TerminateProcess,
+        PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE,
+    };
+
+    /// Exit code of processes ended through `Signal::Terminate` or `Signal::Kill`
+    const KILLED_EXIT_CODE: u32 = 1;
+
+    struct Process(HANDLE);
+
+    impl Process {
+        fn open(pid: u32, access: u32) -> Option<Self> {
+            // SAFETY: the handle is closed on drop
+            let handle = unsafe { OpenProcess(access, 0, pid) };
+            (!handle.is_null()).then_some(Process(handle))
+        }
+    }
+
+    impl Drop for Process {
+        fn drop(&mut self) {
+            // SAFETY: opened by `Process::open`, closed once
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    pub fn is_running(pid: u32) -> bool {
+        let Some(process) = Process::open(pid, PROCESS_QUERY_LIMITED_INFORMATION) else {
+            return false;
+        };
+        let mut code = 0;
+        // SAFETY: `code` outlives the call
+        let ok = unsafe { GetExitCodeProcess(process.0, &mut code) } != 0;
+        ok && code == STILL_ACTIVE as u32
+    }
+
+    pub fn terminate(pid: u32) -> bool {
+        Process::open(pid, PROCESS_TERMINATE)
+            // SAFETY: the handle was opened for termination
+            .is_some_and(|process| unsafe { TerminateProcess(process.0, KILLED_EXIT_CODE) } != 0)
+    }
+
+    /// When `pid` was started, in 100ns ticks, if it still runs
+    fn created_at(pid: u32) -> Option<u64> {
+        let process = Process::open(pid, PROCESS_QUERY_LIMITED_INFORMATION)?;
+        // SAFETY: all zeroes is a valid FILETIME
+        let mut times: [FILETIME; 4] = unsafe { std::mem::zeroed() };
+        let [created, exited, kernel, user] = &mut times;
+        // SAFETY: the times outlive the call
+        let ok = unsafe { GetProcessTimes(process.0, created, exited, kernel, user) } != 0;
+        ok.then(|| (u64::from(times[0].dwHighDateTime) << 32) | u64::from(times[0].dwLowDateTime))
+    }
+
+    /// Processes started by `pid`, and the ones they started
+    ///
+    /// Windows keeps the parent's ID after the parent exits, so a process
+    /// started before the parent is one of an earlier process that had its ID.
+    pub fn descendants(pid: u32) -> Vec<u32> {
+        // SAFETY: the snapshot is closed below
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+        if snapshot == INVALID_HANDLE_VALUE {
+            return Vec::new();
+        }
+        let mut parents = Vec::new();
+        // SAFETY: all zeroes is a valid PROCESSENTRY32
+        let mut entry: PROCESSENTRY32 = unsafe { std::mem::zeroed() };
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32>() as u32;
+        // SAFETY: `entry` is sized as the API expects
+        let mut more = unsafe { Process32First(snapshot, &mut entry) } != 0;
+        while more {
+            parents.push((entry.th32ProcessID, entry.th32ParentProcessID));
+            // SAFETY: as above
+            more = unsafe { Process32Next(snapshot, &mut entry) } != 0;
+        }
+        // SAFETY: opened above, closed once
+        unsafe { CloseHandle(snapshot) };
+
+        let mut found = vec![pid];
+        let mut index = 0;
+        while index < found.len() {
+            let parent = found[index];
+            let parent_created = created_at(parent);
+            found.extend(
+                parents
+                    .iter()
+                    .filter(|(child, of)| *of == parent && *child != pid && !found.contains(child))
+                    .filter(|(child, _)| {
+                        parent_created.is_some_and(|parent_created| {
+                            created_at(*child).is_some_and(|created| created >= parent_created)
+                        })
+                    })
+                    .map(|(child, _)| *child)
+                    .collect::<Vec<_>>(),
+            );
+            index += 1;
+        }
+        found.remove(0);
+        found
+    }
+}
+
+/// Whether `pid` is still running
+#[cfg(windows)]
+pub fn is_running(pid: u32) -> bool {
+    windows::is_running(pid)
+}
+
+/// `Terminate` and `Kill` end `pid`; Windows can't pause processes, so
+/// `Stop` and `Continue` do nothing and return false
+#[cfg(windows)]
+pub fn signal(pid: u32, signal: Signal) -> bool {
+    match signal {
+        Signal::Terminate | Signal::Kill => windows::terminate(pid),
+        Signal::Stop | Signal::Continue => false,
+    }
+}
+
+/// As `signal`, for `pid` and every process it started
+#[cfg(windows)]
+pub fn signal_group(pid: u32, signal: Signal) -> bool {
+    match signal {
+        Signal::Terminate | Signal::Kill => {
+            let children = windows::descendants(pid);
+            let ended = windows::terminate(pid);
+            for child in children {
+                windows::terminate(child);
+            }
+            ended
+        }
+        Signal::Stop | Signal::Continue => false,
+    }
+}
+
+/// Start `command` without a console and outside the Ctrl+C group of the
+/// process that started it
+#[cfg(windows)]
+pub fn detach(command: &mut tokio::process::Command) {
+    use windows_sys::Win32::System::Threading::{CREATE_NEW_PROCESS_GROUP, DETACHED_PROCESS};
+    command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_running_processes_from_exited_ones() {
+        #[cfg(unix)]
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        #[cfg(windows)]
+        let mut child = std::process::Command::new("ping")
+            .args(["-n", "30", "127.0.0.1"])
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+
+        assert!(is_running(child.id()));
+        assert!(signal(child.id(), Signal::Kill));
+        child.wait().unwrap();
+        assert!(!is_running(child.id()));
+    }
+}
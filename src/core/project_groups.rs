@@ -0,0 +1,170 @@
+//! Sub-projects of a monorepo
+//!
+//! A large repository registered as one project lumps all of its sessions
+//! together. Its sub-directories can be registered as projects of their own
+//! with the repository's project as their parent: sessions are listed under the
+//! sub-project they run in and rolled up at the parent, and a sub-project is
+//! addressed by its parent's name and its own, e.g. `--project mono/api`.
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+use super::json_api::{JsonApiResourceRef, ProjectRelationships, ProjectResource};
+use super::session_links::Relationship;
+
+/// How far parents are followed, in case stored projects form a loop
+const MAX_DEPTH: usize = 16;
+
+/// Project names are joined with `/` into addresses, so they can't contain one
+pub fn validate_project_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.contains('/') {
+        return Err(anyhow!(
+            "Invalid project name '{}': it can't be empty or contain '/'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// A sub-project lives in a directory below its parent's
+pub fn validate_subproject(path: &Path, parent_path: &Path) -> Result<()> {
+    if path == parent_path || !path.starts_with(parent_path) {
+        return Err(anyhow!(
+            "A sub-project has to be inside its parent's directory {}",
+            parent_path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Relationships of a project with `parent`, `None` for a top-level one
+pub fn parent_relationships(parent: Option<&str>) -> Option<ProjectRelationships> {
+    let parent = parent?;
+    Some(ProjectRelationships {
+        parent: Some(Relationship {
+            data: JsonApiResourceRef {
+                resource_type: "project".to_string(),
+                id: parent.to_string(),
+            },
+        }),
+        ..Default::default()
+    })
+}
+
+/// ID of the project's parent project
+pub fn parent_id(project: &ProjectResource) -> Option<&str> {
+    let parent = project.relationships.as_ref()?.parent.as_ref()?;
+    Some(parent.data.id.as_str())
+}
+
+/// Whether `id` is a sub-project of `ancestor`, directly or further down
+pub fn is_below(projects: &[ProjectResource], id: &str, ancestor: &str) -> bool {
+    let mut current = id;
+    for _ in 0..MAX_DEPTH {
+        let Some(parent) = projects
+            .iter()
+            .find(|p| p.id == current)
+            .and_then(parent_id)
+        else {
+            return false;
+        };
+        if parent == ancestor {
+            return true;
+        }
+        current = parent;
+    }
+    false
+}
+
+/// The project's name prefixed with its parents' names, e.g. `mono/api`
+pub fn qualified_name(projects: &[ProjectResource], project: &ProjectResource) -> String {
+    let name_of = |p: &ProjectResource| {
+        p.attributes
+            .as_ref()
+            .map_or_else(|| p.id.clone(), |a| a.name.clone())
+    };
+    let mut names = vec![name_of(project)];
+    let mut current = project;
+    while let Some(parent) = parent_id(current).and_then(|id| projects.iter().find(|p| p.id == id))
+    {
+        if names.len() == MAX_DEPTH {
+            break;
+        }
+        names.push(name_of(parent));
+        current = parent;
+    }
+    names.reverse();
+    names.join("/")
+}
+
+/// The project `address` refers to: its ID, its name, or its qualified name
+pub fn find_project<'a>(
+    projects: &'a [ProjectResource],
+    address: &str,
+) -> Option<&'a ProjectResource> {
+    projects
+        .iter()
+        .find(|p| p.id == address)
+        .or_else(|| {
+            projects
+                .iter()
+                .find(|p| p.attributes.as_ref().is_some_and(|a| a.name == address))
+        })
+        .or_else(|| {
+            address.contains('/').then(|| {
+                projects
+                    .iter()
+                    .find(|p| qualified_name(projects, p) == address)
+            })?
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::session::ProjectAttributes;
+
+    fn project(id: &str, name: &str, parent: Option<&str>) -> ProjectResource {
+        ProjectResource {
+            resource_type: "project".to_string(),
+            id: id.to_string(),
+            attributes: Some(ProjectAttributes {
+                name: name.to_string(),
+                path: format!("/src/{}", id),
+                color: "#61afef".to_string(),
+                icon: "📁".to_string(),
+                remote: None,
+                defaults: None,
+            }),
+            relationships: parent_relationships(parent),
+        }
+    }
+
+    #[test]
+    fn addresses_sub_projects_by_their_parents_name() {
+        let projects = vec![
+            project("1", "mono", None),
+            project("2", "api", Some("1")),
+            project("3", "auth", Some("2")),
+            project("4", "api", None),
+        ];
+
+        assert_eq!(qualified_name(&projects, &projects[2]), "mono/api/auth");
+        assert_eq!(find_project(&projects, "mono/api").unwrap().id, "2");
+        assert_eq!(find_project(&projects, "mono/api/auth").unwrap().id, "3");
+        assert_eq!(find_project(&projects, "4").unwrap().id, "4");
+        assert!(find_project(&projects, "mono/web").is_none());
+
+        assert!(is_below(&projects, "3", "1"));
+        assert!(is_below(&projects, "2", "1"));
+        assert!(!is_below(&projects, "1", "1"));
+        assert!(!is_below(&projects, "4", "1"));
+
+        assert!(validate_project_name("api").is_ok());
+        assert!(validate_project_name("mono/api").is_err());
+        let mono = Path::new("/src/mono");
+        assert!(validate_subproject(Path::new("/src/mono/api"), mono).is_ok());
+        assert!(validate_subproject(mono, mono).is_err());
+        assert!(validate_subproject(Path::new("/src/monorepo"), mono).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Names and badges for projects, read from their repository
+//!
+//! Directory names such as `src` or `app` make poor project names. When a
+//! project is added, its manifest or git remote suggests a better one: the
+//! `name` in `package.json` or `Cargo.toml`, else the repository name of the
+//! `origin` remote. The kind of manifest picks the project's icon and the name
+//! picks its color, so a project looks the same in every list.
+
+use std::path::Path;
+
+use super::session::default_session_color;
+
+/// Icon for projects without a manifest codemux recognizes
+pub const DEFAULT_PROJECT_ICON: &str = "📁";
+
+/// Manifests that identify a project's ecosystem, with the icon it gets
+const MANIFESTS: [(&str, &str); 5] = [
+    ("Cargo.toml", "🦀"),
+    ("package.json", "📦"),
+    ("pyproject.toml", "🐍"),
+    ("go.mod", "🐹"),
+    ("Gemfile", "💎"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectMetadata {
+    /// Name from the manifest or the git remote
+    pub name: Option<String>,
+    pub icon: String,
+    /// Accent color as `#rrggbb`
+    pub color: String,
+    /// URL of the `origin` remote
+    pub remote: Option<String>,
+}
+
+impl ProjectMetadata {
+    /// Metadata of the project at `path`; anything unreadable is left out
+    pub fn read(path: &Path) -> Self {
+        let remote = origin_remote(path);
+        let name = manifest_name(path)
+            .or_else(|| remote.as_deref().and_then(repository_name))
+            .filter(|name| !name.is_empty() && !name.contains('/'));
+        let icon = MANIFESTS
+            .iter()
+            .find(|(manifest, _)| path.join(manifest).is_file())
+            .map_or(DEFAULT_PROJECT_ICON, |(_, icon)| icon)
+            .to_string();
+        let seed = match &name {
+            Some(name) => name.clone(),
+            None => path.to_string_lossy().to_string(),
+        };
+        ProjectMetadata {
+            color: default_session_color(&seed),
+            name,
+            icon,
+            remote,
+        }
+    }
+
+    /// The suggested name, or the directory name without one
+    pub fn name_or_dir(&self, path: &Path) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unnamed-project")
+                .to_string()
+        })
+    }
+}
+
+/// `name` of the package in `Cargo.toml` or `package.json`, without an npm scope
+fn manifest_name(path: &Path) -> Option<String> {
+    let cargo = std::fs::read_to_string(path.join("Cargo.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|manifest| {
+            let name = manifest.get("package")?.get("name")?.as_str()?;
+            Some(name.to_string())
+        });
+    cargo.or_else(|| {
+        let content = std::fs::read_to_string(path.join("package.json")).ok()?;
+        let manifest: serde_json::Value = serde_json::from_str(&content).ok()?;
+        let name = manifest["name"].as_str()?;
+        Some(name.rsplit('/').next().unwrap_or(name).to_string())
+    })
+}
+
+fn origin_remote(path: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .current_dir(path)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// `codemux-cli` for `git@github.com:codemuxlab/codemux-cli.git` and the like
+fn repository_name(remote: &str) -> Option<String> {
+    let trimmed = remote.trim_end_matches('/');
+    let trimmed = trimmed.strip_suffix(".git").unwrap_or(trimmed);
+    let name = trimmed.rsplit(['/', ':']).next()?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_projects_after_their_manifest_or_remote() {
+        let base = std::env::temp_dir().join(format!("codemux-metadata-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let (web, api, plain) = (base.join("app"), base.join("src"), base.join("notes"));
+        for dir in [&web, &api, &plain] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(web.join("package.json"), r#"{"name": "@acme/storefront"}"#).unwrap();
+        std::fs::write(
+            api.join("Cargo.toml"),
+            "[package]\nname = \"billing-api\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+
+        let storefront = ProjectMetadata::read(&web);
+        assert_eq!(storefront.name.as_deref(), Some("storefront"));
+        assert_eq!(storefront.icon, "📦");
+        assert_eq!(storefront.color, ProjectMetadata::read(&web).color);
+        let billing = ProjectMetadata::read(&api);
+        assert_eq!(billing.name_or_dir(&api), "billing-api");
+        assert_eq!(billing.icon, "🦀");
+        let notes = ProjectMetadata::read(&plain);
+        assert_eq!(notes.icon, DEFAULT_PROJECT_ICON);
+
+        assert_eq!(
+            repository_name("git@github.com:codemuxlab/codemux-cli.git").as_deref(),
+            Some("codemux-cli")
+        );
+        assert_eq!(
+            repository_name("https://gitlab.com/acme/web/").as_deref(),
+            Some("web")
+        );
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+}
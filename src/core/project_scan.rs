@@ -0,0 +1,85 @@
+//! Finding git repositories below a directory, for `codemux scan`
+//!
+//! The walk stops at each repository it finds, so submodules and vendored
+//! checkouts inside one aren't registered as projects of their own. Hidden
+//! directories and dependency or build directories are skipped, they hold
+//! thousands of entries and no projects worth a session.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// How many directories deep `codemux scan` looks by default
+pub const DEFAULT_SCAN_DEPTH: usize = 4;
+
+/// Directories never descended into
+const SKIPPED_DIRS: [&str; 5] = ["node_modules", "target", "vendor", "venv", "__pycache__"];
+
+/// Whether `dir` is the top of a git repository or worktree
+pub fn is_git_repo(dir: &Path) -> bool {
+    // A directory in a repository, a file in worktrees and submodules
+    dir.join(".git").exists()
+}
+
+/// Git repositories at or below `root`, at most `max_depth` directories
+/// down, sorted by path
+pub fn find_git_repos(root: &Path, max_depth: usize) -> Result<Vec<PathBuf>> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("Invalid path {}", root.display()))?;
+    let mut repos = Vec::new();
+    let mut pending = vec![(root, 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        if is_git_repo(&dir) {
+            repos.push(dir);
+            continue;
+        }
+        if depth == max_depth {
+            continue;
+        }
+        // Unreadable directories are skipped rather than failing the scan
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+                continue;
+            }
+            // `file_type` doesn't follow symlinks, so links can't loop the walk
+            if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+    repos.sort();
+    Ok(repos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_repositories_without_descending_into_them() {
+        let root = std::env::temp_dir().join(format!("codemux-scan-{}", std::process::id()));
+        for dir in [
+            "api/.git",
+            "api/vendor/lib/.git",
+            "api/nested/.git",
+            "tools/cli/.git",
+            "tools/node_modules/dep/.git",
+            ".cache/repo/.git",
+            "deep/a/b/c/d/.git",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+
+        let repos = find_git_repos(&root, DEFAULT_SCAN_DEPTH).unwrap();
+        let root = root.canonicalize().unwrap();
+        assert_eq!(repos, [root.join("api"), root.join("tools/cli")]);
+        assert_eq!(find_git_repos(&root, 5).unwrap().len(), 3);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,23 +1,53 @@
 use anyhow::Result;
+use bytes::Bytes;
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::sync::{broadcast, mpsc, watch, Mutex};
+use tracing::Instrument;
 use ts_rs::TS;
+use utoipa::ToSchema;
+
+use crate::core::activity::{ActivityRecorder, ActivityTimeseries};
+use crate::core::heatmap::{ChangeHeatmap, SessionHeatmap};
+use crate::core::idle::IdleTracker;
+use crate::core::input_control::{InputControlHandle, InputMode};
+use crate::core::input_queue::{self, InputQueue, InputReceiver};
+use crate::core::input_transcript::{interrupt_sequence, InputTranscript};
+use crate::core::paste::{self, PasteConfig};
+use crate::core::telemetry::PipelineTrace;
+use crate::core::viewport::Viewports;
+use crate::utils::prompt_detector::{self, PromptDetector, PromptType};
 
 /// Default PTY dimensions
 pub const DEFAULT_PTY_COLS: u16 = 80;
 pub const DEFAULT_PTY_ROWS: u16 = 30;
 
+/// Wait between interrupting the agent and typing a retracted message back in
+const RETRACT_RESTORE_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// How long a session whose output ended waits for the agent's exit code
+const EXIT_CODE_WAIT: std::time::Duration = std::time::Duration::from_secs(1);
+
 /// Connection status for WebSocket clients
 #[derive(Debug, Clone)]
 pub enum ConnectionStatus {
     Connected,
     Disconnected,
-    Reconnecting { attempt: u32, max_attempts: u32 },
+    Reconnecting {
+        attempt: u32,
+        max_attempts: u32,
+    },
+    /// Reconnected, but the server had restarted and restored the session
+    Restored,
+    /// The agent exited, so there is nothing to reconnect to
+    Exited {
+        code: u32,
+    },
 }
 
 /// Messages that can be sent to control the PTY session
@@ -31,6 +61,29 @@ pub enum PtyControlMessage {
     RequestKeyframe {
         response_tx: tokio::sync::oneshot::Sender<GridUpdateMessage>,
     },
+    /// Request the screen as an escape sequence stream that reproduces it (for raw clients)
+    RequestScreenState {
+        response_tx: tokio::sync::oneshot::Sender<ScreenState>,
+    },
+    /// Request up to `lines` rows of scrollback plus the visible screen, oldest first
+    RequestScrollback {
+        lines: usize,
+        response_tx: tokio::sync::oneshot::Sender<Vec<Vec<GridCell>>>,
+    },
+    /// Request the per-minute I/O history of the session
+    RequestActivity {
+        response_tx: tokio::sync::oneshot::Sender<ActivityTimeseries>,
+    },
+    /// Request how often each cell changed over the last minute
+    RequestHeatmap {
+        response_tx: tokio::sync::oneshot::Sender<SessionHeatmap>,
+    },
+    /// Interrupt the agent and hand back the last message sent to it, writing
+    /// it back into the agent's input when `restore` is set
+    Retract {
+        restore: bool,
+        response_tx: tokio::sync::oneshot::Sender<Option<String>>,
+    },
 }
 
 /// Internal control messages for PTY session coordination
@@ -66,7 +119,7 @@ impl ScrollThrottle {
 }
 
 /// Key event modifiers
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, TS, ToSchema)]
 #[ts(export)]
 pub struct KeyModifiers {
     pub shift: bool,
@@ -76,7 +129,7 @@ pub struct KeyModifiers {
 }
 
 /// Key codes that can be sent to terminal
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, ToSchema)]
 #[ts(export)]
 pub enum KeyCode {
     /// A character key
@@ -114,10 +167,12 @@ pub enum KeyCode {
 }
 
 /// Key event structure
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, ToSchema)]
 #[ts(export)]
 pub struct KeyEvent {
     pub code: KeyCode,
+    /// None pressed when left out
+    #[serde(default)]
     pub modifiers: KeyModifiers,
 }
 
@@ -140,19 +195,66 @@ pub enum PtyInput {
         lines: u16,
         client_id: String,
     },
+    /// Raw bytes written to the PTY as-is
+    Raw { data: Vec<u8>, client_id: String },
+    /// Pasted text, bracketed if the agent turned that on; dropped when
+    /// larger than the session's paste limit
+    Paste { text: String, client_id: String },
+}
+
+impl PtyInput {
+    /// Client whose input this writes to the agent; scrolling only moves the view
+    pub fn writer(&self) -> Option<&str> {
+        match self {
+            PtyInput::Key { client_id, .. }
+            | PtyInput::Raw { client_id, .. }
+            | PtyInput::Paste { client_id, .. } => Some(client_id),
+            PtyInput::Scroll { .. } => None,
+        }
+    }
+
+    /// Client that sent this input
+    pub fn client_id(&self) -> &str {
+        match self {
+            PtyInput::Key { client_id, .. }
+            | PtyInput::Raw { client_id, .. }
+            | PtyInput::Paste { client_id, .. }
+            | PtyInput::Scroll { client_id, .. } => client_id,
+        }
+    }
 }
 
 /// Messages representing PTY input from clients
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PtyInputMessage {
     pub input: PtyInput,
+    /// Client's number for this input, acknowledged once it was written
+    #[serde(default)]
+    pub seq: Option<u64>,
+    /// Span of the client message, continued by the PTY writer
+    #[serde(skip, default = "tracing::Span::none")]
+    pub span: tracing::Span,
 }
 
 /// Messages representing PTY output to clients
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The bytes are shared: every subscriber of the broadcast gets the same
+/// buffer, cloning a message only bumps a reference count.
+#[derive(Debug, Clone)]
 pub struct PtyOutputMessage {
-    pub data: Vec<u8>,
+    pub data: Bytes,
     pub timestamp: std::time::SystemTime,
+    /// Counts up from 1 with each message of a session, see `ScreenState`
+    pub seq: u64,
+}
+
+/// The screen as escape sequences that redraw it, for raw clients to start from
+#[derive(Debug, Clone)]
+pub struct ScreenState {
+    pub data: Vec<u8>,
+    /// `seq` of the last output message the screen includes; clients that
+    /// subscribed to the output before asking skip the messages up to it
+    pub seq: u64,
 }
 
 /// Serializable version of PtySize for grid messages
@@ -173,7 +275,7 @@ impl From<PtySize> for SerializablePtySize {
 }
 
 /// Terminal grid cell representation
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, ToSchema)]
 #[ts(export)]
 pub struct GridCell {
     pub char: String,
@@ -191,7 +293,7 @@ pub struct GridCell {
     pub reverse: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, ToSchema)]
 #[ts(export)]
 pub enum TerminalColor {
     /// Default terminal color (use theme default)
@@ -204,6 +306,20 @@ pub enum TerminalColor {
     Rgb { r: u8, g: u8, b: u8 },
 }
 
+impl From<&vt100::Cell> for GridCell {
+    fn from(cell: &vt100::Cell) -> Self {
+        GridCell {
+            char: cell.contents().to_string(),
+            fg_color: PtySession::vt100_to_terminal_color(cell.fgcolor()),
+            bg_color: PtySession::vt100_to_terminal_color(cell.bgcolor()),
+            bold: cell.bold(),
+            italic: cell.italic(),
+            underline: cell.underline(),
+            reverse: cell.inverse(),
+        }
+    }
+}
+
 impl GridCell {
     /// Check if this cell is just an empty space with no styling
     pub fn is_empty_space(&self) -> bool {
@@ -236,6 +352,11 @@ pub enum GridUpdateMessage {
         scrollback_total: usize,    // total lines available in scrollback buffer
         #[ts(type = "string")]
         timestamp: std::time::SystemTime,
+        /// (row, col, len, cell): `len` identical cells from (row, col) on, in place of
+        /// `cells` for clients with the `rle` capability, see `run_length`
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        #[ts(as = "Option<Vec<(u16, u16, u16, GridCell)>>", optional)]
+        runs: Vec<(u16, u16, u16, GridCell)>,
     },
     /// Incremental changes (sent to existing clients)
     Diff {
@@ -252,12 +373,26 @@ pub enum GridUpdateMessage {
 /// Channel interface for communicating with PTY session
 #[derive(Clone)]
 pub struct PtyChannels {
-    pub input_tx: mpsc::UnboundedSender<PtyInputMessage>,
+    /// Bounded, see `input_queue`
+    pub input_tx: InputQueue,
     pub output_tx: broadcast::Sender<PtyOutputMessage>,
     pub control_tx: mpsc::UnboundedSender<PtyControlMessage>,
     pub size_tx: broadcast::Sender<PtySize>,
     pub grid_tx: broadcast::Sender<GridUpdateMessage>,
     pub connection_status_tx: broadcast::Sender<ConnectionStatus>,
+    /// Warnings for everyone watching the session, e.g. a mass deletion
+    pub alert_tx: broadcast::Sender<String>,
+    /// Which client may type, see `input_control`
+    pub input_control: InputControlHandle,
+    /// Output and attached clients, see `idle`
+    pub idle: IdleTracker,
+    /// The approval the agent waits for, if any, see `utils::prompt_detector`
+    pub prompt_tx: watch::Sender<Option<PromptType>>,
+    pub trace: PipelineTrace,
+    /// Sizes of the attached clients, see `viewport`
+    pub viewports: Viewports,
+    /// The agent's exit code, once it exited
+    pub exit_tx: watch::Sender<Option<u32>>,
 }
 
 impl PtyChannels {
@@ -291,6 +426,72 @@ impl PtyChannels {
         tracing::debug!("PtyChannels::request_keyframe - Received keyframe successfully");
         Ok(keyframe)
     }
+
+    /// Request escape sequences that redraw the current screen, cursor and modes
+    pub async fn request_screen_state(&self) -> Result<ScreenState> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.control_tx
+            .send(PtyControlMessage::RequestScreenState { response_tx: tx })
+            .map_err(|_| anyhow::anyhow!("PTY session is not running"))?;
+
+        rx.await
+            .map_err(|_| anyhow::anyhow!("PTY session did not respond to screen state request"))
+    }
+
+    /// Request the last `lines` rows of terminal history (scrollback + visible screen)
+    pub async fn request_scrollback(&self, lines: usize) -> Result<Vec<Vec<GridCell>>> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.control_tx
+            .send(PtyControlMessage::RequestScrollback {
+                lines,
+                response_tx: tx,
+            })
+            .map_err(|_| anyhow::anyhow!("PTY session is not running"))?;
+
+        rx.await
+            .map_err(|_| anyhow::anyhow!("PTY session did not respond to scrollback request"))
+    }
+
+    /// Request the per-minute input/output timeseries of the session
+    pub async fn request_activity(&self) -> Result<ActivityTimeseries> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.control_tx
+            .send(PtyControlMessage::RequestActivity { response_tx: tx })
+            .map_err(|_| anyhow::anyhow!("PTY session is not running"))?;
+
+        rx.await
+            .map_err(|_| anyhow::anyhow!("PTY session did not respond to activity request"))
+    }
+
+    /// Request the per-cell change counts of the session's screen
+    pub async fn request_heatmap(&self) -> Result<SessionHeatmap> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.control_tx
+            .send(PtyControlMessage::RequestHeatmap { response_tx: tx })
+            .map_err(|_| anyhow::anyhow!("PTY session is not running"))?;
+
+        rx.await
+            .map_err(|_| anyhow::anyhow!("PTY session did not respond to heatmap request"))
+    }
+
+    /// Undo an accidental send: interrupt the agent and return the last message
+    pub async fn retract(&self, restore: bool) -> Result<Option<String>> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.control_tx
+            .send(PtyControlMessage::Retract {
+                restore,
+                response_tx: tx,
+            })
+            .map_err(|_| anyhow::anyhow!("PTY session is not running"))?;
+
+        rx.await
+            .map_err(|_| anyhow::anyhow!("PTY session did not respond to retract request"))
+    }
 }
 
 /// Standalone PTY session component that manages subprocess and I/O
@@ -298,6 +499,11 @@ pub struct PtySession {
     id: String,
     agent: String,
     args: Vec<String>,
+    pid: Option<u32>,
+
+    // The agent process, waited for to learn its exit code
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+    exit_tx: watch::Sender<Option<u32>>,
 
     // Internal PTY management
     pty: Arc<Mutex<Box<dyn portable_pty::MasterPty + Send>>>,
@@ -316,8 +522,35 @@ pub struct PtySession {
     // Debounce timing for keyframe generation
     last_activity: Arc<Mutex<Instant>>,
 
+    // Per-minute I/O counters for the metrics endpoint
+    activity: Arc<Mutex<ActivityRecorder>>,
+
+    // Per-cell change counts, for spotting repaint storms
+    heatmap: Arc<Mutex<ChangeHeatmap>>,
+
+    // Largest paste written to the agent, from `[paste] max_bytes`
+    max_paste_bytes: usize,
+
+    // The agent's own approval prompts, from `[agents.<name>] prompt_patterns`
+    approval_patterns: Vec<regex::Regex>,
+
+    // Messages sent to the agent, for retracting the last one
+    input_transcript: Arc<Mutex<InputTranscript>>,
+
+    // Which client's input reaches the agent
+    input_control: InputControlHandle,
+
+    // Output and attached clients, for ending sessions nobody uses
+    idle: IdleTracker,
+
+    // The approval prompt on screen, for banners in clients
+    prompt_tx: watch::Sender<Option<PromptType>>,
+
+    // Links the spans of the input, processing and send stages
+    trace: PipelineTrace,
+
     // Channel endpoints
-    input_rx: mpsc::UnboundedReceiver<PtyInputMessage>,
+    input_rx: InputReceiver,
     output_tx: broadcast::Sender<PtyOutputMessage>,
     control_rx: mpsc::UnboundedReceiver<PtyControlMessage>,
     size_tx: broadcast::Sender<PtySize>,
@@ -330,6 +563,21 @@ impl PtySession {
         id: String,
         agent: String,
         args: Vec<String>,
+        env: &std::collections::BTreeMap<String, String>,
+        working_dir: std::path::PathBuf,
+    ) -> Result<(Self, PtyChannels)> {
+        let program = agent.clone();
+        Self::new_with_program(id, agent, &program, args, env, working_dir)
+    }
+
+    /// Create a PTY session for `agent` that runs a different program, such as
+    /// reptyr taking over an agent that was started outside codemux
+    pub fn new_with_program(
+        id: String,
+        agent: String,
+        program: &str,
+        args: Vec<String>,
+        env: &std::collections::BTreeMap<String, String>,
         working_dir: std::path::PathBuf,
     ) -> Result<(Self, PtyChannels)> {
         let pty_system = NativePtySystem::default();
@@ -351,7 +599,7 @@ impl PtySession {
             pixel_height: 0,
         })?;
 
-        let mut cmd = CommandBuilder::new(&agent);
+        let mut cmd = CommandBuilder::new(program);
         for arg in &args {
             cmd.arg(arg);
         }
@@ -370,21 +618,35 @@ impl PtySession {
         cmd.env("FORCE_COLOR", "1");
         cmd.env("COLUMNS", initial_cols.to_string());
         cmd.env("LINES", initial_rows.to_string());
+        // Lets wrappers such as `codemux shim` tell they are already inside a session
+        cmd.env("CODEMUX_SESSION_ID", &id);
 
-        tracing::info!("Spawning command: {} with args: {:?}", agent, args);
-        let _child = pty_pair.slave.spawn_command(cmd)?;
+        // Variables requested for this session win over everything inherited
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        tracing::info!("Spawning command: {} with args: {:?}", program, args);
+        let child = pty_pair.slave.spawn_command(cmd)?;
+        let pid = child.process_id();
         tracing::debug!("Command spawned successfully");
 
         let _reader = pty_pair.master.try_clone_reader()?;
         let writer = pty_pair.master.take_writer()?;
 
         // Create channels
-        let (input_tx, input_rx) = mpsc::unbounded_channel();
+        let (input_tx, input_rx) = input_queue::channel();
         let (output_tx, _) = broadcast::channel(1000);
         let (control_tx, control_rx) = mpsc::unbounded_channel();
         let (size_tx, _) = broadcast::channel(100);
         let (grid_tx, _) = broadcast::channel(1000);
         let (connection_status_tx, _) = broadcast::channel(10);
+        let (alert_tx, _) = broadcast::channel(10);
+        let input_control = InputControlHandle::default();
+        let idle = IdleTracker::default();
+        let prompt_tx = watch::Sender::new(None);
+        let trace = PipelineTrace::default();
+        let exit_tx = watch::Sender::new(None);
 
         // Create client channel interface
         let channels = PtyChannels {
@@ -394,12 +656,22 @@ impl PtySession {
             size_tx: size_tx.clone(),
             grid_tx: grid_tx.clone(),
             connection_status_tx: connection_status_tx.clone(),
+            alert_tx,
+            input_control: input_control.clone(),
+            idle: idle.clone(),
+            prompt_tx: prompt_tx.clone(),
+            trace: trace.clone(),
+            viewports: Viewports::default(),
+            exit_tx: exit_tx.clone(),
         };
 
         let session = PtySession {
             id,
             agent,
             args,
+            pid,
+            child,
+            exit_tx,
             pty: Arc::new(Mutex::new(pty_pair.master)),
             writer: Arc::new(Mutex::new(writer)),
             current_size: Arc::new(Mutex::new(PtySize {
@@ -418,6 +690,15 @@ impl PtySession {
             cursor_pos: Arc::new(Mutex::new((0, 0))),
             cursor_visible: Arc::new(Mutex::new(true)), // Default to visible
             last_activity: Arc::new(Mutex::new(Instant::now())),
+            activity: Arc::new(Mutex::new(ActivityRecorder::new())),
+            heatmap: Arc::new(Mutex::new(ChangeHeatmap::new())),
+            max_paste_bytes: PasteConfig::default().max_bytes,
+            approval_patterns: Vec::new(),
+            input_transcript: Arc::new(Mutex::new(InputTranscript::new())),
+            input_control,
+            idle,
+            prompt_tx,
+            trace,
             input_rx,
             output_tx,
             control_rx,
@@ -428,6 +709,26 @@ impl PtySession {
         Ok((session, channels))
     }
 
+    /// Drop pastes larger than `max_bytes` instead of writing them to the agent
+    pub fn set_max_paste_bytes(&mut self, max_bytes: usize) {
+        self.max_paste_bytes = max_bytes;
+    }
+
+    /// Recognize the agent's own approval prompts as well as the built-in ones
+    pub fn set_approval_patterns(&mut self, patterns: Vec<regex::Regex>) {
+        self.approval_patterns = patterns;
+    }
+
+    /// Choose which clients may type, see `input_control`
+    pub fn set_input_mode(&mut self, mode: InputMode) {
+        self.input_control.set_mode(mode);
+    }
+
+    /// Process ID of the program running in the PTY
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
     /// Start the PTY session tasks - runs until completion or error
     pub async fn start(self) -> Result<()> {
         tracing::info!("Starting PTY session tasks for agent: {}", self.agent);
@@ -438,6 +739,9 @@ impl PtySession {
 
         // Extract all channels and state before creating tasks
         let PtySession {
+            id,
+            mut child,
+            exit_tx,
             pty,
             writer,
             current_size,
@@ -447,6 +751,15 @@ impl PtySession {
             cursor_pos,
             cursor_visible,
             last_activity,
+            activity,
+            heatmap,
+            max_paste_bytes,
+            approval_patterns,
+            input_transcript,
+            input_control,
+            idle,
+            prompt_tx,
+            trace,
             input_rx,
             output_tx,
             control_rx,
@@ -455,12 +768,33 @@ impl PtySession {
             ..
         } = self;
 
+        // Wait for the agent on its own thread; its exit code ends the session
+        let waiter_exit_tx = exit_tx.clone();
+        let waiter_session_id = id.clone();
+        tokio::task::spawn_blocking(move || match child.wait() {
+            Ok(status) => {
+                tracing::info!(
+                    "Agent of session {} exited with code {}",
+                    waiter_session_id,
+                    status.exit_code()
+                );
+                waiter_exit_tx.send_replace(Some(status.exit_code()));
+            }
+            Err(e) => tracing::warn!(
+                "Failed to wait for the agent of session {}: {}",
+                waiter_session_id,
+                e
+            ),
+        });
+        let mut exit_rx = exit_tx.subscribe();
+        let mut exit_code_rx = exit_tx.subscribe();
+
         // Clone the reader for the reader task - use std::sync::Mutex for blocking context
         let reader = Arc::new(std::sync::Mutex::new(pty.lock().await.try_clone_reader()?));
         tracing::debug!("PTY reader cloned successfully");
 
         // Create channel for sending raw data from blocking reader to async processor
-        let (raw_data_tx, mut raw_data_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (raw_data_tx, mut raw_data_rx) = mpsc::unbounded_channel::<Bytes>();
 
         // Create the blocking PTY reader task
         let reader_task = tokio::task::spawn_blocking(move || {
@@ -482,7 +816,8 @@ impl PtySession {
                         break;
                     }
                     Ok(n) => {
-                        let data = read_buffer[..n].to_vec();
+                        // The one copy of the output; everything downstream shares it
+                        let data = Bytes::copy_from_slice(&read_buffer[..n]);
 
                         // Debug PTY output
                         let data_str = String::from_utf8_lossy(&data);
@@ -530,7 +865,12 @@ impl PtySession {
             tracing::info!("PTY reader task exiting");
         });
 
+        // Sequence number of the last output message, bumped while the
+        // parser is locked so a screen state includes a message entirely or not at all
+        let output_seq = Arc::new(AtomicU64::new(0));
+
         // Create async data processor task
+        let processor_output_seq = output_seq.clone();
         let processor_buffer = buffer.clone();
         let processor_vt_parser = vt_parser.clone();
         let processor_grid_state = grid_state.clone();
@@ -541,10 +881,17 @@ impl PtySession {
         let processor_output_tx = output_tx.clone();
         let processor_grid_tx = grid_tx.clone();
         let processor_agent = self.agent.clone();
+        let processor_activity = activity.clone();
+        let processor_heatmap = heatmap.clone();
+        let processor_trace = trace.clone();
+        let processor_idle = idle.clone();
+        let processor_prompt_tx = prompt_tx;
+        let processor_session_id = id.clone();
 
         let processor_task = tokio::spawn(async move {
             let mut previous_grid: HashMap<(u16, u16), GridCell> = HashMap::new();
-            let mut pending_data: Vec<Vec<u8>> = Vec::new();
+            let prompt_detector = PromptDetector::with_approval_patterns(&approval_patterns);
+            let mut pending_data: Vec<Bytes> = Vec::new();
             let mut last_data_time = std::time::Instant::now();
             let debounce_delay = tokio::time::Duration::from_millis(16); // True debounce: wait for inactivity
 
@@ -554,6 +901,8 @@ impl PtySession {
                     data = raw_data_rx.recv() => {
                         match data {
                             Some(data) => {
+                                processor_activity.lock().await.record_output(data.len());
+                                processor_idle.output();
                                 pending_data.push(data);
                                 last_data_time = std::time::Instant::now(); // Update last activity time
                             }
@@ -572,6 +921,12 @@ impl PtySession {
                             // Process all accumulated data at once
                             tracing::trace!("Processing {} accumulated data chunks after {}ms of inactivity",
                                 pending_data.len(), last_data_time.elapsed().as_millis());
+                            let process_span = tracing::info_span!(
+                                "vt100.process",
+                                session_id = %processor_session_id,
+                                chunks = pending_data.len()
+                            );
+                            processor_trace.continue_input(&process_span);
 
                             // Track cursor before processing
                             let cursor_before = {
@@ -583,41 +938,41 @@ impl PtySession {
                             };
 
                             // First, update buffer and parse all data through VT100
-                        let mut all_data = Vec::new();
-                        for data in pending_data.drain(..) {
-                            // Update the terminal buffer
-                            {
-                                let mut buffer_guard = processor_buffer.lock().await;
-                                buffer_guard.extend_from_slice(&data);
-
-                                // Keep buffer size reasonable (last 64KB of output)
-                                if buffer_guard.len() > 65536 {
-                                    let drain_count = buffer_guard.len() - 65536;
-                                    buffer_guard.drain(0..drain_count);
-                                }
+                        // A lone chunk is passed on as it is, several are joined once
+                        let all_data = match pending_data.as_slice() {
+                            [data] => data.clone(),
+                            chunks => Bytes::from(chunks.concat()),
+                        };
+                        // Update the terminal buffer
+                        {
+                            let mut buffer_guard = processor_buffer.lock().await;
+                            buffer_guard.extend_from_slice(&all_data);
+
+                            // Keep buffer size reasonable (last 64KB of output)
+                            if buffer_guard.len() > 65536 {
+                                let drain_count = buffer_guard.len() - 65536;
+                                buffer_guard.drain(0..drain_count);
                             }
+                        }
 
-                            // Process through VT100 parser
-                            {
-                                let mut parser_guard = processor_vt_parser.lock().await;
+                        // Process through VT100 parser, all chunks under one lock
+                        // since they go out as one message
+                        let (seq, vt_cursor_visible) = {
+                            let mut parser_guard = processor_vt_parser.lock().await;
+                            for data in pending_data.drain(..) {
                                 parser_guard.process(&data);
                             }
+                            let seq = processor_output_seq.fetch_add(1, Ordering::SeqCst) + 1;
+                            (seq, !parser_guard.screen().hide_cursor())
+                        };
 
-                            // Check cursor visibility from VT100 screen state
-                            {
-                                let parser_guard = processor_vt_parser.lock().await;
-                                let screen = parser_guard.screen();
-                                let vt_cursor_visible = !screen.hide_cursor();
-                                drop(parser_guard);
-
-                                let mut cursor_vis_guard = processor_cursor_visible.lock().await;
-                                if *cursor_vis_guard != vt_cursor_visible {
-                                    *cursor_vis_guard = vt_cursor_visible;
-                                    tracing::trace!("Cursor visibility changed to: {}", vt_cursor_visible);
-                                }
+                        // Check cursor visibility from VT100 screen state
+                        {
+                            let mut cursor_vis_guard = processor_cursor_visible.lock().await;
+                            if *cursor_vis_guard != vt_cursor_visible {
+                                *cursor_vis_guard = vt_cursor_visible;
+                                tracing::trace!("Cursor visibility changed to: {}", vt_cursor_visible);
                             }
-
-                            all_data.extend_from_slice(&data);
                         }
 
                         // Log first 100 chars of processed data for debugging
@@ -639,6 +994,7 @@ impl PtySession {
                         }
 
                         // Now generate a single grid update for all changes
+                        let diff_span = tracing::info_span!(parent: &process_span, "grid.diff");
                         let grid_update = Self::extract_grid_changes(
                             &processor_agent,
                             &processor_vt_parser,
@@ -648,12 +1004,14 @@ impl PtySession {
                             &processor_current_size,
                             &mut previous_grid,
                         )
+                        .instrument(diff_span.clone())
                         .await;
 
                         if let Some(update) = &grid_update {
                             // Categorize the types of changes for debugging
                             match update {
                                 GridUpdateMessage::Keyframe { size, cells, cursor, .. } => {
+                                    processor_heatmap.lock().await.record_keyframe();
                                     tracing::trace!(
                                         "Generated keyframe: {} total cells, size {}x{}, cursor: ({}, {})",
                                         cells.len(),
@@ -664,6 +1022,10 @@ impl PtySession {
                                     );
                                 }
                                 GridUpdateMessage::Diff { changes, cursor, .. } => {
+                                    processor_heatmap
+                                        .lock()
+                                        .await
+                                        .record_diff(changes.iter().map(|(row, col, _)| (*row, *col)));
                                     let mut clear_changes = 0;
                                     let mut text_changes = 0;
                                     let mut style_changes = 0;
@@ -744,6 +1106,30 @@ impl PtySession {
                                     }
                                 }
                             }
+                            // Only a changed screen can show or answer a prompt; it's detected
+                            // before the screen goes out, so watchers of both see it first
+                            let tail = {
+                                let parser = processor_vt_parser.lock().await;
+                                let screen = parser.screen();
+                                let (rows, cols) = screen.size();
+                                prompt_detector::screen_tail(
+                                    (0..rows)
+                                        .rev()
+                                        .map(|row| screen.contents_between(row, 0, row, cols)),
+                                )
+                            };
+                            let prompt = prompt_detector
+                                .detect(&tail)
+                                .filter(PromptType::needs_approval);
+                            processor_prompt_tx.send_if_modified(|current| {
+                                if *current == prompt {
+                                    return false;
+                                }
+                                tracing::debug!("Approval prompt on screen: {:?}", prompt);
+                                *current = prompt;
+                                true
+                            });
+                            processor_trace.update_sent(&diff_span);
                             let _ = processor_grid_tx.send(update.clone());
                         } else {
                             tracing::trace!("No grid update generated (no changes)");
@@ -760,6 +1146,7 @@ impl PtySession {
                             let msg = PtyOutputMessage {
                                 data: all_data,
                                 timestamp: std::time::SystemTime::now(),
+                                seq,
                             };
                             let _ = processor_output_tx.send(msg);
                         }
@@ -778,9 +1165,26 @@ impl PtySession {
         let input_writer = writer.clone();
         let input_vt_parser = vt_parser.clone();
         let input_internal_tx = internal_control_tx.clone();
+        let input_activity = activity.clone();
+        let input_transcript_log = input_transcript.clone();
+        let input_trace = trace.clone();
+        let input_session_id = id.clone();
+        // Runs next to the input task, whose writes may get stuck
+        let input_lag = input_rx.watch_lag();
         let input_task = tokio::spawn(async move {
             let mut input_rx = input_rx;
             while let Some(msg) = input_rx.recv().await {
+                let write_span = tracing::info_span!(parent: &msg.span, "pty.write", session_id = %input_session_id);
+                if let Some(client_id) = msg.input.writer() {
+                    if !input_control.try_write(client_id) {
+                        tracing::debug!(
+                            "Dropping input from {}, it doesn't have control",
+                            client_id
+                        );
+                        input_rx.ack(&msg, false);
+                        continue;
+                    }
+                }
                 match &msg.input {
                     PtyInput::Key { event, .. } => {
                         tracing::trace!("Processing key event: {:?}", event);
@@ -794,13 +1198,60 @@ impl PtySession {
                         }
 
                         let bytes = Self::key_event_to_bytes(event);
+                        input_activity.lock().await.record_input(bytes.len());
+                        input_transcript_log.lock().await.record_key(event);
 
-                        let mut writer_guard = input_writer.lock().await;
-                        if let Err(e) = writer_guard.write_all(&bytes) {
+                        if let Err(e) = Self::write_pty(&input_writer, bytes).await {
                             tracing::error!("Failed to write to PTY: {}", e);
                             break;
                         }
-                        let _ = writer_guard.flush();
+                        input_trace.input_written(&write_span);
+                    }
+                    PtyInput::Raw { data, .. } => {
+                        tracing::trace!("Processing raw input: {} bytes", data.len());
+                        input_activity.lock().await.record_input(data.len());
+                        input_transcript_log.lock().await.record_bytes(data);
+
+                        // Raw input counts as typing, so jump back to live content too
+                        if let Err(e) = input_internal_tx.send(InternalControlMessage::ResetScroll)
+                        {
+                            tracing::warn!("Failed to send scroll reset message: {}", e);
+                        }
+
+                        if let Err(e) = Self::write_pty(&input_writer, data.clone()).await {
+                            tracing::error!("Failed to write to PTY: {}", e);
+                            break;
+                        }
+                        input_trace.input_written(&write_span);
+                    }
+                    PtyInput::Paste { text, client_id } => {
+                        if text.len() > max_paste_bytes {
+                            tracing::warn!(
+                                "Dropping {} byte paste from {}, over the {} byte limit",
+                                text.len(),
+                                client_id,
+                                max_paste_bytes
+                            );
+                            input_rx.ack(&msg, false);
+                            continue;
+                        }
+                        tracing::trace!("Processing paste: {} bytes", text.len());
+                        input_activity.lock().await.record_input(text.len());
+                        input_transcript_log.lock().await.record_paste(text);
+
+                        if let Err(e) = input_internal_tx.send(InternalControlMessage::ResetScroll)
+                        {
+                            tracing::warn!("Failed to send scroll reset message: {}", e);
+                        }
+
+                        // Bracketed only if the agent asked for it, or the markers show up as text
+                        let bracketed = input_vt_parser.lock().await.screen().bracketed_paste();
+                        let bytes = paste::paste_bytes(text, bracketed);
+                        if let Err(e) = Self::write_pty(&input_writer, bytes).await {
+                            tracing::error!("Failed to write to PTY: {}", e);
+                            break;
+                        }
+                        input_trace.input_written(&write_span);
                     }
                     PtyInput::Scroll {
                         direction, lines, ..
@@ -848,6 +1299,7 @@ impl PtySession {
                         }
                     }
                 }
+                input_rx.ack(&msg, true);
             }
         });
 
@@ -857,8 +1309,14 @@ impl PtySession {
         let control_size_tx = size_tx.clone();
         let control_grid_tx = grid_tx.clone();
         let control_vt_parser = vt_parser.clone();
+        let control_output_seq = output_seq.clone();
         let control_cursor_pos = cursor_pos.clone();
         let control_cursor_visible = cursor_visible.clone();
+        let control_activity = activity.clone();
+        let control_heatmap = heatmap.clone();
+        let control_writer = writer.clone();
+        let control_transcript = input_transcript.clone();
+        let control_agent = self.agent.clone();
 
         let control_task = tokio::spawn(async move {
             tracing::info!("PTY Control task - Starting control message loop");
@@ -934,6 +1392,76 @@ impl PtySession {
                                     tracing::debug!("Control task - Keyframe sent successfully to client");
                                 }
                             }
+                            PtyControlMessage::RequestScreenState { response_tx } => {
+                                let state = {
+                                    let parser_guard = control_vt_parser.lock().await;
+                                    ScreenState {
+                                        data: parser_guard.screen().state_formatted(),
+                                        seq: control_output_seq.load(Ordering::SeqCst),
+                                    }
+                                };
+                                if response_tx.send(state).is_err() {
+                                    tracing::warn!("Control task - Screen state requester went away");
+                                }
+                            }
+                            PtyControlMessage::RequestScrollback { lines, response_tx } => {
+                                tracing::debug!("Control task - Scrollback requested ({} lines)", lines);
+                                let history = {
+                                    let mut parser_guard = control_vt_parser.lock().await;
+                                    crate::core::render::history_lines(&mut parser_guard, lines)
+                                };
+
+                                if response_tx.send(history).is_err() {
+                                    tracing::warn!("Control task - Scrollback requester went away");
+                                }
+                            }
+                            PtyControlMessage::RequestActivity { response_tx } => {
+                                let timeseries = control_activity.lock().await.timeseries();
+                                if response_tx.send(timeseries).is_err() {
+                                    tracing::warn!("Control task - Activity requester went away");
+                                }
+                            }
+                            PtyControlMessage::RequestHeatmap { response_tx } => {
+                                let size = *control_current_size.lock().await;
+                                let heatmap =
+                                    control_heatmap.lock().await.snapshot(size.rows, size.cols);
+                                if response_tx.send(heatmap).is_err() {
+                                    tracing::warn!("Control task - Heatmap requester went away");
+                                }
+                            }
+                            PtyControlMessage::Retract { restore, response_tx } => {
+                                let message = control_transcript.lock().await.take_last();
+                                tracing::info!(
+                                    "Retracting last message ({} bytes)",
+                                    message.as_ref().map_or(0, |m| m.len())
+                                );
+                                let interrupt = interrupt_sequence(&control_agent).to_vec();
+                                if let Err(e) = Self::write_pty(&control_writer, interrupt).await {
+                                    tracing::error!("Failed to interrupt agent: {}", e);
+                                }
+
+                                if let Some(text) = message.clone().filter(|_| restore) {
+                                    control_transcript.lock().await.restore(&text);
+                                    let writer = control_writer.clone();
+                                    // Give the agent a moment to settle after the interrupt
+                                    tokio::spawn(async move {
+                                        tokio::time::sleep(RETRACT_RESTORE_DELAY).await;
+                                        // Bracketed so multi-line messages aren't sent line by line
+                                        let bytes = if text.contains('\n') {
+                                            format!("{}{}{}", paste::PASTE_START, text, paste::PASTE_END)
+                                        } else {
+                                            text
+                                        };
+                                        if let Err(e) = Self::write_pty(&writer, bytes.into_bytes()).await {
+                                            tracing::error!("Failed to restore retracted message: {}", e);
+                                        }
+                                    });
+                                }
+
+                                if response_tx.send(message).is_err() {
+                                    tracing::warn!("Control task - Retract requester went away");
+                                }
+                            }
                         }
                     }
                     internal_msg = internal_control_rx.recv() => {
@@ -1015,6 +1543,9 @@ impl PtySession {
             result = reader_task => {
                 tracing::info!("PTY reader task completed");
                 result.map_err(|e| anyhow::anyhow!("Reader task failed: {}", e))?;
+                // Output ends just before the agent does
+                let exit_code = exit_code_rx.wait_for(Option::is_some);
+                let _ = tokio::time::timeout(EXIT_CODE_WAIT, exit_code).await;
             }
             result = processor_task => {
                 tracing::info!("PTY processor task completed");
@@ -1028,6 +1559,12 @@ impl PtySession {
                 tracing::info!("PTY control task completed");
                 result.map_err(|e| anyhow::anyhow!("Control task failed: {}", e))?;
             }
+            // Programs the agent started may keep the PTY open after it exits
+            _ = exit_rx.wait_for(Option::is_some) => {
+                tracing::info!("PTY session agent exited");
+            }
+            // Never completes, it only tells clients when input is lagging
+            _ = input_lag => {}
         }
 
         tracing::info!("PTY session completed");
@@ -1218,6 +1755,7 @@ impl PtySession {
                 scrollback_position: scrollback_pos,
                 scrollback_total,
                 timestamp,
+                runs: Vec::new(),
             })
         } else if !changes.is_empty() || cursor_changed {
             // Send incremental diff
@@ -1330,6 +1868,7 @@ impl PtySession {
             scrollback_position: scrollback_pos,
             scrollback_total,
             timestamp: std::time::SystemTime::now(),
+            runs: Vec::new(),
         }
     }
 
@@ -1348,6 +1887,24 @@ impl PtySession {
         }
     }
 
+    /// Write client input to the PTY on a blocking thread: the write blocks
+    /// while the agent isn't reading its terminal, and must not hold up the
+    /// async workers meanwhile
+    async fn write_pty(
+        writer: &Arc<Mutex<Box<dyn std::io::Write + Send>>>,
+        bytes: Vec<u8>,
+    ) -> std::io::Result<()> {
+        let writer = writer.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut writer_guard = writer.blocking_lock();
+            writer_guard.write_all(&bytes)?;
+            let _ = writer_guard.flush();
+            Ok(())
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+    }
+
     /// Convert key event to terminal byte sequence
     fn key_event_to_bytes(event: &KeyEvent) -> Vec<u8> {
         let KeyEvent { code, modifiers } = event;
@@ -0,0 +1,238 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+use super::json_api::{ProjectResource, SessionResource};
+use super::preferences::UiPreferences;
+
+/// How many attached sessions `recent.json` remembers
+pub const MAX_RECENT: usize = 20;
+
+/// A session someone attached to from the web UI or the TUI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentAttach {
+    pub session_id: String,
+    /// ISO 8601 timestamp of the latest attach
+    pub attached_at: String,
+}
+
+/// Entry in the quick-switch list served by `GET /api/recent`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QuickSession {
+    pub id: String,
+    pub agent: Option<String>,
+    pub project: Option<String>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    /// Whether the session is currently running on the server
+    pub running: bool,
+    pub favorite: bool,
+    pub attached_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct QuickProject {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub icon: String,
+}
+
+/// Starred sessions first, then other recently attached ones, newest first
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RecentList {
+    pub sessions: Vec<QuickSession>,
+    /// Starred projects, in the order they were starred
+    pub projects: Vec<QuickProject>,
+}
+
+fn recent_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("recent.json")
+}
+
+/// Read the attach history, newest first
+pub fn load_recent(data_dir: &Path) -> Result<Vec<RecentAttach>> {
+    match std::fs::read_to_string(recent_path(data_dir)) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Move `session_id` to the front of the attach history
+pub fn record_attach(data_dir: &Path, session_id: &str) -> Result<()> {
+    let mut recent = load_recent(data_dir)?;
+    recent.retain(|entry| entry.session_id != session_id);
+    recent.insert(
+        0,
+        RecentAttach {
+            session_id: session_id.to_string(),
+            attached_at: chrono::Utc::now().to_rfc3339(),
+        },
+    );
+    recent.truncate(MAX_RECENT);
+
+    std::fs::create_dir_all(data_dir)?;
+    std::fs::write(
+        recent_path(data_dir),
+        serde_json::to_string_pretty(&recent)?,
+    )?;
+    Ok(())
+}
+
+/// Combine the attach history and starred items with what the server knows about
+///
+/// `sessions` are the running sessions; sessions that have ended are still listed
+/// with whatever their project's history says about them.
+pub fn build_recent_list(
+    recent: &[RecentAttach],
+    preferences: &UiPreferences,
+    sessions: &[SessionResource],
+    projects: &[ProjectResource],
+) -> RecentList {
+    let quick_session = |id: &str, attached_at: Option<String>| {
+        let running = sessions.iter().find(|session| session.id == id);
+        let historical = projects
+            .iter()
+            .filter_map(|project| project.relationships.as_ref()?.recent_sessions.as_ref())
+            .flatten()
+            .find(|session| session.id == id)
+            .and_then(|session| session.attributes.as_ref());
+        let attributes = running
+            .and_then(|session| session.attributes.as_ref())
+            .or(historical);
+        QuickSession {
+            id: id.to_string(),
+            agent: running
+                .and_then(|session| session.attributes.as_ref())
+                .map(|attributes| attributes.agent.clone())
+                .or_else(|| historical.map(|attributes| attributes.agent.clone())),
+            project: running
+                .and_then(|session| session.attributes.as_ref()?.project.clone())
+                .or_else(|| historical.and_then(|attributes| attributes.project.clone())),
+            color: attributes.map(|attributes| attributes.color.clone()),
+            icon: attributes.map(|attributes| attributes.icon.clone()),
+            running: running.is_some(),
+            favorite: preferences.favorite_sessions.iter().any(|f| f == id),
+            attached_at,
+        }
+    };
+    let attached_at = |id: &str| {
+        recent
+            .iter()
+            .find(|entry| entry.session_id == id)
+            .map(|entry| entry.attached_at.clone())
+    };
+
+    let mut quick_sessions: Vec<QuickSession> = preferences
+        .favorite_sessions
+        .iter()
+        .map(|id| quick_session(id, attached_at(id)))
+        .collect();
+    quick_sessions.extend(
+        recent
+            .iter()
+            .filter(|entry| !preferences.favorite_sessions.contains(&entry.session_id))
+            .map(|entry| quick_session(&entry.session_id, Some(entry.attached_at.clone()))),
+    );
+
+    let quick_projects = preferences
+        .favorite_projects
+        .iter()
+        .filter_map(|id| projects.iter().find(|project| &project.id == id))
+        .filter_map(|project| {
+            let attributes = project.attributes.as_ref()?;
+            Some(QuickProject {
+                id: project.id.clone(),
+                name: attributes.name.clone(),
+                path: attributes.path.clone(),
+                icon: attributes.icon.clone(),
+            })
+        })
+        .collect();
+
+    RecentList {
+        sessions: quick_sessions,
+        projects: quick_projects,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::JsonApiResource;
+
+    #[test]
+    fn favorites_come_first_and_attaches_are_deduplicated() {
+        let data_dir = std::env::temp_dir().join(format!("codemux-recent-{}", std::process::id()));
+        record_attach(&data_dir, "a").unwrap();
+        record_attach(&data_dir, "b").unwrap();
+        record_attach(&data_dir, "a").unwrap();
+        let recent = load_recent(&data_dir).unwrap();
+        let _ = std::fs::remove_dir_all(&data_dir);
+        assert_eq!(
+            recent
+                .iter()
+                .map(|r| r.session_id.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+
+        let preferences = UiPreferences {
+            favorite_sessions: vec!["b".to_string(), "gone".to_string()],
+            favorite_projects: vec!["p1".to_string(), "missing".to_string()],
+            ..UiPreferences::default()
+        };
+        let sessions = vec![JsonApiResource {
+            resource_type: "session".to_string(),
+            id: "a".to_string(),
+            attributes: Some(crate::core::SessionAttributes {
+                agent: "claude".to_string(),
+                project: Some("p1".to_string()),
+                status: "running".to_string(),
+                session_type: crate::core::session::SessionType::Active,
+                last_modified: None,
+                last_message: None,
+                color: "#61afef".to_string(),
+                icon: "✳️".to_string(),
+                note: None,
+                name: None,
+                terminates_at: None,
+                idle_expires_at: None,
+                diff_stats: None,
+                exit_code: None,
+            }),
+            relationships: None,
+        }];
+        let projects = vec![JsonApiResource {
+            resource_type: "project".to_string(),
+            id: "p1".to_string(),
+            attributes: Some(crate::core::ProjectAttributes {
+                name: "app".to_string(),
+                path: "/src/app".to_string(),
+                color: "#98c379".to_string(),
+                icon: "📦".to_string(),
+                remote: None,
+                defaults: None,
+            }),
+            relationships: None,
+        }];
+
+        let list = build_recent_list(&recent, &preferences, &sessions, &projects);
+        let ids: Vec<_> = list.sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["b", "gone", "a"]);
+        assert!(list.sessions[0].favorite && list.sessions[0].attached_at.is_some());
+        assert!(!list.sessions[1].running && list.sessions[1].attached_at.is_none());
+        assert!(list.sessions[2].running && !list.sessions[2].favorite);
+        assert_eq!(list.sessions[2].agent.as_deref(), Some("claude"));
+        assert_eq!(list.sessions[2].color.as_deref(), Some("#61afef"));
+        assert!(list.sessions[1].icon.is_none());
+        assert_eq!(list.projects.len(), 1);
+        assert_eq!(list.projects[0].name, "app");
+    }
+}
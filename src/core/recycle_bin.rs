@@ -0,0 +1,79 @@
+//! Undo for killed sessions
+//!
+//! A mistyped `codemux kill-session` used to end the wrong agent for good.
+//! Killing a session now pauses its agent and only ends it after a grace
+//! period, during which `codemux undo-kill` lets it carry on where it stopped.
+//! Sessions that ended stay in the session history, and `GET
+//! /api/sessions/{id}` keeps answering for them during the retention window.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Grace period and retention window, configured as `[recycle_bin]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecycleBinConfig {
+    /// Seconds a killed session stays paused before it ends; 0 ends it right away
+    pub grace_secs: u64,
+    /// Hours an ended session can still be looked up
+    pub retention_hours: u64,
+}
+
+impl Default for RecycleBinConfig {
+    fn default() -> Self {
+        RecycleBinConfig {
+            grace_secs: 30,
+            retention_hours: 24,
+        }
+    }
+}
+
+impl RecycleBinConfig {
+    /// How long killed sessions wait, `None` if they end right away
+    pub fn grace(&self) -> Option<Duration> {
+        (self.grace_secs > 0).then(|| Duration::from_secs(self.grace_secs))
+    }
+
+    /// Whether a session that ended at `ended_at` (RFC 3339) is still kept at `now`
+    pub fn retains(&self, ended_at: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+        let Ok(ended_at) = chrono::DateTime::parse_from_rfc3339(ended_at) else {
+            return false;
+        };
+        let age = now.signed_duration_since(ended_at);
+        age <= chrono::Duration::hours(self.retention_hours as i64)
+    }
+}
+
+/// A killed session waiting out its grace period
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingKill {
+    /// Tells this kill's timer apart from one of a kill undone and repeated since
+    pub token: u64,
+    /// When the session ends, as RFC 3339
+    pub ends_at: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_ended_sessions_for_the_retention_window() {
+        let config = RecycleBinConfig::default();
+        let now = chrono::DateTime::parse_from_rfc3339("2026-03-02T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert!(config.retains("2026-03-02T11:00:00Z", now));
+        assert!(config.retains("2026-03-01T12:00:00+00:00", now));
+        assert!(!config.retains("2026-03-01T11:59:59Z", now));
+        assert!(!config.retains("yesterday", now));
+
+        assert_eq!(config.grace(), Some(Duration::from_secs(30)));
+        let immediate = RecycleBinConfig {
+            grace_secs: 0,
+            ..config
+        };
+        assert_eq!(immediate.grace(), None);
+    }
+}
@@ -0,0 +1,257 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::html::{escape_html, render_html_fragment};
+use super::{DEFAULT_BG, DEFAULT_FG};
+use crate::core::pty_session::GridCell;
+use crate::core::transcript::{SessionTranscript, TranscriptMessage, TranscriptStats};
+
+/// Document formats a conversation can be exported as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Headings per message, for PR descriptions and issues
+    #[default]
+    Markdown,
+    Json,
+    /// A self-contained page
+    Html,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// What an export is made of: the agent's own transcript, or what the
+/// terminal showed when codemux can't read one
+pub enum ExportSource<'a> {
+    Transcript(&'a SessionTranscript),
+    Terminal(&'a [Vec<GridCell>]),
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    session: &'a str,
+    agent: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    messages: Option<&'a [TranscriptMessage]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a TranscriptStats>,
+    /// Terminal text, without a transcript
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+}
+
+/// Render a session's conversation as a shareable document
+pub fn render_conversation(
+    format: ExportFormat,
+    session_id: &str,
+    agent: &str,
+    source: &ExportSource,
+) -> String {
+    let title = format!("{} session {}", agent, session_id);
+    match (format, source) {
+        (ExportFormat::Markdown, ExportSource::Transcript(transcript)) => {
+            let mut markdown = format!("# {}\n\n_{}_\n", title, summary(&transcript.stats));
+            for message in &transcript.messages {
+                let _ = write!(
+                    markdown,
+                    "\n## {}\n\n{}\n",
+                    role_heading(&message.role),
+                    message.content.trim_end()
+                );
+            }
+            markdown
+        }
+        (ExportFormat::Markdown, ExportSource::Terminal(lines)) => {
+            let text = plain_text(lines);
+            // A fence longer than any run of backticks in the output
+            let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+            let fence = "`".repeat(longest.max(2) + 1);
+            format!("# {}\n\n{}text\n{}\n{}\n", title, fence, text, fence)
+        }
+        (ExportFormat::Json, source) => {
+            let export = match source {
+                ExportSource::Transcript(transcript) => JsonExport {
+                    session: session_id,
+                    agent,
+                    messages: Some(&transcript.messages),
+                    stats: Some(&transcript.stats),
+                    output: None,
+                },
+                ExportSource::Terminal(lines) => JsonExport {
+                    session: session_id,
+                    agent,
+                    messages: None,
+                    stats: None,
+                    output: Some(plain_text(lines)),
+                },
+            };
+            serde_json::to_string_pretty(&export).unwrap_or_default()
+        }
+        (ExportFormat::Html, ExportSource::Transcript(transcript)) => {
+            let mut body = format!(
+                "<h1>{}</h1>\n<p class=\"summary\">{}</p>\n",
+                escape_html(&title),
+                escape_html(&summary(&transcript.stats))
+            );
+            for message in &transcript.messages {
+                let _ = write!(
+                    body,
+                    "<section class=\"message {}\">\n<h2>{}</h2>\n<div class=\"content\">{}</div>\n</section>\n",
+                    if message.role == "user" { "user" } else { "agent" },
+                    escape_html(&role_heading(&message.role)),
+                    escape_html(message.content.trim_end())
+                );
+            }
+            html_document(&title, &body)
+        }
+        (ExportFormat::Html, ExportSource::Terminal(lines)) => {
+            let body = format!(
+                "<h1>{}</h1>\n{}\n",
+                escape_html(&title),
+                render_html_fragment(lines)
+            );
+            html_document(&title, &body)
+        }
+    }
+}
+
+/// "12 messages · 3400 input / 1200 output tokens · $0.42"
+fn summary(stats: &TranscriptStats) -> String {
+    let mut summary = format!("{} messages", stats.messages);
+    if stats.input_tokens > 0 || stats.output_tokens > 0 {
+        let _ = write!(
+            summary,
+            " · {} input / {} output tokens",
+            stats.input_tokens, stats.output_tokens
+        );
+    }
+    if let Some(cost) = stats.cost {
+        let _ = write!(summary, " · ${:.2}", cost);
+    }
+    summary
+}
+
+fn role_heading(role: &str) -> String {
+    let mut chars = role.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Message".to_string(),
+    }
+}
+
+/// Terminal lines as text, without trailing blanks
+fn plain_text(lines: &[Vec<GridCell>]) -> String {
+    let text: Vec<String> = lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|cell| cell.char.as_str())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    text.join("\n").trim_end().to_string()
+}
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+            "<title>{title}</title>\n<style>\n",
+            "body {{ max-width: 860px; margin: 0 auto; padding: 24px; ",
+            "font-family: system-ui, sans-serif; line-height: 1.5; }}\n",
+            ".summary {{ color: #666; }}\n",
+            ".message {{ margin: 16px 0; padding: 12px 16px; border-radius: 8px; }}\n",
+            ".message h2 {{ margin: 0 0 8px; font-size: 14px; }}\n",
+            ".message.user {{ background: #eef4ff; }}\n",
+            ".message.agent {{ background: #f6f6f6; }}\n",
+            ".content {{ white-space: pre-wrap; }}\n",
+            ".codemux-terminal {{ padding: 12px; color: {fg}; background: {bg}; ",
+            "font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; ",
+            "font-size: 13px; overflow-x: auto; }}\n",
+            "</style>\n</head>\n<body>\n{body}</body>\n</html>\n"
+        ),
+        title = escape_html(title),
+        fg = DEFAULT_FG,
+        bg = DEFAULT_BG,
+        body = body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::render::ansi_to_lines;
+
+    #[test]
+    fn exports_transcripts_and_terminal_output() {
+        let messages = vec![
+            TranscriptMessage {
+                role: "user".to_string(),
+                content: "fix the <form>".to_string(),
+                timestamp: None,
+                input_tokens: None,
+                output_tokens: None,
+                cost: None,
+            },
+            TranscriptMessage {
+                role: "assistant".to_string(),
+                content: "Done.\n".to_string(),
+                timestamp: None,
+                input_tokens: Some(1200),
+                output_tokens: Some(80),
+                cost: Some(0.42),
+            },
+        ];
+        let transcript = SessionTranscript {
+            path: None,
+            stats: TranscriptStats::of(&messages),
+            messages,
+        };
+        let source = ExportSource::Transcript(&transcript);
+
+        let markdown = render_conversation(ExportFormat::Markdown, "s1", "claude", &source);
+        assert_eq!(
+            markdown,
+            "# claude session s1\n\n_2 messages · 1200 input / 80 output tokens · $0.42_\n\
+             \n## User\n\nfix the <form>\n\n## Assistant\n\nDone.\n"
+        );
+        let html = render_conversation(ExportFormat::Html, "s1", "claude", &source);
+        assert!(html.contains("<div class=\"content\">fix the &lt;form&gt;</div>"));
+        let json: serde_json::Value = serde_json::from_str(&render_conversation(
+            ExportFormat::Json,
+            "s1",
+            "claude",
+            &source,
+        ))
+        .unwrap();
+        assert_eq!(json["messages"][1]["content"], "Done.\n");
+        assert_eq!(json["stats"]["messages"], 2);
+
+        let lines = ansi_to_lines(b"$ make\r\n```\r\nok   \r\n", 80);
+        let source = ExportSource::Terminal(&lines);
+        let markdown = render_conversation(ExportFormat::Markdown, "s2", "aider", &source);
+        assert_eq!(
+            markdown,
+            "# aider session s2\n\n````text\n$ make\n```\nok\n````\n"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::{CellStyle, DEFAULT_BG, DEFAULT_FG};
+use crate::core::pty_session::GridCell;
+
+/// Render styled terminal lines as a `<pre>` block with inline styles
+///
+/// Only non-default attributes get a `<span>`, and URLs are turned into links,
+/// so the output stays readable when pasted into other documents.
+pub fn render_html_fragment(lines: &[Vec<GridCell>]) -> String {
+    let mut html = String::from("<pre class=\"codemux-terminal\">");
+
+    for (idx, line) in lines.iter().enumerate() {
+        if idx > 0 {
+            html.push('\n');
+        }
+        render_line(&mut html, line);
+    }
+
+    html.push_str("</pre>");
+    html
+}
+
+/// Render styled terminal lines as a complete, self-contained HTML page
+pub fn render_html_document(title: &str, lines: &[Vec<GridCell>]) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+            "<title>{title}</title>\n<style>\n",
+            "body {{ margin: 0; background: {bg}; }}\n",
+            ".codemux-terminal {{ margin: 0; padding: 12px; color: {fg}; background: {bg}; ",
+            "font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; ",
+            "font-size: 13px; line-height: 1.3; }}\n",
+            ".codemux-terminal a {{ color: inherit; }}\n",
+            "</style>\n</head>\n<body>\n"
+        ),
+        title = escape_html(title),
+        fg = DEFAULT_FG,
+        bg = DEFAULT_BG,
+    );
+    html.push_str(&render_html_fragment(lines));
+    html.push_str("\n</body>\n</html>\n");
+    html
+}
+
+fn render_line(html: &mut String, line: &[GridCell]) {
+    // Trailing blanks only matter when they paint a background
+    let visible_len = line
+        .iter()
+        .rposition(|cell| {
+            !cell.char.trim().is_empty() || CellStyle::for_cell(Some(cell)).has_background()
+        })
+        .map_or(0, |pos| pos + 1);
+
+    let mut run_text = String::new();
+    let mut run_style: Option<CellStyle> = None;
+
+    for cell in &line[..visible_len] {
+        let style = CellStyle::for_cell(Some(cell));
+        if run_style.as_ref() != Some(&style) {
+            if let Some(previous) = run_style.take() {
+                push_run(html, &run_text, &previous);
+                run_text.clear();
+            }
+            run_style = Some(style);
+        }
+        // Wide characters are followed by an empty continuation cell
+        run_text.push_str(&cell.char);
+    }
+
+    if let Some(style) = run_style {
+        push_run(html, &run_text, &style);
+    }
+}
+
+fn push_run(html: &mut String, text: &str, style: &CellStyle) {
+    let mut css = String::new();
+    if style.fg != DEFAULT_FG {
+        let _ = write!(css, "color:{};", style.fg);
+    }
+    if style.has_background() {
+        let _ = write!(css, "background-color:{};", style.bg);
+    }
+    if style.bold {
+        css.push_str("font-weight:bold;");
+    }
+    if style.italic {
+        css.push_str("font-style:italic;");
+    }
+    if style.underline {
+        css.push_str("text-decoration:underline;");
+    }
+
+    if css.is_empty() {
+        push_linkified(html, text);
+    } else {
+        let _ = write!(html, "<span style=\"{}\">", css);
+        push_linkified(html, text);
+        html.push_str("</span>");
+    }
+}
+
+fn push_linkified(html: &mut String, text: &str) {
+    static URL_RE: OnceLock<Regex> = OnceLock::new();
+    let url_re = URL_RE.get_or_init(|| Regex::new(r#"https?://[^\s<>"'`]+"#).unwrap());
+
+    let mut last = 0;
+    for url in url_re.find_iter(text) {
+        html.push_str(&escape_html(&text[last..url.start()]));
+        let href = escape_html(url.as_str());
+        let _ = write!(html, "<a href=\"{}\">{}</a>", href, href);
+        last = url.end();
+    }
+    html.push_str(&escape_html(&text[last..]));
+}
+
+pub(super) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::render::ansi_to_lines;
+
+    #[test]
+    fn renders_ansi_styles_and_links() {
+        let lines = ansi_to_lines(
+            b"\x1b[1;31merror\x1b[0m: see https://codemux.dev/docs?a=1&b=2\r\nplain <text>\r\n",
+            80,
+        );
+        let html = render_html_fragment(&lines);
+
+        assert!(html.contains(r#"<span style="color:#cd3131;font-weight:bold;">error</span>"#));
+        assert!(html.contains(
+            r#"<a href="https://codemux.dev/docs?a=1&amp;b=2">https://codemux.dev/docs?a=1&amp;b=2</a>"#
+        ));
+        assert!(html.ends_with("plain &lt;text&gt;</pre>"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Renderers that turn terminal grid snapshots into shareable documents
+//!
+//! The PTY session already maintains the full screen state as a keyframe; these
+//! modules only translate that state into other formats and never touch the PTY.
+//! `conversation` does the same for an agent's transcript.
+
+pub mod conversation;
+pub mod html;
+#[cfg(feature = "png-screenshots")]
+pub mod png;
+pub mod svg;
+
+use serde::Serialize;
+use std::collections::HashMap;
+use ts_rs::TS;
+use utoipa::ToSchema;
+use unicode_width::UnicodeWidthStr;
+
+use super::pty_session::{GridCell, GridUpdateMessage, TerminalColor};
+
+pub use conversation::{render_conversation, ExportFormat, ExportSource};
+pub use html::{render_html_document, render_html_fragment};
+#[cfg(feature = "png-screenshots")]
+pub use png::render_png;
+pub use svg::render_svg;
+
+/// Default foreground used when a cell has no explicit color
+pub const DEFAULT_FG: &str = "#d4d4d4";
+/// Default background used when a cell has no explicit color
+pub const DEFAULT_BG: &str = "#1e1e1e";
+
+/// A point-in-time copy of the visible terminal screen
+#[derive(Debug, Clone)]
+pub struct ScreenSnapshot {
+    pub rows: u16,
+    pub cols: u16,
+    pub cells: HashMap<(u16, u16), GridCell>,
+    pub cursor: (u16, u16),
+    pub cursor_visible: bool,
+}
+
+impl ScreenSnapshot {
+    /// Build a snapshot from a keyframe; diffs carry partial state and yield `None`
+    pub fn from_keyframe(update: GridUpdateMessage) -> Option<Self> {
+        match update {
+            GridUpdateMessage::Keyframe {
+                size,
+                cells,
+                cursor,
+                cursor_visible,
+                ..
+            } => Some(Self {
+                rows: size.rows,
+                cols: size.cols,
+                cells: cells.into_iter().collect(),
+                cursor,
+                cursor_visible,
+            }),
+            GridUpdateMessage::Diff { .. } => None,
+        }
+    }
+
+    /// Get the cell at a position, if the keyframe contained one
+    pub fn cell(&self, row: u16, col: u16) -> Option<&GridCell> {
+        self.cells.get(&(row, col))
+    }
+
+    /// Append the character at (row, col) to `line`: a space for an empty
+    /// cell, nothing for the second half of a wide character
+    pub fn push_char(&self, line: &mut String, row: u16, col: u16) {
+        match self.cell(row, col) {
+            Some(cell) if !cell.char.is_empty() => line.push_str(&cell.char),
+            _ if self.continues_wide(row, col) => {}
+            _ => line.push(' '),
+        }
+    }
+
+    /// Whether (row, col) is the second half of a wide character
+    fn continues_wide(&self, row: u16, col: u16) -> bool {
+        col > 0
+            && self
+                .cell(row, col - 1)
+                .is_some_and(|cell| cell.char.width() > 1)
+    }
+
+    /// The screen row by row, each row as text and as cells
+    pub fn grid(&self) -> ScreenGrid {
+        let blank = |char: &str| GridCell {
+            char: char.to_string(),
+            fg_color: None,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        };
+        let lines = (0..self.rows)
+            .map(|row| {
+                let mut line = String::new();
+                for col in 0..self.cols {
+                    self.push_char(&mut line, row, col);
+                }
+                line.trim_end().to_string()
+            })
+            .collect();
+        let cells = (0..self.rows)
+            .map(|row| {
+                (0..self.cols)
+                    .map(|col| match self.cell(row, col) {
+                        Some(cell) => cell.clone(),
+                        None if self.continues_wide(row, col) => blank(""),
+                        None => blank(" "),
+                    })
+                    .collect()
+            })
+            .collect();
+        ScreenGrid {
+            rows: self.rows,
+            cols: self.cols,
+            cursor: self.cursor,
+            cursor_visible: self.cursor_visible,
+            lines,
+            cells,
+        }
+    }
+}
+
+/// A session's screen, from `GET /api/sessions/:id/screen`
+#[derive(Debug, Clone, Serialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ScreenGrid {
+    pub rows: u16,
+    pub cols: u16,
+    /// (row, col)
+    #[schema(value_type = Vec<u16>)]
+    pub cursor: (u16, u16),
+    pub cursor_visible: bool,
+    /// Each row's text without trailing spaces
+    pub lines: Vec<String>,
+    /// Each row's cells, `cols` of them; the second half of a wide character
+    /// has an empty `char`
+    pub cells: Vec<Vec<GridCell>>,
+}
+
+/// Resolved colors and attributes for a single cell, after applying reverse video
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellStyle {
+    pub fg: String,
+    pub bg: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl CellStyle {
+    pub fn for_cell(cell: Option<&GridCell>) -> Self {
+        let Some(cell) = cell else {
+            return Self {
+                fg: DEFAULT_FG.to_string(),
+                bg: DEFAULT_BG.to_string(),
+                bold: false,
+                italic: false,
+                underline: false,
+            };
+        };
+
+        let mut fg = color_to_hex(cell.fg_color.as_ref(), DEFAULT_FG);
+        let mut bg = color_to_hex(cell.bg_color.as_ref(), DEFAULT_BG);
+        if cell.reverse {
+            std::mem::swap(&mut fg, &mut bg);
+        }
+
+        Self {
+            fg,
+            bg,
+            bold: cell.bold,
+            italic: cell.italic,
+            underline: cell.underline,
+        }
+    }
+
+    /// Whether the background differs from the page background
+    pub fn has_background(&self) -> bool {
+        self.bg != DEFAULT_BG
+    }
+}
+
+/// Convert a terminal color to a CSS hex string, falling back to `default`
+pub fn color_to_hex(color: Option<&TerminalColor>, default: &str) -> String {
+    match color {
+        None | Some(TerminalColor::Default) => default.to_string(),
+        Some(TerminalColor::Indexed(idx)) | Some(TerminalColor::Palette(idx)) => {
+            let (r, g, b) = palette_rgb(*idx);
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        }
+        Some(TerminalColor::Rgb { r, g, b }) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+    }
+}
+
+/// Resolve an xterm 256-color palette index to RGB
+pub fn palette_rgb(idx: u8) -> (u8, u8, u8) {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x31, 0x31),
+        (0x0d, 0xbc, 0x79),
+        (0xe5, 0xe5, 0x10),
+        (0x24, 0x72, 0xc8),
+        (0xbc, 0x3f, 0xbc),
+        (0x11, 0xa8, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x66, 0x66, 0x66),
+        (0xf1, 0x4c, 0x4c),
+        (0x23, 0xd1, 0x8b),
+        (0xf5, 0xf5, 0x43),
+        (0x3b, 0x8e, 0xea),
+        (0xd6, 0x70, 0xd6),
+        (0x29, 0xb8, 0xdb),
+        (0xff, 0xff, 0xff),
+    ];
+
+    match idx {
+        0..=15 => ANSI[idx as usize],
+        16..=231 => {
+            // 6x6x6 color cube
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = idx - 16;
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        232..=255 => {
+            // Grayscale ramp
+            let v = 8 + (idx - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+/// Collect up to `max_lines` rows of history (scrollback followed by the visible screen)
+///
+/// The parser's scroll position is restored afterwards and blank rows below the
+/// last output are dropped.
+pub fn history_lines(parser: &mut vt100::Parser, max_lines: usize) -> Vec<Vec<GridCell>> {
+    let screen = parser.screen_mut();
+    let (rows, cols) = screen.size();
+    let rows = rows as usize;
+    let original_scrollback = screen.scrollback();
+    let total_scrollback = screen.scrollback_lines();
+    let available = total_scrollback + rows;
+
+    let mut index = available.saturating_sub(max_lines);
+    let mut lines = Vec::with_capacity(available - index);
+
+    // Walk the history a page at a time by moving the scrollback viewport
+    while index < available {
+        let offset = total_scrollback.saturating_sub(index);
+        screen.set_scrollback(offset);
+        let first_row = index + offset - total_scrollback;
+
+        for row in first_row..rows {
+            let cells = (0..cols)
+                .map(|col| {
+                    screen
+                        .cell(row as u16, col)
+                        .map(GridCell::from)
+                        .unwrap_or_else(blank_cell)
+                })
+                .collect();
+            lines.push(cells);
+        }
+
+        index += rows - first_row;
+    }
+
+    screen.set_scrollback(original_scrollback);
+
+    while lines
+        .last()
+        .is_some_and(|line: &Vec<GridCell>| line.iter().all(|c| c.char.trim().is_empty()))
+    {
+        lines.pop();
+    }
+
+    lines
+}
+
+/// Parse raw ANSI output into styled lines, as a terminal `cols` wide would show it
+pub fn ansi_to_lines(data: &[u8], cols: u16) -> Vec<Vec<GridCell>> {
+    const ANSI_SCROLLBACK: usize = 100_000;
+
+    let mut parser = vt100::Parser::new(24, cols, ANSI_SCROLLBACK);
+    parser.process(data);
+    history_lines(&mut parser, usize::MAX)
+}
+
+fn blank_cell() -> GridCell {
+    GridCell {
+        char: " ".to_string(),
+        fg_color: None,
+        bg_color: None,
+        bold: false,
+        italic: false,
+        underline: false,
+        reverse: false,
+    }
+}
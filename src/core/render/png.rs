@@ -0,0 +1,26 @@
+use anyhow::{anyhow, Result};
+
+use super::{svg::render_svg, ScreenSnapshot};
+
+/// Rasterize a screen snapshot to PNG bytes via its SVG rendering
+pub fn render_png(snapshot: &ScreenSnapshot) -> Result<Vec<u8>> {
+    let svg = render_svg(snapshot);
+
+    let mut options = resvg::usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+
+    let tree = resvg::usvg::Tree::from_str(&svg, &options)?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height())
+        .ok_or_else(|| anyhow!("Invalid screenshot dimensions"))?;
+
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::default(),
+        &mut pixmap.as_mut(),
+    );
+
+    pixmap
+        .encode_png()
+        .map_err(|e| anyhow!("Failed to encode PNG: {}", e))
+}
@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use super::{CellStyle, ScreenSnapshot, DEFAULT_BG, DEFAULT_FG};
+
+/// Width of a single terminal cell in SVG user units
+const CELL_WIDTH: u32 = 9;
+/// Height of a single terminal row in SVG user units
+const CELL_HEIGHT: u32 = 18;
+/// Font size chosen so a monospace glyph advance matches `CELL_WIDTH`
+const FONT_SIZE: u32 = 15;
+/// Padding around the terminal grid
+const PADDING: u32 = 8;
+
+/// A horizontal stretch of cells sharing the same style
+struct Run {
+    start_col: u16,
+    len: u16,
+    text: String,
+    style: CellStyle,
+}
+
+/// Render a screen snapshot as a standalone SVG document
+pub fn render_svg(snapshot: &ScreenSnapshot) -> String {
+    let width = u32::from(snapshot.cols) * CELL_WIDTH + PADDING * 2;
+    let height = u32::from(snapshot.rows) * CELL_HEIGHT + PADDING * 2;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = width,
+        h = height
+    );
+    let _ = writeln!(
+        svg,
+        r#"<style>text{{font-family:ui-monospace,SFMono-Regular,Menlo,Consolas,"DejaVu Sans Mono",monospace;font-size:{}px;white-space:pre}}</style>"#,
+        FONT_SIZE
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        DEFAULT_BG
+    );
+
+    for row in 0..snapshot.rows {
+        let runs = row_runs(snapshot, row);
+        let y = PADDING + u32::from(row) * CELL_HEIGHT;
+
+        // Backgrounds first so text is always painted on top
+        for run in runs.iter().filter(|run| run.style.has_background()) {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                PADDING + u32::from(run.start_col) * CELL_WIDTH,
+                y,
+                u32::from(run.len) * CELL_WIDTH,
+                CELL_HEIGHT,
+                run.style.bg
+            );
+        }
+
+        for run in runs.iter().filter(|run| !run.text.trim().is_empty()) {
+            let mut attrs = format!(r#" fill="{}""#, run.style.fg);
+            if run.style.bold {
+                attrs.push_str(r#" font-weight="bold""#);
+            }
+            if run.style.italic {
+                attrs.push_str(r#" font-style="italic""#);
+            }
+            if run.style.underline {
+                attrs.push_str(r#" text-decoration="underline""#);
+            }
+
+            // Baseline sits roughly 3/4 down the row; textLength pins the run to the grid
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" textLength="{}" lengthAdjust="spacingAndGlyphs" xml:space="preserve"{}>{}</text>"#,
+                PADDING + u32::from(run.start_col) * CELL_WIDTH,
+                y + CELL_HEIGHT * 3 / 4,
+                u32::from(run.len) * CELL_WIDTH,
+                attrs,
+                escape_xml(&run.text)
+            );
+        }
+    }
+
+    if snapshot.cursor_visible
+        && snapshot.cursor.0 < snapshot.rows
+        && snapshot.cursor.1 < snapshot.cols
+    {
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" fill-opacity="0.5"/>"#,
+            PADDING + u32::from(snapshot.cursor.1) * CELL_WIDTH,
+            PADDING + u32::from(snapshot.cursor.0) * CELL_HEIGHT,
+            CELL_WIDTH,
+            CELL_HEIGHT,
+            DEFAULT_FG
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Group a row's cells into runs of identical style
+fn row_runs(snapshot: &ScreenSnapshot, row: u16) -> Vec<Run> {
+    let mut runs: Vec<Run> = Vec::new();
+
+    for col in 0..snapshot.cols {
+        let cell = snapshot.cell(row, col);
+        let style = CellStyle::for_cell(cell);
+        // Wide characters leave an empty continuation cell behind them
+        let text = cell.map(|c| c.char.as_str()).unwrap_or(" ");
+
+        match runs.last_mut() {
+            Some(run) if run.style == style => {
+                run.len += 1;
+                run.text.push_str(text);
+            }
+            _ => runs.push(Run {
+                start_col: col,
+                len: 1,
+                text: text.to_string(),
+                style,
+            }),
+        }
+    }
+
+    runs
+}
+
+/// Escape text for inclusion in XML, dropping control characters XML cannot carry
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::{GridCell, TerminalColor};
+    use std::collections::HashMap;
+
+    fn cell(ch: &str) -> GridCell {
+        GridCell {
+            char: ch.to_string(),
+            fg_color: None,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    fn snapshot(cells: Vec<((u16, u16), GridCell)>) -> ScreenSnapshot {
+        ScreenSnapshot {
+            rows: 2,
+            cols: 4,
+            cells: cells.into_iter().collect::<HashMap<_, _>>(),
+            cursor: (1, 0),
+            cursor_visible: true,
+        }
+    }
+
+    #[test]
+    fn renders_text_escaped_and_sized_to_grid() {
+        let svg = render_svg(&snapshot(vec![
+            ((0, 0), cell("<")),
+            ((0, 1), cell("&")),
+            ((0, 2), cell("a")),
+        ]));
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"width="52" height="52""#));
+        assert!(svg.contains("&lt;&amp;a"));
+        assert!(svg.contains(r#"fill-opacity="0.5""#), "cursor is drawn");
+    }
+
+    #[test]
+    fn splits_runs_on_style_changes() {
+        let mut red = cell("x");
+        red.fg_color = Some(TerminalColor::Indexed(1));
+        red.bold = true;
+        let mut inverted = cell("y");
+        inverted.reverse = true;
+
+        let svg = render_svg(&snapshot(vec![
+            ((0, 0), red),
+            ((0, 1), inverted),
+            ((0, 2), cell("z")),
+        ]));
+
+        assert!(svg.contains(r##"fill="#cd3131" font-weight="bold""##));
+        // Reverse video paints the default foreground as a background rect
+        assert!(svg.contains(r##"width="9" height="18" fill="#d4d4d4""##));
+        assert!(svg.contains(">z</text>") || svg.contains(">z </text>"));
+    }
+}
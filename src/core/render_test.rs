@@ -0,0 +1,304 @@
+//! Deterministic torture-test grid sequence for checking web renderers
+//!
+//! Served by `GET /api/dev/render-test` in the same message format as a live
+//! session. Every scene is plain ANSI run through the same vt100 parser as a
+//! real PTY, so the cells match what an agent printing the text would produce.
+//! Keep the scene checks in sync with `expo-app/src/app/dev/render-test.tsx`.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use super::pty_session::{GridCell, GridUpdateMessage, SerializablePtySize};
+
+pub const RENDER_TEST_ROWS: u16 = 24;
+pub const RENDER_TEST_COLS: u16 = 80;
+
+/// How long each still scene stays on screen
+const SCENE_HOLD: Duration = Duration::from_secs(4);
+/// Frames in the rapid-diff scene, sent one per `DIFF_INTERVAL`
+const DIFF_FRAMES: u32 = 120;
+const DIFF_INTERVAL: Duration = Duration::from_millis(16);
+/// Column (1-based) of the `|` markers that wide characters must not push around
+const BAR_COL: u16 = 40;
+
+/// One update of the sequence and how long to wait before the next
+pub struct RenderTestFrame {
+    pub delay: Duration,
+    pub update: GridUpdateMessage,
+}
+
+/// The whole sequence: a keyframe per still scene, then a burst of diffs
+pub fn render_test_frames() -> Vec<RenderTestFrame> {
+    let scenes = [wide_scene(), rtl_scene(), combining_scene(), color_scene()];
+    let total = scenes.len() + 1;
+    let mut screen = TestScreen::new();
+    let mut frames = Vec::new();
+
+    for (index, (title, check, body)) in scenes.iter().enumerate() {
+        let header = scene_header(index + 1, total, title, check);
+        frames.push(RenderTestFrame {
+            delay: SCENE_HOLD,
+            update: screen.keyframe(&format!("{}{}", header, body)),
+        });
+    }
+
+    let header = scene_header(
+        total,
+        total,
+        "Rapid diffs",
+        "the counter ends at 120/120 with no stale cells left by the block or the toggling text",
+    );
+    frames.push(RenderTestFrame {
+        delay: DIFF_INTERVAL,
+        update: screen.keyframe(&header),
+    });
+    for frame in 1..=DIFF_FRAMES {
+        frames.push(RenderTestFrame {
+            delay: if frame == DIFF_FRAMES {
+                SCENE_HOLD
+            } else {
+                DIFF_INTERVAL
+            },
+            update: screen.diff(&animation_step(frame)),
+        });
+    }
+
+    frames
+}
+
+fn scene_header(number: usize, total: usize, title: &str, check: &str) -> String {
+    format!(
+        "\x1b[?25l\x1b[1mcodemux render test\x1b[0m  scene {}/{}: \x1b[1m{}\x1b[0m\r\n\
+         \x1b[2mExpect: {}\x1b[0m\r\n\r\n",
+        number, total, title, check
+    )
+}
+
+/// A labelled line ending in a `|` at `BAR_COL`
+fn bar_line(label: &str, text: &str) -> String {
+    format!("{:<10}{}\x1b[{}G|\r\n", label, text, BAR_COL)
+}
+
+fn wide_scene() -> (&'static str, &'static str, String) {
+    let body = [
+        bar_line("ruler", "0123456789012345678901234567"),
+        bar_line("CJK", "漢字かなカナ한글"),
+        bar_line("fullwidth", "ＡＢＣ１２３"),
+        bar_line("emoji", "👍🎉🚀✅"),
+        bar_line("sequences", "👍🏽 🇯🇵 👨‍👩‍👧"),
+        bar_line("mixed", "a漢b字c かd"),
+    ]
+    .concat();
+    (
+        "Wide characters",
+        "each wide glyph fills exactly two cells and every | lines up in one column",
+        body,
+    )
+}
+
+fn rtl_scene() -> (&'static str, &'static str, String) {
+    let body = [
+        bar_line("hebrew", "שלום עולם"),
+        bar_line("arabic", "مرحبا بالعالم"),
+        bar_line("mixed", "abc אבג 123 def"),
+        bar_line("numbers", "עמוד 12 מתוך 345"),
+    ]
+    .concat();
+    (
+        "Right-to-left text",
+        "letters stay one per cell in the order shown here, nothing is mirrored across the line",
+        body,
+    )
+}
+
+fn combining_scene() -> (&'static str, &'static str, String) {
+    let body = [
+        bar_line("accents", "cafe\u{301} n\u{303}o u\u{308}ber"),
+        bar_line("stacked", "Z\u{351}\u{34b}a\u{35b}l\u{307}g\u{30c}o\u{33e}"),
+        bar_line("devanagari", "नमस्ते दुनिया"),
+        bar_line("thai", "สวัสดีชาวโลก"),
+        bar_line("vs16", "☺\u{fe0f} ☺ ❤\u{fe0f} ❤"),
+    ]
+    .concat();
+    (
+        "Combining marks",
+        "marks sit on their base letter and never take a cell of their own",
+        body,
+    )
+}
+
+fn color_scene() -> (&'static str, &'static str, String) {
+    let mut body = String::from("16 colors ");
+    for color in 0..8 {
+        body.push_str(&format!("\x1b[4{}m  \x1b[10{}m  ", color, color));
+    }
+    body.push_str("\x1b[0m\r\n");
+
+    for row in 0..6 {
+        body.push_str(if row == 0 { "palette   " } else { "          " });
+        for offset in 0..36 {
+            body.push_str(&format!("\x1b[48;5;{}m ", 16 + row * 36 + offset));
+        }
+        body.push_str("\x1b[0m\r\n");
+    }
+    body.push_str("greys     ");
+    for grey in 232..=255 {
+        body.push_str(&format!("\x1b[48;5;{}m  ", grey));
+    }
+    body.push_str("\x1b[0m\r\n");
+
+    for (label, channel) in [("24-bit r", 0), ("       g", 1), ("       b", 2)] {
+        body.push_str(&format!("{:<10}", label));
+        for step in 0..64u16 {
+            let mut rgb = [0u16; 3];
+            rgb[channel] = step * 255 / 63;
+            body.push_str(&format!("\x1b[48;2;{};{};{}m ", rgb[0], rgb[1], rgb[2]));
+        }
+        body.push_str("\x1b[0m\r\n");
+    }
+
+    body.push_str(
+        "attributes\x1b[1mbold\x1b[0m \x1b[3mitalic\x1b[0m \x1b[4munderline\x1b[0m \
+         \x1b[7mreverse\x1b[0m \x1b[1;3;4;38;2;255;128;0mall + rgb\x1b[0m\r\n",
+    );
+    (
+        "Colors and attributes",
+        "smooth 64-step ramps with no banding jumps, distinct palette cells, each attribute visible",
+        body,
+    )
+}
+
+/// Frame `n` of the rapid-diff scene: counter, progress bar, moving block and toggling cells
+fn animation_step(frame: u32) -> String {
+    let width = 60;
+    let filled = (frame * width / DIFF_FRAMES) as usize;
+    let column = 1 + (frame % width) as usize;
+    let hue = (frame * 255 / DIFF_FRAMES) as u8;
+    let (wide, marked) = if frame % 2 == 1 {
+        ("漢字漢字", "e\u{301}")
+    } else {
+        ("abcdefgh", "e")
+    };
+
+    format!(
+        "\x1b[5;1Hframe {:03}/{}\
+         \x1b[6;1H[{}{}]\
+         \x1b[7;1H\x1b[2K\x1b[7;{}H\x1b[48;2;{};{};255m  \x1b[0m\
+         \x1b[8;1H{} {}\x1b[8;{}H|",
+        frame,
+        DIFF_FRAMES,
+        "█".repeat(filled),
+        " ".repeat(width as usize - filled),
+        column,
+        hue,
+        255 - hue,
+        wide,
+        marked,
+        BAR_COL
+    )
+}
+
+/// Runs scenes through a vt100 parser and turns the screen into grid updates
+struct TestScreen {
+    parser: vt100::Parser,
+    cells: HashMap<(u16, u16), GridCell>,
+}
+
+impl TestScreen {
+    fn new() -> Self {
+        Self {
+            parser: vt100::Parser::new(RENDER_TEST_ROWS, RENDER_TEST_COLS, 0),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn keyframe(&mut self, ansi: &str) -> GridUpdateMessage {
+        self.parser.process(b"\x1b[2J\x1b[H");
+        self.parser.process(ansi.as_bytes());
+        self.cells = self.grid();
+
+        let mut cells: Vec<_> = self.cells.clone().into_iter().collect();
+        cells.sort_by_key(|(position, _)| *position);
+        let screen = self.parser.screen();
+        GridUpdateMessage::Keyframe {
+            size: SerializablePtySize {
+                rows: RENDER_TEST_ROWS,
+                cols: RENDER_TEST_COLS,
+            },
+            cells,
+            cursor: screen.cursor_position(),
+            cursor_visible: !screen.hide_cursor(),
+            scrollback_position: 0,
+            scrollback_total: 0,
+            timestamp: SystemTime::now(),
+            runs: Vec::new(),
+        }
+    }
+
+    fn diff(&mut self, ansi: &str) -> GridUpdateMessage {
+        self.parser.process(ansi.as_bytes());
+        let grid = self.grid();
+
+        let mut changes: Vec<_> = grid
+            .iter()
+            .filter(|(position, cell)| self.cells.get(position) != Some(cell))
+            .map(|((row, col), cell)| (*row, *col, cell.clone()))
+            .collect();
+        changes.sort_by_key(|(row, col, _)| (*row, *col));
+        self.cells = grid;
+
+        let screen = self.parser.screen();
+        GridUpdateMessage::Diff {
+            changes,
+            cursor: Some(screen.cursor_position()),
+            cursor_visible: Some(!screen.hide_cursor()),
+            scrollback_position: None,
+            scrollback_total: None,
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    fn grid(&self) -> HashMap<(u16, u16), GridCell> {
+        let screen = self.parser.screen();
+        let mut grid = HashMap::new();
+        for row in 0..RENDER_TEST_ROWS {
+            for col in 0..RENDER_TEST_COLS {
+                if let Some(cell) = screen.cell(row, col) {
+                    grid.insert((row, col), GridCell::from(cell));
+                }
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::crash_dump::RenderWatchdog;
+
+    #[test]
+    fn sequence_is_consistent_and_keeps_bars_aligned() {
+        let frames = render_test_frames();
+        let mut watchdog = RenderWatchdog::new();
+        for frame in &frames {
+            assert_eq!(watchdog.observe(&frame.update), None);
+        }
+
+        // The wide-character scene: every labelled line has its bar at BAR_COL
+        let GridUpdateMessage::Keyframe { cells, .. } = &frames[0].update else {
+            panic!("scenes start with a keyframe");
+        };
+        let bars = cells
+            .iter()
+            .filter(|((row, col), cell)| *row >= 3 && *col == BAR_COL - 1 && cell.char == "|")
+            .count();
+        assert_eq!(bars, 6);
+
+        let GridUpdateMessage::Diff { changes, .. } = &frames.last().unwrap().update else {
+            panic!("the sequence ends with a diff");
+        };
+        let counter = changes.iter().find(|(row, col, _)| (*row, *col) == (4, 7));
+        assert_eq!(counter.map(|(_, _, cell)| cell.char.as_str()), Some("2"));
+    }
+}
@@ -0,0 +1,118 @@
+//! Run-length encoded keyframes
+//!
+//! A keyframe lists every cell of the screen, and most of a mostly-empty
+//! screen is the same blank cell over and over. Clients that connect with the
+//! `rle` capability get keyframes whose `runs` stand for the cells instead:
+//! each run is a row's consecutive cells that are identical, character and
+//! style. Runs never cross rows, and diffs are sent as they are.
+
+use super::pty_session::{GridCell, GridUpdateMessage};
+
+impl GridUpdateMessage {
+    /// Move a keyframe's `cells` into `runs`; diffs are left alone
+    pub fn run_length_encode(&mut self) {
+        if let GridUpdateMessage::Keyframe { cells, runs, .. } = self {
+            let mut sorted = std::mem::take(cells);
+            sorted.sort_by_key(|(position, _)| *position);
+            runs.extend(encode(sorted));
+        }
+    }
+
+    /// Expand a keyframe's `runs` back into `cells`
+    pub fn run_length_decode(&mut self) {
+        if let GridUpdateMessage::Keyframe { cells, runs, .. } = self {
+            for (row, col, len, cell) in runs.drain(..) {
+                cells.extend((col..col.saturating_add(len)).map(|col| ((row, col), cell.clone())));
+            }
+        }
+    }
+}
+
+/// Runs of `cells`, which are sorted by position
+fn encode(cells: Vec<((u16, u16), GridCell)>) -> Vec<(u16, u16, u16, GridCell)> {
+    let mut runs: Vec<(u16, u16, u16, GridCell)> = Vec::new();
+    for ((row, col), cell) in cells {
+        match runs.last_mut() {
+            Some((run_row, run_col, len, run_cell))
+                if *run_row == row
+                    && *run_col + *len == col
+                    && *len < u16::MAX
+                    && *run_cell == cell =>
+            {
+                *len += 1
+            }
+            _ => runs.push((row, col, 1, cell)),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::{SerializablePtySize, TerminalColor};
+    use std::collections::BTreeMap;
+
+    fn cell(char: &str, bold: bool) -> GridCell {
+        GridCell {
+            char: char.to_string(),
+            fg_color: None,
+            bg_color: Some(TerminalColor::Indexed(4)),
+            bold,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    fn cells(update: &GridUpdateMessage) -> BTreeMap<(u16, u16), GridCell> {
+        match update {
+            GridUpdateMessage::Keyframe { cells, .. } => cells.iter().cloned().collect(),
+            GridUpdateMessage::Diff { .. } => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn collapses_identical_cells_per_row() {
+        // "ab" in bold, then blanks to the end of both rows, with a gap at (1, 2)
+        let mut screen = vec![((0, 0), cell("a", true)), ((0, 1), cell("b", true))];
+        screen.extend((2..80).map(|col| ((0, col), cell(" ", false))));
+        screen.extend(
+            (0..80)
+                .filter(|col| *col != 2)
+                .map(|col| ((1, col), cell(" ", false))),
+        );
+        screen.reverse();
+        let mut keyframe = GridUpdateMessage::Keyframe {
+            size: SerializablePtySize { rows: 2, cols: 80 },
+            cells: screen,
+            cursor: (0, 2),
+            cursor_visible: true,
+            scrollback_position: 0,
+            scrollback_total: 0,
+            timestamp: std::time::SystemTime::now(),
+            runs: Vec::new(),
+        };
+        let original = cells(&keyframe);
+
+        keyframe.run_length_encode();
+        let GridUpdateMessage::Keyframe {
+            cells: left, runs, ..
+        } = &keyframe
+        else {
+            unreachable!()
+        };
+        assert!(left.is_empty());
+        let spans: Vec<_> = runs
+            .iter()
+            .map(|(row, col, len, _)| (*row, *col, *len))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(0, 0, 1), (0, 1, 1), (0, 2, 78), (1, 0, 2), (1, 3, 77)]
+        );
+
+        keyframe.run_length_decode();
+        assert_eq!(cells(&keyframe), original);
+    }
+}
@@ -0,0 +1,204 @@
+//! Full-text search over what sessions leave behind
+//!
+//! The server collects the text of agent transcripts, the scrollback of
+//! running sessions and handover notes as documents, and this finds the ones
+//! containing every word of a query, with a few snippets around the matches.
+//! Matching ignores ASCII case.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Most snippets shown per hit
+const MAX_SNIPPETS: usize = 3;
+/// Characters of context on each side of a match in a snippet
+const SNIPPET_CONTEXT: usize = 60;
+
+/// What a document's text came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SearchSource {
+    /// The agent's conversation transcript
+    Transcript,
+    /// The terminal history of a running session
+    Scrollback,
+    /// The note left when detaching
+    Note,
+}
+
+/// Text to search, with what it belongs to
+#[derive(Debug, Clone)]
+pub struct SearchDocument {
+    /// The codemux session, when known
+    pub session_id: Option<String>,
+    /// The agent's own conversation ID, for transcripts
+    pub conversation: Option<String>,
+    pub agent: String,
+    /// Project name
+    pub project: Option<String>,
+    pub source: SearchSource,
+    /// ISO 8601 timestamp of when the text last changed, if known
+    pub updated_at: Option<String>,
+    pub text: String,
+}
+
+/// A document that matched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SearchHit {
+    pub session_id: Option<String>,
+    /// Resume it with `codemux run <agent> --resume <conversation>`
+    pub conversation: Option<String>,
+    pub agent: String,
+    pub project: Option<String>,
+    pub source: SearchSource,
+    pub updated_at: Option<String>,
+    /// Excerpts around the matches
+    pub snippets: Vec<String>,
+    /// How often the query's words occur
+    pub matches: usize,
+}
+
+/// Response of `GET /api/search`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SearchResults {
+    pub query: String,
+    /// Best matches first
+    pub hits: Vec<SearchHit>,
+}
+
+/// The documents containing every word of `query`, most matches first, then
+/// most recently updated
+pub fn search(documents: &[SearchDocument], query: &str, limit: usize) -> Vec<SearchHit> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.to_ascii_lowercase())
+        .collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits: Vec<SearchHit> = documents
+        .iter()
+        .filter_map(|document| {
+            let text = document.text.to_ascii_lowercase();
+            let mut positions = Vec::new();
+            for term in &terms {
+                let found: Vec<_> = text
+                    .match_indices(term.as_str())
+                    .map(|(at, _)| (at, term.len()))
+                    .collect();
+                if found.is_empty() {
+                    return None;
+                }
+                positions.extend(found);
+            }
+            positions.sort_unstable();
+            Some(SearchHit {
+                session_id: document.session_id.clone(),
+                conversation: document.conversation.clone(),
+                agent: document.agent.clone(),
+                project: document.project.clone(),
+                source: document.source,
+                updated_at: document.updated_at.clone(),
+                snippets: snippets(&document.text, &positions),
+                matches: positions.len(),
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.matches
+            .cmp(&a.matches)
+            .then_with(|| b.updated_at.cmp(&a.updated_at))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+/// Excerpts around the first matches, overlapping ones merged
+fn snippets(text: &str, positions: &[(usize, usize)]) -> Vec<String> {
+    let mut windows: Vec<(usize, usize)> = Vec::new();
+    for &(at, len) in positions {
+        let start = floor_char_boundary(text, at.saturating_sub(SNIPPET_CONTEXT));
+        let end = ceil_char_boundary(text, at + len + SNIPPET_CONTEXT);
+        if let Some(last) = windows.last_mut().filter(|last| start <= last.1) {
+            last.1 = last.1.max(end);
+        } else if windows.len() == MAX_SNIPPETS {
+            break;
+        } else {
+            windows.push((start, end));
+        }
+    }
+    windows
+        .into_iter()
+        .map(|(start, end)| {
+            let excerpt = text[start..end].split_whitespace().collect::<Vec<_>>();
+            format!(
+                "{}{}{}",
+                if start > 0 { "…" } else { "" },
+                excerpt.join(" "),
+                if end < text.len() { "…" } else { "" }
+            )
+        })
+        .collect()
+}
+
+fn floor_char_boundary(text: &str, mut at: usize) -> usize {
+    while !text.is_char_boundary(at) {
+        at -= 1;
+    }
+    at
+}
+
+fn ceil_char_boundary(text: &str, at: usize) -> usize {
+    let mut at = at.min(text.len());
+    while !text.is_char_boundary(at) {
+        at += 1;
+    }
+    at
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(session_id: &str, updated_at: &str, text: &str) -> SearchDocument {
+        SearchDocument {
+            session_id: Some(session_id.to_string()),
+            conversation: None,
+            agent: "claude".to_string(),
+            project: Some("api".to_string()),
+            source: SearchSource::Transcript,
+            updated_at: Some(updated_at.to_string()),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn finds_documents_with_every_word_best_first() {
+        let filler = "lorem ipsum ".repeat(20);
+        let documents = vec![
+            document("a", "2026-01-01", "The rate limiter drops requests"),
+            document("b", "2026-01-02", "Nothing about limits here"),
+            document(
+                "c",
+                "2026-01-03",
+                &format!("Rate limits {}fixed the Rate Limiter — done", filler),
+            ),
+            document("d", "2026-01-04", "a limiter without the other word"),
+        ];
+
+        let hits = search(&documents, "rate  LIMITER", 10);
+        let ids: Vec<_> = hits.iter().map(|hit| hit.session_id.as_deref()).collect();
+        assert_eq!(ids, [Some("c"), Some("a")]);
+        assert_eq!(hits[0].matches, 3);
+        assert_eq!(hits[0].snippets.len(), 2);
+        assert!(hits[0].snippets[1].starts_with('…'));
+        assert!(hits[0].snippets[1].ends_with("Rate Limiter — done"));
+        assert_eq!(hits[1].snippets, ["The rate limiter drops requests"]);
+
+        assert_eq!(search(&documents, "rate limiter", 1).len(), 1);
+        assert!(search(&documents, "   ", 10).is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! Selections over the terminal grid, for the TUI's copy mode and
+//! `GET /api/sessions/:id/screen/text`
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// How the cells between the two ends of a selection are picked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum SelectionShape {
+    /// From one end to the other in reading order, the way text is selected
+    #[default]
+    Lines,
+    /// The rectangle with the two ends as its corners
+    Block,
+}
+
+/// A selection between two cells, given as (row, col)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub anchor: (u16, u16),
+    pub cursor: (u16, u16),
+    pub shape: SelectionShape,
+}
+
+impl Selection {
+    /// Columns selected on each row as (row, first col, last col), top to
+    /// bottom, on a grid `cols` wide
+    pub fn spans(&self, cols: u16) -> Vec<(u16, u16, u16)> {
+        let last_col = cols.saturating_sub(1);
+        let (start, end) = if self.anchor <= self.cursor {
+            (self.anchor, self.cursor)
+        } else {
+            (self.cursor, self.anchor)
+        };
+        match self.shape {
+            SelectionShape::Lines => (start.0..=end.0)
+                .map(|row| {
+                    let first = if row == start.0 { start.1 } else { 0 };
+                    let last = if row == end.0 { end.1 } else { last_col };
+                    (row, first.min(last_col), last.min(last_col))
+                })
+                .collect(),
+            SelectionShape::Block => {
+                let left = self.anchor.1.min(self.cursor.1).min(last_col);
+                let right = self.anchor.1.max(self.cursor.1).min(last_col);
+                (start.0..=end.0).map(|row| (row, left, right)).collect()
+            }
+        }
+    }
+
+    pub fn contains(&self, row: u16, col: u16, cols: u16) -> bool {
+        self.spans(cols)
+            .iter()
+            .any(|&(r, first, last)| r == row && (first..=last).contains(&col))
+    }
+
+    /// Text of the selection, a line per row without trailing blanks;
+    /// `push_cell` appends the character at (row, col)
+    pub fn text(&self, cols: u16, mut push_cell: impl FnMut(&mut String, u16, u16)) -> String {
+        let lines: Vec<String> = self
+            .spans(cols)
+            .into_iter()
+            .map(|(row, first, last)| {
+                let mut line = String::new();
+                for col in first..=last {
+                    push_cell(&mut line, row, col);
+                }
+                line.trim_end().to_string()
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+/// Text of part of a session's screen, from `GET /api/sessions/:id/screen/text`
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScreenText {
+    pub text: String,
+    /// Size of the whole screen
+    pub rows: u16,
+    pub cols: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_lines_or_blocks() {
+        let screen = ["hello world", "second line", "third"];
+        let text = |selection: Selection| {
+            selection.text(11, |line, row, col| {
+                let cell = screen[row as usize].chars().nth(col as usize);
+                line.push(cell.unwrap_or(' '));
+            })
+        };
+
+        let lines = Selection {
+            anchor: (2, 2),
+            cursor: (0, 6),
+            shape: SelectionShape::Lines,
+        };
+        assert_eq!(text(lines), "world\nsecond line\nthi");
+        let block = Selection {
+            shape: SelectionShape::Block,
+            ..lines
+        };
+        assert_eq!(text(block), "llo w\ncond\nird");
+        assert!(block.contains(1, 4, 11));
+        assert!(!block.contains(1, 7, 11));
+    }
+}
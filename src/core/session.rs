@@ -1,7 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use ts_rs::TS;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct SessionAttributes {
     pub agent: String,
@@ -10,18 +12,229 @@ pub struct SessionAttributes {
     pub session_type: SessionType,
     pub last_modified: Option<String>, // ISO 8601 timestamp string
     pub last_message: Option<String>,  // Most recent message from session
+    /// Accent color as `#rrggbb`, stable for the lifetime of the session
+    #[serde(default)]
+    pub color: String,
+    /// Emoji shown next to the session, picked from the agent unless overridden
+    #[serde(default)]
+    pub icon: String,
+    /// Handover note left when someone detached, e.g. "waiting on CI"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub note: Option<String>,
+    /// Name given at creation, usable wherever the session ID is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub name: Option<String>,
+    /// When a killed session ends for good unless the kill is undone, as RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub terminates_at: Option<String>,
+    /// When the session is ended for sitting idle unless it's used, as RFC
+    /// 3339; unset while a client is attached or without `[session] idle_timeout_secs`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub idle_expires_at: Option<String>,
+    /// Uncommitted changes in the session's directory, only on
+    /// `GET /api/sessions/:id` of a running session in a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub diff_stats: Option<DiffStats>,
+    /// Exit code of the agent of an `exited` session, when it could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub exit_code: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+/// Size of a set of changes, as in `git diff --stat`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct DiffStats {
+    pub files: u32,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+/// Accent colors handed out to sessions, readable on dark and light backgrounds
+pub const SESSION_COLORS: [&str; 10] = [
+    "#e06c75", "#e5a550", "#d7c15b", "#98c379", "#56b6c2", "#61afef", "#7d8cf0", "#c678dd",
+    "#e07ab8", "#a1887f",
+];
+
+/// Color for a session without an explicit one, derived from its ID
+///
+/// Uses FNV-1a rather than `DefaultHasher` so the color doesn't change
+/// between codemux versions.
+pub fn default_session_color(session_id: &str) -> String {
+    let hash = session_id
+        .bytes()
+        .fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+    SESSION_COLORS[(hash % SESSION_COLORS.len() as u64) as usize].to_string()
+}
+
+/// Agent name of `codemux demo` sessions; always allowed, whatever the whitelist says
+pub const DEMO_AGENT: &str = "demo";
+
+/// Icon for sessions of `agent` without an explicit one
+pub fn default_session_icon(agent: &str) -> String {
+    match agent.to_lowercase().as_str() {
+        "claude" => "✳️",
+        "gemini" => "♊",
+        "codex" => "🧩",
+        "aider" => "🛠️",
+        "cursor" => "🖱️",
+        "continue" => "⏩",
+        DEMO_AGENT => "🎬",
+        _ => "🤖",
+    }
+    .to_string()
+}
+
+/// Check a user-supplied session color, accepting `#rrggbb` only
+pub fn validate_session_color(color: &str) -> anyhow::Result<()> {
+    let hex = color.strip_prefix('#').unwrap_or("");
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow::anyhow!(
+            "Invalid session color '{}', expected #rrggbb",
+            color
+        ));
+    }
+    Ok(())
+}
+
+/// Session names are typed on the command line and used in URLs, so they
+/// stick to letters, digits, `.`, `_` and `-`
+pub fn validate_session_name(name: &str) -> anyhow::Result<()> {
+    let valid = (1..=64).contains(&name.len())
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(anyhow::anyhow!(
+            "Invalid session name '{}', expected up to 64 letters, digits, '.', '_' or '-'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub enum SessionType {
     Active,
     Historical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
 #[ts(export)]
 pub struct ProjectAttributes {
     pub name: String,
     pub path: String,
+    /// Accent color as `#rrggbb`, derived from the project's name
+    #[serde(default)]
+    pub color: String,
+    /// Emoji for the kind of project, e.g. 🦀 for a Cargo package
+    #[serde(default)]
+    pub icon: String,
+    /// URL of the repository's `origin` remote
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub remote: Option<String>,
+    /// What the project's sessions start with unless told otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub defaults: Option<ProjectDefaults>,
+}
+
+/// A project's default agent, arguments and environment, set with
+/// `codemux project set`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ProjectDefaults {
+    /// Agent to run when a session in the project is started without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub agent: Option<String>,
+    /// Arguments for sessions of the default agent started without any
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Environment of every session in the project; a session's own env wins
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl ProjectDefaults {
+    pub fn is_empty(&self) -> bool {
+        self.agent.is_none() && self.args.is_empty() && self.env.is_empty()
+    }
+
+    /// `args` for a session of `agent`, or the default arguments when there
+    /// are none and `agent` is the default agent (or there is none); adds
+    /// the default env to `env` under the variables it sets itself
+    pub fn apply(
+        &self,
+        agent: &str,
+        args: Vec<String>,
+        env: &mut BTreeMap<String, String>,
+    ) -> Vec<String> {
+        for (name, value) in &self.env {
+            env.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        let default_agent = self.agent.as_deref().is_none_or(|default| default == agent);
+        if args.is_empty() && default_agent {
+            self.args.clone()
+        } else {
+            args
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_colors_are_stable_and_from_the_palette() {
+        let color = default_session_color("3f2b9c1e-0000-4000-8000-000000000000");
+        assert_eq!(
+            color,
+            default_session_color("3f2b9c1e-0000-4000-8000-000000000000")
+        );
+        assert!(SESSION_COLORS.contains(&color.as_str()));
+        assert!(validate_session_color(&color).is_ok());
+        assert!(validate_session_color("red").is_err());
+        assert!(validate_session_color("#12345g").is_err());
+        assert_eq!(default_session_icon("Claude"), "✳️");
+        assert_eq!(default_session_icon("my-agent"), "🤖");
+        assert!(validate_session_name("api-fix_2.0").is_ok());
+        assert!(validate_session_name("-rf").is_err());
+        assert!(validate_session_name("my session").is_err());
+        assert!(validate_session_name("").is_err());
+    }
+
+    #[test]
+    fn project_defaults_fill_in_what_a_session_leaves_out() {
+        let defaults = ProjectDefaults {
+            agent: Some("claude".to_string()),
+            args: vec!["--model".to_string(), "sonnet".to_string()],
+            env: BTreeMap::from([
+                ("A".to_string(), "project".to_string()),
+                ("B".to_string(), "project".to_string()),
+            ]),
+        };
+        let mut env = BTreeMap::from([("A".to_string(), "session".to_string())]);
+        assert_eq!(
+            defaults.apply("claude", Vec::new(), &mut env),
+            defaults.args
+        );
+        assert_eq!(env["A"], "session");
+        assert_eq!(env["B"], "project");
+
+        let own = vec!["--verbose".to_string()];
+        assert_eq!(defaults.apply("claude", own.clone(), &mut env), own);
+        assert!(defaults.apply("gemini", Vec::new(), &mut env).is_empty());
+    }
 }
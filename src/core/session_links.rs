@@ -0,0 +1,238 @@
+//! Typed links between sessions
+//!
+//! Fanning out several attempts at one task leaves a pile of similar sessions.
+//! Each session remembers where it came from: the template it was started
+//! from, or the session it clones, retries or shares a worktree with. The links
+//! are served as JSON:API relationships and followed back for the lineage
+//! breadcrumb in the web UI.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use super::json_api::JsonApiResourceRef;
+
+/// How far a lineage is followed back, in case the history has a loop
+const MAX_LINEAGE: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, TS, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum SessionLinkKind {
+    /// Started from a configured `[templates.<name>]` entry
+    SpawnedFromTemplate,
+    /// Same agent, arguments and project as another session
+    CloneOf,
+    /// Another go at what another session attempted
+    RetryOf,
+    /// Runs in a git worktree of another session's project
+    WorktreeOf,
+}
+
+impl SessionLinkKind {
+    /// Whether the link points at another session rather than a template
+    pub fn links_session(self) -> bool {
+        self != SessionLinkKind::SpawnedFromTemplate
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct SessionLink {
+    pub kind: SessionLinkKind,
+    /// ID of the related session, or the template name for `spawned_from_template`
+    pub id: String,
+}
+
+/// A JSON:API relationship to one resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct Relationship {
+    pub data: JsonApiResourceRef,
+}
+
+/// `relationships` of a session resource, one member per kind of link
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct SessionRelationships {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub spawned_from_template: Option<Relationship>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub clone_of: Option<Relationship>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub retry_of: Option<Relationship>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub worktree_of: Option<Relationship>,
+}
+
+impl SessionRelationships {
+    /// Relationships for a session's links, `None` without any
+    pub fn from_links(links: &[SessionLink]) -> Option<Self> {
+        if links.is_empty() {
+            return None;
+        }
+        let mut relationships = SessionRelationships::default();
+        for link in links {
+            let resource_type = match link.kind {
+                SessionLinkKind::SpawnedFromTemplate => "template",
+                _ => "session",
+            };
+            let relationship = Some(Relationship {
+                data: JsonApiResourceRef {
+                    resource_type: resource_type.to_string(),
+                    id: link.id.clone(),
+                },
+            });
+            match link.kind {
+                SessionLinkKind::SpawnedFromTemplate => {
+                    relationships.spawned_from_template = relationship
+                }
+                SessionLinkKind::CloneOf => relationships.clone_of = relationship,
+                SessionLinkKind::RetryOf => relationships.retry_of = relationship,
+                SessionLinkKind::WorktreeOf => relationships.worktree_of = relationship,
+            }
+        }
+        Some(relationships)
+    }
+}
+
+/// Check the links given for a new session: one of each kind, pointing at
+/// sessions `known` knows about
+pub fn validate_links(links: &[SessionLink], known: impl Fn(&str) -> bool) -> Result<()> {
+    let mut kinds = HashSet::new();
+    for link in links {
+        if !kinds.insert(link.kind) {
+            return Err(anyhow!("A session can only have one link of each kind"));
+        }
+        if link.kind.links_session() && !known(&link.id) {
+            return Err(anyhow!("Linked session '{}' does not exist", link.id));
+        }
+    }
+    Ok(())
+}
+
+/// The session this one came from, if it came from one
+pub fn parent(links: &[SessionLink]) -> Option<&SessionLink> {
+    links.iter().find(|link| link.kind.links_session())
+}
+
+/// One session in a lineage breadcrumb
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LineageEntry {
+    pub id: String,
+    pub agent: String,
+    pub color: String,
+    pub icon: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub name: Option<String>,
+    /// How this session came from the one before it in the lineage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub kind: Option<SessionLinkKind>,
+    /// Template the session was started from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub template: Option<String>,
+}
+
+/// The ancestors of `session_id`, oldest first, and the session itself last;
+/// `lookup` gives a session's entry and links. Ancestors that can't be found
+/// any more end the lineage.
+pub fn lineage(
+    session_id: &str,
+    lookup: impl Fn(&str) -> Option<(LineageEntry, Vec<SessionLink>)>,
+) -> Vec<LineageEntry> {
+    let mut entries = Vec::new();
+    let mut seen = HashSet::new();
+    let mut next = Some(session_id.to_string());
+    while let Some(id) = next.take() {
+        if entries.len() == MAX_LINEAGE || !seen.insert(id.clone()) {
+            break;
+        }
+        let Some((mut entry, links)) = lookup(&id) else {
+            break;
+        };
+        entry.template = links
+            .iter()
+            .find(|link| link.kind == SessionLinkKind::SpawnedFromTemplate)
+            .map(|link| link.id.clone());
+        if let Some(link) = parent(&links) {
+            entry.kind = Some(link.kind);
+            next = Some(link.id.clone());
+        }
+        entries.push(entry);
+    }
+    entries.reverse();
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(kind: SessionLinkKind, id: &str) -> SessionLink {
+        SessionLink {
+            kind,
+            id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn follows_links_back_to_the_first_attempt() {
+        let links_of = |id: &str| match id {
+            "first" => Some(vec![link(SessionLinkKind::SpawnedFromTemplate, "review")]),
+            "retry" => Some(vec![link(SessionLinkKind::RetryOf, "first")]),
+            "clone" => Some(vec![link(SessionLinkKind::CloneOf, "retry")]),
+            "loop" => Some(vec![link(SessionLinkKind::RetryOf, "loop")]),
+            _ => None,
+        };
+        let lookup = |id: &str| {
+            let entry = LineageEntry {
+                id: id.to_string(),
+                agent: "claude".to_string(),
+                color: "#61afef".to_string(),
+                icon: "✳️".to_string(),
+                name: None,
+                kind: None,
+                template: None,
+            };
+            Some((entry, links_of(id)?))
+        };
+
+        let chain = lineage("clone", lookup);
+        let ids: Vec<_> = chain.iter().map(|entry| entry.id.as_str()).collect();
+        assert_eq!(ids, ["first", "retry", "clone"]);
+        assert_eq!(chain[0].template.as_deref(), Some("review"));
+        assert_eq!(chain[0].kind, None);
+        assert_eq!(chain[2].kind, Some(SessionLinkKind::CloneOf));
+        assert_eq!(lineage("loop", lookup).len(), 1);
+
+        let relationships = SessionRelationships::from_links(&links_of("first").unwrap()).unwrap();
+        assert_eq!(
+            relationships
+                .spawned_from_template
+                .unwrap()
+                .data
+                .resource_type,
+            "template"
+        );
+        assert_eq!(SessionRelationships::from_links(&[]), None);
+
+        let known = |id: &str| links_of(id).is_some();
+        assert!(validate_links(&[link(SessionLinkKind::RetryOf, "first")], known).is_ok());
+        assert!(validate_links(&[link(SessionLinkKind::RetryOf, "gone")], known).is_err());
+        let twice = [
+            link(SessionLinkKind::RetryOf, "first"),
+            link(SessionLinkKind::RetryOf, "retry"),
+        ];
+        assert!(validate_links(&twice, known).is_err());
+    }
+}
@@ -0,0 +1,183 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+/// First line after the shebang, used to recognise our own shims
+const SHIM_MARKER: &str = "# codemux-shim";
+
+/// Default shim directory; it has to come before the agent's directory in `PATH`
+pub fn default_shim_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("shims")
+}
+
+/// Shell script that runs `agent` through codemux
+///
+/// Inside a codemux session (where `CODEMUX_SESSION_ID` is set) and when not
+/// attached to a terminal, as in scripts and pipes, it runs the real agent
+/// directly so the server doesn't loop back into the shim.
+pub fn shim_script(agent: &str, codemux: &Path) -> String {
+    format!(
+        r#"#!/bin/sh
+{marker} for {agent}, remove with: codemux shim uninstall {agent}
+if [ -z "$CODEMUX_SESSION_ID" ] && [ -t 0 ] && [ -t 1 ]; then
+  exec "{codemux}" run {agent} -- "$@"
+fi
+shim_dir=$(CDPATH= cd -- "$(dirname -- "$0")" && pwd)
+IFS=:
+for dir in $PATH; do
+  if [ "$dir" != "$shim_dir" ] && [ -x "$dir/{agent}" ]; then
+    exec "$dir/{agent}" "$@"
+  fi
+done
+echo "codemux shim: no {agent} found in PATH outside $shim_dir" >&2
+exit 127
+"#,
+        marker = SHIM_MARKER,
+        agent = agent,
+        codemux = codemux.display(),
+    )
+}
+
+pub fn is_shim(path: &Path) -> bool {
+    std::fs::read_to_string(path)
+        .map(|content| {
+            content
+                .lines()
+                .nth(1)
+                .unwrap_or("")
+                .starts_with(SHIM_MARKER)
+        })
+        .unwrap_or(false)
+}
+
+/// Write the shim for `agent` into `dir`
+///
+/// Refuses to replace a file that isn't a codemux shim unless `force` is set.
+/// Both names end up in the script, so agent names are limited to letters,
+/// digits, `.`, `_` and `-`, and the codemux path can't contain characters
+/// the shell expands inside double quotes.
+pub fn install_shim(dir: &Path, agent: &str, codemux: &Path, force: bool) -> Result<PathBuf> {
+    let valid_name = agent
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid_name || agent.is_empty() || agent == "." || agent == ".." {
+        return Err(anyhow!("Invalid agent name '{}'", agent));
+    }
+    if codemux.to_string_lossy().contains(['"', '$', '`', '\\']) {
+        return Err(anyhow!(
+            "The shim can't run codemux from {}: the path has a quote, $, backtick or backslash",
+            codemux.display()
+        ));
+    }
+    let path = dir.join(agent);
+    if path.exists() && !is_shim(&path) && !force {
+        return Err(anyhow!(
+            "{} already exists and is not a codemux shim (use --force to replace it)",
+            path.display()
+        ));
+    }
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, shim_script(agent, codemux))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+pub fn uninstall_shim(dir: &Path, agent: &str) -> Result<PathBuf> {
+    let path = dir.join(agent);
+    if !path.exists() {
+        return Err(anyhow!("No shim for '{}' in {}", agent, dir.display()));
+    }
+    if !is_shim(&path) {
+        return Err(anyhow!(
+            "{} is not a codemux shim; leaving it alone",
+            path.display()
+        ));
+    }
+    std::fs::remove_file(&path)?;
+    Ok(path)
+}
+
+/// Agents with a shim installed in `dir`
+pub fn installed_shims(dir: &Path) -> Vec<String> {
+    let mut agents: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| is_shim(&entry.path()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    agents.sort();
+    agents
+}
+
+/// Where `agent` is found in `PATH` relative to the shim, e.g. for warnings
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShimPrecedence {
+    /// The shim is the first `agent` in `PATH`
+    Active,
+    /// The shim directory is not in `PATH`
+    NotInPath,
+    /// Another `agent` comes first in `PATH`
+    Shadowed(PathBuf),
+}
+
+pub fn shim_precedence(dir: &Path, agent: &str, path_var: &std::ffi::OsStr) -> ShimPrecedence {
+    for entry in std::env::split_paths(path_var) {
+        if entry == dir {
+            return ShimPrecedence::Active;
+        }
+        let candidate = entry.join(agent);
+        if candidate.is_file() {
+            return ShimPrecedence::Shadowed(candidate);
+        }
+    }
+    ShimPrecedence::NotInPath
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_refuses_foreign_files_and_uninstall_only_removes_shims() {
+        let dir = std::env::temp_dir().join(format!("codemux-shim-{}", std::process::id()));
+        let codemux = Path::new("/usr/local/bin/codemux");
+
+        let path = install_shim(&dir, "claude", codemux, false).unwrap();
+        assert!(is_shim(&path));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains(r#"exec "/usr/local/bin/codemux" run claude -- "$@""#));
+        // Reinstalling over our own shim is fine
+        install_shim(&dir, "claude", codemux, false).unwrap();
+
+        std::fs::write(dir.join("gemini"), "#!/bin/sh\necho real gemini\n").unwrap();
+        assert!(install_shim(&dir, "gemini", codemux, false).is_err());
+        assert!(uninstall_shim(&dir, "gemini").is_err());
+        assert_eq!(installed_shims(&dir), ["claude"]);
+
+        for agent in ["", "..", "claude; rm -rf ~", "$(id)", "a/b"] {
+            assert!(install_shim(&dir, agent, codemux, false).is_err());
+        }
+        assert!(install_shim(&dir, "codex", Path::new("/opt/$HOME/codemux"), false).is_err());
+        assert!(install_shim(&dir, "codex", Path::new("/opt/`id`/codemux"), false).is_err());
+        assert_eq!(installed_shims(&dir), ["claude"]);
+
+        let other = dir.join("other");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("claude"), "").unwrap();
+        let path_var = std::env::join_paths([&other, &dir]).unwrap();
+        assert_eq!(
+            shim_precedence(&dir, "claude", &path_var),
+            ShimPrecedence::Shadowed(other.join("claude"))
+        );
+
+        uninstall_shim(&dir, "claude").unwrap();
+        assert!(installed_shims(&dir).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
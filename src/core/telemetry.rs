@@ -0,0 +1,175 @@
+//! OpenTelemetry tracing of the session pipeline
+//!
+//! Input from a client travels through several tasks connected by channels:
+//! the WebSocket handler, the PTY writer, the VT100 processor that diffs the
+//! grid, and the WebSocket handlers sending the update back. Each stage opens
+//! a `tracing` span; [`PipelineTrace`] carries the OpenTelemetry context from
+//! one stage to the next so a keystroke and the screen update it causes end up
+//! in a single trace. Spans are exported over OTLP when the `otel` feature is
+//! built and `[telemetry] otlp_endpoint` is set.
+
+use serde::{Deserialize, Serialize};
+
+/// Trace export, configured as `[telemetry]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP collector to export spans to, e.g. `http://localhost:4318/v1/traces`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub otlp_endpoint: Option<String>,
+    /// `service.name` reported with every span
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            otlp_endpoint: None,
+            service_name: "codemux".to_string(),
+        }
+    }
+}
+
+/// Hands the trace of a session's pipeline from one stage to the next
+///
+/// Cloned into every task of a session. Without the `otel` feature it holds
+/// nothing and every method is a no-op.
+#[derive(Clone, Default)]
+pub struct PipelineTrace {
+    #[cfg(feature = "otel")]
+    slots: std::sync::Arc<std::sync::Mutex<TraceSlots>>,
+}
+
+#[cfg(feature = "otel")]
+#[derive(Default)]
+struct TraceSlots {
+    /// Input written to the PTY whose output hasn't been processed yet
+    input: Option<opentelemetry::Context>,
+    /// Stage that produced the grid update most recently broadcast
+    update: Option<opentelemetry::Context>,
+}
+
+impl PipelineTrace {
+    /// Record that the input traced by `span` reached the PTY
+    pub fn input_written(&self, span: &tracing::Span) {
+        #[cfg(feature = "otel")]
+        {
+            use tracing_opentelemetry::OpenTelemetrySpanExt;
+            self.slots.lock().expect("trace slots poisoned").input = Some(span.context());
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = span;
+    }
+
+    /// Continue the trace of the last input written in `span`, which processes
+    /// the output it caused; starts a new trace when no input is pending
+    pub fn continue_input(&self, span: &tracing::Span) {
+        #[cfg(feature = "otel")]
+        {
+            use tracing_opentelemetry::OpenTelemetrySpanExt;
+            let input = self
+                .slots
+                .lock()
+                .expect("trace slots poisoned")
+                .input
+                .take();
+            if let Some(cx) = input {
+                let _ = span.set_parent(cx);
+            }
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = span;
+    }
+
+    /// Record `span` as the stage that produced the grid update being broadcast
+    pub fn update_sent(&self, span: &tracing::Span) {
+        #[cfg(feature = "otel")]
+        {
+            use tracing_opentelemetry::OpenTelemetrySpanExt;
+            self.slots.lock().expect("trace slots poisoned").update = Some(span.context());
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = span;
+    }
+
+    /// Continue the trace of the latest grid update in `span`, which sends it to a client
+    pub fn continue_update(&self, span: &tracing::Span) {
+        #[cfg(feature = "otel")]
+        {
+            use tracing_opentelemetry::OpenTelemetrySpanExt;
+            let update = self
+                .slots
+                .lock()
+                .expect("trace slots poisoned")
+                .update
+                .clone();
+            if let Some(cx) = update {
+                let _ = span.set_parent(cx);
+            }
+        }
+        #[cfg(not(feature = "otel"))]
+        let _ = span;
+    }
+}
+
+#[cfg(feature = "otel")]
+static TRACER_PROVIDER: std::sync::OnceLock<opentelemetry_sdk::trace::SdkTracerProvider> =
+    std::sync::OnceLock::new();
+
+/// Layer exporting spans to the configured OTLP endpoint, if there is one
+#[cfg(feature = "otel")]
+pub fn otlp_layer<S>(
+    config: &TelemetryConfig,
+) -> anyhow::Result<Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a> + Send + Sync,
+{
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::WithExportConfig;
+
+    let Some(endpoint) = &config.otlp_endpoint else {
+        return Ok(None);
+    };
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()?;
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            opentelemetry_sdk::Resource::builder()
+                .with_service_name(config.service_name.clone())
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer("codemux");
+    let _ = TRACER_PROVIDER.set(provider);
+
+    Ok(Some(Box::new(
+        tracing_opentelemetry::layer().with_tracer(tracer),
+    )))
+}
+
+/// Without the `otel` feature there is nothing to export to
+#[cfg(not(feature = "otel"))]
+pub fn otlp_layer<S>(
+    config: &TelemetryConfig,
+) -> anyhow::Result<Option<Box<dyn tracing_subscriber::Layer<S> + Send + Sync>>>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a> + Send + Sync,
+{
+    if config.otlp_endpoint.is_some() {
+        eprintln!("⚠️  [telemetry] otlp_endpoint is set, but this build has no `otel` feature");
+    }
+    Ok(None)
+}
+
+/// Export the spans still buffered; call before the process exits
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            tracing::warn!("Failed to flush OpenTelemetry spans: {}", e);
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Agent conversations as plain messages
+//!
+//! Every adapter turns its agent's transcript entries into the same
+//! `TranscriptMessage`s, which the conversation view and usage totals are
+//! built from. Agents codemux has no adapter for can describe their transcripts
+//! in `[agents.<name>.transcript]` instead: where the files are and where
+//! role, content, timestamp and usage sit in each entry.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+use utoipa::ToSchema;
+
+use super::agent_adapter::AgentAdapter;
+
+/// One message of a conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TranscriptMessage {
+    /// `user` or `assistant`, or whatever else the agent calls the sender
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub input_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "number")]
+    pub output_tokens: Option<u64>,
+    /// In US dollars, for agents that report it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub cost: Option<f64>,
+}
+
+/// Totals over a conversation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct TranscriptStats {
+    pub messages: usize,
+    #[ts(type = "number")]
+    pub input_tokens: u64,
+    #[ts(type = "number")]
+    pub output_tokens: u64,
+    /// `None` when no message reported a cost
+    pub cost: Option<f64>,
+}
+
+impl TranscriptStats {
+    pub fn of(messages: &[TranscriptMessage]) -> Self {
+        let mut stats = TranscriptStats {
+            messages: messages.len(),
+            ..Default::default()
+        };
+        for message in messages {
+            stats.input_tokens += message.input_tokens.unwrap_or(0);
+            stats.output_tokens += message.output_tokens.unwrap_or(0);
+            if let Some(cost) = message.cost {
+                *stats.cost.get_or_insert(0.0) += cost;
+            }
+        }
+        stats
+    }
+
+    /// Fold another conversation's totals into these
+    pub fn add(&mut self, other: &TranscriptStats) {
+        self.messages += other.messages;
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        if let Some(cost) = other.cost {
+            *self.cost.get_or_insert(0.0) += cost;
+        }
+    }
+}
+
+/// The conversation of a running session, as plain messages
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SessionTranscript {
+    /// Transcript file the messages come from, `None` until the agent wrote one
+    pub path: Option<String>,
+    pub messages: Vec<TranscriptMessage>,
+    pub stats: TranscriptStats,
+}
+
+/// Token and cost totals of a running session, served by `GET /api/sessions/:id/usage`
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub agent: String,
+    /// Project ID
+    pub project: Option<String>,
+    pub usage: TranscriptStats,
+}
+
+/// Usage of the sessions of one project
+#[derive(Debug, Clone, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct ProjectUsage {
+    /// Project ID, `None` for sessions outside any project
+    pub project: Option<String>,
+    pub name: Option<String>,
+    pub sessions: usize,
+    pub usage: TranscriptStats,
+}
+
+/// Usage of every running session, per session, per project and overall,
+/// served by `GET /api/usage`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS, ToSchema)]
+#[ts(export)]
+pub struct UsageSummary {
+    pub sessions: Vec<SessionUsage>,
+    pub projects: Vec<ProjectUsage>,
+    pub total: TranscriptStats,
+}
+
+impl UsageSummary {
+    /// Totals of `sessions`; `project_name` names project IDs
+    pub fn of(sessions: Vec<SessionUsage>, project_name: impl Fn(&str) -> Option<String>) -> Self {
+        let mut projects: Vec<ProjectUsage> = Vec::new();
+        let mut total = TranscriptStats::default();
+        for session in &sessions {
+            total.add(&session.usage);
+            let index = match projects
+                .iter()
+                .position(|project| project.project == session.project)
+            {
+                Some(index) => index,
+                None => {
+                    projects.push(ProjectUsage {
+                        project: session.project.clone(),
+                        name: session.project.as_deref().and_then(&project_name),
+                        sessions: 0,
+                        usage: TranscriptStats::default(),
+                    });
+                    projects.len() - 1
+                }
+            };
+            projects[index].sessions += 1;
+            projects[index].usage.add(&session.usage);
+        }
+        UsageSummary {
+            sessions,
+            projects,
+            total,
+        }
+    }
+}
+
+/// A conversation an agent kept for a project, served by
+/// `GET /api/projects/:id/conversations` whether or not a session runs it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ConversationAttributes {
+    pub agent: String,
+    /// First thing the user asked, shortened
+    pub title: Option<String>,
+    pub last_modified: String,
+    /// Transcript size in bytes
+    #[ts(type = "number")]
+    pub size: u64,
+    /// Written to in the last few minutes
+    pub active: bool,
+}
+
+/// The value at a dotted path such as `message.usage.input_tokens`; numbers index arrays
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(value, |value, key| match value {
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => value.get(key),
+        })
+}
+
+/// Readable text of message content: strings as they are, and the `text` of
+/// content blocks or parts joined by blank lines
+pub fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .map(text_of)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Value::Object(block) => block.get("text").map(text_of).unwrap_or_default(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// How entries of a transcript file are laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptFormat {
+    /// One JSON entry per line
+    #[default]
+    Jsonl,
+    /// One JSON document holding all entries
+    Json,
+}
+
+/// Where an agent keeps its transcripts and how to read them, configured as
+/// `[agents.<name>.transcript]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSpec {
+    /// Glob of the transcript files, e.g. `~/.mycli/history/{project_name}/*.jsonl`;
+    /// `{project}` is the project path and `{project_name}` its last component
+    pub files: String,
+    #[serde(default)]
+    pub format: TranscriptFormat,
+    /// Dotted path to the array of entries in a `json` document; the document itself if empty
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub entries: String,
+    /// Dotted paths inside each entry; entries without content are left out
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<String>,
+}
+
+impl TranscriptSpec {
+    /// The `files` glob for one project, with `~` and the placeholders filled in
+    fn pattern(&self, home: &Path, project: &Path) -> String {
+        let project_name = project
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let files = match self.files.strip_prefix("~/") {
+            Some(rest) => format!("{}/{}", home.to_string_lossy(), rest),
+            None => self.files.clone(),
+        };
+        files
+            .replace("{project}", &project.to_string_lossy())
+            .replace("{project_name}", &project_name)
+    }
+}
+
+/// Adapter for an agent described by a `TranscriptSpec`; `--continue` and
+/// `--resume` are passed to it as they are
+pub struct SpecAdapter(pub TranscriptSpec);
+
+impl AgentAdapter for SpecAdapter {
+    fn resume_args(&self, conversation: Option<&str>) -> Vec<String> {
+        match conversation {
+            Some(id) => vec!["--resume".to_string(), id.to_string()],
+            None => vec!["--continue".to_string()],
+        }
+    }
+
+    /// The part of the glob before the first wildcard
+    fn transcript_dir(&self, home: &Path, project: &Path) -> PathBuf {
+        let pattern = self.0.pattern(home, project);
+        Path::new(&pattern)
+            .components()
+            .take_while(|component| {
+                !component
+                    .as_os_str()
+                    .to_string_lossy()
+                    .contains(['*', '?', '['])
+            })
+            .collect()
+    }
+
+    fn is_transcript(&self, _path: &Path) -> bool {
+        // Needs the project to fill in the glob, see `transcripts`
+        true
+    }
+
+    fn transcripts(&self, home: &Path, project: &Path) -> Vec<PathBuf> {
+        let glob = glob_regex(&self.0.pattern(home, project));
+        let mut files = Vec::new();
+        walk(&self.transcript_dir(home, project), &mut files);
+        let mut transcripts: Vec<_> = files
+            .into_iter()
+            .filter(|path| glob.is_match(&path.to_string_lossy()))
+            .filter_map(|path| {
+                let modified = path.metadata().and_then(|meta| meta.modified()).ok()?;
+                Some((modified, path))
+            })
+            .collect();
+        transcripts.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        transcripts.into_iter().map(|(_, path)| path).collect()
+    }
+
+    fn parse_transcript(&self, content: &str) -> Vec<Value> {
+        match self.0.format {
+            TranscriptFormat::Jsonl => content
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect(),
+            TranscriptFormat::Json => serde_json::from_str::<Value>(content)
+                .ok()
+                .and_then(|document| match lookup(&document, &self.0.entries) {
+                    Some(Value::Array(entries)) => Some(entries.clone()),
+                    _ => None,
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    fn messages(&self, entries: &[Value]) -> Vec<TranscriptMessage> {
+        let spec = &self.0;
+        let field = |entry: &Value, path: &Option<String>| {
+            path.as_deref()
+                .and_then(|path| lookup(entry, path))
+                .cloned()
+        };
+        entries
+            .iter()
+            .filter_map(|entry| {
+                let content = text_of(lookup(entry, &spec.content)?);
+                if content.is_empty() {
+                    return None;
+                }
+                Some(TranscriptMessage {
+                    role: lookup(entry, &spec.role).map(text_of).unwrap_or_default(),
+                    content,
+                    timestamp: field(entry, &spec.timestamp).map(|value| text_of(&value)),
+                    input_tokens: field(entry, &spec.input_tokens).and_then(|v| v.as_u64()),
+                    output_tokens: field(entry, &spec.output_tokens).and_then(|v| v.as_u64()),
+                    cost: field(entry, &spec.cost).and_then(|v| v.as_f64()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `*` and `?` stay within one directory, `**` spans any number of them
+fn glob_regex(pattern: &str) -> Regex {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).expect("escaped glob is a valid regex")
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => walk(&path, files),
+            Ok(kind) if kind.is_file() => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_transcripts_described_in_config() {
+        let home = std::env::temp_dir().join(format!("codemux-transcript-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&home);
+        let spec: TranscriptSpec = toml::from_str(
+            r#"
+            files = "~/.mycli/{project_name}/**/*.jsonl"
+            role = "author"
+            content = "body.parts"
+            timestamp = "at"
+            input_tokens = "usage.in"
+            output_tokens = "usage.out"
+            cost = "usage.usd"
+            "#,
+        )
+        .unwrap();
+        let adapter = SpecAdapter(spec);
+        let project = Path::new("/work/api");
+
+        let dir = home.join(".mycli/api/2025/09");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a transcript").unwrap();
+        std::fs::write(
+            dir.join("chat.jsonl"),
+            concat!(
+                r#"{"author":"user","body":{"parts":["fix the login form"]},"at":"2025-09-20T14:03:00Z"}"#,
+                "\n",
+                r#"{"author":"tool","body":{"parts":[]}}"#,
+                "\n",
+                r#"{"author":"assistant","body":{"parts":[{"text":"Done."}]},"usage":{"in":1200,"out":80,"usd":0.01}}"#,
+                "\n",
+                r#"{"author":"assistant","body":{"parts":"Anything else?"},"usage":{"in":1300,"out":5,"usd":0.02}}"#,
+            ),
+        )
+        .unwrap();
+
+        assert_eq!(
+            adapter.transcript_dir(&home, project),
+            home.join(".mycli/api")
+        );
+        let found = adapter.transcripts(&home, project);
+        assert_eq!(found, vec![dir.join("chat.jsonl")]);
+
+        let entries = adapter.parse_transcript(&std::fs::read_to_string(&found[0]).unwrap());
+        let messages = adapter.messages(&entries);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].content, "fix the login form");
+        assert_eq!(
+            messages[0].timestamp.as_deref(),
+            Some("2025-09-20T14:03:00Z")
+        );
+        assert_eq!(messages[1].role, "assistant");
+        assert_eq!(messages[1].content, "Done.");
+
+        let stats = TranscriptStats::of(&messages);
+        assert_eq!((stats.input_tokens, stats.output_tokens), (2500, 85));
+        assert!((stats.cost.unwrap() - 0.03).abs() < 1e-9);
+        assert_eq!(TranscriptStats::of(&messages[..1]).cost, None);
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn sums_usage_per_project() {
+        let usage = |input_tokens, cost| TranscriptStats {
+            messages: 2,
+            input_tokens,
+            output_tokens: 10,
+            cost,
+        };
+        let session = |id: &str, project: Option<&str>, usage| SessionUsage {
+            session_id: id.to_string(),
+            agent: "claude".to_string(),
+            project: project.map(str::to_string),
+            usage,
+        };
+        let summary = UsageSummary::of(
+            vec![
+                session("s1", Some("p1"), usage(100, Some(0.5))),
+                session("s2", None, usage(40, None)),
+                session("s3", Some("p1"), usage(200, Some(0.25))),
+            ],
+            |id| (id == "p1").then(|| "api".to_string()),
+        );
+
+        assert_eq!(summary.projects.len(), 2);
+        assert_eq!(summary.projects[0].name.as_deref(), Some("api"));
+        assert_eq!(summary.projects[0].sessions, 2);
+        assert_eq!(summary.projects[0].usage.input_tokens, 300);
+        assert_eq!(summary.projects[0].usage.cost, Some(0.75));
+        assert_eq!(summary.projects[1].project, None);
+        assert_eq!(summary.projects[1].usage.cost, None);
+        assert_eq!(summary.total.messages, 6);
+        assert_eq!(summary.total.output_tokens, 30);
+        assert_eq!(summary.total.cost, Some(0.75));
+    }
+}
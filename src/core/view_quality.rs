@@ -0,0 +1,350 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::core::pty_session::{GridCell, GridUpdateMessage, TerminalColor};
+
+/// Detail a terminal WebSocket client asks for when it connects
+///
+/// Passive viewers such as dashboards and phones watching a session don't need
+/// every diff in full color; they trade latency and fidelity for bandwidth.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ViewQuality {
+    /// At most this many grid updates per second; diffs in between are merged
+    pub max_fps: Option<u32>,
+    /// Only send a fresh keyframe this often, in seconds, and only when the screen changed
+    pub keyframe_every: Option<u64>,
+    /// Colors to reduce cells to
+    pub colors: ColorDepth,
+}
+
+impl ViewQuality {
+    /// How often held back updates are flushed, if the client doesn't get every update
+    pub fn flush_interval(&self) -> Option<Duration> {
+        match (self.keyframe_every, self.max_fps) {
+            (Some(secs), _) => Some(Duration::from_secs(secs.max(1))),
+            (None, Some(fps)) => Some(Duration::from_secs(1) / fps.max(1)),
+            (None, None) => None,
+        }
+    }
+}
+
+/// Color depth of the cells sent to a client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum ColorDepth {
+    /// Colors as the agent set them, including true color
+    #[default]
+    #[serde(rename = "full")]
+    Full,
+    /// True colors mapped onto the 256-color palette
+    #[serde(rename = "256")]
+    Palette256,
+    /// Everything mapped onto the 16 standard colors
+    #[serde(rename = "16")]
+    Basic16,
+    /// No colors at all; bold, reverse and the like are kept
+    #[serde(rename = "mono")]
+    Mono,
+}
+
+/// The 16 standard colors as xterm draws them
+const BASIC_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Reduce the colors of every cell in `update`
+    pub fn apply(self, update: &mut GridUpdateMessage) {
+        if self == ColorDepth::Full {
+            return;
+        }
+        match update {
+            GridUpdateMessage::Keyframe { cells, .. } => {
+                for (_, cell) in cells.iter_mut() {
+                    self.reduce_cell(cell);
+                }
+            }
+            GridUpdateMessage::Diff { changes, .. } => {
+                for (_, _, cell) in changes.iter_mut() {
+                    self.reduce_cell(cell);
+                }
+            }
+        }
+    }
+
+    fn reduce_cell(self, cell: &mut GridCell) {
+        cell.fg_color = cell.fg_color.take().and_then(|color| self.reduce(color));
+        cell.bg_color = cell.bg_color.take().and_then(|color| self.reduce(color));
+    }
+
+    fn reduce(self, color: TerminalColor) -> Option<TerminalColor> {
+        match (self, color) {
+            (ColorDepth::Full, color) | (_, color @ TerminalColor::Default) => Some(color),
+            (ColorDepth::Mono, _) => None,
+            (ColorDepth::Palette256, TerminalColor::Rgb { r, g, b }) => {
+                Some(TerminalColor::Palette(nearest_cube_index(r, g, b)))
+            }
+            (ColorDepth::Palette256, color) => Some(color),
+            (ColorDepth::Basic16, TerminalColor::Indexed(index)) => {
+                Some(TerminalColor::Indexed(index))
+            }
+            (ColorDepth::Basic16, TerminalColor::Palette(index)) if index < 16 => {
+                Some(TerminalColor::Indexed(index))
+            }
+            (ColorDepth::Basic16, TerminalColor::Palette(index)) => {
+                let (r, g, b) = palette_rgb(index);
+                Some(TerminalColor::Indexed(nearest_basic_index(r, g, b)))
+            }
+            (ColorDepth::Basic16, TerminalColor::Rgb { r, g, b }) => {
+                Some(TerminalColor::Indexed(nearest_basic_index(r, g, b)))
+            }
+        }
+    }
+}
+
+fn nearest_cube_level(value: u8) -> usize {
+    CUBE_LEVELS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, level)| (**level as i16 - value as i16).abs())
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+fn nearest_cube_index(r: u8, g: u8, b: u8) -> u8 {
+    (16 + 36 * nearest_cube_level(r) + 6 * nearest_cube_level(g) + nearest_cube_level(b)) as u8
+}
+
+/// RGB of a color of the 256-color palette
+fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => BASIC_COLORS[index as usize],
+        16..=231 => {
+            let cube = (index - 16) as usize;
+            (
+                CUBE_LEVELS[cube / 36],
+                CUBE_LEVELS[(cube / 6) % 6],
+                CUBE_LEVELS[cube % 6],
+            )
+        }
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_basic_index(r: u8, g: u8, b: u8) -> u8 {
+    let distance = |&(cr, cg, cb): &(u8, u8, u8)| {
+        let dr = cr as i32 - r as i32;
+        let dg = cg as i32 - g as i32;
+        let db = cb as i32 - b as i32;
+        dr * dr + dg * dg + db * db
+    };
+    BASIC_COLORS
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, color)| distance(color))
+        .map(|(index, _)| index as u8)
+        .unwrap_or(7)
+}
+
+/// Grid updates held back from a client that is sent fewer than the session produces
+///
+/// Updates are merged so that applying the result gives the same screen as
+/// applying all of them in order.
+#[derive(Debug, Default)]
+pub struct UpdateCoalescer {
+    pending: Option<GridUpdateMessage>,
+}
+
+impl UpdateCoalescer {
+    pub fn push(&mut self, update: GridUpdateMessage) {
+        self.pending = Some(match (self.pending.take(), update) {
+            (None, update) | (Some(_), update @ GridUpdateMessage::Keyframe { .. }) => update,
+            (
+                Some(GridUpdateMessage::Keyframe {
+                    size,
+                    cells,
+                    cursor,
+                    cursor_visible,
+                    scrollback_position,
+                    scrollback_total,
+                    ..
+                }),
+                GridUpdateMessage::Diff {
+                    changes,
+                    cursor: new_cursor,
+                    cursor_visible: new_cursor_visible,
+                    scrollback_position: new_scrollback_position,
+                    scrollback_total: new_scrollback_total,
+                    timestamp,
+                },
+            ) => {
+                let mut merged: HashMap<(u16, u16), GridCell> = cells.into_iter().collect();
+                for (row, col, cell) in changes {
+                    merged.insert((row, col), cell);
+                }
+                GridUpdateMessage::Keyframe {
+                    size,
+                    cells: merged.into_iter().collect(),
+                    cursor: new_cursor.unwrap_or(cursor),
+                    cursor_visible: new_cursor_visible.unwrap_or(cursor_visible),
+                    scrollback_position: new_scrollback_position.unwrap_or(scrollback_position),
+                    scrollback_total: new_scrollback_total.unwrap_or(scrollback_total),
+                    timestamp,
+                    runs: Vec::new(),
+                }
+            }
+            (
+                Some(GridUpdateMessage::Diff {
+                    changes,
+                    cursor,
+                    cursor_visible,
+                    scrollback_position,
+                    scrollback_total,
+                    ..
+                }),
+                GridUpdateMessage::Diff {
+                    changes: new_changes,
+                    cursor: new_cursor,
+                    cursor_visible: new_cursor_visible,
+                    scrollback_position: new_scrollback_position,
+                    scrollback_total: new_scrollback_total,
+                    timestamp,
+                },
+            ) => {
+                let mut merged: HashMap<(u16, u16), GridCell> = changes
+                    .into_iter()
+                    .map(|(row, col, cell)| ((row, col), cell))
+                    .collect();
+                for (row, col, cell) in new_changes {
+                    merged.insert((row, col), cell);
+                }
+                GridUpdateMessage::Diff {
+                    changes: merged
+                        .into_iter()
+                        .map(|((row, col), cell)| (row, col, cell))
+                        .collect(),
+                    cursor: new_cursor.or(cursor),
+                    cursor_visible: new_cursor_visible.or(cursor_visible),
+                    scrollback_position: new_scrollback_position.or(scrollback_position),
+                    scrollback_total: new_scrollback_total.or(scrollback_total),
+                    timestamp,
+                }
+            }
+        });
+    }
+
+    /// Whether anything changed since the last `take`
+    pub fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn take(&mut self) -> Option<GridUpdateMessage> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(char: &str, fg_color: Option<TerminalColor>) -> GridCell {
+        GridCell {
+            char: char.to_string(),
+            fg_color,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    fn diff(changes: Vec<(u16, u16, GridCell)>, cursor: Option<(u16, u16)>) -> GridUpdateMessage {
+        GridUpdateMessage::Diff {
+            changes,
+            cursor,
+            cursor_visible: None,
+            scrollback_position: None,
+            scrollback_total: None,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn merges_diffs_and_reduces_colors() {
+        let mut coalescer = UpdateCoalescer::default();
+        coalescer.push(diff(
+            vec![(0, 0, cell("a", None)), (0, 1, cell("b", None))],
+            Some((0, 2)),
+        ));
+        coalescer.push(diff(vec![(0, 1, cell("c", None))], None));
+
+        let Some(GridUpdateMessage::Diff {
+            mut changes,
+            cursor,
+            ..
+        }) = coalescer.take()
+        else {
+            panic!("expected a merged diff");
+        };
+        changes.sort_by_key(|(row, col, _)| (*row, *col));
+        let chars: Vec<&str> = changes
+            .iter()
+            .map(|(_, _, cell)| cell.char.as_str())
+            .collect();
+        assert_eq!(chars, vec!["a", "c"]);
+        assert_eq!(cursor, Some((0, 2)));
+        assert!(!coalescer.has_pending());
+
+        let mut update = diff(
+            vec![(
+                0,
+                0,
+                cell(
+                    "x",
+                    Some(TerminalColor::Rgb {
+                        r: 250,
+                        g: 10,
+                        b: 10,
+                    }),
+                ),
+            )],
+            None,
+        );
+        ColorDepth::Basic16.apply(&mut update);
+        let GridUpdateMessage::Diff { changes, .. } = &update else {
+            unreachable!()
+        };
+        assert_eq!(changes[0].2.fg_color, Some(TerminalColor::Indexed(9)));
+
+        ColorDepth::Mono.apply(&mut update);
+        let GridUpdateMessage::Diff { changes, .. } = &update else {
+            unreachable!()
+        };
+        assert_eq!(changes[0].2.fg_color, None);
+        assert_eq!(
+            ColorDepth::Palette256.reduce(TerminalColor::Rgb { r: 255, g: 0, b: 0 }),
+            Some(TerminalColor::Palette(196))
+        );
+    }
+}
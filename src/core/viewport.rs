@@ -0,0 +1,331 @@
+//! Clients of different sizes attached to one session
+//!
+//! A session has one PTY, but a phone and a wide terminal may watch it at the
+//! same time. The PTY takes the size of the client that asked to drive it, or
+//! else the most rows and columns any client reported; every client whose
+//! screen is smaller gets grid updates cropped to its own size. Cropping keeps
+//! the bottom rows, where agents keep their prompt, and the left columns.
+//! Clients that never report a size get the whole screen, and read-only
+//! clients only ever crop: their size doesn't count for the PTY's.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+use super::pty_session::{GridUpdateMessage, PtyControlMessage};
+
+/// The sizes clients of a session reported, shared by its connections
+#[derive(Clone, Default)]
+pub struct Viewports {
+    inner: Arc<Mutex<ViewportState>>,
+}
+
+#[derive(Default)]
+struct ViewportState {
+    /// (rows, cols) by client ID
+    sizes: HashMap<String, (u16, u16)>,
+    /// Client whose size the PTY takes while it's attached
+    driver: Option<String>,
+    /// Size last asked of the PTY
+    requested: Option<(u16, u16)>,
+}
+
+impl ViewportState {
+    /// The size the PTY should have, if any client reported one
+    fn pty_size(&self) -> Option<(u16, u16)> {
+        if let Some(size) = self.driver.as_ref().and_then(|id| self.sizes.get(id)) {
+            return Some(*size);
+        }
+        let rows = self.sizes.values().map(|(rows, _)| *rows).max()?;
+        let cols = self.sizes.values().map(|(_, cols)| *cols).max()?;
+        Some((rows, cols))
+    }
+
+    /// The PTY size to ask for, if it changed since it was last asked for
+    fn resize(&mut self) -> Option<(u16, u16)> {
+        let size = self.pty_size()?;
+        (self.requested != Some(size)).then(|| {
+            self.requested = Some(size);
+            size
+        })
+    }
+}
+
+impl Viewports {
+    /// Start tracking `client_id`; it counts until the guard is dropped
+    pub fn join(
+        &self,
+        client_id: &str,
+        control_tx: mpsc::UnboundedSender<PtyControlMessage>,
+    ) -> ViewportGuard {
+        ViewportGuard {
+            viewports: self.clone(),
+            client_id: client_id.to_string(),
+            control_tx: Some(control_tx),
+            size: None,
+            pty_size: None,
+        }
+    }
+
+    /// Track `client_id` without letting its size count for the PTY's
+    pub fn watch(&self, client_id: &str) -> ViewportGuard {
+        ViewportGuard {
+            viewports: self.clone(),
+            client_id: client_id.to_string(),
+            control_tx: None,
+            size: None,
+            pty_size: None,
+        }
+    }
+
+    /// The size the PTY should have for the attached clients
+    pub fn pty_size(&self) -> Option<(u16, u16)> {
+        self.inner.lock().unwrap().pty_size()
+    }
+}
+
+/// A client's place among a session's viewports, and what it sees of the screen
+pub struct ViewportGuard {
+    viewports: Viewports,
+    client_id: String,
+    /// `None` for read-only clients, which never resize the PTY
+    control_tx: Option<mpsc::UnboundedSender<PtyControlMessage>>,
+    /// (rows, cols) the client reported
+    size: Option<(u16, u16)>,
+    /// (rows, cols) of the PTY, from keyframes and size updates
+    pty_size: Option<(u16, u16)>,
+}
+
+impl ViewportGuard {
+    /// The client's screen is `rows` x `cols`; with `driver` the PTY takes
+    /// this size while the client is attached. True when the PTY is resized.
+    pub fn resize(&mut self, rows: u16, cols: u16, driver: bool) -> bool {
+        if rows == 0 || cols == 0 {
+            return false;
+        }
+        self.size = Some((rows, cols));
+        if self.control_tx.is_none() {
+            return false;
+        }
+        let size = {
+            let mut state = self.viewports.inner.lock().unwrap();
+            state.sizes.insert(self.client_id.clone(), (rows, cols));
+            if driver {
+                state.driver = Some(self.client_id.clone());
+            } else if state.driver.as_deref() == Some(self.client_id.as_str()) {
+                state.driver = None;
+            }
+            state.resize()
+        };
+        size.is_some_and(|size| self.resize_pty(size))
+    }
+
+    /// The PTY was resized to `rows` x `cols`
+    pub fn pty_resized(&mut self, rows: u16, cols: u16) {
+        self.pty_size = Some((rows, cols));
+    }
+
+    /// Whether the client sees less than the whole screen
+    pub fn crops(&self) -> bool {
+        match (self.size, self.pty_size) {
+            (Some((rows, cols)), Some((pty_rows, pty_cols))) => rows < pty_rows || cols < pty_cols,
+            _ => false,
+        }
+    }
+
+    /// The size of the screen as the client sees it
+    pub fn visible(&self, rows: u16, cols: u16) -> (u16, u16) {
+        match self.size {
+            Some((own_rows, own_cols)) => (rows.min(own_rows), cols.min(own_cols)),
+            None => (rows, cols),
+        }
+    }
+
+    /// Crop `update` to the client's screen
+    pub fn fit(&mut self, update: &mut GridUpdateMessage) {
+        if let GridUpdateMessage::Keyframe { size, .. } = update {
+            self.pty_size = Some((size.rows, size.cols));
+        }
+        let (Some((rows, cols)), Some((pty_rows, _))) = (self.size, self.pty_size) else {
+            return;
+        };
+        crop(update, pty_rows.saturating_sub(rows), rows, cols);
+    }
+
+    fn resize_pty(&self, (rows, cols): (u16, u16)) -> bool {
+        self.control_tx.as_ref().is_some_and(|control_tx| {
+            control_tx
+                .send(PtyControlMessage::Resize { rows, cols })
+                .is_ok()
+        })
+    }
+}
+
+impl Drop for ViewportGuard {
+    fn drop(&mut self) {
+        if self.control_tx.is_none() {
+            return;
+        }
+        let size = {
+            let mut state = self.viewports.inner.lock().unwrap();
+            state.sizes.remove(&self.client_id);
+            if state.driver.as_deref() == Some(self.client_id.as_str()) {
+                state.driver = None;
+            }
+            state.resize()
+        };
+        if let Some(size) = size {
+            self.resize_pty(size);
+        }
+    }
+}
+
+/// Keep the `rows` x `cols` of `update` from row `top` on, moved to the top left
+fn crop(update: &mut GridUpdateMessage, top: u16, rows: u16, cols: u16) {
+    let visible = |row: u16, col: u16| row >= top && row - top < rows && col < cols;
+    let cursor_in_view = |(row, col): (u16, u16)| {
+        (
+            row.saturating_sub(top).min(rows.saturating_sub(1)),
+            col.min(cols),
+        )
+    };
+    match update {
+        GridUpdateMessage::Keyframe {
+            size,
+            cells,
+            cursor,
+            ..
+        } => {
+            cells.retain(|((row, col), _)| visible(*row, *col));
+            for ((row, _), _) in cells.iter_mut() {
+                *row -= top;
+            }
+            size.rows = size.rows.min(rows);
+            size.cols = size.cols.min(cols);
+            *cursor = cursor_in_view(*cursor);
+        }
+        GridUpdateMessage::Diff {
+            changes, cursor, ..
+        } => {
+            changes.retain(|(row, col, _)| visible(*row, *col));
+            for (row, _, _) in changes.iter_mut() {
+                *row -= top;
+            }
+            *cursor = cursor.map(cursor_in_view);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::{GridCell, SerializablePtySize};
+
+    fn cell(char: &str) -> GridCell {
+        GridCell {
+            char: char.to_string(),
+            fg_color: None,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    fn resizes(control_rx: &mut mpsc::UnboundedReceiver<PtyControlMessage>) -> Vec<(u16, u16)> {
+        let mut sizes = Vec::new();
+        while let Ok(message) = control_rx.try_recv() {
+            if let PtyControlMessage::Resize { rows, cols } = message {
+                sizes.push((rows, cols));
+            }
+        }
+        sizes
+    }
+
+    #[test]
+    fn sizes_the_pty_for_the_largest_client_or_the_driver() {
+        let (control_tx, mut control_rx) = mpsc::unbounded_channel();
+        let viewports = Viewports::default();
+
+        let mut tui = viewports.join("tui", control_tx.clone());
+        assert!(tui.resize(50, 200, false));
+        let mut phone = viewports.join("phone", control_tx.clone());
+        // Already big enough for the phone
+        assert!(!phone.resize(40, 60, false));
+        assert_eq!(resizes(&mut control_rx), vec![(50, 200)]);
+
+        // Read-only clients only crop, even when asking to drive
+        let mut watcher = viewports.watch("watcher");
+        assert!(!watcher.resize(100, 300, true));
+        assert_eq!(viewports.pty_size(), Some((50, 200)));
+        drop(watcher);
+
+        assert!(phone.resize(40, 60, true));
+        assert_eq!(viewports.pty_size(), Some((40, 60)));
+        drop(phone);
+        drop(tui);
+        assert_eq!(resizes(&mut control_rx), vec![(40, 60), (50, 200)]);
+        assert_eq!(viewports.pty_size(), None);
+    }
+
+    #[test]
+    fn crops_updates_to_the_bottom_left_of_the_screen() {
+        let (control_tx, _control_rx) = mpsc::unbounded_channel();
+        let mut phone = Viewports::default().join("phone", control_tx);
+        phone.resize(2, 3, false);
+
+        let mut keyframe = GridUpdateMessage::Keyframe {
+            size: SerializablePtySize { rows: 4, cols: 6 },
+            cells: vec![
+                ((0, 0), cell("a")),
+                ((2, 1), cell("b")),
+                ((3, 2), cell("c")),
+                ((3, 5), cell("d")),
+            ],
+            cursor: (3, 5),
+            cursor_visible: true,
+            scrollback_position: 0,
+            scrollback_total: 0,
+            timestamp: std::time::SystemTime::now(),
+            runs: Vec::new(),
+        };
+        phone.fit(&mut keyframe);
+        assert!(phone.crops());
+        let GridUpdateMessage::Keyframe {
+            size,
+            cells,
+            cursor,
+            ..
+        } = &keyframe
+        else {
+            unreachable!()
+        };
+        assert_eq!((size.rows, size.cols), (2, 3));
+        let chars: Vec<_> = cells
+            .iter()
+            .map(|(at, cell)| (*at, cell.char.as_str()))
+            .collect();
+        assert_eq!(chars, vec![((0, 1), "b"), ((1, 2), "c")]);
+        assert_eq!(*cursor, (1, 3));
+
+        let mut diff = GridUpdateMessage::Diff {
+            changes: vec![(1, 0, cell("x")), (3, 0, cell("y"))],
+            cursor: Some((3, 1)),
+            cursor_visible: None,
+            scrollback_position: None,
+            scrollback_total: None,
+            timestamp: std::time::SystemTime::now(),
+        };
+        phone.fit(&mut diff);
+        let GridUpdateMessage::Diff {
+            changes, cursor, ..
+        } = &diff
+        else {
+            unreachable!()
+        };
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].0, changes[0].1), (1, 0));
+        assert_eq!(*cursor, Some((1, 1)));
+    }
+}
@@ -1,4 +1,6 @@
+use super::input_control::InputControl;
 use super::GridUpdateMessage;
+use crate::utils::PromptType;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 
@@ -7,18 +9,97 @@ use ts_rs::TS;
 #[serde(tag = "type")]
 #[ts(export)]
 pub enum ClientMessage {
+    /// `seq`, when given, is acknowledged with `input_ack` once written;
+    /// the same goes for `input` and `paste`
     #[serde(rename = "key")]
     Key {
         code: crate::core::pty_session::KeyCode,
         modifiers: crate::core::pty_session::KeyModifiers,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        seq: Option<u64>,
     },
+    /// The client's screen size; the PTY takes the largest size any client
+    /// reported, or with `driver` this client's, see `viewport`
     #[serde(rename = "resize")]
-    Resize { rows: u16, cols: u16 },
+    Resize {
+        rows: u16,
+        cols: u16,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        #[ts(as = "Option<bool>", optional)]
+        driver: bool,
+    },
     #[serde(rename = "scroll")]
     Scroll {
         direction: crate::core::pty_session::ScrollDirection,
         lines: u16,
     },
+    #[serde(rename = "input")]
+    Input {
+        data: Vec<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        seq: Option<u64>,
+    },
+    /// Pasted text, written to the agent as one bracketed paste when it
+    /// supports that; subject to the server's `[paste] max_bytes` limit
+    #[serde(rename = "paste")]
+    Paste {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional, type = "number")]
+        seq: Option<u64>,
+    },
+    /// Ask for write control of a session that isn't in `shared` input mode
+    #[serde(rename = "request_control")]
+    RequestControl,
+    /// Give write control back, or leave the queue for it
+    #[serde(rename = "release_control")]
+    ReleaseControl,
+}
+
+/// Control messages accepted as text frames on the raw PTY WebSocket
+///
+/// Binary frames on that socket are written to the PTY untouched.
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[serde(tag = "type")]
+#[ts(export)]
+pub enum RawClientMessage {
+    #[serde(rename = "input")]
+    Input { data: String },
+    #[serde(rename = "resize")]
+    Resize { rows: u16, cols: u16 },
+}
+
+/// Optional protocol features, asked for with `?capabilities=` on the terminal WebSocket
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
+pub enum ProtocolCapability {
+    /// Messages close together may share a frame, as a JSON array
+    Batch,
+    /// Grid updates and output come as MessagePack in binary frames, see `frame_encoding`
+    Msgpack,
+    /// Grid updates and output come in deflated binary frames, see `frame_encoding`
+    Deflate,
+    /// Keyframes list runs of identical cells instead of every cell, see `run_length`
+    Rle,
+}
+
+impl ProtocolCapability {
+    /// Capabilities in a comma-separated list; ones this server doesn't know are left out
+    pub fn parse_list(list: &str) -> Vec<Self> {
+        let mut capabilities = Vec::new();
+        for name in list.split(',').map(str::trim) {
+            let Ok(capability) = serde_json::from_value(serde_json::Value::from(name)) else {
+                continue;
+            };
+            if !capabilities.contains(&capability) {
+                capabilities.push(capability);
+            }
+        }
+        capabilities
+    }
 }
 
 /// Messages sent from server to client - flattened to match frontend expectations
@@ -41,4 +122,47 @@ pub enum ServerMessage {
     PtySize { rows: u16, cols: u16 },
     #[serde(rename = "error")]
     Error { message: String },
+    /// First message to a client that reconnected with `?rebind=true`; `restarted`
+    /// means the session wasn't running and was restored, e.g. after a server restart
+    #[serde(rename = "rebound")]
+    Rebound { restarted: bool },
+    /// Something everyone watching should see right away, e.g. a mass deletion
+    #[serde(rename = "alert")]
+    Alert { message: String },
+    /// Sent on connect and whenever write control changes hands; `client_id` is
+    /// this client's, as named in `holder` and `waiting`
+    #[serde(rename = "input_control")]
+    InputControl {
+        #[serde(flatten)]
+        control: InputControl,
+        client_id: String,
+        can_write: bool,
+    },
+    /// First message to a client that asked for capabilities, listing the ones
+    /// this server enabled
+    #[serde(rename = "capabilities")]
+    Capabilities {
+        capabilities: Vec<ProtocolCapability>,
+    },
+    /// Input this client sent with `seq` was written to the agent, or, when
+    /// `written` is false, refused or dropped
+    #[serde(rename = "input_ack")]
+    InputAck {
+        #[ts(type = "number")]
+        seq: u64,
+        written: bool,
+    },
+    /// Sent whenever input starts or stops waiting unusually long for the agent
+    #[serde(rename = "input_lag")]
+    InputLag { lagging: bool },
+    /// The agent waits for approval, e.g. a tool permission or a y/n
+    /// question; sent on connect too while one is on screen
+    #[serde(rename = "prompt_detected")]
+    PromptDetected { prompt: PromptType },
+    /// The approval prompt was answered or went away
+    #[serde(rename = "prompt_cleared")]
+    PromptCleared,
+    /// The agent exited with `code`; the server closes the connection after this
+    #[serde(rename = "exited")]
+    Exited { code: u32 },
 }
@@ -1,17 +1,21 @@
 // CodeMux Library
 // Terminal multiplexer for AI coding CLIs with server-client architecture
 
+#[cfg(feature = "web-ui")]
 pub mod assets;
+#[cfg(feature = "capture")]
 pub mod capture;
 pub mod cli;
 pub mod client;
 pub mod core;
+#[cfg(feature = "server")]
 pub mod server;
 pub mod utils;
 
 // Re-export commonly used types
 pub use client::http::CodeMuxClient;
 pub use core::{Config, ProjectAttributes, ProjectResource, SessionAttributes, SessionResource};
+#[cfg(feature = "server")]
 pub use server::SessionManagerHandle;
 
 // Error handling
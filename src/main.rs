@@ -2,21 +2,27 @@ use clap::Parser;
 use std::io::Write;
 use std::path::PathBuf;
 use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
 use codemux::cli::handlers::{self, RunSessionParams};
 use codemux::cli::{Cli, Commands};
+use codemux::core::listing::ListQuery;
 use codemux::utils::tui_writer::TuiWriter;
 use codemux::{Config, Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
-    let config = Config::load()?;
+    let mut config = Config::load()?;
+    if let Some(profile) = &cli.profile {
+        config.use_profile(profile)?;
+    }
 
     // Configure tracing differently for Claude/TUI mode vs other commands
     let log_rx = match &cli.command {
-        Commands::Claude { logfile, .. } => {
+        Commands::Claude { logfile, .. } | Commands::Run { logfile, .. } => {
             // For commands that use TUI, create TUI writer to capture logs
             let (tui_writer, log_rx) = TuiWriter::new();
 
@@ -101,8 +107,8 @@ async fn main() -> Result<()> {
 
             log_rx
         }
-        Commands::Attach { .. } => {
-            // For attach command (TUI mode but no logfile option)
+        Commands::Attach { .. } | Commands::Demo { agent: false, .. } | Commands::Replay { .. } => {
+            // For attach, demo and replay commands (TUI mode but no logfile option)
             let (tui_writer, log_rx) = TuiWriter::new();
 
             let env_filter = if std::env::var("RUST_LOG").is_ok() {
@@ -127,9 +133,12 @@ async fn main() -> Result<()> {
                 EnvFilter::from_default_env().add_directive("codemux=info".parse().unwrap())
             };
 
-            tracing_subscriber::fmt()
-                .with_writer(std::io::stderr)
-                .with_env_filter(env_filter)
+            // Spans of the session pipeline also go to OpenTelemetry when configured;
+            // `codemux server log-level` changes the filter of a running server
+            tracing_subscriber::registry()
+                .with(codemux::core::log_level::reloadable(env_filter))
+                .with(codemux::core::telemetry::otlp_layer(&config.telemetry)?)
+                .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
                 .init();
 
             // Create dummy channel for consistency
@@ -146,35 +155,218 @@ async fn main() -> Result<()> {
             resume_session,
             project,
             logfile,
+            present,
+            color,
+            icon,
+            name,
+            cwd,
+            record,
+            keep_alive,
             args,
         } => {
             handlers::run_client_session(RunSessionParams {
                 config,
-                agent: "claude".to_string(),
+                agent: Some("claude".to_string()),
                 open: *open,
                 continue_session: *continue_session,
                 resume_session: resume_session.clone(),
                 project: project.clone(),
                 logfile: logfile.clone(),
+                present: *present,
+                color: color.clone(),
+                icon: icon.clone(),
+                name: name.clone(),
+                cwd: cwd.clone(),
+                record: *record,
+                keep_alive: *keep_alive,
+                tmux: false,
                 args: args.clone(),
                 log_rx,
             })
             .await
         }
+        Commands::Run {
+            agent,
+            open,
+            continue_session,
+            resume_session,
+            project,
+            logfile,
+            present,
+            color,
+            icon,
+            name,
+            cwd,
+            record,
+            keep_alive,
+            tmux,
+            args,
+        } => {
+            handlers::run_client_session(RunSessionParams {
+                config,
+                agent: agent.clone(),
+                open: *open,
+                continue_session: *continue_session,
+                resume_session: resume_session.clone(),
+                project: project.clone(),
+                logfile: logfile.clone(),
+                present: *present,
+                color: color.clone(),
+                icon: icon.clone(),
+                name: name.clone(),
+                cwd: cwd.clone(),
+                record: *record,
+                keep_alive: *keep_alive,
+                tmux: *tmux,
+                args: args.clone(),
+                log_rx,
+            })
+            .await
+        }
+        Commands::ShellInit { shell, register } => {
+            print!(
+                "{}",
+                codemux::cli::shell_init::shell_init_script(*shell, *register)
+            );
+            Ok(())
+        }
+        Commands::Shim { command } => handlers::handle_shim_command(config, command.clone()),
         Commands::Server { command } => {
             handlers::handle_server_command(config, command.as_ref().cloned()).await
         }
-        Commands::Attach { session_id } => {
-            handlers::attach_to_session(config, session_id.clone(), log_rx).await
+        Commands::Attach {
+            session_id,
+            raw,
+            read_only,
+        } => {
+            handlers::attach_to_session(config, session_id.clone(), *raw, *read_only, log_rx).await
+        }
+        Commands::Tmux { command } => handlers::handle_tmux_command(config, command.clone()).await,
+        Commands::Share {
+            session_id,
+            ttl,
+            interactive,
+        } => handlers::share_session(config, session_id, *ttl, *interactive).await,
+        Commands::Demo {
+            open,
+            present,
+            recording,
+            speed,
+            agent,
+            replay,
+        } => {
+            if *agent {
+                #[cfg(feature = "capture")]
+                let result = match (recording, replay) {
+                    (Some(recording), true) => {
+                        codemux::capture::player::run_replay_agent(recording, *speed)
+                    }
+                    _ => codemux::capture::demo::run_demo_agent(recording.as_deref(), *speed),
+                };
+                #[cfg(not(feature = "capture"))]
+                let result = Err(anyhow::anyhow!(
+                    "The demo agent needs the `capture` feature, which this build was compiled without"
+                ));
+                result
+            } else {
+                handlers::run_demo(config, *open, *present, recording.clone(), *speed, log_rx).await
+            }
         }
-        Commands::KillSession { session_id } => {
-            handlers::kill_session(config, session_id.clone()).await
+        Commands::Replay {
+            recording,
+            open,
+            speed,
+        } => handlers::run_replay(config, recording.clone(), *open, *speed, log_rx).await,
+        Commands::Focus {
+            project,
+            agent,
+            no_open,
+        } => handlers::focus_project(config, project.clone(), agent.clone(), *no_open).await,
+        Commands::Watch {
+            session_id,
+            accessible,
+        } => handlers::watch_session(config, session_id.clone(), *accessible).await,
+        Commands::Adopt { pid, scan } => handlers::adopt_process(config, *pid, *scan).await,
+        Commands::KillSession { session_id, now } => {
+            handlers::kill_session(config, session_id.clone(), *now).await
+        }
+        Commands::UndoKill { session_id } => handlers::undo_kill(config, session_id.clone()).await,
+        Commands::Export {
+            session_id,
+            format,
+            output,
+        } => {
+            handlers::export_conversation(config, session_id.clone(), *format, output.clone()).await
         }
-        Commands::AddProject { path, name } => {
-            handlers::add_project(config, path.clone(), name.clone()).await
+        Commands::ExportScrollback {
+            session_id,
+            lines,
+            output,
+        } => handlers::export_scrollback(config, session_id.clone(), *lines, output.clone()).await,
+        Commands::AddProject { path, name, parent } => {
+            handlers::add_project(config, path.clone(), name.clone(), parent.clone()).await
+        }
+        Commands::Project { command } => {
+            handlers::handle_project_command(config, command.clone()).await
+        }
+        Commands::RemoveProject {
+            project,
+            kill_sessions,
+        } => handlers::remove_project(config, project.clone(), *kill_sessions).await,
+        Commands::RenameProject { project, name } => {
+            handlers::rename_project(config, project.clone(), name.clone()).await
+        }
+        Commands::Scan {
+            dir,
+            max_depth,
+            dry_run,
+        } => handlers::scan_projects(config, dir.clone(), *max_depth, *dry_run).await,
+        Commands::List {
+            agent,
+            status,
+            project,
+            page_size,
+            page,
+        } => {
+            let query = ListQuery {
+                page_size: *page_size,
+                page_number: *page,
+                agent: agent.clone(),
+                status: status.clone(),
+                project: project.clone(),
+                sort: None,
+            };
+            handlers::list_sessions(config, query).await
         }
-        Commands::List => handlers::list_sessions(config).await,
         Commands::ListProjects => handlers::list_projects(config).await,
+        Commands::Recent => handlers::list_recent(config).await,
+        Commands::Dnd { command } => handlers::handle_dnd_command(config, command.clone()).await,
+        Commands::AgentConfig { command } => {
+            handlers::handle_agent_config_command(config, command.clone())
+        }
+        Commands::Digest { hours } => handlers::print_digest(config, *hours).await,
+        Commands::Usage { session_id } => handlers::print_usage(config, session_id.clone()).await,
+        Commands::Search { query, limit } => {
+            handlers::search_sessions(config, query.join(" "), *limit).await
+        }
+        Commands::Recordings { command } => {
+            handlers::handle_recordings_command(config, command.clone())
+        }
+        Commands::Checkpoints { command } => {
+            handlers::handle_checkpoints_command(config, command.clone()).await
+        }
+        Commands::Archive { project, out } => {
+            handlers::archive_project(config, project.clone(), out.clone()).await
+        }
+        Commands::Login { server, with_token } => {
+            handlers::login(config, server.clone(), *with_token).await
+        }
+        Commands::Logout { server } => handlers::logout(config, server.clone()),
+        Commands::Token { command } => {
+            handlers::handle_token_command(config, command.clone()).await
+        }
         Commands::Stop => handlers::stop_server(config).await,
+        Commands::Doctor => handlers::doctor(config).await,
+        Commands::Dev { command } => handlers::handle_dev_command(command.clone()),
     }
 }
@@ -0,0 +1,334 @@
+//! Everything codemux kept about a project, in one `.tar.zst` file
+//!
+//! `codemux archive` and `GET /api/projects/:id/archive` bundle the project's
+//! session history, its agents' transcripts, the recordings of its sessions
+//! and its pinned git checkpoints, for retention or for handing the project
+//! over to a teammate. `manifest.json` says what is inside, with a SHA-256 of
+//! every file.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::manager::SessionManagerHandle;
+use super::storage::SessionRecord;
+use crate::core::agent_adapter::AgentAdapter;
+use crate::core::{checkpoint, project_groups};
+
+/// Version of the archive's layout, raised when it changes
+pub const ARCHIVE_FORMAT: u32 = 1;
+/// Name of the file describing the archive, first in it
+pub const MANIFEST_NAME: &str = "manifest.json";
+/// Name of the git bundle holding the pinned checkpoints
+pub const CHECKPOINTS_NAME: &str = "checkpoints.bundle";
+
+/// Contents of `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub format: u32,
+    pub codemux_version: String,
+    /// RFC 3339
+    pub created_at: String,
+    pub project: ArchivedProject,
+    /// The project's sessions from the history, newest first
+    pub sessions: Vec<SessionRecord>,
+    pub files: Vec<ArchivedFile>,
+    /// Pinned checkpoints, all in `checkpoints.bundle`
+    pub checkpoints: Vec<ArchivedCheckpoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedProject {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchivedFileKind {
+    Transcript,
+    Recording,
+    Checkpoints,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// Path inside the archive, below its top directory
+    pub path: String,
+    pub kind: ArchivedFileKind,
+    /// Agent a transcript is from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// Session a recording is of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub bytes: u64,
+    /// Hex SHA-256 of the contents
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedCheckpoint {
+    pub reference: String,
+    pub commit: String,
+}
+
+/// A finished archive
+pub struct ProjectArchive {
+    /// Suggested file name, `<project>.tar.zst`
+    pub file_name: String,
+    pub manifest: ArchiveManifest,
+    /// The compressed tarball
+    pub data: Vec<u8>,
+}
+
+/// Archive the project with this ID or name; `None` if there is no such project
+pub async fn build(
+    manager: &SessionManagerHandle,
+    project: &str,
+) -> Result<Option<ProjectArchive>> {
+    let projects = manager.list_projects().await;
+    let Some((id, attributes)) = project_groups::find_project(&projects, project)
+        .and_then(|project| Some((project.id.clone(), project.attributes.clone()?)))
+    else {
+        return Ok(None);
+    };
+    let project = ArchivedProject {
+        id,
+        name: attributes.name,
+        path: attributes.path,
+        remote: attributes.remote,
+    };
+    let sessions: Vec<SessionRecord> = manager
+        .storage()
+        .load_sessions()
+        .await?
+        .into_iter()
+        .filter(|session| session.project.as_deref() == Some(project.id.as_str()))
+        .collect();
+
+    let mut agents: Vec<String> = sessions.iter().map(|s| s.agent.clone()).collect();
+    agents.sort();
+    agents.dedup();
+    let adapters = agents
+        .into_iter()
+        .filter_map(|agent| Some((manager.agent_adapter(&agent)?, agent)))
+        .collect();
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()));
+    let recordings_dir = manager.recordings_dir().to_path_buf();
+    let archive = tokio::task::spawn_blocking(move || {
+        write_archive(project, sessions, adapters, &home, &recordings_dir)
+    })
+    .await??;
+    Ok(Some(archive))
+}
+
+fn write_archive(
+    project: ArchivedProject,
+    sessions: Vec<SessionRecord>,
+    adapters: Vec<(Box<dyn AgentAdapter>, String)>,
+    home: &Path,
+    recordings_dir: &Path,
+) -> Result<ProjectArchive> {
+    let project_dir = PathBuf::from(&project.path);
+    let mut files = Vec::new();
+    let mut add = |path: String, kind, agent: Option<&str>, session_id: Option<&str>, data| {
+        let file = ArchivedFile {
+            path,
+            kind,
+            agent: agent.map(str::to_string),
+            session_id: session_id.map(str::to_string),
+            bytes: Vec::len(&data) as u64,
+            sha256: format!("{:x}", Sha256::digest(&data)),
+        };
+        files.push((file, data));
+    };
+
+    for (adapter, agent) in &adapters {
+        for transcript in adapter.transcripts(home, &project_dir) {
+            let Some(name) = transcript.file_name() else {
+                continue;
+            };
+            // The agent may have cleaned it up since it was listed
+            let Ok(data) = std::fs::read(&transcript) else {
+                tracing::warn!("Could not read transcript {}", transcript.display());
+                continue;
+            };
+            let path = format!("transcripts/{}/{}", agent, name.to_string_lossy());
+            add(path, ArchivedFileKind::Transcript, Some(agent), None, data);
+        }
+    }
+    for session in &sessions {
+        let recording = recordings_dir.join(format!("{}.jsonl", session.id));
+        if let Ok(data) = std::fs::read(recording) {
+            let path = format!("recordings/{}.jsonl", session.id);
+            let kind = ArchivedFileKind::Recording;
+            add(path, kind, None, Some(&session.id), data);
+        }
+    }
+
+    let checkpoints: Vec<ArchivedCheckpoint> = checkpoint::pinned(&project_dir)
+        .into_iter()
+        .map(|(reference, commit)| ArchivedCheckpoint { reference, commit })
+        .collect();
+    if !checkpoints.is_empty() {
+        let bundle = std::env::temp_dir().join(format!(
+            "codemux-checkpoints-{}.bundle",
+            uuid::Uuid::new_v4()
+        ));
+        let data =
+            checkpoint::bundle(&project_dir, &bundle).and_then(|()| Ok(std::fs::read(&bundle)?));
+        let _ = std::fs::remove_file(&bundle);
+        let kind = ArchivedFileKind::Checkpoints;
+        add(CHECKPOINTS_NAME.to_string(), kind, None, None, data?);
+    }
+
+    let now = chrono::Utc::now();
+    let root = top_directory(&project.name);
+    let manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT,
+        codemux_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        project,
+        sessions,
+        files: files.iter().map(|(file, _)| file.clone()).collect(),
+        checkpoints,
+    };
+
+    let encoder = zstd::Encoder::new(Vec::new(), zstd::DEFAULT_COMPRESSION_LEVEL)?;
+    let mut tar = tar::Builder::new(encoder);
+    let mtime = now.timestamp().max(0) as u64;
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    append(
+        &mut tar,
+        &format!("{}/{}", root, MANIFEST_NAME),
+        &manifest_json,
+        mtime,
+    )?;
+    for (file, data) in &files {
+        append(&mut tar, &format!("{}/{}", root, file.path), data, mtime)?;
+    }
+    let data = tar.into_inner()?.finish()?;
+
+    Ok(ProjectArchive {
+        file_name: format!("{}.tar.zst", root),
+        manifest,
+        data,
+    })
+}
+
+fn append<W: Write>(tar: &mut tar::Builder<W>, path: &str, data: &[u8], mtime: u64) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    tar.append_data(&mut header, path, data)?;
+    Ok(())
+}
+
+/// Directory everything in the archive is under, named after the project
+fn top_directory(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '-',
+        })
+        .collect();
+    match name.trim_matches('.') {
+        "" => "project".to_string(),
+        name => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::process::Command;
+
+    #[test]
+    fn archives_transcripts_recordings_and_checkpoints() {
+        let root = std::env::temp_dir().join(format!("codemux-archive-{}", std::process::id()));
+        let project_dir = root.join("shop");
+        std::fs::create_dir_all(&project_dir).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&project_dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q"]);
+        git(&["commit", "-q", "--allow-empty", "-m", "start"]);
+        git(&[
+            "update-ref",
+            "refs/codemux/checkpoints/before-delete",
+            "HEAD",
+        ]);
+
+        let session = SessionRecord {
+            id: "s1".to_string(),
+            agent: "claude".to_string(),
+            project: Some("p1".to_string()),
+            color: "#000000".to_string(),
+            icon: "🤖".to_string(),
+            created_at: "2025-09-02T14:30:00Z".to_string(),
+            ended_at: None,
+            exited: false,
+            exit_code: None,
+            links: Vec::new(),
+            cwd: None,
+        };
+        let recordings_dir = root.join("recordings");
+        std::fs::create_dir_all(&recordings_dir).unwrap();
+        std::fs::write(recordings_dir.join("s1.jsonl"), "{}\n").unwrap();
+        let adapter = crate::core::agent_adapter::adapter_for("claude", None).unwrap();
+        let home = root.join("home");
+        let transcripts = adapter.transcript_dir(&home, &project_dir);
+        std::fs::create_dir_all(&transcripts).unwrap();
+        std::fs::write(transcripts.join("s1.jsonl"), "{\"type\":\"user\"}\n").unwrap();
+
+        let project = ArchivedProject {
+            id: "p1".to_string(),
+            name: "shop".to_string(),
+            path: project_dir.display().to_string(),
+            remote: None,
+        };
+        let adapters = vec![(adapter, "claude".to_string())];
+        let archive =
+            write_archive(project, vec![session], adapters, &home, &recordings_dir).unwrap();
+        assert_eq!(archive.file_name, "shop.tar.zst");
+        assert_eq!(archive.manifest.checkpoints.len(), 1);
+
+        let mut tar = tar::Archive::new(zstd::Decoder::new(&archive.data[..]).unwrap());
+        let mut entries = Vec::new();
+        for entry in tar.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            entries.push((entry.path().unwrap().display().to_string(), data));
+        }
+        let paths: Vec<&str> = entries.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "shop/manifest.json",
+                "shop/transcripts/claude/s1.jsonl",
+                "shop/recordings/s1.jsonl",
+                "shop/checkpoints.bundle",
+            ]
+        );
+        for (file, (_, data)) in archive.manifest.files.iter().zip(&entries[1..]) {
+            assert_eq!(file.sha256, format!("{:x}", Sha256::digest(data)));
+        }
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,184 @@
+//! Git checkpoints after each agent turn
+//!
+//! With `[checkpoints]` on, the manager watches each session's screen and,
+//! once the agent is done with a turn, snapshots the working tree and pins it
+//! as `refs/codemux/checkpoints/<session-id>/turn-<n>`. Turns that changed no
+//! files get no checkpoint. When an agent wrecks files, `codemux checkpoints
+//! restore` puts the tree back the way it was after an earlier turn.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use super::manager::record_notification;
+use super::storage::Storage;
+use crate::core::checkpoint::{self, CheckpointsConfig, TurnTracker};
+use crate::core::notifications::{Notification, NotificationKind, NotificationsConfig};
+use crate::core::pty_session::PtyChannels;
+
+/// How often a quiet screen is checked for the end of a turn
+const TICK: Duration = Duration::from_secs(1);
+/// Name prefix of turn checkpoints, to tell them from the deletion guard's
+const TURN_PREFIX: &str = "turn-";
+
+/// A session whose turns are checkpointed
+pub struct CheckpointedSession {
+    pub session_id: String,
+    pub working_dir: PathBuf,
+    /// Project name, for the `[notifications]` project rules
+    pub project: Option<String>,
+}
+
+/// Checkpoint the session's working tree after each turn until the session ends
+pub fn spawn(
+    session: CheckpointedSession,
+    config: CheckpointsConfig,
+    rules: NotificationsConfig,
+    storage: Arc<dyn Storage>,
+    channels: &PtyChannels,
+) {
+    let mut grid_rx = channels.grid_tx.subscribe();
+    let mut prompt_rx = channels.prompt_tx.subscribe();
+    tokio::spawn(async move {
+        let mut turns = TurnTracker::new(config.settle());
+        let mut timer = tokio::time::interval(TICK);
+        loop {
+            let ended = tokio::select! {
+                update = grid_rx.recv() => match update {
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                        turns.output(Instant::now());
+                        false
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                // The agent stops for approval before it touches anything,
+                // which makes a good point to go back to
+                Ok(()) = prompt_rx.changed() => {
+                    prompt_rx.borrow_and_update().is_some() && turns.interrupt()
+                }
+                _ = timer.tick() => turns.settled(Instant::now()),
+            };
+            if ended {
+                checkpoint_turn(&session, &config, &rules, storage.as_ref()).await;
+            }
+        }
+        // The agent's last words before it exited
+        if turns.interrupt() {
+            checkpoint_turn(&session, &config, &rules, storage.as_ref()).await;
+        }
+        tracing::debug!("Checkpoints for session {} stopped", session.session_id);
+    });
+}
+
+async fn checkpoint_turn(
+    session: &CheckpointedSession,
+    config: &CheckpointsConfig,
+    rules: &NotificationsConfig,
+    storage: &dyn Storage,
+) {
+    let saved = {
+        let dir = session.working_dir.clone();
+        let session_id = session.session_id.clone();
+        let keep = config.keep;
+        tokio::task::spawn_blocking(move || save_turn(&dir, &session_id, keep)).await
+    };
+    let name = match saved {
+        Ok(Ok(Some(name))) => name,
+        Ok(Ok(None)) => return,
+        Ok(Err(e)) => {
+            tracing::warn!("Session {}: checkpoint failed: {}", session.session_id, e);
+            return;
+        }
+        Err(e) => {
+            tracing::warn!("Session {}: checkpoint panicked: {}", session.session_id, e);
+            return;
+        }
+    };
+    tracing::debug!("Session {}: checkpoint {}", session.session_id, name);
+    let message = format!(
+        "Checkpoint {} saved; `codemux checkpoints restore {} {}` brings the files back",
+        name, session.session_id, name
+    );
+    let notification = Notification::new(
+        NotificationKind::CheckpointCreated,
+        &session.session_id,
+        message,
+    );
+    if let Err(e) =
+        record_notification(storage, rules, session.project.as_deref(), notification).await
+    {
+        tracing::warn!("Failed to store checkpoint notification: {}", e);
+    }
+}
+
+/// Snapshot the working tree as the session's next turn checkpoint, and drop
+/// the oldest beyond `keep`; returns its name, or `None` when nothing changed
+/// since the last one or `dir` isn't in a git repository
+fn save_turn(dir: &Path, session_id: &str, keep: usize) -> Result<Option<String>> {
+    let turns: Vec<_> = checkpoint::session_checkpoints(dir, session_id)
+        .into_iter()
+        .filter_map(|checkpoint| {
+            let turn: u32 = checkpoint.name.strip_prefix(TURN_PREFIX)?.parse().ok()?;
+            Some((turn, checkpoint))
+        })
+        .collect();
+    let last = turns.iter().max_by_key(|(turn, _)| *turn);
+    let next = last.map_or(1, |(turn, _)| turn + 1);
+
+    let message = format!("codemux: {} turn {}", session_id, next);
+    let Some(commit) = checkpoint::snapshot(dir, &message)? else {
+        return Ok(None);
+    };
+    if last.is_some_and(|(_, last)| checkpoint::same_tree(dir, &last.commit, &commit)) {
+        return Ok(None);
+    }
+    let name = format!("{}{}", TURN_PREFIX, next);
+    checkpoint::pin(dir, &format!("{}/{}", session_id, name), &commit)?;
+
+    let mut turns = turns;
+    turns.sort_by_key(|(turn, _)| *turn);
+    // The one just pinned isn't in `turns`
+    let excess = (turns.len() + 1).saturating_sub(keep.max(1));
+    for (_, old) in turns.iter().take(excess) {
+        checkpoint::unpin(dir, &old.reference)?;
+    }
+    Ok(Some(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_turns_that_changed_files_and_keeps_the_latest() {
+        let dir = &std::env::temp_dir().join(format!("codemux-turn-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+        assert_eq!(save_turn(dir, "s1", 2).unwrap(), None);
+
+        let status = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::write(dir.join("a.txt"), "1").unwrap();
+        assert_eq!(save_turn(dir, "s1", 2).unwrap().as_deref(), Some("turn-1"));
+        assert_eq!(save_turn(dir, "s1", 2).unwrap(), None);
+
+        std::fs::write(dir.join("a.txt"), "2").unwrap();
+        assert_eq!(save_turn(dir, "s1", 2).unwrap().as_deref(), Some("turn-2"));
+        std::fs::write(dir.join("a.txt"), "3").unwrap();
+        assert_eq!(save_turn(dir, "s1", 2).unwrap().as_deref(), Some("turn-3"));
+
+        let names: Vec<_> = checkpoint::session_checkpoints(dir, "s1")
+            .into_iter()
+            .map(|checkpoint| checkpoint.name)
+            .collect();
+        assert_eq!(names, ["turn-2", "turn-3"]);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
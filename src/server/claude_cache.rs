@@ -8,6 +8,8 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, error, info, warn};
 
+use crate::core::transcript::{lookup, text_of};
+
 /// Convert Claude's encoded project path to actual filesystem path
 /// e.g., "-Users-cinoss-Code-playground-mojo" -> Some("/Users/cinoss/Code/playground/mojo")  
 /// e.g., "-a-b.c-d" -> Some("/a/b.c/d") (using filesystem search with glob patterns)
@@ -105,6 +107,9 @@ pub struct CachedSession {
     pub file_size: u64,
     pub is_active: bool,
     pub last_message: Option<String>,
+    /// First thing the user asked, shortened
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
 /// Events for cache updates
@@ -263,6 +268,10 @@ impl ClaudeProjectsCache {
 
         // Extract the last message from the file
         let last_message = self.get_last_message_from_file(file_path).await;
+        let title = tokio::fs::read_to_string(file_path)
+            .await
+            .ok()
+            .and_then(|content| conversation_title(&content));
 
         // Check if the session is currently active (recently modified)
         let is_active = {
@@ -280,6 +289,7 @@ impl ClaudeProjectsCache {
             file_size,
             is_active,
             last_message,
+            title,
         })
     }
 
@@ -407,6 +417,26 @@ impl ClaudeProjectsCache {
     }
 }
 
+/// Longest conversation title, in characters
+const TITLE_CHARS: usize = 80;
+
+/// First line of the first thing the user typed in a Claude transcript;
+/// tool results and slash commands such as `<command-name>/clear` don't count
+fn conversation_title(content: &str) -> Option<String> {
+    let text = content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|entry| entry["type"] == "user" && entry["isMeta"] != true)
+        .filter_map(|entry| Some(text_of(lookup(&entry, "message.content")?)))
+        .find(|text| !text.trim().is_empty() && !text.trim_start().starts_with('<'))?;
+    let line = text.trim().lines().next().unwrap_or_default();
+    let mut title: String = line.chars().take(TITLE_CHARS).collect();
+    if title.len() < line.len() {
+        title.push('…');
+    }
+    Some(title)
+}
+
 /// Handle file system events
 async fn handle_fs_event(
     event: Event,
@@ -457,6 +487,17 @@ async fn handle_fs_event(
 
                             // Check if this is a new or modified session
                             let is_new = !sessions_guard.contains_key(&session_id);
+                            // The first message doesn't change once it's written
+                            let title = match sessions_guard
+                                .get(&session_id)
+                                .and_then(|session| session.title.clone())
+                            {
+                                Some(title) => Some(title),
+                                None => tokio::fs::read_to_string(&path)
+                                    .await
+                                    .ok()
+                                    .and_then(|content| conversation_title(&content)),
+                            };
 
                             let session = CachedSession {
                                 session_id: session_id.clone(),
@@ -467,6 +508,7 @@ async fn handle_fs_event(
                                 file_size,
                                 is_active: true,    // Just modified, so it's active
                                 last_message: None, // TODO: Could be extracted for real-time updates
+                                title,
                             };
 
                             sessions_guard.insert(session_id.clone(), session.clone());
@@ -510,3 +552,33 @@ async fn handle_fs_event(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn titles_come_from_the_first_typed_message() {
+        let transcript = [
+            r#"{"type":"summary","summary":"Login fixes"}"#,
+            r#"{"type":"user","isMeta":true,"message":{"role":"user","content":"Caveat: ..."}}"#,
+            r#"{"type":"user","message":{"role":"user","content":"<command-name>/clear</command-name>"}}"#,
+            r#"{"type":"user","message":{"role":"user","content":[{"type":"text","text":"fix the login form\nit rejects valid emails"}]}}"#,
+            r#"{"type":"assistant","message":{"role":"assistant","content":"On it."}}"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            conversation_title(&transcript).as_deref(),
+            Some("fix the login form")
+        );
+
+        let long = format!(
+            r#"{{"type":"user","message":{{"role":"user","content":"{}"}}}}"#,
+            "a".repeat(100)
+        );
+        let title = conversation_title(&long).unwrap();
+        assert_eq!(title.chars().count(), TITLE_CHARS + 1);
+        assert!(title.ends_with('…'));
+        assert_eq!(conversation_title(""), None);
+    }
+}
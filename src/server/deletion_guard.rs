@@ -0,0 +1,201 @@
+//! Safety checkpoints for mass deletions
+//!
+//! With `[deletion_guard]` on, the manager watches each session's working tree
+//! and keeps a snapshot of it from the last time things were calm. When files
+//! start disappearing faster than the configured rate, that snapshot is pinned
+//! as a checkpoint and everyone watching the session is alerted, so an
+//! overeager `rm -rf` can be undone with one git command.
+
+use anyhow::Result;
+use notify::event::{CreateKind, RemoveKind};
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc};
+
+use super::manager::record_notification;
+use super::storage::Storage;
+use crate::core::checkpoint;
+use crate::core::deletion_guard::{DeletionBurst, DeletionGuardConfig};
+use crate::core::notifications::{Notification, NotificationKind, NotificationsConfig};
+use crate::core::pty_session::PtyChannels;
+
+/// How long the tree has to be left alone before the snapshot is refreshed
+const QUIET_PERIOD: Duration = Duration::from_secs(10);
+/// Snapshots are cheap but not free on big trees
+const MIN_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where a guarded session's alerts go
+pub struct GuardedSession {
+    pub session_id: String,
+    pub working_dir: PathBuf,
+    /// Project name, for the `[notifications]` project rules
+    pub project: Option<String>,
+}
+
+/// Watch the session's working tree until the session ends
+pub fn spawn(
+    session: GuardedSession,
+    config: DeletionGuardConfig,
+    rules: NotificationsConfig,
+    storage: Arc<dyn Storage>,
+    channels: &PtyChannels,
+) -> Result<()> {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<Event, notify::Error>| match res {
+            Ok(event) => {
+                let _ = event_tx.send(event);
+            }
+            Err(e) => tracing::warn!("Deletion guard watcher error: {:?}", e),
+        },
+        Config::default(),
+    )?;
+    watch_tree(&mut watcher, &session.working_dir, &config)?;
+
+    let alert_tx = channels.alert_tx.clone();
+    // The size channel closes with the session, and is quiet until then
+    let mut size_rx = channels.size_tx.subscribe();
+    tokio::spawn(async move {
+        let dir = session.working_dir.clone();
+        let mut burst = DeletionBurst::new(&config);
+        let mut baseline = take_snapshot(&dir, "codemux: baseline").await;
+        let mut baseline_at = Instant::now();
+        let mut last_change: Option<Instant> = None;
+        let mut timer = tokio::time::interval(QUIET_PERIOD);
+
+        loop {
+            tokio::select! {
+                event = event_rx.recv() => {
+                    let Some(event) = event else { break };
+                    let paths: Vec<&PathBuf> = event
+                        .paths
+                        .iter()
+                        .filter(|path| {
+                            path.strip_prefix(&dir)
+                                .is_ok_and(|relative| !config.ignores(relative))
+                        })
+                        .collect();
+                    if paths.is_empty() {
+                        continue;
+                    }
+                    let now = Instant::now();
+                    last_change = Some(now);
+                    match event.kind {
+                        EventKind::Create(CreateKind::Folder | CreateKind::Any) => {
+                            // Top-level directories are watched one by one, see `watch_tree`
+                            for path in paths {
+                                if path.parent() == Some(dir.as_path()) && path.is_dir() {
+                                    let _ = watcher.watch(path, RecursiveMode::Recursive);
+                                }
+                            }
+                        }
+                        EventKind::Remove(RemoveKind::File | RemoveKind::Any) => {
+                            if let Some(count) = burst.record(now, paths.len()) {
+                                let message = safety_checkpoint(&session, &config, count, baseline.as_deref()).await;
+                                tracing::warn!("Session {}: {}", session.session_id, message);
+                                let _ = alert_tx.send(message.clone());
+                                let notification = Notification::new(NotificationKind::MassDeletion, &session.session_id, message);
+                                if let Err(e) = record_notification(storage.as_ref(), &rules, session.project.as_deref(), notification).await {
+                                    tracing::warn!("Failed to store mass deletion notification: {}", e);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                _ = timer.tick() => {
+                    let settled = last_change.is_some_and(|at| at.elapsed() >= QUIET_PERIOD);
+                    if settled
+                        && baseline_at.elapsed() >= MIN_SNAPSHOT_INTERVAL
+                        && !burst.in_progress(Instant::now())
+                    {
+                        baseline = take_snapshot(&dir, "codemux: baseline").await;
+                        baseline_at = Instant::now();
+                        last_change = None;
+                    }
+                }
+                size = size_rx.recv() => {
+                    if matches!(size, Err(broadcast::error::RecvError::Closed)) {
+                        break;
+                    }
+                }
+            }
+        }
+        tracing::debug!("Deletion guard for session {} stopped", session.session_id);
+    });
+    Ok(())
+}
+
+/// Watch everything below `dir` except ignored top-level directories, so
+/// e.g. `node_modules` doesn't use up the system's file watches
+fn watch_tree(
+    watcher: &mut RecommendedWatcher,
+    dir: &Path,
+    config: &DeletionGuardConfig,
+) -> Result<()> {
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.is_dir() && !config.ignores(Path::new(&entry.file_name())) {
+            if let Err(e) = watcher.watch(&path, RecursiveMode::Recursive) {
+                tracing::warn!("Deletion guard can't watch {}: {}", path.display(), e);
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn take_snapshot(dir: &Path, message: &'static str) -> Option<String> {
+    let dir = dir.to_path_buf();
+    match tokio::task::spawn_blocking(move || checkpoint::snapshot(&dir, message)).await {
+        Ok(Ok(commit)) => commit,
+        Ok(Err(e)) => {
+            tracing::warn!("Deletion guard snapshot failed: {}", e);
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+/// Pin the snapshot from before the burst and describe how to get it back
+async fn safety_checkpoint(
+    session: &GuardedSession,
+    config: &DeletionGuardConfig,
+    count: usize,
+    baseline: Option<&str>,
+) -> String {
+    let what = format!(
+        "At least {} files deleted within {}s",
+        count, config.window_secs
+    );
+    let Some(commit) = baseline.map(str::to_string) else {
+        return format!(
+            "{}. There is no checkpoint to go back to, {} is not in a git repository",
+            what,
+            session.working_dir.display()
+        );
+    };
+    let dir = session.working_dir.clone();
+    let name = format!(
+        "{}/safety-{}",
+        session.session_id,
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let pinned = {
+        let commit = commit.clone();
+        tokio::task::spawn_blocking(move || checkpoint::pin(&dir, &name, &commit)).await
+    };
+    match pinned {
+        Ok(Ok(reference)) => format!(
+            "{}. The files from before are saved as {}; `{}` in {} brings them back",
+            what,
+            reference,
+            checkpoint::restore_command(&commit),
+            session.working_dir.display()
+        ),
+        Ok(Err(e)) => format!("{}, and saving a checkpoint failed: {}", what, e),
+        Err(e) => format!("{}, and saving a checkpoint failed: {}", what, e),
+    }
+}
@@ -0,0 +1,76 @@
+//! Daily digest delivery
+//!
+//! Once a day at `[digest] at` the session manager summarizes the 24 hours
+//! before into Markdown, which is written to `digests/<date>.md` in the data
+//! dir and announced in the notification center.
+
+use anyhow::Result;
+use chrono::{DateTime, Local, NaiveTime};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::manager::{record_notification, SessionManagerHandle};
+use crate::core::digest::DigestConfig;
+use crate::core::notifications::{Notification, NotificationKind, NotificationsConfig};
+
+/// Put a digest together every day at the configured time
+pub fn spawn_daily_digest(
+    manager: SessionManagerHandle,
+    config: DigestConfig,
+    rules: NotificationsConfig,
+) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(until_next(config.at, Local::now())).await;
+            if let Err(e) = deliver(&manager, &config, &rules).await {
+                tracing::warn!("Failed to deliver the daily digest: {}", e);
+            }
+            // Don't fire twice within the same minute
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
+    });
+}
+
+async fn deliver(
+    manager: &SessionManagerHandle,
+    config: &DigestConfig,
+    rules: &NotificationsConfig,
+) -> Result<()> {
+    let until = chrono::Utc::now();
+    let digest = manager
+        .digest(until - chrono::Duration::hours(24), until)
+        .await?;
+
+    let mut message = digest.summary();
+    if config.save {
+        let path = digest_path(manager.data_dir(), Local::now());
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&path, &digest.markdown).await?;
+        tracing::info!("Daily digest written to {}", path.display());
+        message = format!("{}, saved to {}", message, path.display());
+    }
+    if config.notify {
+        let notification = Notification::new(NotificationKind::Digest, "", message);
+        record_notification(manager.storage().as_ref(), rules, None, notification).await?;
+    }
+    Ok(())
+}
+
+/// `digests/<date>.md` under the data dir
+fn digest_path(data_dir: &std::path::Path, now: DateTime<Local>) -> PathBuf {
+    data_dir
+        .join("digests")
+        .join(format!("{}.md", now.format("%Y-%m-%d")))
+}
+
+/// Time left until the clock next shows `at`
+fn until_next(at: NaiveTime, now: DateTime<Local>) -> Duration {
+    let now = now.naive_local();
+    let mut next = now.date().and_time(at);
+    if next <= now {
+        next += chrono::Duration::days(1);
+    }
+    (next - now).to_std().unwrap_or_default()
+}
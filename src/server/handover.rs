@@ -0,0 +1,212 @@
+//! Zero-downtime upgrades: a new server takes over the port and running sessions
+//!
+//! Every server binds its port with `SO_REUSEPORT`, so `codemux server upgrade`
+//! can start the new binary while the old one still listens. The new server:
+//!
+//! 1. binds the port without listening yet,
+//! 2. asks the old server for its sessions with `POST /api/handover`, which
+//!    also stops it from starting new ones,
+//! 3. moves each agent process onto a terminal of its own with reptyr, keeping
+//!    the session ID,
+//! 4. starts listening and stops the old server.
+//!
+//! Clients lose their WebSocket when the old server exits and rebind to the
+//! same session on the new one. Sessions that can't be moved end with the old
+//! server and are resumed from the session history when a client rebinds.
+//!
+//! Windows has neither `SO_REUSEPORT` nor reptyr, so servers there bind their
+//! port exclusively and can't hand over; `upgrade` fails to bind while the old
+//! server runs.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Socket, Type};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use crate::core::input_control::InputMode;
+use crate::core::launch::LaunchOptions;
+use crate::core::process::{self, Signal};
+use crate::core::session_links::SessionLink;
+use crate::server::manager::SessionManagerHandle;
+
+/// A running session, as the old server hands it over
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoverSession {
+    pub id: String,
+    pub agent: String,
+    /// Working directory the agent was started in
+    pub path: PathBuf,
+    /// Directory below `path` the agent was started in, if it was asked for
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Whether the session is recorded; the new server continues the recording
+    #[serde(default)]
+    pub record: bool,
+    /// Whether the session is exempt from `[session] idle_timeout_secs`
+    #[serde(default)]
+    pub keep_alive: bool,
+    pub color: String,
+    pub icon: String,
+    /// Agent process to move to the new server
+    pub pid: u32,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub links: Vec<SessionLink>,
+    #[serde(default)]
+    pub input_mode: InputMode,
+}
+
+/// Response of `POST /api/handover`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Handover {
+    /// Process ID of the server handing over, stopped once the new one listens
+    pub pid: u32,
+    pub sessions: Vec<HandoverSession>,
+}
+
+/// Bind `port` so that a later server can bind it too, without listening yet
+///
+/// On Windows, where `SO_REUSEADDR` would let any process steal the port, it is
+/// bound exclusively instead.
+pub fn bind_shared(port: u16) -> Result<Socket> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+    #[cfg(unix)]
+    {
+        socket.set_reuse_address(true)?;
+        socket.set_reuse_port(true)?;
+    }
+    #[cfg(windows)]
+    exclusive_address_use(&socket)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into()).map_err(|e| {
+        anyhow!(
+            "Port {} is taken: {} (servers from before `codemux server upgrade` existed \
+             can't hand over; restart them once with `codemux server stop`)",
+            port,
+            e
+        )
+    })?;
+    Ok(socket)
+}
+
+#[cfg(windows)]
+fn exclusive_address_use(socket: &Socket) -> Result<()> {
+    use std::os::windows::io::AsRawSocket;
+    use windows_sys::Win32::Networking::WinSock::{setsockopt, SOL_SOCKET, SO_EXCLUSIVEADDRUSE};
+
+    let enable: i32 = 1;
+    // SAFETY: `enable` outlives the call and is as long as the length passed
+    let result = unsafe {
+        setsockopt(
+            socket.as_raw_socket() as usize,
+            SOL_SOCKET,
+            SO_EXCLUSIVEADDRUSE,
+            &enable as *const i32 as *const u8,
+            std::mem::size_of::<i32>() as i32,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Start accepting connections on a socket from `bind_shared`
+pub fn listen(socket: Socket) -> Result<tokio::net::TcpListener> {
+    socket.listen(1024)?;
+    Ok(tokio::net::TcpListener::from_std(socket.into())?)
+}
+
+/// Take over the sessions of the server on `port`; returns its process ID
+///
+/// Call between `bind_shared` and `listen`, so the request reaches the old server.
+/// `auth_token` is the old server's token, which the new one keeps.
+pub async fn take_over(
+    port: u16,
+    auth_token: Option<&str>,
+    session_manager: &SessionManagerHandle,
+) -> Result<u32> {
+    let mut request =
+        reqwest::Client::new().post(format!("http://localhost:{}/api/handover", port));
+    if let Some(token) = auth_token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| anyhow!("No server on port {} to take over from: {}", port, e))?;
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Server on port {} refused the handover: {}",
+            port,
+            response.status()
+        ));
+    }
+    let handover: Handover = response.json().await?;
+
+    let count = handover.sessions.len();
+    let mut moved = 0;
+    for session in handover.sessions {
+        let launch = LaunchOptions {
+            adopt_pid: Some(session.pid),
+            color: Some(session.color),
+            icon: Some(session.icon),
+            name: session.name,
+            links: session.links,
+            input_mode: Some(session.input_mode),
+            cwd: session.cwd,
+            record: session.record,
+            keep_alive: session.keep_alive,
+            ..LaunchOptions::default()
+        };
+        match session_manager
+            .create_session_with_path(
+                session.agent,
+                Vec::new(),
+                None,
+                Some(session.path.to_string_lossy().to_string()),
+                Some(session.id.clone()),
+                launch,
+            )
+            .await
+        {
+            Ok(_) => {
+                if session.note.is_some() {
+                    session_manager
+                        .set_session_note(&session.id, session.note)
+                        .await;
+                }
+                moved += 1
+            }
+            Err(e) => tracing::warn!("Could not take over session {}: {}", session.id, e),
+        }
+    }
+    tracing::info!(
+        "Took over {} of {} sessions from server {}",
+        moved,
+        count,
+        handover.pid
+    );
+    Ok(handover.pid)
+}
+
+/// Stop the server that handed over and wait for it to exit
+pub async fn stop_old_server(pid: u32) -> Result<()> {
+    if !process::signal(pid, Signal::Terminate) {
+        return Err(anyhow!("Failed to stop the old server (PID {})", pid));
+    }
+
+    for _ in 0..50 {
+        if !process::is_running(pid) {
+            return Ok(());
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    tracing::warn!("Old server (PID {}) is still running after 5s", pid);
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! Running `[[hooks]]` on session lifecycle events
+//!
+//! Each hook that fires on an event runs its command, POSTs to its URL, or
+//! both, in the background so the session manager never waits on them. Like
+//! pushed notifications, delivery is best effort: failures and commands that
+//! run past `HOOK_TIMEOUT` are logged and not retried.
+
+use anyhow::{bail, Result};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::core::hooks::{Hook, HookPayload};
+
+/// How long a hook command or URL may take before it's given up on
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Run the `hooks` that fire on `payload`'s event in the background
+pub fn fire(hooks: &[Hook], payload: HookPayload) {
+    let hooks: Vec<Hook> = hooks
+        .iter()
+        .filter(|hook| hook.fires_on(payload.event))
+        .cloned()
+        .collect();
+    if hooks.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        run(&hooks, &payload).await;
+    });
+}
+
+/// Run each of `hooks`, logging the ones that fail
+pub async fn run(hooks: &[Hook], payload: &HookPayload) {
+    for hook in hooks {
+        if let Some(command) = &hook.command {
+            if let Err(e) = run_command(command, payload).await {
+                tracing::warn!(
+                    "Hook `{}` failed on {}: {}",
+                    command,
+                    payload.event.as_str(),
+                    e
+                );
+            }
+        }
+        if let Some(url) = &hook.url {
+            if let Err(e) = post(url, payload).await {
+                tracing::warn!(
+                    "Failed to send {} to hook {}: {}",
+                    payload.event.as_str(),
+                    url,
+                    e
+                );
+            }
+        }
+    }
+}
+
+async fn run_command(command: &str, payload: &HookPayload) -> Result<()> {
+    let mut process = if cfg!(windows) {
+        let mut process = tokio::process::Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = tokio::process::Command::new("sh");
+        process.args(["-c", command]);
+        process
+    };
+    // The directory may be gone by the time a session ends
+    if payload.session.path.is_dir() {
+        process.current_dir(&payload.session.path);
+    }
+    process
+        .envs(payload.env())
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+    let status = match tokio::time::timeout(HOOK_TIMEOUT, process.status()).await {
+        Ok(status) => status?,
+        Err(_) => bail!("Still running after {}s", HOOK_TIMEOUT.as_secs()),
+    };
+    if !status.success() {
+        bail!("Exited with {}", status);
+    }
+    Ok(())
+}
+
+fn http() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(HOOK_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client")
+    })
+}
+
+async fn post(url: &str, payload: &HookPayload) -> Result<()> {
+    http()
+        .post(url)
+        .json(payload)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::core::hooks::{HookEvent, HookSession};
+
+    #[tokio::test]
+    async fn runs_commands_with_the_session_in_the_environment() {
+        let dir = std::env::temp_dir().join(format!("codemux-hooks-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let hooks = [
+            Hook {
+                events: vec![HookEvent::AgentExited],
+                command: Some(
+                    "echo \"$CODEMUX_EVENT $CODEMUX_AGENT $CODEMUX_EXIT_CODE $CODEMUX_PROJECT\" > exited"
+                        .to_string(),
+                ),
+                url: None,
+            },
+            Hook {
+                events: vec![HookEvent::SessionCreated],
+                command: Some("touch created".to_string()),
+                url: None,
+            },
+        ];
+        let mut payload = HookPayload::new(
+            HookEvent::AgentExited,
+            HookSession {
+                session_id: "0b5e6f9a-session".to_string(),
+                agent: "claude".to_string(),
+                project: Some("web".to_string()),
+                path: dir.clone(),
+                name: None,
+            },
+        );
+        payload.exit_code = Some(3);
+
+        let firing: Vec<_> = hooks
+            .iter()
+            .filter(|hook| hook.fires_on(payload.event))
+            .cloned()
+            .collect();
+        run(&firing, &payload).await;
+
+        let exited = std::fs::read_to_string(dir.join("exited")).unwrap();
+        let created = dir.join("created").exists();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(exited, "agent_exited claude 3 web\n");
+        assert!(!created);
+    }
+}
@@ -0,0 +1,114 @@
+//! One server per runtime directory, without trusting the PID file
+//!
+//! A running server holds an exclusive lock on `server.lock` in the runtime
+//! directory. The OS lets go of it when the process exits, however it exits,
+//! so a file left behind by a crashed or killed server never keeps the next
+//! one from starting, and two servers started at once can't both get it: the
+//! port is bound shared for `server upgrade`, so binding alone doesn't tell
+//! them apart. The PID file is still written for `server upgrade` to watch,
+//! but nothing decides from it whether a server runs.
+
+use anyhow::{anyhow, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long a server taking over waits for the old one to let go of the lock
+pub const TAKEOVER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The lock of the running server, held until it's dropped
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Take the lock at `path`, failing when another server holds it
+    pub fn acquire(path: &Path) -> Result<Self> {
+        match Self::try_acquire(path)? {
+            Some(lock) => Ok(lock),
+            None => {
+                // Locked files can't be read on every platform
+                let holder = std::fs::read_to_string(path)
+                    .ok()
+                    .map(|pid| pid.trim().to_string())
+                    .filter(|pid| !pid.is_empty())
+                    .map(|pid| format!(" (PID {})", pid))
+                    .unwrap_or_default();
+                Err(anyhow!(
+                    "Another codemux server is running{}; stop it with 'codemux server stop'",
+                    holder
+                ))
+            }
+        }
+    }
+
+    /// Take the lock at `path` once the server being taken over exits,
+    /// waiting at most `timeout`
+    pub async fn acquire_within(path: &Path, timeout: Duration) -> Result<Self> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(lock) = Self::try_acquire(path)? {
+                return Ok(lock);
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "The old server still holds {} after {}s",
+                    path.display(),
+                    timeout.as_secs()
+                ));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// The lock, or `None` while another process holds it
+    fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(|e| anyhow!("Can't open the lock file {}: {}", path.display(), e))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => {
+                return Err(anyhow!("Can't lock {}: {}", path.display(), e))
+            }
+        }
+        // Only for the error message of a server that finds it locked
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Some(Self { _file: file }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn one_holder_at_a_time_and_stale_files_do_not_block() {
+        let dir = std::env::temp_dir().join(format!("codemux-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.lock");
+        // Left behind by a server that was killed
+        std::fs::write(&path, "999999").unwrap();
+
+        let lock = InstanceLock::acquire(&path).unwrap();
+        let error = InstanceLock::acquire(&path).unwrap_err().to_string();
+        assert!(error.contains("Another codemux server is running"));
+        assert!(
+            InstanceLock::acquire_within(&path, Duration::from_millis(200))
+                .await
+                .is_err()
+        );
+
+        drop(lock);
+        assert!(InstanceLock::acquire(&path).is_ok());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,21 +1,90 @@
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
 use std::path::PathBuf;
-use tokio::sync::{mpsc, oneshot};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
 use uuid::Uuid;
 
+#[cfg(feature = "capture")]
+use crate::capture::demo;
 use crate::core::{
+    accessible::{AccessibleEvent, AnnouncementKind, Linearizer},
+    adopt,
+    agent_adapter::{self, AgentAdapter},
+    agent_config,
+    agent_version::{AgentConfig, AgentVersionChecker, AgentVersionStatus},
+    commits::{self, AgentCommit},
+    digest::{Digest, DigestSession, SessionOutcome},
+    hooks::{HookEvent, HookPayload, HookSession},
+    launch::{self, AgentOption, LaunchOptions, ProjectOption, SessionOptions, TemplateOption},
+    limits::{RunningSessions, SessionLimitError},
+    notifications::{Notification, NotificationKind, NotificationsConfig},
+    paste::PasteConfig,
+    process::Signal,
+    project_groups,
+    project_metadata::ProjectMetadata,
     pty_session::{PtyChannels, PtySession},
-    session::{ProjectAttributes, SessionAttributes, SessionType},
+    recycle_bin::PendingKill,
+    session::{
+        default_session_color, default_session_icon, validate_session_color, validate_session_name,
+        ProjectAttributes, ProjectDefaults, SessionAttributes, SessionType, DEMO_AGENT,
+    },
+    session_links::{self, SessionLink, SessionLinkKind, SessionRelationships},
     Config,
 };
 use crate::core::{ProjectResource, SessionResource};
-use crate::server::claude_cache::{CacheEvent, ClaudeProjectsCache};
+use crate::server::checkpoints::{self, CheckpointedSession};
+use crate::server::claude_cache::{CacheEvent, CachedSession, ClaudeProjectsCache};
+use crate::server::deletion_guard::{self, GuardedSession};
+use crate::server::handover::HandoverSession;
+use crate::server::hooks;
+use crate::server::notify;
+#[cfg(feature = "capture")]
+use crate::server::recording;
+use crate::server::search::SearchableSession;
+use crate::server::shutdown;
+use crate::server::storage::{LocalStorage, SessionRecord, Storage, StoredProject};
+
+/// A session asking questions back to back gets one notification for them
+const QUESTION_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(60);
+/// How often sessions are checked for having sat idle too long
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// How often the watchdog looks at the request the actor is handling
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest a health check waits for the actor, however long requests may take
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Store a notification, silenced if `[notifications]` or do-not-disturb rule out an alert now,
+/// and push the alert to the project's targets, see `notify`
+pub(crate) async fn record_notification(
+    storage: &dyn Storage,
+    rules: &NotificationsConfig,
+    project: Option<&str>,
+    mut notification: Notification,
+) -> Result<()> {
+    let dnd = storage.load_do_not_disturb().await?;
+    notification.silenced = dnd.is_active_at(chrono::Utc::now())
+        || !rules.allows(notification.kind, project, chrono::Local::now().time());
+    storage.save_notification(&notification).await?;
+    if !notification.silenced {
+        let targets = rules.push_targets(notification.kind, project);
+        notify::push(targets, project.map(str::to_string), notification);
+    }
+    Ok(())
+}
 
 // Cleanup messages for session lifecycle management
 #[derive(Debug)]
 pub enum SessionCleanupMessage {
-    SessionCompleted { session_id: String },
+    SessionCompleted {
+        session_id: String,
+        /// Exit code of the agent, when it exited on its own
+        exit_code: Option<u32>,
+    },
+    /// The grace period of a killed session is over
+    KillGraceOver { session_id: String, token: u64 },
 }
 
 // Commands that can be sent to the SessionManager actor
@@ -26,8 +95,12 @@ pub enum SessionCommand {
         project_id: Option<String>,
         path: Option<String>,
         resume_session_id: Option<String>,
+        launch: Box<LaunchOptions>,
         response_tx: oneshot::Sender<Result<SessionResource>>,
     },
+    GetSessionOptions {
+        response_tx: oneshot::Sender<SessionOptions>,
+    },
     GetSession {
         session_id: String,
         response_tx: oneshot::Sender<Option<SessionResource>>,
@@ -39,25 +112,69 @@ pub enum SessionCommand {
     ListSessions {
         response_tx: oneshot::Sender<Vec<SessionResource>>,
     },
+    ListEndedSessions {
+        response_tx: oneshot::Sender<Vec<SessionResource>>,
+    },
     GetRecentProjectSessions {
         project_path: std::path::PathBuf,
         response_tx: oneshot::Sender<Vec<SessionResource>>,
     },
+    ListConversations {
+        project_path: std::path::PathBuf,
+        response_tx: oneshot::Sender<Vec<CachedSession>>,
+    },
+    /// Kill a session, after the `[recycle_bin]` grace period unless `now`
     CloseSession {
         session_id: String,
-        response_tx: oneshot::Sender<Result<()>>,
+        now: bool,
+        response_tx: oneshot::Sender<Result<Option<String>>>,
+    },
+    UndoKill {
+        session_id: String,
+        response_tx: oneshot::Sender<Result<Option<SessionResource>>>,
+    },
+    SetSessionNote {
+        session_id: String,
+        note: Option<String>,
+        response_tx: oneshot::Sender<Option<SessionResource>>,
     },
     CreateProject {
         name: String,
         path: String,
+        parent: Option<String>,
         response_tx: oneshot::Sender<Result<ProjectResource>>,
     },
     ListProjects {
         response_tx: oneshot::Sender<Vec<ProjectResource>>,
     },
+    RemoveProject {
+        project: String,
+        kill_sessions: bool,
+        response_tx: oneshot::Sender<Result<Option<RemovedProject>>>,
+    },
+    UpdateProject {
+        project: String,
+        name: Option<String>,
+        defaults: Option<ProjectDefaults>,
+        response_tx: oneshot::Sender<Result<Option<ProjectResource>>>,
+    },
     ShutdownAllSessions {
         response_tx: oneshot::Sender<()>,
     },
+    Ping {
+        response_tx: oneshot::Sender<ManagerStatus>,
+    },
+    Handover {
+        response_tx: oneshot::Sender<Vec<HandoverSession>>,
+    },
+    Digest {
+        since: chrono::DateTime<chrono::Utc>,
+        until: chrono::DateTime<chrono::Utc>,
+        response_tx: oneshot::Sender<Result<Digest>>,
+    },
+    SearchableSessions {
+        response_tx: oneshot::Sender<Result<Vec<SearchableSession>>>,
+    },
     ResumeSession {
         session_id: String,
         agent: String,
@@ -67,10 +184,110 @@ pub enum SessionCommand {
     },
 }
 
+impl SessionCommand {
+    /// Name of the command, for logs
+    fn name(&self) -> &'static str {
+        match self {
+            SessionCommand::CreateSession { .. } => "CreateSession",
+            SessionCommand::GetSessionOptions { .. } => "GetSessionOptions",
+            SessionCommand::GetSession { .. } => "GetSession",
+            SessionCommand::GetSessionChannels { .. } => "GetSessionChannels",
+            SessionCommand::ListSessions { .. } => "ListSessions",
+            SessionCommand::ListEndedSessions { .. } => "ListEndedSessions",
+            SessionCommand::GetRecentProjectSessions { .. } => "GetRecentProjectSessions",
+            SessionCommand::ListConversations { .. } => "ListConversations",
+            SessionCommand::CloseSession { .. } => "CloseSession",
+            SessionCommand::UndoKill { .. } => "UndoKill",
+            SessionCommand::SetSessionNote { .. } => "SetSessionNote",
+            SessionCommand::CreateProject { .. } => "CreateProject",
+            SessionCommand::ListProjects { .. } => "ListProjects",
+            SessionCommand::RemoveProject { .. } => "RemoveProject",
+            SessionCommand::UpdateProject { .. } => "UpdateProject",
+            SessionCommand::ShutdownAllSessions { .. } => "ShutdownAllSessions",
+            SessionCommand::Ping { .. } => "Ping",
+            SessionCommand::Handover { .. } => "Handover",
+            SessionCommand::Digest { .. } => "Digest",
+            SessionCommand::SearchableSessions { .. } => "SearchableSessions",
+            SessionCommand::ResumeSession { .. } => "ResumeSession",
+        }
+    }
+
+    /// How long handling the command may take, given `[server] request_timeout_secs`
+    fn deadline(&self, request_timeout: Duration) -> Duration {
+        match self {
+            // Waits for the agents to exit
+            SessionCommand::ShutdownAllSessions { .. } => request_timeout + shutdown::AGENT_GRACE,
+            SessionCommand::Ping { .. } => request_timeout.min(PING_TIMEOUT),
+            _ => request_timeout,
+        }
+    }
+}
+
+/// What the session manager answers a health check with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ManagerStatus {
+    pub running_sessions: usize,
+    /// False once the sessions were handed over to a newer server
+    pub accepting_sessions: bool,
+}
+
+/// The session manager didn't answer a request before its deadline
+#[derive(Debug)]
+pub struct RequestTimeout {
+    pub command: &'static str,
+    pub timeout: Duration,
+}
+
+impl fmt::Display for RequestTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The session manager didn't answer {} within {} seconds",
+            self.command,
+            self.timeout.as_secs()
+        )
+    }
+}
+
+impl std::error::Error for RequestTimeout {}
+
+/// The answer to a request whose callers take no errors, logging failures
+fn or_default<T: Default>(answer: Result<T>) -> T {
+    answer.unwrap_or_else(|e| {
+        tracing::warn!("{}", e);
+        T::default()
+    })
+}
+
+/// The command the actor is handling, for the watchdog
+#[derive(Clone, Copy)]
+struct InFlight {
+    command: &'static str,
+    started: Instant,
+    deadline: Duration,
+}
+
 // Actor handle for communicating with SessionManager
 #[derive(Clone)]
 pub struct SessionManagerHandle {
     command_tx: mpsc::UnboundedSender<SessionCommand>,
+    data_dir: PathBuf,
+    recordings_dir: PathBuf,
+    pastes_dir: PathBuf,
+    pid_file: PathBuf,
+    paste: PasteConfig,
+    /// How long a queued session creation waits, when `[limits]` queueing is on
+    queue_timeout: Option<Duration>,
+    /// `[server] request_timeout_secs`
+    request_timeout: Duration,
+    storage: Arc<dyn Storage>,
+    versions: AgentVersionChecker,
+    /// Whitelisted and configured agents, sorted
+    agent_names: Vec<String>,
+    /// `[agents.<name>]` tables, by agent
+    agents: Arc<BTreeMap<String, AgentConfig>>,
+    /// The request the actor is handling, as the watchdog sees it
+    in_flight: watch::Receiver<Option<InFlight>>,
 }
 
 // Internal session manager state (runs in its own task)
@@ -82,6 +299,25 @@ struct SessionManagerActor {
     cleanup_rx: mpsc::UnboundedReceiver<SessionCleanupMessage>,
     cleanup_tx: mpsc::UnboundedSender<SessionCleanupMessage>,
     claude_cache: Option<ClaudeProjectsCache>,
+    storage: Arc<dyn Storage>,
+    /// Creations waiting for a free slot under `[limits]`, oldest first
+    queued: VecDeque<QueuedSession>,
+    /// Set once a new server took the sessions over; no more are started
+    handed_over: bool,
+    /// Last token handed to a killed session's grace period timer
+    kill_token: u64,
+    /// The command being handled, for the watchdog and `/healthz`
+    in_flight_tx: watch::Sender<Option<InFlight>>,
+}
+
+struct QueuedSession {
+    agent: String,
+    args: Vec<String>,
+    project_id: Option<String>,
+    path: Option<String>,
+    resume_session_id: Option<String>,
+    launch: LaunchOptions,
+    response_tx: oneshot::Sender<Result<SessionResource>>,
 }
 
 struct SessionState {
@@ -89,18 +325,132 @@ struct SessionState {
     agent: String,
     channels: PtyChannels,
     project_id: Option<String>,
+    color: String,
+    icon: String,
+    /// The agent process, or the adopted one for sessions that run reptyr
+    pid: Option<u32>,
+    working_dir: PathBuf,
+    /// Directory the agent was started in, when asked for with `cwd`
+    cwd: Option<String>,
+    /// Whether the session's output is recorded, see `recording`
+    record: bool,
+    /// Exempt from ending when idle, see `core::idle`
+    keep_alive: bool,
+    /// How long the session may sit idle before it's ended
+    idle_timeout: Option<Duration>,
+    /// Handover note left by whoever detached last
+    note: Option<String>,
+    name: Option<String>,
+    links: Vec<SessionLink>,
+    /// Set while the session is killed but can still be brought back
+    pending_kill: Option<PendingKill>,
+}
+
+/// A removed project and what happened to its running sessions
+pub struct RemovedProject {
+    pub project: ProjectResource,
+    /// Running sessions of the project, killed or left without a project
+    pub sessions: Vec<String>,
 }
 
 struct Project {
     id: String,
     name: String,
     path: PathBuf,
+    /// ID of the project this one is a sub-project of
+    parent: Option<String>,
+    metadata: ProjectMetadata,
+    defaults: ProjectDefaults,
+}
+
+impl SessionState {
+    fn resource(&self) -> SessionResource {
+        let status = match self.pending_kill {
+            Some(_) => "terminating",
+            None => "running",
+        };
+        SessionResource {
+            resource_type: "session".to_string(),
+            id: self.id.clone(),
+            attributes: Some(SessionAttributes {
+                agent: self.agent.clone(),
+                project: self.project_id.clone(),
+                status: status.to_string(),
+                session_type: SessionType::Active,
+                last_modified: Some(chrono::Utc::now().to_rfc3339()),
+                last_message: None, // Active sessions don't have historical messages
+                color: self.color.clone(),
+                icon: self.icon.clone(),
+                note: self.note.clone(),
+                name: self.name.clone(),
+                terminates_at: self.pending_kill.as_ref().map(|kill| kill.ends_at.clone()),
+                idle_expires_at: self
+                    .idle_timeout
+                    .filter(|_| self.pending_kill.is_none())
+                    .and_then(|timeout| self.channels.idle.expires_at(timeout)),
+                diff_stats: None,
+                exit_code: None,
+            }),
+            relationships: SessionRelationships::from_links(&self.links),
+        }
+    }
+}
+
+impl Project {
+    fn new(id: String, name: String, path: PathBuf, parent: Option<String>) -> Self {
+        let metadata = ProjectMetadata::read(&path);
+        Project {
+            id,
+            name,
+            path,
+            parent,
+            metadata,
+            defaults: ProjectDefaults::default(),
+        }
+    }
+
+    fn resource(&self) -> ProjectResource {
+        ProjectResource {
+            resource_type: "project".to_string(),
+            id: self.id.clone(),
+            attributes: Some(ProjectAttributes {
+                name: self.name.clone(),
+                path: self.path.to_string_lossy().to_string(),
+                color: self.metadata.color.clone(),
+                icon: self.metadata.icon.clone(),
+                remote: self.metadata.remote.clone(),
+                defaults: (!self.defaults.is_empty()).then(|| self.defaults.clone()),
+            }),
+            relationships: project_groups::parent_relationships(self.parent.as_deref()),
+        }
+    }
 }
 
 impl SessionManagerHandle {
+    /// A manager that keeps its state in JSON files under the data directory
     pub fn new(config: Config) -> Self {
+        let storage = Arc::new(LocalStorage::new(config.server.data_dir.clone()));
+        Self::with_storage(config, storage)
+    }
+
+    /// A manager backed by `storage`, e.g. from `storage::open` for the `[storage]` section
+    pub fn with_storage(config: Config, storage: Arc<dyn Storage>) -> Self {
         let (command_tx, command_rx) = mpsc::unbounded_channel();
         let (cleanup_tx, cleanup_rx) = mpsc::unbounded_channel();
+        let data_dir = config.server.data_dir.clone();
+        let recordings_dir = config.server.recordings_dir();
+        let pastes_dir = config.server.pastes_dir();
+        let pid_file = config.server.pid_file();
+        let paste = config.paste.clone();
+        let queue_timeout = config.limits.queue.then(|| config.limits.queue_timeout());
+        let request_timeout = config.server.request_timeout();
+        let versions = AgentVersionChecker::new(config.agents.clone());
+        versions.spawn_daily_check();
+        let digest = config.digest.clone();
+        let notification_rules = config.notifications.clone();
+        let agent_names = config.agent_names();
+        let agents = Arc::new(config.agents.clone());
+        let (in_flight_tx, in_flight) = watch::channel(None);
 
         let actor = SessionManagerActor {
             config,
@@ -110,12 +460,89 @@ impl SessionManagerHandle {
             cleanup_rx,
             cleanup_tx: cleanup_tx.clone(),
             claude_cache: None, // Will be initialized in run()
+            storage: storage.clone(),
+            queued: VecDeque::new(),
+            handed_over: false,
+            kill_token: 0,
+            in_flight_tx,
         };
 
         // Spawn the actor task
         tokio::spawn(actor.run());
 
-        Self { command_tx }
+        let handle = Self {
+            command_tx,
+            data_dir,
+            recordings_dir,
+            pastes_dir,
+            pid_file,
+            paste,
+            queue_timeout,
+            request_timeout,
+            storage,
+            versions,
+            agent_names,
+            agents,
+            in_flight,
+        };
+        if digest.enabled {
+            crate::server::digest::spawn_daily_digest(handle.clone(), digest, notification_rules);
+        }
+        handle
+    }
+
+    /// Server data directory (crash dumps, host keys, ...)
+    pub fn data_dir(&self) -> &std::path::Path {
+        &self.data_dir
+    }
+
+    /// Where sessions created with `record` are recorded
+    pub fn recordings_dir(&self) -> &std::path::Path {
+        &self.recordings_dir
+    }
+
+    /// Where pastes are saved to files, under the cache directory
+    pub fn pastes_dir(&self) -> &std::path::Path {
+        &self.pastes_dir
+    }
+
+    /// PID file the server writes once it listens
+    pub fn pid_file(&self) -> &std::path::Path {
+        &self.pid_file
+    }
+
+    /// Where projects, session history, recent attaches and preferences are kept
+    pub fn storage(&self) -> Arc<dyn Storage> {
+        self.storage.clone()
+    }
+
+    /// Paste thresholds and limit from the `[paste]` config section
+    pub fn paste_config(&self) -> &PasteConfig {
+        &self.paste
+    }
+
+    /// How `agent` resumes conversations and where its transcripts are, if known
+    pub fn agent_adapter(&self, agent: &str) -> Option<Box<dyn AgentAdapter>> {
+        let spec = self
+            .agents
+            .get(agent)
+            .and_then(|config| config.transcript.as_ref());
+        agent_adapter::adapter_for(agent, spec)
+    }
+
+    /// Arguments that resume the latest conversation of `agent`, or the given
+    /// one, if codemux knows how the agent spells them
+    pub fn resume_args(&self, agent: &str, conversation: Option<&str>) -> Option<Vec<String>> {
+        agent_adapter::resume_args(agent, self.agents.get(agent), conversation)
+    }
+
+    /// Installed versions of the whitelisted and configured agents, checked against their pins
+    pub async fn agent_versions(&self) -> Vec<AgentVersionStatus> {
+        let checks = self
+            .agent_names
+            .iter()
+            .map(|agent| self.versions.check(agent));
+        futures::future::join_all(checks).await
     }
 
     pub async fn create_session_with_path(
@@ -125,7 +552,13 @@ impl SessionManagerHandle {
         project_id: Option<String>,
         path: Option<String>,
         resume_session_id: Option<String>,
+        launch: LaunchOptions,
     ) -> Result<SessionResource> {
+        // An adopted agent is already running, so its version is what it is
+        if launch.adopt_pid.is_none() {
+            self.versions.ensure_allowed(&agent).await?;
+        }
+
         let (response_tx, response_rx) = oneshot::channel();
 
         let command = SessionCommand::CreateSession {
@@ -134,6 +567,7 @@ impl SessionManagerHandle {
             project_id,
             path,
             resume_session_id,
+            launch: Box::new(launch),
             response_tx,
         };
 
@@ -141,68 +575,122 @@ impl SessionManagerHandle {
             .send(command)
             .map_err(|_| anyhow!("SessionManager actor is not running"))?;
 
-        response_rx
-            .await
-            .map_err(|_| anyhow!("SessionManager actor did not respond"))?
+        // Dropping the receiver on timeout takes the request out of the queue
+        let response = match self.queue_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, response_rx)
+                .await
+                .map_err(|_| SessionLimitError::QueueTimeout(timeout))?,
+            None => tokio::time::timeout(self.request_timeout, response_rx)
+                .await
+                .map_err(|_| RequestTimeout {
+                    command: "CreateSession",
+                    timeout: self.request_timeout,
+                })?,
+        };
+        response.map_err(|_| anyhow!("SessionManager actor did not respond"))?
     }
 
-    pub async fn get_session(&self, session_id: &str) -> Option<SessionResource> {
+    /// Send the command `build` makes around the response sender to the actor
+    /// and wait for the answer, at most until the command's deadline
+    async fn request<T>(
+        &self,
+        build: impl FnOnce(oneshot::Sender<T>) -> SessionCommand,
+    ) -> Result<T> {
         let (response_tx, response_rx) = oneshot::channel();
+        let command = build(response_tx);
+        let name = command.name();
+        let timeout = command.deadline(self.request_timeout);
 
-        let command = SessionCommand::GetSession {
-            session_id: session_id.to_string(),
-            response_tx,
-        };
+        self.command_tx
+            .send(command)
+            .map_err(|_| anyhow!("SessionManager actor is not running"))?;
 
-        if self.command_tx.send(command).is_err() {
-            return None;
-        }
+        tokio::time::timeout(timeout, response_rx)
+            .await
+            .map_err(|_| RequestTimeout {
+                command: name,
+                timeout,
+            })?
+            .map_err(|_| anyhow!("SessionManager actor did not respond"))
+    }
 
-        response_rx.await.unwrap_or(None)
+    pub async fn get_session(&self, session_id: &str) -> Option<SessionResource> {
+        let session_id = session_id.to_string();
+        or_default(
+            self.request(|response_tx| SessionCommand::GetSession {
+                session_id,
+                response_tx,
+            })
+            .await,
+        )
     }
 
     pub async fn get_session_channels(&self, session_id: &str) -> Option<PtyChannels> {
-        let (response_tx, response_rx) = oneshot::channel();
-
-        let command = SessionCommand::GetSessionChannels {
-            session_id: session_id.to_string(),
-            response_tx,
-        };
-
-        if self.command_tx.send(command).is_err() {
-            return None;
-        }
-
-        response_rx.await.unwrap_or(None)
+        let session_id = session_id.to_string();
+        or_default(
+            self.request(|response_tx| SessionCommand::GetSessionChannels {
+                session_id,
+                response_tx,
+            })
+            .await,
+        )
     }
 
     pub async fn list_sessions(&self) -> Vec<SessionResource> {
-        let (response_tx, response_rx) = oneshot::channel();
-
-        let command = SessionCommand::ListSessions { response_tx };
-
-        if self.command_tx.send(command).is_err() {
-            return vec![];
-        }
-
-        response_rx.await.unwrap_or_else(|_| vec![])
+        or_default(
+            self.request(|response_tx| SessionCommand::ListSessions { response_tx })
+                .await,
+        )
     }
 
-    pub async fn close_session(&self, session_id: &str) -> Result<()> {
-        let (response_tx, response_rx) = oneshot::channel();
+    /// Sessions that ended within the `[recycle_bin]` retention, newest first
+    pub async fn list_ended_sessions(&self) -> Vec<SessionResource> {
+        or_default(
+            self.request(|response_tx| SessionCommand::ListEndedSessions { response_tx })
+                .await,
+        )
+    }
 
-        let command = SessionCommand::CloseSession {
-            session_id: session_id.to_string(),
+    /// Kill a session; unless `now`, it is paused for the `[recycle_bin]`
+    /// grace period first. Returns when the session ends, `None` if it did already.
+    pub async fn close_session(&self, session_id: &str, now: bool) -> Result<Option<String>> {
+        let session_id = session_id.to_string();
+        self.request(|response_tx| SessionCommand::CloseSession {
+            session_id,
+            now,
             response_tx,
-        };
+        })
+        .await?
+    }
 
-        self.command_tx
-            .send(command)
-            .map_err(|_| anyhow!("SessionManager actor is not running"))?;
+    /// Keep a killed session running if its grace period isn't over yet;
+    /// `None` if there is no such session
+    pub async fn undo_kill(&self, session_id: &str) -> Result<Option<SessionResource>> {
+        let session_id = session_id.to_string();
+        self.request(|response_tx| SessionCommand::UndoKill {
+            session_id,
+            response_tx,
+        })
+        .await?
+    }
 
-        response_rx
-            .await
-            .map_err(|_| anyhow!("SessionManager actor did not respond"))?
+    /// Leave a handover note on a running session, or clear it with `None`
+    ///
+    /// Returns the updated session, or `None` if it isn't running.
+    pub async fn set_session_note(
+        &self,
+        session_id: &str,
+        note: Option<String>,
+    ) -> Option<SessionResource> {
+        let session_id = session_id.to_string();
+        or_default(
+            self.request(|response_tx| SessionCommand::SetSessionNote {
+                session_id,
+                note,
+                response_tx,
+            })
+            .await,
+        )
     }
 
     pub async fn resume_session(
@@ -212,82 +700,223 @@ impl SessionManagerHandle {
         args: Vec<String>,
         project_id: Option<String>,
     ) -> Result<SessionResource> {
-        let (response_tx, response_rx) = oneshot::channel();
+        self.versions.ensure_allowed(&agent).await?;
 
-        let command = SessionCommand::ResumeSession {
+        self.request(|response_tx| SessionCommand::ResumeSession {
             session_id,
             agent,
             args,
             project_id,
             response_tx,
-        };
-
-        self.command_tx
-            .send(command)
-            .map_err(|_| anyhow!("SessionManager actor is not running"))?;
-
-        response_rx
-            .await
-            .map_err(|_| anyhow!("SessionManager actor did not respond"))?
+        })
+        .await?
     }
 
-    pub async fn create_project(&self, name: String, path: String) -> Result<ProjectResource> {
-        let (response_tx, response_rx) = oneshot::channel();
-
-        let command = SessionCommand::CreateProject {
+    /// Register `path` as a project, as a sub-project of `parent` (ID, name or
+    /// qualified name) if given
+    pub async fn create_project(
+        &self,
+        name: String,
+        path: String,
+        parent: Option<String>,
+    ) -> Result<ProjectResource> {
+        self.request(|response_tx| SessionCommand::CreateProject {
             name,
             path,
+            parent,
             response_tx,
-        };
-
-        self.command_tx
-            .send(command)
-            .map_err(|_| anyhow!("SessionManager actor is not running"))?;
-
-        response_rx
-            .await
-            .map_err(|_| anyhow!("SessionManager actor did not respond"))?
+        })
+        .await?
     }
 
     pub async fn list_projects(&self) -> Vec<ProjectResource> {
-        let (response_tx, response_rx) = oneshot::channel();
-
-        let command = SessionCommand::ListProjects { response_tx };
+        or_default(
+            self.request(|response_tx| SessionCommand::ListProjects { response_tx })
+                .await,
+        )
+    }
 
-        if self.command_tx.send(command).is_err() {
-            return vec![];
-        }
+    /// Remove a project by ID or (qualified) name. Its running sessions are
+    /// ended with `kill_sessions`, else they run on without a project
+    ///
+    /// Returns `None` if there's no such project.
+    pub async fn remove_project(
+        &self,
+        project: &str,
+        kill_sessions: bool,
+    ) -> Result<Option<RemovedProject>> {
+        let project = project.to_string();
+        self.request(|response_tx| SessionCommand::RemoveProject {
+            project,
+            kill_sessions,
+            response_tx,
+        })
+        .await?
+    }
 
-        response_rx.await.unwrap_or_else(|_| vec![])
+    /// Rename a project by ID or (qualified) name, or replace its defaults;
+    /// `None` if there's no such project
+    pub async fn update_project(
+        &self,
+        project: &str,
+        name: Option<String>,
+        defaults: Option<ProjectDefaults>,
+    ) -> Result<Option<ProjectResource>> {
+        let project = project.to_string();
+        self.request(|response_tx| SessionCommand::UpdateProject {
+            project,
+            name,
+            defaults,
+            response_tx,
+        })
+        .await?
     }
 
     pub async fn get_recent_project_sessions(
         &self,
         project_path: std::path::PathBuf,
     ) -> Vec<SessionResource> {
-        let (response_tx, response_rx) = oneshot::channel();
+        or_default(
+            self.request(|response_tx| SessionCommand::GetRecentProjectSessions {
+                project_path,
+                response_tx,
+            })
+            .await,
+        )
+    }
+
+    /// Claude conversations kept for a project, newest first, whether or not
+    /// a session is running them
+    pub async fn project_conversations(
+        &self,
+        project_path: std::path::PathBuf,
+    ) -> Vec<CachedSession> {
+        or_default(
+            self.request(|response_tx| SessionCommand::ListConversations {
+                project_path,
+                response_tx,
+            })
+            .await,
+        )
+    }
 
-        let command = SessionCommand::GetRecentProjectSessions {
-            project_path,
+    /// Agents, templates and projects to offer when creating a session
+    pub async fn session_options(&self) -> Result<SessionOptions> {
+        self.request(|response_tx| SessionCommand::GetSessionOptions { response_tx })
+            .await
+    }
+
+    /// Stop starting sessions and describe the running ones for a new server to take over
+    pub async fn handover(&self) -> Result<Vec<HandoverSession>> {
+        self.request(|response_tx| SessionCommand::Handover { response_tx })
+            .await
+    }
+
+    /// Summary of the sessions that ran between `since` and `until`
+    pub async fn digest(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Digest> {
+        self.request(|response_tx| SessionCommand::Digest {
+            since,
+            until,
             response_tx,
-        };
+        })
+        .await?
+    }
+
+    /// Running sessions and those of the history, with what search looks through
+    pub async fn searchable_sessions(&self) -> Result<Vec<SearchableSession>> {
+        self.request(|response_tx| SessionCommand::SearchableSessions { response_tx })
+            .await?
+    }
 
-        if self.command_tx.send(command).is_err() {
-            return vec![];
+    /// Fails when the actor has stopped, or is still handling a request past
+    /// its deadline; asks nothing of the actor, so a slow request that's
+    /// within its deadline doesn't count, as `/healthz` needs
+    pub fn check_alive(&self) -> Result<()> {
+        if self.command_tx.is_closed() {
+            return Err(anyhow!("The session manager has stopped"));
         }
+        if let Some(request) = *self.in_flight.borrow() {
+            let running = request.started.elapsed();
+            if running > request.deadline {
+                return Err(anyhow!(
+                    "The session manager is stuck on {} for {}s",
+                    request.command,
+                    running.as_secs()
+                ));
+            }
+        }
+        Ok(())
+    }
 
-        response_rx.await.unwrap_or_else(|_| vec![])
+    /// Check that the actor answers within `PING_TIMEOUT`, as `/readyz` does
+    pub async fn ping(&self) -> Result<ManagerStatus> {
+        self.request(|response_tx| SessionCommand::Ping { response_tx })
+            .await
     }
 
     pub async fn shutdown_all_sessions(&self) {
-        let (response_tx, response_rx) = oneshot::channel();
+        or_default(
+            self.request(|response_tx| SessionCommand::ShutdownAllSessions { response_tx })
+                .await,
+        )
+    }
+}
 
-        let command = SessionCommand::ShutdownAllSessions { response_tx };
+/// A session of the history, as long as the recycle bin keeps it
+fn ended_session_resource(record: SessionRecord) -> SessionResource {
+    SessionResource {
+        resource_type: "session".to_string(),
+        id: record.id,
+        attributes: Some(SessionAttributes {
+            agent: record.agent,
+            project: record.project,
+            status: if record.exited { "exited" } else { "killed" }.to_string(),
+            session_type: SessionType::Historical,
+            last_modified: record.ended_at,
+            last_message: None,
+            color: record.color,
+            icon: record.icon,
+            note: None,
+            name: None,
+            terminates_at: None,
+            idle_expires_at: None,
+            diff_stats: None,
+            exit_code: record.exit_code,
+        }),
+        relationships: SessionRelationships::from_links(&record.links),
+    }
+}
 
-        if self.command_tx.send(command).is_ok() {
-            let _ = response_rx.await;
+/// Log requests that run past their deadline; their callers have failed
+/// already, and the requests queued behind them wait
+fn spawn_watchdog(mut in_flight: watch::Receiver<Option<InFlight>>) {
+    tokio::spawn(async move {
+        let mut check = tokio::time::interval(WATCHDOG_INTERVAL);
+        let mut reported = None;
+        loop {
+            check.tick().await;
+            // The actor is gone
+            if in_flight.has_changed().is_err() {
+                break;
+            }
+            let Some(request) = *in_flight.borrow_and_update() else {
+                continue;
+            };
+            let running = request.started.elapsed();
+            if running > request.deadline + WATCHDOG_INTERVAL && reported != Some(request.started) {
+                reported = Some(request.started);
+                tracing::error!(
+                    "Session manager blocked on {} for {:?}; the API waits on it",
+                    request.command,
+                    running
+                );
+            }
         }
-    }
+    });
 }
 
 impl SessionManagerActor {
@@ -296,21 +925,54 @@ impl SessionManagerActor {
     }
 
     async fn run(mut self) {
+        // Restore registered projects before the cache discovers more
+        match self.storage.load_projects().await {
+            Ok(projects) => {
+                for project in projects {
+                    let mut loaded =
+                        Project::new(project.id, project.name, project.path, project.parent);
+                    loaded.defaults = project.defaults;
+                    self.projects.insert(loaded.id.clone(), loaded);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to load stored projects: {}", e),
+        }
+
         // Initialize the Claude projects cache
         match self.initialize_claude_cache().await {
             Ok(()) => tracing::info!("Claude projects cache initialized successfully"),
             Err(e) => tracing::warn!("Failed to initialize Claude projects cache: {}", e),
         }
 
+        let mut idle_check = tokio::time::interval(IDLE_CHECK_INTERVAL);
+        idle_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let end_idle_sessions = self.config.session.idle_timeout().is_some();
+        let request_timeout = self.config.server.request_timeout();
+        spawn_watchdog(self.in_flight_tx.subscribe());
+
         // Process commands and cleanup messages
         loop {
             tokio::select! {
                 Some(command) = self.command_rx.recv() => {
+                    let request = InFlight {
+                        command: command.name(),
+                        started: Instant::now(),
+                        deadline: command.deadline(request_timeout),
+                    };
+                    self.in_flight_tx.send_replace(Some(request));
+                    // Runs to the end even past its deadline: dropping it halfway
+                    // could leave an agent that was started but never registered,
+                    // or state that was changed but never stored. The caller has
+                    // given up by then, and the watchdog reports it.
                     self.handle_command(command).await;
+                    self.in_flight_tx.send_replace(None);
                 }
                 Some(cleanup_msg) = self.cleanup_rx.recv() => {
                     self.handle_cleanup(cleanup_msg).await;
                 }
+                _ = idle_check.tick(), if end_idle_sessions => {
+                    self.end_idle_sessions().await;
+                }
                 else => {
                     tracing::info!("SessionManager shutting down");
                     break;
@@ -375,22 +1037,19 @@ impl SessionManagerActor {
                     .any(|p| p.path.to_string_lossy() == project_path_str);
 
                 if !project_exists {
-                    // Auto-create project from cached session
-                    let project_name = session
-                        .project_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unnamed")
-                        .to_string();
-
+                    // Auto-create project from cached session, named after its repository
                     let project_id = Uuid::new_v4().to_string();
-                    let project = Project {
-                        id: project_id.clone(),
-                        name: project_name.clone(),
-                        path: session.project_path.clone(),
-                    };
+                    let mut project = Project::new(
+                        project_id.clone(),
+                        String::new(),
+                        session.project_path.clone(),
+                        None,
+                    );
+                    project.name = project.metadata.name_or_dir(&project.path);
+                    let project_name = project.name.clone();
 
-                    self.projects.insert(project_id, project);
+                    self.projects.insert(project_id.clone(), project);
+                    self.persist_project(&project_id).await;
                     tracing::info!(
                         "Auto-discovered project from cache: {} at {:?}",
                         project_name,
@@ -405,40 +1064,201 @@ impl SessionManagerActor {
 
     async fn handle_cleanup(&mut self, cleanup_msg: SessionCleanupMessage) {
         match cleanup_msg {
-            SessionCleanupMessage::SessionCompleted { session_id } => {
+            SessionCleanupMessage::SessionCompleted {
+                session_id,
+                exit_code,
+            } => {
                 tracing::info!("Cleaning up completed session: {}", session_id);
+                // Sessions that were closed or shut down are already gone, so
+                // this one exited on its own
                 if let Some(removed) = self.sessions.remove(&session_id) {
                     tracing::info!(
                         "Removed dead session {} (agent: {}) from session manager",
                         session_id,
                         removed.agent
                     );
+                    // After a handover the new server owns the session's history
+                    if !self.handed_over {
+                        self.record_session_exit(&session_id, &removed, exit_code)
+                            .await;
+                    }
                 } else {
                     tracing::warn!("Attempted to cleanup non-existent session: {}", session_id);
                 }
+                self.start_queued_sessions().await;
+            }
+            SessionCleanupMessage::KillGraceOver { session_id, token } => {
+                let still_killed = self.sessions.get(&session_id).is_some_and(|state| {
+                    state.pending_kill.as_ref().map(|kill| kill.token) == Some(token)
+                });
+                if still_killed {
+                    tracing::info!("Grace period of killed session {} is over", session_id);
+                    if let Err(e) = self.close_session(&session_id).await {
+                        tracing::warn!("Failed to end killed session {}: {}", session_id, e);
+                    }
+                    self.start_queued_sessions().await;
+                }
             }
         }
     }
 
-    async fn handle_command(&mut self, command: SessionCommand) {
-        match command {
-            SessionCommand::CreateSession {
-                agent,
-                args,
-                project_id,
-                path,
-                resume_session_id,
-                response_tx,
-            } => {
-                let result = self
-                    .create_session_with_path(agent, args, project_id, path, resume_session_id)
-                    .await;
-                let _ = response_tx.send(result);
-            }
-            SessionCommand::GetSession {
+    /// End the sessions that wrote nothing with nobody attached for longer
+    /// than `[session] idle_timeout_secs`, see `core::idle`
+    async fn end_idle_sessions(&mut self) {
+        let idle: Vec<(String, Duration)> = self
+            .sessions
+            .values()
+            .filter(|state| state.pending_kill.is_none())
+            .filter_map(|state| {
+                let idle_for = state.channels.idle.idle_for();
+                (idle_for >= state.idle_timeout?).then(|| (state.id.clone(), idle_for))
+            })
+            .collect();
+        if idle.is_empty() {
+            return;
+        }
+        for (session_id, idle_for) in idle {
+            tracing::info!(
+                "Ending session {}, idle for {} min",
+                session_id,
+                idle_for.as_secs() / 60
+            );
+            if let Err(e) = self.close_session(&session_id).await {
+                tracing::warn!("Failed to end idle session {}: {}", session_id, e);
+            }
+        }
+        self.start_queued_sessions().await;
+    }
+
+    /// Start queued creations that fit under `[limits]` now, oldest first
+    async fn start_queued_sessions(&mut self) {
+        let mut waiting = VecDeque::new();
+        while let Some(queued) = self.queued.pop_front() {
+            // The caller gave up waiting
+            if queued.response_tx.is_closed() {
+                continue;
+            }
+            let project_id = self.target_project(&queued.project_id, &queued.path);
+            let agent = self.target_agent(&queued.agent, project_id.as_deref());
+            if self.check_limits(&agent, project_id.as_deref()).is_err() {
+                waiting.push_back(queued);
+                continue;
+            }
+            tracing::info!("Starting queued {} session", agent);
+            let result = self
+                .create_session_with_path(
+                    queued.agent,
+                    queued.args,
+                    queued.project_id,
+                    queued.path,
+                    queued.resume_session_id,
+                    queued.launch,
+                )
+                .await;
+            let _ = queued.response_tx.send(result);
+        }
+        self.queued = waiting;
+    }
+
+    /// The registered project a new session would join, without creating one for `path`
+    fn target_project(&self, project_id: &Option<String>, path: &Option<String>) -> Option<String> {
+        match (project_id, path) {
+            (Some(project_id), _) => Some(project_id.clone()),
+            (None, Some(path)) => self
+                .projects
+                .values()
+                .find(|p| p.path.to_string_lossy() == path.as_str())
+                .map(|p| p.id.clone()),
+            (None, None) => None,
+        }
+    }
+
+    /// The agent a new session would run: `agent`, or the project's default
+    /// agent when none is given
+    fn target_agent(&self, agent: &str, project_id: Option<&str>) -> String {
+        if !agent.is_empty() {
+            return agent.to_string();
+        }
+        project_id
+            .and_then(|project_id| self.projects.get(project_id))
+            .and_then(|project| project.defaults.agent.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether one more `agent` session in `project_id` fits under the configured caps
+    fn check_limits(
+        &self,
+        agent: &str,
+        project_id: Option<&str>,
+    ) -> std::result::Result<(), SessionLimitError> {
+        let running = RunningSessions {
+            total: self.sessions.len(),
+            in_project: project_id.map_or(0, |project_id| {
+                self.sessions
+                    .values()
+                    .filter(|s| s.project_id.as_deref() == Some(project_id))
+                    .count()
+            }),
+            of_agent: self.sessions.values().filter(|s| s.agent == agent).count(),
+        };
+        let agent_limit = self
+            .config
+            .agents
+            .get(agent)
+            .and_then(|agent| agent.max_sessions);
+        self.config.limits.check(running, agent, agent_limit)
+    }
+
+    async fn handle_command(&mut self, command: SessionCommand) {
+        match command {
+            SessionCommand::CreateSession {
+                agent,
+                args,
+                project_id,
+                path,
+                resume_session_id,
+                launch,
+                response_tx,
+            } => {
+                let target_project = self.target_project(&project_id, &path);
+                let target_agent = self.target_agent(&agent, target_project.as_deref());
+                if let Err(e) = self.check_limits(&target_agent, target_project.as_deref()) {
+                    if self.config.limits.queue {
+                        tracing::info!("{}; queueing the new {} session", e, target_agent);
+                        self.queued.push_back(QueuedSession {
+                            agent,
+                            args,
+                            project_id,
+                            path,
+                            resume_session_id,
+                            launch: *launch,
+                            response_tx,
+                        });
+                    } else {
+                        let _ = response_tx.send(Err(e.into()));
+                    }
+                    return;
+                }
+                let result = self
+                    .create_session_with_path(
+                        agent,
+                        args,
+                        project_id,
+                        path,
+                        resume_session_id,
+                        *launch,
+                    )
+                    .await;
+                let _ = response_tx.send(result);
+            }
+            SessionCommand::GetSessionOptions { response_tx } => {
+                let _ = response_tx.send(self.session_options());
+            }
+            SessionCommand::GetSession {
                 session_id,
                 response_tx,
             } => {
+                let session_id = self.resolve_session_id(&session_id);
                 let result = self.get_session(&session_id).await;
                 let _ = response_tx.send(result);
             }
@@ -446,6 +1266,7 @@ impl SessionManagerActor {
                 session_id,
                 response_tx,
             } => {
+                let session_id = self.resolve_session_id(&session_id);
                 let result = self.get_session_channels(&session_id);
                 let _ = response_tx.send(result);
             }
@@ -453,11 +1274,50 @@ impl SessionManagerActor {
                 let result = self.list_sessions();
                 let _ = response_tx.send(result);
             }
+            SessionCommand::Ping { response_tx } => {
+                let _ = response_tx.send(ManagerStatus {
+                    running_sessions: self.sessions.len(),
+                    accepting_sessions: !self.handed_over,
+                });
+            }
+            SessionCommand::ListEndedSessions { response_tx } => {
+                let result = self.list_ended_sessions().await;
+                let _ = response_tx.send(result);
+            }
             SessionCommand::CloseSession {
                 session_id,
+                now,
                 response_tx,
             } => {
-                let result = self.close_session(&session_id).await;
+                let session_id = self.resolve_session_id(&session_id);
+                let result = match self.config.recycle_bin.grace() {
+                    Some(grace) if !now => self.kill_later(&session_id, grace),
+                    _ => self.close_session(&session_id).await.map(|()| None),
+                };
+                let _ = response_tx.send(result);
+            }
+            SessionCommand::UndoKill {
+                session_id,
+                response_tx,
+            } => {
+                let session_id = self.resolve_session_id(&session_id);
+                let _ = response_tx.send(self.undo_kill(&session_id).await);
+            }
+            SessionCommand::SetSessionNote {
+                session_id,
+                note,
+                response_tx,
+            } => {
+                let session_id = self.resolve_session_id(&session_id);
+                let result = match self.sessions.get_mut(&session_id) {
+                    Some(state) => {
+                        state.note = note
+                            .map(|note| note.trim().to_string())
+                            .filter(|note| !note.is_empty());
+                        self.get_session(&session_id).await
+                    }
+                    None => None,
+                };
                 let _ = response_tx.send(result);
             }
             SessionCommand::ResumeSession {
@@ -475,15 +1335,33 @@ impl SessionManagerActor {
             SessionCommand::CreateProject {
                 name,
                 path,
+                parent,
                 response_tx,
             } => {
-                let result = self.create_project(name, path);
+                let result = self.create_project(name, path, parent).await;
                 let _ = response_tx.send(result);
             }
             SessionCommand::ListProjects { response_tx } => {
                 let result = self.list_projects();
                 let _ = response_tx.send(result);
             }
+            SessionCommand::RemoveProject {
+                project,
+                kill_sessions,
+                response_tx,
+            } => {
+                let result = self.remove_project(&project, kill_sessions).await;
+                let _ = response_tx.send(result);
+            }
+            SessionCommand::UpdateProject {
+                project,
+                name,
+                defaults,
+                response_tx,
+            } => {
+                let result = self.update_project(&project, name, defaults).await;
+                let _ = response_tx.send(result);
+            }
             SessionCommand::GetRecentProjectSessions {
                 project_path,
                 response_tx,
@@ -491,7 +1369,43 @@ impl SessionManagerActor {
                 let result = self.get_recent_project_sessions(&project_path).await;
                 let _ = response_tx.send(result);
             }
+            SessionCommand::ListConversations {
+                project_path,
+                response_tx,
+            } => {
+                let mut conversations = match &self.claude_cache {
+                    Some(cache) => cache.get_project_sessions(&project_path).await,
+                    None => Vec::new(),
+                };
+                conversations.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
+                let _ = response_tx.send(conversations);
+            }
+            SessionCommand::Handover { response_tx } => {
+                // The new server would adopt paused agents nobody can bring back
+                let killed: Vec<String> = self
+                    .sessions
+                    .values()
+                    .filter(|state| state.pending_kill.is_some())
+                    .map(|state| state.id.clone())
+                    .collect();
+                for session_id in killed {
+                    let _ = self.close_session(&session_id).await;
+                }
+                let _ = response_tx.send(self.handover());
+            }
+            SessionCommand::Digest {
+                since,
+                until,
+                response_tx,
+            } => {
+                let _ = response_tx.send(self.digest(since, until).await);
+            }
+            SessionCommand::SearchableSessions { response_tx } => {
+                let _ = response_tx.send(self.searchable_sessions().await);
+            }
             SessionCommand::ShutdownAllSessions { response_tx } => {
+                // Dropping the queued requests fails them
+                self.queued.clear();
                 self.shutdown_all_sessions().await;
                 let _ = response_tx.send(());
             }
@@ -505,20 +1419,78 @@ impl SessionManagerActor {
         project_id: Option<String>,
         path: Option<String>,
         resume_session_id: Option<String>,
+        mut launch: LaunchOptions,
     ) -> Result<SessionResource> {
-        if !self.config.is_agent_allowed(&agent) {
+        if self.handed_over {
+            return Err(anyhow!(
+                "The server is being upgraded; try again in a moment"
+            ));
+        }
+        // The project's defaults fill in the agent, arguments and env the
+        // session leaves out
+        let defaults = self
+            .target_project(&project_id, &path)
+            .and_then(|id| self.projects.get(&id))
+            .map(|project| project.defaults.clone());
+        let agent = if agent.is_empty() {
+            defaults
+                .as_ref()
+                .and_then(|defaults| defaults.agent.clone())
+                .ok_or_else(|| anyhow!("No agent given, and the project has no default agent"))?
+        } else {
+            agent
+        };
+        let args = match &defaults {
+            Some(defaults) => defaults.apply(&agent, args, &mut launch.env),
+            None => args,
+        };
+        // The demo agent is part of the binary, so it only exists with the capture feature
+        let is_demo = cfg!(feature = "capture") && agent == DEMO_AGENT;
+        if !is_demo && !self.config.is_agent_allowed(&agent) {
             return Err(anyhow!("Code agent '{}' is not whitelisted", agent));
         }
+        let resolved = launch::resolve_launch(&agent, args, &launch, &self.config.templates)?;
+        let agent_config = self.config.agents.get(&agent).cloned().unwrap_or_default();
+        let approval_patterns = agent_config
+            .approval_patterns()
+            .map_err(|e| anyhow!("[agents.{}]: {}", agent, e))?;
+        let missing_env = self.config.missing_agent_env(&agent, &resolved.env);
+        if launch.adopt_pid.is_none() && !missing_env.is_empty() {
+            return Err(anyhow!(
+                "{} needs {} set in the server's environment or the session's env",
+                agent,
+                missing_env.join(", ")
+            ));
+        }
+        if let Some(color) = &launch.color {
+            validate_session_color(color)?;
+        }
+        let name = launch.name.clone().filter(|name| !name.is_empty());
+        if let Some(name) = &name {
+            validate_session_name(name)?;
+            let taken = self
+                .sessions
+                .values()
+                .any(|state| state.id == *name || state.name.as_deref() == Some(name));
+            if taken {
+                return Err(anyhow!("A running session is already called '{}'", name));
+            }
+        }
 
         // Use provided resume session ID or generate new one
         let (session_id, is_resuming) = match resume_session_id {
             Some(id) => (id, true),
             None => (Uuid::new_v4().to_string(), false),
         };
+        // Links of a session taken over from another server were checked there
+        let links = match is_resuming && launch.adopt_pid.is_some() {
+            true => launch.links.clone(),
+            false => self.session_links(&launch).await?,
+        };
 
         // Add session ID to args if the agent is Claude
         // Only add --session-id if we're NOT resuming (resume already has the session ID)
-        let mut final_args = args.clone();
+        let mut final_args = resolved.args;
         if agent.to_lowercase() == "claude" && !is_resuming {
             final_args.push("--session-id".to_string());
             final_args.push(session_id.clone());
@@ -545,21 +1517,15 @@ impl SessionManagerActor {
                 (Some(existing_id), Some(path_buf))
             } else {
                 // Create temporary project for this path
-                let project_name = path_buf
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("Unknown")
-                    .to_string();
-
                 let temp_project_id = Uuid::new_v4().to_string();
-                self.projects.insert(
+                let mut project = Project::new(
                     temp_project_id.clone(),
-                    Project {
-                        id: temp_project_id.clone(),
-                        name: format!("{} (temporary)", project_name),
-                        path: path_buf.clone(),
-                    },
+                    String::new(),
+                    path_buf.clone(),
+                    None,
                 );
+                project.name = format!("{} (temporary)", project.metadata.name_or_dir(&path_buf));
+                self.projects.insert(temp_project_id.clone(), project);
 
                 (Some(temp_project_id), Some(path_buf))
             }
@@ -570,23 +1536,104 @@ impl SessionManagerActor {
             (None, Some(current_dir))
         };
 
+        let mut working_dir = working_dir.expect("working_dir should always be Some");
+        if resolved.worktree && launch.adopt_pid.is_some() {
+            return Err(anyhow!(
+                "An adopted process keeps its own directory; worktree can't be used"
+            ));
+        }
+        let project_dir = working_dir.clone();
+        if resolved.worktree {
+            working_dir =
+                launch::create_worktree(&working_dir, &self.config.server.data_dir, &session_id)?;
+        }
+        // The agent may start further down; templates and watchers stay with the project
+        let agent_dir = match &launch.cwd {
+            Some(cwd) => launch::resolve_cwd(&project_dir, &working_dir, cwd)?,
+            None => working_dir.clone(),
+        };
+
         tracing::debug!(
             "SessionManager - Creating PTY session with ID: {}, agent: {}",
             session_id,
             agent
         );
-        let (session, channels) = PtySession::new(
-            session_id.clone(),
-            agent.clone(),
-            final_args,
-            working_dir.expect("working_dir should always be Some"),
-        )?;
+        // An adopted agent already read its config
+        if launch.adopt_pid.is_none() && !is_demo {
+            let project = resolved_project_id
+                .as_ref()
+                .and_then(|id| self.projects.get(id))
+                .map(|project| project.name.clone());
+            self.sync_agent_config(&agent, project, &working_dir).await;
+        }
+        let session_dir = working_dir.clone();
+        let recorded_args = launch.record.then(|| final_args.clone());
+        let (program, final_args) = match launch.adopt_pid {
+            Some(pid) => adopt::adopt_command(pid, &agent, self.config.agent_command(&agent))?,
+            #[cfg(feature = "capture")]
+            None if is_demo => demo::demo_command(final_args)?,
+            None => {
+                let program = self.config.agent_command(&agent).to_string();
+                let args = agent_config
+                    .args
+                    .iter()
+                    .cloned()
+                    .chain(final_args)
+                    .collect();
+                (program, args)
+            }
+        };
+        // Opening the PTY and starting the agent block, so not on the actor's task
+        let (mut session, channels) = {
+            let (session_id, agent) = (session_id.clone(), agent.clone());
+            let (env, agent_dir) = (resolved.env.clone(), agent_dir.clone());
+            tokio::task::spawn_blocking(move || {
+                PtySession::new_with_program(
+                    session_id, agent, &program, final_args, &env, agent_dir,
+                )
+            })
+            .await??
+        };
+        session.set_max_paste_bytes(self.config.paste.max_bytes);
+        session.set_input_mode(launch.input_mode.unwrap_or(self.config.input.mode));
+        session.set_approval_patterns(approval_patterns);
+        let pid = launch.adopt_pid.or(session.pid());
         tracing::debug!(
             "SessionManager - PTY session created, channels available, spawning start task"
         );
 
         // Clone channels for storage
         let channels_clone = channels.clone();
+        let hook_session = self.hook_session(
+            &session_id,
+            &agent,
+            resolved_project_id.as_deref(),
+            &session_dir,
+            name.as_deref(),
+        );
+        self.watch_for_questions(hook_session.clone(), &channels);
+        self.watch_for_commits(
+            &session_id,
+            &agent,
+            resolved_project_id.as_deref(),
+            &session_dir,
+            &channels,
+        );
+        self.guard_deletions(
+            &session_id,
+            resolved_project_id.as_deref(),
+            &session_dir,
+            &channels,
+        );
+        self.checkpoint_turns(
+            &session_id,
+            resolved_project_id.as_deref(),
+            &session_dir,
+            &channels,
+        );
+        if let Some(args) = recorded_args {
+            self.record_session(&session_id, &agent, args, &channels)?;
+        }
 
         // Create a cleanup handle for session management
         let session_id_for_cleanup = session_id.clone();
@@ -594,6 +1641,7 @@ impl SessionManagerActor {
 
         // Spawn the PTY session start task to actually begin reading from the PTY
         let session_id_clone = session_id.clone();
+        let exit_tx = channels.exit_tx.clone();
         tokio::spawn(async move {
             tracing::info!(
                 "SessionManager - Starting PTY session tasks for {}",
@@ -614,23 +1662,68 @@ impl SessionManagerActor {
             // Notify session manager to clean up this session
             if let Err(e) = cleanup_tx.send(SessionCleanupMessage::SessionCompleted {
                 session_id: session_id_for_cleanup,
+                exit_code: *exit_tx.borrow(),
             }) {
                 tracing::warn!("Failed to send session cleanup notification: {}", e);
             }
         });
 
         // Store the session state
+        let color = launch
+            .color
+            .clone()
+            .unwrap_or_else(|| default_session_color(&session_id));
+        let icon = launch
+            .icon
+            .clone()
+            .filter(|icon| !icon.trim().is_empty())
+            .unwrap_or_else(|| default_session_icon(&agent));
+        let cwd = launch.cwd.as_ref().map(|_| agent_dir.display().to_string());
         let session_state = SessionState {
             id: session_id.clone(),
             agent: agent.clone(),
             channels: channels_clone,
             project_id: resolved_project_id.clone(),
+            color: color.clone(),
+            icon: icon.clone(),
+            pid,
+            working_dir: session_dir,
+            cwd: cwd.clone(),
+            record: launch.record,
+            keep_alive: launch.keep_alive,
+            idle_timeout: self
+                .config
+                .session
+                .idle_timeout()
+                .filter(|_| !launch.keep_alive),
+            note: None,
+            name: name.clone(),
+            links: links.clone(),
+            pending_kill: None,
         };
         self.sessions.insert(session_id.clone(), session_state);
         tracing::info!(
             "SessionManager - Session {} stored successfully, channels ready for use",
             session_id
         );
+        self.fire_hooks(HookPayload::new(HookEvent::SessionCreated, hook_session));
+
+        let record = SessionRecord {
+            id: session_id.clone(),
+            agent: agent.clone(),
+            project: resolved_project_id.clone(),
+            color: color.clone(),
+            icon: icon.clone(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            ended_at: None,
+            exited: false,
+            exit_code: None,
+            links: links.clone(),
+            cwd: cwd.clone(),
+        };
+        if let Err(e) = self.storage.save_session(&record).await {
+            tracing::warn!("Failed to add session {} to the history: {}", session_id, e);
+        }
 
         Ok(SessionResource {
             resource_type: "session".to_string(),
@@ -642,27 +1735,67 @@ impl SessionManagerActor {
                 session_type: SessionType::Active,
                 last_modified: Some(chrono::Utc::now().to_rfc3339()),
                 last_message: None, // Active sessions don't have historical messages
+                color,
+                icon,
+                note: None,
+                name,
+                terminates_at: None,
+                idle_expires_at: None,
+                diff_stats: None,
+                exit_code: None,
             }),
-            relationships: None,
+            relationships: SessionRelationships::from_links(&links),
         })
     }
 
+    /// Links of a new session: the ones asked for, pointing at session IDs
+    /// even when given by name, and the template it starts from
+    async fn session_links(&self, launch: &LaunchOptions) -> Result<Vec<SessionLink>> {
+        let mut links: Vec<SessionLink> = launch
+            .links
+            .iter()
+            .map(|link| SessionLink {
+                kind: link.kind,
+                id: match link.kind.links_session() {
+                    true => self.resolve_session_id(&link.id),
+                    false => link.id.clone(),
+                },
+            })
+            .collect();
+        let history = match links.iter().any(|link| link.kind.links_session()) {
+            true => self.storage.load_sessions().await?,
+            false => Vec::new(),
+        };
+        session_links::validate_links(&links, |id| {
+            self.sessions.contains_key(id) || history.iter().any(|record| record.id == id)
+        })?;
+        if let Some(template) = &launch.template {
+            links.retain(|link| link.kind.links_session());
+            links.push(SessionLink {
+                kind: SessionLinkKind::SpawnedFromTemplate,
+                id: template.clone(),
+            });
+        }
+        Ok(links)
+    }
+
+    /// ID of the running session with this ID or name; anything else is
+    /// returned as it is, so historical sessions are still found by ID
+    fn resolve_session_id(&self, id_or_name: &str) -> String {
+        if self.sessions.contains_key(id_or_name) {
+            return id_or_name.to_string();
+        }
+        self.sessions
+            .values()
+            .find(|state| state.name.as_deref() == Some(id_or_name))
+            .map(|state| state.id.clone())
+            .unwrap_or_else(|| id_or_name.to_string())
+    }
+
     async fn get_session(&self, session_id: &str) -> Option<SessionResource> {
         // First check active sessions
         if let Some(state) = self.sessions.get(session_id) {
-            return Some(SessionResource {
-                resource_type: "session".to_string(),
-                id: state.id.clone(),
-                attributes: Some(SessionAttributes {
-                    agent: state.agent.clone(),
-                    project: state.project_id.clone(),
-                    status: "running".to_string(),
-                    session_type: SessionType::Active,
-                    last_modified: Some(chrono::Utc::now().to_rfc3339()),
-                    last_message: None, // Active sessions don't have historical messages
-                }),
-                relationships: None,
-            });
+            return Some(state.resource());
         }
 
         // If not active, check the cache for historical sessions
@@ -674,6 +1807,8 @@ impl SessionManagerActor {
                     .values()
                     .find(|p| p.path == cached_session.project_path)
                     .map(|p| p.id.clone());
+                let color = default_session_color(&cached_session.session_id);
+                let icon = default_session_icon(&cached_session.agent);
 
                 return Some(SessionResource {
                     resource_type: "session".to_string(),
@@ -690,13 +1825,35 @@ impl SessionManagerActor {
                         session_type: SessionType::Historical,
                         last_modified: Some(cached_session.last_modified.to_rfc3339()),
                         last_message: cached_session.last_message.clone(),
+                        color,
+                        icon,
+                        note: None,
+                        name: None,
+                        terminates_at: None,
+                        idle_expires_at: None,
+                        diff_stats: None,
+                        exit_code: None,
                     }),
                     relationships: None,
                 });
             }
         }
 
-        None
+        // Sessions that ended recently are still in the history
+        let records = self.storage.load_sessions().await.ok()?;
+        records
+            .into_iter()
+            .find(|record| record.id == session_id && self.recently_ended(record))
+            .map(ended_session_resource)
+    }
+
+    /// Whether `record` is of a session that ended within the `[recycle_bin]` retention
+    fn recently_ended(&self, record: &SessionRecord) -> bool {
+        record.ended_at.as_deref().is_some_and(|ended_at| {
+            self.config
+                .recycle_bin
+                .retains(ended_at, chrono::Utc::now())
+        })
     }
 
     fn get_session_channels(&mut self, session_id: &str) -> Option<PtyChannels> {
@@ -738,22 +1895,20 @@ impl SessionManagerActor {
     }
 
     fn list_sessions(&self) -> Vec<SessionResource> {
-        self.sessions
-            .values()
-            .map(|state| SessionResource {
-                resource_type: "session".to_string(),
-                id: state.id.clone(),
-                attributes: Some(SessionAttributes {
-                    agent: state.agent.clone(),
-                    project: state.project_id.clone(),
-                    status: "running".to_string(),
-                    session_type: SessionType::Active,
-                    last_modified: Some(chrono::Utc::now().to_rfc3339()),
-                    last_message: None, // Active sessions don't have historical messages
-                }),
-                relationships: None,
-            })
-            .collect()
+        self.sessions.values().map(SessionState::resource).collect()
+    }
+
+    async fn list_ended_sessions(&self) -> Vec<SessionResource> {
+        let mut records: Vec<SessionRecord> = self
+            .storage
+            .load_sessions()
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|record| !self.sessions.contains_key(&record.id) && self.recently_ended(record))
+            .collect();
+        records.sort_by(|a, b| b.ended_at.cmp(&a.ended_at));
+        records.into_iter().map(ended_session_resource).collect()
     }
 
     async fn resume_session(
@@ -764,6 +1919,11 @@ impl SessionManagerActor {
         project_id: Option<String>,
     ) -> Result<SessionResource> {
         tracing::info!("Resuming session {}", session_id);
+        if self.handed_over {
+            return Err(anyhow!(
+                "The server is being upgraded; try again in a moment"
+            ));
+        }
 
         // First, check if the session is already active
         if self.sessions.contains_key(&session_id) {
@@ -776,6 +1936,9 @@ impl SessionManagerActor {
             }
         }
 
+        // Resuming starts a process too, but is never queued
+        self.check_limits(&agent, project_id.as_deref())?;
+
         // Check if we have stored session info for this session ID
         // For now, we'll create a new PTY session with the provided parameters
         // In a full implementation, we might want to restore from persisted JSONL files
@@ -809,28 +1972,107 @@ impl SessionManagerActor {
                     session_id
                 );
             }
+        } else if let Some(flags) =
+            agent_adapter::resume_args(&agent, self.config.agents.get(&agent), None)
+        {
+            // Other agents don't know codemux's session IDs, but the latest
+            // conversation in the session's directory is the session's
+            if !resume_args.iter().any(|arg| flags.contains(arg)) {
+                resume_args.extend(flags);
+            }
         }
 
         tracing::info!("Creating new PTY session for resumed session {} with resume args: {:?} in directory: {:?}", session_id, resume_args, project_path);
 
-        let (pty_session, channels) = PtySession::new(
-            session_id.clone(),
-            agent.clone(),
-            resume_args,
-            project_path.unwrap_or_else(|| {
-                std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
-            }),
-        )?;
+        let working_dir = project_path.unwrap_or_else(|| {
+            std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
+        });
+        let project = project_id
+            .as_ref()
+            .and_then(|id| self.projects.get(id))
+            .map(|project| project.name.clone());
+        self.sync_agent_config(&agent, project, &working_dir).await;
+        // A session started further down the project resumes there, where the
+        // agent keeps its conversation
+        let cwd = match self.storage.load_sessions().await {
+            Ok(records) => records
+                .into_iter()
+                .find(|record| record.id == session_id)
+                .and_then(|record| record.cwd)
+                .filter(|dir| std::path::Path::new(dir).is_dir()),
+            Err(e) => {
+                tracing::warn!("Failed to read the session history: {}", e);
+                None
+            }
+        };
+        let agent_config = self.config.agents.get(&agent).cloned().unwrap_or_default();
+        let approval_patterns = agent_config
+            .approval_patterns()
+            .map_err(|e| anyhow!("[agents.{}]: {}", agent, e))?;
+        let (mut pty_session, channels) = {
+            let (session_id, agent) = (session_id.clone(), agent.clone());
+            let program = self.config.agent_command(&agent).to_string();
+            let args = agent_config.args.into_iter().chain(resume_args).collect();
+            let dir = cwd
+                .as_ref()
+                .map_or_else(|| working_dir.clone(), PathBuf::from);
+            tokio::task::spawn_blocking(move || {
+                PtySession::new_with_program(
+                    session_id,
+                    agent,
+                    &program,
+                    args,
+                    &Default::default(),
+                    dir,
+                )
+            })
+            .await??
+        };
+        pty_session.set_max_paste_bytes(self.config.paste.max_bytes);
+        pty_session.set_input_mode(self.config.input.mode);
+        pty_session.set_approval_patterns(approval_patterns);
 
         // Store the session with the specific session_id
+        let color = default_session_color(&session_id);
+        let icon = default_session_icon(&agent);
         let session_state = SessionState {
             id: session_id.clone(),
             agent: agent.clone(),
             channels: channels.clone(),
             project_id: project_id.clone(),
+            color: color.clone(),
+            icon: icon.clone(),
+            pid: pty_session.pid(),
+            working_dir: working_dir.clone(),
+            cwd,
+            record: false,
+            keep_alive: false,
+            idle_timeout: self.config.session.idle_timeout(),
+            note: None,
+            name: None,
+            links: Vec::new(),
+            pending_kill: None,
         };
 
         self.sessions.insert(session_id.clone(), session_state);
+        let hook_session = self.hook_session(
+            &session_id,
+            &agent,
+            project_id.as_deref(),
+            &working_dir,
+            None,
+        );
+        self.watch_for_questions(hook_session.clone(), &channels);
+        self.fire_hooks(HookPayload::new(HookEvent::SessionCreated, hook_session));
+        self.watch_for_commits(
+            &session_id,
+            &agent,
+            project_id.as_deref(),
+            &working_dir,
+            &channels,
+        );
+        self.guard_deletions(&session_id, project_id.as_deref(), &working_dir, &channels);
+        self.checkpoint_turns(&session_id, project_id.as_deref(), &working_dir, &channels);
 
         // Create cleanup handle for resumed session
         let session_id_for_cleanup = session_id.clone();
@@ -838,6 +2080,7 @@ impl SessionManagerActor {
 
         // Spawn the PTY session start task
         let session_id_clone = session_id.clone();
+        let exit_tx = channels.exit_tx.clone();
         tokio::spawn(async move {
             tracing::info!("Starting resumed PTY session {}", session_id_clone);
             if let Err(e) = pty_session.start().await {
@@ -848,6 +2091,7 @@ impl SessionManagerActor {
             // Notify session manager to clean up this session
             if let Err(e) = cleanup_tx.send(SessionCleanupMessage::SessionCompleted {
                 session_id: session_id_for_cleanup,
+                exit_code: *exit_tx.borrow(),
             }) {
                 tracing::warn!("Failed to send resumed session cleanup notification: {}", e);
             }
@@ -866,11 +2110,45 @@ impl SessionManagerActor {
                 session_type: SessionType::Active,
                 last_modified: Some(chrono::Utc::now().to_rfc3339()),
                 last_message: None, // Active sessions don't have historical messages
+                color,
+                icon,
+                note: None,
+                name: None,
+                terminates_at: None,
+                idle_expires_at: None,
+                diff_stats: None,
+                exit_code: None,
             }),
             relationships: None,
         })
     }
 
+    fn handover(&mut self) -> Vec<HandoverSession> {
+        self.handed_over = true;
+        // Queued creations would never start here
+        self.queued.clear();
+        self.sessions
+            .values()
+            .filter_map(|state| {
+                Some(HandoverSession {
+                    id: state.id.clone(),
+                    agent: state.agent.clone(),
+                    path: state.working_dir.clone(),
+                    cwd: state.cwd.clone(),
+                    record: state.record,
+                    keep_alive: state.keep_alive,
+                    color: state.color.clone(),
+                    icon: state.icon.clone(),
+                    pid: state.pid?,
+                    note: state.note.clone(),
+                    name: state.name.clone(),
+                    links: state.links.clone(),
+                    input_mode: state.channels.input_control.state().mode,
+                })
+            })
+            .collect()
+    }
+
     async fn close_session(&mut self, session_id: &str) -> Result<()> {
         if let Some(state) = self.sessions.remove(session_id) {
             // Send terminate signal
@@ -885,53 +2163,488 @@ impl SessionManagerActor {
                     e
                 );
             }
+            // A paused agent only notices the closed terminal once it runs again
+            if let (Some(_), Some(pid)) = (&state.pending_kill, state.pid) {
+                shutdown::signal_agent(pid, Signal::Continue);
+            }
+            self.record_session_end(session_id, false, None).await;
+            let hook_session = self.hook_session(
+                session_id,
+                &state.agent,
+                state.project_id.as_deref(),
+                &state.working_dir,
+                state.name.as_deref(),
+            );
+            self.fire_hooks(HookPayload::new(HookEvent::SessionKilled, hook_session));
             Ok(())
         } else {
             Err(anyhow!("Session not found"))
         }
     }
 
-    fn create_project(&mut self, name: String, path: String) -> Result<ProjectResource> {
+    /// Pause a killed session's agent and end the session once `grace` is
+    /// over, unless the kill is undone; returns when it ends
+    fn kill_later(&mut self, session_id: &str, grace: Duration) -> Result<Option<String>> {
+        let Some(state) = self.sessions.get_mut(session_id) else {
+            return Err(anyhow!("Session not found"));
+        };
+        if let Some(kill) = &state.pending_kill {
+            return Ok(Some(kill.ends_at.clone()));
+        }
+        self.kill_token += 1;
+        let token = self.kill_token;
+        if let Some(pid) = state.pid {
+            shutdown::signal_agent(pid, Signal::Stop);
+        }
+        let ends_at =
+            (chrono::Utc::now() + chrono::Duration::seconds(grace.as_secs() as i64)).to_rfc3339();
+        state.pending_kill = Some(PendingKill {
+            token,
+            ends_at: ends_at.clone(),
+        });
+        let _ = state.channels.alert_tx.send(format!(
+            "This session was killed and ends in {}s; `codemux undo-kill {}` keeps it running",
+            grace.as_secs(),
+            state.name.as_deref().unwrap_or(session_id)
+        ));
+        tracing::info!("Session {} killed, ending it in {:?}", session_id, grace);
+
+        let cleanup_tx = self.cleanup_tx.clone();
+        let session_id = session_id.to_string();
+        tokio::spawn(async move {
+            tokio::time::sleep(grace).await;
+            let _ = cleanup_tx.send(SessionCleanupMessage::KillGraceOver { session_id, token });
+        });
+        Ok(Some(ends_at))
+    }
+
+    /// Let a killed session carry on where its agent was paused
+    async fn undo_kill(&mut self, session_id: &str) -> Result<Option<SessionResource>> {
+        if let Some(state) = self.sessions.get_mut(session_id) {
+            if state.pending_kill.take().is_none() {
+                return Err(anyhow!(
+                    "Session {} is running and wasn't killed",
+                    session_id
+                ));
+            }
+            if let Some(pid) = state.pid {
+                shutdown::signal_agent(pid, Signal::Continue);
+            }
+            let _ = state
+                .channels
+                .alert_tx
+                .send("The kill was undone; this session keeps running".to_string());
+            tracing::info!("Kill of session {} undone", session_id);
+            return Ok(Some(state.resource()));
+        }
+        let ended = self
+            .storage
+            .load_sessions()
+            .await?
+            .into_iter()
+            .find(|record| record.id == session_id)
+            .and_then(|record| record.ended_at);
+        match ended {
+            Some(ended_at) => Err(anyhow!(
+                "Session {} already ended at {}; it can't be brought back",
+                session_id,
+                ended_at
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Record that the agent of a session quit by itself, and tell whoever is listening
+    async fn record_session_exit(
+        &self,
+        session_id: &str,
+        removed: &SessionState,
+        exit_code: Option<u32>,
+    ) {
+        self.record_session_end(session_id, true, exit_code).await;
+        let mut payload = HookPayload::new(
+            HookEvent::AgentExited,
+            self.hook_session(
+                session_id,
+                &removed.agent,
+                removed.project_id.as_deref(),
+                &removed.working_dir,
+                removed.name.as_deref(),
+            ),
+        );
+        payload.exit_code = exit_code;
+        self.fire_hooks(payload);
+        let message = match exit_code {
+            Some(code) if code != 0 => {
+                format!("{} session exited with code {}", removed.agent, code)
+            }
+            _ => format!("{} session exited", removed.agent),
+        };
+        let notification = Notification::new(NotificationKind::Exited, session_id, message);
+        let project = removed
+            .project_id
+            .as_ref()
+            .and_then(|id| self.projects.get(id))
+            .map(|project| project.name.as_str());
+        if let Err(e) = record_notification(
+            self.storage.as_ref(),
+            &self.config.notifications,
+            project,
+            notification,
+        )
+        .await
+        {
+            tracing::warn!("Failed to store exit notification: {}", e);
+        }
+    }
+
+    /// What `[[hooks]]` are told about a session
+    fn hook_session(
+        &self,
+        session_id: &str,
+        agent: &str,
+        project_id: Option<&str>,
+        working_dir: &std::path::Path,
+        name: Option<&str>,
+    ) -> HookSession {
+        HookSession {
+            session_id: session_id.to_string(),
+            agent: agent.to_string(),
+            project: project_id
+                .and_then(|id| self.projects.get(id))
+                .map(|project| project.name.clone()),
+            path: working_dir.to_path_buf(),
+            name: name.map(str::to_string),
+        }
+    }
+
+    fn fire_hooks(&self, payload: HookPayload) {
+        hooks::fire(&self.config.hooks, payload);
+    }
+
+    async fn record_session_end(&self, session_id: &str, exited: bool, exit_code: Option<u32>) {
+        if let Err(e) = self
+            .storage
+            .end_session(session_id, exited, exit_code)
+            .await
+        {
+            tracing::warn!("Failed to record the end of session {}: {}", session_id, e);
+        }
+    }
+
+    async fn digest(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        until: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Digest> {
+        let records = self.storage.load_sessions().await?;
+        let notifications = self.storage.load_notifications().await?;
+        let parse = |time: &str| {
+            chrono::DateTime::parse_from_rfc3339(time)
+                .ok()
+                .map(|time| time.with_timezone(&chrono::Utc))
+        };
+
+        let sessions: Vec<DigestSession> = records
+            .into_iter()
+            .filter_map(|record| {
+                let ended_at = record.ended_at.as_deref().and_then(parse);
+                let outcome = if self.sessions.contains_key(&record.id) {
+                    SessionOutcome::Running
+                } else if ended_at.is_none() {
+                    SessionOutcome::Unknown
+                } else if record.exited {
+                    SessionOutcome::Exited
+                } else {
+                    SessionOutcome::Stopped
+                };
+                let questions = notifications
+                    .iter()
+                    .filter(|n| n.session_id == record.id)
+                    .filter(|n| n.kind == NotificationKind::WaitingForInput)
+                    .filter(|n| parse(&n.created_at).is_some_and(|at| since <= at && at < until))
+                    .count();
+                Some(DigestSession {
+                    started_at: parse(&record.created_at)?,
+                    // Without a recorded end, only a running session is known to overlap
+                    ended_at: match outcome {
+                        SessionOutcome::Unknown => parse(&record.created_at),
+                        _ => ended_at,
+                    },
+                    project: record
+                        .project
+                        .as_ref()
+                        .and_then(|id| self.projects.get(id))
+                        .map(|project| project.name.clone()),
+                    id: record.id,
+                    agent: record.agent,
+                    icon: record.icon,
+                    outcome,
+                    questions,
+                })
+            })
+            .collect();
+        Ok(Digest::build(since, until, &sessions))
+    }
+
+    async fn searchable_sessions(&self) -> Result<Vec<SearchableSession>> {
+        let records = self.storage.load_sessions().await?;
+        let project = |id: Option<&String>| id.and_then(|id| self.projects.get(id));
+
+        let mut sessions: Vec<SearchableSession> = self
+            .sessions
+            .values()
+            .map(|state| SearchableSession {
+                id: state.id.clone(),
+                agent: state.agent.clone(),
+                project: project(state.project_id.as_ref()).map(|project| project.name.clone()),
+                dir: state
+                    .cwd
+                    .as_ref()
+                    .map_or_else(|| state.working_dir.clone(), PathBuf::from),
+                note: state.note.clone(),
+                channels: Some(state.channels.clone()),
+                started_at: None,
+            })
+            .collect();
+        for record in records {
+            if let Some(running) = sessions.iter_mut().find(|session| session.id == record.id) {
+                running.started_at = Some(record.created_at);
+                continue;
+            }
+            let project = project(record.project.as_ref());
+            let Some(dir) = record
+                .cwd
+                .map(PathBuf::from)
+                .or_else(|| project.map(|project| project.path.clone()))
+            else {
+                continue;
+            };
+            sessions.push(SearchableSession {
+                id: record.id,
+                agent: record.agent,
+                project: project.map(|project| project.name.clone()),
+                dir,
+                note: None,
+                channels: None,
+                started_at: Some(record.created_at),
+            });
+        }
+        Ok(sessions)
+    }
+
+    /// Save a project so it keeps its ID across restarts
+    async fn persist_project(&self, project_id: &str) {
+        let Some(project) = self.projects.get(project_id) else {
+            return;
+        };
+        let stored = StoredProject {
+            id: project.id.clone(),
+            name: project.name.clone(),
+            path: project.path.clone(),
+            parent: project.parent.clone(),
+            defaults: project.defaults.clone(),
+        };
+        if let Err(e) = self.storage.save_project(&stored).await {
+            tracing::warn!("Failed to save project {}: {}", project_id, e);
+        }
+    }
+
+    async fn create_project(
+        &mut self,
+        name: String,
+        path: String,
+        parent: Option<String>,
+    ) -> Result<ProjectResource> {
         let project_id = Uuid::new_v4().to_string();
         let project_path = std::path::PathBuf::from(&path);
 
         if !project_path.exists() {
             return Err(anyhow!("Project path does not exist"));
         }
+        project_groups::validate_project_name(&name)?;
+        let parent = match parent {
+            Some(parent) => {
+                let projects = self.list_projects();
+                let parent = project_groups::find_project(&projects, &parent)
+                    .and_then(|found| self.projects.get(&found.id))
+                    .ok_or_else(|| anyhow!("Parent project '{}' does not exist", parent))?;
+                project_groups::validate_subproject(&project_path, &parent.path)?;
+                Some(parent.id.clone())
+            }
+            None => None,
+        };
 
-        self.projects.insert(
-            project_id.clone(),
-            Project {
-                id: project_id.clone(),
-                name: name.clone(),
-                path: project_path.clone(),
-            },
-        );
+        // Registering a path again (e.g. from the shell hook) returns the existing project,
+        // turning a temporary project from an ad-hoc session into a named one, or a
+        // project into a sub-project
+        if let Some(existing) = self.projects.values_mut().find(|p| p.path == project_path) {
+            let renamed = existing.name.ends_with(" (temporary)");
+            if renamed {
+                existing.name = name;
+            }
+            let moved = parent.is_some() && existing.parent != parent;
+            if moved {
+                existing.parent = parent;
+            }
+            let resource = existing.resource();
+            if renamed || moved {
+                self.persist_project(&resource.id).await;
+            }
+            return Ok(resource);
+        }
 
-        Ok(ProjectResource {
-            resource_type: "project".to_string(),
-            id: project_id,
-            attributes: Some(ProjectAttributes {
-                name,
-                path: project_path.to_string_lossy().to_string(),
-            }),
-            relationships: None,
-        })
+        let project = Project::new(project_id.clone(), name, project_path, parent);
+        let resource = project.resource();
+        self.projects.insert(project_id.clone(), project);
+        self.persist_project(&project_id).await;
+        Ok(resource)
     }
 
-    fn list_projects(&self) -> Vec<ProjectResource> {
-        self.projects
+    fn session_options(&self) -> SessionOptions {
+        let agents: Vec<AgentOption> = self
+            .config
+            .agent_names()
+            .into_iter()
+            .map(|name| AgentOption {
+                installed: launch::find_executable(self.config.agent_command(&name)).is_some(),
+                supports_sandbox: launch::sandbox_args(&name).is_some(),
+                name,
+            })
+            .collect();
+
+        let default_agent = if self.config.is_agent_allowed("claude") {
+            Some("claude".to_string())
+        } else {
+            agents.first().map(|agent| agent.name.clone())
+        };
+
+        let templates = self
+            .config
+            .templates
+            .iter()
+            .filter(|(_, template)| self.config.is_agent_allowed(&template.agent))
+            .map(|(name, template)| TemplateOption {
+                name: name.clone(),
+                template: template.clone(),
+            })
+            .collect();
+
+        // Sub-projects go by their qualified name, e.g. `mono/api`
+        let resources = self.list_projects();
+        let mut projects: Vec<ProjectOption> = self
+            .projects
             .values()
-            .map(|p| ProjectResource {
-                resource_type: "project".to_string(),
+            .filter_map(|p| Some((p, resources.iter().find(|r| r.id == p.id)?)))
+            .map(|(p, resource)| ProjectOption {
                 id: p.id.clone(),
-                attributes: Some(ProjectAttributes {
-                    name: p.name.clone(),
-                    path: p.path.to_string_lossy().to_string(),
-                }),
-                relationships: None,
+                name: project_groups::qualified_name(&resources, resource),
+                path: p.path.to_string_lossy().to_string(),
+                icon: p.metadata.icon.clone(),
+                git: p.path.join(".git").exists(),
             })
-            .collect()
+            .collect();
+        projects.sort_by(|a, b| a.name.cmp(&b.name));
+
+        SessionOptions {
+            agents,
+            templates,
+            projects,
+            default_agent,
+        }
+    }
+
+    fn list_projects(&self) -> Vec<ProjectResource> {
+        self.projects.values().map(Project::resource).collect()
+    }
+
+    /// ID of the project at `address`, an ID or (qualified) name
+    fn resolve_project(&self, address: &str) -> Option<String> {
+        let projects = self.list_projects();
+        project_groups::find_project(&projects, address).map(|project| project.id.clone())
+    }
+
+    async fn remove_project(
+        &mut self,
+        address: &str,
+        kill_sessions: bool,
+    ) -> Result<Option<RemovedProject>> {
+        let Some(project_id) = self.resolve_project(address) else {
+            return Ok(None);
+        };
+        if let Some(child) = self
+            .projects
+            .values()
+            .find(|p| p.parent.as_deref() == Some(project_id.as_str()))
+        {
+            return Err(anyhow!(
+                "'{}' has sub-projects such as '{}'; remove them first",
+                address,
+                child.name
+            ));
+        }
+
+        let sessions: Vec<String> = self
+            .sessions
+            .values()
+            .filter(|state| state.project_id.as_deref() == Some(project_id.as_str()))
+            .map(|state| state.id.clone())
+            .collect();
+        for session_id in &sessions {
+            if kill_sessions {
+                if let Err(e) = self.close_session(session_id).await {
+                    tracing::warn!("Failed to end session {}: {}", session_id, e);
+                }
+            } else if let Some(state) = self.sessions.get_mut(session_id) {
+                state.project_id = None;
+            }
+        }
+
+        let Some(project) = self.projects.remove(&project_id) else {
+            return Ok(None);
+        };
+        if let Err(e) = self.storage.delete_project(&project_id).await {
+            tracing::warn!("Failed to delete project {}: {}", project_id, e);
+        }
+        Ok(Some(RemovedProject {
+            project: project.resource(),
+            sessions,
+        }))
+    }
+
+    async fn update_project(
+        &mut self,
+        address: &str,
+        name: Option<String>,
+        defaults: Option<ProjectDefaults>,
+    ) -> Result<Option<ProjectResource>> {
+        if let Some(name) = &name {
+            project_groups::validate_project_name(name)?;
+        }
+        if let Some(agent) = defaults.as_ref().and_then(|d| d.agent.as_deref()) {
+            if !self.config.is_agent_allowed(agent) {
+                return Err(anyhow!("Code agent '{}' is not whitelisted", agent));
+            }
+        }
+        // Every later session in the project gets this env, so it's checked now
+        if let Some(defaults) = &defaults {
+            launch::validate_env(&defaults.env)?;
+        }
+        let Some(project_id) = self.resolve_project(address) else {
+            return Ok(None);
+        };
+        let Some(project) = self.projects.get_mut(&project_id) else {
+            return Ok(None);
+        };
+        if let Some(name) = name {
+            project.name = name;
+        }
+        if let Some(defaults) = defaults {
+            project.defaults = defaults;
+        }
+        let resource = project.resource();
+        self.persist_project(&project_id).await;
+        Ok(Some(resource))
     }
 
     /// Get the 5 most recent historical sessions for a project from the Claude cache
@@ -939,6 +2652,29 @@ impl SessionManagerActor {
         &self,
         project_path: &std::path::Path,
     ) -> Vec<SessionResource> {
+        // Agents that exited lately come first, with their exit code
+        let project_id = self
+            .projects
+            .values()
+            .find(|p| p.path == project_path)
+            .map(|p| p.id.clone());
+        let mut exited: Vec<SessionResource> = match &project_id {
+            Some(project_id) => self
+                .storage
+                .load_sessions()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|record| {
+                    record.exited
+                        && record.project.as_ref() == Some(project_id)
+                        && self.recently_ended(record)
+                })
+                .map(ended_session_resource)
+                .collect(),
+            None => Vec::new(),
+        };
+
         if let Some(cache) = &self.claude_cache {
             let mut sessions = cache.get_project_sessions(project_path).await;
 
@@ -947,39 +2683,349 @@ impl SessionManagerActor {
             sessions.truncate(5);
 
             // Convert to SessionResource
-            sessions
-                .into_iter()
-                .map(|cached_session| {
-                    // Find the project ID for this cached session
-                    let project_id = self
-                        .projects
-                        .values()
-                        .find(|p| p.path == cached_session.project_path)
-                        .map(|p| p.id.clone());
-
-                    SessionResource {
-                        resource_type: "session".to_string(),
-                        id: cached_session.session_id,
-                        attributes: Some(SessionAttributes {
-                            agent: cached_session.agent,
-                            project: project_id,
-                            status: if cached_session.is_active {
-                                "inactive"
-                            } else {
-                                "completed"
+            exited.extend(sessions.into_iter().map(|cached_session| {
+                // Find the project ID for this cached session
+                let project_id = self
+                    .projects
+                    .values()
+                    .find(|p| p.path == cached_session.project_path)
+                    .map(|p| p.id.clone());
+                let color = default_session_color(&cached_session.session_id);
+                let icon = default_session_icon(&cached_session.agent);
+
+                SessionResource {
+                    resource_type: "session".to_string(),
+                    id: cached_session.session_id,
+                    attributes: Some(SessionAttributes {
+                        agent: cached_session.agent,
+                        project: project_id,
+                        status: if cached_session.is_active {
+                            "inactive"
+                        } else {
+                            "completed"
+                        }
+                        .to_string(),
+                        session_type: SessionType::Historical,
+                        last_modified: Some(cached_session.last_modified.to_rfc3339()),
+                        last_message: cached_session.last_message.clone(),
+                        color,
+                        icon,
+                        note: None,
+                        name: None,
+                        terminates_at: None,
+                        idle_expires_at: None,
+                        diff_stats: None,
+                        exit_code: None,
+                    }),
+                    relationships: None,
+                }
+            }));
+        }
+        exited
+    }
+
+    /// Store a notification and fire `prompt_detected` hooks whenever the
+    /// session's agent asks a question or waits for approval, see `utils::prompt_detector`
+    ///
+    /// Runs until the session's grid channel closes.
+    fn watch_for_questions(&self, session: HookSession, channels: &PtyChannels) {
+        let storage = self.storage.clone();
+        let rules = self.config.notifications.clone();
+        let hooks = self.config.hooks.clone();
+        let mut grid_rx = channels.grid_tx.subscribe();
+        let mut prompt_rx = channels.prompt_tx.subscribe();
+        tokio::spawn(async move {
+            let mut linearizer = Linearizer::new();
+            let mut last_notified: Option<Instant> = None;
+            loop {
+                let (kind, message) = tokio::select! {
+                    update = grid_rx.recv() => {
+                        let update = match update {
+                            Ok(update) => update,
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        };
+                        let question = linearizer
+                            .apply(&update)
+                            .into_iter()
+                            .find_map(|event| match event {
+                                AccessibleEvent::Announcement {
+                                    kind: AnnouncementKind::Question,
+                                    text,
+                                } => Some(text),
+                                _ => None,
+                            });
+                        // An approval prompt is detected before its screen is
+                        // sent, and gets a notification of its own
+                        match question {
+                            Some(question) if prompt_rx.borrow().is_none() => {
+                                (NotificationKind::WaitingForInput, question)
                             }
-                            .to_string(),
-                            session_type: SessionType::Historical,
-                            last_modified: Some(cached_session.last_modified.to_rfc3339()),
-                            last_message: cached_session.last_message.clone(),
-                        }),
-                        relationships: None,
+                            _ => continue,
+                        }
                     }
-                })
-                .collect()
-        } else {
-            Vec::new()
+                    Ok(()) = prompt_rx.changed() => {
+                        let prompt = prompt_rx.borrow_and_update().clone();
+                        match prompt {
+                            Some(prompt) => (NotificationKind::ApprovalNeeded, prompt.prompt().to_string()),
+                            None => continue,
+                        }
+                    }
+                };
+                if last_notified.is_some_and(|at| at.elapsed() < QUESTION_NOTIFICATION_INTERVAL) {
+                    continue;
+                }
+                last_notified = Some(Instant::now());
+                let mut payload = HookPayload::new(HookEvent::PromptDetected, session.clone());
+                payload.prompt = Some(message.clone());
+                hooks::fire(&hooks, payload);
+                let notification = Notification::new(kind, &session.session_id, message);
+                if let Err(e) = record_notification(
+                    storage.as_ref(),
+                    &rules,
+                    session.project.as_deref(),
+                    notification,
+                )
+                .await
+                {
+                    tracing::warn!("Failed to store question notification: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Write the agent's config templates into the session's directory, if
+    /// `[agent_config]` is on; a failed sync doesn't stop the session
+    async fn sync_agent_config(
+        &self,
+        agent: &str,
+        project: Option<String>,
+        working_dir: &std::path::Path,
+    ) {
+        let settings = self.config.agent_config.clone();
+        if !settings.enabled {
+            return;
+        }
+        let data_dir = self.config.server.data_dir.clone();
+        let agent = agent.to_string();
+        let dir = working_dir.to_path_buf();
+        let synced = tokio::task::spawn_blocking(move || {
+            let project = project.unwrap_or_else(|| {
+                dir.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            let vars = agent_config::TemplateVars {
+                project: project.trim_end_matches(" (temporary)"),
+                project_path: &dir,
+                agent: &agent,
+            };
+            agent_config::sync(
+                &agent_config::templates_root(&data_dir),
+                &data_dir,
+                &vars,
+                &settings,
+            )
+        })
+        .await;
+        match synced {
+            Ok(Ok(report)) => {
+                if !report.written.is_empty() {
+                    tracing::info!(
+                        "Agent config v{} written to {}: {:?}",
+                        report.version,
+                        working_dir.display(),
+                        report.written
+                    );
+                }
+                if !report.kept.is_empty() {
+                    tracing::warn!(
+                        "Agent config files edited in {} were kept: {:?}",
+                        working_dir.display(),
+                        report.kept
+                    );
+                }
+            }
+            Ok(Err(e)) => tracing::warn!(
+                "Failed to sync agent config into {}: {}",
+                working_dir.display(),
+                e
+            ),
+            Err(e) => tracing::warn!("Agent config sync panicked: {}", e),
+        }
+    }
+
+    /// Take a safety checkpoint when the session's working tree suddenly shrinks,
+    /// if `[deletion_guard]` is on
+    fn guard_deletions(
+        &self,
+        session_id: &str,
+        project_id: Option<&str>,
+        working_dir: &std::path::Path,
+        channels: &PtyChannels,
+    ) {
+        let config = &self.config.deletion_guard;
+        if !config.enabled {
+            return;
         }
+        let session = GuardedSession {
+            session_id: session_id.to_string(),
+            working_dir: working_dir.to_path_buf(),
+            project: project_id
+                .and_then(|id| self.projects.get(id))
+                .map(|project| project.name.clone()),
+        };
+        if let Err(e) = deletion_guard::spawn(
+            session,
+            config.clone(),
+            self.config.notifications.clone(),
+            self.storage.clone(),
+            channels,
+        ) {
+            tracing::warn!(
+                "Can't guard {} against mass deletions: {}",
+                working_dir.display(),
+                e
+            );
+        }
+    }
+
+    /// Pin a git checkpoint of the session's working tree after each agent
+    /// turn, if `[checkpoints]` is on
+    fn checkpoint_turns(
+        &self,
+        session_id: &str,
+        project_id: Option<&str>,
+        working_dir: &std::path::Path,
+        channels: &PtyChannels,
+    ) {
+        let config = &self.config.checkpoints;
+        if !config.enabled {
+            return;
+        }
+        let session = CheckpointedSession {
+            session_id: session_id.to_string(),
+            working_dir: working_dir.to_path_buf(),
+            project: project_id
+                .and_then(|id| self.projects.get(id))
+                .map(|project| project.name.clone()),
+        };
+        checkpoints::spawn(
+            session,
+            config.clone(),
+            self.config.notifications.clone(),
+            self.storage.clone(),
+            channels,
+        );
+    }
+
+    /// Tee the session's output into its recording under the data dir
+    #[cfg(feature = "capture")]
+    fn record_session(
+        &self,
+        session_id: &str,
+        agent: &str,
+        args: Vec<String>,
+        channels: &PtyChannels,
+    ) -> Result<()> {
+        let path = recording::recording_path(&self.config.server.recordings_dir(), session_id);
+        recording::spawn(&path, agent.to_string(), args, channels)
+            .map_err(|e| anyhow!("Can't record to {}: {}", path.display(), e))
+    }
+
+    #[cfg(not(feature = "capture"))]
+    fn record_session(
+        &self,
+        _session_id: &str,
+        _agent: &str,
+        _args: Vec<String>,
+        _channels: &PtyChannels,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "Recording sessions needs the `capture` feature, which this build was compiled without"
+        ))
+    }
+
+    /// Link the commits an agent announces to the session, for agents that do
+    ///
+    /// Runs until the session's grid channel closes.
+    fn watch_for_commits(
+        &self,
+        session_id: &str,
+        agent: &str,
+        project_id: Option<&str>,
+        working_dir: &std::path::Path,
+        channels: &PtyChannels,
+    ) {
+        if !commits::announces_commits(agent) {
+            return;
+        }
+        let storage = self.storage.clone();
+        let rules = self.config.notifications.clone();
+        let project = project_id
+            .and_then(|id| self.projects.get(id))
+            .map(|project| project.name.clone());
+        let session_id = session_id.to_string();
+        let working_dir = working_dir.to_path_buf();
+        let mut grid_rx = channels.grid_tx.subscribe();
+        tokio::spawn(async move {
+            let mut linearizer = Linearizer::new();
+            let mut seen = std::collections::HashSet::new();
+            loop {
+                let update = match grid_rx.recv().await {
+                    Ok(update) => update,
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                for event in linearizer.apply(&update) {
+                    let AccessibleEvent::Line { text } = event else {
+                        continue;
+                    };
+                    let Some((hash, message)) = commits::parse_announcement(&text) else {
+                        continue;
+                    };
+                    if !seen.insert(hash.clone()) {
+                        continue;
+                    }
+                    let dir = working_dir.clone();
+                    let announced = hash.clone();
+                    let (hash, message) =
+                        tokio::task::spawn_blocking(move || commits::lookup(&dir, &announced))
+                            .await
+                            .ok()
+                            .flatten()
+                            .unwrap_or((hash, message));
+                    let commit = AgentCommit {
+                        session_id: session_id.clone(),
+                        hash,
+                        message,
+                        created_at: chrono::Utc::now().to_rfc3339(),
+                    };
+                    if let Err(e) = storage.save_commit(&commit).await {
+                        tracing::warn!("Failed to store commit {}: {}", commit.hash, e);
+                    }
+                    let notification = Notification::new(
+                        NotificationKind::Commit,
+                        &session_id,
+                        format!(
+                            "Committed {}: {}",
+                            commit.hash.get(..7).unwrap_or(&commit.hash),
+                            commit.message
+                        ),
+                    );
+                    if let Err(e) = record_notification(
+                        storage.as_ref(),
+                        &rules,
+                        project.as_deref(),
+                        notification,
+                    )
+                    .await
+                    {
+                        tracing::warn!("Failed to store commit notification: {}", e);
+                    }
+                }
+            }
+        });
     }
 
     async fn shutdown_all_sessions(&mut self) {
@@ -1003,8 +3049,89 @@ impl SessionManagerActor {
             }
         }
 
+        // After a handover the agents belong to the new server
+        let mut pids = Vec::new();
+        if !self.handed_over {
+            let ids: Vec<String> = self.sessions.keys().cloned().collect();
+            for session_id in ids {
+                self.record_session_end(&session_id, false, None).await;
+            }
+            pids = self
+                .sessions
+                .values()
+                .filter_map(|state| state.pid)
+                .collect();
+        }
+
         // Clear the sessions map
         self.sessions.clear();
-        tracing::info!("All sessions terminated");
+
+        let killed = shutdown::stop_agents(&pids, shutdown::AGENT_GRACE).await;
+        tracing::info!(
+            "All sessions terminated ({} of {} agent processes killed)",
+            killed,
+            pids.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn requests_fail_when_the_actor_does_not_answer_in_time() {
+        // An actor stuck on an earlier request never reads the next ones
+        let (command_tx, _command_rx) = mpsc::unbounded_channel();
+        let (in_flight_tx, in_flight) = watch::channel(None);
+        let handle = SessionManagerHandle {
+            command_tx,
+            data_dir: PathBuf::new(),
+            recordings_dir: PathBuf::new(),
+            pastes_dir: PathBuf::new(),
+            pid_file: PathBuf::new(),
+            paste: PasteConfig::default(),
+            queue_timeout: None,
+            request_timeout: Duration::from_millis(50),
+            storage: Arc::new(LocalStorage::new(PathBuf::new())),
+            versions: AgentVersionChecker::new(Default::default()),
+            agent_names: Vec::new(),
+            agents: Default::default(),
+            in_flight,
+        };
+
+        // Slow but within its deadline is alive, past it is stuck
+        let started = Instant::now();
+        in_flight_tx.send_replace(Some(InFlight {
+            command: "CreateSession",
+            started,
+            deadline: Duration::from_secs(30),
+        }));
+        assert!(handle.check_alive().is_ok());
+        in_flight_tx.send_replace(Some(InFlight {
+            command: "CreateSession",
+            started,
+            deadline: Duration::ZERO,
+        }));
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert!(handle.check_alive().is_err());
+
+        let error = handle.close_session("stuck", true).await.unwrap_err();
+        let timeout = error.downcast_ref::<RequestTimeout>().unwrap();
+        assert_eq!(timeout.command, "CloseSession");
+        assert!(handle.list_sessions().await.is_empty());
+        let error = handle.ping().await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RequestTimeout>().unwrap().command,
+            "Ping"
+        );
+
+        let handle = SessionManagerHandle {
+            command_tx: mpsc::unbounded_channel().0,
+            ..handle
+        };
+        let error = handle.session_options().await.unwrap_err();
+        assert!(error.downcast_ref::<RequestTimeout>().is_none());
+        assert!(handle.check_alive().is_err());
     }
 }
@@ -1,7 +1,25 @@
+pub mod archive;
+pub mod checkpoints;
 pub mod claude_cache;
+pub mod deletion_guard;
+pub mod digest;
+pub mod handover;
+pub mod hooks;
+pub mod lifecycle;
 pub mod manager;
+pub mod notify;
+#[cfg(feature = "capture")]
+pub mod recording;
+pub mod search;
+pub mod shutdown;
+#[cfg(feature = "ssh-server")]
+pub mod ssh;
+pub mod storage;
 pub mod web;
 
 pub use claude_cache::ClaudeProjectsCache;
 pub use manager::SessionManagerHandle;
-pub use web::start_web_server;
+pub use storage::Storage;
+#[cfg(unix)]
+pub use web::{bind_socket, serve_with_socket};
+pub use web::{build_router, serve_listener, start_web_server, AppState};
@@ -0,0 +1,199 @@
+//! Pushing alerts off the server: desktop notifications, webhooks and ntfy
+//!
+//! Notifications that alert (see `[notifications]`) at `push_min_severity` or
+//! above, by default those about an agent blocked on an approval or a
+//! question, also go to the targets configured for their project, so someone
+//! away from the terminal hears about it. Delivery is best effort: failures
+//! are logged and not retried.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::core::notifications::{Notification, NtfyConfig, PushTargets, Severity};
+
+/// How long a webhook or ntfy server may take to answer
+const PUSH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to webhooks
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    #[serde(flatten)]
+    notification: &'a Notification,
+    project: Option<&'a str>,
+    severity: Severity,
+}
+
+/// Push a notification to `targets` in the background
+pub fn push(targets: PushTargets, project: Option<String>, notification: Notification) {
+    if targets.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        deliver(&targets, project.as_deref(), &notification).await;
+    });
+}
+
+/// Push a notification to each of `targets`, logging the ones that fail
+pub async fn deliver(targets: &PushTargets, project: Option<&str>, notification: &Notification) {
+    let title = title(project, notification);
+    if targets.desktop {
+        if let Err(e) = show_on_desktop(&title, notification).await {
+            tracing::warn!("Failed to show a desktop notification: {}", e);
+        }
+    }
+    for url in &targets.webhooks {
+        if let Err(e) = post_webhook(url, project, notification).await {
+            tracing::warn!("Failed to send a notification to webhook {}: {}", url, e);
+        }
+    }
+    if let Some(ntfy) = &targets.ntfy {
+        if let Err(e) = publish_to_ntfy(ntfy, &title, notification).await {
+            tracing::warn!("Failed to publish a notification to ntfy: {}", e);
+        }
+    }
+}
+
+fn title(project: Option<&str>, notification: &Notification) -> String {
+    match project {
+        Some(project) => format!("codemux · {}", project),
+        None if notification.session_id.is_empty() => "codemux".to_string(),
+        None => format!(
+            "codemux · session {}",
+            notification.session_id.chars().take(8).collect::<String>()
+        ),
+    }
+}
+
+fn http() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(PUSH_TIMEOUT)
+            .build()
+            .expect("Failed to create HTTP client")
+    })
+}
+
+async fn post_webhook(url: &str, project: Option<&str>, notification: &Notification) -> Result<()> {
+    let payload = WebhookPayload {
+        notification,
+        project,
+        severity: notification.kind.severity(),
+    };
+    http()
+        .post(url)
+        .json(&payload)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+async fn publish_to_ntfy(
+    ntfy: &NtfyConfig,
+    title: &str,
+    notification: &Notification,
+) -> Result<()> {
+    let url = format!("{}/{}", ntfy.server.trim_end_matches('/'), ntfy.topic);
+    let priority = match notification.kind.severity() {
+        Severity::Urgent => "high",
+        Severity::Warning | Severity::Info => "default",
+    };
+    let kind = serde_json::to_value(notification.kind)?;
+    let mut request = http()
+        .post(url)
+        .header("Title", title)
+        .header("Priority", priority)
+        .header("Tags", kind.as_str().unwrap_or_default())
+        .body(notification.message.clone());
+    if let Some(token) = &ntfy.token {
+        request = request.bearer_auth(token);
+    }
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
+
+/// Through `notify-send` on Linux and Notification Center on macOS
+async fn show_on_desktop(title: &str, notification: &Notification) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        // Passed as arguments so the text needs no AppleScript quoting
+        let mut command = tokio::process::Command::new("osascript");
+        command.args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 2 of argv) with title (item 1 of argv)",
+            "-e",
+            "end run",
+            title,
+            &notification.message,
+        ]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let urgency = match notification.kind.severity() {
+            Severity::Urgent => "--urgency=critical",
+            Severity::Warning | Severity::Info => "--urgency=normal",
+        };
+        let mut command = tokio::process::Command::new("notify-send");
+        command.args(["--app-name=codemux", urgency, title, &notification.message]);
+        command
+    } else {
+        bail!("Desktop notifications aren't supported on this platform");
+    };
+    let status = command.status().await?;
+    if !status.success() {
+        bail!("The notifier exited with {}", status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::notifications::NotificationKind;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn posts_alerts_to_webhooks() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let received = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            // The JSON body comes last and ends the request
+            while !request.ends_with(b"}") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let notification = Notification::new(
+            NotificationKind::ApprovalNeeded,
+            "0b5e6f9a-session",
+            "Do you want to make this edit to main.rs?",
+        );
+        let targets = PushTargets {
+            webhooks: vec![url],
+            ..Default::default()
+        };
+        deliver(&targets, Some("web"), &notification).await;
+
+        let request = received.await.unwrap();
+        assert!(request.starts_with("POST /hook "));
+        let body: serde_json::Value =
+            serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["kind"], "approval_needed");
+        assert_eq!(body["project"], "web");
+        assert_eq!(body["severity"], "urgent");
+        assert_eq!(body["message"], "Do you want to make this edit to main.rs?");
+        assert_eq!(title(None, &notification), "codemux · session 0b5e6f9a");
+    }
+}
@@ -0,0 +1,351 @@
+//! Recordings of sessions created with `record`
+//!
+//! The manager tees a recorded session's raw output and screen into a JSONL
+//! file under `<data_dir>/recordings`, in the format `codemux-capture` writes,
+//! so a session that misbehaved in real use can be replayed and attached to a
+//! bug report without reproducing it under the capture tool. See
+//! `codemux recordings`.
+
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::broadcast;
+
+use crate::capture::session_data::{
+    GridCell, GridCellWithPos, JsonlRecorder, SessionEvent, SessionMetadata, SYNC_INTERVAL,
+};
+use crate::core::pty_session::{self, GridUpdateMessage, PtyChannels, TerminalColor};
+use crate::core::render::color_to_hex;
+
+/// Where the recording of `session_id` is written
+pub fn recording_path(recordings_dir: &Path, session_id: &str) -> PathBuf {
+    recordings_dir.join(format!("{}.jsonl", session_id))
+}
+
+/// Record the session until it ends
+///
+/// An existing recording of the session is continued, e.g. after
+/// `codemux server upgrade` took it over.
+pub fn spawn(path: &Path, agent: String, args: Vec<String>, channels: &PtyChannels) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut recorder = if path.exists() {
+        JsonlRecorder::resume(path)?
+    } else {
+        JsonlRecorder::new(path, agent, args)?
+    };
+    let resumed_at = Duration::from_millis(recorder.resumed_at() as u64);
+    let started = Instant::now();
+    let timestamp = move || (resumed_at + started.elapsed()).as_millis() as u32;
+
+    let channels = channels.clone();
+    let mut output_rx = channels.output_tx.subscribe();
+    let mut grid_rx = channels.grid_tx.subscribe();
+    let mut size_rx = channels.size_tx.subscribe();
+    let path = path.to_path_buf();
+    tokio::spawn(async move {
+        let mut screen = Screen::default();
+        // Diffs only make sense on top of what's on screen already
+        if let Ok(keyframe) = channels.request_keyframe().await {
+            screen.apply(&keyframe);
+        }
+        let mut sync = tokio::time::interval(SYNC_INTERVAL);
+        sync.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            let event = tokio::select! {
+                output = output_rx.recv() => match output {
+                    Ok(output) => {
+                        let now = timestamp();
+                        SessionEvent::RawPtyOutput {
+                            timestamp_begin: now,
+                            timestamp_end: now,
+                            data: output.data.to_vec(),
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(
+                            "Recording of {} missed {} outputs",
+                            path.display(),
+                            missed
+                        );
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                update = grid_rx.recv() => match update {
+                    Ok(update) => {
+                        screen.apply(&update);
+                        screen.event(timestamp())
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(
+                            "Recording of {} missed {} screen updates",
+                            path.display(),
+                            missed
+                        );
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                size = size_rx.recv() => match size {
+                    Ok(size) => SessionEvent::Resize {
+                        timestamp: timestamp(),
+                        rows: size.rows,
+                        cols: size.cols,
+                    },
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = sync.tick() => {
+                    if let Err(e) = recorder.sync_if_due() {
+                        tracing::warn!("Failed to sync recording {}: {}", path.display(), e);
+                    }
+                    continue;
+                }
+            };
+            if let Err(e) = recorder.write_event(&event) {
+                tracing::warn!("Stopped recording {}: {}", path.display(), e);
+                return;
+            }
+        }
+        if let Err(e) = recorder.finalize() {
+            tracing::warn!("Failed to finish recording {}: {}", path.display(), e);
+        }
+    });
+    Ok(())
+}
+
+/// The session's screen, kept up to date from its grid updates to record it
+/// whole each time, as the replayer expects
+#[derive(Default)]
+struct Screen {
+    size: (u16, u16),
+    cells: HashMap<(u16, u16), GridCell>,
+    cursor: (u16, u16),
+}
+
+impl Screen {
+    fn apply(&mut self, update: &GridUpdateMessage) {
+        match update {
+            GridUpdateMessage::Keyframe {
+                size,
+                cells,
+                cursor,
+                ..
+            } => {
+                self.size = (size.rows, size.cols);
+                self.cells.clear();
+                for ((row, col), cell) in cells {
+                    self.set((*row, *col), cell);
+                }
+                self.cursor = *cursor;
+            }
+            GridUpdateMessage::Diff {
+                changes, cursor, ..
+            } => {
+                for (row, col, cell) in changes {
+                    self.set((*row, *col), cell);
+                }
+                if let Some(cursor) = cursor {
+                    self.cursor = *cursor;
+                }
+            }
+        }
+    }
+
+    /// Keep the cells that show something, like the capture tool does
+    fn set(&mut self, pos: (u16, u16), cell: &pty_session::GridCell) {
+        let cell = recorded_cell(cell);
+        if cell.char.trim().is_empty() && cell.bg_color.is_none() && !cell.reverse {
+            self.cells.remove(&pos);
+        } else {
+            self.cells.insert(pos, cell);
+        }
+    }
+
+    fn event(&self, timestamp: u32) -> SessionEvent {
+        SessionEvent::GridUpdate {
+            timestamp_begin: timestamp,
+            timestamp_end: timestamp,
+            size: self.size,
+            cells: self
+                .cells
+                .iter()
+                .map(|(&(row, col), cell)| GridCellWithPos {
+                    row,
+                    col,
+                    cell: cell.clone(),
+                })
+                .collect(),
+            cursor: self.cursor,
+        }
+    }
+}
+
+/// A cell as recordings store it, with colors as `#rrggbb` and `None` for the
+/// terminal's default
+fn recorded_cell(cell: &pty_session::GridCell) -> GridCell {
+    let hex = |color: &Option<TerminalColor>| {
+        color
+            .as_ref()
+            .filter(|color| **color != TerminalColor::Default)
+            .map(|color| color_to_hex(Some(color), ""))
+    };
+    GridCell {
+        char: cell.char.clone(),
+        fg_color: hex(&cell.fg_color),
+        bg_color: hex(&cell.bg_color),
+        bold: cell.bold,
+        italic: cell.italic,
+        underline: cell.underline,
+        reverse: cell.reverse,
+    }
+}
+
+/// A recording on disk, described without reading all of it
+#[derive(Debug, Clone)]
+pub struct RecordingSummary {
+    pub session_id: String,
+    pub path: PathBuf,
+    pub agent: String,
+    pub start_time: SystemTime,
+    /// When the last event was written, roughly
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+/// Recordings in `recordings_dir`, oldest first
+pub fn list(recordings_dir: &Path) -> Result<Vec<RecordingSummary>> {
+    let entries = match std::fs::read_dir(recordings_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut recordings = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(session_id) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".jsonl"))
+        else {
+            continue;
+        };
+        let session_id = session_id.to_string();
+        match summarize(&path, session_id) {
+            Ok(summary) => recordings.push(summary),
+            Err(e) => tracing::warn!("Skipping recording {}: {}", path.display(), e),
+        }
+    }
+    recordings.sort_by_key(|recording| recording.start_time);
+    Ok(recordings)
+}
+
+fn summarize(path: &Path, session_id: String) -> Result<RecordingSummary> {
+    let file = std::fs::File::open(path)?;
+    let file_metadata = file.metadata()?;
+    let mut first_line = String::new();
+    std::io::BufReader::new(file).read_line(&mut first_line)?;
+    let metadata: SessionMetadata = serde_json::from_str(&first_line)?;
+    Ok(RecordingSummary {
+        session_id,
+        path: path.to_path_buf(),
+        agent: metadata.agent,
+        start_time: metadata.start_time,
+        modified: file_metadata.modified()?,
+        size: file_metadata.len(),
+    })
+}
+
+/// The recording of the session whose ID is or starts with `session_id`
+pub fn find(recordings_dir: &Path, session_id: &str) -> Result<RecordingSummary> {
+    let mut matches: Vec<_> = list(recordings_dir)?
+        .into_iter()
+        .filter(|recording| recording.session_id.starts_with(session_id))
+        .collect();
+    match matches.len() {
+        1 => Ok(matches.remove(0)),
+        0 => Err(anyhow!("No recording of session {}", session_id)),
+        n => Err(anyhow!(
+            "{} recordings match {}; give more of the session ID",
+            n,
+            session_id
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pty_session::SerializablePtySize;
+
+    fn cell(char: &str, fg_color: Option<TerminalColor>) -> pty_session::GridCell {
+        pty_session::GridCell {
+            char: char.to_string(),
+            fg_color,
+            bg_color: None,
+            bold: false,
+            italic: false,
+            underline: false,
+            reverse: false,
+        }
+    }
+
+    #[test]
+    fn records_the_whole_screen_from_keyframes_and_diffs() {
+        let mut screen = Screen::default();
+        screen.apply(&GridUpdateMessage::Keyframe {
+            size: SerializablePtySize { rows: 2, cols: 3 },
+            cells: vec![
+                ((0, 0), cell("$", Some(TerminalColor::Default))),
+                ((0, 1), cell(" ", None)),
+            ],
+            cursor: (0, 1),
+            cursor_visible: true,
+            scrollback_position: 0,
+            scrollback_total: 0,
+            timestamp: SystemTime::now(),
+            runs: Vec::new(),
+        });
+        screen.apply(&GridUpdateMessage::Diff {
+            changes: vec![
+                (0, 0, cell(" ", None)),
+                (
+                    1,
+                    2,
+                    cell(
+                        "x",
+                        Some(TerminalColor::Rgb {
+                            r: 255,
+                            g: 0,
+                            b: 16,
+                        }),
+                    ),
+                ),
+            ],
+            cursor: Some((1, 2)),
+            cursor_visible: None,
+            scrollback_position: None,
+            scrollback_total: None,
+            timestamp: SystemTime::now(),
+        });
+
+        let SessionEvent::GridUpdate {
+            size,
+            cells,
+            cursor,
+            ..
+        } = screen.event(42)
+        else {
+            panic!("expected a grid update");
+        };
+        assert_eq!(size, (2, 3));
+        assert_eq!(cursor, (1, 2));
+        assert_eq!(cells.len(), 1);
+        assert_eq!((cells[0].row, cells[0].col), (1, 2));
+        assert_eq!(cells[0].cell.fg_color.as_deref(), Some("#ff0010"));
+    }
+}
@@ -0,0 +1,172 @@
+//! Gathering what `GET /api/search` looks through, see `core::search`
+//!
+//! Transcripts of every agent in every directory a session ran in, running
+//! sessions' scrollback and their handover notes. A transcript is read once and
+//! kept in memory until its file changes, so repeated searches only read what
+//! the agents wrote since.
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use super::manager::SessionManagerHandle;
+use crate::core::agent_adapter::AgentAdapter;
+use crate::core::pty_session::{GridCell, PtyChannels};
+use crate::core::search::{self, SearchDocument, SearchHit, SearchSource};
+
+/// Lines of a running session's history searched
+const SCROLLBACK_LINES: usize = 1000;
+
+/// A session whose transcripts, scrollback and note can be searched
+pub struct SearchableSession {
+    pub id: String,
+    pub agent: String,
+    /// Project name
+    pub project: Option<String>,
+    /// Directory the agent ran in, where its transcripts are kept by
+    pub dir: PathBuf,
+    pub note: Option<String>,
+    /// Set while the session runs
+    pub channels: Option<PtyChannels>,
+    /// ISO 8601 timestamp from the history
+    pub started_at: Option<String>,
+}
+
+/// Transcript text kept between searches
+#[derive(Default)]
+pub struct SearchIndex {
+    transcripts: Mutex<HashMap<PathBuf, IndexedTranscript>>,
+}
+
+struct IndexedTranscript {
+    modified: SystemTime,
+    text: String,
+}
+
+impl SearchIndex {
+    /// The messages of a transcript as one text, read again when the file changed
+    fn transcript_text(
+        &self,
+        path: &Path,
+        modified: SystemTime,
+        adapter: &dyn AgentAdapter,
+    ) -> Option<String> {
+        let mut transcripts = self.transcripts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(indexed) = transcripts.get(path) {
+            if indexed.modified == modified {
+                return Some(indexed.text.clone());
+            }
+        }
+        let content = std::fs::read_to_string(path).ok()?;
+        let text = adapter
+            .messages(&adapter.parse_transcript(&content))
+            .into_iter()
+            .map(|message| message.content)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        transcripts.insert(
+            path.to_path_buf(),
+            IndexedTranscript {
+                modified,
+                text: text.clone(),
+            },
+        );
+        Some(text)
+    }
+}
+
+/// The sessions, transcripts and notes matching `query`, best first
+pub async fn search(
+    manager: &SessionManagerHandle,
+    index: Arc<SearchIndex>,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SearchHit>> {
+    let sessions = manager.searchable_sessions().await?;
+    let mut documents = Vec::new();
+
+    for session in &sessions {
+        let document = |source, text: String| SearchDocument {
+            session_id: Some(session.id.clone()),
+            conversation: None,
+            agent: session.agent.clone(),
+            project: session.project.clone(),
+            source,
+            updated_at: session.started_at.clone(),
+            text,
+        };
+        if let Some(note) = &session.note {
+            documents.push(document(SearchSource::Note, note.clone()));
+        }
+        if let Some(channels) = &session.channels {
+            match channels.request_scrollback(SCROLLBACK_LINES).await {
+                Ok(lines) => documents.push(document(SearchSource::Scrollback, plain_text(&lines))),
+                Err(e) => {
+                    tracing::debug!("Not searching scrollback of {}: {}", session.id, e)
+                }
+            }
+        }
+    }
+
+    // Sessions of one agent in one directory share its transcripts
+    let mut dirs = BTreeMap::new();
+    for session in &sessions {
+        dirs.entry((session.agent.clone(), session.dir.clone()))
+            .or_insert_with(|| session.project.clone());
+    }
+    let sources: Vec<_> = dirs
+        .into_iter()
+        .filter_map(|((agent, dir), project)| {
+            let adapter = manager.agent_adapter(&agent)?;
+            Some((agent, dir, project, adapter))
+        })
+        .collect();
+    let session_ids: HashSet<String> = sessions.into_iter().map(|session| session.id).collect();
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()));
+
+    let transcripts = tokio::task::spawn_blocking(move || {
+        let mut documents = Vec::new();
+        for (agent, dir, project, adapter) in sources {
+            for path in adapter.transcripts(&home, &dir) {
+                let Ok(modified) = path.metadata().and_then(|meta| meta.modified()) else {
+                    continue;
+                };
+                let Some(text) = index.transcript_text(&path, modified, adapter.as_ref()) else {
+                    continue;
+                };
+                let updated_at = chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339();
+                let conversation = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned());
+                documents.push(SearchDocument {
+                    // Claude's conversations are named after the codemux session
+                    session_id: conversation.clone().filter(|id| session_ids.contains(id)),
+                    conversation,
+                    agent: agent.clone(),
+                    project: project.clone(),
+                    source: SearchSource::Transcript,
+                    updated_at: Some(updated_at),
+                    text,
+                });
+            }
+        }
+        documents
+    })
+    .await?;
+    documents.extend(transcripts);
+
+    Ok(search::search(&documents, query, limit))
+}
+
+fn plain_text(lines: &[Vec<GridCell>]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            let text: String = line.iter().map(|cell| cell.char.as_str()).collect();
+            text.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
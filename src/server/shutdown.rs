@@ -0,0 +1,133 @@
+//! Stopping the server without leaving agents behind
+//!
+//! `POST /api/shutdown` (what `codemux stop` calls) ends every session, then
+//! asks the agent processes to exit. Most agents already take the closed
+//! terminal as a hangup, but some keep going, e.g. in the middle of a tool
+//! call, so the ones still running after a grace period are killed.
+//!
+//! SIGTERM, as `docker stop` and systemd send it, and Ctrl+C do the same
+//! before the listener stops, so a server running as a container's PID 1
+//! exits cleanly instead of being killed with its agents left behind.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::core::process::{self, Signal};
+use crate::server::manager::SessionManagerHandle;
+
+/// How long agents get to exit on their own before they are killed
+pub const AGENT_GRACE: Duration = Duration::from_secs(5);
+/// How long open connections get to finish once the sessions ended
+pub const CONNECTION_DRAIN: Duration = Duration::from_secs(5);
+
+/// Wait for SIGTERM or Ctrl+C
+pub async fn terminate_requested() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = terminate.recv() => {}
+                Ok(()) = tokio::signal::ctrl_c() => {}
+            }
+            return;
+        }
+    }
+    if tokio::signal::ctrl_c().await.is_err() {
+        // Without signal handlers the server runs until it's killed
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Wait for SIGTERM or Ctrl+C, then end the sessions as `POST /api/shutdown`
+/// does; the listener stops once this returns
+pub async fn on_terminate(session_manager: SessionManagerHandle) {
+    terminate_requested().await;
+    tracing::info!("Received a request to stop, shutting down all sessions...");
+    // After a handover (`server upgrade` stops the old server this way) the
+    // agents belong to the new server
+    let handed_over = session_manager
+        .ping()
+        .await
+        .is_ok_and(|status| !status.accepting_sessions);
+    if !handed_over {
+        session_manager.shutdown_all_sessions().await;
+    }
+    remove_pid_file(session_manager.pid_file());
+    let _ = tokio::task::spawn_blocking(crate::core::telemetry::shutdown).await;
+}
+
+/// Signal the agent and the processes it started; agents lead their own
+/// process group, adopted ones may not, so fall back to the process alone
+pub fn signal_agent(pid: u32, signal: Signal) -> bool {
+    process::signal_group(pid, signal) || process::signal(pid, signal)
+}
+
+/// Ask the agent processes to exit and kill those still running after
+/// `grace`; returns how many had to be killed
+pub async fn stop_agents(pids: &[u32], grace: Duration) -> usize {
+    for pid in pids {
+        process::signal(*pid, Signal::Terminate);
+        // Paused agents, e.g. of sessions killed moments ago, act on it once continued
+        signal_agent(*pid, Signal::Continue);
+    }
+    let deadline = Instant::now() + grace;
+    loop {
+        let running: Vec<u32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| process::is_running(*pid))
+            .collect();
+        if running.is_empty() {
+            return 0;
+        }
+        if Instant::now() >= deadline {
+            for pid in &running {
+                tracing::warn!("Agent process {} did not exit, killing it", pid);
+                process::signal(*pid, Signal::Kill);
+            }
+            return running.len();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Remove the PID file if it still names this process; after `server upgrade`
+/// it belongs to the new server
+pub fn remove_pid_file(path: &Path) {
+    let written = std::fs::read_to_string(path).unwrap_or_default();
+    if written.trim() == std::process::id().to_string() {
+        if let Err(e) = std::fs::remove_file(path) {
+            tracing::warn!("Failed to remove PID file {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::process::is_running;
+    use std::process::Command;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kills_agents_that_ignore_the_request_to_exit() {
+        let mut polite = Command::new("sleep").arg("30").spawn().unwrap();
+        // Ignored signals stay ignored across exec
+        let mut stubborn = Command::new("sh")
+            .args(["-c", "trap '' TERM; exec sleep 30"])
+            .spawn()
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(is_running(stubborn.id()));
+
+        let pids = [polite.id(), stubborn.id()];
+        assert_eq!(stop_agents(&pids, Duration::from_millis(500)).await, 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!is_running(polite.id()));
+        assert!(!is_running(stubborn.id()));
+        polite.wait().unwrap();
+        stubborn.wait().unwrap();
+    }
+}
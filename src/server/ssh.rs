@@ -0,0 +1,349 @@
+use anyhow::{anyhow, Result};
+use russh::keys::{Algorithm, LineEnding, PrivateKey, PublicKey};
+use russh::server::{Auth, Msg, Server as _, Session};
+use russh::{Channel, ChannelId, CryptoVec};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use crate::core::config::SshConfig;
+use crate::core::input_queue::TrySendError;
+use crate::core::pty_session::{PtyChannels, PtyInput, PtyInputMessage};
+use crate::core::viewport::ViewportGuard;
+use crate::server::manager::SessionManagerHandle;
+
+/// Run the embedded SSH server until it fails
+///
+/// The SSH username selects the session to attach to, so
+/// `ssh -p 2222 <session-id>@host` streams that session's PTY in raw passthrough mode.
+pub async fn start_ssh_server(
+    config: SshConfig,
+    data_dir: PathBuf,
+    session_manager: SessionManagerHandle,
+) -> Result<()> {
+    let host_key_path = config
+        .host_key
+        .clone()
+        .unwrap_or_else(|| data_dir.join("ssh_host_ed25519_key"));
+    let host_key = load_or_generate_host_key(&host_key_path)?;
+
+    let authorized_keys: Vec<PublicKey> = config
+        .authorized_keys
+        .iter()
+        .filter_map(|line| match PublicKey::from_openssh(line.trim()) {
+            Ok(key) => Some(key),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid SSH authorized key '{}': {}", line, e);
+                None
+            }
+        })
+        .collect();
+
+    if authorized_keys.is_empty() {
+        tracing::warn!("SSH server has no authorized keys configured; all logins will be rejected");
+    }
+
+    let russh_config = russh::server::Config {
+        keys: vec![host_key],
+        auth_rejection_time: Duration::from_secs(1),
+        auth_rejection_time_initial: Some(Duration::from_secs(0)),
+        ..Default::default()
+    };
+
+    let mut server = SshServer {
+        session_manager,
+        authorized_keys: Arc::new(authorized_keys),
+    };
+
+    tracing::info!("CodeMux SSH server listening on 0.0.0.0:{}", config.port);
+    server
+        .run_on_address(Arc::new(russh_config), ("0.0.0.0", config.port))
+        .await?;
+    Ok(())
+}
+
+fn load_or_generate_host_key(path: &Path) -> Result<PrivateKey> {
+    if path.exists() {
+        return russh::keys::load_secret_key(path, None)
+            .map_err(|e| anyhow!("Failed to load SSH host key {}: {}", path.display(), e));
+    }
+
+    tracing::info!("Generating SSH host key at {}", path.display());
+    let key = PrivateKey::random(&mut rand::rngs::OsRng, Algorithm::Ed25519)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    key.write_openssh_file(path, LineEnding::LF)?;
+    Ok(key)
+}
+
+struct SshServer {
+    session_manager: SessionManagerHandle,
+    authorized_keys: Arc<Vec<PublicKey>>,
+}
+
+impl russh::server::Server for SshServer {
+    type Handler = SshHandler;
+
+    fn new_client(&mut self, peer_addr: Option<SocketAddr>) -> SshHandler {
+        SshHandler {
+            session_manager: self.session_manager.clone(),
+            authorized_keys: self.authorized_keys.clone(),
+            client_id: peer_addr
+                .map(|addr| format!("ssh:{}", addr))
+                .unwrap_or_else(|| "ssh".to_string()),
+            session_id: None,
+            pty_channels: None,
+            viewport: None,
+            channel_id: None,
+            output_task: None,
+        }
+    }
+
+    fn handle_session_error(&mut self, error: anyhow::Error) {
+        tracing::warn!("SSH session error: {}", error);
+    }
+}
+
+/// Per-connection state; one SSH connection attaches to exactly one session
+struct SshHandler {
+    session_manager: SessionManagerHandle,
+    authorized_keys: Arc<Vec<PublicKey>>,
+    client_id: String,
+    session_id: Option<String>,
+    pty_channels: Option<PtyChannels>,
+    /// The client's terminal size among the session's clients, see `viewport`
+    viewport: Option<ViewportGuard>,
+    channel_id: Option<ChannelId>,
+    output_task: Option<JoinHandle<()>>,
+}
+
+impl SshHandler {
+    fn is_authorized(&self, key: &PublicKey) -> bool {
+        self.authorized_keys
+            .iter()
+            .any(|authorized| authorized.key_data() == key.key_data())
+    }
+
+    /// The PTY passes through untouched, so it can't be cropped to fit a
+    /// smaller terminal; the client's size still counts towards the PTY's
+    fn resize(&mut self, cols: u32, rows: u32) {
+        let Some(viewport) = &mut self.viewport else {
+            return;
+        };
+        let (Ok(rows), Ok(cols)) = (u16::try_from(rows), u16::try_from(cols)) else {
+            return;
+        };
+        viewport.resize(rows, cols, false);
+    }
+}
+
+impl russh::server::Handler for SshHandler {
+    type Error = anyhow::Error;
+
+    async fn auth_publickey_offered(
+        &mut self,
+        _user: &str,
+        public_key: &PublicKey,
+    ) -> Result<Auth, Self::Error> {
+        if self.is_authorized(public_key) {
+            Ok(Auth::Accept)
+        } else {
+            Ok(Auth::reject())
+        }
+    }
+
+    async fn auth_publickey(
+        &mut self,
+        user: &str,
+        public_key: &PublicKey,
+    ) -> Result<Auth, Self::Error> {
+        if !self.is_authorized(public_key) {
+            tracing::warn!("SSH login from {} rejected: unknown key", self.client_id);
+            return Ok(Auth::reject());
+        }
+
+        match self.session_manager.get_session_channels(user).await {
+            Some(pty_channels) => {
+                tracing::info!("SSH client {} attached to session {}", self.client_id, user);
+                self.session_id = Some(user.to_string());
+                self.viewport = Some(
+                    pty_channels
+                        .viewports
+                        .join(&self.client_id, pty_channels.control_tx.clone()),
+                );
+                self.pty_channels = Some(pty_channels);
+                Ok(Auth::Accept)
+            }
+            None => {
+                tracing::warn!(
+                    "SSH login from {} rejected: no running session {}",
+                    self.client_id,
+                    user
+                );
+                Ok(Auth::reject())
+            }
+        }
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        if self.channel_id.is_some() {
+            return Ok(false);
+        }
+        self.channel_id = Some(channel.id());
+        Ok(true)
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        _term: &str,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _modes: &[(russh::Pty, u32)],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.resize(col_width, row_height);
+        session.channel_success(channel)?;
+        Ok(())
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let pty_channels = self
+            .pty_channels
+            .clone()
+            .ok_or_else(|| anyhow!("Shell requested before authentication"))?;
+        session.channel_success(channel)?;
+
+        // Subscribe before snapshotting so no output falls between the two;
+        // output the snapshot already shows is skipped below
+        let mut output_rx = pty_channels.output_tx.subscribe();
+        let screen_state = pty_channels.request_screen_state().await;
+        let handle = session.handle();
+        let session_id = self.session_id.clone().unwrap_or_default();
+        // Held until the task ends, so the session doesn't count as idle meanwhile
+        let attached = pty_channels.idle.connect();
+
+        self.output_task = Some(tokio::spawn(async move {
+            let _attached = attached;
+            let mut replayed_seq = 0;
+            match screen_state {
+                Ok(screen) => {
+                    replayed_seq = screen.seq;
+                    if handle
+                        .data(channel, CryptoVec::from(screen.data))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to get screen state for SSH client: {}", e),
+            }
+
+            loop {
+                match output_rx.recv().await {
+                    Ok(output_msg) if output_msg.seq <= replayed_seq => {}
+                    Ok(output_msg) => {
+                        if handle
+                            .data(channel, CryptoVec::from_slice(&output_msg.data))
+                            .await
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "SSH client lagged behind PTY output, {} messages dropped",
+                            skipped
+                        );
+                    }
+                    Err(RecvError::Closed) => {
+                        tracing::info!("Session {} ended, closing SSH channel", session_id);
+                        let _ = handle.exit_status_request(channel, 0).await;
+                        let _ = handle.eof(channel).await;
+                        let _ = handle.close(channel).await;
+                        break;
+                    }
+                }
+            }
+        }));
+
+        Ok(())
+    }
+
+    async fn data(
+        &mut self,
+        _channel: ChannelId,
+        data: &[u8],
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let Some(pty_channels) = &self.pty_channels else {
+            return Ok(());
+        };
+        let input_msg = PtyInputMessage {
+            input: PtyInput::Raw {
+                data: data.to_vec(),
+                client_id: self.client_id.clone(),
+            },
+            seq: None,
+            span: tracing::info_span!(
+                "ssh.input",
+                session_id = self.session_id.as_deref().unwrap_or_default()
+            ),
+        };
+        match pty_channels.input_tx.send(input_msg).await {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                tracing::warn!("Dropping SSH input, the agent isn't reading what's typed");
+                Ok(())
+            }
+            Err(TrySendError::Closed(_)) => Err(anyhow!("PTY input channel closed")),
+        }
+    }
+
+    async fn window_change_request(
+        &mut self,
+        _channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.resize(col_width, row_height);
+        Ok(())
+    }
+}
+
+impl Drop for SshHandler {
+    fn drop(&mut self) {
+        if let Some(task) = self.output_task.take() {
+            task.abort();
+        }
+        if let Some(pty_channels) = &self.pty_channels {
+            pty_channels.input_control.release(&self.client_id);
+        }
+        if let Some(session_id) = &self.session_id {
+            tracing::info!(
+                "SSH client {} detached from session {}",
+                self.client_id,
+                session_id
+            );
+        }
+    }
+}
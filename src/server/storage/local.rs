@@ -0,0 +1,343 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::{SessionRecord, Storage, StoredProject, MAX_SESSION_HISTORY};
+use crate::core::commits::{AgentCommit, MAX_COMMITS};
+use crate::core::notifications::{DoNotDisturb, Notification, MAX_NOTIFICATIONS};
+use crate::core::preferences::{self, UiPreferences, UpdatePreferencesRequest};
+use crate::core::recent::{self, RecentAttach};
+
+/// JSON files in the server data directory, next to crash dumps and host keys
+///
+/// `projects.json`, `sessions.json`, `notifications.json` and `commits.json` hold
+/// the projects, session history, notifications and agent commits,
+/// `do_not_disturb.json` the do-not-disturb
+/// switch; recent attaches and preferences keep their existing `recent.json` and
+/// `preferences.json` files.
+#[derive(Clone)]
+pub struct LocalStorage {
+    data_dir: PathBuf,
+    /// Serializes read-modify-write cycles on the files
+    lock: Arc<Mutex<()>>,
+}
+
+impl LocalStorage {
+    pub fn new(data_dir: PathBuf) -> Self {
+        Self {
+            data_dir,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Run file IO off the async runtime, one operation at a time
+    async fn with_files<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Path) -> Result<T> + Send + 'static,
+    {
+        let _guard = self.lock.lock().await;
+        let data_dir = self.data_dir.clone();
+        tokio::task::spawn_blocking(move || f(&data_dir)).await?
+    }
+}
+
+fn read_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_list<T: Serialize>(data_dir: &Path, path: &Path, items: &[T]) -> Result<()> {
+    std::fs::create_dir_all(data_dir)?;
+    std::fs::write(path, serde_json::to_string_pretty(items)?)?;
+    Ok(())
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn load_projects(&self) -> Result<Vec<StoredProject>> {
+        self.with_files(|data_dir| read_list(&data_dir.join("projects.json")))
+            .await
+    }
+
+    async fn save_project(&self, project: &StoredProject) -> Result<()> {
+        let project = project.clone();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("projects.json");
+            let mut projects: Vec<StoredProject> = read_list(&path)?;
+            match projects.iter_mut().find(|p| p.id == project.id) {
+                Some(existing) => *existing = project,
+                None => projects.push(project),
+            }
+            write_list(data_dir, &path, &projects)
+        })
+        .await
+    }
+
+    async fn delete_project(&self, project_id: &str) -> Result<()> {
+        let project_id = project_id.to_string();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("projects.json");
+            let mut projects: Vec<StoredProject> = read_list(&path)?;
+            projects.retain(|p| p.id != project_id);
+            write_list(data_dir, &path, &projects)
+        })
+        .await
+    }
+
+    async fn load_sessions(&self) -> Result<Vec<SessionRecord>> {
+        self.with_files(|data_dir| read_list(&data_dir.join("sessions.json")))
+            .await
+    }
+
+    async fn save_session(&self, session: &SessionRecord) -> Result<()> {
+        let session = session.clone();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("sessions.json");
+            let mut sessions: Vec<SessionRecord> = read_list(&path)?;
+            sessions.retain(|s| s.id != session.id);
+            sessions.insert(0, session);
+            sessions.truncate(MAX_SESSION_HISTORY);
+            write_list(data_dir, &path, &sessions)
+        })
+        .await
+    }
+
+    async fn end_session(
+        &self,
+        session_id: &str,
+        exited: bool,
+        exit_code: Option<u32>,
+    ) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("sessions.json");
+            let mut sessions: Vec<SessionRecord> = read_list(&path)?;
+            if let Some(session) = sessions.iter_mut().find(|s| s.id == session_id) {
+                session.ended_at = Some(chrono::Utc::now().to_rfc3339());
+                session.exited = exited;
+                session.exit_code = exit_code;
+                write_list(data_dir, &path, &sessions)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load_recent(&self) -> Result<Vec<RecentAttach>> {
+        self.with_files(recent::load_recent).await
+    }
+
+    async fn record_attach(&self, session_id: &str) -> Result<()> {
+        let session_id = session_id.to_string();
+        self.with_files(move |data_dir| recent::record_attach(data_dir, &session_id))
+            .await
+    }
+
+    async fn load_preferences(&self) -> Result<UiPreferences> {
+        self.with_files(preferences::load_preferences).await
+    }
+
+    async fn update_preferences(&self, update: &UpdatePreferencesRequest) -> Result<UiPreferences> {
+        let update = update.clone();
+        self.with_files(move |data_dir| {
+            let mut preferences = preferences::load_preferences(data_dir)?;
+            preferences.apply(&update);
+            preferences::save_preferences(data_dir, &preferences)?;
+            Ok(preferences)
+        })
+        .await
+    }
+
+    async fn load_notifications(&self) -> Result<Vec<Notification>> {
+        self.with_files(|data_dir| read_list(&data_dir.join("notifications.json")))
+            .await
+    }
+
+    async fn save_notification(&self, notification: &Notification) -> Result<()> {
+        let notification = notification.clone();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("notifications.json");
+            let mut notifications: Vec<Notification> = read_list(&path)?;
+            notifications.insert(0, notification);
+            notifications.truncate(MAX_NOTIFICATIONS);
+            write_list(data_dir, &path, &notifications)
+        })
+        .await
+    }
+
+    async fn mark_notification_read(&self, id: &str) -> Result<bool> {
+        let id = id.to_string();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("notifications.json");
+            let mut notifications: Vec<Notification> = read_list(&path)?;
+            let Some(notification) = notifications.iter_mut().find(|n| n.id == id) else {
+                return Ok(false);
+            };
+            notification.read = true;
+            write_list(data_dir, &path, &notifications)?;
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn load_do_not_disturb(&self) -> Result<DoNotDisturb> {
+        self.with_files(|data_dir| {
+            match std::fs::read_to_string(data_dir.join("do_not_disturb.json")) {
+                Ok(content) => Ok(serde_json::from_str(&content)?),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DoNotDisturb::default()),
+                Err(e) => Err(e.into()),
+            }
+        })
+        .await
+    }
+
+    async fn save_do_not_disturb(&self, dnd: &DoNotDisturb) -> Result<()> {
+        let dnd = dnd.clone();
+        self.with_files(move |data_dir| {
+            std::fs::create_dir_all(data_dir)?;
+            std::fs::write(
+                data_dir.join("do_not_disturb.json"),
+                serde_json::to_string_pretty(&dnd)?,
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn load_commits(&self, session_id: &str) -> Result<Vec<AgentCommit>> {
+        let session_id = session_id.to_string();
+        self.with_files(move |data_dir| {
+            let mut commits: Vec<AgentCommit> = read_list(&data_dir.join("commits.json"))?;
+            commits.retain(|commit| commit.session_id == session_id);
+            commits.reverse();
+            Ok(commits)
+        })
+        .await
+    }
+
+    async fn save_commit(&self, commit: &AgentCommit) -> Result<()> {
+        let commit = commit.clone();
+        self.with_files(move |data_dir| {
+            let path = data_dir.join("commits.json");
+            let mut commits: Vec<AgentCommit> = read_list(&path)?;
+            commits.insert(0, commit);
+            commits.truncate(MAX_COMMITS);
+            write_list(data_dir, &path, &commits)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::notifications::NotificationKind;
+    use crate::core::session::ProjectDefaults;
+
+    #[tokio::test]
+    async fn keeps_projects_and_newest_sessions_first() {
+        let data_dir = std::env::temp_dir().join(format!("codemux-storage-{}", std::process::id()));
+        let storage = LocalStorage::new(data_dir.clone());
+
+        let mut project = StoredProject {
+            id: "p1".to_string(),
+            name: "web (temporary)".to_string(),
+            path: "/src/web".into(),
+            parent: None,
+            defaults: ProjectDefaults::default(),
+        };
+        storage.save_project(&project).await.unwrap();
+        project.name = "web".to_string();
+        storage.save_project(&project).await.unwrap();
+        let removed = StoredProject {
+            id: "p2".to_string(),
+            name: "old".to_string(),
+            path: "/src/old".into(),
+            parent: None,
+            defaults: ProjectDefaults::default(),
+        };
+        storage.save_project(&removed).await.unwrap();
+        storage.delete_project("p2").await.unwrap();
+
+        for id in ["a", "b", "a"] {
+            let session = SessionRecord {
+                id: id.to_string(),
+                agent: "claude".to_string(),
+                project: Some("p1".to_string()),
+                color: "#61afef".to_string(),
+                icon: "✳️".to_string(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                ended_at: None,
+                exited: false,
+                exit_code: None,
+                links: Vec::new(),
+                cwd: None,
+            };
+            storage.save_session(&session).await.unwrap();
+        }
+        storage.end_session("b", true, Some(1)).await.unwrap();
+
+        let update = UpdatePreferencesRequest {
+            favorite_projects: Some(vec!["p1".to_string()]),
+            ..UpdatePreferencesRequest::default()
+        };
+        storage.update_preferences(&update).await.unwrap();
+
+        // A fresh handle on the same directory sees everything
+        let reopened = LocalStorage::new(data_dir.clone());
+        let projects = reopened.load_projects().await.unwrap();
+        let sessions = reopened.load_sessions().await.unwrap();
+        let preferences = reopened.load_preferences().await.unwrap();
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        assert_eq!(projects, vec![project]);
+        assert_eq!(
+            sessions.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert!(sessions[0].ended_at.is_none());
+        assert!(sessions[1].ended_at.is_some() && sessions[1].exited);
+        assert_eq!(sessions[1].exit_code, Some(1));
+        assert_eq!(preferences.favorite_projects, ["p1"]);
+    }
+
+    #[tokio::test]
+    async fn marks_notifications_read() {
+        let data_dir =
+            std::env::temp_dir().join(format!("codemux-notifications-{}", std::process::id()));
+        let storage = LocalStorage::new(data_dir.clone());
+
+        let exited = Notification::new(NotificationKind::Exited, "s1", "claude session exited");
+        let question = Notification::new(
+            NotificationKind::WaitingForInput,
+            "s2",
+            "Do you want to apply this edit?",
+        );
+        storage.save_notification(&exited).await.unwrap();
+        storage.save_notification(&question).await.unwrap();
+
+        assert!(storage.mark_notification_read(&exited.id).await.unwrap());
+        assert!(!storage.mark_notification_read("missing").await.unwrap());
+
+        let notifications = LocalStorage::new(data_dir.clone())
+            .load_notifications()
+            .await
+            .unwrap();
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        assert_eq!(
+            notifications
+                .iter()
+                .map(|n| (n.session_id.as_str(), n.read))
+                .collect::<Vec<_>>(),
+            [("s2", false), ("s1", true)]
+        );
+    }
+}
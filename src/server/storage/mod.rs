@@ -0,0 +1,155 @@
+//! Persistence for projects, session history, recent attaches, preferences,
+//! notifications and agent commits
+//!
+//! The session manager and web handlers only talk to the `Storage` trait, so a
+//! team server can keep its state in a shared database instead of the data
+//! directory. Pick the backend with the `[storage]` config section.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::core::commits::AgentCommit;
+use crate::core::config::StorageBackend;
+use crate::core::notifications::{DoNotDisturb, Notification};
+use crate::core::preferences::{UiPreferences, UpdatePreferencesRequest};
+use crate::core::recent::RecentAttach;
+use crate::core::session::ProjectDefaults;
+use crate::core::session_links::SessionLink;
+use crate::core::Config;
+
+mod local;
+#[cfg(feature = "postgres")]
+mod postgres;
+
+pub use local::LocalStorage;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresStorage;
+
+/// How many started sessions the history keeps
+pub const MAX_SESSION_HISTORY: usize = 500;
+
+/// A registered project
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredProject {
+    pub id: String,
+    pub name: String,
+    pub path: PathBuf,
+    /// ID of the project this one is a sub-project of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, skip_serializing_if = "ProjectDefaults::is_empty")]
+    pub defaults: ProjectDefaults,
+}
+
+/// A session the server started, kept after it ends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub id: String,
+    pub agent: String,
+    pub project: Option<String>,
+    pub color: String,
+    pub icon: String,
+    /// ISO 8601 timestamp of when the session started
+    pub created_at: String,
+    /// ISO 8601 timestamp of when it ended; unset while running, or if the server died first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+    /// Whether the agent exited on its own rather than being stopped
+    #[serde(default)]
+    pub exited: bool,
+    /// Exit code of the agent, when it exited and the code could be read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<u32>,
+    /// Template or sessions it came from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<SessionLink>,
+    /// Directory the agent ran in when it wasn't the project's, so a resumed
+    /// session starts there again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+}
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn load_projects(&self) -> Result<Vec<StoredProject>>;
+
+    /// Insert or replace the project with the same ID
+    async fn save_project(&self, project: &StoredProject) -> Result<()>;
+
+    /// Forget a project; a missing one is no error
+    async fn delete_project(&self, project_id: &str) -> Result<()>;
+
+    /// Session history, newest first
+    async fn load_sessions(&self) -> Result<Vec<SessionRecord>>;
+
+    /// Add a session to the history, dropping the oldest beyond `MAX_SESSION_HISTORY`
+    async fn save_session(&self, session: &SessionRecord) -> Result<()>;
+
+    /// Record that a session of the history ended just now
+    async fn end_session(
+        &self,
+        session_id: &str,
+        exited: bool,
+        exit_code: Option<u32>,
+    ) -> Result<()>;
+
+    /// Attach history, newest first
+    async fn load_recent(&self) -> Result<Vec<RecentAttach>>;
+
+    /// Move `session_id` to the front of the attach history
+    async fn record_attach(&self, session_id: &str) -> Result<()>;
+
+    async fn load_preferences(&self) -> Result<UiPreferences>;
+
+    /// Apply a partial update and return the full set, atomically for concurrent writers
+    async fn update_preferences(&self, update: &UpdatePreferencesRequest) -> Result<UiPreferences>;
+
+    /// Notifications, newest first
+    async fn load_notifications(&self) -> Result<Vec<Notification>>;
+
+    /// Add a notification, dropping the oldest beyond `MAX_NOTIFICATIONS`
+    async fn save_notification(&self, notification: &Notification) -> Result<()>;
+
+    /// Mark a notification read; false if there is none with that ID
+    async fn mark_notification_read(&self, id: &str) -> Result<bool>;
+
+    async fn load_do_not_disturb(&self) -> Result<DoNotDisturb>;
+
+    async fn save_do_not_disturb(&self, dnd: &DoNotDisturb) -> Result<()>;
+
+    /// Commits announced in a session, oldest first
+    async fn load_commits(&self, session_id: &str) -> Result<Vec<AgentCommit>>;
+
+    /// Add a commit, dropping the oldest beyond `MAX_COMMITS`
+    async fn save_commit(&self, commit: &AgentCommit) -> Result<()>;
+}
+
+/// Open the backend chosen in `[storage]`
+pub async fn open(config: &Config) -> Result<Arc<dyn Storage>> {
+    match config.storage.backend {
+        StorageBackend::Local => Ok(Arc::new(LocalStorage::new(config.server.data_dir.clone()))),
+        StorageBackend::Postgres => {
+            let url = config
+                .storage
+                .url
+                .as_deref()
+                .ok_or_else(|| anyhow!("storage.backend = \"postgres\" needs storage.url"))?;
+            open_postgres(url).await
+        }
+    }
+}
+
+#[cfg(feature = "postgres")]
+async fn open_postgres(url: &str) -> Result<Arc<dyn Storage>> {
+    Ok(Arc::new(PostgresStorage::connect(url).await?))
+}
+
+#[cfg(not(feature = "postgres"))]
+async fn open_postgres(_url: &str) -> Result<Arc<dyn Storage>> {
+    Err(anyhow!(
+        "storage.backend = \"postgres\" but this build lacks the postgres feature"
+    ))
+}
@@ -0,0 +1,449 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+use tokio_postgres::{Client, NoTls};
+
+use super::{SessionRecord, Storage, StoredProject, MAX_SESSION_HISTORY};
+use crate::core::commits::{AgentCommit, MAX_COMMITS};
+use crate::core::notifications::{DoNotDisturb, Notification, MAX_NOTIFICATIONS};
+use crate::core::preferences::{UiPreferences, UpdatePreferencesRequest};
+use crate::core::recent::{RecentAttach, MAX_RECENT};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS codemux_projects (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL,
+        path TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS codemux_sessions (
+        id TEXT PRIMARY KEY,
+        agent TEXT NOT NULL,
+        project TEXT,
+        color TEXT NOT NULL,
+        icon TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    ALTER TABLE codemux_projects ADD COLUMN IF NOT EXISTS parent TEXT;
+    ALTER TABLE codemux_projects ADD COLUMN IF NOT EXISTS defaults TEXT NOT NULL DEFAULT '{}';
+    ALTER TABLE codemux_sessions ADD COLUMN IF NOT EXISTS ended_at TEXT;
+    ALTER TABLE codemux_sessions ADD COLUMN IF NOT EXISTS exited BOOLEAN NOT NULL DEFAULT FALSE;
+    ALTER TABLE codemux_sessions ADD COLUMN IF NOT EXISTS links TEXT NOT NULL DEFAULT '[]';
+    ALTER TABLE codemux_sessions ADD COLUMN IF NOT EXISTS cwd TEXT;
+    ALTER TABLE codemux_sessions ADD COLUMN IF NOT EXISTS exit_code BIGINT;
+    CREATE TABLE IF NOT EXISTS codemux_recent (
+        session_id TEXT PRIMARY KEY,
+        attached_at TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS codemux_preferences (
+        id TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS codemux_notifications (
+        id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        session_id TEXT NOT NULL,
+        message TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        read BOOLEAN NOT NULL DEFAULT FALSE
+    );
+    ALTER TABLE codemux_notifications ADD COLUMN IF NOT EXISTS silenced BOOLEAN NOT NULL DEFAULT FALSE;
+    CREATE TABLE IF NOT EXISTS codemux_commits (
+        session_id TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        message TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        PRIMARY KEY (session_id, hash)
+    );
+";
+
+/// Preferences are shared by every user of a server, stored as one JSON row
+const PREFERENCES_ID: &str = "ui";
+/// The do-not-disturb switch is another row of the preferences table
+const DO_NOT_DISTURB_ID: &str = "do_not_disturb";
+
+/// A PostgreSQL database, so several servers behind a load balancer share their state
+///
+/// Tables are created on connect. Timestamps are stored as RFC 3339 text in
+/// UTC, which sorts chronologically.
+pub struct PostgresStorage {
+    client: Mutex<Client>,
+}
+
+impl PostgresStorage {
+    pub async fn connect(url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(url, NoTls)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to Postgres storage: {}", e))?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::error!("Postgres storage connection closed: {}", e);
+            }
+        });
+
+        client.batch_execute(SCHEMA).await?;
+        tracing::info!("Using Postgres storage");
+        Ok(Self {
+            client: Mutex::new(client),
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for PostgresStorage {
+    async fn load_projects(&self) -> Result<Vec<StoredProject>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT id, name, path, parent, defaults FROM codemux_projects ORDER BY name",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| StoredProject {
+                id: row.get(0),
+                name: row.get(1),
+                path: row.get::<_, String>(2).into(),
+                parent: row.get(3),
+                defaults: serde_json::from_str(row.get::<_, &str>(4)).unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    async fn save_project(&self, project: &StoredProject) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO codemux_projects (id, name, path, parent, defaults)
+                 VALUES ($1, $2, $3, $4, $5)
+                 ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, path = EXCLUDED.path,
+                     parent = EXCLUDED.parent, defaults = EXCLUDED.defaults",
+                &[
+                    &project.id,
+                    &project.name,
+                    &project.path.to_string_lossy().to_string(),
+                    &project.parent,
+                    &serde_json::to_string(&project.defaults)?,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_project(&self, project_id: &str) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute("DELETE FROM codemux_projects WHERE id = $1", &[&project_id])
+            .await?;
+        Ok(())
+    }
+
+    async fn load_sessions(&self) -> Result<Vec<SessionRecord>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT id, agent, project, color, icon, created_at, ended_at, exited, links, cwd,
+                     exit_code
+                 FROM codemux_sessions ORDER BY created_at DESC",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| SessionRecord {
+                id: row.get(0),
+                agent: row.get(1),
+                project: row.get(2),
+                color: row.get(3),
+                icon: row.get(4),
+                created_at: row.get(5),
+                ended_at: row.get(6),
+                exited: row.get(7),
+                // Links are stored as JSON, like the preferences
+                links: serde_json::from_str(row.get::<_, &str>(8)).unwrap_or_default(),
+                cwd: row.get(9),
+                exit_code: row.get::<_, Option<i64>>(10).map(|code| code as u32),
+            })
+            .collect())
+    }
+
+    async fn save_session(&self, session: &SessionRecord) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO codemux_sessions
+                     (id, agent, project, color, icon, created_at, ended_at, exited, links, cwd,
+                      exit_code)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 ON CONFLICT (id) DO UPDATE SET agent = EXCLUDED.agent,
+                     project = EXCLUDED.project, color = EXCLUDED.color,
+                     icon = EXCLUDED.icon, created_at = EXCLUDED.created_at,
+                     ended_at = EXCLUDED.ended_at, exited = EXCLUDED.exited,
+                     links = EXCLUDED.links, cwd = EXCLUDED.cwd,
+                     exit_code = EXCLUDED.exit_code",
+                &[
+                    &session.id,
+                    &session.agent,
+                    &session.project,
+                    &session.color,
+                    &session.icon,
+                    &session.created_at,
+                    &session.ended_at,
+                    &session.exited,
+                    &serde_json::to_string(&session.links)?,
+                    &session.cwd,
+                    &session.exit_code.map(i64::from),
+                ],
+            )
+            .await?;
+        client
+            .execute(
+                "DELETE FROM codemux_sessions WHERE id NOT IN (
+                     SELECT id FROM codemux_sessions ORDER BY created_at DESC LIMIT $1)",
+                &[&(MAX_SESSION_HISTORY as i64)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn end_session(
+        &self,
+        session_id: &str,
+        exited: bool,
+        exit_code: Option<u32>,
+    ) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "UPDATE codemux_sessions SET ended_at = $2, exited = $3, exit_code = $4
+                 WHERE id = $1",
+                &[
+                    &session_id,
+                    &chrono::Utc::now().to_rfc3339(),
+                    &exited,
+                    &exit_code.map(i64::from),
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_recent(&self) -> Result<Vec<RecentAttach>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT session_id, attached_at FROM codemux_recent ORDER BY attached_at DESC",
+                &[],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| RecentAttach {
+                session_id: row.get(0),
+                attached_at: row.get(1),
+            })
+            .collect())
+    }
+
+    async fn record_attach(&self, session_id: &str) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO codemux_recent (session_id, attached_at) VALUES ($1, $2)
+                 ON CONFLICT (session_id) DO UPDATE SET attached_at = EXCLUDED.attached_at",
+                &[&session_id, &chrono::Utc::now().to_rfc3339()],
+            )
+            .await?;
+        client
+            .execute(
+                "DELETE FROM codemux_recent WHERE session_id NOT IN (
+                     SELECT session_id FROM codemux_recent ORDER BY attached_at DESC LIMIT $1)",
+                &[&(MAX_RECENT as i64)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_preferences(&self) -> Result<UiPreferences> {
+        let client = self.client.lock().await;
+        let row = client
+            .query_opt(
+                "SELECT value FROM codemux_preferences WHERE id = $1",
+                &[&PREFERENCES_ID],
+            )
+            .await?;
+        match row {
+            Some(row) => Ok(serde_json::from_str(row.get(0))?),
+            None => Ok(UiPreferences::default()),
+        }
+    }
+
+    async fn update_preferences(&self, update: &UpdatePreferencesRequest) -> Result<UiPreferences> {
+        let mut client = self.client.lock().await;
+        let transaction = client.transaction().await?;
+        transaction
+            .execute(
+                "INSERT INTO codemux_preferences (id, value) VALUES ($1, $2)
+                 ON CONFLICT (id) DO NOTHING",
+                &[
+                    &PREFERENCES_ID,
+                    &serde_json::to_string(&UiPreferences::default())?,
+                ],
+            )
+            .await?;
+        // Lock the row so servers sharing the database don't lose each other's updates
+        let row = transaction
+            .query_one(
+                "SELECT value FROM codemux_preferences WHERE id = $1 FOR UPDATE",
+                &[&PREFERENCES_ID],
+            )
+            .await?;
+        let mut preferences: UiPreferences = serde_json::from_str(row.get(0))?;
+        preferences.apply(update);
+        transaction
+            .execute(
+                "UPDATE codemux_preferences SET value = $2 WHERE id = $1",
+                &[&PREFERENCES_ID, &serde_json::to_string(&preferences)?],
+            )
+            .await?;
+        transaction.commit().await?;
+        Ok(preferences)
+    }
+
+    async fn load_notifications(&self) -> Result<Vec<Notification>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT id, kind, session_id, message, created_at, read, silenced
+                 FROM codemux_notifications ORDER BY created_at DESC",
+                &[],
+            )
+            .await?;
+        rows.iter()
+            .map(|row| {
+                Ok(Notification {
+                    id: row.get(0),
+                    kind: serde_json::from_value(serde_json::Value::String(row.get(1)))?,
+                    session_id: row.get(2),
+                    message: row.get(3),
+                    created_at: row.get(4),
+                    read: row.get(5),
+                    silenced: row.get(6),
+                })
+            })
+            .collect()
+    }
+
+    async fn save_notification(&self, notification: &Notification) -> Result<()> {
+        let kind = match serde_json::to_value(notification.kind)? {
+            serde_json::Value::String(kind) => kind,
+            other => return Err(anyhow!("Unexpected notification kind {}", other)),
+        };
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO codemux_notifications
+                     (id, kind, session_id, message, created_at, read, silenced)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &notification.id,
+                    &kind,
+                    &notification.session_id,
+                    &notification.message,
+                    &notification.created_at,
+                    &notification.read,
+                    &notification.silenced,
+                ],
+            )
+            .await?;
+        client
+            .execute(
+                "DELETE FROM codemux_notifications WHERE id NOT IN (
+                     SELECT id FROM codemux_notifications ORDER BY created_at DESC LIMIT $1)",
+                &[&(MAX_NOTIFICATIONS as i64)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_notification_read(&self, id: &str) -> Result<bool> {
+        let client = self.client.lock().await;
+        let updated = client
+            .execute(
+                "UPDATE codemux_notifications SET read = TRUE WHERE id = $1",
+                &[&id],
+            )
+            .await?;
+        Ok(updated > 0)
+    }
+
+    async fn load_do_not_disturb(&self) -> Result<DoNotDisturb> {
+        let client = self.client.lock().await;
+        let row = client
+            .query_opt(
+                "SELECT value FROM codemux_preferences WHERE id = $1",
+                &[&DO_NOT_DISTURB_ID],
+            )
+            .await?;
+        match row {
+            Some(row) => Ok(serde_json::from_str(row.get(0))?),
+            None => Ok(DoNotDisturb::default()),
+        }
+    }
+
+    async fn save_do_not_disturb(&self, dnd: &DoNotDisturb) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO codemux_preferences (id, value) VALUES ($1, $2)
+                 ON CONFLICT (id) DO UPDATE SET value = EXCLUDED.value",
+                &[&DO_NOT_DISTURB_ID, &serde_json::to_string(dnd)?],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn load_commits(&self, session_id: &str) -> Result<Vec<AgentCommit>> {
+        let client = self.client.lock().await;
+        let rows = client
+            .query(
+                "SELECT session_id, hash, message, created_at FROM codemux_commits
+                 WHERE session_id = $1 ORDER BY created_at",
+                &[&session_id],
+            )
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| AgentCommit {
+                session_id: row.get(0),
+                hash: row.get(1),
+                message: row.get(2),
+                created_at: row.get(3),
+            })
+            .collect())
+    }
+
+    async fn save_commit(&self, commit: &AgentCommit) -> Result<()> {
+        let client = self.client.lock().await;
+        client
+            .execute(
+                "INSERT INTO codemux_commits (session_id, hash, message, created_at)
+                 VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING",
+                &[
+                    &commit.session_id,
+                    &commit.hash,
+                    &commit.message,
+                    &commit.created_at,
+                ],
+            )
+            .await?;
+        client
+            .execute(
+                "DELETE FROM codemux_commits WHERE (session_id, hash) NOT IN (
+                     SELECT session_id, hash FROM codemux_commits ORDER BY created_at DESC LIMIT $1)",
+                &[&(MAX_COMMITS as i64)],
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+use axum::{extract::State, response::Response};
+
+use super::types::AppState;
+use crate::core::json_api_response_with_headers;
+use crate::core::JsonApiResource;
+
+/// Whitelisted agents with their installed version and pin status
+pub async fn list_agents(State(state): State<AppState>) -> Response {
+    let agents: Vec<_> = state
+        .session_manager
+        .agent_versions()
+        .await
+        .into_iter()
+        .map(|status| JsonApiResource::<_, ()> {
+            resource_type: "agent".to_string(),
+            id: status.agent.clone(),
+            attributes: Some(status),
+            relationships: None,
+        })
+        .collect();
+
+    json_api_response_with_headers(agents)
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use super::types::AppState;
+use crate::core::json_api_error_response_with_headers;
+use crate::server::archive;
+
+/// The project's history, transcripts, recordings and checkpoints as a `.tar.zst`
+pub async fn get_project_archive(
+    State(state): State<AppState>,
+    Path(project): Path<String>,
+) -> Response {
+    match archive::build(&state.session_manager, &project).await {
+        Ok(Some(archive)) => {
+            tracing::info!(
+                "Archived project {} ({} files, {} bytes)",
+                archive.manifest.project.name,
+                archive.manifest.files.len(),
+                archive.data.len()
+            );
+            let disposition = format!("attachment; filename=\"{}\"", archive.file_name);
+            (
+                [
+                    (header::CONTENT_TYPE, "application/zstd".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                    (header::CACHE_CONTROL, "no-store".to_string()),
+                ],
+                archive.data,
+            )
+                .into_response()
+        }
+        Ok(None) => json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Project Not Found".to_string(),
+            format!("No project with ID or name {}", project),
+        ),
+        Err(e) => {
+            tracing::error!("Failed to archive project {}: {}", project, e);
+            json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Archive Failed".to_string(),
+                e.to_string(),
+            )
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use axum::{
+    extract::{Query, Request, State},
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+
+use super::json_api::json_api_error_response_with_headers;
+use super::oidc::session_cookie;
+use super::types::AppState;
+use crate::core::api_tokens::{TokenScope, API_TOKEN_PREFIX};
+use crate::core::auth::token_matches;
+
+/// Query parameter carrying the token, for browsers' WebSockets which can't
+/// send headers
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Let a request through if it carries the server's token, as an
+/// `Authorization: Bearer` header or a `token` query parameter
+///
+/// With `[web.oidc]` a `codemux login` token sent the same way, or a browser's
+/// sign-in cookie, also does; the `User` is then added to the request. So
+/// does a token from `/api/tokens` if its scope covers the request, see
+/// `required_scope`; the `ApiToken` is then added to the request. A share
+/// link's token only gets to its own session, see `session_allows`.
+pub async fn require_token(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(token) = state.auth_token.as_deref() else {
+        return next.run(request).await;
+    };
+
+    let sent = sent_token(request.headers(), request.uri());
+
+    if let Some(oidc) = &state.oidc {
+        let user = [sent.as_deref(), session_cookie(request.headers())]
+            .into_iter()
+            .flatten()
+            .find_map(|secret| oidc.user(secret.trim()));
+        if let Some(user) = user {
+            request.extensions_mut().insert(user);
+            return next.run(request).await;
+        }
+    }
+
+    let api_token = sent
+        .as_deref()
+        .and_then(|sent| state.api_tokens.authenticate(sent.trim()));
+    if let Some(api_token) = api_token {
+        let path = request.uri().path();
+        if let Some(session_id) = &api_token.session_id {
+            if !session_allows(session_id, path) {
+                return json_api_error_response_with_headers(
+                    StatusCode::FORBIDDEN,
+                    "Not Shared".to_string(),
+                    format!(
+                        "The token {:?} only opens session {}",
+                        api_token.name, session_id
+                    ),
+                );
+            }
+        }
+        let required = required_scope(request.method(), path);
+        if api_token.scope < required {
+            return json_api_error_response_with_headers(
+                StatusCode::FORBIDDEN,
+                "Insufficient Scope".to_string(),
+                format!(
+                    "The token {:?} has the {} scope; this needs {}",
+                    api_token.name,
+                    api_token.scope.as_str(),
+                    required.as_str()
+                ),
+            );
+        }
+        request.extensions_mut().insert(api_token);
+        return next.run(request).await;
+    }
+
+    match sent {
+        Some(sent) if token_matches(sent.trim(), token) => next.run(request).await,
+        Some(sent) if sent.trim().starts_with(API_TOKEN_PREFIX) => {
+            json_api_error_response_with_headers(
+                StatusCode::UNAUTHORIZED,
+                "Unauthorized".to_string(),
+                "The API token expired or was revoked".to_string(),
+            )
+        }
+        None if state.oidc.is_some() => json_api_error_response_with_headers(
+            StatusCode::UNAUTHORIZED,
+            "Login Required".to_string(),
+            "Sign in at /auth/login, or with `codemux login` for the CLI".to_string(),
+        ),
+        Some(_) => json_api_error_response_with_headers(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized".to_string(),
+            "The token is wrong; the server may have restarted with a new one".to_string(),
+        ),
+        None => json_api_error_response_with_headers(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized".to_string(),
+            "Send the server's token as a Bearer token or the token query parameter".to_string(),
+        ),
+    }
+}
+
+/// Whether a request to an open route carries credentials `require_token`
+/// would let through, for routes that tell those callers more; share links'
+/// tokens don't count
+pub fn is_authenticated(state: &AppState, headers: &HeaderMap, uri: &Uri) -> bool {
+    let Some(token) = state.auth_token.as_deref() else {
+        return true;
+    };
+    let sent = sent_token(headers, uri);
+    let signed_in = state.oidc.as_ref().is_some_and(|oidc| {
+        [sent.as_deref(), session_cookie(headers)]
+            .into_iter()
+            .flatten()
+            .any(|secret| oidc.user(secret.trim()).is_some())
+    });
+    let Some(sent) = sent else {
+        return signed_in;
+    };
+    signed_in
+        || token_matches(sent.trim(), token)
+        || state
+            .api_tokens
+            .authenticate(sent.trim())
+            .is_some_and(|api_token| api_token.session_id.is_none())
+}
+
+/// The token a request sends, as a Bearer token or the `token` query parameter
+fn sent_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    bearer.or_else(|| {
+        Query::<TokenQuery>::try_from_uri(uri)
+            .ok()
+            .and_then(|query| query.0.token)
+    })
+}
+
+/// Scope an API token needs for a request
+///
+/// Reading takes `read`, which also watches sessions over the terminal
+/// WebSocket: `websocket_handler` makes such connections read-only. The raw
+/// WebSocket has no read-only mode, so typing into sessions takes
+/// `sessions:write`. Managing tokens, the log level, adopting processes and
+/// stopping the server take `admin`.
+fn required_scope(method: &Method, path: &str) -> TokenScope {
+    let admin = [
+        "/api/tokens",
+        "/api/shutdown",
+        "/api/handover",
+        "/api/adopt",
+        "/api/log-level",
+    ];
+    if admin.iter().any(|prefix| path.starts_with(prefix)) {
+        TokenScope::Admin
+    } else if (method == Method::GET || method == Method::HEAD)
+        && !(path.starts_with("/ws/session/") && path.ends_with("/raw"))
+    {
+        TokenScope::Read
+    } else {
+        TokenScope::SessionsWrite
+    }
+}
+
+/// Whether a share link's token for `session_id` may make a request to `path`:
+/// the session's WebSockets and API routes, except sharing it again
+fn session_allows(session_id: &str, path: &str) -> bool {
+    let under = |prefix: String| {
+        path.strip_prefix(&prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    let api = format!("/api/sessions/{}", session_id);
+    under(format!("/ws/{}", session_id))
+        || under(format!("/ws/session/{}", session_id))
+        || (under(api.clone()) && path != format!("{}/share", api))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::api_tokens::TokenScope;
+    use crate::server::manager::SessionManagerHandle;
+    use crate::server::web::{build_router, AppState};
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn requires_token_on_api_but_not_web_ui() {
+        let mut config = crate::core::Config::default();
+        let data_dir = std::env::temp_dir().join(format!("codemux-auth-{}", std::process::id()));
+        config.server.data_dir = data_dir.clone();
+        let state = AppState::new(SessionManagerHandle::new(config)).with_auth_token("secret");
+        let read_only = state
+            .api_tokens
+            .create("ci", TokenScope::Read, None)
+            .unwrap();
+        let share = state.api_tokens.share("shared", true, None).unwrap();
+        let app = build_router(state);
+
+        let status = |request: Request<Body>| {
+            let app = app.clone();
+            async move { app.oneshot(request).await.unwrap().status() }
+        };
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        let bearer = |token: &str| {
+            Request::get("/api/paste-settings")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        assert_eq!(
+            status(get("/api/paste-settings")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(status(bearer("wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(bearer("secret")).await, StatusCode::OK);
+        assert_eq!(
+            status(get("/api/paste-settings?token=secret")).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            status(get("/ws/some-session")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_ne!(status(get("/")).await, StatusCode::UNAUTHORIZED);
+
+        assert_eq!(status(bearer(&read_only.secret)).await, StatusCode::OK);
+        let create_token = Request::post("/api/tokens")
+            .header(
+                header::AUTHORIZATION,
+                format!("Bearer {}", read_only.secret),
+            )
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"name":"escalated","scope":"admin"}"#))
+            .unwrap();
+        assert_eq!(status(create_token).await, StatusCode::FORBIDDEN);
+
+        // Share links open their session and nothing else
+        assert_eq!(status(bearer(&share.secret)).await, StatusCode::FORBIDDEN);
+        let shared = |uri: &str| get(&format!("{}?token={}", uri, share.secret));
+        assert_eq!(
+            status(shared("/api/sessions/shared")).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            status(shared("/api/sessions/other")).await,
+            StatusCode::FORBIDDEN
+        );
+        let reshare = Request::post(format!("/api/sessions/shared/share?token={}", share.secret))
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(status(reshare).await, StatusCode::FORBIDDEN);
+        let _ = std::fs::remove_dir_all(&data_dir);
+    }
+}
@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+use std::path::PathBuf;
+
+use super::types::AppState;
+use crate::core::checkpoint::{self, Checkpoint, RestoredCheckpoint};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+fn checkpoint_resource(checkpoint: Checkpoint) -> JsonApiResource<Checkpoint, ()> {
+    JsonApiResource {
+        resource_type: "checkpoint".to_string(),
+        id: checkpoint.name.clone(),
+        attributes: Some(checkpoint),
+        relationships: None,
+    }
+}
+
+fn session_not_found(session_id: &str) -> Response {
+    json_api_error_response_with_headers(
+        StatusCode::NOT_FOUND,
+        "Session Not Found".to_string(),
+        format!("Session with id '{}' not found", session_id),
+    )
+}
+
+/// The session's ID, which its checkpoints are kept under, and its working
+/// directory; `id` may be the session's name
+async fn session_dir(state: &AppState, id: &str) -> Option<(String, PathBuf)> {
+    let session = state.session_manager.get_session(id).await?;
+    let dir = super::git::get_session_working_dir(&session.id, state).await?;
+    Some((session.id, PathBuf::from(dir)))
+}
+
+/// Git checkpoints of the session's working tree, oldest first: one per agent
+/// turn with `[checkpoints]` on, and the deletion guard's safety checkpoints
+pub async fn list_session_checkpoints(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Response {
+    let Some((session_id, dir)) = session_dir(&state, &session_id).await else {
+        return session_not_found(&session_id);
+    };
+    let listed =
+        tokio::task::spawn_blocking(move || checkpoint::session_checkpoints(&dir, &session_id))
+            .await
+            .unwrap_or_default();
+    json_api_response_with_headers(
+        listed
+            .into_iter()
+            .map(checkpoint_resource)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Put the session's working tree back the way it was at a checkpoint, given
+/// by name or commit; the files as they are now are checkpointed first, so
+/// the restore can be undone the same way
+pub async fn restore_session_checkpoint(
+    State(state): State<AppState>,
+    Path((session_id, name)): Path<(String, String)>,
+) -> Response {
+    let Some((session_id, dir)) = session_dir(&state, &session_id).await else {
+        return session_not_found(&session_id);
+    };
+    let found = {
+        let (dir, session_id) = (dir.clone(), session_id.clone());
+        tokio::task::spawn_blocking(move || checkpoint::session_checkpoints(&dir, &session_id))
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .find(|checkpoint| checkpoint.name == name || checkpoint.commit.starts_with(&name))
+    };
+    let Some(restored) = found else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Checkpoint Not Found".to_string(),
+            format!("Session {} has no checkpoint {}", session_id, name),
+        );
+    };
+
+    let result = {
+        let (dir, session_id, commit) = (dir.clone(), session_id.clone(), restored.commit.clone());
+        tokio::task::spawn_blocking(move || -> anyhow::Result<Option<String>> {
+            let message = format!("codemux: {} before restore", session_id);
+            let backup = match checkpoint::snapshot(&dir, &message)? {
+                Some(current) => {
+                    let name = format!(
+                        "before-restore-{}",
+                        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+                    );
+                    checkpoint::pin(&dir, &format!("{}/{}", session_id, name), &current)?;
+                    Some(name)
+                }
+                None => None,
+            };
+            checkpoint::restore(&dir, &commit)?;
+            Ok(backup)
+        })
+        .await
+    };
+    match result {
+        Ok(Ok(backup)) => {
+            tracing::info!(
+                "Restored checkpoint {} of session {} in {}",
+                restored.name,
+                session_id,
+                dir.display()
+            );
+            json_api_response_with_headers(JsonApiResource::<_, ()> {
+                resource_type: "checkpoint-restore".to_string(),
+                id: restored.name.clone(),
+                attributes: Some(RestoredCheckpoint { restored, backup }),
+                relationships: None,
+            })
+        }
+        Ok(Err(e)) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Checkpoint Not Restored".to_string(),
+            e.to_string(),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Checkpoint Not Restored".to_string(),
+            e.to_string(),
+        ),
+    }
+}
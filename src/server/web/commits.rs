@@ -0,0 +1,39 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::types::AppState;
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Commits the session's agent announced, oldest first; kept after the session ends
+pub async fn get_session_commits(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Response {
+    match state
+        .session_manager
+        .storage()
+        .load_commits(&session_id)
+        .await
+    {
+        Ok(commits) => json_api_response_with_headers(
+            commits
+                .into_iter()
+                .map(|commit| JsonApiResource::<_, ()> {
+                    resource_type: "commit".to_string(),
+                    id: commit.hash.clone(),
+                    attributes: Some(commit),
+                    relationships: None,
+                })
+                .collect::<Vec<_>>(),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Commits Unavailable".to_string(),
+            e.to_string(),
+        ),
+    }
+}
@@ -0,0 +1,133 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+use std::path::PathBuf;
+
+use super::types::AppState;
+use crate::core::agent_adapter::{AgentAdapter, ClaudeAdapter};
+use crate::core::project_groups;
+use crate::core::transcript::{ConversationAttributes, SessionTranscript, TranscriptStats};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+use crate::server::claude_cache::CachedSession;
+
+fn conversation_resource(
+    conversation: CachedSession,
+) -> JsonApiResource<ConversationAttributes, ()> {
+    JsonApiResource {
+        resource_type: "conversation".to_string(),
+        id: conversation.session_id,
+        attributes: Some(ConversationAttributes {
+            agent: conversation.agent,
+            title: conversation.title,
+            last_modified: conversation.last_modified.to_rfc3339(),
+            size: conversation.file_size,
+            active: conversation.is_active,
+        }),
+        relationships: None,
+    }
+}
+
+fn project_not_found(project: &str) -> Response {
+    json_api_error_response_with_headers(
+        StatusCode::NOT_FOUND,
+        "Project Not Found".to_string(),
+        format!("No project with ID or name {}", project),
+    )
+}
+
+/// Claude conversations kept for the project, newest first
+async fn project_conversations(state: &AppState, project: &str) -> Option<Vec<CachedSession>> {
+    let projects = state.session_manager.list_projects().await;
+    let path = project_groups::find_project(&projects, project)?
+        .attributes
+        .as_ref()?
+        .path
+        .clone();
+    Some(
+        state
+            .session_manager
+            .project_conversations(PathBuf::from(path))
+            .await,
+    )
+}
+
+/// The project's past Claude conversations, newest first, read from
+/// `~/.claude/projects` without a running session
+pub async fn list_project_conversations(
+    State(state): State<AppState>,
+    Path(project): Path<String>,
+) -> Response {
+    let Some(conversations) = project_conversations(&state, &project).await else {
+        return project_not_found(&project);
+    };
+    json_api_response_with_headers(
+        conversations
+            .into_iter()
+            .map(conversation_resource)
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Messages and token/cost totals of one of the project's conversations
+pub async fn get_conversation_messages(
+    State(state): State<AppState>,
+    Path((project, conversation_id)): Path<(String, String)>,
+) -> Response {
+    let Some(conversations) = project_conversations(&state, &project).await else {
+        return project_not_found(&project);
+    };
+    let Some(conversation) = conversations
+        .into_iter()
+        .find(|conversation| conversation.session_id == conversation_id)
+    else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Conversation Not Found".to_string(),
+            format!(
+                "Project {} has no conversation with ID {}",
+                project, conversation_id
+            ),
+        );
+    };
+
+    let path = conversation.file_path;
+    let read = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || {
+            let content = std::fs::read_to_string(&path)?;
+            let adapter = ClaudeAdapter;
+            Ok::<_, std::io::Error>(adapter.messages(&adapter.parse_transcript(&content)))
+        })
+        .await
+    };
+    let messages = match read {
+        Ok(Ok(messages)) => messages,
+        Ok(Err(e)) => {
+            return json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Transcript Unreadable".to_string(),
+                format!("Failed to read {}: {}", path.display(), e),
+            )
+        }
+        Err(e) => {
+            return json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Transcript Unreadable".to_string(),
+                e.to_string(),
+            )
+        }
+    };
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "transcript".to_string(),
+        id: conversation_id,
+        attributes: Some(SessionTranscript {
+            path: Some(path.to_string_lossy().into_owned()),
+            stats: TranscriptStats::of(&messages),
+            messages,
+        }),
+        relationships: None,
+    })
+}
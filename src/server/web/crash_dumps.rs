@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+
+use super::types::AppState;
+use crate::core::crash_dump::{write_crash_dump, RenderCrashReport};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Store a render crash report sent by the web UI
+pub async fn create_crash_dump(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+    Json(report): Json<RenderCrashReport>,
+) -> Response {
+    let data_dir = state.session_manager.data_dir().to_path_buf();
+    let result = tokio::task::spawn_blocking(move || {
+        write_crash_dump(&data_dir, &session_id, &report).map(|(id, path)| (id, path, report))
+    })
+    .await;
+
+    match result {
+        Ok(Ok((crash_id, path, report))) => {
+            tracing::warn!(
+                "{} renderer hit an inconsistency ({}), crash dump {} written to {}",
+                report.source,
+                report.reason,
+                crash_id,
+                path.display()
+            );
+            json_api_response_with_headers(JsonApiResource::<_, ()> {
+                resource_type: "crash-dump".to_string(),
+                id: crash_id,
+                attributes: Some(serde_json::json!({
+                    "path": path,
+                    "update_count": report.updates.len(),
+                })),
+                relationships: None,
+            })
+        }
+        Ok(Err(e)) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Crash Dump Failed".to_string(),
+            e.to_string(),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Crash Dump Failed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::types::{AppState, DigestQuery};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Summary of the sessions of the last `hours` (24 by default), as the daily digest has it
+pub async fn get_digest(
+    State(state): State<AppState>,
+    Query(query): Query<DigestQuery>,
+) -> Response {
+    let until = chrono::Utc::now();
+    let since = until - chrono::Duration::hours(query.hours.unwrap_or(24).max(1) as i64);
+    match state.session_manager.digest(since, until).await {
+        Ok(digest) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "digest".to_string(),
+            id: digest.until.clone(),
+            attributes: Some(digest),
+            relationships: None,
+        }),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Digest Unavailable".to_string(),
+            e.to_string(),
+        ),
+    }
+}
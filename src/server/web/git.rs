@@ -1,14 +1,31 @@
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
+use std::collections::HashMap;
 use std::process::Command;
 
-use super::types::{AppState, GitDiff, GitFileDiff, GitFileStatus, GitStatus};
-
+use super::types::{AppState, GitDiff, GitDiffQuery, GitFileDiff, GitFileStatus, GitStatus};
+use crate::core::project_groups;
+use crate::core::session::DiffStats;
+
+/// Git's ID of the empty tree, to diff against before the first commit
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}/git/status",
+    tag = "git",
+    params(("id" = String, Path, description = "Session ID or name")),
+    responses(
+        (status = 200, description = "`git status` of the session's directory", body = GitStatus),
+        (status = 404, description = "No such session"),
+        (status = 500, description = "git failed"),
+    )
+)]
 pub async fn get_git_status(
     Path(session_id): Path<String>,
     State(state): State<AppState>,
@@ -23,17 +40,23 @@ pub async fn get_git_status(
         }
     };
 
-    match execute_git_status(&working_dir).await {
-        Ok(status) => Json(status).into_response(),
-        Err(e) => Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(Body::from(format!("Git error: {}", e)))
-            .unwrap(),
-    }
+    git_status_response(&working_dir).await
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}/git/diff",
+    tag = "git",
+    params(("id" = String, Path, description = "Session ID or name"), GitDiffQuery),
+    responses(
+        (status = 200, description = "Changes since the last commit, file by file", body = GitDiff),
+        (status = 404, description = "No such session"),
+        (status = 500, description = "git failed"),
+    )
+)]
 pub async fn get_git_diff(
     Path(session_id): Path<String>,
+    Query(query): Query<GitDiffQuery>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
     let working_dir = match get_session_working_dir(&session_id, &state).await {
@@ -46,17 +69,23 @@ pub async fn get_git_diff(
         }
     };
 
-    match execute_git_diff(&working_dir).await {
-        Ok(diff) => Json(diff).into_response(),
-        Err(e) => Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(Body::from(format!("Git error: {}", e)))
-            .unwrap(),
-    }
+    git_diff_response(&working_dir, query.staged).await
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}/git/diff/{path}",
+    tag = "git",
+    params(("id" = String, Path, description = "Session ID or name"), ("path" = String, Path, description = "File path relative to the directory"), GitDiffQuery),
+    responses(
+        (status = 200, description = "Unified diff of the file", body = String, content_type = "text/plain"),
+        (status = 404, description = "No such session"),
+        (status = 500, description = "git failed"),
+    )
+)]
 pub async fn get_git_file_diff(
     Path((session_id, file_path)): Path<(String, String)>,
+    Query(query): Query<GitDiffQuery>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
     let working_dir = match get_session_working_dir(&session_id, &state).await {
@@ -69,7 +98,103 @@ pub async fn get_git_file_diff(
         }
     };
 
-    match execute_git_file_diff(&working_dir, &file_path).await {
+    git_file_diff_response(&working_dir, &file_path, query.staged).await
+}
+
+/// `git status` of a project's directory, by project ID or name
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/git/status",
+    tag = "git",
+    params(("id" = String, Path, description = "Project ID or name")),
+    responses(
+        (status = 200, description = "`git status` of the project's directory", body = GitStatus),
+        (status = 404, description = "No such project"),
+        (status = 500, description = "git failed"),
+    )
+)]
+pub async fn get_project_git_status(
+    Path(project): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    match get_project_dir(&project, &state).await {
+        Some(dir) => git_status_response(&dir).await,
+        None => project_not_found(),
+    }
+}
+
+/// Changes in a project's directory since its last commit, file by file
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/diff",
+    tag = "git",
+    params(("id" = String, Path, description = "Project ID or name"), GitDiffQuery),
+    responses(
+        (status = 200, description = "Changes since the last commit, file by file", body = GitDiff),
+        (status = 404, description = "No such project"),
+        (status = 500, description = "git failed"),
+    )
+)]
+pub async fn get_project_diff(
+    Path(project): Path<String>,
+    Query(query): Query<GitDiffQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    match get_project_dir(&project, &state).await {
+        Some(dir) => git_diff_response(&dir, query.staged).await,
+        None => project_not_found(),
+    }
+}
+
+/// Unified diff of one file in a project's directory
+#[utoipa::path(
+    get,
+    path = "/api/projects/{id}/diff/{path}",
+    tag = "git",
+    params(("id" = String, Path, description = "Project ID or name"), ("path" = String, Path, description = "File path relative to the directory"), GitDiffQuery),
+    responses(
+        (status = 200, description = "Unified diff of the file", body = String, content_type = "text/plain"),
+        (status = 404, description = "No such project"),
+        (status = 500, description = "git failed"),
+    )
+)]
+pub async fn get_project_file_diff(
+    Path((project, file_path)): Path<(String, String)>,
+    Query(query): Query<GitDiffQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    match get_project_dir(&project, &state).await {
+        Some(dir) => git_file_diff_response(&dir, &file_path, query.staged).await,
+        None => project_not_found(),
+    }
+}
+
+async fn git_status_response(working_dir: &str) -> Response {
+    match execute_git_status(working_dir).await {
+        Ok(status) => Json(status).into_response(),
+        Err(e) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(format!("Git error: {}", e)))
+            .unwrap(),
+    }
+}
+
+async fn git_diff_response(working_dir: &str, staged: Option<bool>) -> Response {
+    match execute_git_diff(working_dir, staged).await {
+        Ok(diff) => Json(diff).into_response(),
+        Err(e) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::from(format!("Git error: {}", e)))
+            .unwrap(),
+    }
+}
+
+async fn git_file_diff_response(
+    working_dir: &str,
+    file_path: &str,
+    staged: Option<bool>,
+) -> Response {
+    match execute_git_file_diff(working_dir, file_path, staged).await {
         Ok(diff) => Response::builder()
             .status(StatusCode::OK)
             .header(header::CONTENT_TYPE, "text/plain")
@@ -82,31 +207,44 @@ pub async fn get_git_file_diff(
     }
 }
 
+fn project_not_found() -> Response {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("Project not found"))
+        .unwrap()
+}
+
 // Helper functions
-async fn get_session_working_dir(session_id: &str, state: &AppState) -> Option<String> {
+pub(super) async fn get_session_working_dir(session_id: &str, state: &AppState) -> Option<String> {
     // Get session info from session manager
     let session_info = state.session_manager.get_session(session_id).await?;
-    
+
     // Get the project ID from the session
     let project_id = session_info.attributes?.project?;
-    
+
     // Get all projects to find the one matching our project_id
     let projects = state.session_manager.list_projects().await;
-    
+
     // Find the project with the matching ID and return its path
     for project in projects {
         if project.id == project_id {
             return project.attributes?.path.into();
         }
     }
-    
+
     // Fallback to current directory if project not found
     std::env::current_dir()
         .ok()
         .map(|p| p.to_string_lossy().to_string())
 }
 
-async fn execute_git_status(
+async fn get_project_dir(project: &str, state: &AppState) -> Option<String> {
+    let projects = state.session_manager.list_projects().await;
+    let project = project_groups::find_project(&projects, project)?;
+    Some(project.attributes.as_ref()?.path.clone())
+}
+
+pub(super) async fn execute_git_status(
     working_dir: &str,
 ) -> Result<GitStatus, Box<dyn std::error::Error + Send + Sync>> {
     let output = Command::new("git")
@@ -122,6 +260,12 @@ async fn execute_git_status(
     let mut files = Vec::new();
     let mut branch = None;
 
+    let staged_stats = numstat(working_dir, &["--cached"])?;
+    let unstaged_stats = numstat(working_dir, &[])?;
+    let mut staged_files = Vec::new();
+    let mut unstaged_files = Vec::new();
+    let mut untracked_files = Vec::new();
+
     for line in output_str.lines() {
         if line.starts_with("##") {
             // Branch information
@@ -134,24 +278,42 @@ async fn execute_git_status(
                     .to_string(),
             );
         } else if line.len() >= 3 {
-            let status_chars = &line[0..2];
-            let file_path = line[3..].to_string();
-
-            let status = match status_chars {
-                " M" | "M " | "MM" => "modified",
-                "A " | "AM" => "added",
-                " D" | "D " => "deleted",
-                "R " => "renamed",
-                "??" => "untracked",
-                _ => "unknown",
+            // XY: the change in the index, then the one in the working tree
+            let mut status_chars = line[0..2].chars();
+            let index = status_chars.next().unwrap_or(' ');
+            let worktree = status_chars.next().unwrap_or(' ');
+            let (old_path, file_path) = match line[3..].split_once(" -> ") {
+                Some((old, new)) => (Some(old.to_string()), new.to_string()),
+                None => (None, line[3..].to_string()),
+            };
+            let entry = |status: &str, stats: &HashMap<String, (u32, u32)>| {
+                let counts = stats.get(&file_path);
+                GitFileStatus {
+                    path: file_path.clone(),
+                    status: status.to_string(),
+                    old_path: old_path.clone(),
+                    additions: counts.map(|(additions, _)| *additions),
+                    deletions: counts.map(|(_, deletions)| *deletions),
+                }
             };
 
-            files.push(GitFileStatus {
-                path: file_path,
-                status: status.to_string(),
-                additions: None, // TODO: Get from git diff --numstat
-                deletions: None,
-            });
+            if (index, worktree) == ('?', '?') {
+                untracked_files.push(entry("untracked", &HashMap::new()));
+                files.push(entry("untracked", &HashMap::new()));
+                continue;
+            }
+            let staged = change_name(index).map(|status| entry(status, &staged_stats));
+            let unstaged = change_name(worktree).map(|status| entry(status, &unstaged_stats));
+            let status = staged
+                .as_ref()
+                .or(unstaged.as_ref())
+                .map_or("unknown", |file| file.status.as_str());
+            let mut file = entry(status, &HashMap::new());
+            file.additions = sum(staged.as_ref(), unstaged.as_ref(), |file| file.additions);
+            file.deletions = sum(staged.as_ref(), unstaged.as_ref(), |file| file.deletions);
+            files.push(file);
+            staged_files.extend(staged);
+            unstaged_files.extend(unstaged);
         }
     }
 
@@ -160,17 +322,142 @@ async fn execute_git_status(
         files,
         branch,
         clean: is_clean,
+        staged_files,
+        unstaged_files,
+        untracked_files,
     })
 }
 
+/// Name of a `git status --porcelain` change letter, `None` for unchanged
+fn change_name(letter: char) -> Option<&'static str> {
+    match letter {
+        'M' | 'T' => Some("modified"),
+        'A' => Some("added"),
+        'D' => Some("deleted"),
+        'R' => Some("renamed"),
+        'C' => Some("copied"),
+        'U' => Some("conflicted"),
+        _ => None,
+    }
+}
+
+fn sum(
+    staged: Option<&GitFileStatus>,
+    unstaged: Option<&GitFileStatus>,
+    count: impl Fn(&GitFileStatus) -> Option<u32>,
+) -> Option<u32> {
+    match (staged.and_then(&count), unstaged.and_then(&count)) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    }
+}
+
+/// Lines added and deleted per path, from `git diff --numstat`; binary files are left out
+fn numstat(
+    working_dir: &str,
+    args: &[&str],
+) -> Result<HashMap<String, (u32, u32)>, Box<dyn std::error::Error + Send + Sync>> {
+    let output = Command::new("git")
+        .args(["diff", "--numstat", "--no-renames"])
+        .args(args)
+        .current_dir(working_dir)
+        .output()?;
+    let output_str = String::from_utf8_lossy(&output.stdout);
+    Ok(output_str
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let additions = fields.next()?.parse().ok()?;
+            let deletions = fields.next()?.parse().ok()?;
+            Some((fields.next()?.to_string(), (additions, deletions)))
+        })
+        .collect())
+}
+
+/// Uncommitted changes in `working_dir`, staged or not, with untracked files;
+/// `None` outside a git repository
+pub(super) async fn execute_diff_stats(working_dir: &str) -> Option<DiffStats> {
+    let diff = execute_git_diff(working_dir, None).await.ok()?;
+    Some(diff.summary)
+}
+
+/// What `git diff` compares the working tree against: the index for
+/// unstaged changes, the last commit otherwise
+fn diff_base(
+    working_dir: &str,
+    staged: Option<bool>,
+) -> Result<Vec<&'static str>, Box<dyn std::error::Error + Send + Sync>> {
+    let head = Command::new("git")
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .current_dir(working_dir)
+        .output()?;
+    // Before the first commit everything is compared against nothing
+    let last_commit = if head.status.success() {
+        "HEAD"
+    } else {
+        EMPTY_TREE
+    };
+    Ok(match staged {
+        Some(true) => vec!["--cached", last_commit],
+        Some(false) => vec![],
+        None => vec![last_commit],
+    })
+}
+
+fn untracked_files(working_dir: &str) -> Vec<String> {
+    let Ok(output) = Command::new("git")
+        .args(["ls-files", "--others", "--exclude-standard"])
+        .current_dir(working_dir)
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Diff showing an untracked file as added in full, with its line count
+fn untracked_diff(working_dir: &str, file_path: &str) -> (String, u32) {
+    // Read the full content of the untracked file
+    let file_content = std::fs::read_to_string(std::path::Path::new(working_dir).join(file_path))
+        .unwrap_or_else(|_| String::from("Binary file or read error"));
+
+    // Create a fake diff showing the entire file as added
+    let fake_diff = if file_content.is_empty() {
+        format!("diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..0000000\n--- /dev/null\n+++ b/{}\n", file_path, file_path, file_path)
+    } else {
+        let mut diff_lines = vec![
+            format!("diff --git a/{} b/{}", file_path, file_path),
+            "new file mode 100644".to_string(),
+            "index 0000000..0000000".to_string(),
+            "--- /dev/null".to_string(),
+            format!("+++ b/{}", file_path),
+        ];
+
+        // Add each line of the file as an addition
+        for line in file_content.lines() {
+            diff_lines.push(format!("+{}", line));
+        }
+
+        diff_lines.join("\n")
+    };
+
+    (fake_diff, file_content.lines().count() as u32)
+}
+
 async fn execute_git_diff(
     working_dir: &str,
+    staged: Option<bool>,
 ) -> Result<GitDiff, Box<dyn std::error::Error + Send + Sync>> {
     let mut files = Vec::new();
+    let base = diff_base(working_dir, staged)?;
 
     // Get tracked file changes
     let output = Command::new("git")
-        .args(["diff", "--name-status"])
+        .args(["diff", "--name-status", "-M"])
+        .args(&base)
         .current_dir(working_dir)
         .output()?;
 
@@ -181,98 +468,78 @@ async fn execute_git_diff(
     let output_str = String::from_utf8_lossy(&output.stdout);
 
     for line in output_str.lines() {
-        if let Some((status_char, file_path)) = line.split_once('\t') {
-            let status = match status_char {
-                "M" => "modified",
-                "A" => "added",
-                "D" => "deleted",
-                "R" => "renamed",
-                _ => "unknown",
-            };
-
-            // Get detailed diff for this file
-            let diff_output = Command::new("git")
-                .args(["diff", file_path])
-                .current_dir(working_dir)
-                .output()?;
-
-            let diff_content = String::from_utf8_lossy(&diff_output.stdout).to_string();
-
-            // Parse additions/deletions from diff
-            let (additions, deletions) = parse_diff_stats(&diff_content);
+        let mut fields = line.split('\t');
+        let (Some(status_field), Some(first_path)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        // Renames list the old path, then the new one
+        let (old_path, file_path) = match fields.next() {
+            Some(new_path) => (Some(first_path), new_path),
+            None => (None, first_path),
+        };
+        let status = status_field
+            .chars()
+            .next()
+            .and_then(change_name)
+            .unwrap_or("unknown");
+
+        // Get detailed diff for this file
+        let diff_output = Command::new("git")
+            .args(["diff", "-M"])
+            .args(&base)
+            .arg("--")
+            .args(old_path)
+            .arg(file_path)
+            .current_dir(working_dir)
+            .output()?;
+
+        let diff_content = String::from_utf8_lossy(&diff_output.stdout).to_string();
+
+        // Parse additions/deletions from diff
+        let (additions, deletions) = parse_diff_stats(&diff_content);
+
+        files.push(GitFileDiff {
+            path: file_path.to_string(),
+            old_path: old_path.map(str::to_string),
+            status: status.to_string(),
+            additions,
+            deletions,
+            diff: diff_content,
+        });
+    }
 
+    // Add untracked files (show full content as "added"); they are never staged
+    if staged != Some(true) {
+        for file_path in untracked_files(working_dir) {
+            let (diff, line_count) = untracked_diff(working_dir, &file_path);
             files.push(GitFileDiff {
-                path: file_path.to_string(),
-                old_path: None, // TODO: Handle renamed files
-                status: status.to_string(),
-                additions,
-                deletions,
-                diff: diff_content,
+                path: file_path,
+                old_path: None,
+                status: "untracked".to_string(),
+                additions: line_count,
+                deletions: 0,
+                diff,
             });
         }
     }
 
-    // Add untracked files (show full content as "added")
-    let untracked_output = Command::new("git")
-        .args(["status", "--porcelain", "--untracked-files=all"])
-        .current_dir(working_dir)
-        .output()?;
-
-    if untracked_output.status.success() {
-        let untracked_str = String::from_utf8_lossy(&untracked_output.stdout);
-
-        for line in untracked_str.lines() {
-            if line.starts_with("??") && line.len() >= 3 {
-                let file_path = &line[3..];
-
-                // Read the full content of the untracked file
-                let file_content =
-                    std::fs::read_to_string(std::path::Path::new(working_dir).join(file_path))
-                        .unwrap_or_else(|_| String::from("Binary file or read error"));
-
-                // Create a fake diff showing the entire file as added
-                let fake_diff = if file_content.is_empty() {
-                    format!("diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..0000000\n--- /dev/null\n+++ b/{}\n", file_path, file_path, file_path)
-                } else {
-                    let mut diff_lines = vec![
-                        format!("diff --git a/{} b/{}", file_path, file_path),
-                        "new file mode 100644".to_string(),
-                        "index 0000000..0000000".to_string(),
-                        "--- /dev/null".to_string(),
-                        format!("+++ b/{}", file_path),
-                    ];
-
-                    // Add each line of the file as an addition
-                    for line in file_content.lines() {
-                        diff_lines.push(format!("+{}", line));
-                    }
-
-                    diff_lines.join("\n")
-                };
-
-                let line_count = file_content.lines().count() as u32;
-
-                files.push(GitFileDiff {
-                    path: file_path.to_string(),
-                    old_path: None,
-                    status: "untracked".to_string(),
-                    additions: line_count,
-                    deletions: 0,
-                    diff: fake_diff,
-                });
-            }
-        }
-    }
-
-    Ok(GitDiff { files })
+    let summary = DiffStats {
+        files: files.len() as u32,
+        additions: files.iter().map(|file| file.additions).sum(),
+        deletions: files.iter().map(|file| file.deletions).sum(),
+    };
+    Ok(GitDiff { files, summary })
 }
 
 async fn execute_git_file_diff(
     working_dir: &str,
     file_path: &str,
+    staged: Option<bool>,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let output = Command::new("git")
-        .args(["diff", file_path])
+        .arg("diff")
+        .args(diff_base(working_dir, staged)?)
+        .args(["--", file_path])
         .current_dir(working_dir)
         .output()?;
 
@@ -285,38 +552,15 @@ async fn execute_git_file_diff(
 
     // If git diff returns empty or fails, check if it's an untracked file
     let status_output = Command::new("git")
-        .args(["status", "--porcelain", file_path])
+        .args(["status", "--porcelain", "--", file_path])
         .current_dir(working_dir)
         .output()?;
 
-    if status_output.status.success() {
+    if status_output.status.success() && staged != Some(true) {
         let status_str = String::from_utf8_lossy(&status_output.stdout);
         if status_str.starts_with("??") {
             // It's an untracked file, show full content as additions
-            let file_content =
-                std::fs::read_to_string(std::path::Path::new(working_dir).join(file_path))
-                    .unwrap_or_else(|_| String::from("Binary file or read error"));
-
-            let fake_diff = if file_content.is_empty() {
-                format!("diff --git a/{} b/{}\nnew file mode 100644\nindex 0000000..0000000\n--- /dev/null\n+++ b/{}\n", file_path, file_path, file_path)
-            } else {
-                let mut diff_lines = vec![
-                    format!("diff --git a/{} b/{}", file_path, file_path),
-                    "new file mode 100644".to_string(),
-                    "index 0000000..0000000".to_string(),
-                    "--- /dev/null".to_string(),
-                    format!("+++ b/{}", file_path),
-                ];
-
-                // Add each line of the file as an addition
-                for line in file_content.lines() {
-                    diff_lines.push(format!("+{}", line));
-                }
-
-                diff_lines.join("\n")
-            };
-
-            return Ok(fake_diff);
+            return Ok(untracked_diff(working_dir, file_path).0);
         }
     }
 
@@ -337,3 +581,71 @@ fn parse_diff_stats(diff_content: &str) -> (u32, u32) {
 
     (additions, deletions)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn splits_staged_unstaged_and_untracked_changes() {
+        let dir = std::env::temp_dir().join(format!("codemux-git-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=t", "-c", "user.email=t@t"])
+                .args(args)
+                .current_dir(&dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        git(&["init", "-q"]);
+        std::fs::write(dir.join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(dir.join("old.txt"), "moved\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "start"]);
+
+        std::fs::write(dir.join("a.txt"), "one\n2\n").unwrap();
+        git(&["add", "a.txt"]);
+        std::fs::write(dir.join("a.txt"), "one\n2\nthree\n").unwrap();
+        git(&["mv", "old.txt", "new.txt"]);
+        std::fs::write(dir.join("notes.md"), "todo\n").unwrap();
+        let working_dir = dir.to_str().unwrap();
+
+        let status = execute_git_status(working_dir).await.unwrap();
+        let paths = |files: &[GitFileStatus]| -> Vec<(String, String)> {
+            files
+                .iter()
+                .map(|file| (file.path.clone(), file.status.clone()))
+                .collect()
+        };
+        let pair = |path: &str, status: &str| (path.to_string(), status.to_string());
+        assert_eq!(
+            paths(&status.staged_files),
+            [pair("a.txt", "modified"), pair("new.txt", "renamed")]
+        );
+        assert_eq!(status.staged_files[1].old_path.as_deref(), Some("old.txt"));
+        assert_eq!(paths(&status.unstaged_files), [pair("a.txt", "modified")]);
+        assert_eq!(
+            paths(&status.untracked_files),
+            [pair("notes.md", "untracked")]
+        );
+        // One line changed in the index, one added since
+        assert_eq!(status.files[0].additions, Some(2));
+        assert_eq!(status.files[0].deletions, Some(1));
+
+        let staged = execute_git_diff(working_dir, Some(true)).await.unwrap();
+        assert_eq!(staged.summary.files, 2);
+        let all = execute_git_diff(working_dir, None).await.unwrap();
+        assert_eq!(
+            all.summary,
+            DiffStats {
+                files: 3,
+                additions: 3,
+                deletions: 1,
+            }
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
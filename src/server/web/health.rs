@@ -0,0 +1,169 @@
+//! `/healthz` and `/readyz`, for systemd, Docker and Kubernetes to supervise
+//! the server
+//!
+//! Both answer without a token, but only with `status` then; the rest of the
+//! report goes to callers `require_token` would let in. `/healthz` fails only
+//! when the session manager stopped or is stuck on a request past its
+//! deadline, which a restart fixes; a slow session start within its deadline
+//! doesn't count, since restarting would end every session. `/readyz` asks
+//! the session manager, so it also fails while a slow request holds it up,
+//! while the server hands its sessions over to a newer one and when the web
+//! UI's assets are missing from a build that should serve them.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode, Uri},
+    response::IntoResponse,
+    Json,
+};
+use serde::Serialize;
+
+use super::auth::is_authenticated;
+use super::static_files::web_asset;
+use super::types::AppState;
+
+/// What both endpoints answer with
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// `ok`, or `unavailable` when the endpoint's checks failed
+    pub status: &'static str,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub details: Option<HealthDetails>,
+}
+
+/// The part of the report for authenticated callers
+#[derive(Debug, Serialize)]
+pub struct HealthDetails {
+    pub version: &'static str,
+    pub uptime_secs: u64,
+    /// `ok`, `draining` after a handover, or why the session manager failed its check
+    pub session_manager: String,
+    /// Unset unless the session manager was asked, by `/readyz`, and answered
+    pub running_sessions: Option<usize>,
+    /// `ok`, `missing`, or `disabled` in builds without the web UI
+    pub web_assets: &'static str,
+}
+
+/// Whether the server is alive
+pub async fn healthz(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> impl IntoResponse {
+    let alive = state.session_manager.check_alive();
+    let session_manager = match &alive {
+        Ok(()) => "ok".to_string(),
+        Err(e) => e.to_string(),
+    };
+    let details = details(&state, session_manager, None);
+    respond(&state, &headers, &uri, details, alive.is_ok())
+}
+
+/// Whether the server is ready for clients
+pub async fn readyz(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> impl IntoResponse {
+    let (session_manager, running_sessions) = match state.session_manager.ping().await {
+        Ok(status) if status.accepting_sessions => {
+            ("ok".to_string(), Some(status.running_sessions))
+        }
+        Ok(status) => ("draining".to_string(), Some(status.running_sessions)),
+        Err(e) => (e.to_string(), None),
+    };
+    let details = details(&state, session_manager, running_sessions);
+    let ready = details.session_manager == "ok" && details.web_assets != "missing";
+    respond(&state, &headers, &uri, details, ready)
+}
+
+fn details(
+    state: &AppState,
+    session_manager: String,
+    running_sessions: Option<usize>,
+) -> HealthDetails {
+    let web_assets = if !cfg!(feature = "web-ui") {
+        "disabled"
+    } else if web_asset("index.html").is_some() {
+        "ok"
+    } else {
+        "missing"
+    };
+    HealthDetails {
+        version: env!("CARGO_PKG_VERSION"),
+        uptime_secs: state.started_at.elapsed().as_secs(),
+        session_manager,
+        running_sessions,
+        web_assets,
+    }
+}
+
+fn respond(
+    state: &AppState,
+    headers: &HeaderMap,
+    uri: &Uri,
+    details: HealthDetails,
+    ok: bool,
+) -> impl IntoResponse {
+    let report = HealthReport {
+        status: if ok { "ok" } else { "unavailable" },
+        details: is_authenticated(state, headers, uri).then_some(details),
+    };
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::manager::SessionManagerHandle;
+    use crate::server::web::{build_router, AppState};
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn reports_details_only_to_authenticated_callers() {
+        let mut config = crate::core::Config::default();
+        config.server.data_dir =
+            std::env::temp_dir().join(format!("codemux-health-{}", std::process::id()));
+        let state = AppState::new(SessionManagerHandle::new(config)).with_auth_token("secret");
+        let app = build_router(state);
+
+        let report = |uri: &str, token: Option<&str>| {
+            let mut request = Request::get(uri);
+            if let Some(token) = token {
+                request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+            }
+            let response = app.clone().oneshot(request.body(Body::empty()).unwrap());
+            async move {
+                let response = response.await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX);
+                (
+                    status,
+                    serde_json::from_slice::<serde_json::Value>(&body.await.unwrap()).unwrap(),
+                )
+            }
+        };
+
+        assert_eq!(
+            report("/healthz", None).await,
+            (StatusCode::OK, serde_json::json!({ "status": "ok" }))
+        );
+        let (status, health) = report("/healthz", Some("secret")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(health["session_manager"], "ok");
+        assert!(health["running_sessions"].is_null());
+
+        // Not ready in builds whose web UI assets weren't built, which doesn't matter here
+        let (_, ready) = report("/readyz?token=secret", None).await;
+        assert_eq!(ready["session_manager"], "ok");
+        assert_eq!(ready["running_sessions"], 0);
+        let (_, ready) = report("/readyz", Some("wrong")).await;
+        assert!(ready.get("version").is_none());
+    }
+}
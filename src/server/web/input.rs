@@ -0,0 +1,174 @@
+//! `POST /api/sessions/:id/input`, typing into a session without a WebSocket
+//!
+//! Each request is a client of its own, called `api-…`, that sends its input
+//! with sequence numbers and answers once the PTY wrote all of it. Input
+//! control treats it like any other client: it's refused while someone else
+//! holds control or the session is read-only. Typing while nobody holds
+//! control takes it, so the request lets go of it again before answering.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::error::TrySendError;
+
+use super::openapi::MessageDocument;
+use super::types::{AppState, SessionInputRequest};
+use crate::core::pty_session::{PtyChannels, PtyInput, PtyInputMessage};
+use crate::core::JsonApiErrorDocument;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// How long the PTY may take to write a request's input
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Type text and keys into a session, as a script would at its terminal
+#[utoipa::path(
+    post,
+    path = "/api/sessions/{id}/input",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID or name")),
+    request_body = SessionInputRequest,
+    responses(
+        (status = 200, description = "The session wrote all of the input", body = MessageDocument),
+        (status = 404, description = "No running session", body = JsonApiErrorDocument),
+        (status = 409, description = "Another client has control, or the session is read-only", body = JsonApiErrorDocument),
+        (status = 503, description = "The session is busy with earlier input", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn send_session_input(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<SessionInputRequest>,
+) -> Response {
+    let Some(channels) = state
+        .session_manager
+        .get_session_channels(&session_id)
+        .await
+    else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No running session with id '{}'", session_id),
+        );
+    };
+
+    let client_id = format!("api-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    let mut inputs = Vec::new();
+    if let Some(text) = request.text.filter(|text| !text.is_empty()) {
+        inputs.push(if request.paste {
+            PtyInput::Paste {
+                text,
+                client_id: client_id.clone(),
+            }
+        } else {
+            PtyInput::Raw {
+                data: text.into_bytes(),
+                client_id: client_id.clone(),
+            }
+        });
+    }
+    inputs.extend(request.keys.into_iter().map(|event| PtyInput::Key {
+        event,
+        client_id: client_id.clone(),
+    }));
+    if inputs.is_empty() {
+        return json_api_error_response_with_headers(
+            StatusCode::BAD_REQUEST,
+            "No Input".to_string(),
+            "Send `text`, `keys` or both".to_string(),
+        );
+    }
+
+    let control = channels.input_control.state();
+    if !control.can_write(&client_id) {
+        let detail = match control.holder {
+            Some(holder) => format!("{} has control of this session", holder),
+            None => "This session is read-only".to_string(),
+        };
+        return json_api_error_response_with_headers(
+            StatusCode::CONFLICT,
+            "Input Refused".to_string(),
+            detail,
+        );
+    }
+
+    let count = inputs.len();
+    let result = write_inputs(&channels, &session_id, &client_id, inputs).await;
+    channels.input_control.release(&client_id);
+    if let Err(response) = result {
+        return response;
+    }
+    json_api_response_with_headers(serde_json::json!({
+        "message": "Input written",
+        "inputs": count
+    }))
+}
+
+/// Queue `inputs` in order and wait until the PTY wrote them all
+async fn write_inputs(
+    channels: &PtyChannels,
+    session_id: &str,
+    client_id: &str,
+    inputs: Vec<PtyInput>,
+) -> Result<(), Response> {
+    let refused = |detail: &str| {
+        json_api_error_response_with_headers(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Input Refused".to_string(),
+            detail.to_string(),
+        )
+    };
+
+    // Subscribed before sending, so no acknowledgement is missed
+    let mut acks = channels.input_tx.subscribe_acks();
+    let last_seq = inputs.len() as u64;
+    for (seq, input) in (1..).zip(inputs) {
+        let message = PtyInputMessage {
+            input,
+            seq: Some(seq),
+            span: tracing::info_span!("api.input", session_id = %session_id),
+        };
+        match channels.input_tx.send(message).await {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                return Err(refused(
+                    "The session is busy writing earlier input, try again shortly",
+                ))
+            }
+            Err(TrySendError::Closed(_)) => return Err(refused("The session has ended")),
+        }
+    }
+
+    let written = tokio::time::timeout(WRITE_TIMEOUT, async {
+        loop {
+            match acks.recv().await {
+                Ok(ack) if ack.client_id != client_id => {}
+                Ok(ack) if !ack.written => return false,
+                Ok(ack) if ack.seq == last_seq => return true,
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return false,
+            }
+        }
+    })
+    .await;
+    match written {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(json_api_error_response_with_headers(
+            StatusCode::CONFLICT,
+            "Input Refused".to_string(),
+            "Another client took control, or the paste was over the size limit".to_string(),
+        )),
+        Err(_) => Err(json_api_error_response_with_headers(
+            StatusCode::GATEWAY_TIMEOUT,
+            "Input Not Written".to_string(),
+            format!(
+                "The session didn't write the input within {}s",
+                WRITE_TIMEOUT.as_secs()
+            ),
+        )),
+    }
+}
@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::types::AppState;
+use crate::core::session_links::{self, LineageEntry};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// The sessions a session came from, oldest first and ending with the session
+/// itself, for the lineage breadcrumb
+pub async fn get_session_lineage(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Response {
+    let history = match state.session_manager.storage().load_sessions().await {
+        Ok(history) => history,
+        Err(e) => {
+            return json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Lineage Unavailable".to_string(),
+                e.to_string(),
+            )
+        }
+    };
+    // Links are in the history; names only while sessions run
+    let running = state.session_manager.list_sessions().await;
+    let name_of = |id: &str| {
+        running
+            .iter()
+            .find(|session| session.id == id)
+            .and_then(|session| session.attributes.as_ref()?.name.clone())
+    };
+    let session_id = running
+        .iter()
+        .find(|session| {
+            session.id == session_id
+                || session.attributes.as_ref().and_then(|a| a.name.as_deref())
+                    == Some(session_id.as_str())
+        })
+        .map(|session| session.id.clone())
+        .unwrap_or(session_id);
+
+    let lineage = session_links::lineage(&session_id, |id| {
+        let record = history.iter().find(|record| record.id == id)?;
+        let entry = LineageEntry {
+            id: record.id.clone(),
+            agent: record.agent.clone(),
+            color: record.color.clone(),
+            icon: record.icon.clone(),
+            name: name_of(id),
+            kind: None,
+            template: None,
+        };
+        Some((entry, record.links.clone()))
+    });
+    if lineage.is_empty() {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No session with id '{}' in the history", session_id),
+        );
+    }
+    json_api_response_with_headers(
+        lineage
+            .into_iter()
+            .map(|entry| JsonApiResource::<_, ()> {
+                resource_type: "lineage-entry".to_string(),
+                id: entry.id.clone(),
+                attributes: Some(entry),
+                relationships: None,
+            })
+            .collect::<Vec<_>>(),
+    )
+}
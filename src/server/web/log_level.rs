@@ -0,0 +1,42 @@
+use axum::{http::StatusCode, response::Response, Json};
+
+use crate::core::log_level::{self, LogLevel};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+fn log_level_response(filter: String) -> Response {
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "log_level".to_string(),
+        id: "server".to_string(),
+        attributes: Some(LogLevel { filter }),
+        relationships: None,
+    })
+}
+
+/// The server's log filter
+pub async fn get_log_level() -> Response {
+    match log_level::current() {
+        Some(filter) => log_level_response(filter),
+        None => json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Log Level Unavailable".to_string(),
+            "This server's log filter wasn't set up by codemux".to_string(),
+        ),
+    }
+}
+
+/// Swap the server's log filter, e.g. to `debug` while reproducing a bug;
+/// sessions keep running
+pub async fn set_log_level(Json(request): Json<LogLevel>) -> Response {
+    match log_level::set(&request.filter) {
+        Ok(filter) => {
+            tracing::info!("Log filter changed to {}", filter);
+            log_level_response(filter)
+        }
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::BAD_REQUEST,
+            "Log Level Not Changed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
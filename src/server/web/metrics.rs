@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::types::AppState;
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Per-minute input/output history of a running session, for activity sparklines
+pub async fn get_session_timeseries(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    let channels = match state
+        .session_manager
+        .get_session_channels(&session_id)
+        .await
+    {
+        Some(channels) => channels,
+        None => {
+            return json_api_error_response_with_headers(
+                StatusCode::NOT_FOUND,
+                "Session Not Found".to_string(),
+                format!("No running session with id '{}'", session_id),
+            )
+        }
+    };
+
+    match channels.request_activity().await {
+        Ok(timeseries) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "session-timeseries".to_string(),
+            id: session_id,
+            attributes: Some(timeseries),
+            relationships: None,
+        }),
+        Err(e) => {
+            tracing::error!(
+                "Failed to read activity metrics for session {}: {}",
+                session_id,
+                e
+            );
+            json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Metrics Unavailable".to_string(),
+                e.to_string(),
+            )
+        }
+    }
+}
+
+/// How often each screen cell changed over the last minute, for spotting repaint storms
+pub async fn get_session_heatmap(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    let channels = match state
+        .session_manager
+        .get_session_channels(&session_id)
+        .await
+    {
+        Some(channels) => channels,
+        None => {
+            return json_api_error_response_with_headers(
+                StatusCode::NOT_FOUND,
+                "Session Not Found".to_string(),
+                format!("No running session with id '{}'", session_id),
+            )
+        }
+    };
+
+    match channels.request_heatmap().await {
+        Ok(heatmap) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "session-heatmap".to_string(),
+            id: session_id,
+            attributes: Some(heatmap),
+            relationships: None,
+        }),
+        Err(e) => {
+            tracing::error!("Failed to read heatmap for session {}: {}", session_id, e);
+            json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Metrics Unavailable".to_string(),
+                e.to_string(),
+            )
+        }
+    }
+}
@@ -1,11 +1,43 @@
+pub mod agents;
+pub mod archive;
+pub mod auth;
+pub mod checkpoints;
+pub mod commits;
+pub mod conversations;
+pub mod crash_dumps;
+pub mod digest;
 pub mod git;
+pub mod health;
+pub mod input;
 pub mod json_api;
+pub mod lineage;
+pub mod log_level;
+pub mod metrics;
+pub mod notifications;
+pub mod oidc;
+pub mod openapi;
+pub mod paste;
+pub mod preferences;
 pub mod projects;
+pub mod recent;
+pub mod render_test;
+pub mod retract;
 pub mod routes;
+pub mod screen;
+pub mod screen_text;
+pub mod screenshot;
+pub mod scrollback;
+pub mod search;
 pub mod sessions;
 pub mod static_files;
+pub mod tokens;
+pub mod transcript;
 pub mod types;
+pub mod usage;
 pub mod websocket;
+pub mod workspace;
 
-pub use routes::start_web_server;
+#[cfg(unix)]
+pub use routes::{bind_socket, serve_with_socket};
+pub use routes::{build_router, serve_listener, start_web_server};
 pub use types::AppState;
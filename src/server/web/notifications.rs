@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+
+use super::types::AppState;
+use crate::core::notifications::{DoNotDisturb, NotificationList, SetDoNotDisturbRequest};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+fn notifications_error(e: impl std::fmt::Display) -> Response {
+    json_api_error_response_with_headers(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Notifications Unavailable".to_string(),
+        e.to_string(),
+    )
+}
+
+async fn notifications_response(state: &AppState) -> Response {
+    match state.session_manager.storage().load_notifications().await {
+        Ok(notifications) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "notifications".to_string(),
+            id: "inbox".to_string(),
+            attributes: Some(NotificationList::new(notifications)),
+            relationships: None,
+        }),
+        Err(e) => notifications_error(e),
+    }
+}
+
+/// Stored notifications, newest first, with the unread count
+pub async fn get_notifications(State(state): State<AppState>) -> Response {
+    notifications_response(&state).await
+}
+
+/// Mark one notification read and return the updated list
+pub async fn mark_notification_read(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match state
+        .session_manager
+        .storage()
+        .mark_notification_read(&id)
+        .await
+    {
+        Ok(true) => notifications_response(&state).await,
+        Ok(false) => json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Notification Not Found".to_string(),
+            format!("No notification with ID {}", id),
+        ),
+        Err(e) => notifications_error(e),
+    }
+}
+
+fn do_not_disturb_response(dnd: DoNotDisturb) -> Response {
+    // Expired do-not-disturb reads as switched off
+    let dnd = if dnd.is_active_at(chrono::Utc::now()) {
+        dnd
+    } else {
+        DoNotDisturb::default()
+    };
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "do-not-disturb".to_string(),
+        id: "server".to_string(),
+        attributes: Some(dnd),
+        relationships: None,
+    })
+}
+
+/// Whether alerts are switched off by hand, and until when
+pub async fn get_do_not_disturb(State(state): State<AppState>) -> Response {
+    match state.session_manager.storage().load_do_not_disturb().await {
+        Ok(dnd) => do_not_disturb_response(dnd),
+        Err(e) => notifications_error(e),
+    }
+}
+
+/// Switch do-not-disturb on, optionally for a while, or off
+pub async fn set_do_not_disturb(
+    State(state): State<AppState>,
+    Json(request): Json<SetDoNotDisturbRequest>,
+) -> Response {
+    let dnd = DoNotDisturb {
+        enabled: request.enabled,
+        until: request.minutes.filter(|_| request.enabled).map(|minutes| {
+            (chrono::Utc::now() + chrono::Duration::minutes(minutes as i64)).to_rfc3339()
+        }),
+    };
+    match state
+        .session_manager
+        .storage()
+        .save_do_not_disturb(&dnd)
+        .await
+    {
+        Ok(()) => do_not_disturb_response(dnd),
+        Err(e) => notifications_error(e),
+    }
+}
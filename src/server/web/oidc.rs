@@ -0,0 +1,372 @@
+//! Sign-in through `[web.oidc]`, see `core::oidc`
+//!
+//! `/auth/login` sends the browser to the provider, which sends it back to
+//! `/auth/callback`. There the code is exchanged for an ID token and the
+//! browser gets a session cookie, or for `codemux login` the CLI's loopback
+//! address gets a token. Both live in memory, so signing in again is needed
+//! after the server restarts.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Redirect, Response},
+    Extension,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::types::AppState;
+use crate::core::auth::generate_token;
+use crate::core::oidc::{self, OidcConfig, Pkce, ProviderMetadata, User};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Cookie holding a browser's sign-in
+pub const SESSION_COOKIE: &str = "codemux_session";
+/// How long the provider's login page may take
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Sign-ins in progress and the people signed in
+pub struct OidcLogin {
+    config: OidcConfig,
+    http: reqwest::Client,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+    /// Browser sessions and CLI tokens, by their secret
+    grants: Mutex<HashMap<String, Grant>>,
+}
+
+struct PendingLogin {
+    provider: ProviderMetadata,
+    pkce: Pkce,
+    nonce: String,
+    then: AfterLogin,
+    started: Instant,
+}
+
+/// Where a sign-in leads once the provider confirmed who it is
+enum AfterLogin {
+    /// A page of the web UI, getting a session cookie
+    Browser { return_to: String },
+    /// The loopback address `codemux login` listens on, getting a token
+    Cli { callback: url::Url, state: String },
+}
+
+struct Grant {
+    user: User,
+    expires: Instant,
+}
+
+impl OidcLogin {
+    pub fn new(config: OidcConfig) -> Self {
+        OidcLogin {
+            config,
+            http: reqwest::Client::new(),
+            pending: Mutex::default(),
+            grants: Mutex::default(),
+        }
+    }
+
+    /// Who a session cookie or CLI token belongs to, while it's valid
+    pub fn user(&self, secret: &str) -> Option<User> {
+        let mut grants = self.grants.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        grants.retain(|_, grant| grant.expires > now);
+        grants.get(secret).map(|grant| grant.user.clone())
+    }
+
+    /// Sign `user` in for `ttl_hours`; `None` if that's too far in the future,
+    /// which a config checked on loading never asks for
+    fn grant(&self, user: User, ttl_hours: u64) -> Option<(String, chrono::DateTime<chrono::Utc>)> {
+        let ttl = Duration::from_secs(ttl_hours.checked_mul(3600)?);
+        let grant = Grant {
+            user,
+            expires: Instant::now().checked_add(ttl)?,
+        };
+        let expires_at =
+            chrono::Utc::now().checked_add_signed(chrono::Duration::from_std(ttl).ok()?)?;
+        let secret = generate_token();
+        self.grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(secret.clone(), grant);
+        Some((secret, expires_at))
+    }
+
+    fn revoke(&self, secret: &str) {
+        self.grants
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(secret);
+    }
+
+    /// The provider's login page for a new sign-in
+    async fn start(&self, then: AfterLogin) -> anyhow::Result<String> {
+        let provider: ProviderMetadata = self
+            .http
+            .get(oidc::discovery_url(&self.config.issuer))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let state = generate_token();
+        let nonce = generate_token();
+        let pkce = Pkce::new();
+        let url = oidc::authorization_url(&self.config, &provider, &state, &nonce, &pkce)?;
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, login| login.started.elapsed() < LOGIN_TIMEOUT);
+        pending.insert(
+            state,
+            PendingLogin {
+                provider,
+                pkce,
+                nonce,
+                then,
+                started: Instant::now(),
+            },
+        );
+        Ok(url)
+    }
+
+    /// Exchange the code the provider sent back for who signed in
+    async fn finish(&self, code: &str, state: &str) -> anyhow::Result<(User, AfterLogin)> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(state)
+            .filter(|login| login.started.elapsed() < LOGIN_TIMEOUT)
+            .ok_or_else(|| anyhow::anyhow!("The sign-in expired or was already used"))?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_url),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", &login.pkce.verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret));
+        }
+        let tokens: TokenResponse = self
+            .http
+            .post(&login.provider.token_endpoint)
+            .form(&form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let user = oidc::verify_id_token(
+            &self.config,
+            &login.provider,
+            &tokens.id_token,
+            &login.nonce,
+            chrono::Utc::now().timestamp(),
+        )?;
+        Ok((user, login.then))
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// The value of the session cookie a request carries
+pub fn session_cookie(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == SESSION_COOKIE).then_some(value)
+        })
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    /// Page of the web UI to go back to
+    return_to: Option<String>,
+    /// Loopback address of `codemux login` to send the token to
+    cli_callback: Option<String>,
+    /// Echoed back to `cli_callback`, so the CLI knows the token is its own
+    cli_state: Option<String>,
+}
+
+/// `GET /auth/login`, off to the provider's login page
+pub async fn login(State(state): State<AppState>, Query(query): Query<LoginQuery>) -> Response {
+    let Some(oidc) = &state.oidc else {
+        return not_configured();
+    };
+    let then = match (query.cli_callback, query.cli_state) {
+        (Some(callback), Some(cli_state)) => match loopback_url(&callback) {
+            Some(callback) => AfterLogin::Cli {
+                callback,
+                state: cli_state,
+            },
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    "cli_callback must be an http:// address on this machine",
+                )
+                    .into_response()
+            }
+        },
+        _ => AfterLogin::Browser {
+            return_to: local_path(&state.base_path, query.return_to),
+        },
+    };
+    match oidc.start(then).await {
+        Ok(url) => Redirect::to(&url).into_response(),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to start a sign-in with {}: {}",
+                oidc.config.issuer,
+                e
+            );
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("The identity provider can't be reached: {}", e),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// `GET /auth/callback`, where the provider sends the browser back
+pub async fn callback(
+    State(state): State<AppState>,
+    Query(query): Query<CallbackQuery>,
+) -> Response {
+    let Some(oidc) = &state.oidc else {
+        return not_configured();
+    };
+    let (Some(code), Some(login_state)) = (query.code, query.state) else {
+        let reason = query
+            .error_description
+            .or(query.error)
+            .unwrap_or_else(|| "the provider sent no code".to_string());
+        return (StatusCode::FORBIDDEN, format!("Sign-in failed: {}", reason)).into_response();
+    };
+    let (user, then) = match oidc.finish(&code, &login_state).await {
+        Ok(signed_in) => signed_in,
+        Err(e) => {
+            tracing::warn!("Sign-in failed: {}", e);
+            return (StatusCode::FORBIDDEN, format!("Sign-in failed: {}", e)).into_response();
+        }
+    };
+    tracing::info!("{} signed in", user.name);
+
+    let ttl_hours = match then {
+        AfterLogin::Browser { .. } => oidc.config.session_ttl_hours,
+        AfterLogin::Cli { .. } => oidc.config.cli_token_ttl_hours,
+    };
+    let Some((secret, expires_at)) = oidc.grant(user, ttl_hours) else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("[web.oidc] TTL of {} hours is too long", ttl_hours),
+        )
+            .into_response();
+    };
+    match then {
+        AfterLogin::Browser { return_to } => {
+            let cookie = format!(
+                "{}={}; Path={}/; Max-Age={}; HttpOnly; SameSite=Strict{}",
+                SESSION_COOKIE,
+                secret,
+                state.base_path,
+                ttl_hours * 3600,
+                if oidc.config.redirect_url.starts_with("https://") {
+                    "; Secure"
+                } else {
+                    ""
+                }
+            );
+            ([(header::SET_COOKIE, cookie)], Redirect::to(&return_to)).into_response()
+        }
+        AfterLogin::Cli {
+            mut callback,
+            state: cli_state,
+        } => {
+            callback
+                .query_pairs_mut()
+                .append_pair("token", &secret)
+                .append_pair(
+                    "expires_at",
+                    &expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                )
+                .append_pair("state", &cli_state);
+            Redirect::to(callback.as_str()).into_response()
+        }
+    }
+}
+
+/// `POST /auth/logout`, ending the browser's sign-in
+pub async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    if let (Some(oidc), Some(secret)) = (&state.oidc, session_cookie(&headers)) {
+        oidc.revoke(secret);
+    }
+    let cookie = format!(
+        "{}=; Path={}/; Max-Age=0; HttpOnly; SameSite=Strict",
+        SESSION_COOKIE, state.base_path
+    );
+    ([(header::SET_COOKIE, cookie)], StatusCode::NO_CONTENT).into_response()
+}
+
+/// `GET /auth/me`, who the request is signed in as
+pub async fn current_user(user: Option<Extension<User>>) -> Response {
+    match user {
+        Some(Extension(user)) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "user".to_string(),
+            id: user.subject.clone(),
+            attributes: Some(user),
+            relationships: None,
+        }),
+        None => json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Not Signed In".to_string(),
+            "The request used the server's token rather than a sign-in".to_string(),
+        ),
+    }
+}
+
+fn not_configured() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        "Sign-in is not configured on this server; see [web.oidc], \
+         or give `codemux login --with-token` the server's token",
+    )
+        .into_response()
+}
+
+/// `url` if it points at this machine over plain HTTP, where `codemux login` listens
+fn loopback_url(url: &str) -> Option<url::Url> {
+    let url = url::Url::parse(url).ok()?;
+    let loopback = match url.host()? {
+        url::Host::Domain(domain) => domain == "localhost",
+        url::Host::Ipv4(ip) => ip.is_loopback(),
+        url::Host::Ipv6(ip) => ip.is_loopback(),
+    };
+    (url.scheme() == "http" && loopback).then_some(url)
+}
+
+/// A path on this server to return to, never another site
+fn local_path(base_path: &str, return_to: Option<String>) -> String {
+    return_to
+        .filter(|path| path.starts_with('/') && !path.starts_with("//") && !path.contains('\\'))
+        .unwrap_or_else(|| format!("{}/", base_path))
+}
@@ -0,0 +1,122 @@
+//! The REST API as an OpenAPI document, served at `/api/openapi.json`
+//!
+//! Paths come from the `#[utoipa::path]` attributes on the handlers and
+//! schemas from the types they take and return, so the document follows the
+//! code. Endpoints without an attribute are left out of it; the WebSockets
+//! are too, OpenAPI has no way to describe them. `/api/docs` shows the
+//! document in Swagger UI.
+
+use axum::Json;
+use serde::Serialize;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use super::{git, input, projects, screen, sessions, usage};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "codemux",
+        description = "Sessions of AI coding agents, their projects, git changes and usage"
+    ),
+    paths(
+        sessions::list_sessions,
+        sessions::create_session,
+        sessions::get_session,
+        sessions::delete_session,
+        sessions::undo_kill_session,
+        sessions::set_session_note,
+        screen::get_session_screen,
+        input::send_session_input,
+        git::get_git_status,
+        git::get_git_diff,
+        git::get_git_file_diff,
+        projects::list_projects,
+        projects::add_project,
+        projects::update_project,
+        projects::delete_project,
+        git::get_project_git_status,
+        git::get_project_diff,
+        git::get_project_file_diff,
+        usage::get_session_usage,
+        usage::get_usage,
+    ),
+    components(schemas(crate::core::JsonApiErrorDocument)),
+    modifiers(&BearerToken),
+    security(("token" = [])),
+    tags(
+        (name = "sessions", description = "Running and ended agent sessions"),
+        (name = "projects", description = "Directories sessions run in"),
+        (name = "git", description = "Changes in a session's or project's directory"),
+        (name = "usage", description = "Tokens and cost, from the agents' transcripts"),
+    )
+)]
+pub struct ApiDoc;
+
+/// The server's token, a `codemux login` token or one from `/api/tokens`
+struct BearerToken;
+
+impl Modify for BearerToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// A JSON:API document holding one resource without relationships, the
+/// shape most endpoints answer in; only described, never built
+#[derive(Serialize, ToSchema)]
+pub struct ResourceDocument<T> {
+    pub data: Resource<T>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Resource<T> {
+    #[serde(rename = "type")]
+    pub resource_type: String,
+    pub id: String,
+    pub attributes: T,
+}
+
+/// `{"data": {"message": ...}}`, as endpoints that only confirm answer
+#[derive(Serialize, ToSchema)]
+pub struct MessageDocument {
+    pub data: Message,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Message {
+    pub message: String,
+}
+
+pub async fn get_openapi_spec() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_the_rest_surface() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let paths = spec["paths"].as_object().unwrap();
+        for path in [
+            "/api/sessions",
+            "/api/sessions/{id}",
+            "/api/sessions/{id}/input",
+            "/api/projects",
+            "/api/projects/{id}/git/status",
+            "/api/usage",
+        ] {
+            assert!(paths.contains_key(path), "{} is missing", path);
+        }
+        assert!(paths["/api/sessions/{id}"]["delete"].is_object());
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        assert!(schemas.contains_key("SessionAttributes"));
+        assert!(schemas.contains_key("GitStatus"));
+    }
+}
@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use serde::Deserialize;
+
+use super::types::AppState;
+use crate::core::paste::write_paste_file;
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+#[derive(Deserialize)]
+pub struct PasteFileRequest {
+    pub text: String,
+}
+
+/// Paste confirmation thresholds and the server's paste limit
+pub async fn get_paste_settings(State(state): State<AppState>) -> Response {
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "paste-settings".to_string(),
+        id: "current".to_string(),
+        attributes: Some(state.session_manager.paste_config().clone()),
+        relationships: None,
+    })
+}
+
+/// Save a large paste to a file on the server so its path can be sent to the agent instead
+pub async fn create_paste_file(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<PasteFileRequest>,
+) -> Response {
+    // The session ID becomes part of the path, so only accept sessions we know
+    if state
+        .session_manager
+        .get_session(&session_id)
+        .await
+        .is_none()
+    {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No session with ID {}", session_id),
+        );
+    }
+
+    let pastes_dir = state.session_manager.pastes_dir().to_path_buf();
+    let bytes = request.text.len();
+    let result = tokio::task::spawn_blocking(move || {
+        write_paste_file(&pastes_dir, &session_id, &request.text)
+    })
+    .await
+    .map_err(anyhow::Error::from)
+    .and_then(|result| result);
+
+    match result {
+        Ok(path) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "paste-file".to_string(),
+            id: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            attributes: Some(serde_json::json!({
+                "path": path,
+                "bytes": bytes,
+            })),
+            relationships: None,
+        }),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Paste File Failed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
@@ -0,0 +1,47 @@
+use axum::{extract::State, http::StatusCode, response::Response, Json};
+
+use super::types::AppState;
+use crate::core::preferences::{UiPreferences, UpdatePreferencesRequest};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+fn preferences_response(preferences: UiPreferences) -> Response {
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "preferences".to_string(),
+        id: "ui".to_string(),
+        attributes: Some(preferences),
+        relationships: None,
+    })
+}
+
+fn preferences_error(e: impl std::fmt::Display) -> Response {
+    json_api_error_response_with_headers(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Preferences Unavailable".to_string(),
+        e.to_string(),
+    )
+}
+
+/// Display preferences shared by every client
+pub async fn get_preferences(State(state): State<AppState>) -> Response {
+    match state.session_manager.storage().load_preferences().await {
+        Ok(preferences) => preferences_response(preferences),
+        Err(e) => preferences_error(e),
+    }
+}
+
+/// Update some preferences and return the full set
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    Json(update): Json<UpdatePreferencesRequest>,
+) -> Response {
+    match state
+        .session_manager
+        .storage()
+        .update_preferences(&update)
+        .await
+    {
+        Ok(preferences) => preferences_response(preferences),
+        Err(e) => preferences_error(e),
+    }
+}
@@ -1,11 +1,43 @@
-use axum::{extract::State, response::IntoResponse, Json};
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
 use chrono::{DateTime, Utc};
 use std::path::PathBuf;
 
-use super::types::{AddProjectRequest, AppState};
-use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+use super::openapi::MessageDocument;
+use super::types::{AddProjectRequest, AppState, DeleteProjectQuery, UpdateProjectRequest};
+use crate::core::json_api::{JsonApiPage, ProjectResource, ProjectResourceTS};
+use crate::core::listing::ListQuery;
+use crate::core::project_groups;
+use crate::core::{
+    json_api_error_response_with_headers, json_api_page_response_with_headers,
+    json_api_response_with_headers,
+};
+use crate::core::{JsonApiDocument, JsonApiErrorDocument};
 
-pub async fn list_projects(State(state): State<AppState>) -> impl IntoResponse {
+/// Fields `GET /api/projects` sorts by; without `sort` the most recently
+/// used projects come first
+const PROJECT_SORT_FIELDS: &[&str] = &["name", "path"];
+
+/// Projects with their sessions. `filter[agent]` and `filter[status]` apply
+/// to the sessions, leaving out projects with none that pass
+#[utoipa::path(
+    get,
+    path = "/api/projects",
+    tag = "projects",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "One page of projects with their recent sessions, most recently used first", body = JsonApiPage<ProjectResourceTS>),
+        (status = 400, description = "An unknown sort field or a page out of range", body = JsonApiErrorDocument),
+        (status = 404, description = "No project matches `filter[project]`", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn list_projects(
+    Query(query): Query<ListQuery>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
     // Return actual projects with their sessions
     let mut projects = state.session_manager.list_projects().await;
     let active_sessions = state.session_manager.list_sessions().await;
@@ -25,6 +57,7 @@ pub async fn list_projects(State(state): State<AppState>) -> impl IntoResponse {
                 resource_type: "session".to_string(),
                 id: session.id.clone(),
                 attributes: session.attributes.clone(),
+                relationships: session.relationships.clone(),
             })
             .collect();
 
@@ -43,25 +76,44 @@ pub async fn list_projects(State(state): State<AppState>) -> impl IntoResponse {
                     resource_type: "session".to_string(),
                     id: session.id.clone(),
                     attributes: session.attributes.clone(),
+                    relationships: session.relationships.clone(),
                 }
             }));
 
             // Update relationships
-            if let Some(ref mut relationships) = project.relationships {
-                relationships.recent_sessions = if all_sessions.is_empty() {
-                    None
-                } else {
-                    Some(all_sessions)
-                };
+            let relationships = project.relationships.get_or_insert_with(Default::default);
+            relationships.recent_sessions = if all_sessions.is_empty() {
+                None
             } else {
-                project.relationships = Some(crate::core::ProjectRelationships {
-                    recent_sessions: if all_sessions.is_empty() {
-                        None
-                    } else {
-                        Some(all_sessions)
-                    },
-                });
-            }
+                Some(all_sessions)
+            };
+        }
+    }
+
+    // Roll the running sessions of sub-projects up to every project above them
+    let groups = projects.clone();
+    for project in &mut projects {
+        let subproject_sessions: Vec<_> = active_sessions
+            .iter()
+            .filter(|session| {
+                session
+                    .attributes
+                    .as_ref()
+                    .and_then(|attrs| attrs.project.as_deref())
+                    .is_some_and(|id| project_groups::is_below(&groups, id, &project.id))
+            })
+            .map(|session| crate::core::json_api::SessionResourceTS {
+                resource_type: "session".to_string(),
+                id: session.id.clone(),
+                attributes: session.attributes.clone(),
+                relationships: session.relationships.clone(),
+            })
+            .collect();
+        if !subproject_sessions.is_empty() {
+            project
+                .relationships
+                .get_or_insert_with(Default::default)
+                .subproject_sessions = Some(subproject_sessions);
         }
     }
 
@@ -111,16 +163,84 @@ pub async fn list_projects(State(state): State<AppState>) -> impl IntoResponse {
         }
     });
 
-    json_api_response_with_headers(projects)
+    if let Some(address) = &query.project {
+        let Some(id) = project_groups::find_project(&projects, address).map(|p| p.id.clone())
+        else {
+            return json_api_error_response_with_headers(
+                axum::http::StatusCode::NOT_FOUND,
+                "Project Not Found".to_string(),
+                format!("No project '{}'", address),
+            );
+        };
+        projects.retain(|project| project.id == id);
+    }
+    if query.filters_sessions() {
+        for project in &mut projects {
+            if let Some(relationships) = &mut project.relationships {
+                for sessions in [
+                    &mut relationships.recent_sessions,
+                    &mut relationships.subproject_sessions,
+                ] {
+                    if let Some(list) = sessions {
+                        list.retain(|session| query.matches(session.attributes.as_ref()));
+                        if list.is_empty() {
+                            *sessions = None;
+                        }
+                    }
+                }
+            }
+        }
+        projects.retain(|project| {
+            project
+                .relationships
+                .as_ref()
+                .is_some_and(|r| r.recent_sessions.is_some())
+        });
+    }
+
+    let page = query
+        .sort(&mut projects, PROJECT_SORT_FIELDS, project_field)
+        .and_then(|()| query.paginate(projects));
+    match page {
+        Ok(page) => {
+            let links = query.links(&format!("{}/api/projects", state.base_path), &page.meta);
+            json_api_page_response_with_headers(page, links)
+        }
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::BAD_REQUEST,
+            "Invalid List Parameters".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+/// A project's value of one of `PROJECT_SORT_FIELDS`
+fn project_field(project: &ProjectResource, field: &str) -> Option<String> {
+    let attributes = project.attributes.as_ref()?;
+    match field {
+        "name" => Some(attributes.name.clone()),
+        "path" => Some(attributes.path.clone()),
+        _ => None,
+    }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/projects",
+    tag = "projects",
+    request_body = AddProjectRequest,
+    responses(
+        (status = 200, description = "The added project", body = JsonApiDocument<ProjectResourceTS>),
+        (status = 500, description = "The project couldn't be added", body = JsonApiErrorDocument),
+    )
+)]
 pub async fn add_project(
     State(state): State<AppState>,
     Json(req): Json<AddProjectRequest>,
 ) -> impl IntoResponse {
     match state
         .session_manager
-        .create_project(req.name, req.path)
+        .create_project(req.name, req.path, req.parent)
         .await
     {
         Ok(info) => json_api_response_with_headers(info),
@@ -131,3 +251,139 @@ pub async fn add_project(
         ),
     }
 }
+
+/// Remove a project. Its running sessions are ended with `kill_sessions`,
+/// else they run on without a project; their history stays
+#[utoipa::path(
+    delete,
+    path = "/api/projects/{id}",
+    tag = "projects",
+    params(("id" = String, Path, description = "Project ID or (qualified) name"), DeleteProjectQuery),
+    responses(
+        (status = 200, description = "The project was removed; `sessions` lists its running sessions", body = MessageDocument),
+        (status = 404, description = "No such project", body = JsonApiErrorDocument),
+        (status = 409, description = "The project has sub-projects", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn delete_project(
+    Path(id): Path<String>,
+    Query(query): Query<DeleteProjectQuery>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    match state
+        .session_manager
+        .remove_project(&id, query.kill_sessions)
+        .await
+    {
+        Ok(Some(removed)) => json_api_response_with_headers(serde_json::json!({
+            "message": if query.kill_sessions {
+                "Project removed, its sessions were ended"
+            } else {
+                "Project removed, its sessions run on without a project"
+            },
+            "sessions": removed.sessions
+        })),
+        Ok(None) => json_api_error_response_with_headers(
+            axum::http::StatusCode::NOT_FOUND,
+            "Project Not Found".to_string(),
+            format!("No project '{}'", id),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::CONFLICT,
+            "Project Not Removed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+/// Rename a project or set its defaults
+#[utoipa::path(
+    patch,
+    path = "/api/projects/{id}",
+    tag = "projects",
+    params(("id" = String, Path, description = "Project ID or (qualified) name")),
+    request_body = UpdateProjectRequest,
+    responses(
+        (status = 200, description = "The updated project", body = JsonApiDocument<ProjectResourceTS>),
+        (status = 400, description = "The name is empty or contains `/`, the default agent isn't whitelisted or the default env sets a variable that can't be set", body = JsonApiErrorDocument),
+        (status = 404, description = "No such project", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn update_project(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(req): Json<UpdateProjectRequest>,
+) -> impl IntoResponse {
+    match state
+        .session_manager
+        .update_project(&id, req.name, req.defaults)
+        .await
+    {
+        Ok(Some(project)) => json_api_response_with_headers(project),
+        Ok(None) => json_api_error_response_with_headers(
+            axum::http::StatusCode::NOT_FOUND,
+            "Project Not Found".to_string(),
+            format!("No project '{}'", id),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::BAD_REQUEST,
+            "Invalid Project Update".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::manager::SessionManagerHandle;
+    use crate::server::web::{build_router, AppState};
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn updates_projects_and_removes_them_without_sub_projects() {
+        let root = std::env::temp_dir().join(format!("codemux-projects-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("shop/api")).unwrap();
+        let mut config = crate::core::Config::default();
+        config.server.data_dir = root.join("data");
+        let app = build_router(AppState::new(SessionManagerHandle::new(config)));
+
+        let request = |method: &str, uri: &str, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+
+        let shop = root.join("shop").display().to_string();
+        let body = json!({"name": "shop", "path": shop});
+        request("POST", "/api/projects", body).await;
+        let api = root.join("shop/api").display().to_string();
+        let body = json!({"name": "api", "path": api, "parent": "shop"});
+        request("POST", "/api/projects", body).await;
+
+        let refused = request("DELETE", "/api/projects/shop", json!(null)).await;
+        assert_eq!(refused["errors"][0]["status"], "409");
+        let body = json!({"defaults": {"env": {"LD_PRELOAD": "/tmp/evil.so"}}});
+        let refused = request("PATCH", "/api/projects/api", body).await;
+        assert_eq!(refused["errors"][0]["status"], "400");
+        let body = json!({"name": "backend"});
+        let renamed = request("PATCH", "/api/projects/api", body).await;
+        assert_eq!(renamed["data"]["attributes"]["name"], "backend");
+        let removed = request("DELETE", "/api/projects/backend", json!(null)).await;
+        assert_eq!(removed["data"]["sessions"], json!([]));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
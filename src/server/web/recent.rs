@@ -0,0 +1,51 @@
+use axum::{extract::State, http::StatusCode, response::Response};
+
+use super::types::AppState;
+use crate::core::recent::build_recent_list;
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Starred and recently attached sessions plus starred projects
+pub async fn get_recent(State(state): State<AppState>) -> Response {
+    let storage = state.session_manager.storage();
+    let stored = async {
+        anyhow::Ok((
+            storage.load_recent().await?,
+            storage.load_preferences().await?,
+            storage.load_sessions().await?,
+        ))
+    }
+    .await;
+
+    let (recent, preferences, history) = match stored {
+        Ok(stored) => stored,
+        Err(e) => {
+            return json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Recent Sessions Unavailable".to_string(),
+                e.to_string(),
+            )
+        }
+    };
+
+    let sessions = state.session_manager.list_sessions().await;
+    let projects = state.session_manager.list_projects().await;
+    let mut list = build_recent_list(&recent, &preferences, &sessions, &projects);
+
+    // Ended sessions the project history no longer mentions still have their stored details
+    for session in list.sessions.iter_mut().filter(|s| s.agent.is_none()) {
+        if let Some(record) = history.iter().find(|record| record.id == session.id) {
+            session.agent = Some(record.agent.clone());
+            session.project = record.project.clone();
+            session.color = Some(record.color.clone());
+            session.icon = Some(record.icon.clone());
+        }
+    }
+
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "recent".to_string(),
+        id: "quick-switch".to_string(),
+        attributes: Some(list),
+        relationships: None,
+    })
+}
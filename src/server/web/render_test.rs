@@ -0,0 +1,58 @@
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    response::IntoResponse,
+};
+
+use crate::core::render_test::{render_test_frames, RENDER_TEST_COLS, RENDER_TEST_ROWS};
+use crate::core::ServerMessage;
+
+/// Stream the renderer torture test in the `/ws/:session_id` format, looping until the client leaves
+///
+/// Input from the client is read and ignored, so the web terminal can connect
+/// to it unchanged.
+pub async fn render_test_websocket(ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(stream_render_test)
+}
+
+async fn stream_render_test(mut socket: WebSocket) {
+    let size = ServerMessage::PtySize {
+        rows: RENDER_TEST_ROWS,
+        cols: RENDER_TEST_COLS,
+    };
+    if !send_message(&mut socket, &size).await {
+        return;
+    }
+
+    loop {
+        for frame in render_test_frames() {
+            let update = ServerMessage::GridUpdate {
+                update: frame.update,
+            };
+            if !send_message(&mut socket, &update).await {
+                return;
+            }
+
+            let pause = tokio::time::sleep(frame.delay);
+            tokio::pin!(pause);
+            loop {
+                tokio::select! {
+                    _ = &mut pause => break,
+                    message = socket.recv() => match message {
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                        Some(Ok(_)) => {}
+                    },
+                }
+            }
+        }
+    }
+}
+
+async fn send_message(socket: &mut WebSocket, message: &ServerMessage) -> bool {
+    match serde_json::to_string(message) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(e) => {
+            tracing::error!("Failed to serialize render test message: {}", e);
+            false
+        }
+    }
+}
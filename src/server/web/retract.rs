@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use serde::Deserialize;
+
+use super::types::AppState;
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+#[derive(Deserialize, Default)]
+pub struct RetractRequest {
+    /// Type the message back into the agent's input after interrupting it
+    #[serde(default)]
+    pub restore: bool,
+}
+
+/// Undo an accidental send: interrupt the agent and return the last message sent to it
+pub async fn retract_last_message(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+    request: Option<Json<RetractRequest>>,
+) -> Response {
+    let Json(request) = request.unwrap_or_default();
+    let channels = match state
+        .session_manager
+        .get_session_channels(&session_id)
+        .await
+    {
+        Some(channels) => channels,
+        None => {
+            return json_api_error_response_with_headers(
+                StatusCode::NOT_FOUND,
+                "Session Not Found".to_string(),
+                format!("No running session with id '{}'", session_id),
+            )
+        }
+    };
+
+    match channels.retract(request.restore).await {
+        Ok(text) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "retraction".to_string(),
+            id: session_id,
+            attributes: Some(serde_json::json!({
+                "text": text,
+                "restored": request.restore && text.is_some(),
+            })),
+            relationships: None,
+        }),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Retract Failed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
@@ -1,37 +1,215 @@
 use anyhow::Result;
-use axum::{routing::get, Router};
+use axum::{middleware, routing::get, Router};
 use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
+use utoipa_swagger_ui::{Config, SwaggerUi};
 
 use super::{
-    git::{get_git_diff, get_git_file_diff, get_git_status},
-    projects::{add_project, list_projects},
+    agents::list_agents,
+    archive::get_project_archive,
+    auth::require_token,
+    checkpoints::{list_session_checkpoints, restore_session_checkpoint},
+    commits::get_session_commits,
+    conversations::{get_conversation_messages, list_project_conversations},
+    crash_dumps::create_crash_dump,
+    digest::get_digest,
+    git::{
+        get_git_diff, get_git_file_diff, get_git_status, get_project_diff, get_project_file_diff,
+        get_project_git_status,
+    },
+    health::{healthz, readyz},
+    input::send_session_input,
+    lineage::get_session_lineage,
+    log_level::{get_log_level, set_log_level},
+    metrics::{get_session_heatmap, get_session_timeseries},
+    notifications::{
+        get_do_not_disturb, get_notifications, mark_notification_read, set_do_not_disturb,
+    },
+    oidc::{callback, current_user, login, logout},
+    openapi::get_openapi_spec,
+    paste::{create_paste_file, get_paste_settings},
+    preferences::{get_preferences, update_preferences},
+    projects::{add_project, delete_project, list_projects, update_project},
+    recent::get_recent,
+    render_test::render_test_websocket,
+    retract::retract_last_message,
+    screen::get_session_screen,
+    screen_text::get_session_screen_text,
+    screenshot::{get_session_screenshot_png, get_session_screenshot_svg},
+    scrollback::get_session_scrollback_html,
+    search::search_sessions,
     sessions::{
-        create_session, delete_session, get_session, shutdown_server, stream_session_jsonl,
+        adopt_process, create_session, delete_session, get_session, get_session_options,
+        handover_server, list_sessions, set_session_note, shutdown_server, stream_session_jsonl,
+        undo_kill_session,
     },
     static_files::{react_spa_handler, server_index, session_page, static_handler},
+    tokens::{create_token, list_tokens, revoke_token, share_session},
+    transcript::{export_session_conversation, get_session_transcript},
     types::AppState,
-    websocket::websocket_handler,
+    usage::{get_session_usage, get_usage},
+    websocket::{accessible_websocket_handler, raw_websocket_handler, websocket_handler},
+    workspace::get_session_workspace,
 };
+use crate::core::oidc::OidcConfig;
 use crate::server::manager::SessionManagerHandle;
+use crate::server::{handover, shutdown};
 
-pub async fn start_web_server(port: u16, session_manager: SessionManagerHandle) -> Result<()> {
-    let state = AppState { session_manager };
-
+/// Build the API, WebSocket and web UI routes without binding a listener
+///
+/// Embed codemux in another axum service by merging the result into its
+/// router. With `AppState::with_base_path` everything, including the web UI's
+/// assets, is served under that prefix instead of the root. With
+/// `AppState::with_auth_token` the API and WebSockets require the token; the
+/// web UI's pages and assets don't, it takes the token from its address. With
+/// `AppState::with_oidc` as well, a sign-in cookie or `codemux login` token
+/// does instead. Tokens from `/api/tokens` work too, within their scope, and
+/// share links' tokens from `/api/sessions/:id/share` for their session only.
+/// The OpenAPI document at `/api/openapi.json` and its Swagger UI at
+/// `/api/docs` are open to all, like the web UI and the `/healthz` and
+/// `/readyz` probes.
+pub fn build_router(state: AppState) -> Router {
+    let base_path = state.base_path.clone();
     let app = Router::new()
-        .route("/", get(server_index))
-        .route("/session/:session_id", get(session_page))
         .route("/ws/:session_id", get(websocket_handler))
-        .route("/api/sessions", axum::routing::post(create_session))
+        .route("/ws/session/:session_id/raw", get(raw_websocket_handler))
+        .route(
+            "/ws/session/:session_id/accessible",
+            get(accessible_websocket_handler),
+        )
+        .route("/api/sessions", get(list_sessions).post(create_session))
+        .route("/api/session-options", get(get_session_options))
+        .route("/api/agents", get(list_agents))
+        .route("/api/log-level", get(get_log_level).put(set_log_level))
         .route("/api/sessions/:id", get(get_session))
         .route("/api/sessions/:id", axum::routing::delete(delete_session))
+        .route(
+            "/api/sessions/:id/undo-kill",
+            axum::routing::post(undo_kill_session),
+        )
+        .route(
+            "/api/sessions/:id/note",
+            axum::routing::put(set_session_note),
+        )
         .route("/api/sessions/:id/stream", get(stream_session_jsonl))
+        .route("/api/sessions/:id/transcript", get(get_session_transcript))
+        .route("/api/sessions/:id/export", get(export_session_conversation))
+        .route("/api/sessions/:id/usage", get(get_session_usage))
+        .route("/api/sessions/:id/workspace", get(get_session_workspace))
+        .route(
+            "/api/sessions/:id/screenshot.svg",
+            get(get_session_screenshot_svg),
+        )
+        .route(
+            "/api/sessions/:id/screenshot.png",
+            get(get_session_screenshot_png),
+        )
+        .route("/api/sessions/:id/screen", get(get_session_screen))
+        .route(
+            "/api/sessions/:id/screen/text",
+            get(get_session_screen_text),
+        )
+        .route(
+            "/api/sessions/:id/input",
+            axum::routing::post(send_session_input),
+        )
+        .route(
+            "/api/sessions/:id/scrollback.html",
+            get(get_session_scrollback_html),
+        )
+        .route(
+            "/api/sessions/:id/crash-dumps",
+            axum::routing::post(create_crash_dump),
+        )
+        .route(
+            "/api/sessions/:id/paste-file",
+            axum::routing::post(create_paste_file),
+        )
+        .route(
+            "/api/sessions/:id/retract",
+            axum::routing::post(retract_last_message),
+        )
+        .route(
+            "/api/sessions/:id/metrics/timeseries",
+            get(get_session_timeseries),
+        )
+        .route(
+            "/api/sessions/:id/metrics/heatmap",
+            get(get_session_heatmap),
+        )
+        .route(
+            "/api/sessions/:id/share",
+            axum::routing::post(share_session),
+        )
+        .route("/api/sessions/:id/commits", get(get_session_commits))
+        .route("/api/sessions/:id/lineage", get(get_session_lineage))
+        .route(
+            "/api/sessions/:id/checkpoints",
+            get(list_session_checkpoints),
+        )
+        .route(
+            "/api/sessions/:id/checkpoints/:checkpoint/restore",
+            axum::routing::post(restore_session_checkpoint),
+        )
         .route("/api/sessions/:id/git/status", get(get_git_status))
         .route("/api/sessions/:id/git/diff", get(get_git_diff))
         .route("/api/sessions/:id/git/diff/*path", get(get_git_file_diff))
         .route("/api/projects", get(list_projects))
         .route("/api/projects", axum::routing::post(add_project))
+        .route(
+            "/api/projects/:id",
+            axum::routing::delete(delete_project).patch(update_project),
+        )
+        .route("/api/projects/:id/archive", get(get_project_archive))
+        .route(
+            "/api/projects/:id/conversations",
+            get(list_project_conversations),
+        )
+        .route(
+            "/api/projects/:id/conversations/:conversation/messages",
+            get(get_conversation_messages),
+        )
+        .route("/api/projects/:id/git/status", get(get_project_git_status))
+        .route("/api/projects/:id/diff", get(get_project_diff))
+        .route("/api/projects/:id/diff/*path", get(get_project_file_diff))
+        .route(
+            "/api/preferences",
+            get(get_preferences).patch(update_preferences),
+        )
+        .route("/api/recent", get(get_recent))
+        .route("/api/notifications", get(get_notifications))
+        .route("/api/digest", get(get_digest))
+        .route("/api/usage", get(get_usage))
+        .route("/api/search", get(search_sessions))
+        .route(
+            "/api/notifications/dnd",
+            get(get_do_not_disturb).put(set_do_not_disturb),
+        )
+        .route(
+            "/api/notifications/:id/read",
+            axum::routing::post(mark_notification_read),
+        )
+        .route("/api/paste-settings", get(get_paste_settings))
+        .route("/api/tokens", get(list_tokens).post(create_token))
+        .route("/api/tokens/:id", axum::routing::delete(revoke_token))
+        .route("/api/dev/render-test", get(render_test_websocket))
         .route("/api/shutdown", axum::routing::post(shutdown_server))
+        .route("/api/handover", axum::routing::post(handover_server))
+        .route("/api/adopt", axum::routing::post(adopt_process))
+        .route("/auth/me", get(current_user))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .route("/auth/login", get(login))
+        .route("/auth/callback", get(callback))
+        .route("/auth/logout", axum::routing::post(logout))
+        .route("/api/openapi.json", get(get_openapi_spec))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .merge(
+            SwaggerUi::new("/api/docs")
+                .config(Config::new([format!("{}/api/openapi.json", base_path)])),
+        )
+        .route("/", get(server_index))
+        .route("/session/:session_id", get(session_page))
         .route("/_expo/static/*path", get(static_handler))
         .route("/*path", get(react_spa_handler))
         .layer(
@@ -44,9 +222,254 @@ pub async fn start_web_server(port: u16, session_manager: SessionManagerHandle)
         )
         .with_state(state);
 
-    let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    tracing::info!("CodeMux web server listening on http://0.0.0.0:{}", port);
+    if base_path.is_empty() {
+        app
+    } else {
+        Router::new().nest(&base_path, app)
+    }
+}
+
+pub async fn start_web_server(
+    port: u16,
+    session_manager: SessionManagerHandle,
+    auth_token: Option<String>,
+    oidc: Option<OidcConfig>,
+) -> Result<()> {
+    // Shared binding, so a newer server can take over with `codemux server upgrade`
+    let listener = handover::listen(handover::bind_shared(port)?)?;
+    serve_listener(listener, session_manager, auth_token, oidc).await
+}
+
+/// Serve on a listener that is already bound, such as one taken over from an older server
+pub async fn serve_listener(
+    listener: tokio::net::TcpListener,
+    session_manager: SessionManagerHandle,
+    auth_token: Option<String>,
+    oidc: Option<OidcConfig>,
+) -> Result<()> {
+    let state = app_state(session_manager, auth_token, oidc);
+    serve_tcp(listener, state).await
+}
+
+/// As `serve_listener`, and on the Unix socket from `bind_socket` as well
+///
+/// Requests over the socket need no token or sign-in: only the user the
+/// server runs as can connect to it.
+#[cfg(unix)]
+pub async fn serve_with_socket(
+    listener: tokio::net::TcpListener,
+    socket: tokio::net::UnixListener,
+    session_manager: SessionManagerHandle,
+    auth_token: Option<String>,
+    oidc: Option<OidcConfig>,
+) -> Result<()> {
+    let state = app_state(session_manager, auth_token, oidc);
+    let local = build_router(AppState {
+        auth_token: None,
+        oidc: None,
+        ..state.clone()
+    });
+    tokio::spawn(async move {
+        if let Err(e) = serve_unix(socket, local).await {
+            tracing::error!("Unix socket listener stopped: {}", e);
+        }
+    });
+    serve_tcp(listener, state).await
+}
+
+/// Bind a Unix socket at `path` that only this user can connect to,
+/// replacing one an earlier server left behind
+#[cfg(unix)]
+pub fn bind_socket(path: &std::path::Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(anyhow::anyhow!(
+                "Can't replace the socket {}: {}",
+                path.display(),
+                e
+            ))
+        }
+        _ => {}
+    }
+    let listener = tokio::net::UnixListener::bind(path)
+        .map_err(|e| anyhow::anyhow!("Can't bind the socket {}: {}", path.display(), e))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
 
-    axum::serve(listener, app).await?;
+fn app_state(
+    session_manager: SessionManagerHandle,
+    auth_token: Option<String>,
+    oidc: Option<OidcConfig>,
+) -> AppState {
+    let mut state = AppState::new(session_manager);
+    if let Some(token) = auth_token {
+        state = state.with_auth_token(token);
+    }
+    if let Some(oidc) = oidc {
+        state = state.with_oidc(oidc);
+    }
+    state
+}
+
+/// Serve until SIGTERM or Ctrl+C; the sessions end first, so WebSocket
+/// clients see them close, then open connections get `CONNECTION_DRAIN` to
+/// finish
+async fn serve_tcp(listener: tokio::net::TcpListener, state: AppState) -> Result<()> {
+    tracing::info!(
+        "CodeMux web server listening on http://{}",
+        listener.local_addr()?
+    );
+    let (stopping_tx, stopping_rx) = tokio::sync::oneshot::channel();
+    let session_manager = state.session_manager.clone();
+    let server = axum::serve(listener, build_router(state)).with_graceful_shutdown(async move {
+        shutdown::on_terminate(session_manager).await;
+        let _ = stopping_tx.send(());
+    });
+    let drained = async {
+        match stopping_rx.await {
+            Ok(()) => tokio::time::sleep(shutdown::CONNECTION_DRAIN).await,
+            Err(_) => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        result = async { server.await } => result?,
+        () = drained => tracing::warn!(
+            "Connections still open after {}s, stopping anyway",
+            shutdown::CONNECTION_DRAIN.as_secs()
+        ),
+    }
     Ok(())
 }
+
+/// `axum::serve` only takes TCP listeners, so connections on the socket are
+/// served by hyper directly, WebSocket upgrades included
+#[cfg(unix)]
+async fn serve_unix(listener: tokio::net::UnixListener, app: Router) -> Result<()> {
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto::Builder;
+    use hyper_util::service::TowerToHyperService;
+
+    if let Ok(addr) = listener.local_addr() {
+        tracing::info!("CodeMux web server listening on {:?}", addr);
+    }
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = TowerToHyperService::new(app.clone());
+        tokio::spawn(async move {
+            if let Err(e) = Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Unix socket connection ended: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn serves_routes_under_base_path() {
+        let mut config = crate::core::Config::default();
+        config.server.data_dir =
+            std::env::temp_dir().join(format!("codemux-router-{}", std::process::id()));
+        let state = AppState::new(SessionManagerHandle::new(config)).with_base_path("codemux/");
+        let app = build_router(state);
+
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        let nested = app.clone().oneshot(get("/codemux/api/paste-settings"));
+        assert_eq!(nested.await.unwrap().status(), StatusCode::OK);
+        let root = app.oneshot(get("/api/paste-settings"));
+        assert_eq!(root.await.unwrap().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn api_documents_match_snapshots() {
+        let root = std::env::temp_dir().join(format!("codemux-snapshots-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("shop/api")).unwrap();
+        // Without a manifest naming it, a project's color comes from its path
+        std::fs::write(root.join("shop/package.json"), r#"{"name": "shop"}"#).unwrap();
+        std::fs::write(root.join("shop/api/package.json"), r#"{"name": "api"}"#).unwrap();
+        let mut config = crate::core::Config::default();
+        config.server.data_dir = root.join("data");
+        let app = build_router(AppState::new(SessionManagerHandle::new(config)));
+
+        let request = |method: &str, uri: &str, body: serde_json::Value| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+            }
+        };
+        // IDs, paths and times differ from run to run
+        let mut unstable = vec![(root.display().to_string(), "[root]".to_string())];
+        let stable = |document: serde_json::Value, unstable: &[(String, String)]| {
+            let mut text = document.to_string();
+            for (value, placeholder) in unstable {
+                text = text.replace(value, placeholder);
+            }
+            serde_json::from_str::<serde_json::Value>(&text).unwrap()
+        };
+
+        let shop = root.join("shop").display().to_string();
+        let project = request(
+            "POST",
+            "/api/projects",
+            json!({"name": "shop", "path": shop}),
+        )
+        .await;
+        unstable.push((
+            project["data"]["id"].as_str().unwrap().to_string(),
+            "[shop-id]".into(),
+        ));
+        insta::assert_json_snapshot!("project", stable(project, &unstable));
+
+        let api = root.join("shop/api").display().to_string();
+        let body = json!({"name": "api", "path": api, "parent": "shop"});
+        let subproject = request("POST", "/api/projects", body).await;
+        unstable.push((
+            subproject["data"]["id"].as_str().unwrap().to_string(),
+            "[api-id]".into(),
+        ));
+        insta::assert_json_snapshot!("subproject", stable(subproject, &unstable));
+
+        let missing = request("GET", "/api/sessions/missing", json!(null)).await;
+        insta::assert_json_snapshot!("session-not-found", stable(missing, &unstable));
+
+        let body = json!({"name": "ci", "scope": "read", "ttl_seconds": 3600});
+        let token = request("POST", "/api/tokens", body).await;
+        for field in ["id", "secret", "created_at", "expires_at"] {
+            let value = token["data"]["attributes"][field]
+                .as_str()
+                .unwrap()
+                .to_string();
+            unstable.push((value, format!("[{}]", field)));
+        }
+        insta::assert_json_snapshot!("api-token", stable(token, &unstable));
+
+        let revoked = request("DELETE", "/api/tokens/missing", json!(null)).await;
+        insta::assert_json_snapshot!("token-not-found", stable(revoked, &unstable));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
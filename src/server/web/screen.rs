@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+
+use super::openapi::ResourceDocument;
+use super::screenshot::capture_snapshot;
+use super::types::{AppState, ScreenFormat, ScreenQuery};
+use crate::core::json_api_response_with_headers;
+use crate::core::render::ScreenGrid;
+use crate::core::{JsonApiErrorDocument, JsonApiResource};
+
+/// The live screen, for scripts that read what an agent shows
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}/screen",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID or name"), ScreenQuery),
+    responses(
+        (status = 200, description = "A `screen` resource, or with `format=text` the rows as plain text", body = ResourceDocument<ScreenGrid>, content_type = ["application/json", "text/plain"]),
+        (status = 404, description = "No running session", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn get_session_screen(
+    Path(session_id): Path<String>,
+    Query(query): Query<ScreenQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let snapshot = match capture_snapshot(&session_id, &state).await {
+        Ok(snapshot) => snapshot,
+        Err(response) => return response,
+    };
+    let grid = snapshot.grid();
+
+    match query.format {
+        ScreenFormat::Grid => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "screen".to_string(),
+            id: session_id,
+            attributes: Some(grid),
+            relationships: None,
+        }),
+        ScreenFormat::Text => (
+            [
+                (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+                (header::CACHE_CONTROL, "no-store"),
+            ],
+            grid.lines.join("\n") + "\n",
+        )
+            .into_response(),
+    }
+}
@@ -0,0 +1,51 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Response,
+};
+
+use super::screenshot::capture_snapshot;
+use super::types::{AppState, ScreenTextQuery};
+use crate::core::json_api_response_with_headers;
+use crate::core::selection::{ScreenText, Selection, SelectionShape};
+use crate::core::JsonApiResource;
+
+/// Text of a part of the live screen, the whole screen without parameters,
+/// for copying from the web terminal
+pub async fn get_session_screen_text(
+    Path(session_id): Path<String>,
+    Query(query): Query<ScreenTextQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let snapshot = match capture_snapshot(&session_id, &state).await {
+        Ok(snapshot) => snapshot,
+        Err(response) => return response,
+    };
+
+    let last_row = snapshot.rows.saturating_sub(1);
+    let last_col = snapshot.cols.saturating_sub(1);
+    let selection = Selection {
+        anchor: (
+            query.top.unwrap_or(0).min(last_row),
+            query.left.unwrap_or(0),
+        ),
+        cursor: (
+            query.bottom.unwrap_or(last_row).min(last_row),
+            query.right.unwrap_or(last_col),
+        ),
+        shape: query.shape.unwrap_or(SelectionShape::Block),
+    };
+    let text = selection.text(snapshot.cols, |line, row, col| {
+        snapshot.push_char(line, row, col)
+    });
+
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "screen-text".to_string(),
+        id: session_id,
+        attributes: Some(ScreenText {
+            text,
+            rows: snapshot.rows,
+            cols: snapshot.cols,
+        }),
+        relationships: None,
+    })
+}
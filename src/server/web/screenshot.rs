@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use super::types::AppState;
+use crate::core::json_api_error_response_with_headers;
+use crate::core::render::{self, ScreenSnapshot};
+
+/// Capture the current screen of a live session as a snapshot
+pub(super) async fn capture_snapshot(
+    session_id: &str,
+    state: &AppState,
+) -> Result<ScreenSnapshot, Response> {
+    let channels = match state.session_manager.get_session_channels(session_id).await {
+        Some(channels) => channels,
+        None => {
+            return Err(json_api_error_response_with_headers(
+                StatusCode::NOT_FOUND,
+                "Session Not Found".to_string(),
+                format!("No running session with id '{}'", session_id),
+            ))
+        }
+    };
+
+    let keyframe = channels.request_keyframe().await.map_err(|e| {
+        tracing::error!("Failed to get keyframe for session {}: {}", session_id, e);
+        json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Screen Capture Failed".to_string(),
+            e.to_string(),
+        )
+    })?;
+
+    ScreenSnapshot::from_keyframe(keyframe).ok_or_else(|| {
+        json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Screen Capture Failed".to_string(),
+            "Session returned a diff instead of a keyframe".to_string(),
+        )
+    })
+}
+
+pub async fn get_session_screenshot_svg(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    let snapshot = match capture_snapshot(&session_id, &state).await {
+        Ok(snapshot) => snapshot,
+        Err(response) => return response,
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        render::render_svg(&snapshot),
+    )
+        .into_response()
+}
+
+#[cfg(feature = "png-screenshots")]
+pub async fn get_session_screenshot_png(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
+    let snapshot = match capture_snapshot(&session_id, &state).await {
+        Ok(snapshot) => snapshot,
+        Err(response) => return response,
+    };
+
+    // Rasterizing (and loading system fonts) is CPU-bound, keep it off the runtime
+    match tokio::task::spawn_blocking(move || render::render_png(&snapshot)).await {
+        Ok(Ok(png)) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                (header::CACHE_CONTROL, "no-store"),
+            ],
+            png,
+        )
+            .into_response(),
+        Ok(Err(e)) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Screenshot Rendering Failed".to_string(),
+            e.to_string(),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Screenshot Rendering Failed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+#[cfg(not(feature = "png-screenshots"))]
+pub async fn get_session_screenshot_png(Path(_session_id): Path<String>) -> Response {
+    json_api_error_response_with_headers(
+        StatusCode::NOT_IMPLEMENTED,
+        "PNG Screenshots Unavailable".to_string(),
+        "This build does not include PNG rendering; use screenshot.svg or rebuild with --features png-screenshots".to_string(),
+    )
+}
@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use super::types::{AppState, ScrollbackQuery};
+use crate::core::json_api_error_response_with_headers;
+use crate::core::render;
+
+/// Number of history lines exported when the client doesn't ask for a specific amount
+pub const DEFAULT_SCROLLBACK_LINES: usize = 1000;
+
+pub async fn get_session_scrollback_html(
+    Path(session_id): Path<String>,
+    Query(query): Query<ScrollbackQuery>,
+    State(state): State<AppState>,
+) -> Response {
+    let channels = match state
+        .session_manager
+        .get_session_channels(&session_id)
+        .await
+    {
+        Some(channels) => channels,
+        None => {
+            return json_api_error_response_with_headers(
+                StatusCode::NOT_FOUND,
+                "Session Not Found".to_string(),
+                format!("No running session with id '{}'", session_id),
+            )
+        }
+    };
+
+    let lines = query.lines.unwrap_or(DEFAULT_SCROLLBACK_LINES);
+    match channels.request_scrollback(lines).await {
+        Ok(history) => {
+            let title = format!("codemux session {}", session_id);
+            (
+                [
+                    (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+                    (header::CACHE_CONTROL, "no-store"),
+                ],
+                render::render_html_document(&title, &history),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to read scrollback for session {}: {}",
+                session_id,
+                e
+            );
+            json_api_error_response_with_headers(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Scrollback Export Failed".to_string(),
+                e.to_string(),
+            )
+        }
+    }
+}
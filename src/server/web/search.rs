@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::types::{AppState, SearchQuery};
+use crate::core::search::SearchResults;
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+use crate::server::search;
+
+/// Most hits a search returns, whatever the client asks for
+const MAX_SEARCH_HITS: usize = 100;
+
+/// Sessions whose transcripts, scrollback or notes contain every word of `q`
+pub async fn search_sessions(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Response {
+    if query.q.trim().is_empty() {
+        return json_api_error_response_with_headers(
+            StatusCode::BAD_REQUEST,
+            "Empty Query".to_string(),
+            "Pass the words to search for as the q parameter".to_string(),
+        );
+    }
+    let limit = query.limit.unwrap_or(20).min(MAX_SEARCH_HITS);
+    match search::search(
+        &state.session_manager,
+        state.search_index.clone(),
+        &query.q,
+        limit,
+    )
+    .await
+    {
+        Ok(hits) => json_api_response_with_headers(JsonApiResource::<_, ()> {
+            resource_type: "search".to_string(),
+            id: query.q.clone(),
+            attributes: Some(SearchResults {
+                query: query.q,
+                hits,
+            }),
+            relationships: None,
+        }),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Search Failed".to_string(),
+            e.to_string(),
+        ),
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::{
         sse::{Event, Sse},
         IntoResponse,
@@ -9,8 +9,22 @@ use axum::{
 use futures::stream::Stream;
 use std::convert::Infallible;
 
-use super::types::{AppState, CreateSessionRequest};
-use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+use super::openapi::MessageDocument;
+use super::types::{
+    AdoptProcessRequest, AppState, CreateSessionRequest, DeleteSessionQuery, SetSessionNoteRequest,
+};
+use crate::core::json_api::{JsonApiPage, SessionResourceTS, SessionResponse};
+use crate::core::launch::{InvalidEnvError, LaunchOptions};
+use crate::core::limits::SessionLimitError;
+use crate::core::listing::ListQuery;
+use crate::core::project_groups;
+use crate::core::session::SessionType;
+use crate::core::{
+    json_api_error_response_with_headers, json_api_page_response_with_headers,
+    json_api_response_with_headers,
+};
+use crate::core::{JsonApiErrorDocument, SessionResource};
+use crate::server::handover::Handover;
 use std::path::PathBuf;
 use std::time::SystemTime;
 use tokio::fs;
@@ -85,6 +99,35 @@ async fn find_most_recent_jsonl() -> Result<Option<String>, std::io::Error> {
     Ok(most_recent.map(|(_, session_id)| session_id))
 }
 
+/// Agents, templates and projects for the web UI's "New Session" form
+pub async fn get_session_options(State(state): State<AppState>) -> impl IntoResponse {
+    match state.session_manager.session_options().await {
+        Ok(options) => json_api_response_with_headers(crate::core::JsonApiResource::<_, ()> {
+            resource_type: "session-options".to_string(),
+            id: "new".to_string(),
+            attributes: Some(options),
+            relationships: None,
+        }),
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            "Session Options Unavailable".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sessions",
+    tag = "sessions",
+    request_body = CreateSessionRequest,
+    responses(
+        (status = 200, description = "The started session", body = SessionResponse),
+        (status = 400, description = "An `env` variable that can't be set", body = JsonApiErrorDocument),
+        (status = 404, description = "No such project", body = JsonApiErrorDocument),
+        (status = 429, description = "Over a `[limits]` session limit", body = JsonApiErrorDocument),
+    )
+)]
 pub async fn create_session(
     State(state): State<AppState>,
     Json(mut req): Json<CreateSessionRequest>,
@@ -95,6 +138,15 @@ pub async fn create_session(
         req.args
     );
 
+    // Other agents find their latest conversation themselves, given their own flags
+    if req.agent.to_lowercase() != "claude" {
+        if let Some(resume_args) = state.session_manager.resume_args(&req.agent, None) {
+            if let Some(continue_idx) = req.args.iter().position(|arg| arg == "--continue") {
+                req.args.splice(continue_idx..=continue_idx, resume_args);
+            }
+        }
+    }
+
     // Handle --continue flag for Claude agent
     let resume_session_id = if req.agent.to_lowercase() == "claude" {
         if let Some(continue_idx) = req.args.iter().position(|arg| arg == "--continue") {
@@ -183,6 +235,7 @@ pub async fn create_session(
             req.project_id,
             req.path,
             resume_session_id,
+            req.launch,
         )
         .await
     {
@@ -190,6 +243,21 @@ pub async fn create_session(
             tracing::info!("Session created successfully: {}", info.id);
             json_api_response_with_headers(info)
         }
+        Err(e) if e.downcast_ref::<SessionLimitError>().is_some() => {
+            tracing::warn!("Refused to create session: {}", e);
+            json_api_error_response_with_headers(
+                axum::http::StatusCode::TOO_MANY_REQUESTS,
+                "Session Limit Reached".to_string(),
+                e.to_string(),
+            )
+        }
+        Err(e) if e.downcast_ref::<InvalidEnvError>().is_some() => {
+            json_api_error_response_with_headers(
+                axum::http::StatusCode::BAD_REQUEST,
+                "Invalid Environment".to_string(),
+                e.to_string(),
+            )
+        }
         Err(e) => {
             tracing::error!("Failed to create session: {}", e);
             json_api_error_response_with_headers(
@@ -201,12 +269,106 @@ pub async fn create_session(
     }
 }
 
+/// Fields `GET /api/sessions` sorts by
+const SESSION_SORT_FIELDS: &[&str] = &["name", "agent", "project", "status", "last_modified"];
+
+/// Running sessions, then those that ended within the `[recycle_bin]`
+/// retention, filtered, sorted and paginated, see `core::listing`
+#[utoipa::path(
+    get,
+    path = "/api/sessions",
+    tag = "sessions",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "One page of sessions", body = JsonApiPage<SessionResourceTS>),
+        (status = 400, description = "An unknown sort field or a page out of range", body = JsonApiErrorDocument),
+        (status = 404, description = "No project matches `filter[project]`", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn list_sessions(
+    Query(query): Query<ListQuery>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let project_id = match query.project.as_deref() {
+        Some(address) => {
+            let projects = state.session_manager.list_projects().await;
+            match project_groups::find_project(&projects, address) {
+                Some(project) => Some(project.id.clone()),
+                None => {
+                    return json_api_error_response_with_headers(
+                        axum::http::StatusCode::NOT_FOUND,
+                        "Project Not Found".to_string(),
+                        format!("No project '{}'", address),
+                    )
+                }
+            }
+        }
+        None => None,
+    };
+
+    let mut sessions = state.session_manager.list_sessions().await;
+    sessions.extend(state.session_manager.list_ended_sessions().await);
+    sessions.retain(|session| {
+        query.matches(session.attributes.as_ref())
+            && project_id.as_ref().is_none_or(|id| {
+                session.attributes.as_ref().and_then(|a| a.project.as_ref()) == Some(id)
+            })
+    });
+    let page = query
+        .sort(&mut sessions, SESSION_SORT_FIELDS, session_field)
+        .and_then(|()| query.paginate(sessions));
+    match page {
+        Ok(page) => {
+            let links = query.links(&format!("{}/api/sessions", state.base_path), &page.meta);
+            json_api_page_response_with_headers(page, links)
+        }
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::BAD_REQUEST,
+            "Invalid List Parameters".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+/// A session's value of one of `SESSION_SORT_FIELDS`
+fn session_field(session: &SessionResource, field: &str) -> Option<String> {
+    let attributes = session.attributes.as_ref()?;
+    match field {
+        "name" => attributes.name.clone(),
+        "agent" => Some(attributes.agent.clone()),
+        "project" => attributes.project.clone(),
+        "status" => Some(attributes.status.clone()),
+        "last_modified" => attributes.last_modified.clone(),
+        _ => None,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID or name")),
+    responses(
+        (status = 200, description = "The session, running or from history", body = SessionResponse),
+        (status = 404, description = "No such session", body = JsonApiErrorDocument),
+    )
+)]
 pub async fn get_session(
     Path(id): Path<String>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
     match state.session_manager.get_session(&id).await {
-        Some(info) => json_api_response_with_headers(info),
+        Some(mut info) => {
+            // Only here, since it runs git for each session
+            if let Some(attributes) = info.attributes.as_mut() {
+                if matches!(attributes.session_type, SessionType::Active) {
+                    if let Some(dir) = super::git::get_session_working_dir(&id, &state).await {
+                        attributes.diff_stats = super::git::execute_diff_stats(&dir).await;
+                    }
+                }
+            }
+            json_api_response_with_headers(info)
+        }
         None => json_api_error_response_with_headers(
             axum::http::StatusCode::NOT_FOUND,
             "Session Not Found".to_string(),
@@ -215,14 +377,60 @@ pub async fn get_session(
     }
 }
 
+/// Leave a handover note on a running session for whoever attaches next
+#[utoipa::path(
+    put,
+    path = "/api/sessions/{id}/note",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID or name")),
+    request_body = SetSessionNoteRequest,
+    responses(
+        (status = 200, description = "The session with its note", body = SessionResponse),
+        (status = 404, description = "No running session", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn set_session_note(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<SetSessionNoteRequest>,
+) -> impl IntoResponse {
+    match state
+        .session_manager
+        .set_session_note(&id, request.note)
+        .await
+    {
+        Some(info) => json_api_response_with_headers(info),
+        None => json_api_error_response_with_headers(
+            axum::http::StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No running session with id '{}'", id),
+        ),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/sessions/{id}",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID or name"), DeleteSessionQuery),
+    responses(
+        (status = 200, description = "The session was killed; unless `now`, `ends_at` says when it ends for good", body = MessageDocument),
+        (status = 500, description = "The session couldn't be killed", body = JsonApiErrorDocument),
+    )
+)]
 pub async fn delete_session(
     Path(id): Path<String>,
+    Query(query): Query<DeleteSessionQuery>,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    match state.session_manager.close_session(&id).await {
-        Ok(_) => json_api_response_with_headers(serde_json::json!({
+    match state.session_manager.close_session(&id, query.now).await {
+        Ok(None) => json_api_response_with_headers(serde_json::json!({
             "message": "Session closed successfully"
         })),
+        Ok(Some(ends_at)) => json_api_response_with_headers(serde_json::json!({
+            "message": "Session killed, it ends unless the kill is undone",
+            "ends_at": ends_at
+        })),
         Err(e) => json_api_error_response_with_headers(
             axum::http::StatusCode::INTERNAL_SERVER_ERROR,
             "Session Deletion Failed".to_string(),
@@ -231,6 +439,37 @@ pub async fn delete_session(
     }
 }
 
+/// Keep a killed session running during its grace period
+#[utoipa::path(
+    post,
+    path = "/api/sessions/{id}/undo-kill",
+    tag = "sessions",
+    params(("id" = String, Path, description = "Session ID or name")),
+    responses(
+        (status = 200, description = "The session, running on", body = SessionResponse),
+        (status = 404, description = "No such session", body = JsonApiErrorDocument),
+        (status = 409, description = "The session wasn't killed, or already ended", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn undo_kill_session(
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    match state.session_manager.undo_kill(&id).await {
+        Ok(Some(info)) => json_api_response_with_headers(info),
+        Ok(None) => json_api_error_response_with_headers(
+            axum::http::StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No session with id '{}'", id),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::CONFLICT,
+            "Kill Not Undone".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
 pub async fn stream_session_jsonl(
     Path(session_id): Path<String>,
     State(state): State<AppState>,
@@ -244,7 +483,7 @@ pub async fn stream_session_jsonl(
         let session_info = state.session_manager.get_session(&session_id).await;
 
         if let Some(info) = session_info {
-            // Only process Claude sessions
+            // Claude appends to one JSONL file per session, which is tailed
             if let Some(attrs) = &info.attributes {
                 if attrs.agent.to_lowercase() == "claude" {
                 // Get current working directory and convert to dash-case for project folder
@@ -318,8 +557,48 @@ pub async fn stream_session_jsonl(
                 } else {
                     yield Ok(Event::default().data(format!("JSONL file not found: {}", jsonl_path)));
                 }
+                } else if let Some(adapter) = state.session_manager.agent_adapter(&attrs.agent) {
+                    // Other transcripts are re-read when they change, as some, e.g.
+                    // Gemini's JSON files, are rewritten as a whole
+                    let home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()));
+                    let project = super::git::get_session_working_dir(&session_id, &state)
+                        .await
+                        .map(PathBuf::from)
+                        .unwrap_or_default();
+                    let mut transcript: Option<PathBuf> = None;
+                    let mut modified: Option<SystemTime> = None;
+                    let mut sent = 0;
+                    let mut streaming = false;
+
+                    loop {
+                        let newest = adapter.session_transcript(&home, &project, &session_id);
+                        if newest != transcript {
+                            // The agent started another conversation
+                            transcript = newest;
+                            modified = None;
+                            sent = 0;
+                        }
+                        if let Some(path) = &transcript {
+                            let changed = fs::metadata(path).await.and_then(|meta| meta.modified()).ok();
+                            if changed.is_some() && changed != modified {
+                                modified = changed;
+                                if let Ok(content) = fs::read_to_string(path).await {
+                                    let entries = adapter.parse_transcript(&content);
+                                    for entry in entries.iter().skip(sent) {
+                                        yield Ok(Event::default().data(entry.to_string()));
+                                    }
+                                    sent = sent.max(entries.len());
+                                }
+                            }
+                        }
+                        if !streaming {
+                            yield Ok(Event::default().data("[STREAMING]"));
+                            streaming = true;
+                        }
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                    }
                 } else {
-                    yield Ok(Event::default().data(format!("Not a Claude session: {}", attrs.agent)));
+                    yield Ok(Event::default().data(format!("No conversation transcripts for {} sessions", attrs.agent)));
                 }
             } else {
                 yield Ok(Event::default().data("Session missing attributes"));
@@ -340,14 +619,80 @@ pub async fn shutdown_server(State(state): State<AppState>) -> impl IntoResponse
     // Gracefully shutdown all sessions
     tracing::info!("Shutting down all sessions...");
     state.session_manager.shutdown_all_sessions().await;
+    crate::server::shutdown::remove_pid_file(state.session_manager.pid_file());
 
     // Spawn a task to exit the process after a short delay
     // This allows the HTTP response to be sent before the server shuts down
     tokio::spawn(async {
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         tracing::info!("Exiting server process");
+        let _ = tokio::task::spawn_blocking(crate::core::telemetry::shutdown).await;
         std::process::exit(0);
     });
 
     Json(serde_json::json!({"status": "shutdown initiated"})).into_response()
 }
+
+/// Take over an agent process started outside codemux, for `codemux adopt`
+///
+/// Needs an `admin` token, as it moves a process of the server's user into a
+/// session; the process has to be running `agent`.
+pub async fn adopt_process(
+    State(state): State<AppState>,
+    Json(req): Json<AdoptProcessRequest>,
+) -> impl IntoResponse {
+    let launch = LaunchOptions {
+        adopt_pid: Some(req.pid),
+        ..LaunchOptions::default()
+    };
+    match state
+        .session_manager
+        .create_session_with_path(req.agent, Vec::new(), None, req.path, None, launch)
+        .await
+    {
+        Ok(info) => {
+            tracing::info!("Adopted PID {} as session {}", req.pid, info.id);
+            json_api_response_with_headers(info)
+        }
+        Err(e) if e.downcast_ref::<SessionLimitError>().is_some() => {
+            json_api_error_response_with_headers(
+                axum::http::StatusCode::TOO_MANY_REQUESTS,
+                "Session Limit Reached".to_string(),
+                e.to_string(),
+            )
+        }
+        Err(e) => {
+            tracing::error!("Failed to adopt PID {}: {}", req.pid, e);
+            json_api_error_response_with_headers(
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                "Adoption Failed".to_string(),
+                e.to_string(),
+            )
+        }
+    }
+}
+
+/// Hand the running sessions over to a newer server taking over the port
+///
+/// From here on this server starts no sessions; the new server stops it once
+/// it has moved the agents and accepts connections.
+pub async fn handover_server(State(state): State<AppState>) -> impl IntoResponse {
+    use axum::Json;
+
+    match state.session_manager.handover().await {
+        Ok(sessions) => {
+            tracing::info!("Handing {} sessions over to a new server", sessions.len());
+            Json(Handover {
+                pid: std::process::id(),
+                sessions,
+            })
+            .into_response()
+        }
+        Err(e) => json_api_error_response_with_headers(
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            "Handover Failed".to_string(),
+            e.to_string(),
+        )
+        .into_response(),
+    }
+}
@@ -6,22 +6,82 @@ use axum::{
 };
 
 use super::types::AppState;
+#[cfg(feature = "web-ui")]
 use crate::assets::embedded::ReactAssets;
 
-pub async fn server_index() -> impl IntoResponse {
-    serve_react_asset("index.html").await
+/// A file of the embedded web UI
+#[cfg(feature = "web-ui")]
+pub(super) fn web_asset(path: &str) -> Option<Vec<u8>> {
+    ReactAssets::get(path).map(|content| content.data.into_owned())
 }
 
-pub async fn session_page(State(_state): State<AppState>) -> impl IntoResponse {
+/// Headless builds have no web UI to serve
+#[cfg(not(feature = "web-ui"))]
+pub(super) fn web_asset(_path: &str) -> Option<Vec<u8>> {
+    None
+}
+
+pub async fn server_index(State(state): State<AppState>) -> Response {
+    serve_index(&state).await
+}
+
+pub async fn session_page(State(state): State<AppState>) -> Response {
     // For server mode, serve React app
-    serve_react_asset("index.html").await
+    serve_index(&state).await
+}
+
+/// Serve the web UI's index.html, pointing it at the base path when there is one
+///
+/// Root-relative asset links get the prefix, and the page learns it from
+/// `window.__CODEMUX_BASE_PATH__` so API and WebSocket URLs include it too.
+/// The prefix is dropped from the address bar before the app starts, since
+/// its page routes are root-relative.
+async fn serve_index(state: &AppState) -> Response {
+    if !cfg!(feature = "web-ui") {
+        return Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from(
+                "This codemux server was built without the web-ui feature.\n\
+                 The API and WebSockets are available under /api.\n",
+            ))
+            .unwrap();
+    }
+    if state.base_path.is_empty() {
+        return serve_react_asset("index.html").await.into_response();
+    }
+    let Some(content) = web_asset("index.html") else {
+        return serve_react_asset("index.html").await.into_response();
+    };
+
+    let base_path = &state.base_path;
+    let html = String::from_utf8_lossy(&content)
+        .replace("src=\"/", &format!("src=\"{}/", base_path))
+        .replace("href=\"/", &format!("href=\"{}/", base_path))
+        .replacen(
+            "<head>",
+            &format!(
+                "<head><script>window.__CODEMUX_BASE_PATH__={:?};\
+                 (function(b,l){{if(l.pathname===b||l.pathname.startsWith(b+\"/\"))\
+                 history.replaceState(null,\"\",(l.pathname.slice(b.length)||\"/\")+l.search+l.hash)}})\
+                 (window.__CODEMUX_BASE_PATH__,location)</script>",
+                base_path
+            ),
+            1,
+        );
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .body(Body::from(html))
+        .unwrap()
 }
 
 pub async fn serve_react_asset(path: &str) -> impl IntoResponse {
     tracing::debug!("serve_react_asset called with path: '{}'", path);
-    match ReactAssets::get(path) {
+    match web_asset(path) {
         Some(content) => {
-            let body = Body::from(content.data.into_owned());
+            let body = Body::from(content);
             let mime = mime_guess::from_path(path).first_or_octet_stream();
             tracing::debug!("Found asset '{}', serving with mime: {}", path, mime);
             Response::builder()
@@ -50,7 +110,10 @@ pub async fn static_handler(Path(path): Path<String>) -> impl IntoResponse {
     serve_react_asset(&file_path).await
 }
 
-pub async fn react_spa_handler(Path(_path): Path<String>) -> impl IntoResponse {
+pub async fn react_spa_handler(
+    Path(_path): Path<String>,
+    State(state): State<AppState>,
+) -> Response {
     // For SPA routing, always serve index.html for non-API routes
-    serve_react_asset("index.html").await
+    serve_index(&state).await
 }
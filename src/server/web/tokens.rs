@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Json,
+};
+use std::time::Duration;
+
+use super::types::AppState;
+use crate::core::api_tokens::{
+    ApiToken, ApiTokenStore, CreateApiTokenRequest, ShareSessionRequest,
+};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+fn token_resource<T>(token: &ApiToken, attributes: T) -> JsonApiResource<T, ()> {
+    JsonApiResource {
+        resource_type: "api-token".to_string(),
+        id: token.id.clone(),
+        attributes: Some(attributes),
+        relationships: None,
+    }
+}
+
+/// API tokens, oldest first, without their secrets
+pub async fn list_tokens(State(state): State<AppState>) -> Response {
+    let tokens: Vec<_> = state
+        .api_tokens
+        .list()
+        .into_iter()
+        .map(|token| token_resource(&token, token.clone()))
+        .collect();
+    json_api_response_with_headers(tokens)
+}
+
+/// Create a token and return it with its secret, which isn't shown again
+pub async fn create_token(
+    State(state): State<AppState>,
+    Json(request): Json<CreateApiTokenRequest>,
+) -> Response {
+    let ttl = request.ttl_seconds.map(Duration::from_secs);
+    match state.api_tokens.create(&request.name, request.scope, ttl) {
+        Ok(created) => {
+            tracing::info!(
+                "Created API token {} with scope {:?}",
+                created.token.name,
+                created.token.scope
+            );
+            json_api_response_with_headers(token_resource(&created.token, created.clone()))
+        }
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::BAD_REQUEST,
+            "Token Not Created".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+/// Revoke a token by ID or name; requests with it fail from now on
+pub async fn revoke_token(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.api_tokens.revoke(&id) {
+        Ok(Some(token)) => {
+            tracing::info!("Revoked API token {}", token.name);
+            json_api_response_with_headers(token_resource(&token, token.clone()))
+        }
+        Ok(None) => json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Token Not Found".to_string(),
+            format!("No API token with ID or name {}", id),
+        ),
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Token Not Revoked".to_string(),
+            e.to_string(),
+        ),
+    }
+}
+
+/// Create a token for a share link that opens only this session, read-only
+/// unless the request asks for `interactive`
+pub async fn share_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(request): Json<ShareSessionRequest>,
+) -> Response {
+    // The server resolves names, the token is for the session's ID
+    let Some(session) = state.session_manager.get_session(&id).await else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No session with ID or name {}", id),
+        );
+    };
+    let ttl = request.ttl_seconds.map(Duration::from_secs);
+    if let Some(Err(e)) = ttl.map(ApiTokenStore::check_ttl) {
+        return json_api_error_response_with_headers(
+            StatusCode::BAD_REQUEST,
+            "Session Not Shared".to_string(),
+            e.to_string(),
+        );
+    }
+    match state
+        .api_tokens
+        .share(&session.id, request.interactive, ttl)
+    {
+        Ok(created) => {
+            tracing::info!(
+                "Shared session {} as {} ({:?})",
+                session.id,
+                created.token.name,
+                created.token.scope
+            );
+            json_api_response_with_headers(token_resource(&created.token, created.clone()))
+        }
+        Err(e) => json_api_error_response_with_headers(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Session Not Shared".to_string(),
+            e.to_string(),
+        ),
+    }
+}
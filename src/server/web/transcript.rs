@@ -0,0 +1,169 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use std::path::PathBuf;
+
+use super::scrollback::DEFAULT_SCROLLBACK_LINES;
+use super::types::{AppState, ExportQuery};
+use crate::core::agent_adapter::AgentAdapter;
+use crate::core::render::{render_conversation, ExportSource};
+use crate::core::transcript::{SessionTranscript, TranscriptStats};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Messages and token/cost totals of the session's conversation, for Claude,
+/// Gemini and agents with an `[agents.<name>.transcript]` spec
+pub async fn get_session_transcript(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Response {
+    let Some(agent) = state
+        .session_manager
+        .get_session(&session_id)
+        .await
+        .and_then(|session| session.attributes)
+        .map(|attributes| attributes.agent)
+    else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No running session with ID {}", session_id),
+        );
+    };
+    let Some(adapter) = state.session_manager.agent_adapter(&agent) else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "No Transcripts".to_string(),
+            format!(
+                "codemux doesn't know where {} keeps its transcripts; describe them in [agents.{}.transcript]",
+                agent, agent
+            ),
+        );
+    };
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "transcript".to_string(),
+        id: session_id.clone(),
+        attributes: Some(read_transcript(&state, &session_id, adapter).await),
+        relationships: None,
+    })
+}
+
+/// The session's conversation as a Markdown, JSON or HTML document: the
+/// agent's transcript when codemux can read one, otherwise the terminal's scrollback
+pub async fn export_session_conversation(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Response {
+    let Some(session) = state.session_manager.get_session(&session_id).await else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No session with ID {}", session_id),
+        );
+    };
+    let session_id = session.id;
+    let agent = session
+        .attributes
+        .map(|attributes| attributes.agent)
+        .unwrap_or_default();
+
+    let transcript = match state.session_manager.agent_adapter(&agent) {
+        Some(adapter) => Some(read_transcript(&state, &session_id, adapter).await),
+        None => None,
+    };
+    let document = match transcript.filter(|transcript| !transcript.messages.is_empty()) {
+        Some(transcript) => render_conversation(
+            query.format,
+            &session_id,
+            &agent,
+            &ExportSource::Transcript(&transcript),
+        ),
+        None => {
+            let Some(channels) = state
+                .session_manager
+                .get_session_channels(&session_id)
+                .await
+            else {
+                return json_api_error_response_with_headers(
+                    StatusCode::NOT_FOUND,
+                    "No Conversation".to_string(),
+                    format!(
+                        "Session {} isn't running and codemux can't read a transcript of it",
+                        session_id
+                    ),
+                );
+            };
+            match channels.request_scrollback(DEFAULT_SCROLLBACK_LINES).await {
+                Ok(lines) => render_conversation(
+                    query.format,
+                    &session_id,
+                    &agent,
+                    &ExportSource::Terminal(&lines),
+                ),
+                Err(e) => {
+                    return json_api_error_response_with_headers(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "Export Failed".to_string(),
+                        e.to_string(),
+                    )
+                }
+            }
+        }
+    };
+
+    let disposition = format!(
+        "inline; filename=\"{}.{}\"",
+        session_id,
+        query.format.extension()
+    );
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                query.format.content_type().to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        document,
+    )
+        .into_response()
+}
+
+/// The session's conversation as its agent's adapter reads it, empty until
+/// the agent wrote a transcript
+pub(super) async fn read_transcript(
+    state: &AppState,
+    session_id: &str,
+    adapter: Box<dyn AgentAdapter>,
+) -> SessionTranscript {
+    let project = super::git::get_session_working_dir(session_id, state)
+        .await
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let home = PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string()));
+
+    let id = session_id.to_string();
+    let transcript = tokio::task::spawn_blocking(move || {
+        let path = adapter.session_transcript(&home, &project, &id)?;
+        let content = std::fs::read_to_string(&path).ok()?;
+        let messages = adapter.messages(&adapter.parse_transcript(&content));
+        Some((path, messages))
+    })
+    .await
+    .ok()
+    .flatten();
+
+    let (path, messages) = match transcript {
+        Some((path, messages)) => (Some(path.to_string_lossy().into_owned()), messages),
+        None => (None, Vec::new()),
+    };
+    SessionTranscript {
+        path,
+        stats: TranscriptStats::of(&messages),
+        messages,
+    }
+}
@@ -1,47 +1,313 @@
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+use utoipa::{IntoParams, ToSchema};
 
+use crate::core::api_tokens::ApiTokenStore;
+use crate::core::json_api::SessionResource;
+use crate::core::launch::LaunchOptions;
+use crate::core::oidc::OidcConfig;
+use crate::core::pty_session::{GridUpdateMessage, KeyEvent};
+use crate::core::render::ExportFormat;
+use crate::core::selection::SelectionShape;
+use crate::core::session::{DiffStats, ProjectDefaults};
+use crate::core::transcript::SessionTranscript;
+use crate::core::view_quality::{ColorDepth, ViewQuality};
+use crate::core::ProtocolCapability;
 use crate::server::manager::SessionManagerHandle;
+use crate::server::search::SearchIndex;
+use crate::server::web::oidc::OidcLogin;
 
 #[derive(Clone)]
 pub struct AppState {
     pub session_manager: SessionManagerHandle,
+    /// Path prefix the routes are served under, such as `/codemux`; empty at the root
+    pub base_path: String,
+    /// Token the API and WebSockets require, see `auth`; none when unset
+    pub auth_token: Option<String>,
+    /// Sign-in through an identity provider, accepted besides the token
+    pub oidc: Option<Arc<OidcLogin>>,
+    /// Scoped tokens accepted besides the server's, see `core::api_tokens`
+    pub api_tokens: Arc<ApiTokenStore>,
+    /// Transcripts read by earlier searches
+    pub search_index: Arc<SearchIndex>,
+    /// When the server started, for the uptime `/healthz` reports
+    pub started_at: Instant,
 }
 
-#[derive(Deserialize)]
+impl AppState {
+    pub fn new(session_manager: SessionManagerHandle) -> Self {
+        let api_tokens = Arc::new(ApiTokenStore::open(session_manager.data_dir()));
+        Self {
+            session_manager,
+            base_path: String::new(),
+            auth_token: None,
+            oidc: None,
+            api_tokens,
+            search_index: Arc::default(),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Serve under `base_path`; `codemux`, `/codemux` and `/codemux/` are all the same prefix
+    pub fn with_base_path(mut self, base_path: &str) -> Self {
+        let trimmed = base_path.trim_matches('/');
+        self.base_path = if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", trimmed)
+        };
+        self
+    }
+
+    /// Require `token` on every API and WebSocket route
+    pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Also let people signed in through `config`'s provider in, see `oidc`
+    pub fn with_oidc(mut self, config: OidcConfig) -> Self {
+        self.oidc = Some(Arc::new(OidcLogin::new(config)));
+        self
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct CreateSessionRequest {
+    /// Empty or missing for the project's default agent
+    #[serde(default)]
     pub agent: String,
+    #[serde(default)]
     pub args: Vec<String>,
     pub project_id: Option<String>,
     pub path: Option<String>,
+    #[serde(flatten)]
+    pub launch: LaunchOptions,
 }
 
+/// Body of `POST /api/adopt`
 #[derive(Deserialize)]
+pub struct AdoptProcessRequest {
+    pub agent: String,
+    pub pid: u32,
+    /// Directory the process runs in
+    pub path: Option<String>,
+}
+
+/// Query parameters of `DELETE /api/sessions/:id`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteSessionQuery {
+    /// End the session right away instead of after the `[recycle_bin]` grace period
+    #[serde(default)]
+    pub now: bool,
+}
+
+/// Body of `PUT /api/sessions/:id/note`; a missing or blank note clears it
+#[derive(Deserialize, ToSchema)]
+pub struct SetSessionNoteRequest {
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct AddProjectRequest {
     pub name: String,
     pub path: String,
+    /// Register the project as a sub-project of this one (ID, name or `parent/child`)
+    #[serde(default)]
+    pub parent: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+/// Query parameters of `DELETE /api/projects/:id`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteProjectQuery {
+    /// End the project's running sessions instead of leaving them without a project
+    #[serde(default)]
+    pub kill_sessions: bool,
+}
+
+/// Body of `PATCH /api/projects/:id`; fields left out stay as they are
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateProjectRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Replaces the project's defaults; `{}` clears them
+    #[serde(default)]
+    pub defaults: Option<ProjectDefaults>,
+}
+
+#[derive(Deserialize)]
+pub struct ScrollbackQuery {
+    pub lines: Option<usize>,
+}
+
+/// Query parameters of `GET /api/sessions/:id/export`
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Query parameters of `GET /api/sessions/:id/screen/text`; missing corners
+/// are the screen's
+#[derive(Deserialize)]
+pub struct ScreenTextQuery {
+    pub top: Option<u16>,
+    pub left: Option<u16>,
+    pub bottom: Option<u16>,
+    pub right: Option<u16>,
+    /// The rectangle between the corners (`block`, the default) or the text
+    /// from one to the other (`lines`)
+    pub shape: Option<SelectionShape>,
+}
+
+/// How `GET /api/sessions/:id/screen` returns the screen
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreenFormat {
+    /// A `screen` resource with the rows as text and as cells
+    #[default]
+    Grid,
+    /// The rows as plain text, one per line
+    Text,
+}
+
+/// Query parameters of `GET /api/sessions/:id/screen`
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScreenQuery {
+    #[serde(default)]
+    pub format: ScreenFormat,
+}
+
+/// Body of `POST /api/sessions/:id/input`: text, keys, or text then keys
+#[derive(Deserialize, ToSchema)]
+pub struct SessionInputRequest {
+    /// Typed as-is, so `\r` presses Enter
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Send `text` as a paste, bracketed if the agent asked for that
+    #[serde(default)]
+    pub paste: bool,
+    #[serde(default)]
+    pub keys: Vec<KeyEvent>,
+}
+
+/// Query parameters of `GET /api/digest`
+#[derive(Deserialize)]
+pub struct DigestQuery {
+    /// Hours before now to cover, 24 by default
+    pub hours: Option<u32>,
+}
+
+/// Query parameters of `GET /api/search`
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+    /// Most hits to return, 20 by default
+    pub limit: Option<usize>,
+}
+
+/// Query parameters of `GET /api/sessions/:id/workspace`
+#[derive(Deserialize)]
+pub struct WorkspaceQuery {
+    /// Most recent transcript messages to include, 50 by default
+    pub messages: Option<usize>,
+}
+
+/// Query parameters of the terminal WebSocket
+#[derive(Deserialize)]
+pub struct ConnectQuery {
+    /// Set by clients reconnecting to a session they were attached to
+    #[serde(default)]
+    pub rebind: bool,
+    /// See `ViewQuality`
+    pub max_fps: Option<u32>,
+    pub keyframe_every: Option<u64>,
+    #[serde(default)]
+    pub colors: ColorDepth,
+    /// Comma-separated `ProtocolCapability` names, e.g. `batch`
+    pub capabilities: Option<String>,
+    /// Watch without typing: input is refused and the PTY keeps its size
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+impl ConnectQuery {
+    pub fn quality(&self) -> ViewQuality {
+        ViewQuality {
+            max_fps: self.max_fps,
+            keyframe_every: self.keyframe_every,
+            colors: self.colors,
+        }
+    }
+
+    /// Capabilities the client asked for that this server supports, `None`
+    /// if it didn't ask
+    pub fn capabilities(&self) -> Option<Vec<ProtocolCapability>> {
+        self.capabilities
+            .as_deref()
+            .map(ProtocolCapability::parse_list)
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GitFileStatus {
     pub path: String,
     pub status: String,
+    /// Path before a rename
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
     pub additions: Option<u32>,
     pub deletions: Option<u32>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GitStatus {
+    /// Every changed file once, with its staged change if it has one
     pub files: Vec<GitFileStatus>,
     pub branch: Option<String>,
     pub clean: bool,
+    /// Changes in the index, what `git commit` would commit
+    pub staged_files: Vec<GitFileStatus>,
+    /// Changes in the working tree that aren't staged
+    pub unstaged_files: Vec<GitFileStatus>,
+    pub untracked_files: Vec<GitFileStatus>,
+}
+
+/// Query parameters of the git diff endpoints
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GitDiffQuery {
+    /// Only staged changes (`true`) or only unstaged ones (`false`); both
+    /// when missing, i.e. everything since the last commit
+    pub staged: Option<bool>,
 }
 
+/// What the web UI's session page shows when it opens, in one response
 #[derive(Debug, Serialize)]
+pub struct SessionWorkspace {
+    pub session: SessionResource,
+    /// The screen as a keyframe
+    pub keyframe: Option<GridUpdateMessage>,
+    /// The last messages of the conversation, with stats of all of it; `None`
+    /// for agents codemux can't read transcripts of
+    pub transcript: Option<SessionTranscript>,
+    /// `None` outside a git repository
+    pub git_status: Option<GitStatus>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GitDiff {
     pub files: Vec<GitFileDiff>,
+    pub summary: DiffStats,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GitFileDiff {
     pub path: String,
     pub old_path: Option<String>,
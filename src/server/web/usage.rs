@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::openapi::ResourceDocument;
+use super::transcript::read_transcript;
+use super::types::AppState;
+use crate::core::transcript::{SessionUsage, TranscriptStats, UsageSummary};
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+use crate::core::{JsonApiErrorDocument, JsonApiResource, SessionResource};
+
+/// Tokens and cost the session's agent has used so far, as its transcript reports them
+#[utoipa::path(
+    get,
+    path = "/api/sessions/{id}/usage",
+    tag = "usage",
+    params(("id" = String, Path, description = "Session ID or name")),
+    responses(
+        (status = 200, description = "A `usage` resource", body = ResourceDocument<SessionUsage>),
+        (status = 404, description = "No running session", body = JsonApiErrorDocument),
+    )
+)]
+pub async fn get_session_usage(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+) -> Response {
+    let Some(session) = state.session_manager.get_session(&session_id).await else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("No running session with ID {}", session_id),
+        );
+    };
+    let usage = session_usage(&state, session).await;
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "usage".to_string(),
+        id: usage.session_id.clone(),
+        attributes: Some(usage),
+        relationships: None,
+    })
+}
+
+/// Usage of every running session, totalled per project and overall
+#[utoipa::path(
+    get,
+    path = "/api/usage",
+    tag = "usage",
+    responses(
+        (status = 200, description = "A `usage-summary` resource", body = ResourceDocument<UsageSummary>),
+    )
+)]
+pub async fn get_usage(State(state): State<AppState>) -> Response {
+    let mut sessions = Vec::new();
+    for session in state.session_manager.list_sessions().await {
+        sessions.push(session_usage(&state, session).await);
+    }
+    let projects = state.session_manager.list_projects().await;
+    let summary = UsageSummary::of(sessions, |id| {
+        projects
+            .iter()
+            .find(|project| project.id == id)
+            .and_then(|project| project.attributes.as_ref())
+            .map(|attributes| attributes.name.clone())
+    });
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "usage-summary".to_string(),
+        id: "all".to_string(),
+        attributes: Some(summary),
+        relationships: None,
+    })
+}
+
+/// The session's usage, zero for agents codemux can't read transcripts of
+async fn session_usage(state: &AppState, session: SessionResource) -> SessionUsage {
+    let (agent, project) = session
+        .attributes
+        .map(|attributes| (attributes.agent, attributes.project))
+        .unwrap_or_default();
+    let usage = match state.session_manager.agent_adapter(&agent) {
+        Some(adapter) => read_transcript(state, &session.id, adapter).await.stats,
+        None => TranscriptStats::default(),
+    };
+    SessionUsage {
+        session_id: session.id,
+        agent,
+        project,
+        usage,
+    }
+}
@@ -1,22 +1,57 @@
 use axum::{
-    extract::{ws::WebSocketUpgrade, Path, State},
+    extract::{ws::WebSocketUpgrade, Path, Query, State},
     response::IntoResponse,
+    Extension,
 };
 
-use super::types::AppState;
-use crate::core::{ClientMessage, ServerMessage};
+use super::types::{AppState, ConnectQuery};
+use crate::core::accessible::{AccessibleEvent, AnnouncementKind, Linearizer};
+use crate::core::api_tokens::{ApiToken, TokenScope};
+use crate::core::frame_encoding::FrameEncoding;
+use crate::core::input_control::InputControl;
+use crate::core::input_queue::{InputQueue, TrySendError};
+use crate::core::message_batch::MessageBatch;
+use crate::core::view_quality::{UpdateCoalescer, ViewQuality};
+use crate::core::viewport::ViewportGuard;
+use crate::core::{
+    ClientMessage, ProtocolCapability, PtyChannels, RawClientMessage, ServerMessage,
+};
+use crate::server::storage::SessionRecord;
+use crate::utils::PromptType;
+use tracing::Instrument;
 
 pub async fn websocket_handler(
     Path(session_id): Path<String>,
+    Query(query): Query<ConnectQuery>,
+    api_token: Option<Extension<ApiToken>>,
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, session_id, state))
+    let quality = query.quality();
+    let capabilities = query.capabilities();
+    // `read` tokens, such as those of read-only share links, only watch
+    let read_only = query.read_only
+        || api_token.is_some_and(|Extension(token)| token.scope < TokenScope::SessionsWrite);
+    ws.on_upgrade(move |socket| {
+        handle_socket(
+            socket,
+            session_id,
+            query.rebind,
+            read_only,
+            quality,
+            capabilities,
+            state,
+        )
+    })
 }
 
 async fn handle_socket(
     mut socket: axum::extract::ws::WebSocket,
     session_id: String,
+    rebind: bool,
+    read_only: bool,
+    quality: ViewQuality,
+    capabilities: Option<Vec<ProtocolCapability>>,
     state: AppState,
 ) {
     use axum::extract::ws::Message;
@@ -27,83 +62,38 @@ async fn handle_socket(
     );
 
     // Get PTY channels from session manager or resume the session
-    tracing::debug!("WebSocket requesting channels for session: {}", session_id);
-    let pty_channels = if let Some(channels) = state
-        .session_manager
-        .get_session_channels(&session_id)
-        .await
-    {
-        tracing::debug!(
-            "WebSocket found active channels for session: {}",
-            session_id
-        );
-        channels
-    } else {
-        tracing::info!(
-            "WebSocket: No active session found for {}, attempting to resume...",
-            session_id
-        );
+    let Some((pty_channels, resumed)) = resolve_session_channels(&session_id, rebind, &state).await
+    else {
+        return;
+    };
+    remember_attach(&state, &session_id);
 
-        // Try to get session info to see if it exists but is inactive
-        if let Some(session_info) = state.session_manager.get_session(&session_id).await {
-            tracing::info!(
-                "WebSocket: Found inactive session {}, resuming...",
-                session_id
-            );
+    // Confirm the capabilities before anything that depends on them is sent
+    let mut batch = MessageBatch::new(
+        capabilities
+            .as_ref()
+            .is_some_and(|capabilities| capabilities.contains(&ProtocolCapability::Batch)),
+    );
+    let encoding = FrameEncoding::new(capabilities.as_deref().unwrap_or_default());
+    if let Some(capabilities) = capabilities {
+        let confirmation = ServerMessage::Capabilities { capabilities };
+        if let Ok(confirmation_str) = serde_json::to_string(&confirmation) {
+            if socket.send(Message::Text(confirmation_str)).await.is_err() {
+                return;
+            }
+        }
+    }
 
-            // Resume the session by creating a new PTY session with the same ID
-            let session_attrs = session_info.attributes.as_ref().ok_or_else(|| {
-                tracing::error!("Session {} missing attributes", session_id);
-            });
-            if let Ok(attrs) = session_attrs {
-                match state
-                    .session_manager
-                    .resume_session(
-                        session_id.clone(),
-                        attrs.agent.clone(),
-                        vec![], // Resume with empty args
-                        attrs.project.clone(),
-                    )
-                    .await
-                {
-                    Ok(_resumed_session) => {
-                        tracing::info!("WebSocket: Successfully resumed session {}", session_id);
-                        // Get the channels for the resumed session
-                        if let Some(channels) = state
-                            .session_manager
-                            .get_session_channels(&session_id)
-                            .await
-                        {
-                            channels
-                        } else {
-                            tracing::error!(
-                                "WebSocket: Failed to get channels for resumed session {}",
-                                session_id
-                            );
-                            return;
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "WebSocket: Failed to resume session {}: {}",
-                            session_id,
-                            e
-                        );
-                        return;
-                    }
-                }
-            } else {
-                tracing::error!("WebSocket: Session {} missing attributes", session_id);
+    // A rebinding client learns whether to show that the server restarted;
+    // the keyframe below replaces whatever screen it had
+    if rebind {
+        let rebound = ServerMessage::Rebound { restarted: resumed };
+        if let Ok(rebound_str) = serde_json::to_string(&rebound) {
+            if socket.send(Message::Text(rebound_str)).await.is_err() {
                 return;
             }
-        } else {
-            tracing::error!(
-                "WebSocket: Session {} not found - may have been deleted or never existed",
-                session_id
-            );
-            return;
         }
-    };
+    }
 
     // Send initial connection message
     let session_short = if session_id.len() >= 8 {
@@ -119,7 +109,12 @@ async fn handle_socket(
         .into_bytes(),
         timestamp: std::time::SystemTime::now(),
     };
-    if let Ok(welcome_str) = serde_json::to_string(&welcome_msg) {
+    if encoding.is_binary(&welcome_msg) {
+        if !send_encoded(&mut socket, &mut batch, &encoding, &welcome_msg).await {
+            tracing::error!("Failed to send welcome message via WebSocket");
+            return;
+        }
+    } else if let Ok(welcome_str) = serde_json::to_string(&welcome_msg) {
         tracing::trace!("WebSocket sending welcome message: {}", welcome_str);
         if socket.send(Message::Text(welcome_str)).await.is_err() {
             tracing::error!("Failed to send welcome message via WebSocket");
@@ -152,15 +147,73 @@ async fn handle_socket(
     let mut pty_output_rx = pty_channels.output_tx.subscribe();
     tracing::debug!("Subscribed to PTY output channel");
 
+    // Alerts, e.g. from the deletion guard
+    let mut alert_rx = pty_channels.alert_tx.subscribe();
+
+    // The session doesn't count as idle while someone watches it
+    let _attached = pty_channels.idle.connect();
+
+    // Each connection is its own client when the session arbitrates input
+    let client_id = format!("web-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    // Grid updates are cropped to the size this client reports, see `viewport`;
+    // a read-only client's size never counts for the PTY's
+    let mut viewport = if read_only {
+        pty_channels.viewports.watch(&client_id)
+    } else {
+        pty_channels
+            .viewports
+            .join(&client_id, pty_channels.control_tx.clone())
+    };
+    let mut control_rx = pty_channels.input_control.subscribe();
+    let control_msg =
+        input_control_message(pty_channels.input_control.state(), &client_id, read_only);
+    if let Ok(control_str) = serde_json::to_string(&control_msg) {
+        if socket.send(Message::Text(control_str)).await.is_err() {
+            return;
+        }
+    }
+
     // Clone input channel for sending to PTY
     let pty_input_tx = pty_channels.input_tx.clone();
+    let mut ack_rx = pty_input_tx.subscribe_acks();
+    let mut lag_rx = pty_input_tx.subscribe_lag();
+    if *lag_rx.borrow_and_update() {
+        if let Ok(lag_str) = serde_json::to_string(&ServerMessage::InputLag { lagging: true }) {
+            if socket.send(Message::Text(lag_str)).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    // Told once the agent exits, which ends the connection
+    let mut exit_rx = pty_channels.exit_tx.subscribe();
+    exit_rx.mark_changed();
+
+    // A client connecting while the agent waits for approval learns right away
+    let mut prompt_rx = pty_channels.prompt_tx.subscribe();
+    let waiting = prompt_rx.borrow_and_update().clone();
+    if let Some(prompt) = waiting {
+        if let Ok(prompt_str) = serde_json::to_string(&prompt_message(Some(prompt))) {
+            if socket.send(Message::Text(prompt_str)).await.is_err() {
+                return;
+            }
+        }
+    }
 
     // Request keyframe for new client (so they get current terminal state immediately)
     match pty_channels.request_keyframe().await {
-        Ok(keyframe) => {
+        Ok(mut keyframe) => {
             tracing::debug!("Received keyframe for new WebSocket client");
+            viewport.fit(&mut keyframe);
+            quality.colors.apply(&mut keyframe);
+            encoding.compact(&mut keyframe);
             let keyframe_ws_msg = ServerMessage::GridUpdate { update: keyframe };
-            if let Ok(keyframe_str) = serde_json::to_string(&keyframe_ws_msg) {
+            if encoding.is_binary(&keyframe_ws_msg) {
+                if !send_encoded(&mut socket, &mut batch, &encoding, &keyframe_ws_msg).await {
+                    tracing::error!("Failed to send initial keyframe to new WebSocket client");
+                    return;
+                }
+            } else if let Ok(keyframe_str) = serde_json::to_string(&keyframe_ws_msg) {
                 // Test that we can deserialize what we're about to send
                 match serde_json::from_str::<ServerMessage>(&keyframe_str) {
                     Ok(_) => {
@@ -187,15 +240,78 @@ async fn handle_socket(
         }
     }
 
+    // Clients that asked for fewer updates get what piled up on every flush tick
+    let mut held_back = UpdateCoalescer::default();
+    let mut flush_timer = quality.flush_interval().map(|period| {
+        let mut timer = tokio::time::interval(period);
+        timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        timer
+    });
+
     // Main WebSocket handling loop
     loop {
         tokio::select! {
+            // Send the messages batched since the batch was started
+            _ = until(batch.deadline()) => {
+                if let Some(frame) = batch.take() {
+                    if socket.send(Message::Text(frame)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            // Send the updates held back since the last tick
+            _ = tick(&mut flush_timer) => {
+                if !held_back.has_pending() {
+                    continue;
+                }
+                let mut update = if quality.keyframe_every.is_some() {
+                    held_back.take();
+                    match pty_channels.request_keyframe().await {
+                        Ok(keyframe) => keyframe,
+                        Err(e) => {
+                            tracing::warn!("Failed to request keyframe for session {}: {}", session_id, e);
+                            continue;
+                        }
+                    }
+                } else {
+                    match held_back.take() {
+                        Some(update) => update,
+                        None => continue,
+                    }
+                };
+                viewport.fit(&mut update);
+                quality.colors.apply(&mut update);
+                encoding.compact(&mut update);
+                let ws_msg = ServerMessage::GridUpdate { update };
+                if encoding.is_binary(&ws_msg) {
+                    if !send_encoded(&mut socket, &mut batch, &encoding, &ws_msg).await {
+                        tracing::error!("Failed to send grid update via WebSocket");
+                        break;
+                    }
+                } else if let Ok(grid_msg) = serde_json::to_string(&ws_msg) {
+                    if !send_batched(&mut socket, &mut batch, grid_msg).await {
+                        tracing::error!("Failed to send grid update via WebSocket");
+                        break;
+                    }
+                }
+            }
             // Forward grid updates to WebSocket (primary channel)
             grid_update = grid_rx.recv() => {
                 match grid_update {
-                    Ok(update) => {
+                    Ok(update) if flush_timer.is_some() => held_back.push(update),
+                    Ok(mut update) => {
+                        viewport.fit(&mut update);
+                        quality.colors.apply(&mut update);
+                        encoding.compact(&mut update);
+                        let send_span = tracing::info_span!("ws.send", session_id = %session_id);
+                        pty_channels.trace.continue_update(&send_span);
                         let ws_msg = ServerMessage::GridUpdate { update };
-                        if let Ok(grid_msg) = serde_json::to_string(&ws_msg) {
+                        if encoding.is_binary(&ws_msg) {
+                            if !send_encoded(&mut socket, &mut batch, &encoding, &ws_msg).instrument(send_span).await {
+                                tracing::error!("Failed to send grid update via WebSocket");
+                                break;
+                            }
+                        } else if let Ok(grid_msg) = serde_json::to_string(&ws_msg) {
                             // Test that we can deserialize what we're about to send
                             match serde_json::from_str::<ServerMessage>(&grid_msg) {
                                 Ok(_) => {
@@ -206,7 +322,7 @@ async fn handle_socket(
                                     tracing::error!("Message content: {}", grid_msg);
                                 }
                             }
-                            if socket.send(Message::Text(grid_msg)).await.is_err() {
+                            if !send_batched(&mut socket, &mut batch, grid_msg).instrument(send_span).await {
                                 tracing::error!("Failed to send grid update via WebSocket");
                                 break;
                             }
@@ -243,12 +359,21 @@ async fn handle_socket(
             size_update = size_rx.recv() => {
                 match size_update {
                     Ok(size) => {
-                        let ws_msg = ServerMessage::PtySize { rows: size.rows, cols: size.cols };
+                        let cropped = viewport.crops();
+                        viewport.pty_resized(size.rows, size.cols);
+                        let (rows, cols) = viewport.visible(size.rows, size.cols);
+                        let ws_msg = ServerMessage::PtySize { rows, cols };
                         if let Ok(size_msg_str) = serde_json::to_string(&ws_msg) {
-                            if socket.send(Message::Text(size_msg_str)).await.is_err() {
+                            if !send_batched(&mut socket, &mut batch, size_msg_str).await {
                                 break;
                             }
                         }
+                        // The part of the screen this client sees moved
+                        if (cropped || viewport.crops())
+                            && !send_keyframe(&mut socket, &mut batch, &pty_channels, &mut viewport, quality, &encoding).await
+                        {
+                            break;
+                        }
                     }
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                         tracing::info!("PTY size channel closed");
@@ -260,25 +385,101 @@ async fn handle_socket(
                     }
                 }
             }
+            // Forward alerts to WebSocket
+            Ok(message) = alert_rx.recv() => {
+                if let Ok(alert_str) = serde_json::to_string(&ServerMessage::Alert { message }) {
+                    if !send_batched(&mut socket, &mut batch, alert_str).await {
+                        break;
+                    }
+                }
+            }
+            // Tell the client when write control changes hands
+            Ok(control) = control_rx.recv() => {
+                let control_msg = input_control_message(control, &client_id, read_only);
+                if let Ok(control_str) = serde_json::to_string(&control_msg) {
+                    if !send_batched(&mut socket, &mut batch, control_str).await {
+                        break;
+                    }
+                }
+            }
+            // Tell the client which of its input got written
+            Ok(ack) = ack_rx.recv() => {
+                if ack.client_id == client_id {
+                    let ack_msg = ServerMessage::InputAck { seq: ack.seq, written: ack.written };
+                    if let Ok(ack_str) = serde_json::to_string(&ack_msg) {
+                        if !send_batched(&mut socket, &mut batch, ack_str).await {
+                            break;
+                        }
+                    }
+                }
+            }
+            // Tell the client when input starts or stops lagging
+            Ok(()) = lag_rx.changed() => {
+                let lagging = *lag_rx.borrow_and_update();
+                if let Ok(lag_str) = serde_json::to_string(&ServerMessage::InputLag { lagging }) {
+                    if !send_batched(&mut socket, &mut batch, lag_str).await {
+                        break;
+                    }
+                }
+            }
+            // Tell the client when the agent starts or stops waiting for approval
+            Ok(()) = prompt_rx.changed() => {
+                let prompt = prompt_rx.borrow_and_update().clone();
+                if let Ok(prompt_str) = serde_json::to_string(&prompt_message(prompt)) {
+                    if !send_batched(&mut socket, &mut batch, prompt_str).await {
+                        break;
+                    }
+                }
+            }
+            // Tell the client the agent exited, and let it go
+            Ok(()) = exit_rx.changed() => {
+                let Some(code) = *exit_rx.borrow_and_update() else {
+                    continue;
+                };
+                if let Ok(exit_str) = serde_json::to_string(&ServerMessage::Exited { code }) {
+                    if send_batched(&mut socket, &mut batch, exit_str).await {
+                        if let Some(frame) = batch.take() {
+                            let _ = socket.send(Message::Text(frame)).await;
+                        }
+                    }
+                }
+                break;
+            }
             // Handle WebSocket messages from client
             ws_msg = socket.recv() => {
                 match ws_msg {
                     Some(Ok(Message::Text(text))) => {
                         tracing::trace!("WebSocket received message: {} chars", text.len());
                         if let Ok(client_msg) = serde_json::from_str::<ClientMessage>(&text) {
+                            let input_span = tracing::info_span!("ws.input", session_id = %session_id);
                             match client_msg {
-                                ClientMessage::Key { code, modifiers } => {
+                                ClientMessage::Key { .. }
+                                | ClientMessage::Scroll { .. }
+                                | ClientMessage::Input { .. }
+                                | ClientMessage::Paste { .. }
+                                | ClientMessage::RequestControl
+                                    if read_only =>
+                                {
+                                    tracing::debug!("Refusing input from read-only client {}", client_id);
+                                    if let Ok(error_str) = serde_json::to_string(&read_only_refused()) {
+                                        if !send_batched(&mut socket, &mut batch, error_str).await {
+                                            break;
+                                        }
+                                    }
+                                }
+                                ClientMessage::Key { code, modifiers, seq } => {
                                     tracing::trace!("WebSocket received key event: {:?} with modifiers {:?}", code, modifiers);
                                     // Convert to PtyInputMessage with key event
                                     let key_event = crate::core::pty_session::KeyEvent { code, modifiers };
                                     let input_msg = crate::core::pty_session::PtyInputMessage {
                                         input: crate::core::pty_session::PtyInput::Key {
                                             event: key_event,
-                                            client_id: "web".to_string(),
+                                            client_id: client_id.clone(),
                                         },
+                                        seq,
+                                        span: input_span.clone(),
                                     };
-                                    if pty_input_tx.send(input_msg).is_err() {
-                                        tracing::error!("Failed to send key input to PTY");
+                                    if !queue_input(&mut socket, &mut batch, &pty_input_tx, input_msg).await {
                                         break;
                                     }
                                 }
@@ -289,22 +490,66 @@ async fn handle_socket(
                                         input: crate::core::pty_session::PtyInput::Scroll {
                                             direction,
                                             lines,
-                                            client_id: "web".to_string(),
+                                            client_id: client_id.clone(),
                                         },
+                                        seq: None,
+                                        span: input_span.clone(),
                                     };
-                                    if pty_input_tx.send(input_msg).is_err() {
-                                        tracing::error!("Failed to send scroll input to PTY");
+                                    if !queue_input(&mut socket, &mut batch, &pty_input_tx, input_msg).await {
                                         break;
                                     }
                                 }
-                                ClientMessage::Resize { rows, cols } => {
+                                ClientMessage::Input { data, seq } => {
+                                    tracing::trace!("WebSocket received raw input: {} bytes", data.len());
+                                    let input_msg = crate::core::pty_session::PtyInputMessage {
+                                        input: crate::core::pty_session::PtyInput::Raw {
+                                            data,
+                                            client_id: client_id.clone(),
+                                        },
+                                        seq,
+                                        span: input_span.clone(),
+                                    };
+                                    if !queue_input(&mut socket, &mut batch, &pty_input_tx, input_msg).await {
+                                        break;
+                                    }
+                                }
+                                ClientMessage::Paste { text, seq } => {
+                                    tracing::trace!("WebSocket received paste: {} bytes", text.len());
+                                    let input_msg = crate::core::pty_session::PtyInputMessage {
+                                        input: crate::core::pty_session::PtyInput::Paste {
+                                            text,
+                                            client_id: client_id.clone(),
+                                        },
+                                        seq,
+                                        span: input_span.clone(),
+                                    };
+                                    if !queue_input(&mut socket, &mut batch, &pty_input_tx, input_msg).await {
+                                        break;
+                                    }
+                                }
+                                ClientMessage::RequestControl => {
+                                    if let Err(e) = pty_channels.input_control.request(&client_id) {
+                                        let error = ServerMessage::Error { message: e.to_string() };
+                                        if let Ok(error_str) = serde_json::to_string(&error) {
+                                            if !send_batched(&mut socket, &mut batch, error_str).await {
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
+                                ClientMessage::ReleaseControl => {
+                                    pty_channels.input_control.release(&client_id);
+                                }
+                                ClientMessage::Resize { rows, cols, driver } => {
                                     tracing::trace!("WebSocket received resize: {}x{}", cols, rows);
-                                    // Send resize control message to PTY
-                                    let resize_msg = crate::core::pty_session::PtyControlMessage::Resize { rows, cols };
-                                    if let Err(e) = pty_channels.control_tx.send(resize_msg) {
-                                        tracing::warn!("Failed to send resize to PTY session {}: {}", session_id, e);
-                                    } else {
-                                        tracing::debug!("Sent resize {}x{} to PTY session {}", cols, rows, session_id);
+                                    let cropped = viewport.crops();
+                                    if viewport.resize(rows, cols, driver) {
+                                        tracing::debug!("Resizing PTY session {} for client {}", session_id, client_id);
+                                    } else if (cropped || viewport.crops())
+                                        // The PTY keeps its size, so only this client's view changes
+                                        && !send_keyframe(&mut socket, &mut batch, &pty_channels, &mut viewport, quality, &encoding).await
+                                    {
+                                        break;
                                     }
                                 }
                             }
@@ -331,5 +576,514 @@ async fn handle_socket(
         }
     }
 
+    pty_channels.input_control.release(&client_id);
     tracing::info!("WebSocket connection closed for session: {}", session_id);
 }
+
+/// Send a message now, or with its batch when the client batches
+async fn send_batched(
+    socket: &mut axum::extract::ws::WebSocket,
+    batch: &mut MessageBatch,
+    message: String,
+) -> bool {
+    match batch.push(message) {
+        Some(frame) => socket
+            .send(axum::extract::ws::Message::Text(frame))
+            .await
+            .is_ok(),
+        None => true,
+    }
+}
+
+/// Send a fresh keyframe, fitted to the client's viewport
+async fn send_keyframe(
+    socket: &mut axum::extract::ws::WebSocket,
+    batch: &mut MessageBatch,
+    pty_channels: &PtyChannels,
+    viewport: &mut ViewportGuard,
+    quality: ViewQuality,
+    encoding: &FrameEncoding,
+) -> bool {
+    let mut keyframe = match pty_channels.request_keyframe().await {
+        Ok(keyframe) => keyframe,
+        Err(e) => {
+            tracing::warn!("Failed to request keyframe: {}", e);
+            return true;
+        }
+    };
+    viewport.fit(&mut keyframe);
+    quality.colors.apply(&mut keyframe);
+    encoding.compact(&mut keyframe);
+    let message = ServerMessage::GridUpdate { update: keyframe };
+    if encoding.is_binary(&message) {
+        return send_encoded(socket, batch, encoding, &message).await;
+    }
+    match serde_json::to_string(&message) {
+        Ok(frame) => send_batched(socket, batch, frame).await,
+        Err(_) => true,
+    }
+}
+
+/// Send a grid update or output as a binary frame, after the batched
+/// messages that came before it
+async fn send_encoded(
+    socket: &mut axum::extract::ws::WebSocket,
+    batch: &mut MessageBatch,
+    encoding: &FrameEncoding,
+    message: &ServerMessage,
+) -> bool {
+    use axum::extract::ws::Message;
+
+    if let Some(frame) = batch.take() {
+        if socket.send(Message::Text(frame)).await.is_err() {
+            return false;
+        }
+    }
+    match encoding.encode(message) {
+        Ok(frame) => socket.send(Message::Binary(frame)).await.is_ok(),
+        Err(e) => {
+            tracing::error!("Failed to encode WebSocket message: {}", e);
+            true
+        }
+    }
+}
+
+/// Queue input from a client; a full queue is reported to the client, false
+/// when the connection should end
+async fn queue_input(
+    socket: &mut axum::extract::ws::WebSocket,
+    batch: &mut MessageBatch,
+    queue: &InputQueue,
+    input_msg: crate::core::pty_session::PtyInputMessage,
+) -> bool {
+    match queue.send(input_msg).await {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => match serde_json::to_string(&input_refused()) {
+            Ok(error_str) => send_batched(socket, batch, error_str).await,
+            Err(_) => true,
+        },
+        Err(TrySendError::Closed(_)) => {
+            tracing::error!("Failed to send input to PTY, the session ended");
+            false
+        }
+    }
+}
+
+/// Told to a client whose input didn't fit in the session's queue
+fn input_refused() -> ServerMessage {
+    ServerMessage::Error {
+        message: "Input dropped: the agent isn't reading what's typed".to_string(),
+    }
+}
+
+/// Told to a read-only client that sent input or asked for control
+fn read_only_refused() -> ServerMessage {
+    ServerMessage::Error {
+        message: "Input refused: this connection is read-only".to_string(),
+    }
+}
+
+/// `input_control` message for the client called `client_id`
+fn input_control_message(control: InputControl, client_id: &str, read_only: bool) -> ServerMessage {
+    ServerMessage::InputControl {
+        can_write: !read_only && control.can_write(client_id),
+        control,
+        client_id: client_id.to_string(),
+    }
+}
+
+fn prompt_message(prompt: Option<PromptType>) -> ServerMessage {
+    match prompt {
+        Some(prompt) => ServerMessage::PromptDetected { prompt },
+        None => ServerMessage::PromptCleared,
+    }
+}
+
+/// Raw PTY WebSocket for xterm.js-style frontends (no grid protocol)
+///
+/// Server → client: binary frames carry PTY output bytes, text frames carry
+/// `ServerMessage` JSON (`pty_size`, `error`). Client → server: binary frames are
+/// written to the PTY as-is, text frames are `RawClientMessage` JSON.
+pub async fn raw_websocket_handler(
+    Path(session_id): Path<String>,
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_raw_socket(socket, session_id, state))
+}
+
+async fn handle_raw_socket(
+    mut socket: axum::extract::ws::WebSocket,
+    session_id: String,
+    state: AppState,
+) {
+    use axum::extract::ws::Message;
+    use tokio::sync::broadcast::error::RecvError;
+
+    tracing::info!(
+        "Raw WebSocket connection established for session: {}",
+        session_id
+    );
+
+    let Some((pty_channels, _)) = resolve_session_channels(&session_id, false, &state).await else {
+        let error = ServerMessage::Error {
+            message: format!("Session {} not found", session_id),
+        };
+        if let Ok(error_str) = serde_json::to_string(&error) {
+            let _ = socket.send(Message::Text(error_str)).await;
+        }
+        return;
+    };
+    remember_attach(&state, &session_id);
+    // Takes write control by typing when the session arbitrates input
+    let client_id = format!("raw-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+    // Raw bytes can't be cropped, but the client's size counts towards the PTY's
+    let mut viewport = pty_channels
+        .viewports
+        .join(&client_id, pty_channels.control_tx.clone());
+
+    // Subscribe before snapshotting so no output falls between the two; output
+    // the snapshot already shows is skipped below
+    let mut output_rx = pty_channels.output_tx.subscribe();
+    let mut size_rx = pty_channels.size_tx.subscribe();
+    let mut alert_rx = pty_channels.alert_tx.subscribe();
+    let _attached = pty_channels.idle.connect();
+
+    // Replay the current screen so the client starts from the same state
+    let mut replayed_seq = 0;
+    match pty_channels.request_screen_state().await {
+        Ok(screen) => {
+            replayed_seq = screen.seq;
+            if socket.send(Message::Binary(screen.data)).await.is_err() {
+                return;
+            }
+        }
+        Err(e) => {
+            tracing::warn!("Failed to get screen state for raw WebSocket client: {}", e);
+        }
+    }
+
+    loop {
+        tokio::select! {
+            output = output_rx.recv() => {
+                match output {
+                    Ok(output_msg) if output_msg.seq <= replayed_seq => {}
+                    Ok(output_msg) => {
+                        // axum's frames own their payload, so this is where the shared bytes get copied
+                        if socket.send(Message::Binary(output_msg.data.to_vec())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Closed) => {
+                        tracing::info!("PTY output channel closed");
+                        break;
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Raw WebSocket lagged behind PTY output, {} messages dropped", skipped);
+                    }
+                }
+            }
+            size_update = size_rx.recv() => {
+                if let Ok(size) = size_update {
+                    let ws_msg = ServerMessage::PtySize { rows: size.rows, cols: size.cols };
+                    if let Ok(size_msg_str) = serde_json::to_string(&ws_msg) {
+                        if socket.send(Message::Text(size_msg_str)).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            Ok(message) = alert_rx.recv() => {
+                if let Ok(alert_str) = serde_json::to_string(&ServerMessage::Alert { message }) {
+                    if socket.send(Message::Text(alert_str)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+            ws_msg = socket.recv() => {
+                let data = match ws_msg {
+                    Some(Ok(Message::Binary(data))) => data,
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str::<RawClientMessage>(&text) {
+                        Ok(RawClientMessage::Input { data }) => data.into_bytes(),
+                        Ok(RawClientMessage::Resize { rows, cols }) => {
+                            viewport.resize(rows, cols, false);
+                            continue;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to parse raw WebSocket message: {}", e);
+                            continue;
+                        }
+                    },
+                    Some(Ok(Message::Ping(data))) => {
+                        if socket.send(Message::Pong(data)).await.is_err() {
+                            break;
+                        }
+                        continue;
+                    }
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => {
+                        tracing::error!("Raw WebSocket error: {}", e);
+                        break;
+                    }
+                    _ => continue,
+                };
+
+                let input_msg = crate::core::pty_session::PtyInputMessage {
+                    input: crate::core::pty_session::PtyInput::Raw {
+                        data,
+                        client_id: client_id.clone(),
+                    },
+                    seq: None,
+                    span: tracing::info_span!("ws.input", session_id = %session_id),
+                };
+                match pty_channels.input_tx.send(input_msg).await {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => {
+                        if let Ok(error_str) = serde_json::to_string(&input_refused()) {
+                            if socket.send(Message::Text(error_str)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(TrySendError::Closed(_)) => {
+                        tracing::error!("Failed to send raw input to PTY");
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    pty_channels.input_control.release(&client_id);
+    tracing::info!(
+        "Raw WebSocket connection closed for session: {}",
+        session_id
+    );
+}
+
+/// Wait for the next tick of `timer`, or forever without one
+async fn tick(timer: &mut Option<tokio::time::Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Wait until `deadline`, or forever without one
+async fn until(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Read-only WebSocket for screen readers
+///
+/// Each text frame is an `AccessibleEvent` JSON object: a finished line of
+/// output, or an announcement such as the agent asking a question.
+pub async fn accessible_websocket_handler(
+    Path(session_id): Path<String>,
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_accessible_socket(socket, session_id, state))
+}
+
+async fn handle_accessible_socket(
+    mut socket: axum::extract::ws::WebSocket,
+    session_id: String,
+    state: AppState,
+) {
+    use axum::extract::ws::Message;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let Some((pty_channels, _)) = resolve_session_channels(&session_id, false, &state).await else {
+        let error = ServerMessage::Error {
+            message: format!("Session {} not found", session_id),
+        };
+        if let Ok(error_str) = serde_json::to_string(&error) {
+            let _ = socket.send(Message::Text(error_str)).await;
+        }
+        return;
+    };
+
+    let mut linearizer = Linearizer::new();
+    let mut grid_rx = pty_channels.grid_tx.subscribe();
+    let _attached = pty_channels.idle.connect();
+
+    // Read out the current screen first so the listener has context
+    if let Ok(keyframe) = pty_channels.request_keyframe().await {
+        if !send_accessible_events(&mut socket, linearizer.apply(&keyframe)).await {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            update = grid_rx.recv() => {
+                let events = match update {
+                    Ok(update) => linearizer.apply(&update),
+                    Err(RecvError::Lagged(_)) => {
+                        // Missed diffs leave the mirror stale; resync from a keyframe
+                        match pty_channels.request_keyframe().await {
+                            Ok(keyframe) => linearizer.apply(&keyframe),
+                            Err(_) => continue,
+                        }
+                    }
+                    Err(RecvError::Closed) => {
+                        let ended = AccessibleEvent::Announcement {
+                            kind: AnnouncementKind::SessionEnded,
+                            text: format!("Session {} ended", session_id),
+                        };
+                        send_accessible_events(&mut socket, vec![ended]).await;
+                        break;
+                    }
+                };
+                if !send_accessible_events(&mut socket, events).await {
+                    break;
+                }
+            }
+            // Input is ignored; pings are answered by axum
+            ws_msg = socket.recv() => {
+                if matches!(ws_msg, Some(Ok(Message::Close(_))) | Some(Err(_)) | None) {
+                    break;
+                }
+            }
+        }
+    }
+
+    tracing::info!(
+        "Accessible WebSocket connection closed for session: {}",
+        session_id
+    );
+}
+
+/// Send events as JSON text frames; returns false once the client is gone
+async fn send_accessible_events(
+    socket: &mut axum::extract::ws::WebSocket,
+    events: Vec<AccessibleEvent>,
+) -> bool {
+    for event in events {
+        let Ok(event_str) = serde_json::to_string(&event) else {
+            continue;
+        };
+        if socket
+            .send(axum::extract::ws::Message::Text(event_str))
+            .await
+            .is_err()
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Add the session to the recent list used by the dashboard and `codemux recent`
+fn remember_attach(state: &AppState, session_id: &str) {
+    let storage = state.session_manager.storage();
+    let session_id = session_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = storage.record_attach(&session_id).await {
+            tracing::warn!("Failed to record attach to {}: {}", session_id, e);
+        }
+    });
+}
+
+/// Look up a session's PTY channels, resuming the session if it exists but is inactive
+///
+/// Rebinding clients were attached before, so their session is also restored
+/// from the session history, which survives server restarts. The flag is true
+/// when the session wasn't running and had to be resumed.
+async fn resolve_session_channels(
+    session_id: &str,
+    rebind: bool,
+    state: &AppState,
+) -> Option<(PtyChannels, bool)> {
+    tracing::debug!("WebSocket requesting channels for session: {}", session_id);
+    if let Some(channels) = state.session_manager.get_session_channels(session_id).await {
+        tracing::debug!(
+            "WebSocket found active channels for session: {}",
+            session_id
+        );
+        return Some((channels, false));
+    }
+
+    tracing::info!(
+        "WebSocket: No active session found for {}, attempting to resume...",
+        session_id
+    );
+
+    // Try to get session info to see if it exists but is inactive
+    let (agent, project) = match state.session_manager.get_session(session_id).await {
+        Some(session_info) => {
+            let Some(attrs) = session_info.attributes else {
+                tracing::error!("WebSocket: Session {} missing attributes", session_id);
+                return None;
+            };
+            (attrs.agent, attrs.project)
+        }
+        None if rebind => match find_in_history(state, session_id).await {
+            Some(record) => (record.agent, record.project),
+            None => {
+                tracing::error!(
+                    "WebSocket: Session {} to rebind is not in the session history",
+                    session_id
+                );
+                return None;
+            }
+        },
+        None => {
+            tracing::error!(
+                "WebSocket: Session {} not found - may have been deleted or never existed",
+                session_id
+            );
+            return None;
+        }
+    };
+
+    tracing::info!(
+        "WebSocket: Found inactive session {}, resuming...",
+        session_id
+    );
+
+    // Resume the session by creating a new PTY session with the same ID
+    match state
+        .session_manager
+        .resume_session(
+            session_id.to_string(),
+            agent,
+            vec![], // Resume with empty args
+            project,
+        )
+        .await
+    {
+        Ok(_resumed_session) => {
+            tracing::info!("WebSocket: Successfully resumed session {}", session_id);
+            // Get the channels for the resumed session
+            let channels = state.session_manager.get_session_channels(session_id).await;
+            if channels.is_none() {
+                tracing::error!(
+                    "WebSocket: Failed to get channels for resumed session {}",
+                    session_id
+                );
+            }
+            channels.map(|channels| (channels, true))
+        }
+        Err(e) => {
+            tracing::error!("WebSocket: Failed to resume session {}: {}", session_id, e);
+            None
+        }
+    }
+}
+
+async fn find_in_history(state: &AppState, session_id: &str) -> Option<SessionRecord> {
+    match state.session_manager.storage().load_sessions().await {
+        Ok(sessions) => sessions.into_iter().find(|s| s.id == session_id),
+        Err(e) => {
+            tracing::warn!("Failed to read the session history: {}", e);
+            None
+        }
+    }
+}
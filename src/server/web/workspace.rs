@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+};
+
+use super::types::{AppState, SessionWorkspace, WorkspaceQuery};
+use crate::core::JsonApiResource;
+use crate::core::{json_api_error_response_with_headers, json_api_response_with_headers};
+
+/// Transcript messages included unless the client asks for another number
+const DEFAULT_MESSAGES: usize = 50;
+
+/// The session with its screen, recent conversation and git status, so the
+/// session page can draw all its panes from one request
+///
+/// The parts are gathered concurrently; one that can't be had is left out
+/// rather than failing the rest.
+pub async fn get_session_workspace(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<WorkspaceQuery>,
+) -> Response {
+    let Some(session) = state.session_manager.get_session(&session_id).await else {
+        return json_api_error_response_with_headers(
+            StatusCode::NOT_FOUND,
+            "Session Not Found".to_string(),
+            format!("Session with id '{}' not found", session_id),
+        );
+    };
+    let agent = session
+        .attributes
+        .as_ref()
+        .map(|attributes| attributes.agent.clone())
+        .unwrap_or_default();
+
+    let keyframe = async {
+        let channels = state
+            .session_manager
+            .get_session_channels(&session_id)
+            .await?;
+        channels
+            .request_keyframe()
+            .await
+            .map_err(|e| tracing::debug!("No keyframe of {} for its workspace: {}", session_id, e))
+            .ok()
+    };
+    let transcript = async {
+        let adapter = state.session_manager.agent_adapter(&agent)?;
+        let mut transcript = super::transcript::read_transcript(&state, &session_id, adapter).await;
+        let keep = query.messages.unwrap_or(DEFAULT_MESSAGES);
+        let skip = transcript.messages.len().saturating_sub(keep);
+        transcript.messages.drain(..skip);
+        Some(transcript)
+    };
+    let git_status = async {
+        let dir = super::git::get_session_working_dir(&session_id, &state).await?;
+        super::git::execute_git_status(&dir).await.ok()
+    };
+    let (keyframe, transcript, git_status) = tokio::join!(keyframe, transcript, git_status);
+
+    json_api_response_with_headers(JsonApiResource::<_, ()> {
+        resource_type: "workspace".to_string(),
+        id: session_id.clone(),
+        attributes: Some(SessionWorkspace {
+            session,
+            keyframe,
+            transcript,
+            git_status,
+        }),
+        relationships: None,
+    })
+}
@@ -3,5 +3,5 @@ pub mod prompt_detector;
 pub mod tui_writer;
 
 pub use path::{canonicalize_path, shorten_path_for_display};
-pub use prompt_detector::*;
+pub use prompt_detector::{PromptDetector, PromptType};
 pub use tui_writer::{LogEntry, LogLevel, TuiWriter};
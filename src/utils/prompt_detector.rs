@@ -1,8 +1,16 @@
+use std::sync::OnceLock;
+
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Lines at the bottom of the screen looked at for a prompt
+pub const PROMPT_LINES: usize = 12;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What an agent is waiting for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
 #[serde(tag = "type")]
+#[ts(export)]
 pub enum PromptType {
     TextInput {
         prompt: String,
@@ -26,44 +34,98 @@ pub enum PromptType {
         prompt: String,
         default: Option<String>,
     },
+    /// An agent asking to run a tool, e.g. Claude's "Do you want to make this
+    /// edit?" with numbered answers
+    Permission {
+        prompt: String,
+        options: Vec<String>,
+        selected: Option<usize>,
+    },
+}
+
+impl PromptType {
+    pub fn prompt(&self) -> &str {
+        match self {
+            PromptType::TextInput { prompt, .. }
+            | PromptType::Confirmation { prompt, .. }
+            | PromptType::MultiSelect { prompt, .. }
+            | PromptType::SingleSelect { prompt, .. }
+            | PromptType::FilePath { prompt, .. }
+            | PromptType::Permission { prompt, .. } => prompt,
+        }
+    }
+
+    /// Whether the agent is stuck until someone approves or declines
+    pub fn needs_approval(&self) -> bool {
+        matches!(
+            self,
+            PromptType::Confirmation { .. } | PromptType::Permission { .. }
+        )
+    }
 }
 
 type PromptPattern = (Regex, fn(&str) -> Option<PromptType>);
 
-pub struct _PromptDetector {
+/// Recognizes prompts in the text at the bottom of an agent's screen
+pub struct PromptDetector {
     patterns: Vec<PromptPattern>,
 }
 
-impl _PromptDetector {
-    pub fn _new() -> Self {
+impl Default for PromptDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PromptDetector {
+    pub fn new() -> Self {
         let patterns = vec![
+            (
+                Regex::new(r"(?im)^[\s│|]*(do you want to|allow) .*\?").unwrap(),
+                detect_permission as fn(&str) -> Option<PromptType>,
+            ),
             (
                 Regex::new(r"(?i)(enter|input|provide|type).*:[\s]*$").unwrap(),
-                _detect_text_input as fn(&str) -> Option<PromptType>,
+                detect_text_input as fn(&str) -> Option<PromptType>,
             ),
             (
-                Regex::new(r"(?i)\[y/n\]|continue\?|proceed\?|confirm\?").unwrap(),
-                _detect_confirmation as fn(&str) -> Option<PromptType>,
+                Regex::new(r"(?i)(\[y/n\]|\(y/n\)|continue\?|proceed\?|confirm\?)[\s]*$").unwrap(),
+                detect_confirmation as fn(&str) -> Option<PromptType>,
             ),
             (
                 Regex::new(r"(?i)select.*:[\s]*$|choose.*:[\s]*$").unwrap(),
-                _detect_selection as fn(&str) -> Option<PromptType>,
+                detect_selection as fn(&str) -> Option<PromptType>,
             ),
             (
                 Regex::new(r"(?i)(path|file|directory|folder).*:[\s]*$").unwrap(),
-                _detect_file_path as fn(&str) -> Option<PromptType>,
+                detect_file_path as fn(&str) -> Option<PromptType>,
             ),
         ];
 
-        _PromptDetector { patterns }
+        PromptDetector { patterns }
+    }
+
+    /// The built-in patterns plus an agent's own approval prompts, which are
+    /// checked first; a match counts as a confirmation of the last line
+    pub fn with_approval_patterns(approvals: &[Regex]) -> Self {
+        let approvals = approvals.iter().map(|pattern| {
+            (
+                pattern.clone(),
+                detect_confirmation as fn(&str) -> Option<PromptType>,
+            )
+        });
+        PromptDetector {
+            patterns: approvals.chain(Self::new().patterns).collect(),
+        }
     }
 
-    pub fn _detect(&self, output: &str) -> Option<PromptType> {
-        let clean_output = _strip_ansi_codes(output);
+    pub fn detect(&self, output: &str) -> Option<PromptType> {
+        let clean_output = strip_ansi_codes(output);
+        let clean_output = clean_output.trim_end();
 
         for (pattern, detector) in &self.patterns {
-            if pattern.is_match(&clean_output) {
-                if let Some(prompt_type) = detector(&clean_output) {
+            if pattern.is_match(clean_output) {
+                if let Some(prompt_type) = detector(clean_output) {
                     return Some(prompt_type);
                 }
             }
@@ -73,34 +135,53 @@ impl _PromptDetector {
     }
 }
 
-fn _strip_ansi_codes(text: &str) -> String {
-    let ansi_regex = Regex::new(r"\x1b\[[0-9;]*[a-zA-Z]").unwrap();
+/// The last `PROMPT_LINES` lines with text of a screen, given its rows from
+/// the bottom up so only those rows need to be read
+pub fn screen_tail(rows_from_bottom: impl Iterator<Item = String>) -> String {
+    let mut lines: Vec<String> = rows_from_bottom
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.trim_end().to_string())
+        .take(PROMPT_LINES)
+        .collect();
+    lines.reverse();
+    lines.join("\n")
+}
+
+fn strip_ansi_codes(text: &str) -> String {
+    static ANSI_RE: OnceLock<Regex> = OnceLock::new();
+    let ansi_regex = ANSI_RE.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*[a-zA-Z]").unwrap());
     ansi_regex.replace_all(text, "").to_string()
 }
 
-fn _detect_text_input(text: &str) -> Option<PromptType> {
+/// Prompts only count as the last thing on screen
+fn last_line(text: &str) -> &str {
+    text.lines().next_back().unwrap_or_default().trim()
+}
+
+fn detect_text_input(text: &str) -> Option<PromptType> {
     Some(PromptType::TextInput {
-        prompt: text.trim().to_string(),
+        prompt: last_line(text).to_string(),
         default: None,
     })
 }
 
-fn _detect_confirmation(text: &str) -> Option<PromptType> {
-    let default = if text.contains("[Y/n]") {
+fn detect_confirmation(text: &str) -> Option<PromptType> {
+    let prompt = last_line(text);
+    let default = if prompt.contains("[Y/n]") {
         Some(true)
-    } else if text.contains("[y/N]") {
+    } else if prompt.contains("[y/N]") {
         Some(false)
     } else {
         None
     };
 
     Some(PromptType::Confirmation {
-        prompt: text.trim().to_string(),
+        prompt: prompt.to_string(),
         default,
     })
 }
 
-fn _detect_selection(text: &str) -> Option<PromptType> {
+fn detect_selection(text: &str) -> Option<PromptType> {
     let lines: Vec<&str> = text.lines().collect();
     let mut options = Vec::new();
 
@@ -126,9 +207,120 @@ fn _detect_selection(text: &str) -> Option<PromptType> {
     }
 }
 
-fn _detect_file_path(text: &str) -> Option<PromptType> {
+fn detect_file_path(text: &str) -> Option<PromptType> {
     Some(PromptType::FilePath {
-        prompt: text.trim().to_string(),
+        prompt: last_line(text).to_string(),
         default: None,
     })
 }
+
+/// A question followed by numbered answers, the highlighted one marked `❯`,
+/// as Claude draws them inside a box
+fn detect_permission(text: &str) -> Option<PromptType> {
+    static QUESTION_RE: OnceLock<Regex> = OnceLock::new();
+    static ANSWER_RE: OnceLock<Regex> = OnceLock::new();
+    let question =
+        QUESTION_RE.get_or_init(|| Regex::new(r"(?i)^(do you want to|allow) .*\?").unwrap());
+    let answer = ANSWER_RE.get_or_init(|| Regex::new(r"^(❯|>)?\s*(\d+)\.\s+(.+)$").unwrap());
+
+    let lines: Vec<&str> = text
+        .lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '│' || c == '|'))
+        .collect();
+    let start = lines.iter().rposition(|line| question.is_match(line))?;
+    let mut options = Vec::new();
+    let mut selected = None;
+    for line in &lines[start + 1..] {
+        let Some(captures) = answer.captures(line) else {
+            continue;
+        };
+        if captures.get(1).is_some() {
+            selected = Some(options.len());
+        }
+        options.push(captures[3].trim().to_string());
+    }
+    if options.is_empty() {
+        return None;
+    }
+
+    Some(PromptType::Permission {
+        prompt: lines[start].to_string(),
+        options,
+        selected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_approval_prompts_at_the_bottom_of_the_screen() {
+        let detector = PromptDetector::new();
+        let screen = "\
+● Update(src/main.rs)
+
+╭──────────────────────────────────────────────╮
+│ Do you want to make this edit to main.rs?    │
+│ ❯ 1. Yes                                     │
+│   2. Yes, and don't ask again this session   │
+│   3. No, and tell Claude what to do (esc)    │
+╰──────────────────────────────────────────────╯
+
+
+";
+        let prompt = detector
+            .detect(&screen_tail(screen.lines().rev().map(String::from)))
+            .unwrap();
+        assert_eq!(
+            prompt,
+            PromptType::Permission {
+                prompt: "Do you want to make this edit to main.rs?".to_string(),
+                options: vec![
+                    "Yes".to_string(),
+                    "Yes, and don't ask again this session".to_string(),
+                    "No, and tell Claude what to do (esc)".to_string(),
+                ],
+                selected: Some(0),
+            }
+        );
+        assert!(prompt.needs_approval());
+
+        let prompt = detector
+            .detect("Installing 3 packages\nProceed? [Y/n] ")
+            .unwrap();
+        assert_eq!(
+            prompt,
+            PromptType::Confirmation {
+                prompt: "Proceed? [Y/n]".to_string(),
+                default: Some(true),
+            }
+        );
+
+        // A question further up that was already answered doesn't count
+        assert_eq!(
+            detector.detect("Should I continue?\nYes\nRunning the tests..."),
+            None
+        );
+    }
+
+    #[test]
+    fn agent_approval_patterns_come_first() {
+        let pattern = Regex::new(r"(?m)^Apply patch\? \(a\)ccept").unwrap();
+        let detector = PromptDetector::with_approval_patterns(&[pattern]);
+        let prompt = detector
+            .detect("Edited src/lib.rs\nApply patch? (a)ccept / (r)eject:")
+            .unwrap();
+        assert_eq!(
+            prompt,
+            PromptType::Confirmation {
+                prompt: "Apply patch? (a)ccept / (r)eject:".to_string(),
+                default: None,
+            }
+        );
+        assert_eq!(
+            PromptDetector::new().detect("Edited src/lib.rs\nApply patch? (a)ccept / (r)eject:"),
+            None
+        );
+    }
+}